@@ -0,0 +1,179 @@
+//! Measures emulated clock speed on a few representative workloads.
+//!
+//! Run with `cargo run --release --example bench [-- <cycles>]`. Each
+//! workload is run once per execution mode and reported in emulated MHz, so
+//! dispatcher regressions show up when comparing releases.
+
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use mc6809_core::{Cpu, Memory};
+
+/// Writing to this address acknowledges the interrupt in the IRQ workload.
+const IRQ_ACK: u16 = 0xFF00;
+
+/// 64KB flat RAM with a single interrupt acknowledge register.
+struct BenchMem {
+    mem: Box<[u8; 65536]>,
+    irq_ack: bool,
+}
+
+impl BenchMem {
+    fn new(program: &[u8]) -> Self {
+        let mut mem = Box::new([0u8; 65536]);
+        mem[0x0400..0x0400 + program.len()].copy_from_slice(program);
+        mem[0xFFFE] = 0x04;
+        mem[0xFFFF] = 0x00;
+        // IRQ handler at 0x0600: INC $2000 / STA $FF00 / RTI
+        mem[0x0600..0x0607].copy_from_slice(&[0x7C, 0x20, 0x00, 0xB7, 0xFF, 0x00, 0x3B]);
+        mem[0xFFF8] = 0x06;
+        mem[0xFFF9] = 0x00;
+        Self {
+            mem,
+            irq_ack: false,
+        }
+    }
+}
+
+impl Memory for BenchMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        if addr == IRQ_ACK {
+            self.irq_ack = true;
+        }
+        self.mem[addr as usize] = val;
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.mem[addr.wrapping_add(i as u16) as usize];
+        }
+    }
+}
+
+struct Workload {
+    name: &'static str,
+    program: &'static [u8],
+    /// Raise IRQ every this many cycles, if set.
+    irq_every: Option<u64>,
+}
+
+const WORKLOADS: &[Workload] = &[
+    Workload {
+        name: "tight loop",
+        // loop: LDB #$FF / inner: DECB / BNE inner / BRA loop
+        program: &[0xC6, 0xFF, 0x5A, 0x26, 0xFD, 0x20, 0xF9],
+        irq_every: None,
+    },
+    Workload {
+        name: "fill",
+        // loop:  LDX #$2000 / LDD #$A55A
+        // inner: STD ,X++ / CMPX #$4000 / BNE inner / BRA loop
+        program: &[
+            0x8E, 0x20, 0x00, 0xCC, 0xA5, 0x5A, 0xED, 0x81, 0x8C, 0x40, 0x00, 0x26, 0xF9, 0x20,
+            0xF1,
+        ],
+        irq_every: None,
+    },
+    Workload {
+        name: "indexed",
+        // loop:  LDX #$2000 / LDY #$3000 / LDB #$40
+        // inner: LDA ,X+ / ADDA 1,Y / STA ,Y++ / LDD -2,Y / LEAX 1,X
+        //        DECB / BNE inner / BRA loop
+        program: &[
+            0x8E, 0x20, 0x00, 0x10, 0x8E, 0x30, 0x00, 0xC6, 0x40, 0xA6, 0x80, 0xAB, 0x21, 0xA7,
+            0xA1, 0xEC, 0x3E, 0x30, 0x01, 0x5A, 0x26, 0xF3, 0x20, 0xE8,
+        ],
+        irq_every: None,
+    },
+    Workload {
+        name: "interrupts",
+        // LDS #$0C00 / ANDCC #$EF / loop: LDA #$01 / NOP / BRA loop
+        program: &[
+            0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x86, 0x01, 0x12, 0x20, 0xFB,
+        ],
+        irq_every: Some(200),
+    },
+];
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Run,
+    RunFast,
+    Blocks,
+    Decode,
+}
+
+impl Mode {
+    const ALL: [Mode; 4] = [Mode::Run, Mode::RunFast, Mode::Blocks, Mode::Decode];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Run => "run",
+            Mode::RunFast => "run_fast",
+            Mode::Blocks => "blocks",
+            Mode::Decode => "decode",
+        }
+    }
+
+    fn run(self, cpu: &mut Cpu, mem: &mut BenchMem, cycles: u64) {
+        match self {
+            Mode::Run => cpu.run(mem, cycles),
+            Mode::RunFast | Mode::Blocks | Mode::Decode => cpu.run_fast(mem, cycles),
+        };
+    }
+}
+
+/// Run `workload` for about `cycles` cycles and return emulated MHz.
+fn measure(workload: &Workload, mode: Mode, cycles: u64) -> f64 {
+    let mut mem = BenchMem::new(workload.program);
+    let mut cpu = Cpu::new();
+    cpu.set_block_cache(matches!(mode, Mode::Blocks));
+    cpu.set_decode_cache(matches!(mode, Mode::Decode));
+    cpu.reset(&mut mem);
+
+    let start = Instant::now();
+    match workload.irq_every {
+        None => mode.run(&mut cpu, &mut mem, cycles),
+        Some(every) => {
+            while cpu.cycles() < cycles {
+                mode.run(&mut cpu, &mut mem, every);
+                cpu.set_irq(true);
+                while !mem.irq_ack {
+                    cpu.step(&mut mem);
+                }
+                mem.irq_ack = false;
+                cpu.set_irq(false);
+            }
+        }
+    }
+    cpu.cycles() as f64 / start.elapsed().as_secs_f64() / 1_000_000.0
+}
+
+fn main() {
+    let cycles: u64 = match env::args().nth(1) {
+        Some(arg) => arg.parse().unwrap_or_else(|_| {
+            eprintln!("Usage: bench [cycles]");
+            process::exit(1);
+        }),
+        None => 50_000_000,
+    };
+
+    print!("{:<12}", "workload");
+    for mode in Mode::ALL {
+        print!("{:>12}", mode.name());
+    }
+    println!("    (emulated MHz, {cycles} cycles)");
+
+    for workload in WORKLOADS {
+        print!("{:<12}", workload.name);
+        for mode in Mode::ALL {
+            print!("{:>12.1}", measure(workload, mode, cycles));
+        }
+        println!();
+    }
+}
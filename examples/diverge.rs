@@ -0,0 +1,236 @@
+//! Run one program on two differently configured CPUs in lockstep and
+//! report the first instruction after which they disagree.
+//!
+//! Usage: cargo run --release --example diverge -- FILE[@ADDR] [--a CONFIG] [--b CONFIG] [options]
+//!
+//! Each side gets its own copy of memory. After every instruction the
+//! registers, and optionally the cycle counts and memory, are compared;
+//! the first difference is printed with the instructions leading up to it.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::process;
+
+use mc6809_core::disasm::disassemble;
+use mc6809_core::formats::Format;
+use mc6809_core::{
+    Cpu, CpuConfig, CpuVariant, FlatRam, HalfCarryQuirks, IllegalOpcodeMode, TransferQuirks,
+};
+
+/// Instructions shown before the one that diverged.
+const HISTORY: usize = 8;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args[1].starts_with("--") {
+        usage(&args[0]);
+        process::exit(2);
+    }
+
+    let mut entry = None;
+    let mut config_a = CpuConfig::default();
+    let mut config_b = CpuConfig::default();
+    let mut names = [String::from("default"), String::from("default")];
+    let mut max_steps: u64 = 1_000_000;
+    let mut compare_cycles = false;
+    let mut compare_memory = false;
+
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--a" | "--b" => {
+                let side = &args[i];
+                i += 1;
+                let config = parse_config(side, args.get(i));
+                if side == "--a" {
+                    config_a = config;
+                    names[0] = args[i].clone();
+                } else {
+                    config_b = config;
+                    names[1] = args[i].clone();
+                }
+            }
+            "--entry" => {
+                i += 1;
+                entry = Some(hex("--entry", args.get(i).map(String::as_str)));
+            }
+            "--max-steps" => {
+                i += 1;
+                max_steps = args.get(i).and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("Error: --max-steps requires a numeric argument");
+                    process::exit(2);
+                });
+            }
+            "--cycles" => compare_cycles = true,
+            "--memory" => compare_memory = true,
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(2);
+            }
+        }
+        i += 1;
+    }
+    if config_a == config_b {
+        eprintln!("Warning: both CPUs have the same configuration");
+    }
+
+    let (path, addr) = match args[1].rsplit_once('@') {
+        Some((path, addr)) => (path, Some(hex("load", Some(addr)))),
+        None => (args[1].as_str(), None),
+    };
+    let data = fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", path, e);
+        process::exit(2);
+    });
+    let format = Format::detect(&data);
+    if !format.has_addresses() && addr.is_none() {
+        eprintln!(
+            "Error: '{}' is a raw binary; give its address as FILE@ADDR",
+            path
+        );
+        process::exit(2);
+    }
+    let image = format.parse(&data, addr.unwrap_or(0)).unwrap_or_else(|e| {
+        eprintln!("Error in {:?} file '{}': {}", format, path, e);
+        process::exit(2);
+    });
+
+    let mut mem_a = FlatRam::new();
+    image.load_into(&mut mem_a);
+    if let Some(start) = entry.or(image.entry).or(addr) {
+        mem_a.set_reset_vector(start);
+    }
+    let mut mem_b = mem_a.clone();
+
+    let mut cpu_a = Cpu::with_config(config_a);
+    let mut cpu_b = Cpu::with_config(config_b);
+    cpu_a.reset(&mut mem_a);
+    cpu_b.reset(&mut mem_b);
+    println!("A: {}", names[0]);
+    println!("B: {}", names[1]);
+    println!();
+
+    let mut history = VecDeque::with_capacity(HISTORY);
+    for step in 0..max_steps {
+        if cpu_a.halted() && cpu_b.halted() {
+            println!(
+                "Both CPUs halted after {} instructions; no divergence",
+                step
+            );
+            return;
+        }
+        let pc = cpu_a.registers().pc;
+        let insn = disassemble(&mut mem_a, pc);
+        let before = format!("{:?}", cpu_a);
+        cpu_a.step(&mut mem_a);
+        cpu_b.step(&mut mem_b);
+
+        let mut differences = Vec::new();
+        if cpu_a.registers() != cpu_b.registers()
+            || cpu_a.hd6309_registers() != cpu_b.hd6309_registers()
+        {
+            differences.push("registers".to_string());
+        }
+        if cpu_a.halted() != cpu_b.halted() {
+            differences.push("halt state".to_string());
+        }
+        if compare_cycles && cpu_a.cycles() != cpu_b.cycles() {
+            differences.push(format!("cycles ({} vs {})", cpu_a.cycles(), cpu_b.cycles()));
+        }
+        if compare_memory {
+            let (a, b) = (mem_a.as_slice(), mem_b.as_slice());
+            if let Some(addr) = (0..a.len()).find(|&addr| a[addr] != b[addr]) {
+                differences.push(format!(
+                    "memory at {:04X} ({:02X} vs {:02X})",
+                    addr, a[addr], b[addr]
+                ));
+            }
+        }
+
+        if !differences.is_empty() {
+            println!(
+                "Diverged at instruction {}: {}",
+                step,
+                differences.join(", ")
+            );
+            println!();
+            for line in &history {
+                println!("    {}", line);
+            }
+            println!("--> {}", insn);
+            println!();
+            println!("Before: {}", before);
+            println!("A:      {:?}", cpu_a);
+            println!("B:      {:?}", cpu_b);
+            process::exit(1);
+        }
+
+        if history.len() == HISTORY {
+            history.pop_front();
+        }
+        history.push_back(insn.to_string());
+    }
+    println!("No divergence in {} instructions", max_steps);
+}
+
+/// Build a configuration from comma-separated options, starting from the
+/// default one.
+fn parse_config(option: &str, value: Option<&String>) -> CpuConfig {
+    let value = value.unwrap_or_else(|| {
+        eprintln!("Error: {} requires a configuration", option);
+        process::exit(2);
+    });
+    let mut config = CpuConfig::default();
+    for item in value.split(',').filter(|item| !item.is_empty()) {
+        match item {
+            "default" => {}
+            "6809" => config.variant = CpuVariant::Mc6809,
+            "6309" => config.variant = CpuVariant::Hd6309,
+            "undocumented" => config.undocumented_opcodes = true,
+            "hw-transfer" => config.transfer_quirks = TransferQuirks::Hardware,
+            "hw-half-carry" => config.half_carry_quirks = HalfCarryQuirks::Hardware,
+            "illegal-nop" => config.illegal_opcodes = IllegalOpcodeMode::TreatAsNop,
+            "illegal-halt" => config.illegal_opcodes = IllegalOpcodeMode::Halt,
+            other => {
+                eprintln!("Error: unknown configuration option '{}'", other);
+                process::exit(2);
+            }
+        }
+    }
+    config
+}
+
+fn hex(option: &str, value: Option<&str>) -> u16 {
+    value
+        .and_then(|s| u16::from_str_radix(s.trim_start_matches('$'), 16).ok())
+        .unwrap_or_else(|| {
+            eprintln!("Error: {} requires a hex address", option);
+            process::exit(2);
+        })
+}
+
+fn usage(program: &str) {
+    eprintln!(
+        "Usage: {} FILE[@ADDR] [--a CONFIG] [--b CONFIG] [options]",
+        program
+    );
+    eprintln!();
+    eprintln!("  Runs FILE (S-record, Intel HEX, DECB or raw at ADDR) on two CPUs side");
+    eprintln!("  by side and stops at the first instruction after which their state");
+    eprintln!("  differs. Exits with status 1 on a divergence and 0 otherwise.");
+    eprintln!();
+    eprintln!("  CONFIG is a comma-separated list applied to the default CPU:");
+    eprintln!("    6809, 6309        processor variant");
+    eprintln!("    undocumented      decode undocumented opcodes");
+    eprintln!("    hw-transfer       silicon behaviour for mixed-size TFR and EXG");
+    eprintln!("    hw-half-carry     silicon behaviour for H on subtracts");
+    eprintln!("    illegal-nop, illegal-halt  illegal opcode policy");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --entry ADDR      Start at ADDR (default: the image's entry address,");
+    eprintln!("                    or else its load address)");
+    eprintln!("  --max-steps N     Stop after N instructions (default: 1,000,000)");
+    eprintln!("  --cycles          Also compare cycle counts");
+    eprintln!("  --memory          Also compare memory after every instruction");
+}
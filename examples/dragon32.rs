@@ -0,0 +1,124 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Boot a Dragon 32 BASIC ROM and print the text screen.
+//!
+//! Usage: cargo run --example dragon32 --features dragon32 -- <basic.rom> [options]
+
+use std::env;
+use std::fs;
+use std::process;
+
+use mc6809_core::devices::Cartridge;
+use mc6809_core::machines::dragon32::Dragon32;
+
+/// Frames a key is held down, and then released, when typing.
+const KEY_FRAMES: usize = 3;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <basic.rom> [options]", args[0]);
+        eprintln!();
+        eprintln!("  Boots the 16KB Dragon 32 BASIC ROM, optionally types a line,");
+        eprintln!("  and prints the text screen.");
+        eprintln!();
+        eprintln!("Options:");
+        eprintln!("  --frames N     Frames to run before typing (default: 150, 3 seconds)");
+        eprintln!("  --type TEXT    Type TEXT followed by ENTER, then run one more second");
+        eprintln!("  --cart FILE    Insert FILE as an autostart cartridge at C000");
+        process::exit(1);
+    }
+
+    let mut frames = 150;
+    let mut typed = None;
+    let mut cart = None;
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--frames" => {
+                i += 1;
+                frames = args.get(i).and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("Error: --frames requires a numeric argument");
+                    process::exit(1);
+                });
+            }
+            "--type" => {
+                i += 1;
+                typed = Some(args.get(i).cloned().unwrap_or_else(|| {
+                    eprintln!("Error: --type requires some text");
+                    process::exit(1);
+                }));
+            }
+            "--cart" => {
+                i += 1;
+                cart = Some(args.get(i).cloned().unwrap_or_else(|| {
+                    eprintln!("Error: --cart requires a file name");
+                    process::exit(1);
+                }));
+            }
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let rom = fs::read(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", args[1], e);
+        process::exit(1);
+    });
+    if rom.len() > 0x7F00 {
+        eprintln!("Error: ROM does not fit in 8000-FEFF");
+        process::exit(1);
+    }
+
+    let mut dragon = Dragon32::new(&rom);
+    if let Some(path) = cart {
+        let image = fs::read(&path).unwrap_or_else(|e| {
+            eprintln!("Error reading '{}': {}", path, e);
+            process::exit(1);
+        });
+        if image.is_empty() || image.len() > Cartridge::MAX_SIZE {
+            eprintln!("Error: cartridge must be 1-16384 bytes");
+            process::exit(1);
+        }
+        dragon.insert_cartridge(Cartridge::new(&image).autostart(true));
+    }
+    for _ in 0..frames {
+        dragon.run_frame();
+    }
+
+    if let Some(text) = typed {
+        for key in text.chars().chain(['\r']) {
+            if !dragon.key_down(key) {
+                eprintln!("Warning: no key types {:?}", key);
+                continue;
+            }
+            for _ in 0..KEY_FRAMES {
+                dragon.run_frame();
+            }
+            dragon.key_up(key);
+            for _ in 0..KEY_FRAMES {
+                dragon.run_frame();
+            }
+        }
+        for _ in 0..50 {
+            dragon.run_frame();
+        }
+    }
+
+    print!("{}", dragon.screen_text());
+}
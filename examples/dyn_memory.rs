@@ -0,0 +1,49 @@
+//! Compares running the CPU against a concrete memory type with running it
+//! through `&mut dyn Memory`.
+//!
+//! Run with `cargo run --release --example dyn_memory`.
+
+use std::time::{Duration, Instant};
+
+use mc6809_core::{Cpu, FlatRam, Memory};
+
+/// RAM holding an endless loop that exercises loads, stores and branches.
+fn with_program() -> FlatRam {
+    let program = [
+        0x8E, 0x20, 0x00, // loop: LDX #$2000
+        0xC6, 0x80, //       LDB #$80
+        0xA6, 0x84, // inner: LDA ,X
+        0x4C, //             INCA
+        0xA7, 0x80, //       STA ,X+
+        0x5A, //             DECB
+        0x26, 0xF8, //       BNE inner
+        0x20, 0xF1, //       BRA loop
+    ];
+    let mut mem = FlatRam::new();
+    mem.load(&program, 0x0400);
+    mem.set_reset_vector(0x0400);
+    mem
+}
+
+const CYCLES: u64 = 50_000_000;
+
+fn report(label: &str, elapsed: Duration) {
+    let mhz = CYCLES as f64 / elapsed.as_secs_f64() / 1_000_000.0;
+    println!("{label:<10} {:>8.2?}  ({mhz:.1} MHz emulated)", elapsed);
+}
+
+fn main() {
+    let mut mem = with_program();
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut mem);
+    let start = Instant::now();
+    cpu.run(&mut mem, CYCLES);
+    report("generic", start.elapsed());
+
+    let mut mem: Box<dyn Memory> = Box::new(with_program());
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut mem);
+    let start = Instant::now();
+    cpu.run_dyn(mem.as_mut(), CYCLES);
+    report("dyn", start.elapsed());
+}
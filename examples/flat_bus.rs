@@ -1,83 +1,139 @@
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
 use std::process;
 
-use mc6809_core::{Cpu, Memory};
-
-/// Simple 64KB flat RAM for testing.
-struct FlatMem {
-    mem: Box<[u8; 65536]>,
-}
-
-impl FlatMem {
-    fn new() -> Self {
-        Self {
-            mem: Box::new([0u8; 65536]),
-        }
-    }
-
-    fn load(&mut self, data: &[u8], base: u16) {
-        let start = base as usize;
-        let end = start + data.len();
-        if end > 65536 {
-            eprintln!("Error: data exceeds 64KB address space");
-            process::exit(1);
-        }
-        self.mem[start..end].copy_from_slice(data);
-    }
-
-    /// Set the reset vector to point to the given address.
-    fn set_reset_vector(&mut self, addr: u16) {
-        self.mem[0xFFFE] = (addr >> 8) as u8;
-        self.mem[0xFFFF] = addr as u8;
-    }
-}
-
-impl Memory for FlatMem {
-    fn read(&mut self, addr: u16) -> u8 {
-        self.mem[addr as usize]
-    }
-
-    fn write(&mut self, addr: u16, val: u8) {
-        self.mem[addr as usize] = val;
-    }
-}
+use mc6809_core::backtrace::StackWalker;
+use mc6809_core::disasm::disassemble;
+use mc6809_core::formats::{Format, raw};
+use mc6809_core::machine::Machine;
+use mc6809_core::symbols::SymbolTable;
+use mc6809_core::{FlatRam, IllegalOpcodePolicy, Memory, Reg};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() < 3 {
-        eprintln!(
-            "Usage: {} <binary-file> <load-address-hex> [--trace] [--max-cycles N] [--stop-on-illegal]",
-            args[0]
-        );
-        eprintln!();
-        eprintln!("  Loads a raw binary at the specified address, sets the reset vector,");
-        eprintln!("  and runs the 6809 CPU until it halts or exceeds the cycle limit.");
-        eprintln!("  Illegal opcodes are reported but do not stop execution unless requested.");
-        eprintln!();
-        eprintln!("Options:");
-        eprintln!("  --trace          Print register state after each instruction");
-        eprintln!("  --max-cycles N   Stop after N cycles (default: 1,000,000)");
-        eprintln!("  --stop-on-illegal  Stop after the first illegal opcode is executed");
-        process::exit(1);
+    // The original form, a file and its load address, still works ahead
+    // of the options.
+    let mut loads = Vec::new();
+    let mut i = 1;
+    if args.len() >= 3 && !args[1].starts_with("--") {
+        loads.push((args[1].clone(), Some(args[2].clone())));
+        i = 3;
     }
 
-    let filename = &args[1];
-    let load_addr = u16::from_str_radix(&args[2], 16).unwrap_or_else(|_| {
-        eprintln!("Error: invalid hex address '{}'", args[2]);
-        process::exit(1);
-    });
-
+    let mut flex = false;
     let mut trace = false;
+    let mut trace_range = None;
+    let mut trace_file = None;
     let mut max_cycles: u64 = 1_000_000;
     let mut stop_on_illegal = false;
+    let mut dump = None;
+    let mut symbols = None;
+    let mut stop_at = None;
+    let mut stop_on_swi = false;
+    let mut stop_on_loop = false;
+    let mut exit_code_from = None;
+    let mut entry = None;
+    #[cfg(feature = "hooks")]
+    let mut profile = false;
 
-    let mut i = 3;
     while i < args.len() {
         match args[i].as_str() {
+            "--flex" => flex = true,
             "--trace" => trace = true,
             "--stop-on-illegal" => stop_on_illegal = true,
+            "--stop-on-swi" => stop_on_swi = true,
+            "--stop-on-loop" => stop_on_loop = true,
+            #[cfg(feature = "hooks")]
+            "--profile" => profile = true,
+            #[cfg(not(feature = "hooks"))]
+            "--profile" => {
+                eprintln!("Error: --profile requires the hooks feature");
+                process::exit(1);
+            }
+            "--trace-range" => {
+                i += 1;
+                let range = args.get(i).and_then(|s| s.split_once(".."));
+                let (start, end) = range.unwrap_or_else(|| {
+                    eprintln!("Error: --trace-range requires START..END");
+                    process::exit(1);
+                });
+                trace_range = Some((start.to_string(), end.to_string()));
+                trace = true;
+            }
+            "--trace-file" => {
+                i += 1;
+                trace_file = Some(args.get(i).cloned().unwrap_or_else(|| {
+                    eprintln!("Error: --trace-file requires a file name");
+                    process::exit(1);
+                }));
+                trace = true;
+            }
+            "--load" => {
+                i += 1;
+                let spec = args.get(i).unwrap_or_else(|| {
+                    eprintln!("Error: --load requires FILE[@ADDR]");
+                    process::exit(1);
+                });
+                loads.push(match spec.rsplit_once('@') {
+                    Some((path, addr)) => (path.to_string(), Some(addr.to_string())),
+                    None => (spec.clone(), None),
+                });
+            }
+            "--entry" => {
+                i += 1;
+                entry = Some(args.get(i).cloned().unwrap_or_else(|| {
+                    eprintln!("Error: --entry requires an address");
+                    process::exit(1);
+                }));
+            }
+            "--stop-at" => {
+                i += 1;
+                stop_at = Some(args.get(i).cloned().unwrap_or_else(|| {
+                    eprintln!("Error: --stop-at requires an address");
+                    process::exit(1);
+                }));
+            }
+            "--exit-code-from" => {
+                i += 1;
+                exit_code_from = match args.get(i).and_then(|s| Reg::from_name(s)) {
+                    Some(reg) if !reg.is_16bit() => Some(reg),
+                    _ => {
+                        eprintln!("Error: --exit-code-from requires A, B, CC or DP");
+                        process::exit(1);
+                    }
+                };
+            }
+            "--symbols" => {
+                i += 1;
+                let path = args.get(i).unwrap_or_else(|| {
+                    eprintln!("Error: --symbols requires a file name");
+                    process::exit(1);
+                });
+                let text = fs::read_to_string(path).unwrap_or_else(|e| {
+                    eprintln!("Error reading '{}': {}", path, e);
+                    process::exit(1);
+                });
+                symbols = Some(SymbolTable::parse(&text).unwrap_or_else(|e| {
+                    eprintln!("Error in symbol file '{}': {}", path, e);
+                    process::exit(1);
+                }));
+            }
+            "--dump" => {
+                let range = args.get(i + 1).and_then(|s| {
+                    let (start, len) = s.split_once(':')?;
+                    Some((u16::from_str_radix(start, 16).ok()?, len.parse().ok()?))
+                });
+                match (range, args.get(i + 2)) {
+                    (Some((start, len)), Some(path)) => dump = Some((start, len, path.clone())),
+                    _ => {
+                        eprintln!("Error: --dump requires START:LEN and a file name");
+                        process::exit(1);
+                    }
+                }
+                i += 2;
+            }
             "--max-cycles" => {
                 i += 1;
                 max_cycles = args.get(i).and_then(|s| s.parse().ok()).unwrap_or_else(|| {
@@ -93,50 +149,222 @@ fn main() {
         i += 1;
     }
 
-    let data = fs::read(filename).unwrap_or_else(|e| {
-        eprintln!("Error reading '{}': {}", filename, e);
+    if loads.is_empty() {
+        usage(&args[0]);
         process::exit(1);
-    });
+    }
 
-    let mut mem = FlatMem::new();
-    mem.load(&data, load_addr);
-    mem.set_reset_vector(load_addr);
+    // Resolved after the loop so that a label can come before --symbols.
+    let resolve = |text: &str, option: &str| {
+        let resolved = match &symbols {
+            Some(symbols) => symbols.resolve(text),
+            None => SymbolTable::new().resolve(text),
+        };
+        resolved.unwrap_or_else(|| {
+            eprintln!("Error: invalid {} address '{}'", option, text);
+            process::exit(1);
+        })
+    };
+    let stop_at = stop_at.map(|text| resolve(&text, "--stop-at"));
+    let trace_range = trace_range
+        .map(|(start, end)| resolve(&start, "--trace-range")..resolve(&end, "--trace-range"));
 
-    let mut cpu = Cpu::new();
-    cpu.reset(&mut mem);
+    let mut trace_out: Box<dyn Write> = match &trace_file {
+        Some(path) => Box::new(BufWriter::new(File::create(path).unwrap_or_else(|e| {
+            eprintln!("Error creating '{}': {}", path, e);
+            process::exit(1);
+        }))),
+        None => Box::new(io::stdout()),
+    };
 
-    println!(
-        "Loaded {} bytes at {:04X}, reset vector → {:04X}",
-        data.len(),
-        load_addr,
-        load_addr
-    );
-    println!("Initial state: {:?}", cpu);
+    let mut mem = FlatRam::new();
+    let mut start = entry.map(|text| resolve(&text, "--entry"));
+    for (filename, addr) in &loads {
+        let data = fs::read(filename).unwrap_or_else(|e| {
+            eprintln!("Error reading '{}': {}", filename, e);
+            process::exit(1);
+        });
+        let format = if flex {
+            Format::Flex
+        } else {
+            Format::detect(&data)
+        };
+        let addr = addr.as_deref().map(|text| resolve(text, "load"));
+        if !format.has_addresses() && addr.is_none() {
+            eprintln!(
+                "Error: '{}' is a raw binary; give its address as FILE@ADDR",
+                filename
+            );
+            process::exit(1);
+        }
+        let image = format.parse(&data, addr.unwrap_or(0)).unwrap_or_else(|e| {
+            eprintln!("Error in {:?} file '{}': {}", format, filename, e);
+            process::exit(1);
+        });
+        image.load_into(&mut mem);
+        println!(
+            "Loaded {} bytes in {} segments from '{}' ({:?})",
+            image.len(),
+            image.segments.len(),
+            filename,
+            format
+        );
+        start = start.or(image.entry).or(addr);
+    }
+
+    let start = start.unwrap_or_else(|| {
+        eprintln!("Error: no entry address; use --entry ADDR");
+        process::exit(1);
+    });
+    mem.set_reset_vector(start);
+    println!("Reset vector → {:04X}", start);
+
+    let mut machine = Machine::new(mem);
+    if stop_on_illegal {
+        machine
+            .cpu_mut()
+            .set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    }
+    #[cfg(feature = "hooks")]
+    machine.cpu_mut().set_profiler(profile);
+
+    println!("Initial state: {:?}", machine.cpu());
     println!();
 
-    while cpu.cycles() < max_cycles && !cpu.halted() {
-        if trace {
-            print!("{:?}  ", cpu);
+    let stop = loop {
+        if machine.cpu().cycles() >= max_cycles {
+            break Stop::CycleLimit;
+        }
+        if machine.cpu().halted() {
+            break Stop::Halted;
         }
-        let cyc = cpu.step(&mut mem);
-        if trace {
-            println!("({} cycles)", cyc);
+        let pc = machine.cpu().registers().pc;
+        if stop_at == Some(pc) {
+            break Stop::Address(pc);
         }
-        if stop_on_illegal && cpu.illegal() {
-            break;
+        if stop_on_swi && machine.bus_mut().read(pc) == 0x3F {
+            break Stop::Swi(pc);
         }
+        if stop_on_loop {
+            let insn = disassemble(machine.bus_mut(), pc);
+            if insn.target == Some(pc) && matches!(insn.mnemonic.as_str(), "BRA" | "LBRA" | "JMP") {
+                break Stop::Loop(pc);
+            }
+        }
+        let traced = trace && trace_range.as_ref().is_none_or(|range| range.contains(&pc));
+        let mut line = String::new();
+        if traced {
+            if let Some(label) = symbols.as_ref().and_then(|s| s.describe(pc)) {
+                line = format!("{:<16}", label);
+            }
+            line += &format!("{:?}  ", machine.cpu());
+        }
+        let cyc = machine.step();
+        if traced && let Err(e) = writeln!(trace_out, "{}({} cycles)", line, cyc) {
+            eprintln!("Error writing trace: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = trace_out.flush() {
+        eprintln!("Error writing trace: {}", e);
+        process::exit(1);
     }
 
+    let cpu = machine.cpu();
     println!();
-    if cpu.halted() {
-        println!("CPU halted after {} cycles", cpu.cycles());
-    } else if stop_on_illegal && cpu.illegal() {
-        println!("Stopped on illegal opcode after {} cycles", cpu.cycles());
-    } else {
-        println!("Cycle limit ({}) reached", max_cycles);
+    match stop {
+        Stop::Halted if stop_on_illegal && cpu.illegal() => {
+            println!("Stopped on illegal opcode after {} cycles", cpu.cycles())
+        }
+        Stop::Halted => println!("CPU halted after {} cycles", cpu.cycles()),
+        Stop::CycleLimit => println!("Cycle limit ({}) reached", max_cycles),
+        Stop::Address(pc) => println!("Reached {:04X} after {} cycles", pc, cpu.cycles()),
+        Stop::Swi(pc) => println!("SWI at {:04X} after {} cycles", pc, cpu.cycles()),
+        Stop::Loop(pc) => println!("Loop to self at {:04X} after {} cycles", pc, cpu.cycles()),
     }
     if cpu.illegal() {
         println!("Note: at least one illegal opcode was executed");
     }
     println!("Final state: {:?}", cpu);
+    if matches!(stop, Stop::Halted) {
+        // Read from a copy so the dump below sees memory as the run left it.
+        let trace = StackWalker::new().walk(cpu, &mut machine.bus().clone());
+        println!();
+        println!("Backtrace:");
+        match &symbols {
+            Some(symbols) => print!("{}", trace.text_with(symbols)),
+            None => print!("{}", trace),
+        }
+    }
+    #[cfg(feature = "hooks")]
+    if let Some(profile) = cpu.profile() {
+        println!();
+        print!("{}", profile.flat_report(symbols.as_ref()));
+        println!();
+        print!("{}", profile.call_graph_report(symbols.as_ref()));
+    }
+    let exit_code = exit_code_from.map(|reg| cpu.registers().get(reg));
+
+    if let Some((start, len, path)) = dump {
+        let mem = machine.bus_mut();
+        let result = if path.ends_with(".s19") || path.ends_with(".srec") {
+            raw::save_srec(&path, mem, start, len, None)
+        } else {
+            raw::save(&path, mem, start, len)
+        };
+        if let Err(e) = result {
+            eprintln!("Error writing '{}': {}", path, e);
+            process::exit(1);
+        }
+        println!("Saved {} bytes from {:04X} to {}", len, start, path);
+    }
+
+    if let Some(code) = exit_code {
+        process::exit(code as i32);
+    }
+}
+
+/// Why the run ended.
+enum Stop {
+    Halted,
+    CycleLimit,
+    Address(u16),
+    Swi(u16),
+    Loop(u16),
+}
+
+fn usage(program: &str) {
+    eprintln!(
+        "Usage: {} [<file> <load-address>] [--load FILE[@ADDR]]... [--entry ADDR] [--flex] [--symbols FILE] [--trace] [--trace-range START..END] [--trace-file PATH] [--max-cycles N] [--stop-on-illegal] [--stop-at ADDR] [--stop-on-swi] [--stop-on-loop] [--exit-code-from REG] [--profile]",
+        program
+    );
+    eprintln!();
+    eprintln!("  Loads one or more program files, sets the reset vector, and runs the");
+    eprintln!("  6809 CPU until it halts or exceeds the cycle limit. S-record, Intel HEX");
+    eprintln!("  and DECB binaries are recognised; anything else is raw bytes and needs");
+    eprintln!("  an address. Illegal opcodes are reported but do not stop execution");
+    eprintln!("  unless requested.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --load FILE[@ADDR]  Load FILE, at ADDR if it is a raw binary; repeatable");
+    eprintln!("  --entry ADDR     Start at ADDR (default: the first file's entry address,");
+    eprintln!("                   or else its load address)");
+    eprintln!("  --flex           Load every file as a FLEX .CMD binary");
+    eprintln!("  --symbols FILE   Read labels from a .sym, .noi or lwasm map file");
+    eprintln!("  --trace          Print register state after each instruction");
+    eprintln!("  --trace-range START..END  Trace only instructions from START up to,");
+    eprintln!("                   but not including, END (hex or labels)");
+    eprintln!("  --trace-file PATH  Write the trace to PATH instead of stdout");
+    eprintln!("  --max-cycles N   Stop after N cycles (default: 1,000,000)");
+    eprintln!("  --stop-on-illegal  Stop after the first illegal opcode is executed");
+    eprintln!("  --stop-at ADDR   Stop before executing at ADDR (hex or a label)");
+    eprintln!("  --stop-on-swi    Stop before executing an SWI");
+    eprintln!("  --stop-on-loop   Stop at a branch or jump to itself (BRA *, JMP *)");
+    eprintln!("  --exit-code-from REG  Exit with the value of A, B, CC or DP when stopped,");
+    eprintln!("                   so a test ROM can report pass or fail");
+    eprintln!("  --dump START:LEN FILE  Save LEN bytes from hex address START after the");
+    eprintln!("                   run; FILE ending in .s19 or .srec gets S-records");
+    eprintln!(
+        "  --profile        Print the cycles spent in each subroutine after the run (hooks feature)"
+    );
 }
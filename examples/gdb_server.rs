@@ -0,0 +1,133 @@
+//! Serve a loaded program to GDB over TCP.
+//!
+//! Usage: cargo run --example gdb_server --features gdbstub -- FILE[@ADDR] [--port N] [--entry ADDR]
+//!
+//! Loads the program, waits for a debugger on the port (default 9001) and
+//! runs it under GDB's control: registers, memory, breakpoints, single step
+//! and continue. A 6809-aware GDB attaches with `target remote :9001`.
+
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::process;
+
+use gdbstub::common::Signal;
+use gdbstub::stub::{DisconnectReason, GdbStub};
+use mc6809_core::FlatRam;
+use mc6809_core::formats::Format;
+use mc6809_core::gdb::{GdbEventLoop, GdbTarget};
+use mc6809_core::machine::Machine;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args[1].starts_with("--") {
+        eprintln!("Usage: {} FILE[@ADDR] [--port N] [--entry ADDR]", args[0]);
+        eprintln!();
+        eprintln!("  Loads FILE (S-record, Intel HEX, DECB or raw at ADDR), then waits");
+        eprintln!("  for a GDB remote protocol connection on 127.0.0.1:N (default 9001)");
+        eprintln!("  and runs the program under the debugger's control.");
+        eprintln!();
+        eprintln!("Options:");
+        eprintln!("  --port N       TCP port to listen on");
+        eprintln!("  --entry ADDR   Start at ADDR (default: the image's entry address,");
+        eprintln!("                 or else its load address)");
+        process::exit(1);
+    }
+
+    let mut port: u16 = 9001;
+    let mut entry = None;
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--port" => {
+                i += 1;
+                port = args.get(i).and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("Error: --port requires a port number");
+                    process::exit(1);
+                });
+            }
+            "--entry" => {
+                i += 1;
+                entry = Some(hex("--entry", args.get(i).map(String::as_str)));
+            }
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let (path, addr) = match args[1].rsplit_once('@') {
+        Some((path, addr)) => (path, Some(hex("load", Some(addr)))),
+        None => (args[1].as_str(), None),
+    };
+    let data = fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", path, e);
+        process::exit(1);
+    });
+    let format = Format::detect(&data);
+    if !format.has_addresses() && addr.is_none() {
+        eprintln!(
+            "Error: '{}' is a raw binary; give its address as FILE@ADDR",
+            path
+        );
+        process::exit(1);
+    }
+    let image = format.parse(&data, addr.unwrap_or(0)).unwrap_or_else(|e| {
+        eprintln!("Error in {:?} file '{}': {}", format, path, e);
+        process::exit(1);
+    });
+
+    let mut mem = FlatRam::new();
+    image.load_into(&mut mem);
+    let start = entry.or(image.entry).or(addr).unwrap_or_else(|| {
+        eprintln!("Error: no entry address; use --entry ADDR");
+        process::exit(1);
+    });
+    mem.set_reset_vector(start);
+    println!(
+        "Loaded {} bytes from '{}' ({:?}), reset vector → {:04X}",
+        image.len(),
+        path,
+        format,
+        start
+    );
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).unwrap_or_else(|e| {
+        eprintln!("Error listening on port {}: {}", port, e);
+        process::exit(1);
+    });
+    println!("Waiting for GDB on 127.0.0.1:{} ...", port);
+    let (stream, peer) = listener.accept().unwrap_or_else(|e| {
+        eprintln!("Error accepting a connection: {}", e);
+        process::exit(1);
+    });
+    println!("Debugger connected from {}", peer);
+
+    let mut target = GdbTarget::new(Machine::new(mem));
+    let reason = GdbStub::new(stream)
+        .run_blocking::<GdbEventLoop<_, _>>(&mut target)
+        .unwrap_or_else(|e| {
+            eprintln!("Error in GDB session: {}", e);
+            process::exit(1);
+        });
+    match reason {
+        DisconnectReason::Disconnect => println!("Debugger detached"),
+        DisconnectReason::Kill => println!("Debugger killed the target"),
+        DisconnectReason::TargetExited(code) => println!("Target exited with code {}", code),
+        DisconnectReason::TargetTerminated(Signal(signal)) => {
+            println!("Target terminated by signal {}", signal)
+        }
+    }
+    println!("Final state: {:?}", target.machine().cpu());
+}
+
+fn hex(option: &str, value: Option<&str>) -> u16 {
+    value
+        .and_then(|s| u16::from_str_radix(s.trim_start_matches('$'), 16).ok())
+        .unwrap_or_else(|| {
+            eprintln!("Error: {} requires a hex address", option);
+            process::exit(1);
+        })
+}
@@ -0,0 +1,369 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! An interactive monitor: load a program, then step, trace, set
+//! breakpoints and inspect or change registers and memory.
+//!
+//! Usage: cargo run --example monitor -- <binary-file> <load-address-hex> [options]
+//!
+//! Type `help` at the prompt for the commands. An empty line repeats the
+//! last `step`, `dis` or `mem` command, carrying on from where it stopped.
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use mc6809_core::disasm::disassemble;
+use mc6809_core::formats::{flex, raw};
+use mc6809_core::symbols::SymbolTable;
+use mc6809_core::{ConditionCodes, Cpu, CpuError, FlatRam, Memory};
+
+/// Cycles a `cont` runs before giving control back.
+const CONTINUE_LIMIT: u64 = 100_000_000;
+
+const HELP: &str = "\
+Commands (addresses are hex or labels, as in LOOP+2):
+  r                  Show the registers
+  r REG VALUE        Set A, B, D, X, Y, U, S, PC, DP or CC to hex VALUE
+  d [ADDR] [N]       Disassemble N instructions (default 10) from ADDR (default PC)
+  m ADDR [LEN]       Dump LEN bytes (default 64) from ADDR
+  e ADDR BYTE...     Write hex bytes from ADDR
+  b [ADDR]           Set a breakpoint at ADDR, or list the breakpoints
+  bc ADDR            Clear the breakpoint at ADDR
+  s [N]              Step N instructions (default 1), showing each
+  c                  Continue to a breakpoint, a halt or a fault
+  reset              Reset the CPU
+  q                  Quit";
+
+/// The CPU and memory being debugged, and where the listing commands left
+/// off.
+struct Monitor {
+    cpu: Cpu,
+    mem: FlatRam,
+    symbols: SymbolTable,
+    next_dis: Option<u16>,
+    next_mem: u16,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!(
+            "Usage: {} <binary-file> <load-address-hex> [--flex] [--symbols FILE]",
+            args[0]
+        );
+        eprintln!();
+        eprintln!("  Loads a raw binary at the specified address, sets the reset vector,");
+        eprintln!("  resets the CPU and waits for commands. Type 'help' for a list.");
+        eprintln!();
+        eprintln!("Options:");
+        eprintln!("  --flex           Load a FLEX .CMD binary and start at its transfer");
+        eprintln!("                   address (the load address is used if it has none)");
+        eprintln!("  --symbols FILE   Read labels from a .sym, .noi or lwasm map file");
+        process::exit(1);
+    }
+
+    let filename = &args[1];
+    let load_addr = u16::from_str_radix(&args[2], 16).unwrap_or_else(|_| {
+        eprintln!("Error: invalid hex address '{}'", args[2]);
+        process::exit(1);
+    });
+
+    let mut is_flex = false;
+    let mut symbols = SymbolTable::new();
+    let mut i = 3;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--flex" => is_flex = true,
+            "--symbols" => {
+                i += 1;
+                let path = args.get(i).unwrap_or_else(|| {
+                    eprintln!("Error: --symbols requires a file name");
+                    process::exit(1);
+                });
+                let text = fs::read_to_string(path).unwrap_or_else(|e| {
+                    eprintln!("Error reading '{}': {}", path, e);
+                    process::exit(1);
+                });
+                symbols = SymbolTable::parse(&text).unwrap_or_else(|e| {
+                    eprintln!("Error in symbol file '{}': {}", path, e);
+                    process::exit(1);
+                });
+            }
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let data = fs::read(filename).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", filename, e);
+        process::exit(1);
+    });
+    let mut mem = FlatRam::new();
+    let start = if is_flex {
+        let image = flex::parse(&data).unwrap_or_else(|e| {
+            eprintln!("Error in FLEX binary '{}': {}", filename, e);
+            process::exit(1);
+        });
+        image.load_into(&mut mem);
+        image.entry.unwrap_or(load_addr)
+    } else {
+        raw::load(&data, load_addr, &mut mem).unwrap_or_else(|e| {
+            eprintln!("Error loading '{}': {}", filename, e);
+            process::exit(1);
+        });
+        load_addr
+    };
+    mem.set_reset_vector(start);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut mem);
+    let mut monitor = Monitor {
+        cpu,
+        mem,
+        symbols,
+        next_dis: None,
+        next_mem: start,
+    };
+    println!("Loaded '{}', reset vector → {:04X}", filename, start);
+    monitor.show_registers();
+
+    let stdin = io::stdin();
+    let mut last = String::new();
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            println!();
+            break;
+        }
+        let mut line = line.trim().to_string();
+        if line.is_empty() {
+            line = last.clone();
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, rest)) = words.split_first() else {
+            continue;
+        };
+        if matches!(command, "q" | "quit") {
+            break;
+        }
+        if let Err(e) = monitor.command(command, rest) {
+            println!("Error: {}", e);
+        }
+        // Only the listing and step commands repeat, and they carry on
+        // from where they stopped.
+        last = match command {
+            "s" | "step" => line,
+            "d" | "dis" => "d".to_string(),
+            "m" | "mem" => "m".to_string(),
+            _ => String::new(),
+        };
+    }
+}
+
+impl Monitor {
+    fn command(&mut self, command: &str, args: &[&str]) -> Result<(), String> {
+        match (command, args) {
+            ("h" | "help" | "?", _) => println!("{}", HELP),
+            ("r" | "regs", []) => self.show_registers(),
+            ("r" | "regs", [reg, value]) => {
+                self.set_register(reg, value)?;
+                self.show_registers();
+            }
+            ("d" | "dis", _) => {
+                let mut addr = match args.first() {
+                    Some(addr) => self.addr(addr)?,
+                    None => self.next_dis.unwrap_or(self.cpu.registers().pc),
+                };
+                let count = self.count(args.get(1), 10)?;
+                for _ in 0..count {
+                    addr = self.list(addr);
+                }
+                self.next_dis = Some(addr);
+            }
+            ("m" | "mem", _) => {
+                let start = match args.first() {
+                    Some(addr) => self.addr(addr)?,
+                    None => self.next_mem,
+                };
+                let len = self.count(args.get(1), 64)?;
+                self.dump(start, len);
+                self.next_mem = start.wrapping_add(len as u16);
+            }
+            ("e" | "edit", [addr, bytes @ ..]) if !bytes.is_empty() => {
+                let addr = self.addr(addr)?;
+                for (offset, byte) in bytes.iter().enumerate() {
+                    let byte = u8::from_str_radix(byte, 16)
+                        .map_err(|_| format!("invalid hex byte '{}'", byte))?;
+                    self.mem.write(addr.wrapping_add(offset as u16), byte);
+                }
+            }
+            ("b" | "break", []) => {
+                for addr in self.cpu.breakpoints() {
+                    println!("{}", self.label(addr));
+                }
+            }
+            ("b" | "break", [addr]) => {
+                let addr = self.addr(addr)?;
+                self.cpu.add_breakpoint(addr);
+            }
+            ("bc" | "clear", [addr]) => {
+                let addr = self.addr(addr)?;
+                if !self.cpu.remove_breakpoint(addr) {
+                    return Err(format!("no breakpoint at {:04X}", addr));
+                }
+            }
+            ("s" | "step", _) => {
+                let count = self.count(args.first(), 1)?;
+                for _ in 0..count {
+                    let pc = self.cpu.registers().pc;
+                    self.list(pc);
+                    if let Err(e) = self.step(true) {
+                        println!("{}", e);
+                        break;
+                    }
+                }
+                self.next_dis = None;
+                self.show_registers();
+            }
+            ("c" | "cont", []) => {
+                let end = self.cpu.cycles() + CONTINUE_LIMIT;
+                let mut resume = true;
+                loop {
+                    if let Err(e) = self.step(resume) {
+                        println!("{}", e);
+                        break;
+                    }
+                    if self.cpu.halted() {
+                        println!("CPU halted");
+                        break;
+                    }
+                    resume = false;
+                    if self.cpu.cycles() >= end {
+                        println!("Stopped after {} cycles", CONTINUE_LIMIT);
+                        break;
+                    }
+                }
+                self.next_dis = None;
+                self.show_registers();
+            }
+            ("reset", []) => {
+                self.cpu.reset(&mut self.mem);
+                self.next_dis = None;
+                self.show_registers();
+            }
+            _ => return Err(format!("bad command '{}'; type 'help' for a list", command)),
+        }
+        Ok(())
+    }
+
+    /// Execute one instruction. With `resume`, a breakpoint at PC does not
+    /// stop it, since the user asked to run from there.
+    fn step(&mut self, resume: bool) -> Result<(), CpuError> {
+        match self.cpu.try_step(&mut self.mem) {
+            Err(CpuError::Breakpoint { .. }) if resume => {
+                self.cpu.try_step(&mut self.mem).map(|_| ())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    fn show_registers(&self) {
+        println!("{:?}", self.cpu);
+    }
+
+    /// Print the instruction at `addr` and return the address after it.
+    fn list(&mut self, addr: u16) -> u16 {
+        if let Some(names) = self.symbols.names_at(addr).first() {
+            println!("{}:", names);
+        }
+        let insn = disassemble(&mut self.mem, addr);
+        let bytes: Vec<String> = insn.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        println!(
+            "{:04X}  {:<15}{}",
+            addr,
+            bytes.join(" "),
+            insn.text_with(&self.symbols)
+        );
+        insn.next()
+    }
+
+    fn dump(&mut self, start: u16, len: usize) {
+        for row in (0..len).step_by(16) {
+            let addr = start.wrapping_add(row as u16);
+            let bytes: Vec<u8> = (0..16.min(len - row))
+                .map(|i| self.mem.read(addr.wrapping_add(i as u16)))
+                .collect();
+            let hex: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+            let text: String = bytes
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            println!("{:04X}  {:<48}{}", addr, hex.join(" "), text);
+        }
+    }
+
+    fn set_register(&mut self, reg: &str, value: &str) -> Result<(), String> {
+        let value =
+            u16::from_str_radix(value, 16).map_err(|_| format!("invalid hex value '{}'", value))?;
+        let byte = || u8::try_from(value).map_err(|_| format!("{} is an 8-bit register", reg));
+        let mut regs = self.cpu.registers_mut();
+        match reg.to_ascii_lowercase().as_str() {
+            "a" => regs.set_a(byte()?),
+            "b" => regs.set_b(byte()?),
+            "dp" => regs.dp = byte()?,
+            "cc" => regs.cc = ConditionCodes::from_byte(byte()?),
+            "d" => regs.d = value,
+            "x" => regs.x = value,
+            "y" => regs.y = value,
+            "u" => regs.u = value,
+            "s" => regs.s = value,
+            "pc" => regs.pc = value,
+            _ => return Err(format!("unknown register '{}'", reg)),
+        }
+        Ok(())
+    }
+
+    fn addr(&self, text: &str) -> Result<u16, String> {
+        self.symbols
+            .resolve(text)
+            .ok_or_else(|| format!("invalid address '{}'", text))
+    }
+
+    fn count(&self, text: Option<&&str>, default: usize) -> Result<usize, String> {
+        text.map_or(Ok(default), |text| {
+            text.parse()
+                .map_err(|_| format!("invalid count '{}'", text))
+        })
+    }
+
+    fn label(&self, addr: u16) -> String {
+        match self.symbols.describe(addr) {
+            Some(label) => format!("{:04X}  {}", addr, label),
+            None => format!("{:04X}", addr),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! A minimal serial 6809 computer: ROM, RAM and an MC6850 ACIA wired to
+//! the terminal.
+//!
+//! Usage: cargo run --release --example serial_basic -- ROM [options]
+//!
+//! The default memory map follows the common single-board design that runs
+//! Microsoft BASIC over a serial line: 32K of RAM at 0000, the ACIA at
+//! A000 and the ROM at the top of the address space, so an 8K BASIC ROM
+//! boots straight to its prompt. Typed lines go to the ACIA with a carriage
+//! return in place of the newline; the ACIA's output goes to stdout.
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mc6809_core::BusSignals;
+use mc6809_core::bus::{CompositeBus, MemoryMap};
+use mc6809_core::devices::Mc6850;
+use mc6809_core::machine::Machine;
+
+/// Emulated time run between terminal polls.
+const SLICE: Duration = Duration::from_millis(10);
+
+/// Slices to keep running after stdin closes, so the last line's output
+/// gets printed.
+const DRAIN_SLICES: u32 = 50;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args[1].starts_with("--") {
+        usage(&args[0]);
+        process::exit(1);
+    }
+
+    let mut rom_at = None;
+    let mut acia_at: u16 = 0xA000;
+    let mut ram_size: usize = 0x8000;
+    let mut irq = BusSignals::IRQ;
+    let mut fast = false;
+
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--rom-at" => {
+                i += 1;
+                rom_at = Some(hex("--rom-at", args.get(i)));
+            }
+            "--acia" => {
+                i += 1;
+                acia_at = hex("--acia", args.get(i));
+            }
+            "--ram-size" => {
+                i += 1;
+                ram_size = args
+                    .get(i)
+                    .and_then(|s| usize::from_str_radix(s.trim_start_matches('$'), 16).ok())
+                    .unwrap_or_else(|| {
+                        eprintln!("Error: --ram-size requires a hex size");
+                        process::exit(1);
+                    });
+            }
+            "--firq" => irq = BusSignals::FIRQ,
+            "--fast" => fast = true,
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let rom = fs::read(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", args[1], e);
+        process::exit(1);
+    });
+    if rom.is_empty() || rom.len() > 0x10000 {
+        eprintln!(
+            "Error: '{}' is not a ROM image of 1 to 65536 bytes",
+            args[1]
+        );
+        process::exit(1);
+    }
+    let rom_at = rom_at.unwrap_or((0x10000 - rom.len()) as u16);
+    if rom_at as usize + rom.len() > 0x10000 {
+        eprintln!("Error: the ROM does not fit at {:04X}", rom_at);
+        process::exit(1);
+    }
+    if ram_size > rom_at as usize {
+        eprintln!("Error: {:X} bytes of RAM would overlap the ROM", ram_size);
+        process::exit(1);
+    }
+
+    let map = MemoryMap::new().ram(0x0000, ram_size).rom(rom_at, &rom);
+    let mut bus = CompositeBus::new(map);
+    let acia = bus.add_device(Mc6850::new(irq));
+    bus.map(acia, acia_at, 2, 0);
+    let mut machine = Machine::new(bus);
+    eprintln!(
+        "ROM at {:04X}, RAM {:04X}-{:04X}, ACIA at {:04X}; reset vector → {:04X}",
+        rom_at,
+        0,
+        ram_size - 1,
+        acia_at,
+        machine.cpu().registers().pc
+    );
+
+    // Read stdin on its own thread so the machine keeps running while the
+    // terminal is idle.
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0; 256];
+        let mut stdin = io::stdin();
+        while let Ok(n @ 1..) = stdin.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let cycles_per_slice = machine.cpu().clock_hz() * SLICE.as_millis() as u64 / 1000;
+    let mut stdout = io::stdout();
+    let mut drain = None;
+    loop {
+        let started = Instant::now();
+        machine.run_for(cycles_per_slice);
+        let acia = machine
+            .bus_mut()
+            .device_as::<Mc6850>(acia)
+            .expect("ACIA is mapped");
+
+        let output = acia.take_output();
+        if !output.is_empty() {
+            let _ = stdout.write_all(&output);
+            let _ = stdout.flush();
+        }
+        loop {
+            match rx.try_recv() {
+                Ok(mut line) => {
+                    for byte in &mut line {
+                        if *byte == b'\n' {
+                            *byte = b'\r';
+                        }
+                    }
+                    acia.receive(&line);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if acia.pending_input() == 0 {
+                        drain = Some(drain.unwrap_or(DRAIN_SLICES));
+                    }
+                    break;
+                }
+            }
+        }
+
+        if machine.cpu().halted() {
+            eprintln!();
+            eprintln!("CPU halted: {:?}", machine.cpu());
+            process::exit(1);
+        }
+        match drain {
+            Some(0) => break,
+            Some(left) => drain = Some(left - 1),
+            None => {}
+        }
+        if !fast {
+            thread::sleep(SLICE.saturating_sub(started.elapsed()));
+        }
+    }
+    println!();
+}
+
+fn hex(option: &str, value: Option<&String>) -> u16 {
+    value
+        .and_then(|s| u16::from_str_radix(s.trim_start_matches('$'), 16).ok())
+        .unwrap_or_else(|| {
+            eprintln!("Error: {} requires a hex value", option);
+            process::exit(1);
+        })
+}
+
+fn usage(program: &str) {
+    eprintln!("Usage: {} ROM [options]", program);
+    eprintln!();
+    eprintln!("  Boots a raw ROM image, such as an 8K Microsoft BASIC built for a");
+    eprintln!("  serial 6809 board, with an MC6850 ACIA connected to stdin and stdout.");
+    eprintln!("  Runs at the CPU's clock rate until stdin closes.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --rom-at ADDR    Load the ROM at ADDR (default: so that it ends at FFFF)");
+    eprintln!("  --acia ADDR      Map the ACIA's two registers at ADDR (default: A000)");
+    eprintln!("  --ram-size SIZE  Hex bytes of RAM from 0000 (default: 8000)");
+    eprintln!("  --firq           Wire the ACIA's interrupt to FIRQ instead of IRQ");
+    eprintln!("  --fast           Run as fast as possible rather than in real time");
+}
@@ -0,0 +1,229 @@
+//! Run a 6809 CPU test ROM and report whether it passed.
+//!
+//! Usage: cargo run --release --example test_rom -- FILE[@ADDR] [options]
+//!
+//! Test ROMs report their verdict in one of two ways: by storing a code at
+//! a fixed result address, or by printing text through an output port.
+//! This runner watches for either and exits with status 0 on a pass, 1 on
+//! a failure and 2 if the ROM gives no verdict before the cycle limit.
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+use mc6809_core::formats::Format;
+use mc6809_core::machine::Machine;
+use mc6809_core::{Clocked, FlatRam, Memory};
+
+/// RAM that watches the test ROM's result address and output port.
+struct TestBus {
+    ram: FlatRam,
+    result: Option<u16>,
+    port: Option<u16>,
+    /// The first byte stored at the result address.
+    code: Option<u8>,
+    /// Everything written to the output port.
+    output: Vec<u8>,
+}
+
+impl Memory for TestBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.ram.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        if Some(addr) == self.port {
+            self.output.push(val);
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(&[val]);
+            let _ = stdout.flush();
+        }
+        if Some(addr) == self.result && self.code.is_none() {
+            self.code = Some(val);
+        }
+        self.ram.write(addr, val);
+    }
+}
+
+impl Clocked for TestBus {}
+
+/// How a run ended.
+enum Verdict {
+    Pass,
+    Fail(String),
+    Undecided(String),
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args[1].starts_with("--") {
+        usage(&args[0]);
+        process::exit(2);
+    }
+
+    let mut entry = None;
+    let mut result = None;
+    let mut pass_code = 0x00;
+    let mut port = None;
+    let mut pass_text = String::from("PASS");
+    let mut fail_text = String::from("FAIL");
+    let mut max_cycles: u64 = 100_000_000;
+
+    let mut i = 2;
+    while i < args.len() {
+        let option = args[i].as_str();
+        i += 1;
+        let value = args.get(i).map(String::as_str);
+        match option {
+            "--entry" => entry = Some(hex(option, value)),
+            "--result" => result = Some(hex(option, value)),
+            "--pass" => pass_code = hex(option, value) as u8,
+            "--port" => port = Some(hex(option, value)),
+            "--pass-text" => pass_text = text(option, value),
+            "--fail-text" => fail_text = text(option, value),
+            "--max-cycles" => {
+                max_cycles = value.and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("Error: --max-cycles requires a numeric argument");
+                    process::exit(2);
+                })
+            }
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(2);
+            }
+        }
+        i += 1;
+    }
+    if result.is_none() && port.is_none() {
+        eprintln!("Error: give --result ADDR, --port ADDR or both");
+        process::exit(2);
+    }
+
+    let (path, addr) = match args[1].rsplit_once('@') {
+        Some((path, addr)) => (path, Some(hex("load", Some(addr)))),
+        None => (args[1].as_str(), None),
+    };
+    let data = fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", path, e);
+        process::exit(2);
+    });
+    let format = Format::detect(&data);
+    if !format.has_addresses() && addr.is_none() {
+        eprintln!(
+            "Error: '{}' is a raw binary; give its address as FILE@ADDR",
+            path
+        );
+        process::exit(2);
+    }
+    let image = format.parse(&data, addr.unwrap_or(0)).unwrap_or_else(|e| {
+        eprintln!("Error in {:?} file '{}': {}", format, path, e);
+        process::exit(2);
+    });
+
+    let mut ram = FlatRam::new();
+    image.load_into(&mut ram);
+    // A ROM image may bring its own reset vector; the load address is only
+    // the entry point when it does not.
+    let vector_set = ram.as_slice()[0xFFFE..] != [0, 0];
+    let start = entry.or(image.entry).or(addr.filter(|_| !vector_set));
+    if let Some(start) = start {
+        ram.set_reset_vector(start);
+    } else if !vector_set {
+        eprintln!("Error: no entry address; use --entry ADDR");
+        process::exit(2);
+    }
+
+    let bus = TestBus {
+        ram,
+        result,
+        port,
+        code: None,
+        output: Vec::new(),
+    };
+    let mut machine = Machine::new(bus);
+
+    let verdict = loop {
+        let bus = machine.bus();
+        if let Some(code) = bus.code {
+            break if code == pass_code {
+                Verdict::Pass
+            } else {
+                Verdict::Fail(format!("result code {:02X}", code))
+            };
+        }
+        if port.is_some() {
+            let output = String::from_utf8_lossy(&bus.output);
+            if output.contains(fail_text.as_str()) {
+                break Verdict::Fail(format!("'{}' printed", fail_text));
+            }
+            if output.contains(pass_text.as_str()) {
+                break Verdict::Pass;
+            }
+        }
+        if machine.cpu().halted() {
+            break Verdict::Undecided("CPU halted".to_string());
+        }
+        if machine.cpu().cycles() >= max_cycles {
+            break Verdict::Undecided(format!("cycle limit ({}) reached", max_cycles));
+        }
+        machine.step();
+    };
+
+    let cpu = machine.cpu();
+    if !machine.bus().output.is_empty() {
+        println!();
+    }
+    let status = match verdict {
+        Verdict::Pass => {
+            println!("PASS after {} cycles", cpu.cycles());
+            0
+        }
+        Verdict::Fail(reason) => {
+            println!("FAIL ({}) after {} cycles", reason, cpu.cycles());
+            1
+        }
+        Verdict::Undecided(reason) => {
+            println!("No verdict: {}", reason);
+            2
+        }
+    };
+    println!("Final state: {:?}", cpu);
+    process::exit(status);
+}
+
+fn hex(option: &str, value: Option<&str>) -> u16 {
+    value
+        .and_then(|s| u16::from_str_radix(s.trim_start_matches('$'), 16).ok())
+        .unwrap_or_else(|| {
+            eprintln!("Error: {} requires a hex value", option);
+            process::exit(2);
+        })
+}
+
+fn text(option: &str, value: Option<&str>) -> String {
+    value.map(String::from).unwrap_or_else(|| {
+        eprintln!("Error: {} requires a string", option);
+        process::exit(2);
+    })
+}
+
+fn usage(program: &str) {
+    eprintln!("Usage: {} FILE[@ADDR] [options]", program);
+    eprintln!();
+    eprintln!("  Loads a test ROM (S-record, Intel HEX, DECB or raw at ADDR) and runs");
+    eprintln!("  it until it reports a result. Exits with status 0 on a pass, 1 on a");
+    eprintln!("  failure and 2 if there is no verdict.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --entry ADDR      Start at ADDR (default: the image's entry address,");
+    eprintln!("                    the reset vector it sets, or its load address)");
+    eprintln!("  --result ADDR     Stop at the first write to ADDR and pass if it");
+    eprintln!("                    stores the pass code");
+    eprintln!("  --pass CODE       Pass code for --result (default: 00)");
+    eprintln!("  --port ADDR       Echo bytes written to ADDR and scan them for the");
+    eprintln!("                    pass and fail markers");
+    eprintln!("  --pass-text TEXT  Pass marker for --port (default: PASS)");
+    eprintln!("  --fail-text TEXT  Fail marker for --port (default: FAIL)");
+    eprintln!("  --max-cycles N    Give up after N cycles (default: 100,000,000)");
+}
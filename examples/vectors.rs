@@ -0,0 +1,121 @@
+//! Run single-step JSON test vectors against the core and report by opcode,
+//! or generate them from the core.
+//!
+//! Usage: cargo run --release --example vectors -- <file-or-directory>...
+//!        cargo run --release --example vectors -- --generate DIR [--count N] [--seed S]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use mc6809_core::batch::SeedRng;
+use mc6809_core::vectors::{self, Report};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <file-or-directory>...", args[0]);
+        eprintln!("       {} --generate DIR [--count N] [--seed S]", args[0]);
+        eprintln!();
+        eprintln!("  Runs every JSON test file given, or found in a given directory,");
+        eprintln!("  and prints pass/fail counts by opcode. Exits with status 1 if any");
+        eprintln!("  case fails.");
+        eprintln!();
+        eprintln!("  With --generate, writes N cases (default 1000) for every opcode,");
+        eprintln!("  undefined ones included, to DIR as one file per opcode (86.json,");
+        eprintln!("  108e.json, ...), from random states seeded with S (default 1).");
+        process::exit(1);
+    }
+
+    if args[1] == "--generate" {
+        generate(&args[2..]);
+        return;
+    }
+
+    let mut files = Vec::new();
+    for arg in &args[1..] {
+        collect(Path::new(arg), &mut files);
+    }
+    files.sort();
+
+    let mut report = Report::new();
+    for file in &files {
+        let text = fs::read_to_string(file).unwrap_or_else(|e| {
+            eprintln!("Error reading '{}': {}", file.display(), e);
+            process::exit(1);
+        });
+        let cases = vectors::parse(&text).unwrap_or_else(|e| {
+            eprintln!("Error in '{}': {}", file.display(), e);
+            process::exit(1);
+        });
+        report.run_all(&cases);
+    }
+
+    print!("{report}");
+    if report.failed() > 0 {
+        process::exit(1);
+    }
+}
+
+/// Write a test file for every opcode as `--generate` asks.
+fn generate(args: &[String]) {
+    let Some(dir) = args.first() else {
+        eprintln!("Error: --generate requires a directory");
+        process::exit(1);
+    };
+    let mut count = 1000;
+    let mut seed = 1;
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).and_then(|s| s.parse().ok());
+        match (args[i].as_str(), value) {
+            ("--count", Some(n)) => count = n as usize,
+            ("--seed", Some(s)) => seed = s,
+            (other, _) => {
+                eprintln!("Error: bad option or value: {}", other);
+                process::exit(1);
+            }
+        }
+        i += 2;
+    }
+
+    fs::create_dir_all(dir).unwrap_or_else(|e| {
+        eprintln!("Error creating '{}': {}", dir, e);
+        process::exit(1);
+    });
+    let mut rng = SeedRng::new(seed);
+    let opcodes = (0x00..=0xFF)
+        .filter(|op| !matches!(op, 0x10 | 0x11))
+        .chain((0x1000..=0x10FF).chain(0x1100..=0x11FF));
+    let mut files = 0;
+    for opcode in opcodes {
+        let cases = vectors::generate(opcode, count, &mut rng);
+        let path = Path::new(dir).join(format!("{:02x}.json", opcode));
+        fs::write(&path, vectors::to_json(&cases)).unwrap_or_else(|e| {
+            eprintln!("Error writing '{}': {}", path.display(), e);
+            process::exit(1);
+        });
+        files += 1;
+    }
+    println!("Wrote {} files of {} cases to {}", files, count, dir);
+}
+
+/// Add `path` if it is a file, or the `.json` files in it if it is a
+/// directory.
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let entries = fs::read_dir(path).unwrap_or_else(|e| {
+        eprintln!("Error reading '{}': {}", path.display(), e);
+        process::exit(1);
+    });
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+}
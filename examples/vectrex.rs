@@ -0,0 +1,478 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Vectrex-style machine: a 6809 at 1.5 MHz, a 6522 VIA stub and cartridge
+//! ROM.
+//!
+//! Usage: cargo run --example vectrex -- [<bios.bin> [<cart.bin>]] [--seconds N]
+//!
+//! Without a BIOS image the example runs a built-in demo in which the VIA's
+//! timer 1 interrupts through FIRQ every 501 cycles while the main loop keeps
+//! the CPU busy with long instructions, and reports how late each interrupt
+//! was serviced. Vector drawing on the real machine depends on exactly this
+//! kind of timing: the beam moves for as long as timer 1 holds the RAMP line
+//! (PB7) low.
+//!
+//! With a BIOS image (and optionally a cartridge) it runs the real firmware
+//! against the stub and reports how many vectors were drawn. The VIA is then
+//! wired to IRQ as on the real board, where the BIOS polls its flags instead
+//! of taking interrupts.
+//!
+//! Memory map:
+//!
+//! | Range         | Contents                                   |
+//! |---------------|--------------------------------------------|
+//! | `0000`–`7FFF` | cartridge ROM                              |
+//! | `C800`–`CFFF` | 1 KB RAM, mirrored                         |
+//! | `D000`–`D7FF` | VIA, 16 registers mirrored                 |
+//! | `E000`–`FFFF` | BIOS ROM                                   |
+
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::{Arc, Mutex};
+
+use mc6809_core::bus::{CompositeBus, MemoryMap, Mmio};
+use mc6809_core::machine::Machine;
+use mc6809_core::{BusSignals, Clocked, Snapshot};
+
+/// CPU clock of the Vectrex.
+const CPU_HZ: u64 = 1_500_000;
+
+const REG_ORB: u16 = 0x0;
+const REG_ORA: u16 = 0x1;
+const REG_DDRB: u16 = 0x2;
+const REG_DDRA: u16 = 0x3;
+const REG_T1C_L: u16 = 0x4;
+const REG_T1C_H: u16 = 0x5;
+const REG_T1L_L: u16 = 0x6;
+const REG_T1L_H: u16 = 0x7;
+const REG_T2C_L: u16 = 0x8;
+const REG_T2C_H: u16 = 0x9;
+const REG_SR: u16 = 0xA;
+const REG_ACR: u16 = 0xB;
+const REG_PCR: u16 = 0xC;
+const REG_IFR: u16 = 0xD;
+const REG_IER: u16 = 0xE;
+/// ORA without the CA2 handshake.
+const REG_ORA_NH: u16 = 0xF;
+
+const IF_CA2: u8 = 0x01;
+const IF_CA1: u8 = 0x02;
+const IF_SR: u8 = 0x04;
+const IF_T2: u8 = 0x20;
+const IF_T1: u8 = 0x40;
+
+/// ACR: timer 1 reloads from its latch and keeps interrupting.
+const ACR_T1_CONTINUOUS: u8 = 0x40;
+/// ACR: timer 1 drives PB7 (low while a one-shot count runs).
+const ACR_T1_PB7: u8 = 0x80;
+/// ACR: shift register mode bits.
+const ACR_SR_MODE: u8 = 0x1C;
+
+/// Cycles for the shift register to shift out eight bits at half the CPU
+/// clock.
+const SR_CYCLES: u8 = 18;
+
+/// A 6522 VIA reduced to what drawing depends on: ports, both timers, the
+/// shift-register completion flag and the interrupt logic. The PSG and
+/// integrators it drives on the real board are left out.
+struct Via {
+    orb: u8,
+    ora: u8,
+    ddrb: u8,
+    ddra: u8,
+    acr: u8,
+    pcr: u8,
+    ifr: u8,
+    ier: u8,
+    sr: u8,
+    /// Cycles until the current shift completes, 0 when idle.
+    sr_left: u8,
+    /// Timer counters widened so the reload cycle fits.
+    t1: u32,
+    t1_latch: u16,
+    /// A one-shot timer 1 count is running and will interrupt.
+    t1_armed: bool,
+    t2: u32,
+    t2_latch_lo: u8,
+    t2_armed: bool,
+    /// PB7 level while timer 1 controls it: low is RAMP, the beam moving.
+    pb7: bool,
+    line: BusSignals,
+    /// Cycles ticked so far, the same as the machine's cycle count.
+    cycles: u64,
+    /// Cycle at which timer 1 last expired, until taken by the latency
+    /// probe.
+    t1_expired: Option<u64>,
+    /// Timer 1 expiries while its flag was still set from the last one.
+    overruns: u64,
+    /// Completed RAMP periods: vectors drawn.
+    ramps: u64,
+}
+
+impl Via {
+    fn new(line: BusSignals) -> Self {
+        Self {
+            orb: 0,
+            ora: 0,
+            ddrb: 0,
+            ddra: 0,
+            acr: 0,
+            pcr: 0,
+            ifr: 0,
+            ier: 0,
+            sr: 0,
+            sr_left: 0,
+            t1: 0xFFFF,
+            t1_latch: 0xFFFF,
+            t1_armed: false,
+            t2: 0xFFFF,
+            t2_latch_lo: 0xFF,
+            t2_armed: false,
+            pb7: true,
+            line,
+            cycles: 0,
+            t1_expired: None,
+            overruns: 0,
+            ramps: 0,
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.ifr & self.ier & 0x7F != 0
+    }
+
+    fn set_pb7(&mut self, level: bool) {
+        if level && !self.pb7 {
+            self.ramps += 1;
+        }
+        self.pb7 = level;
+    }
+
+    fn t1_expire(&mut self, at: u64) {
+        if self.ifr & IF_T1 != 0 {
+            self.overruns += 1;
+        }
+        self.ifr |= IF_T1;
+        self.t1_expired = Some(at);
+    }
+
+    fn tick_t1(&mut self, start: u64, cycles: u64) {
+        let (mut now, end) = (start, start + cycles);
+        loop {
+            let expiry = now + self.t1 as u64 + 1;
+            if expiry > end {
+                self.t1 -= (end - now) as u32;
+                return;
+            }
+            now = expiry;
+            if self.acr & ACR_T1_CONTINUOUS != 0 {
+                self.t1_expire(now);
+                if self.acr & ACR_T1_PB7 != 0 {
+                    self.set_pb7(!self.pb7);
+                }
+                // One cycle to reload, so the period is latch + 2.
+                self.t1 = self.t1_latch as u32 + 1;
+            } else {
+                if std::mem::take(&mut self.t1_armed) {
+                    self.t1_expire(now);
+                    if self.acr & ACR_T1_PB7 != 0 {
+                        self.set_pb7(true);
+                    }
+                }
+                self.t1 = 0xFFFF;
+            }
+        }
+    }
+
+    fn tick_t2(&mut self, cycles: u64) {
+        if cycles <= self.t2 as u64 {
+            self.t2 -= cycles as u32;
+            return;
+        }
+        if std::mem::take(&mut self.t2_armed) {
+            self.ifr |= IF_T2;
+        }
+        let past = (cycles - self.t2 as u64 - 1) % 0x10000;
+        self.t2 = 0xFFFF - past as u32;
+    }
+
+    fn start_shift(&mut self) {
+        self.ifr &= !IF_SR;
+        self.sr_left = if self.acr & ACR_SR_MODE != 0 {
+            SR_CYCLES
+        } else {
+            0
+        };
+    }
+}
+
+impl Mmio for Via {
+    fn read(&mut self, offset: u16) -> u8 {
+        match offset & 0xF {
+            REG_ORB => {
+                let pins = (self.orb & self.ddrb) | !self.ddrb;
+                if self.acr & ACR_T1_PB7 != 0 {
+                    (pins & 0x7F) | ((self.pb7 as u8) << 7)
+                } else {
+                    pins
+                }
+            }
+            REG_DDRB => self.ddrb,
+            REG_DDRA => self.ddra,
+            REG_T1C_L => {
+                self.ifr &= !IF_T1;
+                self.t1 as u8
+            }
+            REG_T1C_H => (self.t1 >> 8) as u8,
+            REG_T1L_L => self.t1_latch as u8,
+            REG_T1L_H => (self.t1_latch >> 8) as u8,
+            REG_T2C_L => {
+                self.ifr &= !IF_T2;
+                self.t2 as u8
+            }
+            REG_T2C_H => (self.t2 >> 8) as u8,
+            REG_SR => {
+                self.start_shift();
+                self.sr
+            }
+            REG_ACR => self.acr,
+            REG_PCR => self.pcr,
+            REG_IFR => self.ifr | if self.irq() { 0x80 } else { 0 },
+            REG_IER => self.ier | 0x80,
+            REG_ORA | REG_ORA_NH => {
+                self.ifr &= !(IF_CA1 | IF_CA2);
+                (self.ora & self.ddra) | !self.ddra
+            }
+            _ => unreachable!("offset masked to 4 bits"),
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        match offset & 0xF {
+            REG_ORB => self.orb = val,
+            REG_DDRB => self.ddrb = val,
+            REG_DDRA => self.ddra = val,
+            REG_T1C_L | REG_T1L_L => self.t1_latch = (self.t1_latch & 0xFF00) | val as u16,
+            REG_T1C_H => {
+                self.t1_latch = (self.t1_latch & 0x00FF) | (val as u16) << 8;
+                self.t1 = self.t1_latch as u32;
+                self.t1_armed = true;
+                self.ifr &= !IF_T1;
+                if self.acr & ACR_T1_PB7 != 0 {
+                    self.set_pb7(false);
+                }
+            }
+            REG_T1L_H => {
+                self.t1_latch = (self.t1_latch & 0x00FF) | (val as u16) << 8;
+                self.ifr &= !IF_T1;
+            }
+            REG_T2C_L => self.t2_latch_lo = val,
+            REG_T2C_H => {
+                self.t2 = (val as u32) << 8 | self.t2_latch_lo as u32;
+                self.t2_armed = true;
+                self.ifr &= !IF_T2;
+            }
+            REG_SR => {
+                self.sr = val;
+                self.start_shift();
+            }
+            REG_ACR => self.acr = val,
+            REG_PCR => self.pcr = val,
+            REG_IFR => self.ifr &= !val,
+            REG_IER => {
+                if val & 0x80 != 0 {
+                    self.ier |= val & 0x7F;
+                } else {
+                    self.ier &= !val;
+                }
+            }
+            REG_ORA | REG_ORA_NH => {
+                self.ifr &= !(IF_CA1 | IF_CA2);
+                self.ora = val;
+            }
+            _ => unreachable!("offset masked to 4 bits"),
+        }
+    }
+}
+
+impl Clocked for Via {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        let start = self.cycles;
+        self.cycles += cycles;
+        self.tick_t1(start, cycles);
+        self.tick_t2(cycles);
+        if self.sr_left > 0 {
+            self.sr_left = self.sr_left.saturating_sub(cycles.min(0xFF) as u8);
+            if self.sr_left == 0 {
+                self.ifr |= IF_SR;
+            }
+        }
+        if self.irq() {
+            self.line
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+/// Save states are not used by this example.
+impl Snapshot for Via {}
+
+/// Built-in demo "BIOS" at E000: timer 1 interrupts through FIRQ every 501
+/// cycles while the main loop runs a mix of short and long instructions.
+fn demo_rom() -> Vec<u8> {
+    #[rustfmt::skip]
+    let program: &[u8] = &[
+        0x10, 0xCE, 0xCB, 0xEA, // E000       LDS   #$CBEA
+        0x86, 0xC0,             // E004       LDA   #$C0      T1 continuous, PB7
+        0xB7, 0xD0, 0x0B,       // E006       STA   $D00B     ACR
+        0x86, 0xC0,             // E009       LDA   #$C0      enable T1
+        0xB7, 0xD0, 0x0E,       // E00B       STA   $D00E     IER
+        0xCC, 0x01, 0xF3,       // E00E       LDD   #499
+        0xF7, 0xD0, 0x04,       // E011       STB   $D004     latch low
+        0xB7, 0xD0, 0x05,       // E014       STA   $D005     latch high, start
+        0x1C, 0xBF,             // E017       ANDCC #$BF      unmask FIRQ
+        0xBE, 0xC8, 0x80,       // E019 loop: LDX   $C880
+        0x30, 0x01,             // E01C       LEAX  1,X
+        0xBF, 0xC8, 0x80,       // E01E       STX   $C880
+        0x3D,                   // E021       MUL
+        0x20, 0xF5,             // E022       BRA   loop
+        0x34, 0x02,             // E024 firq: PSHS  A
+        0xB6, 0xD0, 0x04,       // E026       LDA   $D004     acknowledge T1
+        0x7C, 0xC8, 0x82,       // E029       INC   $C882
+        0x35, 0x02,             // E02C       PULS  A
+        0x3B,                   // E02E       RTI
+    ];
+    let mut rom = vec![0xFF; 0x2000];
+    rom[..program.len()].copy_from_slice(program);
+    rom[0x1FF6..0x1FF8].copy_from_slice(&DEMO_FIRQ.to_be_bytes());
+    rom[0x1FFE..].copy_from_slice(&0xE000u16.to_be_bytes());
+    rom
+}
+
+/// Address of the demo's FIRQ handler.
+const DEMO_FIRQ: u16 = 0xE024;
+
+/// Interrupt latency seen by the demo's probe.
+#[derive(Default)]
+struct Latency {
+    count: u64,
+    total: u64,
+    min: u64,
+    max: u64,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut images = Vec::new();
+    let mut seconds = 2;
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--seconds" => {
+                i += 1;
+                seconds = args.get(i).and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("Error: --seconds requires a numeric argument");
+                    process::exit(1);
+                });
+            }
+            "--help" | "-h" => {
+                eprintln!("Usage: {} [<bios.bin> [<cart.bin>]] [--seconds N]", args[0]);
+                eprintln!();
+                eprintln!("  Without a BIOS, runs a built-in FIRQ timing demo and reports");
+                eprintln!("  interrupt latency. With one, runs it and counts vectors drawn.");
+                process::exit(1);
+            }
+            path => images.push(fs::read(path).unwrap_or_else(|e| {
+                eprintln!("Error reading '{}': {}", path, e);
+                process::exit(1);
+            })),
+        }
+        i += 1;
+    }
+
+    let demo = images.is_empty();
+    let bios = if demo { demo_rom() } else { images.remove(0) };
+    if bios.len() > 0x2000 {
+        eprintln!("Error: BIOS image larger than 8 KB");
+        process::exit(1);
+    }
+    let mut map = MemoryMap::new()
+        .ram(0xC800, 0x400)
+        .mirror(0x800)
+        .rom((0x10000 - bios.len()) as u16, &bios);
+    if let Some(cart) = images.first() {
+        if cart.len() > 0x8000 {
+            eprintln!("Error: cartridge image larger than 32 KB");
+            process::exit(1);
+        }
+        map = map.rom(0x0000, cart);
+    }
+
+    let mut bus = CompositeBus::new(map);
+    let line = if demo {
+        BusSignals::FIRQ
+    } else {
+        BusSignals::IRQ
+    };
+    let via = bus.add_device(Via::new(line));
+    bus.map(via, 0xD000, 0x800, 0);
+    let mut machine = Machine::new(bus);
+
+    let latency = Arc::new(Mutex::new(Latency {
+        min: u64::MAX,
+        ..Latency::default()
+    }));
+    if demo {
+        // Time from timer 1 expiring to the first instruction of the handler.
+        let latency = latency.clone();
+        machine.add_step_hook(move |cpu, bus, _| {
+            if cpu.registers().pc != DEMO_FIRQ {
+                return;
+            }
+            let via = bus.device_as::<Via>(via).expect("VIA is mapped");
+            if let Some(expired) = via.t1_expired.take() {
+                let late = via.cycles - expired;
+                let mut latency = latency.lock().unwrap();
+                latency.count += 1;
+                latency.total += late;
+                latency.min = latency.min.min(late);
+                latency.max = latency.max.max(late);
+            }
+        });
+    }
+
+    let cycles = machine.run_for(seconds * CPU_HZ);
+    let via = machine
+        .bus_mut()
+        .device_as::<Via>(via)
+        .expect("VIA is mapped");
+    println!("Ran {} cycles ({} s at 1.5 MHz)", cycles, seconds);
+    println!("Timer 1 overruns: {}", via.overruns);
+    println!("Vectors drawn (RAMP periods): {}", via.ramps);
+    if demo {
+        let latency = latency.lock().unwrap();
+        if latency.count > 0 {
+            println!(
+                "FIRQ latency over {} interrupts: min {} / mean {:.1} / max {} cycles",
+                latency.count,
+                latency.min,
+                latency.total as f64 / latency.count as f64,
+                latency.max
+            );
+        }
+    }
+}
@@ -16,8 +16,20 @@
 //!
 //! The post-byte encodes the index register, offset type, and indirection.
 //! Returns `(effective_address, extra_cycles)`.
+//!
+//! Post-bytes the datasheet leaves undefined decode as observed on silicon:
+//! mode 7 acts as `A,R`, mode A gives `PC | 0xFF`, mode E gives `0xFFFF`, mode
+//! F without indirection is a plain extended address, and the indirect forms
+//! of `,R+` and `,-R` indirect like the others.
+//!
+//! On the HD6309 modes 7, A and E are `E,R`, `F,R` and `W,R`, and the
+//! post-bytes for mode F without indirection and mode 0 with it select the
+//! W-relative modes `,W`, `n,W`, `,W++` and `,--W` (by the register bits,
+//! in that order), plain and indirect. In native mode the auto-increment and
+//! decrement, 16-bit offset, `D,R`, `W,R` and extended indirect modes take
+//! fewer cycles.
 
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, CpuVariant};
 use crate::memory::Memory;
 
 /// Decode an indexed addressing post-byte and compute the effective address.
@@ -44,33 +56,40 @@ pub fn indexed(cpu: &mut Cpu, mem: &mut impl Memory) -> (u16, u8) {
     // Bit 7 == 1: complex indexed modes
     let indirect = post & 0x10 != 0;
     let mode = post & 0x0F;
+    let hd6309 = cpu.variant() == CpuVariant::Hd6309;
+    let native = cpu.native_mode();
+    // Extra cycles, by emulation and native mode
+    let cycles = |emulation: u8, native_mode: u8| if native { native_mode } else { emulation };
 
     let (ea, extra) = match mode {
+        // HD6309: the W-relative modes
+        0x0F if hd6309 && !indirect => w_relative(cpu, mem, post),
+        0x00 if hd6309 && indirect => w_relative(cpu, mem, post),
         // 0x00: ,R+ (post-increment by 1) — no indirect variant
         0x00 => {
             let reg = index_reg(cpu, post);
             let ea = reg;
             set_index_reg(cpu, post, reg.wrapping_add(1));
-            (ea, 2)
+            (ea, cycles(2, 1))
         }
         // 0x01: ,R++ (post-increment by 2)
         0x01 => {
             let reg = index_reg(cpu, post);
             let ea = reg;
             set_index_reg(cpu, post, reg.wrapping_add(2));
-            (ea, 3)
+            (ea, cycles(3, 2))
         }
         // 0x02: ,-R (pre-decrement by 1) — no indirect variant
         0x02 => {
             let reg = index_reg(cpu, post).wrapping_sub(1);
             set_index_reg(cpu, post, reg);
-            (reg, 2)
+            (reg, cycles(2, 1))
         }
         // 0x03: ,--R (pre-decrement by 2)
         0x03 => {
             let reg = index_reg(cpu, post).wrapping_sub(2);
             set_index_reg(cpu, post, reg);
-            (reg, 3)
+            (reg, cycles(3, 2))
         }
         // 0x04: ,R (zero offset)
         0x04 => {
@@ -83,8 +102,14 @@ pub fn indexed(cpu: &mut Cpu, mem: &mut impl Memory) -> (u16, u8) {
             let offset = cpu.registers().b() as i8 as i16 as u16;
             (reg.wrapping_add(offset), 1)
         }
-        // 0x06: A,R
-        0x06 => {
+        // 0x07 (HD6309): E,R
+        0x07 if hd6309 => {
+            let reg = index_reg(cpu, post);
+            let offset = cpu.hd6309_registers().e() as i8 as i16 as u16;
+            (reg.wrapping_add(offset), 1)
+        }
+        // 0x06: A,R, and 0x07 (undocumented), which decodes the same
+        0x06 | 0x07 => {
             let reg = index_reg(cpu, post);
             let offset = cpu.registers().a() as i8 as i16 as u16;
             (reg.wrapping_add(offset), 1)
@@ -99,13 +124,21 @@ pub fn indexed(cpu: &mut Cpu, mem: &mut impl Memory) -> (u16, u8) {
         0x09 => {
             let reg = index_reg(cpu, post);
             let offset = cpu.fetch_word(mem);
-            (reg.wrapping_add(offset), 4)
+            (reg.wrapping_add(offset), cycles(4, 3))
         }
+        // 0x0A (HD6309): F,R
+        0x0A if hd6309 => {
+            let reg = index_reg(cpu, post);
+            let offset = cpu.hd6309_registers().f() as i8 as i16 as u16;
+            (reg.wrapping_add(offset), 1)
+        }
+        // 0x0A (undocumented): PC with the low byte forced to 0xFF
+        0x0A => (cpu.registers().pc | 0x00FF, 4),
         // 0x0B: D,R
         0x0B => {
             let reg = index_reg(cpu, post);
             let offset = cpu.registers().d;
-            (reg.wrapping_add(offset), 4)
+            (reg.wrapping_add(offset), cycles(4, 2))
         }
         // 0x0C: 8-bit offset, PC
         0x0C => {
@@ -117,21 +150,25 @@ pub fn indexed(cpu: &mut Cpu, mem: &mut impl Memory) -> (u16, u8) {
         0x0D => {
             let offset = cpu.fetch_word(mem);
             let ea = cpu.registers().pc.wrapping_add(offset);
-            (ea, 5)
+            (ea, cycles(5, 3))
         }
-        // 0x0F: Extended indirect [address] (only valid with indirect bit)
-        0x0F if indirect => {
-            let ea = cpu.fetch_word(mem);
-            // The indirect dereference happens below. Base extra = 5, then +3 for indirect.
-            // But for extended indirect, total extra = 5 (already includes indirection).
-            let ptr = mem.read_word(ea);
-            return (ptr, 5);
+        // 0x0E (HD6309): W,R
+        0x0E if hd6309 => {
+            let reg = index_reg(cpu, post);
+            let offset = cpu.hd6309_registers().w;
+            (reg.wrapping_add(offset), cycles(4, 1))
         }
-        // Illegal indexed modes
-        _ => {
-            // Undefined behavior — return 0 with 0 extra cycles
-            (0, 0)
+        // 0x0E (undocumented): always 0xFFFF
+        0x0E => (0xFFFF, 5),
+        // 0x0F: Extended indirect [address]; without the indirect bit
+        // (undocumented) the plain extended address
+        0x0F => {
+            let ea = cpu.fetch_word(mem);
+            // Extended indirect totals 5 extra, including the indirection
+            // (4 in native mode).
+            (ea, cycles(2, 1))
         }
+        0x10..=0xFF => unreachable!(),
     };
 
     if indirect {
@@ -143,6 +180,33 @@ pub fn indexed(cpu: &mut Cpu, mem: &mut impl Memory) -> (u16, u8) {
     }
 }
 
+/// Decode a HD6309 W-relative mode, selected by bits 6-5 of the post-byte.
+/// Returns `(ea, extra_cycles)` before any indirection.
+fn w_relative(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) -> (u16, u8) {
+    let w = cpu.hd6309_registers().w;
+    match (post >> 5) & 0x03 {
+        // ,W
+        0 => (w, 0),
+        // 16-bit offset, W
+        1 => {
+            let offset = cpu.fetch_word(mem);
+            (w.wrapping_add(offset), 2)
+        }
+        // ,W++
+        2 => {
+            cpu.hd6309_registers_mut().w = w.wrapping_add(2);
+            (w, 1)
+        }
+        // ,--W
+        3 => {
+            let w = w.wrapping_sub(2);
+            cpu.hd6309_registers_mut().w = w;
+            (w, 1)
+        }
+        _ => unreachable!(),
+    }
+}
+
 /// Read the index register selected by bits 6-5 of the post-byte.
 fn index_reg(cpu: &Cpu, post: u8) -> u16 {
     match (post >> 5) & 0x03 {
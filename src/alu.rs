@@ -12,13 +12,18 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
-//! ALU (Arithmetic Logic Unit) helpers for the 6809.
+//! ALU (Arithmetic Logic Unit) helpers for the 6809 and the HD6309.
 //!
 //! Each operation takes operand(s) and cc flags, performs the operation,
 //! and returns the result with updated flags.
 
 use crate::registers::ConditionCodes;
 
+#[cfg(feature = "alu-verify")]
+pub(crate) mod verify;
+#[cfg(feature = "alu-verify")]
+pub use verify::{AluMismatch, CC_INPUTS, verify};
+
 // ---------------------------------------------------------------------------
 // 8-bit arithmetic
 // ---------------------------------------------------------------------------
@@ -46,7 +51,9 @@ pub fn adc8(a: u8, b: u8, cc: &mut ConditionCodes) -> u8 {
     result
 }
 
-/// SUB: result = a - b. Sets H (undefined per spec, we leave it), N, Z, V, C.
+/// SUB: result = a - b. Sets N, Z, V, C; H is undefined per spec and left
+/// unchanged (the CPU can set it as silicon does, see
+/// [`HalfCarryQuirks`](crate::HalfCarryQuirks)).
 pub fn sub8(a: u8, b: u8, cc: &mut ConditionCodes) -> u8 {
     let r16 = (a as u16).wrapping_sub(b as u16);
     let result = r16 as u8;
@@ -56,7 +63,8 @@ pub fn sub8(a: u8, b: u8, cc: &mut ConditionCodes) -> u8 {
     result
 }
 
-/// SBC: result = a - b - carry. Sets N, Z, V, C.
+/// SBC: result = a - b - carry. Sets N, Z, V, C; H is left unchanged as
+/// for [`sub8`].
 pub fn sbc8(a: u8, b: u8, cc: &mut ConditionCodes) -> u8 {
     let c = cc.carry() as u16;
     let r16 = (a as u16).wrapping_sub(b as u16).wrapping_sub(c);
@@ -67,7 +75,8 @@ pub fn sbc8(a: u8, b: u8, cc: &mut ConditionCodes) -> u8 {
     result
 }
 
-/// NEG: result = 0 - val. Sets N, Z, V, C.
+/// NEG: result = 0 - val. Sets N, Z, V, C; H is left unchanged as for
+/// [`sub8`].
 pub fn neg8(val: u8, cc: &mut ConditionCodes) -> u8 {
     let result = (val as i8).wrapping_neg() as u8;
     cc.set_nz8(result);
@@ -217,6 +226,196 @@ pub fn sub16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
     result
 }
 
+/// ADC16: result = a + b + carry. Sets N, Z, V, C (HD6309 ADCD, ADCR).
+pub fn adc16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
+    let c = cc.carry() as u32;
+    let r32 = a as u32 + b as u32 + c;
+    let result = r32 as u16;
+    cc.set_nz16(result);
+    cc.set_overflow((a ^ result) & (b ^ result) & 0x8000 != 0);
+    cc.set_carry(r32 > 0xFFFF);
+    result
+}
+
+/// SBC16: result = a - b - carry. Sets N, Z, V, C (HD6309 SBCD, SBCR).
+pub fn sbc16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
+    let c = cc.carry() as u32;
+    let r32 = (a as u32).wrapping_sub(b as u32).wrapping_sub(c);
+    let result = r32 as u16;
+    cc.set_nz16(result);
+    cc.set_overflow((a ^ b) & (a ^ result) & 0x8000 != 0);
+    cc.set_carry((a as u32) < b as u32 + c);
+    result
+}
+
+/// NEG16: result = 0 - val. Sets N, Z, V, C.
+pub fn neg16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    sub16(0, val, cc)
+}
+
+/// COM16: result = !val. Sets N, Z, V=0, C=1.
+pub fn com16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = !val;
+    cc.set_nz16(result);
+    cc.set_overflow(false);
+    cc.set_carry(true);
+    result
+}
+
+/// INC16: result = val + 1. Sets N, Z, V. Does NOT affect C.
+pub fn inc16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = val.wrapping_add(1);
+    cc.set_nz16(result);
+    cc.set_overflow(val == 0x7FFF);
+    result
+}
+
+/// DEC16: result = val - 1. Sets N, Z, V. Does NOT affect C.
+pub fn dec16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = val.wrapping_sub(1);
+    cc.set_nz16(result);
+    cc.set_overflow(val == 0x8000);
+    result
+}
+
+/// CLR16: result = 0. Sets N=0, Z=1, V=0, C=0.
+pub fn clr16(cc: &mut ConditionCodes) -> u16 {
+    clr8(cc) as u16
+}
+
+/// TST16: test value. Sets N, Z, V=0. Does NOT affect C.
+pub fn tst16(val: u16, cc: &mut ConditionCodes) {
+    cc.set_nz16(val);
+    cc.set_overflow(false);
+}
+
+// ---------------------------------------------------------------------------
+// 16-bit logical and shifts/rotates (HD6309)
+// ---------------------------------------------------------------------------
+
+/// AND16: result = a & b. Sets N, Z, V=0.
+pub fn and16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = a & b;
+    ld16_flags(result, cc);
+    result
+}
+
+/// OR16: result = a | b. Sets N, Z, V=0.
+pub fn or16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = a | b;
+    ld16_flags(result, cc);
+    result
+}
+
+/// EOR16: result = a ^ b. Sets N, Z, V=0.
+pub fn eor16(a: u16, b: u16, cc: &mut ConditionCodes) -> u16 {
+    let result = a ^ b;
+    ld16_flags(result, cc);
+    result
+}
+
+/// LSR16: logical shift right. Bit 0 → C, 0 → bit 15. Sets N=0, Z, C.
+pub fn lsr16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    cc.set_carry(val & 0x0001 != 0);
+    let result = val >> 1;
+    cc.set_negative(false);
+    cc.set_zero(result == 0);
+    result
+}
+
+/// ASR16: arithmetic shift right. Bit 0 → C, bit 15 preserved. Sets N, Z, C.
+pub fn asr16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    cc.set_carry(val & 0x0001 != 0);
+    let result = ((val as i16) >> 1) as u16;
+    cc.set_nz16(result);
+    result
+}
+
+/// ASL16/LSL16: shift left. Bit 15 → C, 0 → bit 0. Sets N, Z, V, C.
+pub fn asl16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    cc.set_carry(val & 0x8000 != 0);
+    let result = val << 1;
+    cc.set_nz16(result);
+    cc.set_overflow((val ^ result) & 0x8000 != 0);
+    result
+}
+
+/// ROL16: rotate left through carry. Old C → bit 0, bit 15 → new C. Sets N, Z, V, C.
+pub fn rol16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    let old_c = cc.carry() as u16;
+    cc.set_carry(val & 0x8000 != 0);
+    let result = (val << 1) | old_c;
+    cc.set_nz16(result);
+    cc.set_overflow((val ^ result) & 0x8000 != 0);
+    result
+}
+
+/// ROR16: rotate right through carry. Old C → bit 15, bit 0 → new C. Sets N, Z, C.
+pub fn ror16(val: u16, cc: &mut ConditionCodes) -> u16 {
+    let old_c = cc.carry() as u16;
+    cc.set_carry(val & 0x0001 != 0);
+    let result = (val >> 1) | (old_c << 15);
+    cc.set_nz16(result);
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Signed multiply and divide (HD6309)
+// ---------------------------------------------------------------------------
+
+/// MULD: signed 16×16 → 32-bit multiply. Sets N, Z, V=0, C=0.
+pub fn muld(a: u16, b: u16, cc: &mut ConditionCodes) -> u32 {
+    let result = (a as i16 as i32 * b as i16 as i32) as u32;
+    ld32_flags(result, cc);
+    cc.set_carry(false);
+    result
+}
+
+/// DIVD: signed 16 ÷ 8-bit divide, returning `(quotient, remainder)`.
+///
+/// A quotient outside -128..=127 sets V; one outside -256..=255 cannot be
+/// stored at all, so `None` is returned with N, Z and C cleared. Otherwise
+/// sets N and Z from the quotient and C from its bit 0. The remainder takes
+/// the sign of the dividend. `divisor` must not be zero.
+pub fn divd(dividend: u16, divisor: u8, cc: &mut ConditionCodes) -> Option<(u8, u8)> {
+    let dividend = dividend as i16 as i32;
+    let divisor = divisor as i8 as i32;
+    let quotient = dividend / divisor;
+    let remainder = dividend % divisor;
+    div_flags(quotient as i64, i8::MIN as i64, i8::MAX as i64, cc)?;
+    cc.set_nz8(quotient as u8);
+    Some((quotient as u8, remainder as u8))
+}
+
+/// DIVQ: signed 32 ÷ 16-bit divide, returning `(quotient, remainder)`.
+///
+/// As [`divd`], one size up: V is set for a quotient outside
+/// -32768..=32767, and `None` returned for one outside -65536..=65535.
+pub fn divq(dividend: u32, divisor: u16, cc: &mut ConditionCodes) -> Option<(u16, u16)> {
+    let dividend = dividend as i32 as i64;
+    let divisor = divisor as i16 as i64;
+    let quotient = dividend / divisor;
+    let remainder = dividend % divisor;
+    div_flags(quotient, i16::MIN as i64, i16::MAX as i64, cc)?;
+    cc.set_nz16(quotient as u16);
+    Some((quotient as u16, remainder as u16))
+}
+
+/// Overflow and carry for a division whose quotient register holds
+/// `min..=max`. Returns `None` when the quotient does not fit even the
+/// doubled range.
+fn div_flags(quotient: i64, min: i64, max: i64, cc: &mut ConditionCodes) -> Option<()> {
+    cc.set_overflow(quotient < min || quotient > max);
+    if quotient < 2 * min || quotient > 2 * max + 1 {
+        cc.set_negative(false);
+        cc.set_zero(false);
+        cc.set_carry(false);
+        return None;
+    }
+    cc.set_carry(quotient & 1 != 0);
+    Some(())
+}
+
 // ---------------------------------------------------------------------------
 // 16-bit load/store flag helpers
 // ---------------------------------------------------------------------------
@@ -227,6 +426,13 @@ pub fn ld16_flags(val: u16, cc: &mut ConditionCodes) {
     cc.set_overflow(false);
 }
 
+/// Set flags for a 32-bit load result (HD6309 Q). Sets N, Z, V=0.
+pub fn ld32_flags(val: u32, cc: &mut ConditionCodes) {
+    cc.set_negative(val & 0x8000_0000 != 0);
+    cc.set_zero(val == 0);
+    cc.set_overflow(false);
+}
+
 /// Set flags for an 8-bit load result. Sets N, Z, V=0.
 pub fn ld8_flags(val: u8, cc: &mut ConditionCodes) {
     cc.set_nz8(val);
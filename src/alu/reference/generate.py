@@ -0,0 +1,149 @@
+#!/usr/bin/env python3
+#   Copyright 2026 Martin Åkesson
+#
+#   Licensed under the Apache License, Version 2.0 (the "License");
+#   you may not use this file except in compliance with the License.
+#   You may obtain a copy of the License at
+#
+#       http://www.apache.org/licenses/LICENSE-2.0
+#
+#   Unless required by applicable law or agreed to in writing, software
+#   distributed under the License is distributed on an "AS IS" BASIS,
+#   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+#   See the License for the specific language governing permissions and
+#   limitations under the License.
+
+"""Regenerate the ALU reference tables checked by `alu::verify`.
+
+The model here is written from the bit equations in the MC6809 programming
+manual rather than from `alu.rs`, so the two are independent. Each table
+covers every operand (and operand pair) under each starting CC in
+`CC_INPUTS`, as (result, CC) byte pairs, compressed with raw deflate.
+Flags the manual leaves undefined are written as 0; the verifier masks
+them out.
+
+Usage: python3 src/alu/reference/generate.py
+"""
+
+import os
+import zlib
+
+# Must match `CC_INPUTS` in src/alu/verify.rs. Between them they cover every
+# combination of H and C going in, and every flag both set and clear.
+CC_INPUTS = (0x00, 0x01, 0x20, 0xFF)
+
+H, N, Z, V, C = 0x20, 0x08, 0x04, 0x02, 0x01
+
+
+def bit(value, n):
+    return (value >> n) & 1
+
+
+def nz(result):
+    return (N if bit(result, 7) else 0) | (0 if result else Z)
+
+
+def flags(cc, affected, set_bits):
+    """`cc` with the `affected` bits replaced by `set_bits`."""
+    return (cc & ~affected & 0xFF) | set_bits
+
+
+def add(a, b, cc, carry_in):
+    r = (a + b + carry_in) & 0xFF
+    a3, b3, r3 = bit(a, 3), bit(b, 3), bit(r, 3)
+    a7, b7, r7 = bit(a, 7), bit(b, 7), bit(r, 7)
+    h = a3 & b3 | b3 & (1 - r3) | (1 - r3) & a3
+    v = a7 & b7 & (1 - r7) | (1 - a7) & (1 - b7) & r7
+    c = a7 & b7 | b7 & (1 - r7) | (1 - r7) & a7
+    return r, flags(cc, H | N | Z | V | C, h * H | nz(r) | v * V | c * C)
+
+
+def sub(a, b, cc, carry_in):
+    r = (a - b - carry_in) & 0xFF
+    a7, b7, r7 = bit(a, 7), bit(b, 7), bit(r, 7)
+    v = a7 & (1 - b7) & (1 - r7) | (1 - a7) & b7 & r7
+    c = (1 - a7) & b7 | b7 & r7 | r7 & (1 - a7)
+    # H is undefined.
+    return r, flags(cc, H | N | Z | V | C, nz(r) | v * V | c * C)
+
+
+def logical(r, cc):
+    return r, flags(cc, N | Z | V, nz(r))
+
+
+BINARY = {
+    "add8": lambda a, b, cc: add(a, b, cc, 0),
+    "adc8": lambda a, b, cc: add(a, b, cc, cc & C),
+    "sub8": lambda a, b, cc: sub(a, b, cc, 0),
+    "sbc8": lambda a, b, cc: sub(a, b, cc, cc & C),
+    "and8": lambda a, b, cc: logical(a & b, cc),
+    "or8": lambda a, b, cc: logical(a | b, cc),
+    "eor8": lambda a, b, cc: logical(a ^ b, cc),
+}
+
+
+def neg(m, cc):
+    r, cc = sub(0, m, cc, 0)
+    return r, cc
+
+
+def shift_left(m, cc, carry_in):
+    r = (m << 1 | carry_in) & 0xFF
+    v = bit(m, 7) ^ bit(m, 6)
+    return r, flags(cc, N | Z | V | C, nz(r) | v * V | bit(m, 7) * C)
+
+
+def shift_right(m, cc, top):
+    r = m >> 1 | top << 7
+    return r, flags(cc, N | Z | C, nz(r) | bit(m, 0) * C)
+
+
+def daa(a, cc):
+    msn, lsn = a >> 4, a & 0x0F
+    correction = 0
+    if cc & H or lsn > 9:
+        correction |= 0x06
+    if cc & C or msn > 9 or (msn > 8 and lsn > 9):
+        correction |= 0x60
+    r = (a + correction) & 0xFF
+    c = 1 if correction & 0x60 else 0
+    # V is undefined.
+    return r, flags(cc, N | Z | V | C, nz(r) | c * C)
+
+
+UNARY = {
+    "neg8": neg,
+    "com8": lambda m, cc: (m ^ 0xFF, flags(cc, N | Z | V | C, nz(m ^ 0xFF) | C)),
+    "inc8": lambda m, cc: ((m + 1) & 0xFF, flags(cc, N | Z | V, nz((m + 1) & 0xFF) | (V if m == 0x7F else 0))),
+    "dec8": lambda m, cc: ((m - 1) & 0xFF, flags(cc, N | Z | V, nz((m - 1) & 0xFF) | (V if m == 0x80 else 0))),
+    "tst8": lambda m, cc: (m, flags(cc, N | Z | V, nz(m))),
+    "lsr8": lambda m, cc: shift_right(m, cc, 0),
+    "asr8": lambda m, cc: shift_right(m, cc, bit(m, 7)),
+    "asl8": lambda m, cc: shift_left(m, cc, 0),
+    "rol8": lambda m, cc: shift_left(m, cc, cc & C),
+    "ror8": lambda m, cc: shift_right(m, cc, cc & C),
+    "daa": daa,
+}
+
+
+def deflate(data):
+    compressor = zlib.compressobj(9, zlib.DEFLATED, -15, 9)
+    return compressor.compress(bytes(data)) + compressor.flush()
+
+
+def main():
+    out = os.path.dirname(os.path.abspath(__file__))
+    tables = {}
+    for name, op in BINARY.items():
+        tables[name] = [
+            x for cc in CC_INPUTS for a in range(256) for b in range(256) for x in op(a, b, cc)
+        ]
+    for name, op in UNARY.items():
+        tables[name] = [x for cc in CC_INPUTS for m in range(256) for x in op(m, cc)]
+    for name, table in tables.items():
+        with open(os.path.join(out, name + ".bin"), "wb") as f:
+            f.write(deflate(table))
+
+
+if __name__ == "__main__":
+    main()
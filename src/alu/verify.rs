@@ -0,0 +1,195 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Exhaustive checks of the 8-bit ALU against reference tables.
+//!
+//! `reference/*.bin` hold the result and CC of every 8-bit operation for
+//! every operand, or pair of operands, under each starting CC in
+//! [`CC_INPUTS`]. They are generated by `reference/generate.py` from the bit
+//! equations in the MC6809 programming manual, independently of the functions
+//! they check, and stored deflated.
+
+use std::error::Error;
+use std::fmt;
+
+use super::*;
+
+/// The CC values each operation is checked from: every combination of H
+/// and C going in, and every flag both set and clear.
+pub const CC_INPUTS: [u8; 4] = [0x00, 0x01, 0x20, 0xFF];
+
+const H: u8 = 0x20;
+const V: u8 = 0x02;
+
+type Binary = fn(u8, u8, &mut ConditionCodes) -> u8;
+type Unary = fn(u8, &mut ConditionCodes) -> u8;
+
+/// Operations taking two operands, with their table and the flags the
+/// manual leaves undefined.
+const BINARY: [(&str, Binary, &[u8], u8); 7] = [
+    ("add8", add8, include_bytes!("reference/add8.bin"), 0),
+    ("adc8", adc8, include_bytes!("reference/adc8.bin"), 0),
+    ("sub8", sub8, include_bytes!("reference/sub8.bin"), H),
+    ("sbc8", sbc8, include_bytes!("reference/sbc8.bin"), H),
+    ("and8", and8, include_bytes!("reference/and8.bin"), 0),
+    ("or8", or8, include_bytes!("reference/or8.bin"), 0),
+    ("eor8", eor8, include_bytes!("reference/eor8.bin"), 0),
+];
+
+/// As [`BINARY`], for operations on one operand.
+const UNARY: [(&str, Unary, &[u8], u8); 11] = [
+    ("neg8", neg8, include_bytes!("reference/neg8.bin"), H),
+    ("com8", com8, include_bytes!("reference/com8.bin"), 0),
+    ("inc8", inc8, include_bytes!("reference/inc8.bin"), 0),
+    ("dec8", dec8, include_bytes!("reference/dec8.bin"), 0),
+    ("tst8", tst8_result, include_bytes!("reference/tst8.bin"), 0),
+    ("lsr8", lsr8, include_bytes!("reference/lsr8.bin"), 0),
+    ("asr8", asr8, include_bytes!("reference/asr8.bin"), 0),
+    ("asl8", asl8, include_bytes!("reference/asl8.bin"), H),
+    ("rol8", rol8, include_bytes!("reference/rol8.bin"), 0),
+    ("ror8", ror8, include_bytes!("reference/ror8.bin"), 0),
+    ("daa", daa, include_bytes!("reference/daa.bin"), V),
+];
+
+/// [`tst8`] with its operand as the result, to fit the table layout.
+fn tst8_result(val: u8, cc: &mut ConditionCodes) -> u8 {
+    tst8(val, cc);
+    val
+}
+
+/// An input on which an ALU operation disagrees with its reference table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AluMismatch {
+    /// The function, such as `"daa"`.
+    pub op: &'static str,
+    /// The operand, or the first of two.
+    pub a: u8,
+    /// The second operand of a two-operand operation.
+    pub b: Option<u8>,
+    /// CC going in.
+    pub cc: u8,
+    /// Result and CC from the table.
+    pub expected: (u8, u8),
+    /// Result and CC from the function. Flags the manual leaves undefined
+    /// for `op` are cleared in both.
+    pub found: (u8, u8),
+}
+
+impl fmt::Display for AluMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({:02X}", self.op, self.a)?;
+        if let Some(b) = self.b {
+            write!(f, ", {b:02X}")?;
+        }
+        write!(
+            f,
+            ") with CC {:02X}: expected {:02X} CC {:02X}, found {:02X} CC {:02X}",
+            self.cc, self.expected.0, self.expected.1, self.found.0, self.found.1
+        )
+    }
+}
+
+impl Error for AluMismatch {}
+
+/// Check every 8-bit ALU operation and [`daa`] on every input against the
+/// reference tables, returning the first disagreement.
+///
+/// The flags an operation leaves alone are checked too, since every flag is
+/// both set and clear in some starting CC. Only available with the
+/// `alu-verify` feature.
+pub fn verify() -> Result<(), AluMismatch> {
+    for (op, f, table, undefined) in BINARY {
+        verify_binary(op, f, table, undefined)?;
+    }
+    for (op, f, table, undefined) in UNARY {
+        verify_unary(op, f, table, undefined)?;
+    }
+    Ok(())
+}
+
+/// Check `f` against the deflated `table`, laid out as (result, CC) pairs
+/// in order of starting CC, then `a`, then `b`.
+pub(crate) fn verify_binary(
+    op: &'static str,
+    f: Binary,
+    table: &[u8],
+    undefined: u8,
+) -> Result<(), AluMismatch> {
+    let table = inflate(table);
+    assert_eq!(
+        table.len(),
+        CC_INPUTS.len() * 0x10000 * 2,
+        "{op} table size"
+    );
+    let mut entries = table.chunks_exact(2);
+    for cc in CC_INPUTS {
+        for a in 0..=0xFF {
+            for b in 0..=0xFF {
+                let entry = entries.next().unwrap();
+                check(op, a, Some(b), cc, entry, undefined, |flags| f(a, b, flags))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// As [`verify_binary`], for an operation on one operand.
+pub(crate) fn verify_unary(
+    op: &'static str,
+    f: Unary,
+    table: &[u8],
+    undefined: u8,
+) -> Result<(), AluMismatch> {
+    let table = inflate(table);
+    assert_eq!(table.len(), CC_INPUTS.len() * 0x100 * 2, "{op} table size");
+    let mut entries = table.chunks_exact(2);
+    for cc in CC_INPUTS {
+        for a in 0..=0xFF {
+            let entry = entries.next().unwrap();
+            check(op, a, None, cc, entry, undefined, |flags| f(a, flags))?;
+        }
+    }
+    Ok(())
+}
+
+fn check(
+    op: &'static str,
+    a: u8,
+    b: Option<u8>,
+    cc: u8,
+    entry: &[u8],
+    undefined: u8,
+    f: impl FnOnce(&mut ConditionCodes) -> u8,
+) -> Result<(), AluMismatch> {
+    let mut flags = ConditionCodes::from_byte(cc);
+    let result = f(&mut flags);
+    let expected = (entry[0], entry[1] & !undefined);
+    let found = (result, flags.to_byte() & !undefined);
+    if expected == found {
+        Ok(())
+    } else {
+        Err(AluMismatch {
+            op,
+            a,
+            b,
+            cc,
+            expected,
+            found,
+        })
+    }
+}
+
+fn inflate(table: &[u8]) -> Vec<u8> {
+    miniz_oxide::inflate::decompress_to_vec(table).expect("reference table is valid deflate")
+}
@@ -0,0 +1,258 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Best-effort stack backtraces, for post-mortem debugging.
+//!
+//! After a crash, such as a halt or an illegal opcode, [`StackWalker`]
+//! reads the hardware stack up from S and picks out the return addresses of
+//! subroutine calls and the frames interrupts stacked. Nothing marks where
+//! one frame ends and the next begins, so the walk is a guess: a return
+//! address counts only if a `JSR`, `BSR` or `LBSR` ends just before it, and
+//! any byte with the E flag set followed by a plausible PC counts as an
+//! interrupt frame. Limiting the walk to the program's code with
+//! [`StackWalker::code`] weeds out most false matches.
+//!
+//! `FIRQ` frames, which hold only CC and PC, cannot be told from data and
+//! are not reported.
+
+use std::fmt::{self, Write};
+use std::ops::RangeInclusive;
+
+use crate::cpu::{Cpu, CpuVariant};
+use crate::disasm::{self, Instruction};
+use crate::memory::Memory;
+use crate::registers::{ConditionCodes, Registers};
+use crate::symbols::SymbolTable;
+
+/// What a [`StackFrame`] was recognised as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameKind {
+    /// A return address pushed by this call instruction.
+    Call(Instruction),
+    /// An interrupt or SWI frame holding the entire state: the registers
+    /// when the interrupt was taken, with S as it was before stacking.
+    Interrupt(Registers),
+}
+
+/// One frame found on the stack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Where on the stack the frame starts.
+    pub sp: u16,
+    /// The address execution returns to.
+    pub pc: u16,
+    /// What the frame is.
+    pub kind: FrameKind,
+}
+
+/// The result of a [`StackWalker::walk`]: where the CPU stopped and the
+/// frames above it, innermost first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backtrace {
+    /// PC when the walk was taken.
+    pub pc: u16,
+    /// S when the walk was taken.
+    pub sp: u16,
+    /// Frames found, innermost first.
+    pub frames: Vec<StackFrame>,
+}
+
+impl Backtrace {
+    /// One line per frame, starting with the CPU's own PC.
+    pub fn text(&self) -> String {
+        self.render(None)
+    }
+
+    /// [`Self::text`] with addresses shown by label from `symbols`.
+    pub fn text_with(&self, symbols: &SymbolTable) -> String {
+        self.render(Some(symbols))
+    }
+
+    fn render(&self, symbols: Option<&SymbolTable>) -> String {
+        let at = |addr: u16| match symbols.and_then(|symbols| symbols.describe(addr)) {
+            Some(label) => format!("{addr:04X} {label:<16}"),
+            None => format!("{addr:04X} {:<16}", ""),
+        };
+        let mut out = format!("#0  {}  S={:04X}  stopped here\n", at(self.pc), self.sp);
+        for (i, frame) in self.frames.iter().enumerate() {
+            let what = match &frame.kind {
+                FrameKind::Call(call) => {
+                    let text = match symbols {
+                        Some(symbols) => call.text_with(symbols),
+                        None => call.text(),
+                    };
+                    format!("called by {text} at {:04X}", call.addr)
+                }
+                FrameKind::Interrupt(regs) => format!("interrupted, {regs}"),
+            };
+            let _ = writeln!(
+                out,
+                "#{:<2} {}  S={:04X}  {what}",
+                i + 1,
+                at(frame.pc),
+                frame.sp
+            );
+        }
+        out
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text())
+    }
+}
+
+/// Walks the hardware stack for a [`Backtrace`].
+///
+/// # Example
+/// ```
+/// use mc6809_core::backtrace::{FrameKind, StackWalker};
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0xBD, 0xE1, 0x00], 0xE000); // JSR $E100
+/// ram.load(&[0x8D, 0x00], 0xE100); // BSR $E102
+/// ram.load(&[0x01], 0xE102); // an illegal opcode
+/// ram.set_reset_vector(0xE000);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.registers_mut().s = 0x0800;
+/// for _ in 0..3 {
+///     cpu.step(&mut ram);
+/// }
+///
+/// let trace = StackWalker::new().code(0xE000..=0xFFFF).walk(&cpu, &mut ram);
+/// let returns: Vec<_> = trace.frames.iter().map(|frame| frame.pc).collect();
+/// assert_eq!(returns, [0xE102, 0xE003]);
+/// assert!(matches!(&trace.frames[1].kind, FrameKind::Call(call) if call.addr == 0xE000));
+/// ```
+#[derive(Clone, Debug)]
+pub struct StackWalker {
+    code: RangeInclusive<u16>,
+    limit: usize,
+}
+
+impl StackWalker {
+    /// A walker over the 256 bytes above S that takes any address as code.
+    pub fn new() -> Self {
+        Self {
+            code: 0x0000..=0xFFFF,
+            limit: 256,
+        }
+    }
+
+    /// Only take return addresses and interrupted PCs within `range`.
+    pub fn code(mut self, range: RangeInclusive<u16>) -> Self {
+        self.code = range;
+        self
+    }
+
+    /// Read at most `bytes` bytes of stack, stopping early at the end of
+    /// the address space.
+    pub fn limit(mut self, bytes: usize) -> Self {
+        self.limit = bytes;
+        self
+    }
+
+    /// Walk the stack of `cpu`, reading it and the code through `mem`.
+    ///
+    /// Reads go through [`Memory::read`], so a stack in memory with read
+    /// side effects is disturbed.
+    pub fn walk(&self, cpu: &Cpu, mem: &mut impl Memory) -> Backtrace {
+        let regs = cpu.registers();
+        let entire_len = if cpu.variant() == CpuVariant::Hd6309 && cpu.native_mode() {
+            14
+        } else {
+            12
+        };
+        let end = (regs.s as usize + self.limit).min(0x10000);
+        let mut frames = Vec::new();
+        let mut sp = regs.s as usize;
+        while sp + 2 <= end {
+            let addr = sp as u16;
+            let ret = mem.read_word(addr);
+            if let Some(call) = self.caller(mem, ret) {
+                frames.push(StackFrame {
+                    sp: addr,
+                    pc: call.next(),
+                    kind: FrameKind::Call(call),
+                });
+                sp += 2;
+            } else if sp + entire_len <= end
+                && let Some(regs) = self.interrupt(mem, addr, entire_len)
+            {
+                frames.push(StackFrame {
+                    sp: addr,
+                    pc: regs.pc,
+                    kind: FrameKind::Interrupt(regs),
+                });
+                sp += entire_len;
+            } else {
+                sp += 1;
+            }
+        }
+        Backtrace {
+            pc: regs.pc,
+            sp: regs.s,
+            frames,
+        }
+    }
+
+    /// The call instruction that would have pushed `ret`, if there is one.
+    fn caller(&self, mem: &mut impl Memory, ret: u16) -> Option<Instruction> {
+        if !self.code.contains(&ret) {
+            return None;
+        }
+        // BSR and JSR direct or indexed are two bytes long, JSR indexed with
+        // an offset three or four, and JSR extended and LBSR three.
+        (2..=4).find_map(|len| {
+            let call = disasm::disassemble(mem, ret.wrapping_sub(len));
+            let is_call = matches!(call.mnemonic.as_str(), "JSR" | "BSR" | "LBSR");
+            (is_call && call.next() == ret && self.code.contains(&call.addr)).then_some(call)
+        })
+    }
+
+    /// The registers of an entire-state frame of `len` bytes at `sp`, if
+    /// it looks like one.
+    fn interrupt(&self, mem: &mut impl Memory, sp: u16, len: usize) -> Option<Registers> {
+        let cc = ConditionCodes::from_byte(mem.read(sp));
+        if !cc.entire() {
+            return None;
+        }
+        // The HD6309's E and F come between B and DP.
+        let rest = sp.wrapping_add(len as u16 - 9);
+        let pc = mem.read_word(rest.wrapping_add(7));
+        if !self.code.contains(&pc) {
+            return None;
+        }
+        let mut regs = Registers::new();
+        regs.cc = cc;
+        regs.set_a(mem.read(sp.wrapping_add(1)));
+        regs.set_b(mem.read(sp.wrapping_add(2)));
+        regs.dp = mem.read(rest);
+        regs.x = mem.read_word(rest.wrapping_add(1));
+        regs.y = mem.read_word(rest.wrapping_add(3));
+        regs.u = mem.read_word(rest.wrapping_add(5));
+        regs.pc = pc;
+        regs.s = sp.wrapping_add(len as u16);
+        Some(regs)
+    }
+}
+
+impl Default for StackWalker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
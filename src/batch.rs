@@ -0,0 +1,170 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Running many independent CPU instances across threads.
+//!
+//! Intended for fuzzing and batch test-vector evaluation, where each instance
+//! owns its own [`Cpu`](crate::Cpu) and memory and needs a reproducible seed.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Identity of one instance in a batch, passed to the per-instance closure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instance {
+    /// Position of the instance in the batch, `0..count`.
+    pub index: usize,
+    /// Deterministic seed for the instance, see [`instance_seed`].
+    pub seed: u64,
+}
+
+/// Derive the seed of instance `index` from a batch-wide `base_seed`.
+///
+/// The result depends only on its arguments, so a batch can be reproduced
+/// instance by instance regardless of thread count or scheduling.
+pub fn instance_seed(base_seed: u64, index: usize) -> u64 {
+    // SplitMix64 finaliser over the combined input.
+    let mut z = base_seed
+        .wrapping_add(index as u64)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Small deterministic random number generator (SplitMix64) for
+/// reproducible runs.
+///
+/// Every source of randomness a seeded run needs (undefined register values,
+/// open-bus reads, device jitter) draws from one of these, so a run can be
+/// replayed exactly from its seed.
+///
+/// # Example
+/// ```
+/// use mc6809_core::batch::SeedRng;
+///
+/// let mut a = SeedRng::new(7);
+/// let mut b = SeedRng::new(7);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SeedRng(u64);
+
+impl SeedRng {
+    /// Create a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// The next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A random byte.
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    /// A random 16-bit word.
+    pub fn next_u16(&mut self) -> u16 {
+        (self.next_u64() >> 48) as u16
+    }
+
+    /// A random value in `0..=max`.
+    pub fn up_to(&mut self, max: u64) -> u64 {
+        match max.checked_add(1) {
+            Some(bound) => self.next_u64() % bound,
+            None => self.next_u64(),
+        }
+    }
+}
+
+impl Snapshot for SeedRng {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u64(self.0);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.0 = state.u64()?;
+        Ok(())
+    }
+}
+
+/// Run `count` instances of `run` on all available cores and return their
+/// results in index order.
+///
+/// `run` typically builds a CPU and memory, loads a program or test vector
+/// using [`Instance::seed`], runs it and returns whatever the caller wants to
+/// compare. Instances are handed out to worker threads one at a time, so
+/// uneven run times balance out.
+///
+/// # Example
+/// ```
+/// use mc6809_core::batch::run_batch;
+///
+/// let seeds = run_batch(4, 42, |instance| instance.seed);
+/// assert_eq!(seeds, run_batch(4, 42, |instance| instance.seed));
+/// ```
+pub fn run_batch<T, F>(count: usize, base_seed: u64, run: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Instance) -> T + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    run_batch_on(threads, count, base_seed, run)
+}
+
+/// [`run_batch`] with an explicit number of worker threads (at least one).
+pub fn run_batch_on<T, F>(threads: usize, count: usize, base_seed: u64, run: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Instance) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = threads.clamp(1, count.max(1));
+
+    let mut results: Vec<(usize, T)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= count {
+                            break done;
+                        }
+                        let seed = instance_seed(base_seed, index);
+                        done.push((index, run(Instance { index, seed })));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("batch instance panicked"))
+            .collect()
+    });
+
+    results.sort_unstable_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -0,0 +1,150 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Ready-made [`Memory`] implementations.
+
+use crate::cpu::VEC_RESET;
+use crate::memory::Memory;
+use crate::peripheral::Clocked;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+mod composite;
+mod memory_map;
+mod shadow;
+
+pub use composite::{CompositeBus, Device, DeviceId};
+pub use memory_map::{Bank, MemoryMap, Mmio, RegionId, WriteFault, WritePolicy};
+pub use shadow::{ShadowBus, UninitializedHook};
+
+/// A complete system bus: memory access plus peripheral timing.
+///
+/// Implemented for every type that is both [`Memory`] and [`Clocked`]; this
+/// is what a [`Machine`](crate::machine::Machine) drives.
+pub trait Bus: Memory + Clocked {}
+
+impl<T: Memory + Clocked> Bus for T {}
+
+/// 64KB of flat RAM covering the whole address space.
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, bus::FlatRam};
+///
+/// let mut mem = FlatRam::new();
+/// mem.load(&[0x86, 0x42], 0x0400); // LDA #$42
+/// mem.set_reset_vector(0x0400);
+///
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut mem);
+/// cpu.step(&mut mem);
+/// assert_eq!(cpu.registers().a(), 0x42);
+/// ```
+#[derive(Clone)]
+pub struct FlatRam {
+    mem: Box<[u8; 0x10000]>,
+}
+
+impl FlatRam {
+    /// Create 64KB of zeroed RAM.
+    pub fn new() -> Self {
+        Self {
+            mem: Box::new([0; 0x10000]),
+        }
+    }
+
+    /// Copy `data` into RAM starting at `base`.
+    ///
+    /// # Panics
+    /// Panics if `data` extends past the end of the 64KB address space.
+    pub fn load(&mut self, data: &[u8], base: u16) {
+        let start = base as usize;
+        let end = start + data.len();
+        assert!(end <= 0x10000, "data exceeds 64 KB address space");
+        self.mem[start..end].copy_from_slice(data);
+    }
+
+    /// Store `addr` big-endian in the interrupt vector at `vector`
+    /// (for example `0xFFF8` for IRQ).
+    pub fn set_vector(&mut self, vector: u16, addr: u16) {
+        self.write_word(vector, addr);
+    }
+
+    /// Point the reset vector at `addr`.
+    pub fn set_reset_vector(&mut self, addr: u16) {
+        self.set_vector(VEC_RESET, addr);
+    }
+
+    /// The whole address space as a slice, indexed by address.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.mem[..]
+    }
+
+    /// The whole address space as a mutable slice, indexed by address.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.mem[..]
+    }
+}
+
+impl Default for FlatRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clocked for FlatRam {}
+
+impl Snapshot for FlatRam {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.bytes(&self.mem[..]);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        state.bytes_into(&mut self.mem[..])
+    }
+}
+
+impl Memory for FlatRam {
+    #[inline]
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem[addr as usize]
+    }
+
+    #[inline]
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+
+    #[inline]
+    fn read_word(&mut self, addr: u16) -> u16 {
+        let lo = addr.wrapping_add(1);
+        u16::from_be_bytes([self.mem[addr as usize], self.mem[lo as usize]])
+    }
+
+    #[inline]
+    fn write_word(&mut self, addr: u16, val: u16) {
+        let [hi, lo] = val.to_be_bytes();
+        self.mem[addr as usize] = hi;
+        self.mem[addr.wrapping_add(1) as usize] = lo;
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        let start = addr as usize;
+        let first = buf.len().min(0x10000 - start);
+        buf[..first].copy_from_slice(&self.mem[start..start + first]);
+        // Wrap around to address 0 for the remainder.
+        for (i, byte) in buf[first..].iter_mut().enumerate() {
+            *byte = self.mem[i % 0x10000];
+        }
+    }
+}
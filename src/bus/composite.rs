@@ -0,0 +1,287 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Bus composed of a base memory and prioritised memory-mapped devices.
+
+use std::any::Any;
+
+use super::Mmio;
+use crate::memory::{BusFault, Memory};
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// A memory-mapped peripheral for a [`CompositeBus`]: register access through
+/// [`Mmio`], timing through [`Clocked`] and save states through [`Snapshot`].
+///
+/// Implemented for every type that implements all three.
+pub trait Device: Mmio + Clocked + Snapshot + Any {}
+
+impl<T: Mmio + Clocked + Snapshot + Any> Device for T {}
+
+/// Index of a device in a [`CompositeBus`], in the order devices were added.
+pub type DeviceId = usize;
+
+/// One address range decoded to a device.
+struct Mapping {
+    device: DeviceId,
+    start: u16,
+    len: usize,
+    priority: i32,
+}
+
+/// A [`Memory`] that overlays memory-mapped devices on a base memory.
+///
+/// Devices are added with [`Self::add_device`] and given one or more address
+/// ranges with [`Self::map`]. Where ranges overlap, the higher priority wins,
+/// and between equal priorities the range mapped last wins. Addresses not
+/// claimed by any device go to the base memory, typically a
+/// [`FlatRam`](super::FlatRam) or [`MemoryMap`](super::MemoryMap).
+///
+/// [`Clocked::tick`] on the bus ticks the base memory and every device and
+/// combines their signals, so the host loop needs a single call per step.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::{CompositeBus, Mmio};
+/// use mc6809_core::snapshot::{StateError, StateReader, StateWriter};
+/// use mc6809_core::{BusSignals, Clocked, FlatRam, Memory, Snapshot};
+///
+/// /// Raises IRQ after every 100 cycles until its register is read.
+/// struct Ticker { cycles: u64, pending: bool }
+///
+/// impl Mmio for Ticker {
+///     fn read(&mut self, _offset: u16) -> u8 {
+///         self.pending = false;
+///         0
+///     }
+///     fn write(&mut self, _offset: u16, _val: u8) {}
+/// }
+///
+/// impl Clocked for Ticker {
+///     fn tick(&mut self, cycles: u64) -> BusSignals {
+///         self.cycles += cycles;
+///         if self.cycles >= 100 {
+///             self.cycles -= 100;
+///             self.pending = true;
+///         }
+///         if self.pending { BusSignals::IRQ } else { BusSignals::default() }
+///     }
+/// }
+///
+/// impl Snapshot for Ticker {
+///     fn snapshot(&self, out: &mut StateWriter) {
+///         out.u64(self.cycles);
+///         out.bool(self.pending);
+///     }
+///     fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+///         self.cycles = state.u64()?;
+///         self.pending = state.bool()?;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut bus = CompositeBus::new(FlatRam::new());
+/// let ticker = bus.add_device(Ticker { cycles: 0, pending: false });
+/// bus.map(ticker, 0xFF00, 1, 0);
+///
+/// assert_eq!(bus.tick(150), BusSignals::IRQ);
+/// bus.read(0xFF00); // acknowledge
+/// assert_eq!(bus.tick(10), BusSignals::default());
+/// ```
+pub struct CompositeBus<B> {
+    base: B,
+    devices: Vec<Box<dyn Device + Send>>,
+    mappings: Vec<Mapping>,
+    /// Mapping index + 1 for every address; 0 means the base memory.
+    decode: Box<[u16]>,
+}
+
+impl<B: Memory> CompositeBus<B> {
+    /// Create a bus with no devices on top of `base`.
+    pub fn new(base: B) -> Self {
+        Self {
+            base,
+            devices: Vec::new(),
+            mappings: Vec::new(),
+            decode: vec![0; 0x10000].into_boxed_slice(),
+        }
+    }
+
+    /// Add a device without mapping it yet.
+    pub fn add_device(&mut self, device: impl Device + Send + 'static) -> DeviceId {
+        self.devices.push(Box::new(device));
+        self.devices.len() - 1
+    }
+
+    /// Decode `len` bytes from `start` to `device`, which sees offsets from
+    /// `start`. Higher `priority` wins where ranges overlap.
+    ///
+    /// # Panics
+    /// Panics if `device` is unknown, the range is empty or extends past
+    /// 0xFFFF.
+    pub fn map(&mut self, device: DeviceId, start: u16, len: usize, priority: i32) {
+        assert!(device < self.devices.len(), "unknown device {device}");
+        assert!(len > 0, "empty mapping at {start:04X}");
+        assert!(
+            start as usize + len <= 0x10000,
+            "mapping at {start:04X} extends past 0xFFFF"
+        );
+        self.mappings.push(Mapping {
+            device,
+            start,
+            len,
+            priority,
+        });
+        self.rebuild_decode();
+    }
+
+    /// The device that decodes `addr`, or `None` for the base memory.
+    pub fn device_at(&self, addr: u16) -> Option<DeviceId> {
+        let index = (self.decode[addr as usize] as usize).checked_sub(1)?;
+        Some(self.mappings[index].device)
+    }
+
+    /// Shared access to a device.
+    pub fn device(&self, id: DeviceId) -> Option<&(dyn Device + Send + 'static)> {
+        self.devices.get(id).map(|device| &**device)
+    }
+
+    /// Mutable access to a device.
+    pub fn device_mut(&mut self, id: DeviceId) -> Option<&mut (dyn Device + Send + 'static)> {
+        self.devices.get_mut(id).map(|device| &mut **device)
+    }
+
+    /// Mutable access to a device as its concrete type, or `None` if `id`
+    /// is unknown or names a device of another type.
+    pub fn device_as<T: Device>(&mut self, id: DeviceId) -> Option<&mut T> {
+        let device: &mut dyn Any = &mut **self.devices.get_mut(id)?;
+        device.downcast_mut()
+    }
+
+    /// Let device `id` master the bus: run `f` with the device and the bus,
+    /// as for a DMA controller moving data.
+    ///
+    /// The device is taken out of the bus while `f` runs, so its own ranges
+    /// read as `0xFF` and ignore writes. Returns `None` if `id` is unknown or names a
+    /// device of another type.
+    pub fn master<T: Device + Send, R>(
+        &mut self,
+        id: DeviceId,
+        f: impl FnOnce(&mut T, &mut Self) -> R,
+    ) -> Option<R> {
+        self.device_as::<T>(id)?;
+        let mut device = std::mem::replace(&mut self.devices[id], Box::new(Detached));
+        let device_any: &mut dyn Any = &mut *device;
+        let result = device_any.downcast_mut().map(|device| f(device, self));
+        self.devices[id] = device;
+        result
+    }
+
+    /// The base memory.
+    pub fn base(&self) -> &B {
+        &self.base
+    }
+
+    /// Mutable access to the base memory.
+    pub fn base_mut(&mut self) -> &mut B {
+        &mut self.base
+    }
+
+    fn rebuild_decode(&mut self) {
+        self.decode.fill(0);
+        let mut order: Vec<usize> = (0..self.mappings.len()).collect();
+        // Stable sort: among equal priorities the later mapping is filled last.
+        order.sort_by_key(|&index| self.mappings[index].priority);
+        for index in order {
+            let mapping = &self.mappings[index];
+            let start = mapping.start as usize;
+            self.decode[start..start + mapping.len].fill(index as u16 + 1);
+        }
+    }
+}
+
+/// Placeholder for a device taken out by [`CompositeBus::master`].
+struct Detached;
+
+impl Mmio for Detached {
+    fn read(&mut self, _offset: u16) -> u8 {
+        0xFF
+    }
+
+    fn write(&mut self, _offset: u16, _val: u8) {}
+}
+
+impl Clocked for Detached {}
+
+impl Snapshot for Detached {}
+
+impl<B: Memory> Memory for CompositeBus<B> {
+    fn read(&mut self, addr: u16) -> u8 {
+        match (self.decode[addr as usize] as usize).checked_sub(1) {
+            Some(index) => {
+                let mapping = &self.mappings[index];
+                self.devices[mapping.device].read(addr - mapping.start)
+            }
+            None => self.base.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        match (self.decode[addr as usize] as usize).checked_sub(1) {
+            Some(index) => {
+                let mapping = &self.mappings[index];
+                self.devices[mapping.device].write(addr - mapping.start, val)
+            }
+            None => self.base.write(addr, val),
+        }
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.base.take_fault()
+    }
+}
+
+impl<B: Clocked> Clocked for CompositeBus<B> {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        self.devices
+            .iter_mut()
+            .fold(self.base.tick(cycles), |signals, device| {
+                signals | device.tick(cycles)
+            })
+    }
+}
+
+/// Saves the base memory and then every device in the order they were added,
+/// each in its own [section](StateWriter::section). Mappings are
+/// configuration and are not saved.
+impl<B: Snapshot> Snapshot for CompositeBus<B> {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.base.snapshot(out);
+        out.u32(self.devices.len() as u32);
+        for device in &self.devices {
+            out.section(&**device);
+        }
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.base.restore(state)?;
+        if state.u32()? as usize != self.devices.len() {
+            return Err(StateError::Mismatch("device count"));
+        }
+        for device in &mut self.devices {
+            state.section(&mut **device)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,574 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Address-decoded memory map built from RAM, ROM and MMIO regions.
+
+use crate::batch::SeedRng;
+use crate::memory::{BusFault, Memory};
+use crate::peripheral::Clocked;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Memory-mapped I/O handler for a [`MemoryMap`] region.
+///
+/// Offsets are relative to the start of the region.
+pub trait Mmio: Send {
+    /// Read the register at `offset`.
+    fn read(&mut self, offset: u16) -> u8;
+
+    /// Write the register at `offset`.
+    fn write(&mut self, offset: u16, val: u8);
+}
+
+/// [`Mmio`] handler built from a pair of closures (see [`MemoryMap::mmio_fn`]).
+struct FnMmio<R, W> {
+    read: R,
+    write: W,
+}
+
+impl<R, W> Mmio for FnMmio<R, W>
+where
+    R: FnMut(u16) -> u8 + Send,
+    W: FnMut(u16, u8) + Send,
+{
+    fn read(&mut self, offset: u16) -> u8 {
+        (self.read)(offset)
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        (self.write)(offset, val)
+    }
+}
+
+/// Index of a region in a [`MemoryMap`], in the order regions were added.
+pub type RegionId = usize;
+
+/// What a [`MemoryMap`] does with a write to a read-only region.
+///
+/// The write itself is always dropped.
+pub enum WritePolicy {
+    /// Drop the write silently.
+    Ignore,
+    /// Drop the write and report it on stderr, or through `defmt` with the
+    /// `defmt` feature.
+    Log,
+    /// Drop the write and pass its address and value to the callback.
+    Callback(Box<dyn FnMut(u16, u8) + Send>),
+    /// Drop the write and latch it as a [`WriteFault`] for the host loop to
+    /// pick up with [`MemoryMap::take_write_fault`] and stop the CPU.
+    Trap,
+}
+
+/// A write to a read-only region latched by [`WritePolicy::Trap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WriteFault {
+    /// Region that was written.
+    pub region: RegionId,
+    /// Address written.
+    pub addr: u16,
+    /// Value that was dropped.
+    pub val: u8,
+}
+
+/// Most regions a map can hold.
+const MAX_REGIONS: usize = 255;
+
+/// Contents of one bank of a banked region (see [`MemoryMap::banked`]).
+pub enum Bank<'a> {
+    /// Zeroed RAM.
+    Ram,
+    /// ROM holding a copy of the data, padded with `0xFF` to the region
+    /// length. Writes to it are dropped.
+    Rom(&'a [u8]),
+}
+
+struct BankData {
+    data: Box<[u8]>,
+    rom: bool,
+}
+
+enum Backing {
+    Ram(Box<[u8]>),
+    Rom(Box<[u8]>),
+    Mmio(Box<dyn Mmio>),
+    Banked {
+        banks: Vec<BankData>,
+        selected: usize,
+    },
+}
+
+impl Backing {
+    /// Bytes currently decoded by the region; `None` for MMIO.
+    fn data(&self) -> Option<&[u8]> {
+        match self {
+            Backing::Ram(data) | Backing::Rom(data) => Some(data),
+            Backing::Mmio(_) => None,
+            Backing::Banked { banks, selected } => Some(&banks[*selected].data),
+        }
+    }
+
+    fn data_mut(&mut self) -> Option<&mut [u8]> {
+        match self {
+            Backing::Ram(data) | Backing::Rom(data) => Some(data),
+            Backing::Mmio(_) => None,
+            Backing::Banked { banks, selected } => Some(&mut banks[*selected].data),
+        }
+    }
+}
+
+struct Region {
+    start: u16,
+    /// Bytes of backing decoded before the region repeats.
+    len: usize,
+    /// Mask applied to the offset from `start`; narrower than 0xFFFF only for
+    /// mirrored regions (see [`MemoryMap::mirror`]).
+    mask: u16,
+    backing: Backing,
+    /// `Some` if the region is read-only.
+    protect: Option<WritePolicy>,
+}
+
+/// A [`Memory`] composed of RAM, ROM and MMIO regions.
+///
+/// Each region covers `len` bytes from `start`. Where regions overlap, the
+/// one added last wins. Reads from unmapped addresses return the open-bus
+/// value (`0xFF` unless changed with [`Self::unmapped_value`]) and writes to
+/// them are ignored.
+///
+/// ROM regions are read-only and drop writes silently; [`Self::on_write`]
+/// chooses another [`WritePolicy`], and can also make RAM or MMIO regions
+/// read-only.
+///
+/// A region can be repeated through a larger window with [`Self::mirror`].
+///
+/// A banked region ([`Self::banked`]) decodes one of several banks at a
+/// time, chosen with [`Self::select_bank`] or by writes to a latch address
+/// ([`Self::bank_latch`]).
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::MemoryMap;
+/// use mc6809_core::Memory;
+///
+/// let rom = [0x12; 0x2000];
+/// let mut map = MemoryMap::new()
+///     .ram(0x0000, 0x8000)
+///     .rom(0xE000, &rom)
+///     .mmio_fn(0xFF00, 0x04, |offset| offset as u8, |_, _| {});
+///
+/// map.write(0x1234, 0x56);
+/// assert_eq!(map.read(0x1234), 0x56);
+/// assert_eq!(map.read(0xE000), 0x12);
+/// assert_eq!(map.read(0xFF02), 0x02);
+/// assert_eq!(map.read(0x9000), 0xFF); // unmapped
+/// ```
+pub struct MemoryMap {
+    regions: Vec<Region>,
+    /// Region index + 1 for every address; 0 marks an unmapped address.
+    decode: Box<[u8]>,
+    unmapped: u8,
+    /// Source of open-bus values when they are seeded.
+    open_bus: Option<SeedRng>,
+    /// First write trapped by [`WritePolicy::Trap`] and not yet taken.
+    fault: Option<WriteFault>,
+    /// Latch addresses and the banked region each one selects for.
+    latches: Vec<(u16, RegionId)>,
+}
+
+impl MemoryMap {
+    /// Create a map with nothing mapped.
+    pub fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+            decode: vec![0; 0x10000].into_boxed_slice(),
+            unmapped: 0xFF,
+            open_bus: None,
+            fault: None,
+            latches: Vec::new(),
+        }
+    }
+
+    /// Add `len` bytes of zeroed RAM at `start`.
+    ///
+    /// # Panics
+    /// Panics if the region is empty, extends past 0xFFFF, or the map
+    /// already holds 255 regions. The same applies to every region kind.
+    pub fn ram(self, start: u16, len: usize) -> Self {
+        self.region(start, len, Backing::Ram(vec![0; len].into_boxed_slice()))
+    }
+
+    /// Add ROM at `start` holding a copy of `data`. Writes are ignored
+    /// unless [`Self::on_write`] says otherwise.
+    pub fn rom(self, start: u16, data: &[u8]) -> Self {
+        self.region(start, data.len(), Backing::Rom(data.into()))
+    }
+
+    /// Add `len` bytes of memory-mapped I/O at `start`, handled by `device`.
+    pub fn mmio(self, start: u16, len: usize, device: impl Mmio + 'static) -> Self {
+        self.region(start, len, Backing::Mmio(Box::new(device)))
+    }
+
+    /// Add `len` bytes of memory-mapped I/O at `start`, handled by a pair of
+    /// closures taking the offset into the region.
+    pub fn mmio_fn<R, W>(self, start: u16, len: usize, read: R, write: W) -> Self
+    where
+        R: FnMut(u16) -> u8 + Send + 'static,
+        W: FnMut(u16, u8) + Send + 'static,
+    {
+        self.mmio(start, len, FnMmio { read, write })
+    }
+
+    /// Add a `len`-byte region at `start` that decodes one of `banks` at a
+    /// time, starting with bank 0.
+    ///
+    /// # Panics
+    /// Panics if `banks` is empty or a ROM bank is longer than `len`.
+    pub fn banked<'a>(
+        self,
+        start: u16,
+        len: usize,
+        banks: impl IntoIterator<Item = Bank<'a>>,
+    ) -> Self {
+        let banks: Vec<BankData> = banks
+            .into_iter()
+            .map(|bank| match bank {
+                Bank::Ram => BankData {
+                    data: vec![0; len].into_boxed_slice(),
+                    rom: false,
+                },
+                Bank::Rom(rom) => {
+                    assert!(rom.len() <= len, "ROM bank longer than its region");
+                    let mut data = vec![0xFF; len];
+                    data[..rom.len()].copy_from_slice(rom);
+                    BankData {
+                        data: data.into_boxed_slice(),
+                        rom: true,
+                    }
+                }
+            })
+            .collect();
+        assert!(
+            !banks.is_empty(),
+            "banked region at {start:04X} has no banks"
+        );
+        self.region(start, len, Backing::Banked { banks, selected: 0 })
+    }
+
+    /// Let writes to `addr` select the bank of the most recently added
+    /// region, which must be banked. The value written, modulo the number of
+    /// banks, is the new bank; the write is not passed on to whatever region
+    /// decodes `addr`.
+    ///
+    /// # Panics
+    /// Panics if the most recently added region is not banked.
+    pub fn bank_latch(mut self, addr: u16) -> Self {
+        let id = self.regions.len().wrapping_sub(1);
+        assert!(
+            matches!(
+                self.regions.get(id).map(|r| &r.backing),
+                Some(Backing::Banked { .. })
+            ),
+            "bank latch needs a banked region"
+        );
+        self.latches.push((addr, id));
+        self
+    }
+
+    /// Select the bank decoded by a banked region. Out-of-range banks wrap
+    /// modulo the number of banks; other region kinds are left alone.
+    pub fn select_bank(&mut self, id: RegionId, bank: usize) {
+        if let Some(Region {
+            backing: Backing::Banked { banks, selected },
+            ..
+        }) = self.regions.get_mut(id)
+        {
+            *selected = bank % banks.len();
+        }
+    }
+
+    /// The bank currently decoded by a banked region.
+    pub fn selected_bank(&self, id: RegionId) -> Option<usize> {
+        match self.regions.get(id)?.backing {
+            Backing::Banked { selected, .. } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// Mutable contents of one bank of a banked region, selected or not.
+    /// Writes made here bypass ROM protection.
+    pub fn bank_data_mut(&mut self, id: RegionId, bank: usize) -> Option<&mut [u8]> {
+        match &mut self.regions.get_mut(id)?.backing {
+            Backing::Banked { banks, .. } => Some(&mut banks.get_mut(bank)?.data),
+            _ => None,
+        }
+    }
+
+    /// Repeat the most recently added region through `window` bytes from its
+    /// start, so for example 1KB of RAM answers throughout an 8KB window.
+    /// MMIO devices see the offset within the original region.
+    ///
+    /// # Panics
+    /// Panics if the region's length is not a power of two, `window` is
+    /// smaller than that length, or the window extends past 0xFFFF.
+    pub fn mirror(mut self, window: usize) -> Self {
+        let region = self.regions.last_mut().expect("no region to mirror");
+        let (start, len) = (region.start as usize, region.len);
+        assert!(
+            len.is_power_of_two(),
+            "mirrored region length must be a power of two"
+        );
+        assert!(window >= len, "mirror window smaller than its region");
+        assert!(
+            start + window <= 0x10000,
+            "mirror window extends past 0xFFFF"
+        );
+        region.mask = (len - 1) as u16;
+        let tag = self.regions.len() as u8;
+        self.decode[start..start + window].fill(tag);
+        self
+    }
+
+    /// Make the most recently added region read-only, handling writes to it
+    /// with `policy`.
+    ///
+    /// # Panics
+    /// Panics if no region has been added yet.
+    pub fn on_write(mut self, policy: WritePolicy) -> Self {
+        let region = self.regions.last_mut().expect("no region to protect");
+        region.protect = Some(policy);
+        self
+    }
+
+    /// Make a region read-only with `policy`, or writable with `None`.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a region of this map.
+    pub fn set_write_policy(&mut self, id: RegionId, policy: Option<WritePolicy>) {
+        self.regions[id].protect = policy;
+    }
+
+    /// Take the latched [`WritePolicy::Trap`] fault, if any.
+    ///
+    /// Only the first trapped write is kept until it is taken.
+    pub fn take_write_fault(&mut self) -> Option<WriteFault> {
+        self.fault.take()
+    }
+
+    /// Value returned by reads from unmapped addresses.
+    pub fn unmapped_value(mut self, val: u8) -> Self {
+        self.unmapped = val;
+        self
+    }
+
+    /// Return pseudo-random values derived from `seed` for reads from
+    /// unmapped addresses, so floating-bus behaviour replays exactly from
+    /// the seed. Overrides [`Self::unmapped_value`].
+    pub fn open_bus_seed(mut self, seed: u64) -> Self {
+        self.open_bus = Some(SeedRng::new(seed));
+        self
+    }
+
+    /// The region that decodes `addr`, if any.
+    pub fn region_at(&self, addr: u16) -> Option<RegionId> {
+        (self.decode[addr as usize] as usize).checked_sub(1)
+    }
+
+    /// Contents of a RAM or ROM region, or the selected bank of a banked
+    /// region; `None` for MMIO or an unknown id.
+    pub fn region_data(&self, id: RegionId) -> Option<&[u8]> {
+        self.regions.get(id)?.backing.data()
+    }
+
+    /// Mutable form of [`Self::region_data`]. Writes made here bypass ROM
+    /// protection.
+    pub fn region_data_mut(&mut self, id: RegionId) -> Option<&mut [u8]> {
+        self.regions.get_mut(id)?.backing.data_mut()
+    }
+
+    /// Copy `data` into the RAM or ROM backing the addresses from `base`,
+    /// bypassing ROM protection. Banked regions receive it in their selected
+    /// bank.
+    ///
+    /// # Panics
+    /// Panics if `data` extends past 0xFFFF or touches an address that is
+    /// unmapped or decoded as MMIO.
+    pub fn load(&mut self, data: &[u8], base: u16) {
+        assert!(
+            base as usize + data.len() <= 0x10000,
+            "data exceeds 64 KB address space"
+        );
+        for (i, &byte) in data.iter().enumerate() {
+            let addr = base + i as u16;
+            let slot = self
+                .region_at(addr)
+                .and_then(|id| self.backing_byte(id, addr));
+            match slot {
+                Some(slot) => *slot = byte,
+                None => panic!("address {addr:04X} is not backed by RAM or ROM"),
+            }
+        }
+    }
+
+    fn region(mut self, start: u16, len: usize, backing: Backing) -> Self {
+        assert!(len > 0, "empty region at {start:04X}");
+        assert!(
+            start as usize + len <= 0x10000,
+            "region at {start:04X} extends past 0xFFFF"
+        );
+        assert!(self.regions.len() < MAX_REGIONS, "too many regions");
+        let protect = matches!(backing, Backing::Rom(_)).then_some(WritePolicy::Ignore);
+        self.regions.push(Region {
+            start,
+            len,
+            mask: 0xFFFF,
+            backing,
+            protect,
+        });
+        let tag = self.regions.len() as u8;
+        self.decode[start as usize..start as usize + len].fill(tag);
+        self
+    }
+
+    fn backing_byte(&mut self, id: RegionId, addr: u16) -> Option<&mut u8> {
+        let region = &mut self.regions[id];
+        let offset = ((addr - region.start) & region.mask) as usize;
+        region.backing.data_mut()?.get_mut(offset)
+    }
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clocked for MemoryMap {}
+
+/// Saves RAM contents, bank selections and the seeded open-bus generator.
+/// ROM is configuration and is not saved, and neither is the state of MMIO
+/// handlers: put stateful devices on a
+/// [`CompositeBus`](super::CompositeBus) to have them saved.
+impl Snapshot for MemoryMap {
+    fn snapshot(&self, out: &mut StateWriter) {
+        for region in &self.regions {
+            match &region.backing {
+                Backing::Ram(data) => out.bytes(data),
+                Backing::Rom(_) | Backing::Mmio(_) => {}
+                Backing::Banked { banks, selected } => {
+                    out.u32(*selected as u32);
+                    for bank in banks.iter().filter(|bank| !bank.rom) {
+                        out.bytes(&bank.data);
+                    }
+                }
+            }
+        }
+        out.bool(self.open_bus.is_some());
+        if let Some(rng) = &self.open_bus {
+            rng.snapshot(out);
+        }
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for region in &mut self.regions {
+            match &mut region.backing {
+                Backing::Ram(data) => state.bytes_into(data)?,
+                Backing::Rom(_) | Backing::Mmio(_) => {}
+                Backing::Banked { banks, selected } => {
+                    let bank = state.u32()? as usize;
+                    if bank >= banks.len() {
+                        return Err(StateError::Mismatch("bank number"));
+                    }
+                    *selected = bank;
+                    for bank in banks.iter_mut().filter(|bank| !bank.rom) {
+                        state.bytes_into(&mut bank.data)?;
+                    }
+                }
+            }
+        }
+        if state.bool()? != self.open_bus.is_some() {
+            return Err(StateError::Mismatch("open-bus seeding"));
+        }
+        if let Some(rng) = &mut self.open_bus {
+            rng.restore(state)?;
+        }
+        Ok(())
+    }
+}
+
+impl Memory for MemoryMap {
+    fn read(&mut self, addr: u16) -> u8 {
+        let Some(id) = self.region_at(addr) else {
+            return match &mut self.open_bus {
+                Some(rng) => rng.next_u8(),
+                None => self.unmapped,
+            };
+        };
+        let region = &mut self.regions[id];
+        let offset = (addr - region.start) & region.mask;
+        match &mut region.backing {
+            Backing::Ram(data) | Backing::Rom(data) => data[offset as usize],
+            Backing::Mmio(device) => device.read(offset),
+            Backing::Banked { banks, selected } => banks[*selected].data[offset as usize],
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        if let Some(&(_, id)) = self.latches.iter().find(|(latch, _)| *latch == addr) {
+            self.select_bank(id, val as usize);
+            return;
+        }
+        let Some(id) = self.region_at(addr) else {
+            return;
+        };
+        let region = &mut self.regions[id];
+        let offset = (addr - region.start) & region.mask;
+        match (&mut region.protect, &mut region.backing) {
+            (None, Backing::Ram(data) | Backing::Rom(data)) => data[offset as usize] = val,
+            (None, Backing::Mmio(device)) => device.write(offset, val),
+            (None, Backing::Banked { banks, selected }) => {
+                let bank = &mut banks[*selected];
+                if !bank.rom {
+                    bank.data[offset as usize] = val;
+                }
+            }
+            (Some(WritePolicy::Ignore), _) => {}
+            (Some(WritePolicy::Log), _) => {
+                #[cfg(feature = "defmt")]
+                defmt::warn!(
+                    "write to read-only {=u16:04X} = {=u8:02X} ignored",
+                    addr,
+                    val
+                );
+                #[cfg(not(feature = "defmt"))]
+                eprintln!("write to read-only {addr:04X} = {val:02X} ignored");
+            }
+            (Some(WritePolicy::Callback(callback)), _) => callback(addr, val),
+            (Some(WritePolicy::Trap), _) => {
+                self.fault.get_or_insert(WriteFault {
+                    region: id,
+                    addr,
+                    val,
+                });
+            }
+        }
+    }
+
+    /// Reports the latched [`WritePolicy::Trap`] fault, as
+    /// [`take_write_fault`](MemoryMap::take_write_fault) does.
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.take_write_fault().map(|fault| BusFault {
+            addr: fault.addr,
+            write: Some(fault.val),
+        })
+    }
+}
@@ -0,0 +1,221 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Bus wrapper that reports reads of memory nothing has written.
+
+use crate::memory::{BusFault, Memory};
+use crate::peripheral::{BusSignals, Clocked};
+
+/// Callback for [`ShadowBus::on_uninitialized`], given the address read and
+/// the PC of the instruction that read it.
+pub type UninitializedHook = Box<dyn FnMut(u16, u16) + Send>;
+
+/// One bit per address.
+type Bitmap = Box<[u64; 0x10000 / 64]>;
+
+fn bit(map: &Bitmap, addr: u16) -> bool {
+    map[addr as usize / 64] & (1 << (addr % 64)) != 0
+}
+
+fn set_bit(map: &mut Bitmap, addr: u16) {
+    map[addr as usize / 64] |= 1 << (addr % 64);
+}
+
+/// A [`Memory`] wrapper that tracks which bytes have been written and
+/// reports reads of the rest, like Valgrind's uninitialised-value check.
+///
+/// Every write through the wrapper marks its address as initialised;
+/// ranges declared with [`Self::rom`] count as initialised from the start,
+/// which also suits memory-mapped I/O. The first read of any other address
+/// calls the [`Self::on_uninitialized`] callback with the address and the
+/// PC of the reading instruction; later reads of the same address are not
+/// reported again until [`Self::clear`].
+///
+/// The bus cannot see the CPU, so the PC it reports is the one last given
+/// to [`Self::set_pc`]. Keep it current from a
+/// [`Machine`](crate::machine::Machine) step hook, as in the example.
+/// Program images loaded through the wrapper count as written; load them
+/// through [`Self::inner_mut`] to have the program's own data checked too.
+///
+/// # Example
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use mc6809_core::bus::ShadowBus;
+/// use mc6809_core::machine::Machine;
+/// use mc6809_core::{FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0xB6, 0x20, 0x00], 0xE000); // LDA $2000
+/// ram.set_reset_vector(0xE000);
+///
+/// let reads = Arc::new(Mutex::new(Vec::new()));
+/// let log = reads.clone();
+/// let mut bus = ShadowBus::new(ram).rom(0xE000, 0x2000);
+/// bus.on_uninitialized(Some(Box::new(move |addr, pc| {
+///     log.lock().unwrap().push((addr, pc));
+/// })));
+///
+/// let mut machine = Machine::new(bus);
+/// let pc = machine.cpu().registers().pc;
+/// machine.bus_mut().set_pc(pc);
+/// machine.add_step_hook(|cpu, bus: &mut ShadowBus<FlatRam>, _| {
+///     bus.set_pc(cpu.registers().pc)
+/// });
+/// machine.step();
+/// assert_eq!(*reads.lock().unwrap(), [(0x2000, 0xE000)]);
+/// ```
+pub struct ShadowBus<B> {
+    inner: B,
+    written: Bitmap,
+    reported: Bitmap,
+    roms: Vec<(u16, usize)>,
+    pc: u16,
+    hook: Option<UninitializedHook>,
+}
+
+impl<B: Memory> ShadowBus<B> {
+    /// Wrap `inner` with nothing yet written.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            written: Box::new([0; 0x10000 / 64]),
+            reported: Box::new([0; 0x10000 / 64]),
+            roms: Vec::new(),
+            pc: 0,
+            hook: None,
+        }
+    }
+
+    /// Declare `len` bytes from `start` as ROM, never reported.
+    ///
+    /// # Panics
+    /// Panics if the range extends past 0xFFFF.
+    pub fn rom(mut self, start: u16, len: usize) -> Self {
+        assert!(
+            start as usize + len <= 0x10000,
+            "ROM at {start:04X} extends past 0xFFFF"
+        );
+        self.roms.push((start, len));
+        self
+    }
+
+    /// Call `hook` with the address and PC of each first read of an
+    /// uninitialised byte, or stop reporting with `None`.
+    pub fn on_uninitialized(&mut self, hook: Option<UninitializedHook>) {
+        self.hook = hook;
+    }
+
+    /// Set the PC reported for the reads that follow.
+    pub fn set_pc(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    /// The PC reported for reads.
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    /// Whether `addr` has been written, or lies in a ROM range.
+    pub fn is_initialized(&self, addr: u16) -> bool {
+        bit(&self.written, addr) || self.in_rom(addr)
+    }
+
+    /// Mark `len` bytes from `start` as written without writing them,
+    /// wrapping at the end of the address space.
+    pub fn mark_written(&mut self, start: u16, len: usize) {
+        for i in 0..len.min(0x10000) {
+            set_bit(&mut self.written, start.wrapping_add(i as u16));
+        }
+    }
+
+    /// Forget every write and report, as after a power-on reset.
+    pub fn clear(&mut self) {
+        self.written.fill(0);
+        self.reported.fill(0);
+    }
+
+    /// The wrapped memory.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// The wrapped memory, for host access that is neither tracked nor
+    /// checked.
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Unwrap the memory.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    fn in_rom(&self, addr: u16) -> bool {
+        self.roms
+            .iter()
+            .any(|&(start, len)| (addr.wrapping_sub(start) as usize) < len)
+    }
+
+    fn check(&mut self, addr: u16) {
+        if bit(&self.written, addr) || bit(&self.reported, addr) || self.in_rom(addr) {
+            return;
+        }
+        set_bit(&mut self.reported, addr);
+        if let Some(hook) = &mut self.hook {
+            hook(addr, self.pc);
+        }
+    }
+}
+
+impl<B: Memory> Memory for ShadowBus<B> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.check(addr);
+        self.inner.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        set_bit(&mut self.written, addr);
+        self.inner.write(addr, val);
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        self.check(addr);
+        self.check(addr.wrapping_add(1));
+        self.inner.read_word(addr)
+    }
+
+    fn write_word(&mut self, addr: u16, val: u16) {
+        set_bit(&mut self.written, addr);
+        set_bit(&mut self.written, addr.wrapping_add(1));
+        self.inner.write_word(addr, val);
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        for i in 0..buf.len() {
+            self.check(addr.wrapping_add(i as u16));
+        }
+        self.inner.read_slice(addr, buf);
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.inner.take_fault()
+    }
+}
+
+impl<B: Clocked> Clocked for ShadowBus<B> {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        self.inner.tick(cycles)
+    }
+}
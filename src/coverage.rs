@@ -0,0 +1,142 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Execution coverage: which addresses instructions started at.
+//!
+//! With the `hooks` feature, [`Cpu::set_coverage`](crate::Cpu::set_coverage)
+//! makes the CPU record the address of every instruction it starts in a
+//! [`Coverage`] map. Maps from
+//! separate runs can be merged, counted over a ROM's address range and
+//! saved as a bitmap to combine across a whole test campaign.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Size of [`Coverage::to_bytes`] output: one bit per address.
+pub const COVERAGE_BYTES: usize = 0x10000 / 8;
+
+/// A set of addresses, one bit each, where instructions started.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "hooks")] {
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// // BEQ +2; NOP; NOP; NOP
+/// ram.load(&[0x27, 0x02, 0x12, 0x12, 0x12], 0x0400);
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.set_coverage(true);
+/// cpu.step(&mut ram);
+/// cpu.step(&mut ram);
+///
+/// let coverage = cpu.coverage().unwrap();
+/// assert_eq!(coverage.iter().collect::<Vec<_>>(), [0x0400, 0x0402]);
+/// assert_eq!(coverage.count_in(0x0400..=0x0404), 2);
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Coverage {
+    bits: Box<[u64]>,
+}
+
+impl Coverage {
+    /// An empty map.
+    pub fn new() -> Self {
+        Self {
+            bits: vec![0; 0x10000 / 64].into_boxed_slice(),
+        }
+    }
+
+    /// Record an instruction start at `addr`.
+    pub fn insert(&mut self, addr: u16) {
+        self.bits[addr as usize / 64] |= 1 << (addr % 64);
+    }
+
+    /// Whether an instruction started at `addr`.
+    pub fn contains(&self, addr: u16) -> bool {
+        self.bits[addr as usize / 64] & (1 << (addr % 64)) != 0
+    }
+
+    /// Number of addresses recorded.
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// Number of addresses recorded within `range`.
+    pub fn count_in(&self, range: RangeInclusive<u16>) -> usize {
+        range.filter(|&addr| self.contains(addr)).count()
+    }
+
+    /// Forget every address.
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    /// Add every address recorded in `other`.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other;
+        }
+    }
+
+    /// The recorded addresses, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..=u16::MAX).filter(|&addr| self.contains(addr))
+    }
+
+    /// The map as [`COVERAGE_BYTES`] bytes: bit `n` (LSB first) of byte `k`
+    /// is address `8 * k + n`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bits
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    /// Read a map saved with [`Self::to_bytes`], or `None` if `data` is not
+    /// [`COVERAGE_BYTES`] long.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() != COVERAGE_BYTES {
+            return None;
+        }
+        let bits = data
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Some(Self { bits })
+    }
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Coverage({} addresses)", self.len())
+    }
+}
@@ -12,13 +12,50 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
-use crate::memory::Memory;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::batch::SeedRng;
+#[cfg(feature = "hooks")]
+use crate::coverage::Coverage;
+use crate::diag;
+use crate::memory::{BusFault, Memory};
 use crate::peripheral::BusSignals;
-use crate::registers::Registers;
+#[cfg(feature = "hooks")]
+use crate::profile::Profile;
+use crate::registers::{
+    CC_F, CC_I, ConditionCodes, FlagDelta, Hd6309Registers, MD_FIRQ_IRQ, MD_IL, Registers,
+};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+use crate::stats::InterruptSource;
+#[cfg(feature = "hooks")]
+use crate::stats::{InterruptStats, OpcodeStats};
+#[cfg(feature = "hooks")]
+use crate::stats::{RunRecorder, RunStats};
 
+mod block_cache;
+mod code_watch;
+mod config;
+mod control;
+mod decode_cache;
 mod opcodes;
+mod preview;
+mod state;
+mod watchpoint;
 
+use block_cache::{Block, BlockCache, Insn, MAX_BLOCK_INSNS, MAX_INSN_LEN};
+use code_watch::CodeWatch;
+pub use code_watch::{CodeWrite, CodeWriteHook};
+pub use config::{CpuConfig, IllegalOpcodeMode};
+pub use control::{ControlHandle, InterruptHandle};
+use decode_cache::DecodeCache;
 pub use opcodes::instruction_cycles;
+use opcodes::{Page, ends_block};
+pub use state::CpuState;
+use watchpoint::WatchpointMemory;
 
 // ---------------------------------------------------------------------------
 // Interrupt vector addresses
@@ -31,6 +68,13 @@ pub const VEC_IRQ: u16 = 0xFFF8;
 pub const VEC_FIRQ: u16 = 0xFFF6;
 pub const VEC_SWI2: u16 = 0xFFF4;
 pub const VEC_SWI3: u16 = 0xFFF2;
+pub const VEC_TRAP: u16 = 0xFFF0;
+
+/// Most consecutive cycles the CPU gives to a DMA master before taking the
+/// bus back for one refresh cycle.
+const MAX_DMA_CYCLES: u8 = 14;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
 
 // ---------------------------------------------------------------------------
 // CPU state
@@ -40,16 +84,60 @@ pub const VEC_SWI3: u16 = 0xFFF2;
 pub struct Cpu {
     /// Programmer-visible registers.
     reg: Registers,
+    /// Registers only the HD6309 has.
+    pub(crate) reg6309: Hd6309Registers,
+    /// Which processor is emulated.
+    pub(crate) variant: CpuVariant,
+    /// An HD6309 TFM is part way through its transfer; the next byte skips
+    /// the set-up cycles.
+    tfm: bool,
     /// Total elapsed cycles since reset.
     cycles: u64,
+    /// Instructions started since reset.
+    instructions: u64,
+    /// Where the last [`Self::run_budgeted`] budget ended, on `cycles`.
+    budget_end: Option<u64>,
+    /// Cycles of the current step still to be clocked by [`Self::tick`].
+    tick_left: u64,
     /// CPU execution has been explicitly halted by an instruction.
     halted: bool,
     /// Sticky status bit set when an illegal opcode is executed.
     illegal: bool,
+    /// What executing an illegal opcode does beyond setting `illegal`.
+    illegal_policy: IllegalOpcodePolicy,
+    /// Address of the first illegal opcode latched by
+    /// [`IllegalOpcodePolicy::Trap`], for [`Self::try_step`].
+    illegal_fault: Option<u16>,
+    /// Addresses [`Self::try_step`] stops at before executing.
+    breakpoints: BTreeSet<u16>,
+    /// Breakpoint just reported, which the next `try_step` runs past.
+    break_resume: Option<u16>,
+    /// Addresses whose writes stop [`Self::run_until_stop`].
+    watchpoints: BTreeSet<u16>,
+    /// Run the undocumented opcodes that are illegal by default.
+    undocumented_opcodes: bool,
+    /// How TFR/EXG treat mixed sizes and undefined register codes.
+    transfer_quirks: TransferQuirks,
+    /// How SUB, SBC, CMP and NEG affect H.
+    half_carry_quirks: HalfCarryQuirks,
+    /// Clock rate, in cycles per second, for converting to wall time.
+    clock_hz: u64,
+    /// What reset leaves in the registers it does not define.
+    reset_state: ResetState,
+    /// Where reset, interrupts and SWI fetch their vectors.
+    vectors: VectorTable,
+    /// Valid S and U ranges and what to do outside them.
+    stack_monitor: Option<StackMonitor>,
+    /// First violation latched by [`StackAction::Trap`], for
+    /// [`Self::try_step`].
+    stack_fault: Option<StackViolation>,
 
     // ---- interrupt state ----
     /// NMI is armed (becomes true after first write to S).
     nmi_armed: bool,
+    /// Interrupt line the last interrupt check serviced, for
+    /// [`Self::step_info`].
+    interrupt_taken: Option<InterruptSource>,
     /// Pending interrupt lines: `BusSignals::NMI | BusSignals::FIRQ | BusSignals::IRQ`.
     ///
     /// `NMI` is an edge latch (set externally, cleared when serviced).
@@ -57,40 +145,749 @@ pub struct Cpu {
     /// (via for example ['apply_signals`](Self::apply_signals) or
     /// [`set_irq`](Self::set_irq) / [`set_firq`](Self::set_firq)) clears them.
     int_lines: BusSignals,
+    /// Lines shared with [`InterruptHandle`]s (see
+    /// [`Self::interrupt_handle`]).
+    interrupt_handle: Option<InterruptHandle>,
+    /// IRQ and FIRQ levels last sampled from `interrupt_handle`.
+    handle_lines: BusSignals,
     /// CWAI: entire state already pushed, waiting for a serviceable interrupt.
     cwai: bool,
     /// SYNC: waiting for any interrupt edge.
     sync: bool,
+    /// DMA/BREQ pin level.
+    dma_breq: bool,
+    /// Consecutive cycles the bus has been granted to a DMA master.
+    dma_stolen: u8,
+    /// The last step handed the bus to a DMA master.
+    bus_granted: bool,
+
+    /// Optional pre-decoded instruction cache (see [`Self::set_decode_cache`]).
+    decode_cache: Option<Box<DecodeCache>>,
+    /// Optional straight-line block cache (see [`Self::set_block_cache`]).
+    block_cache: Option<Box<BlockCache>>,
+    /// Optional record of executed bytes (see [`Self::set_code_write_hook`]).
+    code_watch: Option<Box<CodeWatch>>,
+    /// Optional map of instruction start addresses (see [`Self::set_coverage`]).
+    #[cfg(feature = "hooks")]
+    coverage: Option<Box<Coverage>>,
+    /// Optional per-opcode execution counts (see [`Self::set_opcode_stats`]).
+    #[cfg(feature = "hooks")]
+    opcode_stats: Option<Box<OpcodeStats>>,
+    /// Optional cycle profile by subroutine (see [`Self::set_profiler`]).
+    #[cfg(feature = "hooks")]
+    profile: Option<Box<Profile>>,
+    /// Optional interrupt counts and timings (see
+    /// [`Self::set_interrupt_stats`]).
+    #[cfg(feature = "hooks")]
+    interrupt_stats: Option<Box<InterruptStats>>,
+    /// Summary of the run in progress (see [`Self::run_with_stats`]).
+    #[cfg(feature = "hooks")]
+    run_stats: Option<Box<RunRecorder>>,
+    /// Optional record of the instruction each step executes (see
+    /// [`Self::set_last_instruction`]).
+    last_insn: Option<Box<LastRecorder>>,
+
+    /// Host callback offered each SWI-family instruction (see [`Self::set_swi_hook`]).
+    swi_hook: Option<SwiHook>,
+    /// Pause flag shared with [`ControlHandle`]s (see
+    /// [`Self::control_handle`]).
+    control: Option<ControlHandle>,
+
+    /// Host callback that decodes fetched opcode bytes (see
+    /// [`Self::set_opcode_translator`]).
+    opcode_translator: Option<OpcodeTranslator>,
+    /// Host callbacks run in place of built-in opcode handlers, keyed by
+    /// opcode with any page prefix in the high byte (see
+    /// [`Self::set_opcode_handler`]).
+    opcode_handlers: BTreeMap<u16, OpcodeHandler>,
+
+    /// Host callback run before each instruction (see [`Self::set_instruction_hook`]).
+    #[cfg(feature = "hooks")]
+    instruction_hook: Option<InstructionHook>,
+}
+
+/// Callback invoked before each instruction is fetched, with the registers
+/// (PC addresses the instruction) and the total cycle count.
+#[cfg(feature = "hooks")]
+pub type InstructionHook = Box<dyn FnMut(&Registers, u64) + Send>;
+
+/// Callback offered each SWI, SWI2 and SWI3 before it vectors (see
+/// [`Cpu::set_swi_hook`]), with the instruction, the registers (PC already
+/// past it, nothing stacked yet) and memory.
+pub type SwiHook = Box<dyn FnMut(Swi, &mut Registers, &mut dyn Memory) -> SwiAction + Send>;
+
+/// Callback that turns each fetched opcode byte into the opcode to execute
+/// (see [`Cpu::set_opcode_translator`]), given the address it was fetched
+/// from, the byte read there and which opcode byte of the instruction it is.
+pub type OpcodeTranslator = Box<dyn FnMut(u16, u8, OpcodeFetch) -> u8 + Send>;
+
+/// Callback run in place of an opcode's built-in handler (see
+/// [`Cpu::set_opcode_handler`]), with the registers (PC already past the
+/// prefix and opcode, at any operands) and memory. Returns the cycles the
+/// instruction took.
+pub type OpcodeHandler = Box<dyn FnMut(&mut Registers, &mut dyn Memory) -> u8 + Send>;
+
+/// Opcode byte offered to an [`OpcodeTranslator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpcodeFetch {
+    /// The first byte of an instruction: a page 0 opcode or a page prefix.
+    First,
+    /// The opcode after a 0x10 prefix.
+    Page1,
+    /// The opcode after a 0x11 prefix.
+    Page2,
+}
+
+/// Software interrupt instruction offered to a [`SwiHook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Swi {
+    /// SWI (0x3F).
+    Swi,
+    /// SWI2 (0x10 0x3F, or the undocumented 0x10 0x3E).
+    Swi2,
+    /// SWI3 (0x11 0x3F).
+    Swi3,
+}
+
+/// What a [`SwiHook`] did with the instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwiAction {
+    /// The hook handled the call natively: nothing is stacked, no vector is
+    /// taken and execution continues at PC as the hook left it.
+    Serviced,
+    /// Carry on with the normal stacking and vectoring.
+    Vector,
+}
+
+/// Callback invoked by [`IllegalOpcodePolicy::Callback`] with the registers
+/// (PC already past the opcode) and the address of the illegal instruction,
+/// prefix included.
+pub type IllegalOpcodeHook = Box<dyn FnMut(&mut Registers, u16) + Send>;
+
+/// Processor emulated by a [`Cpu`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CpuVariant {
+    /// The Motorola MC6809.
+    #[default]
+    Mc6809,
+    /// The Hitachi HD6309: the 6809 instruction set plus the 6309
+    /// extensions, with the E, F, W, V, MD and zero registers (see
+    /// [`Cpu::hd6309_registers`]). The 6809's undocumented opcodes are
+    /// illegal on the 6309, and by default trap (see
+    /// [`IllegalOpcodePolicy::Hardware`]). Setting the native bit of MD (`LDMD #$01`)
+    /// selects native mode: faster cycle counts, and E and F in the
+    /// entire-state stack frame of interrupts, SWI and CWAI.
+    Hd6309,
+}
+
+/// How TFR and EXG move values between registers of different sizes.
+///
+/// In both modes undefined register codes (6, 7 and C to F) read as all ones
+/// and ignore writes; codes below 8 are 16-bit and the rest 8-bit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransferQuirks {
+    /// A mixed 8/16-bit transfer writes all ones (0xFF or 0xFFFF) to the
+    /// destination.
+    #[default]
+    Simple,
+    /// As measured on silicon: a 16-bit source gives its low byte, an 8-bit
+    /// source gives `0xFF00 | value`, and CC or DP give the value in both
+    /// bytes.
+    Hardware,
+}
+
+/// How the 8-bit subtract-class instructions (SUB, SBC, CMP, NEG) affect H.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HalfCarryQuirks {
+    /// H is left unchanged.
+    #[default]
+    Simple,
+    /// As on silicon: H is the borrow out of bit 3, computed as for ADD from
+    /// the operands and the result.
+    Hardware,
+}
+
+/// What [`Cpu::reset`] leaves in D, X, Y, U, S and the E, H, N, Z, V and C
+/// flags, which the MC6809 does not define at reset. PC comes from the reset
+/// vector, DP is cleared and I and F are set in every case.
+#[derive(Clone, Debug, Default)]
+pub enum ResetState {
+    /// All zero.
+    #[default]
+    Zeroed,
+    /// Taken from these registers; their PC, DP, I and F are ignored.
+    Fixed(Registers),
+    /// Drawn from this generator, which advances with every reset, to catch
+    /// firmware that relies on uninitialized registers.
+    Random(SeedRng),
+}
+
+/// Addresses the CPU fetches each vector from.
+///
+/// The MC6809 always uses the top 16 bytes of memory; a different table lets
+/// a test harness keep its vectors elsewhere, or models hardware that remaps
+/// the vector fetches.
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, FlatRam, VectorTable};
+///
+/// let mut ram = FlatRam::new();
+/// ram.set_vector(0x7FFE, 0x0400); // reset vector, moved down 32K
+/// let mut cpu = Cpu::new();
+/// cpu.set_vectors(VectorTable::at(0x7FF0));
+/// cpu.reset(&mut ram);
+/// assert_eq!(cpu.registers().pc, 0x0400);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VectorTable {
+    /// Reset vector (and the undocumented RESET opcode).
+    pub reset: u16,
+    /// NMI vector.
+    pub nmi: u16,
+    /// SWI vector.
+    pub swi: u16,
+    /// IRQ vector.
+    pub irq: u16,
+    /// FIRQ vector (and the undocumented FIRQ opcode).
+    pub firq: u16,
+    /// SWI2 vector.
+    pub swi2: u16,
+    /// SWI3 vector.
+    pub swi3: u16,
+    /// HD6309 trap vector, for illegal instructions and division by zero.
+    pub trap: u16,
+}
+
+impl VectorTable {
+    /// The MC6809's own vectors, at 0xFFF2 to 0xFFFF, and the HD6309 trap
+    /// vector at 0xFFF0.
+    pub const STANDARD: Self = Self::at(0xFFF0);
+
+    /// The MC6809 layout moved to the 16 bytes starting at `base`, so that
+    /// `at(0xFFF0)` is [`Self::STANDARD`].
+    pub const fn at(base: u16) -> Self {
+        Self {
+            reset: base.wrapping_add(VEC_RESET & 0x0F),
+            nmi: base.wrapping_add(VEC_NMI & 0x0F),
+            swi: base.wrapping_add(VEC_SWI & 0x0F),
+            irq: base.wrapping_add(VEC_IRQ & 0x0F),
+            firq: base.wrapping_add(VEC_FIRQ & 0x0F),
+            swi2: base.wrapping_add(VEC_SWI2 & 0x0F),
+            swi3: base.wrapping_add(VEC_SWI3 & 0x0F),
+            trap: base.wrapping_add(VEC_TRAP & 0x0F),
+        }
+    }
+}
+
+impl Default for VectorTable {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Registers with D, X, Y, U, S and CC drawn from `rng`, in the order
+/// [`Cpu::randomize_undefined`] has always used.
+fn random_registers(rng: &mut SeedRng) -> Registers {
+    let mut reg = Registers::new();
+    reg.d = rng.next_u16();
+    reg.x = rng.next_u16();
+    reg.y = rng.next_u16();
+    reg.u = rng.next_u16();
+    reg.s = rng.next_u16();
+    reg.cc = ConditionCodes(rng.next_u8());
+    reg
+}
+
+/// A step completed by [`Cpu::try_step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepInfo {
+    /// PC before the step: the instruction executed, unless the step
+    /// serviced an interrupt or waited instead.
+    pub pc: u16,
+    /// Cycles the step took.
+    pub cycles: u64,
+}
+
+/// What a [`Cpu::step_info`] step did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepResult {
+    /// Cycles the step took.
+    pub cycles: u64,
+    /// PC before the step.
+    pub pc_before: u16,
+    /// PC after the step.
+    pub pc_after: u16,
+    /// The opcode executed, as [`LastInstruction::opcode`] gives it, or
+    /// `None` if the step took an interrupt or waited instead.
+    pub opcode: Option<u16>,
+    /// The interrupt line serviced instead of an instruction, if any.
+    pub interrupt_taken: Option<InterruptSource>,
+}
+
+/// Why [`Cpu::run_until_stop`] returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The cycle budget was used up.
+    CycleBudgetExhausted,
+    /// The CPU is halted (see [`Cpu::halted`]).
+    Halted,
+    /// The instruction at `pc` was illegal. It has run, under the
+    /// [`IllegalOpcodePolicy`] in force.
+    Illegal { pc: u16 },
+    /// PC reached a breakpoint; nothing was executed.
+    Breakpoint { pc: u16 },
+    /// The instruction at `pc` wrote to the watched address `addr`. It has
+    /// run to completion.
+    Watchpoint { pc: u16, addr: u16 },
+    /// The CPU is in CWAI with no serviceable interrupt pending.
+    CwaiWaiting,
+    /// The CPU is in SYNC with no interrupt line active.
+    SyncWaiting,
+    /// A [`ControlHandle`] is paused.
+    Paused,
+}
+
+/// The instruction a step executed, from [`Cpu::last_instruction`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LastInstruction {
+    /// Address of the first byte.
+    pub pc: u16,
+    /// The instruction's bytes as fetched, prefix included; only the first
+    /// [`Self::length`] are used.
+    pub opcode_bytes: [u8; MAX_INSN_LEN as usize],
+    /// Number of bytes fetched.
+    pub length: u8,
+    /// Cycles the step took.
+    pub cycles: u64,
+    /// Address the operand was addressed at, after any indirection: the
+    /// direct, extended or indexed address, or a branch's destination
+    /// whether taken or not. `None` for inherent, immediate and register
+    /// operands.
+    pub effective_address: Option<u16>,
+    /// The condition codes before and after.
+    pub flags: FlagDelta,
+}
+
+impl LastInstruction {
+    /// The bytes fetched.
+    pub fn bytes(&self) -> &[u8] {
+        &self.opcode_bytes[..self.length as usize]
+    }
+
+    /// The opcode as fetched, with a page prefix in the high byte: 0x86 for
+    /// LDA immediate, 0x103F for SWI2.
+    pub fn opcode(&self) -> u16 {
+        match self.bytes() {
+            [prefix @ (0x10 | 0x11), opcode, ..] => u16::from_be_bytes([*prefix, *opcode]),
+            [opcode, ..] => *opcode as u16,
+            [] => 0,
+        }
+    }
+}
+
+/// The instruction being recorded for [`Cpu::last_instruction`], and the
+/// last one completed.
+struct LastRecorder {
+    current: LastInstruction,
+    last: Option<LastInstruction>,
+}
+
+impl LastRecorder {
+    fn new() -> Self {
+        Self {
+            current: LastInstruction {
+                pc: 0,
+                opcode_bytes: [0; MAX_INSN_LEN as usize],
+                length: 0,
+                cycles: 0,
+                effective_address: None,
+                flags: FlagDelta::default(),
+            },
+            last: None,
+        }
+    }
+
+    fn begin(&mut self, reg: &Registers) {
+        self.current.pc = reg.pc;
+        self.current.length = 0;
+        self.current.effective_address = None;
+        self.current.flags.before = reg.cc;
+    }
+
+    fn fetch(&mut self, byte: u8) {
+        if let Some(slot) = self
+            .current
+            .opcode_bytes
+            .get_mut(self.current.length as usize)
+        {
+            *slot = byte;
+            self.current.length += 1;
+        }
+    }
+
+    /// End the step; one that fetched nothing executed no instruction.
+    fn finish(&mut self, reg: &Registers, cycles: u64) {
+        self.current.cycles = cycles;
+        self.current.flags.after = reg.cc;
+        self.last = (self.current.length > 0).then_some(self.current);
+    }
+}
+
+/// Why [`Cpu::try_step`] did not complete cleanly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuError {
+    /// The instruction at `pc` was illegal, under
+    /// [`IllegalOpcodePolicy::Trap`].
+    IllegalOpcode { pc: u16 },
+    /// A push or pull broke the [`StackMonitor`] bounds, under
+    /// [`StackAction::Trap`].
+    StackViolation(StackViolation),
+    /// The bus refused an access during the step.
+    BusFault(BusFault),
+    /// PC reached a breakpoint; nothing was executed.
+    Breakpoint { pc: u16 },
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { pc } => write!(f, "illegal opcode at {pc:04X}"),
+            CpuError::StackViolation(v) => {
+                let access = match v.access {
+                    StackAccess::Push => "push to",
+                    StackAccess::Pull => "pull from",
+                };
+                let reason = if v.collision {
+                    "into the other stack"
+                } else {
+                    "out of bounds"
+                };
+                write!(f, "{:?} stack {access} {:04X} {reason}", v.stack, v.addr)
+            }
+            CpuError::BusFault(BusFault {
+                addr,
+                write: Some(val),
+            }) => write!(f, "bus refused write of {val:02X} to {addr:04X}"),
+            CpuError::BusFault(BusFault { addr, write: None }) => {
+                write!(f, "bus refused read from {addr:04X}")
+            }
+            CpuError::Breakpoint { pc } => write!(f, "breakpoint at {pc:04X}"),
+        }
+    }
+}
+
+impl Error for CpuError {}
+
+/// What the CPU does when it decodes an undefined opcode on any page.
+///
+/// Whatever the policy, the sticky [`Cpu::illegal`] flag is set first.
+#[derive(Default)]
+pub enum IllegalOpcodePolicy {
+    /// Do as the processor does. The 6809 carries on with the next
+    /// instruction, as for [`TreatAsNop`](Self::TreatAsNop); the HD6309 sets
+    /// the IL bit of MD and traps through [`VectorTable::trap`], taking 20
+    /// cycles (22 in native mode).
+    #[default]
+    Hardware,
+    /// Carry on with the next instruction, as the 6809 itself does.
+    TreatAsNop,
+    /// Stop as a halt instruction does; [`Cpu::halted`] becomes true.
+    Halt,
+    /// Run a host callback, which may change the registers (for example PC)
+    /// before execution continues.
+    Callback(IllegalOpcodeHook),
+    /// Carry on as [`TreatAsNop`](Self::TreatAsNop) does, but latch the
+    /// address of the first such opcode for [`Cpu::try_step`] to report as
+    /// [`CpuError::IllegalOpcode`].
+    Trap,
+    /// Trap like SWI through the vector at the given address: the entire
+    /// state is pushed, IRQ and FIRQ are masked and PC is loaded from the
+    /// vector. Takes as many cycles as SWI.
+    TrapToVector(u16),
+}
+
+/// Bounds for the S and U stacks, checked on every push and pull (see
+/// [`Cpu::set_stack_monitor`]).
+pub struct StackMonitor {
+    /// Addresses S may push to and pull from; `None` leaves S unchecked.
+    pub s: Option<RangeInclusive<u16>>,
+    /// Addresses U may push to and pull from; `None` leaves U unchecked,
+    /// as suits code that uses U only as a pointer.
+    pub u: Option<RangeInclusive<u16>>,
+    /// What a violation does.
+    pub action: StackAction,
+}
+
+/// What the CPU does when a push or pull breaks the [`StackMonitor`]
+/// bounds. The access itself goes ahead in every case.
+pub enum StackAction {
+    /// Stop after the instruction, as a halt does; [`Cpu::halted`] becomes
+    /// true.
+    Halt,
+    /// Carry on, but latch the first violation for [`Cpu::try_step`] to
+    /// report as [`CpuError::StackViolation`].
+    Trap,
+    /// Run a host callback and carry on.
+    Callback(StackHook),
+}
+
+/// Callback invoked by [`StackAction::Callback`] with the violation and the
+/// registers, S or U already moved by the access.
+pub type StackHook = Box<dyn FnMut(StackViolation, &Registers) + Send>;
+
+/// Stack pointer named in a [`StackViolation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stack {
+    /// The hardware stack.
+    S,
+    /// The user stack.
+    U,
+}
+
+/// Direction of the access in a [`StackViolation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackAccess {
+    /// A push, moving the pointer down.
+    Push,
+    /// A pull, moving the pointer up.
+    Pull,
+}
+
+/// A push or pull that broke the [`StackMonitor`] bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackViolation {
+    /// The stack accessed.
+    pub stack: Stack,
+    /// Whether it was pushed to or pulled from.
+    pub access: StackAccess,
+    /// Lowest address accessed.
+    pub addr: u16,
+    /// `false` if the access left the stack's own range, `true` if it was
+    /// a push into the live part of the other stack (between the other
+    /// pointer and the top of its range).
+    pub collision: bool,
 }
 
 impl Cpu {
+    /// Clock rate of a new CPU, in cycles per second: the 1 MHz of the
+    /// MC6809 and MC6809E.
+    pub const DEFAULT_CLOCK_HZ: u64 = 1_000_000;
+
     /// Create a new CPU with all state zeroed.
     pub fn new() -> Self {
         Self {
             reg: Registers::new(),
+            reg6309: Hd6309Registers::new(),
+            variant: CpuVariant::Mc6809,
+            tfm: false,
             cycles: 0,
+            instructions: 0,
+            budget_end: None,
+            tick_left: 0,
             halted: false,
             illegal: false,
+            illegal_policy: IllegalOpcodePolicy::Hardware,
+            illegal_fault: None,
+            breakpoints: BTreeSet::new(),
+            break_resume: None,
+            watchpoints: BTreeSet::new(),
+            undocumented_opcodes: false,
+            transfer_quirks: TransferQuirks::Simple,
+            half_carry_quirks: HalfCarryQuirks::Simple,
+            clock_hz: Self::DEFAULT_CLOCK_HZ,
+            reset_state: ResetState::Zeroed,
+            vectors: VectorTable::STANDARD,
+            stack_monitor: None,
+            stack_fault: None,
             nmi_armed: false,
+            interrupt_taken: None,
             int_lines: BusSignals::default(),
+            interrupt_handle: None,
+            handle_lines: BusSignals::default(),
             cwai: false,
             sync: false,
+            dma_breq: false,
+            dma_stolen: 0,
+            bus_granted: false,
+            decode_cache: None,
+            block_cache: None,
+            code_watch: None,
+            #[cfg(feature = "hooks")]
+            coverage: None,
+            #[cfg(feature = "hooks")]
+            opcode_stats: None,
+            #[cfg(feature = "hooks")]
+            profile: None,
+            #[cfg(feature = "hooks")]
+            interrupt_stats: None,
+            #[cfg(feature = "hooks")]
+            run_stats: None,
+            last_insn: None,
+            swi_hook: None,
+            control: None,
+            opcode_translator: None,
+            opcode_handlers: BTreeMap::new(),
+            #[cfg(feature = "hooks")]
+            instruction_hook: None,
         }
     }
 
     /// Hardware reset: read PC from reset vector, set I+F, clear state.
+    ///
+    /// D, X, Y, U, S and the E, H, N, Z, V and C flags are then filled as
+    /// the [`ResetState`] selects; DP is always cleared.
     pub fn reset(&mut self, mem: &mut impl Memory) {
         self.reg = Registers::new();
+        self.reg6309.md = 0;
+        self.tfm = false;
         self.reg.cc.set_irq_inhibit(true);
         self.reg.cc.set_firq_inhibit(true);
-        self.reg.pc = mem.read_word(VEC_RESET);
+        self.reg.pc = mem.read_word(self.vectors.reset);
         self.cycles = 0;
+        self.instructions = 0;
+        self.budget_end = None;
+        self.tick_left = 0;
         self.halted = false;
         self.illegal = false;
+        self.illegal_fault = None;
+        self.stack_fault = None;
+        self.break_resume = None;
         self.nmi_armed = false;
         self.int_lines = BusSignals::default();
+        self.handle_lines = BusSignals::default();
         self.cwai = false;
         self.sync = false;
+        self.dma_breq = false;
+        self.dma_stolen = 0;
+        self.bus_granted = false;
+        self.flush_decode_cache();
+        self.flush_block_cache();
+
+        let undefined = match &mut self.reset_state {
+            ResetState::Zeroed => None,
+            ResetState::Fixed(reg) => Some(*reg),
+            ResetState::Random(rng) => Some(random_registers(rng)),
+        };
+        if let Some(reg) = undefined {
+            self.set_undefined(&reg);
+        }
+    }
+
+    /// Select where reset, interrupts, SWI, SWI2, SWI3 and the undocumented
+    /// RESET and FIRQ opcodes fetch their vectors. Defaults to
+    /// [`VectorTable::STANDARD`]; reset leaves it alone.
+    pub fn set_vectors(&mut self, vectors: VectorTable) {
+        self.vectors = vectors;
+    }
+
+    /// The current vector table.
+    pub fn vectors(&self) -> &VectorTable {
+        &self.vectors
+    }
+
+    /// Select what [`Self::reset`] leaves in the registers the MC6809 does
+    /// not define at reset. Defaults to [`ResetState::Zeroed`]; takes effect
+    /// at the next reset.
+    pub fn set_reset_state(&mut self, state: ResetState) {
+        self.reset_state = state;
+    }
+
+    /// The current reset register state.
+    pub fn reset_state(&self) -> &ResetState {
+        &self.reset_state
+    }
+
+    /// Fill the registers that reset leaves undefined (D, X, Y, U, S and the
+    /// E, H, N, Z, V and C flags) from `rng`, as after a reset on real
+    /// hardware. PC, DP and the interrupt masks keep their reset values and
+    /// NMI stays disarmed.
+    pub fn randomize_undefined(&mut self, rng: &mut SeedRng) {
+        self.set_undefined(&random_registers(rng));
+    }
+
+    /// Copy the registers reset leaves undefined from `from`.
+    fn set_undefined(&mut self, from: &Registers) {
+        self.reg.d = from.d;
+        self.reg.x = from.x;
+        self.reg.y = from.y;
+        self.reg.u = from.u;
+        self.reg.s = from.s;
+        let masks = CC_I | CC_F;
+        self.reg.cc = ConditionCodes((self.reg.cc.0 & masks) | (from.cc.0 & !masks));
+    }
+
+    /// Whether an interrupt would be taken before the next instruction: NMI
+    /// is latched, or FIRQ or IRQ is asserted and not masked.
+    pub fn interrupt_pending(&self) -> bool {
+        self.int_lines.contains(BusSignals::NMI)
+            || (self.int_lines.contains(BusSignals::FIRQ) && !self.reg.cc.firq_inhibit())
+            || (self.int_lines.contains(BusSignals::IRQ) && !self.reg.cc.irq_inhibit())
+    }
+
+    /// Advance the cycle count by `cycles` without executing anything, for
+    /// host loops that skip time the CPU would spend spinning in place.
+    pub fn add_idle_cycles(&mut self, cycles: u64) {
+        self.cycles += cycles;
+    }
+
+    /// Select the processor to emulate. Defaults to [`CpuVariant::Mc6809`];
+    /// reset leaves it alone.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, CpuVariant, FlatRam};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x10, 0x86, 0x12, 0x34], 0x0400); // LDW #$1234
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.set_variant(CpuVariant::Hd6309);
+    /// cpu.reset(&mut ram);
+    /// cpu.step(&mut ram);
+    /// assert_eq!(cpu.hd6309_registers().w, 0x1234);
+    /// ```
+    pub fn set_variant(&mut self, variant: CpuVariant) {
+        self.variant = variant;
+        self.flush_decode_cache();
+        self.flush_block_cache();
+    }
+
+    /// The processor being emulated.
+    pub fn variant(&self) -> CpuVariant {
+        self.variant
+    }
+
+    /// `true` if a 6309 is in native mode, with the native cycle counts and
+    /// E and F in the entire-state stack frame.
+    pub(crate) fn native_mode(&self) -> bool {
+        self.variant == CpuVariant::Hd6309 && self.reg6309.native()
+    }
+
+    /// `true` if a 6309 has FIRQ save the entire state, as IRQ does.
+    fn firq_as_irq(&self) -> bool {
+        self.variant == CpuVariant::Hd6309 && self.reg6309.md & MD_FIRQ_IRQ != 0
+    }
+
+    /// Cycles an interrupt or SWI takes to push the entire state: two more
+    /// in native mode, for E and F.
+    fn entire_state_cycles(&self, cycles: u64) -> u64 {
+        if self.native_mode() {
+            cycles + 2
+        } else {
+            cycles
+        }
+    }
+
+    /// The registers only the HD6309 has. Reset clears MD and leaves W and
+    /// V alone; under [`CpuVariant::Mc6809`] nothing uses them.
+    pub fn hd6309_registers(&self) -> &Hd6309Registers {
+        &self.reg6309
+    }
+
+    /// Mutable access to the registers only the HD6309 has.
+    pub fn hd6309_registers_mut(&mut self) -> &mut Hd6309Registers {
+        &mut self.reg6309
     }
 
     /// Read-only access to the programmer-visible registers.
@@ -120,193 +917,1656 @@ impl Cpu {
     ///     mem[r.s as usize] = lo;
     /// } // NMI armed here via Drop
     /// ```
-    pub fn registers_mut(&mut self) -> RegistersMut<'_> {
-        let prev_s = self.reg.s;
-        RegistersMut { cpu: self, prev_s }
+    pub fn registers_mut(&mut self) -> RegistersMut<'_> {
+        let prev_s = self.reg.s;
+        RegistersMut { cpu: self, prev_s }
+    }
+
+    /// Total elapsed cycles since the last [`Self::reset`].
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Instructions executed since the last [`Self::reset`].
+    ///
+    /// Interrupt entry and the cycles spent waiting in SYNC, CWAI or for DMA
+    /// are not instructions; an HD6309 TFM counts once however many bytes it
+    /// moves. Unlike [`Self::cycles`] the count is not part of a
+    /// [`Snapshot`] or [`CpuState`].
+    pub fn instructions_retired(&self) -> u64 {
+        self.instructions
+    }
+
+    /// `true` if the CPU has been halted by a halt instruction.
+    ///
+    /// Illegal opcodes only set this flag under
+    /// [`IllegalOpcodePolicy::Halt`].
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Assert or de-assert the halted state.
+    pub fn set_halted(&mut self, active: bool) {
+        self.halted = active;
+    }
+
+    /// Sticky flag set when an illegal opcode is executed.
+    ///
+    /// The flag is set under every [`IllegalOpcodePolicy`]; what else
+    /// happens is up to the policy (see [`Self::set_illegal_opcode_policy`]).
+    pub fn illegal(&self) -> bool {
+        self.illegal
+    }
+
+    /// Clear the illegal opcode flag.
+    pub fn clear_illegal(&mut self) {
+        self.illegal = false;
+    }
+
+    /// Set what executing an illegal opcode does. The default,
+    /// [`IllegalOpcodePolicy::Hardware`], keeps running on the 6809 and
+    /// traps on the 6309, as the processors do.
+    /// The policy is configuration: reset and state loads leave it alone.
+    pub fn set_illegal_opcode_policy(&mut self, policy: IllegalOpcodePolicy) {
+        self.illegal_policy = policy;
+    }
+
+    /// Check every push and pull against `monitor`'s bounds, or stop
+    /// checking with `None`. Each byte or word access is checked on its
+    /// own, so one PSHS can report several violations. Off by default;
+    /// reset leaves it alone.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, StackAction, StackMonitor};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x34, 0x06], 0x0400); // PSHS D
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.registers_mut().s = 0x7F01;
+    /// cpu.set_stack_monitor(Some(StackMonitor {
+    ///     s: Some(0x7F00..=0x7FFF),
+    ///     u: None,
+    ///     action: StackAction::Halt,
+    /// }));
+    /// cpu.step(&mut ram);
+    /// assert!(cpu.halted());
+    /// ```
+    pub fn set_stack_monitor(&mut self, monitor: Option<StackMonitor>) {
+        self.stack_monitor = monitor;
+    }
+
+    /// The current stack monitor.
+    pub fn stack_monitor(&self) -> Option<&StackMonitor> {
+        self.stack_monitor.as_ref()
+    }
+
+    /// The current illegal opcode policy.
+    pub fn illegal_opcode_policy(&self) -> &IllegalOpcodePolicy {
+        &self.illegal_policy
+    }
+
+    /// Enable or disable full undocumented-opcode accuracy.
+    ///
+    /// The undocumented opcodes that alias or extend documented ones (XNC,
+    /// XDEC, XCLR, XHCF, X18, XANDCC, XRES, XSWI2, XFIRQ and the like) always
+    /// run. Enabling this also runs, as silicon does, the ones that are
+    /// treated as illegal by default:
+    ///
+    /// - the store immediates (0x87 STA, 0xC7 STB, 0x8F STX, 0xCF STU,
+    ///   0x10 0x8F STY, 0x10 0xCF STS), which write the register over their
+    ///   own operand bytes, setting the flags as the store would;
+    /// - undefined opcodes on page 1 and 2, which ignore their prefix and
+    ///   run the page 0 instruction one cycle longer.
+    ///
+    /// [`instruction_cycles`] keeps reporting the default, illegal, cycle
+    /// counts for these. Off by default; reset leaves it alone.
+    pub fn set_undocumented_opcodes(&mut self, enabled: bool) {
+        self.undocumented_opcodes = enabled;
+    }
+
+    /// `true` if full undocumented-opcode accuracy is enabled.
+    pub fn undocumented_opcodes(&self) -> bool {
+        self.undocumented_opcodes
+    }
+
+    /// Select how TFR and EXG behave between registers of different sizes
+    /// and with undefined register codes. Defaults to
+    /// [`TransferQuirks::Simple`]; reset leaves it alone.
+    pub fn set_transfer_quirks(&mut self, quirks: TransferQuirks) {
+        self.transfer_quirks = quirks;
+    }
+
+    /// The current TFR/EXG behaviour.
+    pub fn transfer_quirks(&self) -> TransferQuirks {
+        self.transfer_quirks
+    }
+
+    /// Select how the 8-bit subtract-class instructions (SUB, SBC, CMP, NEG
+    /// and their undocumented aliases) affect H, which the datasheet leaves
+    /// undefined. Defaults to [`HalfCarryQuirks::Simple`]; reset leaves it
+    /// alone.
+    pub fn set_half_carry_quirks(&mut self, quirks: HalfCarryQuirks) {
+        self.half_carry_quirks = quirks;
+    }
+
+    /// The current H behaviour for subtract-class instructions.
+    pub fn half_carry_quirks(&self) -> HalfCarryQuirks {
+        self.half_carry_quirks
+    }
+
+    /// Set the clock rate, in cycles per second, that
+    /// [`Self::cycles_to_duration`] and [`Self::duration_to_cycles`] convert
+    /// with. Defaults to [`Self::DEFAULT_CLOCK_HZ`]; it does not change how
+    /// fast anything runs, and reset and state loads leave it alone.
+    ///
+    /// # Panics
+    /// Panics if `hz` is zero.
+    pub fn set_clock_hz(&mut self, hz: u64) {
+        assert!(hz > 0, "clock rate must be non-zero");
+        self.clock_hz = hz;
+    }
+
+    /// The clock rate, in cycles per second.
+    pub fn clock_hz(&self) -> u64 {
+        self.clock_hz
+    }
+
+    /// Emulated time `cycles` take at the configured clock rate, rounded
+    /// down to the nanosecond.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mc6809_core::Cpu;
+    ///
+    /// let mut cpu = Cpu::new();
+    /// cpu.set_clock_hz(2_000_000);
+    /// assert_eq!(cpu.cycles_to_duration(3), Duration::from_nanos(1_500));
+    /// assert_eq!(cpu.duration_to_cycles(Duration::from_millis(1)), 2_000);
+    /// ```
+    pub fn cycles_to_duration(&self, cycles: u64) -> Duration {
+        let nanos = cycles as u128 * NANOS_PER_SEC / self.clock_hz as u128;
+        Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX))
+    }
+
+    /// Whole cycles that fit in `dt` at the configured clock rate,
+    /// saturating at `u64::MAX`.
+    pub fn duration_to_cycles(&self, dt: Duration) -> u64 {
+        let cycles = dt.as_nanos() * self.clock_hz as u128 / NANOS_PER_SEC;
+        cycles.try_into().unwrap_or(u64::MAX)
+    }
+
+    /// Assert or de-assert the IRQ line (level-triggered).
+    ///
+    /// The CPU samples this each step. Only the peripheral should de-assert it
+    /// (by calling `set_irq(false)`); the CPU never clears it internally.
+    pub fn set_irq(&mut self, active: bool) {
+        if active {
+            self.int_lines.insert(BusSignals::IRQ);
+        } else {
+            self.int_lines.remove(BusSignals::IRQ);
+        }
+    }
+
+    /// Assert or de-assert the FIRQ line (level-triggered).
+    ///
+    /// The CPU samples this each step. Only the peripheral should de-assert it
+    /// (by calling `set_firq(false)`); the CPU never clears it internally.
+    pub fn set_firq(&mut self, active: bool) {
+        if active {
+            self.int_lines.insert(BusSignals::FIRQ);
+        } else {
+            self.int_lines.remove(BusSignals::FIRQ);
+        }
+    }
+
+    /// Trigger an NMI (edge-triggered). Only effective if NMI is armed.
+    pub fn trigger_nmi(&mut self) {
+        if self.nmi_armed {
+            self.int_lines.insert(BusSignals::NMI);
+        }
+    }
+
+    /// A handle other threads can use to assert IRQ, FIRQ and NMI. Every
+    /// call returns a handle to the same lines.
+    pub fn interrupt_handle(&mut self) -> InterruptHandle {
+        self.interrupt_handle
+            .get_or_insert_with(InterruptHandle::default)
+            .clone()
+    }
+
+    /// Take the lines held through the [`InterruptHandle`], if there is one.
+    #[inline(always)]
+    fn sample_interrupt_handle(&mut self) {
+        let Some(handle) = &self.interrupt_handle else {
+            return;
+        };
+        let lines = handle.sample();
+        if lines.contains(BusSignals::NMI) {
+            self.trigger_nmi();
+        }
+        let levels = lines & (BusSignals::IRQ | BusSignals::FIRQ);
+        self.int_lines.remove(self.handle_lines & !levels);
+        self.int_lines.insert(levels);
+        self.handle_lines = levels;
+    }
+
+    /// Assert or de-assert the DMA/BREQ pin (level-triggered).
+    ///
+    /// While the pin is asserted each step steals one cycle for a DMA master
+    /// instead of executing, and [`Self::bus_granted`] reports that the
+    /// master may use the bus for that cycle. As on the real part, the CPU
+    /// takes the bus back for one refresh cycle after every 14 stolen
+    /// cycles.
+    pub fn set_dma_breq(&mut self, active: bool) {
+        self.dma_breq = active;
+    }
+
+    /// `true` if the last step handed the bus to a DMA master (see
+    /// [`Self::set_dma_breq`]).
+    pub fn bus_granted(&self) -> bool {
+        self.bus_granted
+    }
+
+    /// Apply a snapshot of bus signals to the CPU, handling NMI edge detection.
+    ///
+    /// Call this from the host loop whenever [`BusSignals`] change. Passing the
+    /// previous snapshot allows the CPU to detect the NMI rising edge internally,
+    /// so the caller does not need to track edge transitions for NMI.
+    ///
+    /// IRQ and FIRQ are level-triggered: their state is mirrored directly into
+    /// the CPU. The CPU will hold the line until the peripheral de-asserts it
+    /// (i.e. returns a snapshot without `IRQ`/`FIRQ` set on a subsequent tick).
+    ///
+    /// RESET is not handled here; the host loop is responsible for calling
+    /// [`Cpu::reset`] when `signals` contains [`BusSignals::RESET`].
+    ///
+    /// # Host loop pattern
+    /// ```ignore
+    /// let mut prev_signals = BusSignals::default();
+    /// loop {
+    ///     let cycles = cpu.step(&mut mem);
+    ///     let signals = peripheral.tick(cycles);
+    ///
+    ///     if signals.contains(BusSignals::RESET) {
+    ///         cpu.reset(&mut mem);
+    ///         prev_signals = BusSignals::default();
+    ///         continue;
+    ///     }
+    ///
+    ///     if signals != prev_signals {
+    ///         cpu.apply_signals(signals, prev_signals);
+    ///         prev_signals = signals;
+    ///     }
+    ///
+    ///     if cpu.halted() { break; }
+    /// }
+    /// ```
+    pub fn apply_signals(&mut self, signals: BusSignals, prev: BusSignals) {
+        // NMI: edge-triggered — arm on rising edge only
+        if signals.contains(BusSignals::NMI) && !prev.contains(BusSignals::NMI) {
+            self.trigger_nmi();
+        }
+        // IRQ/FIRQ: level-triggered — mirror current pin state
+        if signals.contains(BusSignals::FIRQ) {
+            self.int_lines.insert(BusSignals::FIRQ);
+        } else {
+            self.int_lines.remove(BusSignals::FIRQ);
+        }
+        if signals.contains(BusSignals::IRQ) {
+            self.int_lines.insert(BusSignals::IRQ);
+        } else {
+            self.int_lines.remove(BusSignals::IRQ);
+        }
+        self.dma_breq = signals.contains(BusSignals::DMA_BREQ);
+    }
+
+    /// Execute a single instruction (or handle a pending interrupt).
+    /// Returns the number of cycles consumed.
+    ///
+    /// If the decoded instruction is illegal, the CPU records that in
+    /// [`Self::illegal`] and then follows its [`IllegalOpcodePolicy`].
+    pub fn step<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        if self.last_insn.is_some() {
+            return self.step_recorded(mem);
+        }
+        self.step_unrecorded(mem)
+    }
+
+    /// [`Self::step`], reporting what the step did.
+    ///
+    /// Records the instruction as [`Self::set_last_instruction`] does for
+    /// the one step if recording is off, so it costs more than a plain
+    /// `step`.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory, StepResult};
+    /// use mc6809_core::stats::InterruptSource;
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x10, 0x8E, 0x12, 0x34], 0x0400); // LDY #$1234
+    /// ram.write_word(0xFFF8, 0x0500);
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    ///
+    /// let result = cpu.step_info(&mut ram);
+    /// assert_eq!(result.opcode, Some(0x108E));
+    /// assert_eq!((result.pc_before, result.pc_after), (0x0400, 0x0404));
+    /// assert_eq!(result.cycles, 4);
+    ///
+    /// cpu.registers_mut().s = 0x0800;
+    /// cpu.registers_mut().cc.set_irq_inhibit(false);
+    /// cpu.set_irq(true);
+    /// let result = cpu.step_info(&mut ram);
+    /// assert_eq!(result.opcode, None);
+    /// assert_eq!(result.interrupt_taken, Some(InterruptSource::Irq));
+    /// assert_eq!(result.pc_after, 0x0500);
+    /// ```
+    pub fn step_info<M: Memory>(&mut self, mem: &mut M) -> StepResult {
+        let pc_before = self.reg.pc;
+        self.interrupt_taken = None;
+        let recording = self.last_insn.is_some();
+        if !recording {
+            self.last_insn = Some(Box::new(LastRecorder::new()));
+        }
+        let cycles = self.step(mem);
+        let opcode = self.last_instruction().map(LastInstruction::opcode);
+        if !recording {
+            self.last_insn = None;
+        }
+        StepResult {
+            cycles,
+            pc_before,
+            pc_after: self.reg.pc,
+            opcode,
+            interrupt_taken: self.interrupt_taken.take(),
+        }
+    }
+
+    /// Clock the CPU through one cycle, for a system that drives every
+    /// component from a shared clock. Returns `true` on the last cycle of
+    /// an instruction (or of an interrupt entry or wait), when the next
+    /// tick starts a new one.
+    ///
+    /// Each step runs as a whole on its first cycle, as [`Self::step`]:
+    /// its bus accesses, register changes and the addition to
+    /// [`Self::cycles`] all happen then, and the ticks that follow only
+    /// count its remaining cycles down. Interrupt lines are sampled when a
+    /// step starts. Use either ticks or the step and run methods between
+    /// resets, not both.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x12, 0x86, 0x42], 0x0400); // NOP / LDA #$42
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    ///
+    /// let boundaries: Vec<bool> = (0..4).map(|_| cpu.tick(&mut ram)).collect();
+    /// assert_eq!(boundaries, [false, true, false, true]);
+    /// assert_eq!(cpu.registers().a(), 0x42);
+    /// ```
+    pub fn tick<M: Memory>(&mut self, mem: &mut M) -> bool {
+        if self.tick_left == 0 {
+            self.tick_left = self.step(mem).max(1);
+        }
+        self.tick_left -= 1;
+        self.tick_left == 0
+    }
+
+    /// Cycles of the current step [`Self::tick`] has still to clock.
+    pub fn tick_cycles_left(&self) -> u64 {
+        self.tick_left
+    }
+
+    /// [`Self::step`] without recording the last instruction.
+    fn step_unrecorded<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        if self.code_watch.is_some() {
+            return self.step_watched(mem);
+        }
+        self.step_dispatch(mem)
+    }
+
+    /// [`Self::step`] recording the instruction executed for
+    /// [`Self::last_instruction`].
+    fn step_recorded<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        if let Some(recorder) = &mut self.last_insn {
+            recorder.begin(&self.reg);
+        }
+        let cycles = self.step_unrecorded(mem);
+        if let Some(recorder) = &mut self.last_insn {
+            recorder.finish(&self.reg, cycles);
+        }
+        cycles
+    }
+
+    /// [`Self::step`] through whichever cache is enabled.
+    fn step_dispatch<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        if self.decode_cache.is_some() {
+            return self.step_cached(mem);
+        }
+        if self.block_cache.is_some() {
+            return self.step_tracked(mem);
+        }
+        self.step_uncached(mem)
+    }
+
+    /// [`Self::step`] that reports why the step went wrong, if it did.
+    ///
+    /// Before executing, stops with [`CpuError::Breakpoint`] if PC is at a
+    /// breakpoint (see [`Self::add_breakpoint`]); the next call runs past
+    /// it. After executing, returns [`CpuError::IllegalOpcode`] for an
+    /// illegal opcode under [`IllegalOpcodePolicy::Trap`],
+    /// [`CpuError::StackViolation`] under [`StackAction::Trap`], then
+    /// [`CpuError::BusFault`] for an access `mem` refused (see
+    /// [`Memory::take_fault`]). The instruction has run in both cases.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, CpuError, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x12, 0x12], 0x0400); // NOP / NOP
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.add_breakpoint(0x0401);
+    ///
+    /// assert_eq!(cpu.try_step(&mut ram).unwrap().cycles, 2);
+    /// assert_eq!(cpu.try_step(&mut ram), Err(CpuError::Breakpoint { pc: 0x0401 }));
+    /// assert_eq!(cpu.try_step(&mut ram).unwrap().pc, 0x0401);
+    /// ```
+    pub fn try_step<M: Memory>(&mut self, mem: &mut M) -> Result<StepInfo, CpuError> {
+        let pc = self.reg.pc;
+        if self.at_breakpoint() {
+            return Err(CpuError::Breakpoint { pc });
+        }
+
+        let cycles = self.step(mem);
+        self.left_breakpoint(pc);
+
+        if let Some(addr) = self.illegal_fault.take() {
+            return Err(CpuError::IllegalOpcode { pc: addr });
+        }
+        if let Some(violation) = self.stack_fault.take() {
+            return Err(CpuError::StackViolation(violation));
+        }
+        if let Some(fault) = mem.take_fault() {
+            return Err(CpuError::BusFault(fault));
+        }
+        Ok(StepInfo { pc, cycles })
+    }
+
+    /// Whether [`Self::try_step`] stops before the instruction at PC for a
+    /// breakpoint. Once it has, it does not stop there again until a step
+    /// has run the instruction (see [`Self::left_breakpoint`]).
+    pub(crate) fn at_breakpoint(&mut self) -> bool {
+        let pc = self.reg.pc;
+        if self.break_resume == Some(pc) || !self.breakpoints.contains(&pc) {
+            return false;
+        }
+        self.break_resume = Some(pc);
+        #[cfg(feature = "hooks")]
+        if let Some(stats) = &mut self.run_stats {
+            stats.breakpoint();
+        }
+        true
+    }
+
+    /// Note a step from `pc`, which ran past any breakpoint there unless it
+    /// fetched nothing.
+    pub(crate) fn left_breakpoint(&mut self, pc: u16) {
+        let stalled = self.reg.pc == pc && (self.halted || self.sync || self.cwai || self.dma_breq);
+        if !stalled {
+            self.break_resume = None;
+        }
+    }
+
+    /// Stop [`Self::try_step`] before executing at `addr`.
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    /// Remove the breakpoint at `addr`. Returns `false` if there was none.
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    /// Remove every breakpoint.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// The breakpoints, in address order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Stop [`Self::run_until_stop`] after an instruction that writes to
+    /// `addr`.
+    pub fn add_watchpoint(&mut self, addr: u16) {
+        self.watchpoints.insert(addr);
+    }
+
+    /// Remove the watchpoint at `addr`. Returns `false` if there was none.
+    pub fn remove_watchpoint(&mut self, addr: u16) -> bool {
+        self.watchpoints.remove(&addr)
+    }
+
+    /// Remove every watchpoint.
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    /// The watchpoints, in address order.
+    pub fn watchpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.watchpoints.iter().copied()
+    }
+
+    /// [`Self::step`] straight against `mem`, ignoring any cache.
+    fn step_uncached<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        if let Some(cycles) = self.service_pending(mem) {
+            return cycles;
+        }
+
+        // Fetch and execute one instruction
+        self.before_instruction();
+        let opcode = self.fetch_opcode(mem, OpcodeFetch::First);
+        let cycles = self.execute(mem, opcode) as u64;
+        self.cycles += cycles;
+        cycles
+    }
+
+    /// [`Self::step`] with the decode cache in front of `mem`.
+    fn step_cached<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        let Some(mut cache) = self.decode_cache.take() else {
+            return self.step_uncached(mem);
+        };
+
+        let cycles = self.step_through(&mut cache, mem);
+        self.decode_cache = Some(cache);
+        cycles
+    }
+
+    /// [`Self::step`] through `cache`, taken out of the CPU by the caller.
+    fn step_through<M: Memory>(&mut self, cache: &mut DecodeCache, mem: &mut M) -> u64 {
+        // Interrupt entry pushes onto the stack, so writes must still
+        // invalidate cached instructions even though nothing is fetched.
+        match self.service_pending(&mut cache.bind(mem)) {
+            Some(cycles) => cycles,
+            None => self.execute_cached(cache, mem),
+        }
+    }
+
+    /// Execute the instruction at PC, dispatching a cached one straight to
+    /// its handler, or fetching, decoding and caching it on a miss.
+    fn execute_cached<M: Memory>(&mut self, cache: &mut DecodeCache, mem: &mut M) -> u64 {
+        self.before_instruction();
+        let pc = self.reg.pc;
+        let cycles = match cache.get(pc) {
+            Some(hit) => {
+                if let Some(recorder) = &mut self.last_insn {
+                    hit.opcode_bytes()
+                        .iter()
+                        .for_each(|&byte| recorder.fetch(byte));
+                }
+                self.reg.pc = pc.wrapping_add(hit.page.opcode_len());
+                let mut cached = cache.bind_hit(mem, pc, &hit);
+                self.execute_on_page(&mut cached, hit.page, hit.opcode)
+            }
+            None => {
+                // The fetch helpers hand the last-instruction recorder exactly
+                // the bytes they fetch, and nothing read as data; lend one if
+                // none is installed to learn the instruction's bytes.
+                let lent = self.last_insn.is_none();
+                if lent {
+                    let mut recorder = Box::new(LastRecorder::new());
+                    recorder.begin(&self.reg);
+                    self.last_insn = Some(recorder);
+                }
+                let mut cached = cache.bind_miss(mem, pc);
+                let (page, opcode) = match self.fetch_opcode(&mut cached, OpcodeFetch::First) {
+                    0x10 => (
+                        Page::Page1,
+                        self.fetch_opcode(&mut cached, OpcodeFetch::Page1),
+                    ),
+                    0x11 => (
+                        Page::Page2,
+                        self.fetch_opcode(&mut cached, OpcodeFetch::Page2),
+                    ),
+                    op => (Page::Page0, op),
+                };
+                let cycles = self.execute_on_page(&mut cached, page, opcode);
+                let fetched = self.last_insn.as_ref().map(|recorder| recorder.current);
+                if lent {
+                    self.last_insn = None;
+                }
+                if let Some(fetched) = fetched {
+                    cached.finish(page, opcode, fetched.bytes());
+                }
+                cycles
+            }
+        } as u64;
+        self.cycles += cycles;
+        cycles
+    }
+
+    /// [`Self::step`] with instruction fetches and writes checked by the
+    /// code watch.
+    fn step_watched<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        let Some(mut watch) = self.code_watch.take() else {
+            return self.step_dispatch(mem);
+        };
+        let pc = self.reg.pc;
+        let cycles = self.step_dispatch(&mut watch.bind(mem, pc));
+        self.code_watch = Some(watch);
+        cycles
+    }
+
+    /// [`Self::step`] with CPU writes reported to the block cache.
+    fn step_tracked<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        let Some(mut cache) = self.block_cache.take() else {
+            return self.step_uncached(mem);
+        };
+        let cycles = self.step_uncached(&mut cache.bind(mem));
+        self.block_cache = Some(cache);
+        cycles
+    }
+
+    /// Handle DMA bus requests, the halted state, SYNC/CWAI waits and
+    /// pending interrupts.
+    ///
+    /// Returns `Some(cycles)` when the step ends without fetching an
+    /// instruction, or `None` when the caller should fetch and execute.
+    fn service_pending(&mut self, mem: &mut impl Memory) -> Option<u64> {
+        self.sample_interrupt_handle();
+
+        // DMA/BREQ: give the bus away, keeping one cycle in every
+        // MAX_DMA_CYCLES + 1 for refresh.
+        self.bus_granted = self.dma_breq && self.dma_stolen < MAX_DMA_CYCLES;
+        if self.dma_breq {
+            self.dma_stolen = if self.bus_granted {
+                self.dma_stolen + 1
+            } else {
+                0
+            };
+            self.cycles += 1;
+            return Some(1);
+        }
+        self.dma_stolen = 0;
+
+        if self.halted {
+            return Some(1);
+        }
+
+        let start_cycles = self.cycles;
+        #[cfg(feature = "hooks")]
+        if let Some(stats) = &mut self.interrupt_stats {
+            stats.sample(self.int_lines, start_cycles);
+        }
+
+        // Handle SYNC state: wait for any interrupt edge
+        if self.sync {
+            if !self.int_lines.is_empty() {
+                self.sync = false;
+            } else {
+                self.cycles += 1;
+                return Some(1);
+            }
+        }
+
+        // Handle CWAI state: entire state already pushed, waiting for a
+        // serviceable interrupt (NMI is always serviceable; FIRQ/IRQ respect masks).
+        if self.cwai && !self.interrupt_pending() {
+            self.cycles += 1;
+            return Some(1);
+        }
+
+        // Check pending interrupts (priority: NMI > FIRQ > IRQ). An
+        // interrupted TFM starts over when it resumes.
+        if let Some(source) = self.check_interrupts(mem) {
+            self.interrupt_taken = Some(source);
+            self.tfm = false;
+            let cycles = self.cycles - start_cycles;
+            #[cfg(feature = "hooks")]
+            if let Some(profile) = &mut self.profile {
+                profile.interrupt(self.reg.pc, self.reg.s, cycles);
+            }
+            return Some(cycles);
+        }
+
+        None
+    }
+
+    /// Enable or disable the pre-decoded instruction cache.
+    ///
+    /// When enabled, each executed instruction is remembered by address as
+    /// the page and opcode it decoded to, which select its handler, together
+    /// with its bytes. Later executions dispatch straight to the handler
+    /// without fetching or decoding the opcode, and serve the post-byte and
+    /// operand fetches from the cached bytes instead of [`Memory::read`].
+    /// Writes made by the CPU invalidate any cached instruction they overlap,
+    /// so self-modifying code stays correct.
+    ///
+    /// Writes that bypass the CPU (DMA, host-side loading, bank switching)
+    /// are not seen by the cache; call [`Self::invalidate_decode_cache`] or
+    /// [`Self::flush_decode_cache`] after them. The cache is only suitable for
+    /// code executing from RAM or ROM, not from regions with read side
+    /// effects. Disabling the cache discards its contents. Enabling it
+    /// disables the block cache.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        if !enabled {
+            self.decode_cache = None;
+        } else if self.decode_cache.is_none() {
+            self.block_cache = None;
+            self.decode_cache = Some(Box::new(DecodeCache::new()));
+        }
+    }
+
+    /// `true` if the pre-decoded instruction cache is enabled.
+    pub fn decode_cache_enabled(&self) -> bool {
+        self.decode_cache.is_some()
+    }
+
+    /// Drop any cached instruction that overlaps `addr`.
+    pub fn invalidate_decode_cache(&mut self, addr: u16) {
+        if let Some(cache) = &mut self.decode_cache {
+            cache.invalidate(addr);
+        }
+    }
+
+    /// Drop every cached instruction, keeping the cache enabled.
+    pub fn flush_decode_cache(&mut self) {
+        if let Some(cache) = &mut self.decode_cache {
+            cache.flush();
+        }
+    }
+
+    /// Enable or disable straight-line block execution in [`Self::run_fast`].
+    ///
+    /// When enabled, `run_fast` records runs of consecutive instructions that
+    /// cannot branch, trap or write PC, and replays them on later visits by
+    /// dispatching each instruction directly to its handler without
+    /// re-fetching its opcode or checking for interrupts in between. Writes
+    /// made by the CPU drop any block they overlap, so self-modifying code
+    /// stays correct.
+    ///
+    /// As with the decode cache, writes that bypass the CPU are not seen;
+    /// call [`Self::invalidate_block_cache`] or [`Self::flush_block_cache`]
+    /// after them. Disabling the cache discards its contents. Enabling it
+    /// disables the decode cache.
+    pub fn set_block_cache(&mut self, enabled: bool) {
+        if !enabled {
+            self.block_cache = None;
+        } else if self.block_cache.is_none() {
+            self.decode_cache = None;
+            self.block_cache = Some(Box::new(BlockCache::new()));
+        }
+    }
+
+    /// `true` if straight-line block execution is enabled.
+    pub fn block_cache_enabled(&self) -> bool {
+        self.block_cache.is_some()
+    }
+
+    /// Drop any recorded block that covers `addr`.
+    pub fn invalidate_block_cache(&mut self, addr: u16) {
+        if let Some(cache) = &mut self.block_cache {
+            cache.invalidate(addr);
+        }
+    }
+
+    /// Drop every recorded block, keeping block execution enabled.
+    pub fn flush_block_cache(&mut self) {
+        if let Some(cache) = &mut self.block_cache {
+            cache.flush();
+        }
+    }
+
+    /// Install or remove the callback told about self-modifying code.
+    ///
+    /// While a hook is installed, the CPU marks every byte it fetches as part
+    /// of an instruction, and each later CPU write to a marked byte is
+    /// reported as a [`CodeWrite`] before it goes ahead. Installing a hook
+    /// starts from nothing executed and flushes the decode cache, so that
+    /// every instruction is fetched through the watch at least once;
+    /// removing it forgets what has run. [`Self::run_fast`] steps one
+    /// instruction at a time while a hook is installed.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use mc6809_core::{CodeWrite, Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// // LDA #$12 / STA $0401 (patching the LDA's operand)
+    /// ram.load(&[0x86, 0x12, 0xB7, 0x04, 0x01], 0x0400);
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    ///
+    /// let writes = Arc::new(Mutex::new(Vec::new()));
+    /// let log = writes.clone();
+    /// cpu.set_code_write_hook(Some(Box::new(move |write| log.lock().unwrap().push(write))));
+    /// cpu.step(&mut ram);
+    /// cpu.step(&mut ram);
+    /// assert_eq!(
+    ///     *writes.lock().unwrap(),
+    ///     [CodeWrite { pc: 0x0402, addr: 0x0401, val: 0x12 }]
+    /// );
+    /// ```
+    pub fn set_code_write_hook(&mut self, hook: Option<CodeWriteHook>) {
+        self.code_watch = hook.map(|hook| Box::new(CodeWatch::new(hook)));
+        if self.code_watch.is_some() {
+            self.flush_decode_cache();
+        }
+    }
+
+    /// Whether `addr` has been fetched as an instruction byte since the
+    /// code write hook was installed. Always `false` without a hook.
+    pub fn executed(&self, addr: u16) -> bool {
+        self.code_watch
+            .as_ref()
+            .is_some_and(|watch| watch.is_executed(addr))
+    }
+
+    /// Start or stop recording execution coverage: the address of every
+    /// instruction the CPU starts, interrupted or not. Starting keeps any
+    /// map already being recorded; stopping discards it.
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn set_coverage(&mut self, enabled: bool) {
+        if !enabled {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some(Box::default());
+        }
+    }
+
+    /// The coverage recorded so far, or `None` if it is not being recorded.
+    #[cfg(feature = "hooks")]
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_deref()
+    }
+
+    /// Take the coverage recorded so far and carry on recording into an
+    /// empty map, or `None` if it is not being recorded.
+    #[cfg(feature = "hooks")]
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage
+            .as_mut()
+            .map(|coverage| std::mem::take(&mut **coverage))
+    }
+
+    /// Start or stop counting executions of each opcode. Starting keeps any
+    /// counts already being recorded; stopping discards them.
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn set_opcode_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.opcode_stats = None;
+        } else if self.opcode_stats.is_none() {
+            self.opcode_stats = Some(Box::default());
+        }
+    }
+
+    /// The opcode counts so far, or `None` if they are not being recorded.
+    #[cfg(feature = "hooks")]
+    pub fn opcode_stats(&self) -> Option<&OpcodeStats> {
+        self.opcode_stats.as_deref()
+    }
+
+    /// Take the opcode counts so far and carry on counting from zero, or
+    /// `None` if they are not being recorded.
+    #[cfg(feature = "hooks")]
+    pub fn take_opcode_stats(&mut self) -> Option<OpcodeStats> {
+        self.opcode_stats
+            .as_mut()
+            .map(|stats| std::mem::take(&mut **stats))
+    }
+
+    /// Start or stop profiling: charging the cycles of each instruction
+    /// and interrupt entry to the subroutine running it (see [`Profile`]).
+    /// Starting keeps any profile already being recorded; stopping
+    /// discards it.
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn set_profiler(&mut self, enabled: bool) {
+        if !enabled {
+            self.profile = None;
+        } else if self.profile.is_none() {
+            self.profile = Some(Box::default());
+        }
+    }
+
+    /// The profile so far, or `None` if the profiler is off.
+    #[cfg(feature = "hooks")]
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_deref()
+    }
+
+    /// Take the profile so far and carry on profiling from zero cycles,
+    /// still inside the routines running, or `None` if the profiler is off.
+    #[cfg(feature = "hooks")]
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.as_mut().map(|profile| profile.take())
+    }
+
+    /// Start or stop recording interrupt statistics (see
+    /// [`InterruptStats`]). Starting keeps any already being recorded;
+    /// stopping discards them.
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn set_interrupt_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.interrupt_stats = None;
+        } else if self.interrupt_stats.is_none() {
+            self.interrupt_stats = Some(Box::default());
+        }
+    }
+
+    /// The interrupt statistics so far, or `None` if they are not being
+    /// recorded.
+    #[cfg(feature = "hooks")]
+    pub fn interrupt_stats(&self) -> Option<&InterruptStats> {
+        self.interrupt_stats.as_deref()
+    }
+
+    /// Take the interrupt statistics so far and carry on from zero, still
+    /// following the handlers running, or `None` if they are not being
+    /// recorded.
+    #[cfg(feature = "hooks")]
+    pub fn take_interrupt_stats(&mut self) -> Option<InterruptStats> {
+        self.interrupt_stats.as_mut().map(|stats| stats.take())
+    }
+
+    /// Tell the interrupt statistics that `source` was taken, starting at
+    /// cycle `started`, with PC now at its handler.
+    #[cfg(feature = "hooks")]
+    pub(super) fn record_interrupt(&mut self, source: InterruptSource, started: u64) {
+        if let Some(stats) = &mut self.interrupt_stats {
+            stats.enter(source, started, self.cycles);
+        }
+        if let Some(stats) = &mut self.run_stats {
+            stats.interrupt(source, self.reg.s);
+        }
+    }
+
+    /// Start or stop recording the instruction each [`Self::step`] executes,
+    /// for [`Self::last_instruction`].
+    ///
+    /// [`Self::run_fast`] runs as [`Self::run`] while this is on.
+    pub fn set_last_instruction(&mut self, enabled: bool) {
+        if !enabled {
+            self.last_insn = None;
+        } else if self.last_insn.is_none() {
+            self.last_insn = Some(Box::new(LastRecorder::new()));
+        }
+    }
+
+    /// The instruction the last step executed, or `None` if that step
+    /// executed none (it serviced an interrupt, waited or was halted) or
+    /// recording is off.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0xA6, 0x88, 0x10], 0x0400); // LDA $10,X
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.registers_mut().x = 0x2000;
+    /// cpu.set_last_instruction(true);
+    /// cpu.step(&mut ram);
+    ///
+    /// let last = cpu.last_instruction().unwrap();
+    /// assert_eq!(last.pc, 0x0400);
+    /// assert_eq!(last.bytes(), [0xA6, 0x88, 0x10]);
+    /// assert_eq!(last.cycles, 5);
+    /// assert_eq!(last.effective_address, Some(0x2010));
+    /// ```
+    pub fn last_instruction(&self) -> Option<&LastInstruction> {
+        self.last_insn.as_ref()?.last.as_ref()
+    }
+
+    /// Start summing up a run in [`RunStats`].
+    #[cfg(feature = "hooks")]
+    pub(crate) fn begin_run_stats(&mut self) {
+        self.run_stats = Some(Box::new(RunRecorder::new(self.reg.s)));
+    }
+
+    /// The [`RunStats`] since [`Self::begin_run_stats`], with `cycles`.
+    #[cfg(feature = "hooks")]
+    pub(crate) fn end_run_stats(&mut self, cycles: u64) -> RunStats {
+        self.run_stats
+            .take()
+            .map_or_else(RunStats::default, |recorder| recorder.finish(cycles))
+    }
+
+    /// Install or remove the callback offered every SWI, SWI2 and SWI3, for
+    /// semihosting: OS calls emulated in the host, or test harness
+    /// "syscalls". The instruction takes its usual cycles either way.
+    ///
+    /// Writes the hook makes to S through the registers do not arm NMI.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Swi, SwiAction};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x3F, 0x12], 0x0400); // SWI / NOP
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.set_swi_hook(Some(Box::new(|swi, reg, _mem| {
+    ///     assert_eq!(swi, Swi::Swi);
+    ///     reg.set_a(0x42); // the "system call" result
+    ///     SwiAction::Serviced
+    /// })));
+    /// cpu.step(&mut ram);
+    /// assert_eq!(cpu.registers().a(), 0x42);
+    /// assert_eq!(cpu.registers().pc, 0x0401);
+    /// ```
+    pub fn set_swi_hook(&mut self, hook: Option<SwiHook>) {
+        self.swi_hook = hook;
+    }
+
+    /// Install or remove the callback that decodes opcode bytes.
+    ///
+    /// The translator sees every opcode fetch, prefixes included, and the
+    /// CPU executes what it returns; operands, post-bytes and data are read
+    /// as they are. That is how scrambled-opcode derivatives such as
+    /// Konami's KONAMI-1, which XORs each opcode with a pattern taken from
+    /// its address, decode their ROMs. With the decode or block cache on,
+    /// the result must depend only on the arguments.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x12 ^ 0xFF, 0x86 ^ 0xFF, 0x42], 0x0400); // NOP / LDA #$42, opcodes inverted
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.set_opcode_translator(Some(Box::new(|_pc, byte, _fetch| byte ^ 0xFF)));
+    /// cpu.step(&mut ram);
+    /// cpu.step(&mut ram);
+    /// assert_eq!(cpu.registers().a(), 0x42);
+    /// ```
+    pub fn set_opcode_translator(&mut self, translator: Option<OpcodeTranslator>) {
+        self.opcode_translator = translator;
+        self.flush_decode_cache();
+        self.flush_block_cache();
+    }
+
+    /// Install or remove the callback run in place of the built-in handler
+    /// for `opcode`, given with any page prefix in the high byte: 0x86 for
+    /// LDA immediate, 0x103F for SWI2.
+    ///
+    /// The handler runs once the prefix and opcode are fetched, with PC at
+    /// the operands; it fetches those itself, leaves PC wherever execution
+    /// should continue and returns the instruction's total cycle count. The
+    /// page prefixes 0x10 and 0x11 themselves cannot be replaced. A page 0
+    /// handler also runs for the prefixed aliases that undocumented opcodes
+    /// allow (see [`Self::set_undocumented_opcodes`]), one cycle longer,
+    /// unless the prefixed opcode has its own. Handlers apply to this CPU
+    /// only and survive reset.
+    ///
+    /// # Panics
+    ///
+    /// If `opcode` is not on page 0, 1 or 2, or is a page prefix.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x01, 0x42], 0x0400); // undefined 0x01, patched to "LDA #imm"
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.set_opcode_handler(0x01, Some(Box::new(|reg, mem| {
+    ///     reg.set_a(mem.read(reg.pc));
+    ///     reg.pc = reg.pc.wrapping_add(1);
+    ///     2
+    /// })));
+    /// assert_eq!(cpu.step(&mut ram), 2);
+    /// assert_eq!(cpu.registers().a(), 0x42);
+    /// ```
+    pub fn set_opcode_handler(&mut self, opcode: u16, handler: Option<OpcodeHandler>) {
+        assert!(
+            matches!(opcode >> 8, 0x00 | 0x10 | 0x11) && !matches!(opcode, 0x10 | 0x11),
+            "{opcode:#06X} is not an opcode on page 0, 1 or 2"
+        );
+        match handler {
+            Some(handler) => self.opcode_handlers.insert(opcode, handler),
+            None => self.opcode_handlers.remove(&opcode),
+        };
+        // Recorded blocks assume their opcodes fall through.
+        self.flush_block_cache();
     }
 
-    /// Total elapsed cycles since the last [`Self::reset`].
-    pub fn cycles(&self) -> u64 {
-        self.cycles
+    /// Run the handler installed for `opcode` on `page`, if there is one,
+    /// and return its cycles.
+    pub(super) fn run_opcode_handler<M: Memory>(
+        &mut self,
+        mem: &mut M,
+        page: Page,
+        opcode: u8,
+    ) -> Option<u8> {
+        let handler = self
+            .opcode_handlers
+            .get_mut(&(page.prefix() | opcode as u16))?;
+        Some(handler(&mut self.reg, mem))
     }
 
-    /// `true` if the CPU has been halted by a halt instruction.
+    /// Offer an SWI-family instruction to the SWI hook. Returns `true` if
+    /// the hook serviced it and the instruction should not vector.
+    pub(super) fn swi_serviced<M: Memory>(&mut self, swi: Swi, mem: &mut M) -> bool {
+        let serviced = match &mut self.swi_hook {
+            Some(hook) => hook(swi, &mut self.reg, mem) == SwiAction::Serviced,
+            None => false,
+        };
+        #[cfg(feature = "hooks")]
+        if !serviced && (self.interrupt_stats.is_some() || self.run_stats.is_some()) {
+            let source = match swi {
+                Swi::Swi => InterruptSource::Swi,
+                Swi::Swi2 => InterruptSource::Swi2,
+                Swi::Swi3 => InterruptSource::Swi3,
+            };
+            self.record_interrupt(source, self.cycles);
+        }
+        serviced
+    }
+
+    /// Install or remove the callback run before each instruction.
     ///
-    /// Illegal opcodes do not set this flag; they only set [`Self::illegal`]
-    /// so the host can decide whether to keep running or stop.
-    pub fn halted(&self) -> bool {
-        self.halted
+    /// Only available with the `hooks` feature; without it the fetch-execute
+    /// loop carries no instrumentation at all.
+    #[cfg(feature = "hooks")]
+    pub fn set_instruction_hook(&mut self, hook: Option<InstructionHook>) {
+        self.instruction_hook = hook;
     }
 
-    /// Assert or de-assert the halted state.
-    pub fn set_halted(&mut self, active: bool) {
-        self.halted = active;
+    /// Count the instruction about to start and, with the `hooks` feature,
+    /// show it to whatever instrumentation is recording and to the
+    /// instruction hook. Without the feature only the count remains.
+    #[inline(always)]
+    fn before_instruction(&mut self) {
+        // A TFM resumed after each byte is still one instruction.
+        if !self.tfm {
+            self.instructions += 1;
+        }
+        #[cfg(feature = "hooks")]
+        if let Some(coverage) = &mut self.coverage {
+            coverage.insert(self.reg.pc);
+        }
+        #[cfg(feature = "hooks")]
+        if let Some(stats) = &mut self.run_stats {
+            stats.instruction(self.reg.s);
+        }
+        #[cfg(feature = "hooks")]
+        if let Some(hook) = &mut self.instruction_hook {
+            hook(&self.reg, self.cycles);
+        }
     }
 
-    /// Sticky flag set when an illegal opcode is executed.
+    /// Run until at least `cycle_budget` cycles have been consumed.
     ///
-    /// The 6809 keeps running after undefined opcodes, so this flag does not
-    /// halt the CPU by itself. Hosts that want trap-like behaviour can check
-    /// this flag after each [`Self::step`] and stop on their own policy.
-    pub fn illegal(&self) -> bool {
-        self.illegal
+    /// This method stops only when the cycle budget is exhausted,
+    /// [`Self::halted`] becomes true or a [`ControlHandle`] pauses it.
+    /// Illegal opcodes stop `run` only under [`IllegalOpcodePolicy::Halt`].
+    pub fn run(&mut self, mem: &mut impl Memory, cycle_budget: u64) -> u64 {
+        let start_cycles = self.cycles;
+        let target = self.cycles + cycle_budget;
+        while self.cycles < target && !self.halted && !self.paused() {
+            self.step(mem);
+        }
+        self.cycles - start_cycles
     }
 
-    /// Clear the illegal opcode flag.
-    pub fn clear_illegal(&mut self) {
-        self.illegal = false;
+    /// A handle another thread can use to pause the run loops. Every call
+    /// returns a handle to the same flag.
+    pub fn control_handle(&mut self) -> ControlHandle {
+        self.control
+            .get_or_insert_with(ControlHandle::default)
+            .clone()
     }
 
-    /// Assert or de-assert the IRQ line (level-triggered).
+    /// Whether a [`ControlHandle`] has paused the run loops.
+    pub fn paused(&self) -> bool {
+        self.control.as_ref().is_some_and(ControlHandle::is_paused)
+    }
+
+    /// Take `n` steps, or fewer if the CPU halts or is paused, and return
+    /// the cycles they took.
     ///
-    /// The CPU samples this each step. Only the peripheral should de-assert it
-    /// (by calling `set_irq(false)`); the CPU never clears it internally.
-    pub fn set_irq(&mut self, active: bool) {
-        if active {
-            self.int_lines.insert(BusSignals::IRQ);
-        } else {
-            self.int_lines.remove(BusSignals::IRQ);
+    /// Each step is one [`Self::step`], so it may service an interrupt or
+    /// wait rather than execute an instruction; compare
+    /// [`Self::instructions_retired`] to count instructions.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x12, 0x12, 0x12], 0x0400); // NOP / NOP / NOP
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    ///
+    /// assert_eq!(cpu.step_n(&mut ram, 3), 6);
+    /// assert_eq!(cpu.instructions_retired(), 3);
+    /// ```
+    pub fn step_n<M: Memory>(&mut self, mem: &mut M, n: u64) -> u64 {
+        let start_cycles = self.cycles;
+        for _ in 0..n {
+            if self.halted || self.paused() {
+                break;
+            }
+            self.step(mem);
         }
+        self.cycles - start_cycles
     }
 
-    /// Assert or de-assert the FIRQ line (level-triggered).
+    /// [`Self::run`] a budget of cycles, carrying any overshoot into the
+    /// next call so that a fixed-rate loop stays cycle-exact.
     ///
-    /// The CPU samples this each step. Only the peripheral should de-assert it
-    /// (by calling `set_firq(false)`); the CPU never clears it internally.
-    pub fn set_firq(&mut self, active: bool) {
-        if active {
-            self.int_lines.insert(BusSignals::FIRQ);
-        } else {
-            self.int_lines.remove(BusSignals::FIRQ);
+    /// Budgets are laid end to end on [`Self::cycles`]: each ends
+    /// `cycle_budget` after the previous one ended, not after the last
+    /// instruction of the previous call, so the cycles an instruction runs
+    /// past the end are taken out of the next budget (see
+    /// [`Self::cycle_debt`]). If the CPU has run past the next end by other
+    /// means, the budget starts from the current cycle count instead.
+    /// Returns the cycles consumed, and returns early if the CPU halts or is
+    /// paused.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x20, 0xFE], 0x0400); // BRA *, 3 cycles
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    ///
+    /// assert_eq!(cpu.run_budgeted(&mut ram, 10), 12);
+    /// assert_eq!(cpu.cycle_debt(), 2);
+    /// assert_eq!(cpu.run_budgeted(&mut ram, 10), 9);
+    /// // 10 calls of 10 cycles take 100 cycles, give or take one instruction.
+    /// for _ in 2..10 {
+    ///     cpu.run_budgeted(&mut ram, 10);
+    /// }
+    /// assert_eq!(cpu.cycles() - cpu.cycle_debt(), 100);
+    /// ```
+    pub fn run_budgeted<M: Memory>(&mut self, mem: &mut M, cycle_budget: u64) -> u64 {
+        let start_cycles = self.cycles;
+        let start = match self.budget_end {
+            Some(end) if end + cycle_budget > self.cycles => end,
+            _ => self.cycles,
+        };
+        let end = start + cycle_budget;
+        while self.cycles < end && !self.halted && !self.paused() {
+            self.step(mem);
         }
+        self.budget_end = Some(end);
+        self.cycles - start_cycles
     }
 
-    /// Trigger an NMI (edge-triggered). Only effective if NMI is armed.
-    pub fn trigger_nmi(&mut self) {
-        if self.nmi_armed {
-            self.int_lines.insert(BusSignals::NMI);
+    /// Cycles the last [`Self::run_budgeted`] ran past the end of its
+    /// budget, to be taken out of the next.
+    pub fn cycle_debt(&self) -> u64 {
+        self.budget_end
+            .map_or(0, |end| self.cycles.saturating_sub(end))
+    }
+
+    /// [`Self::run`], summing the run up in [`RunStats`]. Breakpoints are
+    /// counted where [`Self::try_step`] would stop at them, but do not stop
+    /// the run.
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn run_with_stats(&mut self, mem: &mut impl Memory, cycle_budget: u64) -> RunStats {
+        self.begin_run_stats();
+        let start_cycles = self.cycles;
+        let target = self.cycles + cycle_budget;
+        while self.cycles < target && !self.halted && !self.paused() {
+            let pc = self.reg.pc;
+            self.at_breakpoint();
+            self.step(mem);
+            self.left_breakpoint(pc);
         }
+        self.end_run_stats(self.cycles - start_cycles)
     }
 
-    /// Apply a snapshot of bus signals to the CPU, handling NMI edge detection.
+    /// Run until `done` returns `true`, checked before each instruction,
+    /// or until [`Self::run`] would stop.
     ///
-    /// Call this from the host loop whenever [`BusSignals`] change. Passing the
-    /// previous snapshot allows the CPU to detect the NMI rising edge internally,
-    /// so the caller does not need to track edge transitions for NMI.
+    /// `done` is given the CPU and `mem`, so it can wait for a flag in
+    /// memory as well as for a register value; its reads go to the bus like
+    /// any other. Returns `true` if `done` ended the run and `false` if the
+    /// budget ran out or the CPU halted first. A predicate already true at
+    /// the start executes nothing.
     ///
-    /// IRQ and FIRQ are level-triggered: their state is mirrored directly into
-    /// the CPU. The CPU will hold the line until the peripheral de-asserts it
-    /// (i.e. returns a snapshot without `IRQ`/`FIRQ` set on a subsequent tick).
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
     ///
-    /// RESET is not handled here; the host loop is responsible for calling
-    /// [`Cpu::reset`] when `signals` contains [`BusSignals::RESET`].
+    /// let mut ram = FlatRam::new();
+    /// // LDB #$03 / DECB / BNE *-1 / STB $2000 / BRA *
+    /// ram.load(&[0xC6, 0x03, 0x5A, 0x26, 0xFD, 0xF7, 0x20, 0x00, 0x20, 0xFE], 0x0400);
+    /// ram.write(0x2000, 0xFF);
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
     ///
-    /// # Host loop pattern
-    /// ```ignore
-    /// let mut prev_signals = BusSignals::default();
-    /// loop {
-    ///     let cycles = cpu.step(&mut mem);
-    ///     let signals = peripheral.tick(cycles);
+    /// assert!(cpu.run_until(&mut ram, 1000, |cpu, _| cpu.registers().b() == 0));
+    /// assert!(cpu.run_until(&mut ram, 1000, |_, ram| ram.read(0x2000) == 0));
+    /// assert_eq!(cpu.registers().pc, 0x0408);
+    /// assert!(!cpu.run_until(&mut ram, 100, |cpu, _| cpu.registers().b() != 0));
+    /// ```
+    pub fn run_until<M: Memory>(
+        &mut self,
+        mem: &mut M,
+        max_cycles: u64,
+        mut done: impl FnMut(&Cpu, &mut M) -> bool,
+    ) -> bool {
+        let target = self.cycles + max_cycles;
+        loop {
+            if done(self, mem) {
+                return true;
+            }
+            if self.cycles >= target || self.halted || self.paused() {
+                return false;
+            }
+            self.step(mem);
+        }
+    }
+
+    /// Run until the cycle budget is used up or something else stops the
+    /// CPU, and say which.
     ///
-    ///     if signals.contains(BusSignals::RESET) {
-    ///         cpu.reset(&mut mem);
-    ///         prev_signals = BusSignals::default();
-    ///         continue;
-    ///     }
+    /// Before each instruction this stops if the CPU is halted or paused
+    /// (see [`ControlHandle`]), the budget is spent, the CPU waits in CWAI
+    /// or SYNC with nothing to wake it, or PC is at a breakpoint (run past
+    /// on the next call, as with [`Self::try_step`]). After each
+    /// instruction it stops if the instruction was illegal or wrote to a
+    /// watchpoint (see [`Self::add_watchpoint`]). The cycles consumed are
+    /// the change in [`Self::cycles`].
     ///
-    ///     if signals != prev_signals {
-    ///         cpu.apply_signals(signals, prev_signals);
-    ///         prev_signals = signals;
-    ///     }
+    /// # Example
+    /// ```
+    /// use mc6809_core::{Cpu, FlatRam, Memory, StopReason};
     ///
-    ///     if cpu.halted() { break; }
-    /// }
+    /// let mut ram = FlatRam::new();
+    /// // LDA #$01 / STA $2000 / BRA *
+    /// ram.load(&[0x86, 0x01, 0xB7, 0x20, 0x00, 0x20, 0xFE], 0x0400);
+    /// ram.set_reset_vector(0x0400);
+    /// let mut cpu = Cpu::new();
+    /// cpu.reset(&mut ram);
+    /// cpu.add_watchpoint(0x2000);
+    ///
+    /// assert_eq!(
+    ///     cpu.run_until_stop(&mut ram, 100),
+    ///     StopReason::Watchpoint { pc: 0x0402, addr: 0x2000 }
+    /// );
+    /// assert_eq!(cpu.run_until_stop(&mut ram, 100), StopReason::CycleBudgetExhausted);
     /// ```
-    pub fn apply_signals(&mut self, signals: BusSignals, prev: BusSignals) {
-        // NMI: edge-triggered — arm on rising edge only
-        if signals.contains(BusSignals::NMI) && !prev.contains(BusSignals::NMI) {
-            self.trigger_nmi();
-        }
-        // IRQ/FIRQ: level-triggered — mirror current pin state
-        if signals.contains(BusSignals::FIRQ) {
-            self.int_lines.insert(BusSignals::FIRQ);
-        } else {
-            self.int_lines.remove(BusSignals::FIRQ);
-        }
-        if signals.contains(BusSignals::IRQ) {
-            self.int_lines.insert(BusSignals::IRQ);
-        } else {
-            self.int_lines.remove(BusSignals::IRQ);
-        }
+    pub fn run_until_stop<M: Memory>(&mut self, mem: &mut M, cycle_budget: u64) -> StopReason {
+        let target = self.cycles + cycle_budget;
+        let watchpoints = std::mem::take(&mut self.watchpoints);
+        let reason = loop {
+            if self.halted {
+                break StopReason::Halted;
+            }
+            if self.paused() {
+                break StopReason::Paused;
+            }
+            if self.cycles >= target {
+                break StopReason::CycleBudgetExhausted;
+            }
+            self.sample_interrupt_handle();
+            if self.cwai && !self.interrupt_pending() {
+                break StopReason::CwaiWaiting;
+            }
+            if self.sync && self.int_lines.is_empty() {
+                break StopReason::SyncWaiting;
+            }
+            let pc = self.reg.pc;
+            if self.at_breakpoint() {
+                break StopReason::Breakpoint { pc };
+            }
+
+            // The sticky flag is cleared around the step to see whether this
+            // instruction set it.
+            let was_illegal = std::mem::take(&mut self.illegal);
+            let hit = if watchpoints.is_empty() {
+                self.step(mem);
+                None
+            } else {
+                let mut watched = WatchpointMemory::new(mem, &watchpoints);
+                self.step(&mut watched);
+                watched.hit
+            };
+            let illegal = self.illegal;
+            self.illegal |= was_illegal;
+            self.left_breakpoint(pc);
+
+            if illegal {
+                break StopReason::Illegal { pc };
+            }
+            if let Some(addr) = hit {
+                break StopReason::Watchpoint { pc, addr };
+            }
+        };
+        self.watchpoints = watchpoints;
+        reason
     }
 
-    /// Execute a single instruction (or handle a pending interrupt).
-    /// Returns the number of cycles consumed.
+    /// [`Self::step`] through a trait object.
     ///
-    /// If the decoded instruction is illegal, the CPU records that in
-    /// [`Self::illegal`] and continues execution unless the caller chooses to
-    /// stop.
-    pub fn step(&mut self, mem: &mut impl Memory) -> u64 {
-        if self.halted {
-            return 1;
+    /// The generic methods are monomorphized per memory type; frontends that
+    /// switch between several memory types at runtime can use this entry point
+    /// to share a single instantiation at the cost of a virtual call per bus
+    /// access.
+    pub fn step_dyn(&mut self, mut mem: &mut dyn Memory) -> u64 {
+        self.step(&mut mem)
+    }
+
+    /// [`Self::run`] through a trait object. See [`Self::step_dyn`].
+    pub fn run_dyn(&mut self, mut mem: &mut dyn Memory, cycle_budget: u64) -> u64 {
+        self.run(&mut mem, cycle_budget)
+    }
+
+    /// Run until at least `cycle_budget` cycles have been consumed, without
+    /// the per-step bookkeeping of [`Self::step`].
+    ///
+    /// Intended for frontends that only synchronise with peripherals every few
+    /// thousand cycles. While no interrupt line is active and the CPU is not in
+    /// SYNC or CWAI, instructions are fetched and dispatched directly; anything
+    /// else falls back to [`Self::step`]. Stops on the same conditions as
+    /// [`Self::run`] and returns the number of cycles consumed.
+    ///
+    /// With the block cache enabled ([`Self::set_block_cache`]) straight-line
+    /// code is replayed from recorded blocks; with the decode cache enabled
+    /// ([`Self::set_decode_cache`]) each instruction is dispatched from it.
+    pub fn run_fast<M: Memory>(&mut self, mem: &mut M, cycle_budget: u64) -> u64 {
+        if self.code_watch.is_some() || self.last_insn.is_some() {
+            return self.run(mem, cycle_budget);
+        }
+        if self.decode_cache.is_some() {
+            return self.run_decoded(mem, cycle_budget);
+        }
+        if self.block_cache.is_some() {
+            return self.run_blocks(mem, cycle_budget);
         }
 
         let start_cycles = self.cycles;
-
-        // Handle SYNC state: wait for any interrupt edge
-        if self.sync {
-            if !self.int_lines.is_empty() {
-                self.sync = false;
+        let target = self.cycles + cycle_budget;
+        while self.cycles < target && !self.halted && !self.paused() {
+            self.sample_interrupt_handle();
+            if self.int_lines.is_empty() && !self.sync && !self.cwai && !self.dma_breq {
+                self.before_instruction();
+                let opcode = self.fetch_opcode(mem, OpcodeFetch::First);
+                self.cycles += self.execute(mem, opcode) as u64;
             } else {
-                self.cycles += 1;
-                return 1;
+                self.step(mem);
             }
         }
+        self.cycles - start_cycles
+    }
 
-        // Handle CWAI state: entire state already pushed, waiting for a
-        // serviceable interrupt (NMI is always serviceable; FIRQ/IRQ respect masks).
-        if self.cwai {
-            let serviceable = self.int_lines.contains(BusSignals::NMI)
-                || (self.int_lines.contains(BusSignals::FIRQ) && !self.reg.cc.firq_inhibit())
-                || (self.int_lines.contains(BusSignals::IRQ) && !self.reg.cc.irq_inhibit());
-            if !serviceable {
-                self.cycles += 1;
-                return 1;
-            }
-        }
+    /// [`Self::run_fast`] with the decode cache in front of `mem`.
+    fn run_decoded<M: Memory>(&mut self, mem: &mut M, cycle_budget: u64) -> u64 {
+        let Some(mut cache) = self.decode_cache.take() else {
+            return self.run_fast(mem, cycle_budget);
+        };
 
-        // Check pending interrupts (priority: NMI > FIRQ > IRQ)
-        if self.check_interrupts(mem) {
-            return self.cycles - start_cycles;
+        let start_cycles = self.cycles;
+        let target = self.cycles + cycle_budget;
+        while self.cycles < target && !self.halted && !self.paused() {
+            self.sample_interrupt_handle();
+            if self.int_lines.is_empty() && !self.sync && !self.cwai && !self.dma_breq {
+                self.execute_cached(&mut cache, mem);
+            } else {
+                self.step_through(&mut cache, mem);
+            }
         }
 
-        // Fetch and execute one instruction
-        let opcode = self.fetch_byte(mem);
-        self.execute(mem, opcode);
-
+        self.decode_cache = Some(cache);
         self.cycles - start_cycles
     }
 
-    /// Run until at least `cycle_budget` cycles have been consumed.
-    ///
-    /// This method stops only when the cycle budget is exhausted or
-    /// [`Self::halted`] becomes true. Illegal opcodes do not stop `run`; check
-    /// [`Self::illegal`] in the host loop if that policy is desired.
-    pub fn run(&mut self, mem: &mut impl Memory, cycle_budget: u64) -> u64 {
+    /// [`Self::run_fast`] with the block cache in front of `mem`.
+    fn run_blocks<M: Memory>(&mut self, mem: &mut M, cycle_budget: u64) -> u64 {
+        let Some(mut cache) = self.block_cache.take() else {
+            return self.run_fast(mem, cycle_budget);
+        };
+
         let start_cycles = self.cycles;
         let target = self.cycles + cycle_budget;
-        while self.cycles < target && !self.halted {
-            self.step(mem);
+        while self.cycles < target && !self.halted && !self.paused() {
+            self.sample_interrupt_handle();
+            if !self.int_lines.is_empty() || self.sync || self.cwai || self.dma_breq {
+                self.step_uncached(&mut cache.bind(mem));
+            } else if let Some(block) = cache.take(self.reg.pc) {
+                self.run_block(&mut cache, mem, block, target);
+            } else {
+                self.record_block(&mut cache, mem, target);
+            }
         }
+
+        self.block_cache = Some(cache);
         self.cycles - start_cycles
     }
 
+    /// Replay `block`, which starts at PC, followed by the instruction that
+    /// ended it. The block is dropped if it overwrites itself.
+    fn run_block<M: Memory>(
+        &mut self,
+        cache: &mut BlockCache,
+        mem: &mut M,
+        block: Block,
+        target: u64,
+    ) {
+        let start = self.reg.pc;
+        let mut mem = cache.bind(mem);
+        mem.watch(start, block.len);
+        for insn in &block.insns {
+            self.before_instruction();
+            self.reg.pc = self.reg.pc.wrapping_add(insn.page.opcode_len());
+            self.cycles += self.execute_on_page(&mut mem, insn.page, insn.opcode) as u64;
+            if mem.watched_written() {
+                return;
+            }
+            if self.cycles >= target || self.halted || self.paused() {
+                cache.restore(start, block);
+                return;
+            }
+        }
+        // Run the instruction that ended the block without another lookup.
+        self.before_instruction();
+        let opcode = self.fetch_opcode(&mut mem, OpcodeFetch::First);
+        self.cycles += self.execute(&mut mem, opcode) as u64;
+        if !mem.watched_written() {
+            cache.restore(start, block);
+        }
+    }
+
+    /// Execute instructions from PC up to and including the next one that
+    /// ends a block, recording the ones before it as a new block.
+    fn record_block<M: Memory>(&mut self, cache: &mut BlockCache, mem: &mut M, target: u64) {
+        let start = self.reg.pc;
+        let mut insns = Vec::new();
+        let mut len = 0;
+        let mut mem = cache.bind(mem);
+        loop {
+            // Also covers the bytes of the instruction about to run.
+            mem.watch(start, len + MAX_INSN_LEN);
+            self.before_instruction();
+            let (page, opcode) = match self.fetch_opcode(&mut mem, OpcodeFetch::First) {
+                0x10 => (Page::Page1, self.fetch_opcode(&mut mem, OpcodeFetch::Page1)),
+                0x11 => (Page::Page2, self.fetch_opcode(&mut mem, OpcodeFetch::Page2)),
+                op => (Page::Page0, op),
+            };
+            // Illegal opcodes may halt, trap or call out depending on the
+            // policy, so they end the block too; the sticky flag is cleared
+            // around the instruction to tell whether it was one.
+            let was_illegal = std::mem::take(&mut self.illegal);
+            self.cycles += self.execute_on_page(&mut mem, page, opcode) as u64;
+            let illegal = self.illegal;
+            self.illegal |= was_illegal;
+            if mem.watched_written() {
+                return;
+            }
+            let replaced = self
+                .opcode_handlers
+                .contains_key(&(page.prefix() | opcode as u16));
+            if illegal || self.halted || replaced || ends_block(page, opcode) {
+                break;
+            }
+            insns.push(Insn { page, opcode });
+            len = self.reg.pc.wrapping_sub(start);
+            if insns.len() == MAX_BLOCK_INSNS || self.cycles >= target || self.paused() {
+                break;
+            }
+        }
+        if !insns.is_empty() {
+            let block = Block {
+                insns: insns.into_boxed_slice(),
+                len,
+            };
+            cache.insert(start, block);
+        }
+    }
+
     // ---- interrupt logic ----
 
-    fn check_interrupts(&mut self, mem: &mut impl Memory) -> bool {
+    /// Service the highest priority pending interrupt, if any, and return
+    /// its source.
+    fn check_interrupts(&mut self, mem: &mut impl Memory) -> Option<InterruptSource> {
         if self.int_lines.is_empty() {
-            return false;
+            return None;
         }
+        #[cfg(feature = "hooks")]
+        let start_cycles = self.cycles;
 
         // NMI (edge-triggered, highest priority): clear the latch on service.
         if self.int_lines.contains(BusSignals::NMI) {
@@ -318,24 +2578,40 @@ impl Cpu {
             self.cwai = false;
             self.reg.cc.set_irq_inhibit(true);
             self.reg.cc.set_firq_inhibit(true);
-            self.reg.pc = mem.read_word(VEC_NMI);
-            self.cycles += 19;
-            return true;
+            self.reg.pc = mem.read_word(self.vectors.nmi);
+            self.cycles += self.entire_state_cycles(19);
+            diag::log_trace!("NMI taken, PC={=u16:04X}", self.reg.pc);
+            #[cfg(feature = "hooks")]
+            self.record_interrupt(InterruptSource::Nmi, start_cycles);
+            return Some(InterruptSource::Nmi);
         }
 
         // FIRQ (level-triggered): do NOT clear — only the peripheral de-asserts.
+        // A 6309 can be set to save the entire state for it, as for IRQ.
         if self.int_lines.contains(BusSignals::FIRQ) && !self.reg.cc.firq_inhibit() {
+            let entire = self.firq_as_irq();
             if !self.cwai {
-                self.reg.cc.set_entire(false);
-                self.push_word_s(mem, self.reg.pc);
-                self.push_byte_s(mem, self.reg.cc.to_byte());
+                self.reg.cc.set_entire(entire);
+                if entire {
+                    self.push_entire_state(mem);
+                } else {
+                    self.push_word_s(mem, self.reg.pc);
+                    self.push_byte_s(mem, self.reg.cc.to_byte());
+                }
             }
             self.cwai = false;
             self.reg.cc.set_irq_inhibit(true);
             self.reg.cc.set_firq_inhibit(true);
-            self.reg.pc = mem.read_word(VEC_FIRQ);
-            self.cycles += 10;
-            return true;
+            self.reg.pc = mem.read_word(self.vectors.firq);
+            self.cycles += if entire {
+                self.entire_state_cycles(19)
+            } else {
+                10
+            };
+            diag::log_trace!("FIRQ taken, PC={=u16:04X}", self.reg.pc);
+            #[cfg(feature = "hooks")]
+            self.record_interrupt(InterruptSource::Firq, start_cycles);
+            return Some(InterruptSource::Firq);
         }
 
         // IRQ (level-triggered): do NOT clear — only the peripheral de-asserts.
@@ -346,12 +2622,15 @@ impl Cpu {
             }
             self.cwai = false;
             self.reg.cc.set_irq_inhibit(true);
-            self.reg.pc = mem.read_word(VEC_IRQ);
-            self.cycles += 19;
-            return true;
+            self.reg.pc = mem.read_word(self.vectors.irq);
+            self.cycles += self.entire_state_cycles(19);
+            diag::log_trace!("IRQ taken, PC={=u16:04X}", self.reg.pc);
+            #[cfg(feature = "hooks")]
+            self.record_interrupt(InterruptSource::Irq, start_cycles);
+            return Some(InterruptSource::Irq);
         }
 
-        false
+        None
     }
 
     // ---- stack helpers ----
@@ -359,12 +2638,18 @@ impl Cpu {
     /// Push a byte onto the hardware stack (S).
     pub(super) fn push_byte_s(&mut self, mem: &mut impl Memory, val: u8) {
         self.reg.s = self.reg.s.wrapping_sub(1);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::S, StackAccess::Push, self.reg.s, 1);
+        }
         mem.write(self.reg.s, val);
     }
 
     /// Push a 16-bit word onto the hardware stack (S), low byte first.
     pub(super) fn push_word_s(&mut self, mem: &mut impl Memory, val: u16) {
         self.reg.s = self.reg.s.wrapping_sub(2);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::S, StackAccess::Push, self.reg.s, 2);
+        }
         mem.write_word(self.reg.s, val);
     }
 
@@ -372,6 +2657,9 @@ impl Cpu {
     pub(super) fn pull_byte_s(&mut self, mem: &mut impl Memory) -> u8 {
         let val = mem.read(self.reg.s);
         self.reg.s = self.reg.s.wrapping_add(1);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::S, StackAccess::Pull, self.reg.s.wrapping_sub(1), 1);
+        }
         val
     }
 
@@ -379,18 +2667,27 @@ impl Cpu {
     pub(super) fn pull_word_s(&mut self, mem: &mut impl Memory) -> u16 {
         let val = mem.read_word(self.reg.s);
         self.reg.s = self.reg.s.wrapping_add(2);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::S, StackAccess::Pull, self.reg.s.wrapping_sub(2), 2);
+        }
         val
     }
 
     /// Push a byte onto the user stack (U).
     pub(super) fn push_byte_u(&mut self, mem: &mut impl Memory, val: u8) {
         self.reg.u = self.reg.u.wrapping_sub(1);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::U, StackAccess::Push, self.reg.u, 1);
+        }
         mem.write(self.reg.u, val);
     }
 
     /// Push a 16-bit word onto the user stack (U).
     pub(super) fn push_word_u(&mut self, mem: &mut impl Memory, val: u16) {
         self.reg.u = self.reg.u.wrapping_sub(2);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::U, StackAccess::Push, self.reg.u, 2);
+        }
         mem.write_word(self.reg.u, val);
     }
 
@@ -398,6 +2695,9 @@ impl Cpu {
     pub(super) fn pull_byte_u(&mut self, mem: &mut impl Memory) -> u8 {
         let val = mem.read(self.reg.u);
         self.reg.u = self.reg.u.wrapping_add(1);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::U, StackAccess::Pull, self.reg.u.wrapping_sub(1), 1);
+        }
         val
     }
 
@@ -405,17 +2705,123 @@ impl Cpu {
     pub(super) fn pull_word_u(&mut self, mem: &mut impl Memory) -> u16 {
         let val = mem.read_word(self.reg.u);
         self.reg.u = self.reg.u.wrapping_add(2);
+        if self.stack_monitor.is_some() {
+            self.check_stack(Stack::U, StackAccess::Pull, self.reg.u.wrapping_sub(2), 2);
+        }
         val
     }
 
+    /// Check a stack access of `len` bytes at `addr` against the stack
+    /// monitor and act on a violation.
+    #[cold]
+    fn check_stack(&mut self, stack: Stack, access: StackAccess, addr: u16, len: u16) {
+        let Some(monitor) = &mut self.stack_monitor else {
+            return;
+        };
+        let (own, other, other_ptr) = match stack {
+            Stack::S => (&monitor.s, &monitor.u, self.reg.u),
+            Stack::U => (&monitor.u, &monitor.s, self.reg.s),
+        };
+        let last = addr.wrapping_add(len - 1);
+        let outside = own
+            .as_ref()
+            .is_some_and(|r| !r.contains(&addr) || !r.contains(&last));
+        let collision = access == StackAccess::Push
+            && other
+                .as_ref()
+                .is_some_and(|r| last >= other_ptr && addr <= *r.end() && r.contains(&other_ptr));
+        if !outside && !collision {
+            return;
+        }
+        let violation = StackViolation {
+            stack,
+            access,
+            addr,
+            collision: !outside,
+        };
+        match &mut monitor.action {
+            StackAction::Halt => {
+                diag::log_info!("halted on a stack violation at {=u16:04X}", addr);
+                self.halted = true;
+            }
+            StackAction::Trap => {
+                self.stack_fault.get_or_insert(violation);
+            }
+            StackAction::Callback(hook) => hook(violation, &self.reg),
+        }
+    }
+
+    /// Record an illegal opcode and apply the policy. `len` is the number of
+    /// bytes fetched for it (prefix and opcode). Returns the extra cycles.
+    pub(super) fn illegal_opcode(&mut self, mem: &mut impl Memory, len: u16) -> u8 {
+        self.illegal = true;
+        diag::log_warn!(
+            "illegal opcode at {=u16:04X}",
+            self.reg.pc.wrapping_sub(len)
+        );
+        match &mut self.illegal_policy {
+            IllegalOpcodePolicy::Hardware if self.variant == CpuVariant::Hd6309 => {
+                self.hd6309_trap(mem, MD_IL);
+                // 20 cycles in all, against a base of 1 on page 0 and 2 on
+                // pages 1 and 2.
+                self.entire_state_cycles(20 - len as u64) as u8
+            }
+            IllegalOpcodePolicy::Hardware | IllegalOpcodePolicy::TreatAsNop => 0,
+            IllegalOpcodePolicy::Halt => {
+                diag::log_info!("halted on an illegal opcode");
+                self.halted = true;
+                0
+            }
+            IllegalOpcodePolicy::Trap => {
+                let addr = self.reg.pc.wrapping_sub(len);
+                self.illegal_fault.get_or_insert(addr);
+                0
+            }
+            IllegalOpcodePolicy::Callback(hook) => {
+                let addr = self.reg.pc.wrapping_sub(len);
+                hook(&mut self.reg, addr);
+                0
+            }
+            IllegalOpcodePolicy::TrapToVector(vector) => {
+                let vector = *vector;
+                self.trap_to(mem, vector);
+                // SWI takes 19 cycles against a base of 1 on page 0, and
+                // SWI2/SWI3 20 against a base of 2 on pages 1 and 2.
+                self.entire_state_cycles(18) as u8
+            }
+        }
+    }
+
+    /// Stack the entire state, mask IRQ and FIRQ and load PC from `vector`,
+    /// as SWI does.
+    fn trap_to(&mut self, mem: &mut impl Memory, vector: u16) {
+        self.reg.cc.set_entire(true);
+        self.push_entire_state(mem);
+        self.reg.cc.set_irq_inhibit(true);
+        self.reg.cc.set_firq_inhibit(true);
+        self.reg.pc = mem.read_word(vector);
+    }
+
+    /// Take the HD6309 trap for `status` (`MD_IL` or `MD_DZ`): set it in MD
+    /// and trap through [`VectorTable::trap`].
+    pub(super) fn hd6309_trap(&mut self, mem: &mut impl Memory, status: u8) {
+        self.reg6309.md |= status;
+        self.trap_to(mem, self.vectors.trap);
+    }
+
     /// Push the entire register state onto S (used by NMI, IRQ, SWI).
     /// Order: CC, A, B, DP, X, Y, U, PC (PC pushed first = highest address).
+    /// A 6309 in native mode has E and F between B and DP.
     pub(super) fn push_entire_state(&mut self, mem: &mut impl Memory) {
         self.push_word_s(mem, self.reg.pc);
         self.push_word_s(mem, self.reg.u);
         self.push_word_s(mem, self.reg.y);
         self.push_word_s(mem, self.reg.x);
         self.push_byte_s(mem, self.reg.dp);
+        if self.native_mode() {
+            self.push_byte_s(mem, self.reg6309.f());
+            self.push_byte_s(mem, self.reg6309.e());
+        }
         self.push_byte_s(mem, self.reg.b());
         self.push_byte_s(mem, self.reg.a());
         self.push_byte_s(mem, self.reg.cc.to_byte());
@@ -427,44 +2833,74 @@ impl Cpu {
     pub(super) fn fetch_byte(&mut self, mem: &mut impl Memory) -> u8 {
         let val = mem.read(self.reg.pc);
         self.reg.pc = self.reg.pc.wrapping_add(1);
+        if let Some(recorder) = &mut self.last_insn {
+            recorder.fetch(val);
+        }
         val
     }
 
+    /// Fetch an opcode byte from [PC] and advance PC, passing it through the
+    /// opcode translator if there is one.
+    pub(super) fn fetch_opcode(&mut self, mem: &mut impl Memory, fetch: OpcodeFetch) -> u8 {
+        let pc = self.reg.pc;
+        let byte = self.fetch_byte(mem);
+        match &mut self.opcode_translator {
+            Some(translate) => translate(pc, byte, fetch),
+            None => byte,
+        }
+    }
+
     /// Fetch a big-endian 16-bit word from [PC] and advance PC by 2.
     pub(super) fn fetch_word(&mut self, mem: &mut impl Memory) -> u16 {
-        let val = mem.read_word(self.reg.pc);
+        let mut bytes = [0; 2];
+        mem.read_slice(self.reg.pc, &mut bytes);
         self.reg.pc = self.reg.pc.wrapping_add(2);
-        val
+        if let Some(recorder) = &mut self.last_insn {
+            recorder.fetch(bytes[0]);
+            recorder.fetch(bytes[1]);
+        }
+        u16::from_be_bytes(bytes)
     }
 
     // ---- addressing mode helpers ----
 
+    /// Note the effective address for [`Self::last_instruction`].
+    #[inline(always)]
+    fn effective_address(&mut self, ea: u16) -> u16 {
+        if let Some(recorder) = &mut self.last_insn {
+            recorder.current.effective_address = Some(ea);
+        }
+        ea
+    }
+
     /// Direct addressing: DP:fetch_byte → effective address.
     pub(super) fn addr_direct(&mut self, mem: &mut impl Memory) -> u16 {
         let lo = self.fetch_byte(mem) as u16;
-        ((self.reg.dp as u16) << 8) | lo
+        self.effective_address(((self.reg.dp as u16) << 8) | lo)
     }
 
     /// Extended addressing: fetch 16-bit absolute address.
     pub(super) fn addr_extended(&mut self, mem: &mut impl Memory) -> u16 {
-        self.fetch_word(mem)
+        let ea = self.fetch_word(mem);
+        self.effective_address(ea)
     }
 
     /// Indexed addressing: decode post-byte and return (effective_address, extra_cycles).
     pub(super) fn addr_indexed(&mut self, mem: &mut impl Memory) -> (u16, u8) {
-        crate::addressing::indexed(self, mem)
+        let (ea, extra) = crate::addressing::indexed(self, mem);
+        (self.effective_address(ea), extra)
     }
 
     /// Relative 8-bit: signed offset from current PC.
     pub(super) fn addr_relative8(&mut self, mem: &mut impl Memory) -> u16 {
         let offset = self.fetch_byte(mem) as i8 as i16 as u16;
-        self.reg.pc.wrapping_add(offset)
+        self.effective_address(self.reg.pc.wrapping_add(offset))
     }
 
     /// Relative 16-bit: signed offset from current PC.
     pub(super) fn addr_relative16(&mut self, mem: &mut impl Memory) -> u16 {
         let offset = self.fetch_word(mem);
-        self.reg.pc.wrapping_add(offset)
+        self.effective_address(self.reg.pc.wrapping_add(offset))
     }
 
     /// Arm the NMI (called when S is first written to).
@@ -473,12 +2909,73 @@ impl Cpu {
     }
 }
 
+// A `Cpu` must stay `Send` so instances can be moved to worker threads
+// (see `crate::batch`).
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Cpu>();
+};
+
 impl Default for Cpu {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Saves the registers and the cycle, halt, interrupt and DMA state.
+/// Configuration (caches, the instruction hook) is left alone, but both caches
+/// are flushed on load since memory is normally restored alongside.
+impl Snapshot for Cpu {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.reg.snapshot(out);
+        if self.variant == CpuVariant::Hd6309 {
+            self.reg6309.snapshot(out);
+            out.bool(self.tfm);
+        }
+        out.u64(self.cycles);
+        for flag in [
+            self.halted,
+            self.illegal,
+            self.nmi_armed,
+            self.cwai,
+            self.sync,
+            self.dma_breq,
+            self.bus_granted,
+        ] {
+            out.bool(flag);
+        }
+        self.int_lines.snapshot(out);
+        out.u8(self.dma_stolen);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.reg.restore(state)?;
+        if self.variant == CpuVariant::Hd6309 {
+            self.reg6309.restore(state)?;
+            self.tfm = state.bool()?;
+        }
+        self.cycles = state.u64()?;
+        self.budget_end = None;
+        self.tick_left = 0;
+        for flag in [
+            &mut self.halted,
+            &mut self.illegal,
+            &mut self.nmi_armed,
+            &mut self.cwai,
+            &mut self.sync,
+            &mut self.dma_breq,
+            &mut self.bus_granted,
+        ] {
+            *flag = state.bool()?;
+        }
+        self.int_lines.restore(state)?;
+        self.dma_stolen = state.u8()?;
+        self.flush_decode_cache();
+        self.flush_block_cache();
+        Ok(())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // RegistersMut — RAII guard for mutable register access
 // ---------------------------------------------------------------------------
@@ -519,5 +3016,3 @@ impl fmt::Debug for Cpu {
         write!(f, "{} cyc={}", self.reg, self.cycles)
     }
 }
-
-use std::fmt;
@@ -0,0 +1,164 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Straight-line block cache.
+//!
+//! A block is a run of consecutive instructions that always fall through to
+//! the next one (see [`ends_block`](super::opcodes::ends_block)). Once
+//! recorded, a block is replayed by dispatching each instruction straight to
+//! its page handler, skipping the opcode fetch, prefix decoding and per-step
+//! interrupt checks. Writes through [`BlockMemory`] drop every block whose
+//! bytes they touch.
+
+use super::opcodes::Page;
+use crate::memory::Memory;
+
+/// Longest 6809 instruction, in bytes.
+pub(super) const MAX_INSN_LEN: u16 = 5;
+
+/// Most instructions recorded into a single block.
+pub(super) const MAX_BLOCK_INSNS: usize = 32;
+
+/// Furthest a block's last byte can be from its start address.
+const MAX_BLOCK_SPAN: u16 = MAX_BLOCK_INSNS as u16 * MAX_INSN_LEN;
+
+/// One decoded instruction of a block.
+#[derive(Clone, Copy)]
+pub(super) struct Insn {
+    pub(super) page: Page,
+    pub(super) opcode: u8,
+}
+
+/// Recorded instructions starting at some address.
+pub(super) struct Block {
+    pub(super) insns: Box<[Insn]>,
+    /// Number of bytes covered, starting at the block's address.
+    pub(super) len: u16,
+}
+
+/// Blocks indexed by start address, plus a bitmap of every byte that any
+/// recorded block has covered.
+pub(super) struct BlockCache {
+    blocks: Box<[Option<Block>]>,
+    code: Box<[u64]>,
+    /// Start and length of the range watched by [`BlockMemory::watch`].
+    watched: (u16, u16),
+    /// A write has hit the watched range.
+    watched_written: bool,
+}
+
+impl BlockCache {
+    pub(super) fn new() -> Self {
+        Self {
+            blocks: (0..0x10000).map(|_| None).collect(),
+            code: vec![0; 0x10000 / 64].into_boxed_slice(),
+            watched: (0, 0),
+            watched_written: false,
+        }
+    }
+
+    /// Remove the block starting at `addr` so it can run while the cache is
+    /// borrowed by [`BlockMemory`]. Hand it back with [`Self::restore`].
+    pub(super) fn take(&mut self, addr: u16) -> Option<Block> {
+        self.blocks[addr as usize].take()
+    }
+
+    /// Put back a block removed by [`Self::take`].
+    pub(super) fn restore(&mut self, addr: u16, block: Block) {
+        self.blocks[addr as usize] = Some(block);
+    }
+
+    /// Store a newly recorded block starting at `addr`.
+    pub(super) fn insert(&mut self, addr: u16, block: Block) {
+        for offset in 0..block.len {
+            let byte = addr.wrapping_add(offset) as usize;
+            self.code[byte / 64] |= 1 << (byte % 64);
+        }
+        self.blocks[addr as usize] = Some(block);
+    }
+
+    /// Drop every block that covers `addr`.
+    pub(super) fn invalidate(&mut self, addr: u16) {
+        if !self.is_code(addr) {
+            return;
+        }
+        for back in 0..MAX_BLOCK_SPAN {
+            let slot = &mut self.blocks[addr.wrapping_sub(back) as usize];
+            if slot.as_ref().is_some_and(|block| block.len > back) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Drop all blocks.
+    pub(super) fn flush(&mut self) {
+        self.blocks.iter_mut().for_each(|slot| *slot = None);
+        self.code.fill(0);
+    }
+
+    /// Wrap `mem` so writes invalidate blocks and are checked against the
+    /// range given to [`BlockMemory::watch`].
+    pub(super) fn bind<'a, M: Memory>(&'a mut self, mem: &'a mut M) -> BlockMemory<'a, M> {
+        self.watched = (0, 0);
+        BlockMemory { mem, cache: self }
+    }
+
+    fn is_code(&self, addr: u16) -> bool {
+        let byte = addr as usize;
+        self.code[byte / 64] & (1 << (byte % 64)) != 0
+    }
+}
+
+/// Memory adapter used while running or recording blocks.
+pub(super) struct BlockMemory<'a, M> {
+    mem: &'a mut M,
+    cache: &'a mut BlockCache,
+}
+
+impl<M: Memory> BlockMemory<'_, M> {
+    /// Watch the `len` bytes starting at `addr` for writes, forgetting any
+    /// earlier hit.
+    pub(super) fn watch(&mut self, addr: u16, len: u16) {
+        self.cache.watched = (addr, len);
+        self.cache.watched_written = false;
+    }
+
+    /// Whether a write has hit the watched range since [`Self::watch`].
+    pub(super) fn watched_written(&self) -> bool {
+        self.cache.watched_written
+    }
+}
+
+impl<M: Memory> Memory for BlockMemory<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem.write(addr, val);
+        self.cache.invalidate(addr);
+        let (start, len) = self.cache.watched;
+        if addr.wrapping_sub(start) < len {
+            self.cache.watched_written = true;
+        }
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        self.mem.read_word(addr)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        self.mem.read_slice(addr, buf)
+    }
+}
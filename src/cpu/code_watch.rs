@@ -0,0 +1,131 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Self-modifying code detection.
+//!
+//! While a step runs, [`WatchedMemory`] sits between the CPU and the real
+//! memory. Instruction bytes, fetched in order from PC, are marked as
+//! executed as they are read; a later write to a marked byte is passed to
+//! the host callback.
+
+use crate::memory::{BusFault, Memory};
+
+/// Longest instruction, in bytes, on either CPU.
+const MAX_INSN_LEN: u16 = 5;
+
+/// A CPU write to a byte that has been executed as part of an instruction,
+/// reported through [`Cpu::set_code_write_hook`](super::Cpu::set_code_write_hook).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeWrite {
+    /// Address of the instruction that wrote.
+    pub pc: u16,
+    /// Address written.
+    pub addr: u16,
+    /// Value written.
+    pub val: u8,
+}
+
+/// Callback given each [`CodeWrite`].
+pub type CodeWriteHook = Box<dyn FnMut(CodeWrite) + Send>;
+
+/// One bit for every address that has been fetched as an instruction byte.
+pub(super) struct CodeWatch {
+    executed: Box<[u64]>,
+    hook: CodeWriteHook,
+}
+
+impl CodeWatch {
+    pub(super) fn new(hook: CodeWriteHook) -> Self {
+        Self {
+            executed: vec![0; 0x10000 / 64].into_boxed_slice(),
+            hook,
+        }
+    }
+
+    pub(super) fn is_executed(&self, addr: u16) -> bool {
+        self.executed[addr as usize / 64] & (1 << (addr % 64)) != 0
+    }
+
+    /// Wrap `mem` for the step that starts at `pc`.
+    pub(super) fn bind<'a, M: Memory>(
+        &'a mut self,
+        mem: &'a mut M,
+        pc: u16,
+    ) -> WatchedMemory<'a, M> {
+        WatchedMemory {
+            mem,
+            watch: self,
+            pc,
+            fetched: 0,
+        }
+    }
+}
+
+/// Memory seen by the CPU while the code watch is on.
+pub(super) struct WatchedMemory<'a, M: Memory> {
+    mem: &'a mut M,
+    watch: &'a mut CodeWatch,
+    pc: u16,
+    /// Bytes read in order from `pc` so far.
+    fetched: u16,
+}
+
+impl<M: Memory> WatchedMemory<'_, M> {
+    /// Mark `addr` executed if it is the next instruction byte.
+    fn fetch(&mut self, addr: u16) {
+        if self.fetched < MAX_INSN_LEN && addr == self.pc.wrapping_add(self.fetched) {
+            self.watch.executed[addr as usize / 64] |= 1 << (addr % 64);
+            self.fetched += 1;
+        }
+    }
+
+    fn check(&mut self, addr: u16, val: u8) {
+        if self.watch.is_executed(addr) {
+            (self.watch.hook)(CodeWrite {
+                pc: self.pc,
+                addr,
+                val,
+            });
+        }
+    }
+}
+
+impl<M: Memory> Memory for WatchedMemory<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.fetch(addr);
+        self.mem.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.check(addr, val);
+        self.mem.write(addr, val);
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        self.fetch(addr);
+        self.fetch(addr.wrapping_add(1));
+        self.mem.read_word(addr)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        for i in 0..buf.len() {
+            self.fetch(addr.wrapping_add(i as u16));
+        }
+        self.mem.read_slice(addr, buf);
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.mem.take_fault()
+    }
+}
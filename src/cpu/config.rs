@@ -0,0 +1,164 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! The CPU's accuracy options gathered into one value.
+
+use super::{Cpu, CpuVariant, HalfCarryQuirks, IllegalOpcodePolicy, TransferQuirks, VectorTable};
+
+/// An [`IllegalOpcodePolicy`] without its callback, so that it can be
+/// copied, compared and stored in a [`CpuConfig`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IllegalOpcodeMode {
+    /// [`IllegalOpcodePolicy::Hardware`].
+    #[default]
+    Hardware,
+    /// [`IllegalOpcodePolicy::TreatAsNop`].
+    TreatAsNop,
+    /// [`IllegalOpcodePolicy::Halt`].
+    Halt,
+    /// [`IllegalOpcodePolicy::Callback`], with whatever callback is
+    /// installed.
+    Callback,
+    /// [`IllegalOpcodePolicy::Trap`].
+    Trap,
+    /// [`IllegalOpcodePolicy::TrapToVector`].
+    TrapToVector(u16),
+}
+
+impl IllegalOpcodePolicy {
+    /// The policy without its callback.
+    pub fn mode(&self) -> IllegalOpcodeMode {
+        match self {
+            Self::Hardware => IllegalOpcodeMode::Hardware,
+            Self::TreatAsNop => IllegalOpcodeMode::TreatAsNop,
+            Self::Halt => IllegalOpcodeMode::Halt,
+            Self::Callback(_) => IllegalOpcodeMode::Callback,
+            Self::Trap => IllegalOpcodeMode::Trap,
+            Self::TrapToVector(vector) => IllegalOpcodeMode::TrapToVector(*vector),
+        }
+    }
+}
+
+/// Every option that selects how closely a [`Cpu`] follows a particular
+/// processor, in one value a frontend can show, store and apply again.
+///
+/// [`Cpu::config`] reads the options in use, [`Cpu::with_config`] builds a
+/// CPU with them and [`Cpu::set_config`] changes them all at once. Hooks,
+/// caches, breakpoints and the reset state are not accuracy options and
+/// are not included. The default is the configuration of [`Cpu::new`].
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, CpuConfig, CpuVariant, IllegalOpcodeMode};
+///
+/// let config = CpuConfig {
+///     variant: CpuVariant::Hd6309,
+///     illegal_opcodes: IllegalOpcodeMode::Halt,
+///     ..CpuConfig::default()
+/// };
+/// let cpu = Cpu::with_config(config);
+/// assert_eq!(cpu.variant(), CpuVariant::Hd6309);
+/// assert_eq!(cpu.config(), config);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuConfig {
+    /// See [`Cpu::set_variant`].
+    pub variant: CpuVariant,
+    /// See [`Cpu::set_illegal_opcode_policy`].
+    pub illegal_opcodes: IllegalOpcodeMode,
+    /// See [`Cpu::set_undocumented_opcodes`].
+    pub undocumented_opcodes: bool,
+    /// See [`Cpu::set_transfer_quirks`].
+    pub transfer_quirks: TransferQuirks,
+    /// See [`Cpu::set_half_carry_quirks`].
+    pub half_carry_quirks: HalfCarryQuirks,
+    /// See [`Cpu::set_vectors`].
+    pub vectors: VectorTable,
+    /// See [`Cpu::set_clock_hz`].
+    pub clock_hz: u64,
+}
+
+impl Default for CpuConfig {
+    fn default() -> Self {
+        Self {
+            variant: CpuVariant::default(),
+            illegal_opcodes: IllegalOpcodeMode::default(),
+            undocumented_opcodes: false,
+            transfer_quirks: TransferQuirks::default(),
+            half_carry_quirks: HalfCarryQuirks::default(),
+            vectors: VectorTable::STANDARD,
+            clock_hz: Cpu::DEFAULT_CLOCK_HZ,
+        }
+    }
+}
+
+impl Cpu {
+    /// Create a CPU, as [`Self::new`] does, with the options in `config`.
+    ///
+    /// # Panics
+    /// Panics if `config.clock_hz` is zero.
+    pub fn with_config(config: CpuConfig) -> Self {
+        let mut cpu = Self::new();
+        cpu.set_config(config);
+        cpu
+    }
+
+    /// The accuracy options in use.
+    pub fn config(&self) -> CpuConfig {
+        CpuConfig {
+            variant: self.variant,
+            illegal_opcodes: self.illegal_policy.mode(),
+            undocumented_opcodes: self.undocumented_opcodes,
+            transfer_quirks: self.transfer_quirks,
+            half_carry_quirks: self.half_carry_quirks,
+            vectors: self.vectors,
+            clock_hz: self.clock_hz,
+        }
+    }
+
+    /// Apply every option in `config`, as the individual setters do.
+    ///
+    /// A config cannot carry a callback, so
+    /// [`IllegalOpcodeMode::Callback`] keeps the installed callback policy
+    /// and, if there is none, selects [`IllegalOpcodePolicy::Hardware`].
+    ///
+    /// # Panics
+    /// Panics if `config.clock_hz` is zero.
+    pub fn set_config(&mut self, config: CpuConfig) {
+        if config.variant != self.variant {
+            self.set_variant(config.variant);
+        }
+        let policy = match config.illegal_opcodes {
+            IllegalOpcodeMode::Hardware => Some(IllegalOpcodePolicy::Hardware),
+            IllegalOpcodeMode::TreatAsNop => Some(IllegalOpcodePolicy::TreatAsNop),
+            IllegalOpcodeMode::Halt => Some(IllegalOpcodePolicy::Halt),
+            IllegalOpcodeMode::Callback => match self.illegal_policy {
+                IllegalOpcodePolicy::Callback(_) => None,
+                _ => Some(IllegalOpcodePolicy::Hardware),
+            },
+            IllegalOpcodeMode::Trap => Some(IllegalOpcodePolicy::Trap),
+            IllegalOpcodeMode::TrapToVector(vector) => {
+                Some(IllegalOpcodePolicy::TrapToVector(vector))
+            }
+        };
+        if let Some(policy) = policy {
+            self.set_illegal_opcode_policy(policy);
+        }
+        self.set_undocumented_opcodes(config.undocumented_opcodes);
+        self.set_transfer_quirks(config.transfer_quirks);
+        self.set_half_carry_quirks(config.half_carry_quirks);
+        self.set_vectors(config.vectors);
+        self.set_clock_hz(config.clock_hz);
+    }
+}
@@ -0,0 +1,173 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Handles for pausing and interrupting a CPU from other threads.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::peripheral::BusSignals;
+
+/// A handle another thread can use to pause a [`Cpu`](super::Cpu)'s run
+/// loops, from [`Cpu::control_handle`](super::Cpu::control_handle).
+///
+/// While the handle is paused, [`Cpu::run`](super::Cpu::run) and the other
+/// `run_*` loops, [`Cpu::step_n`](super::Cpu::step_n) and the
+/// [`Machine`](crate::machine::Machine) run loops return at the next
+/// instruction boundary, and any run started afterwards returns at once,
+/// until [`Self::resume`]. A single [`Cpu::step`](super::Cpu::step) still
+/// executes. Clones share the same state and may be sent to any thread.
+///
+/// # Example
+/// ```
+/// use std::thread;
+///
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x20, 0xFE], 0x0400); // BRA *
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+///
+/// let handle = cpu.control_handle();
+/// let pauser = thread::spawn({
+///     let handle = handle.clone();
+///     move || handle.pause()
+/// });
+/// // Runs until the other thread pauses it, not for u64::MAX cycles.
+/// cpu.run(&mut ram, u64::MAX / 2);
+/// pauser.join().unwrap();
+/// assert_eq!(cpu.run(&mut ram, 100), 0);
+///
+/// handle.resume();
+/// assert!(cpu.run(&mut ram, 100) >= 100);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ControlHandle {
+    paused: Arc<AtomicBool>,
+}
+
+impl ControlHandle {
+    /// Stop the CPU's run loops at the next instruction boundary and keep
+    /// them stopped.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Let the run loops go again. The run that was paused is not
+    /// restarted; the next call runs normally.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether the run loops are paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+/// [`InterruptHandle`] state bits.
+const IRQ: u8 = 0x01;
+const FIRQ: u8 = 0x02;
+const NMI: u8 = 0x04;
+
+/// A handle other threads can use to drive the CPU's interrupt lines, from
+/// [`Cpu::interrupt_handle`](super::Cpu::interrupt_handle).
+///
+/// The CPU samples the handle where it checks for interrupts, at the start
+/// of each step, so a line set from another thread is seen before the next
+/// instruction at the latest. IRQ and FIRQ are levels, wired-OR with the
+/// lines set through [`Cpu::set_irq`](super::Cpu::set_irq) and
+/// [`Cpu::apply_signals`](super::Cpu::apply_signals): while the handle
+/// holds a line the CPU sees it asserted, and releasing it through the
+/// handle releases it on the CPU. An NMI requested with [`Self::nmi`] is
+/// taken once, as by [`Cpu::trigger_nmi`](super::Cpu::trigger_nmi). Clones
+/// share the same lines.
+///
+/// # Example
+/// ```
+/// use std::thread;
+///
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x1C, 0xEF, 0x20, 0xFE], 0x0400); // ANDCC #$EF / BRA *
+/// ram.load(&[0x3B], 0x0500); // RTI
+/// ram.write_word(0xFFF8, 0x0500);
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.registers_mut().s = 0x0800;
+///
+/// let irq = cpu.interrupt_handle();
+/// thread::spawn(move || irq.set_irq(true)).join().unwrap();
+/// cpu.step(&mut ram);
+/// cpu.step(&mut ram);
+/// assert_eq!(cpu.registers().pc, 0x0500);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle {
+    lines: Arc<AtomicU8>,
+}
+
+impl InterruptHandle {
+    /// Assert or release IRQ.
+    pub fn set_irq(&self, active: bool) {
+        self.set(IRQ, active);
+    }
+
+    /// Assert or release FIRQ.
+    pub fn set_firq(&self, active: bool) {
+        self.set(FIRQ, active);
+    }
+
+    /// Request an NMI, taken when the CPU next samples the handle.
+    pub fn nmi(&self) {
+        self.lines.fetch_or(NMI, Ordering::Relaxed);
+    }
+
+    /// The IRQ and FIRQ levels the handle holds, and NMI if one is waiting
+    /// to be sampled.
+    pub fn lines(&self) -> BusSignals {
+        signals(self.lines.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, line: u8, active: bool) {
+        if active {
+            self.lines.fetch_or(line, Ordering::Relaxed);
+        } else {
+            self.lines.fetch_and(!line, Ordering::Relaxed);
+        }
+    }
+
+    /// The lines, taking any NMI request.
+    pub(super) fn sample(&self) -> BusSignals {
+        signals(self.lines.fetch_and(!NMI, Ordering::Relaxed))
+    }
+}
+
+fn signals(bits: u8) -> BusSignals {
+    let mut signals = BusSignals::default();
+    for (bit, line) in [
+        (IRQ, BusSignals::IRQ),
+        (FIRQ, BusSignals::FIRQ),
+        (NMI, BusSignals::NMI),
+    ] {
+        if bits & bit != 0 {
+            signals.insert(line);
+        }
+    }
+    signals
+}
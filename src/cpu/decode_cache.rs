@@ -0,0 +1,237 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Pre-decoded instruction cache.
+//!
+//! The cache remembers, for the instruction starting at each address, the
+//! page and opcode its prefix and opcode bytes decoded to, which select its
+//! handler, and the instruction's bytes, which hold its addressing data
+//! (post-byte, offsets and immediates). A hit dispatches straight to the
+//! handler without fetching or decoding the opcode again, and
+//! [`CachedMemory`] serves the operand fetches from the cached bytes. Every
+//! write invalidates the cached instructions it overlaps.
+
+use super::opcodes::Page;
+use crate::memory::Memory;
+
+/// Longest 6809 instruction: page prefix, opcode, post-byte and a 16-bit offset.
+const MAX_INSN_LEN: usize = 5;
+
+/// One decoded instruction.
+#[derive(Clone, Copy)]
+pub(super) struct Decoded {
+    /// Page and page-local opcode, selecting the handler.
+    pub(super) page: Page,
+    pub(super) opcode: u8,
+    len: u8,
+    bytes: [u8; MAX_INSN_LEN],
+}
+
+impl Decoded {
+    /// The prefix and opcode bytes, as fetched.
+    pub(super) fn opcode_bytes(&self) -> &[u8] {
+        &self.bytes[..self.page.opcode_len() as usize]
+    }
+}
+
+/// One slot per address in the 64KB address space, plus a bitmap of every
+/// byte that any cached instruction covers.
+pub(super) struct DecodeCache {
+    entries: Box<[Option<Decoded>]>,
+    code: Box<[u64]>,
+}
+
+impl DecodeCache {
+    pub(super) fn new() -> Self {
+        Self {
+            entries: vec![None; 0x10000].into_boxed_slice(),
+            code: vec![0; 0x10000 / 64].into_boxed_slice(),
+        }
+    }
+
+    /// The decoded instruction at `pc`, if it is cached.
+    pub(super) fn get(&self, pc: u16) -> Option<Decoded> {
+        self.entries[pc as usize]
+    }
+
+    /// Drop every cached instruction that covers `addr`.
+    pub(super) fn invalidate(&mut self, addr: u16) {
+        if !self.is_code(addr) {
+            return;
+        }
+        for back in 0..MAX_INSN_LEN as u16 {
+            let slot = &mut self.entries[addr.wrapping_sub(back) as usize];
+            if slot.is_some_and(|entry| entry.len as u16 > back) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Drop all cached instructions.
+    pub(super) fn flush(&mut self) {
+        self.entries.fill(None);
+        self.code.fill(0);
+    }
+
+    /// Whether any cached instruction has covered `addr` since the last flush.
+    fn is_code(&self, addr: u16) -> bool {
+        let byte = addr as usize;
+        self.code[byte / 64] & (1 << (byte % 64)) != 0
+    }
+
+    fn insert(&mut self, pc: u16, entry: Decoded) {
+        for offset in 0..entry.len as u16 {
+            let byte = pc.wrapping_add(offset) as usize;
+            self.code[byte / 64] |= 1 << (byte % 64);
+        }
+        self.entries[pc as usize] = Some(entry);
+    }
+
+    /// Wrap `mem` so writes invalidate the cache. Nothing is served.
+    pub(super) fn bind<'a, M: Memory>(&'a mut self, mem: &'a mut M) -> CachedMemory<'a, M> {
+        CachedMemory {
+            mem,
+            cache: self,
+            pc: 0,
+            served: 0,
+            bytes: [0; MAX_INSN_LEN],
+        }
+    }
+
+    /// Wrap `mem` for executing `hit`, the cached instruction at `pc`, whose
+    /// prefix and opcode are taken as already fetched: serve its operands.
+    pub(super) fn bind_hit<'a, M: Memory>(
+        &'a mut self,
+        mem: &'a mut M,
+        pc: u16,
+        hit: &Decoded,
+    ) -> CachedMemory<'a, M> {
+        CachedMemory {
+            mem,
+            cache: self,
+            pc,
+            served: hit.len,
+            bytes: hit.bytes,
+        }
+    }
+
+    /// Wrap `mem` for executing the uncached instruction at `pc`, so it can
+    /// be cached afterwards unless it overwrites itself.
+    pub(super) fn bind_miss<'a, M: Memory>(
+        &'a mut self,
+        mem: &'a mut M,
+        pc: u16,
+    ) -> RecordingMemory<'a, M> {
+        RecordingMemory {
+            mem,
+            cache: self,
+            pc,
+            overwritten: false,
+        }
+    }
+}
+
+/// Memory adapter serving a cached instruction's bytes.
+pub(super) struct CachedMemory<'a, M> {
+    mem: &'a mut M,
+    cache: &'a mut DecodeCache,
+    /// Address of the instruction being executed.
+    pc: u16,
+    /// Number of bytes from `pc` served from `bytes`.
+    served: u8,
+    bytes: [u8; MAX_INSN_LEN],
+}
+
+impl<M: Memory> Memory for CachedMemory<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        let offset = addr.wrapping_sub(self.pc) as usize;
+        if offset < self.served as usize {
+            return self.bytes[offset];
+        }
+        self.mem.read(addr)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        let served = self.served as usize;
+        // Let the backing memory's fast path handle anything not served from
+        // the cache.
+        if (0..buf.len())
+            .all(|i| addr.wrapping_add(i as u16).wrapping_sub(self.pc) as usize >= served)
+        {
+            return self.mem.read_slice(addr, buf);
+        }
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.read(addr.wrapping_add(i as u16));
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem.write(addr, val);
+        // A served instruction is cached, so only writes to code can hit it.
+        if self.cache.is_code(addr) {
+            self.cache.invalidate(addr);
+            // The instruction being executed overwrote itself: stop serving it.
+            if (addr.wrapping_sub(self.pc) as usize) < MAX_INSN_LEN {
+                self.served = 0;
+            }
+        }
+    }
+}
+
+/// Memory adapter used while executing an instruction on a cache miss.
+pub(super) struct RecordingMemory<'a, M> {
+    mem: &'a mut M,
+    cache: &'a mut DecodeCache,
+    /// Address of the instruction being executed.
+    pc: u16,
+    /// The instruction has written over its own bytes.
+    overwritten: bool,
+}
+
+impl<M: Memory> RecordingMemory<'_, M> {
+    /// Cache the instruction once it has finished executing, decoded as
+    /// `page` and `opcode` from `bytes`, the bytes its fetches read.
+    pub(super) fn finish(self, page: Page, opcode: u8, bytes: &[u8]) {
+        if self.overwritten || bytes.len() < page.opcode_len() as usize {
+            return;
+        }
+        let mut entry = Decoded {
+            page,
+            opcode,
+            len: bytes.len() as u8,
+            bytes: [0; MAX_INSN_LEN],
+        };
+        entry.bytes[..bytes.len()].copy_from_slice(bytes);
+        self.cache.insert(self.pc, entry);
+    }
+}
+
+impl<M: Memory> Memory for RecordingMemory<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem.read(addr)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        self.mem.read_slice(addr, buf);
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem.write(addr, val);
+        self.cache.invalidate(addr);
+        // The instruction being executed overwrote itself: do not cache it.
+        if (addr.wrapping_sub(self.pc) as usize) < MAX_INSN_LEN {
+            self.overwritten = true;
+        }
+    }
+}
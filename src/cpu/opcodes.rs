@@ -13,13 +13,62 @@
 //   limitations under the License.
 
 //! Opcode dispatch and cycle tables for the 6809.
+//!
+//! Each page owns a 256-entry table of handler function pointers, indexed by
+//! the opcode byte. Tables are built at compile time by [`dispatch_table!`]
+//! once per memory type, so dispatch is a single indexed call with no match
+//! chain in the hot path. [`Cpu::set_opcode_handler`] replaces individual
+//! entries for one CPU.
 
+use std::marker::PhantomData;
+
+use crate::cpu::{Cpu, CpuVariant, OpcodeFetch};
+use crate::memory::Memory;
+
+/// Opcode handler: executes one instruction whose opcode byte has been fetched
+/// and returns the cycles it took beyond the base count in the page's cycle
+/// table (taken long branches, indexed post-byte costs, stacked registers).
+type Handler<M> = fn(&mut Cpu, &mut M) -> u8;
+
+/// Namespace for the per-page dispatch tables of memory type `M`.
+///
+/// Each page module adds its own associated table constant.
+struct Dispatch<M>(PhantomData<M>);
+
+/// Build a 256-entry handler table from `opcode => handler` pairs.
+///
+/// Opcodes not listed dispatch to the given fallback handler.
+macro_rules! dispatch_table {
+    ($m:ident, $fallback:ident; $($($op:literal)|+ => $handler:ident,)*) => {{
+        let mut t: [Handler<$m>; 256] = [$fallback::<$m>; 256];
+        $($(t[$op] = $handler::<$m>;)+)*
+        t
+    }};
+}
+
+mod hd6309;
 mod page0;
 mod page1;
 mod page2;
 
-use crate::cpu::Cpu;
-use crate::memory::Memory;
+/// Returned by the page 1 and page 2 fallback handlers in place of extra
+/// cycles, so the page's `execute` can resolve the opcode it dispatched.
+const UNDEFINED: u8 = u8::MAX;
+
+/// Execute an opcode undefined on page 1 or 2, whose prefix and opcode have
+/// been fetched, and return its cycle count.
+///
+/// With undocumented opcodes enabled the 6809 ignores the prefix and runs
+/// the page 0 instruction, one cycle longer, through any handler installed
+/// in its place. Otherwise, or if the opcode is itself a prefix, it is
+/// illegal and takes 2 cycles plus whatever the illegal opcode policy adds.
+fn undefined_on_page<M: Memory>(cpu: &mut Cpu, mem: &mut M, opcode: u8) -> u8 {
+    if cpu.undocumented_opcodes && !matches!(opcode, 0x10 | 0x11) {
+        1 + cpu.dispatch(mem, Page::Page0, opcode)
+    } else {
+        2 + cpu.illegal_opcode(mem, 2)
+    }
+}
 
 /// Returns the base cycle count for a 6809 instruction.
 ///
@@ -40,25 +89,116 @@ pub fn instruction_cycles(bytes: &[u8]) -> u8 {
     }
 }
 
-/// Execute a single opcode (already fetched).
+/// Opcode page, selected by an optional `0x10` / `0x11` prefix byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Page {
+    Page0,
+    Page1,
+    Page2,
+}
+
+impl Page {
+    /// Number of bytes before the page-local opcode's operands: the prefix,
+    /// if any, plus the opcode byte.
+    pub(crate) fn opcode_len(self) -> u16 {
+        match self {
+            Page::Page0 => 1,
+            Page::Page1 | Page::Page2 => 2,
+        }
+    }
+
+    /// The prefix byte selecting the page, in the high byte of an opcode.
+    pub(crate) fn prefix(self) -> u16 {
+        match self {
+            Page::Page0 => 0x0000,
+            Page::Page1 => 0x1000,
+            Page::Page2 => 0x1100,
+        }
+    }
+}
+
+/// Whether the opcode can leave the straight-line instruction stream: it
+/// branches, jumps, returns, traps, waits, halts or may write PC.
+///
+/// Opcodes on page 1 and 2 also count if their page 0 namesake does, as
+/// undefined ones may run it (see [`undefined_on_page`]). The 6309's own
+/// such opcodes count whichever CPU runs them.
+pub(crate) fn ends_block(page: Page, opcode: u8) -> bool {
+    hd6309::ends_block(page, opcode)
+        || match page {
+            Page::Page0 => page0::ends_block(opcode),
+            Page::Page1 => page1::ends_block(opcode) || page0::ends_block(opcode),
+            Page::Page2 => page2::ends_block(opcode) || page0::ends_block(opcode),
+        }
+}
+
+/// Execute a single opcode (already fetched) and return its cycle count.
 ///
 /// Repeated page-prefix chaining is intentionally unsupported: if a page
 /// prefix fetches another prefix as its sub-opcode, that second prefix is
 /// handled as the page-local opcode byte rather than being discarded.
 impl Cpu {
-    pub(crate) fn execute(&mut self, mem: &mut impl Memory, opcode: u8) {
+    pub(crate) fn execute<M: Memory>(&mut self, mem: &mut M, opcode: u8) -> u8 {
         match opcode {
             0x10 => {
-                let op2 = self.fetch_byte(mem);
-                page1::execute(self, mem, op2);
+                let op2 = self.fetch_opcode(mem, OpcodeFetch::Page1);
+                self.execute_on_page(mem, Page::Page1, op2)
             }
             0x11 => {
-                let op2 = self.fetch_byte(mem);
-                page2::execute(self, mem, op2);
-            }
-            _ => {
-                page0::execute(self, mem, opcode);
+                let op2 = self.fetch_opcode(mem, OpcodeFetch::Page2);
+                self.execute_on_page(mem, Page::Page2, op2)
             }
+            _ => self.execute_on_page(mem, Page::Page0, opcode),
+        }
+    }
+
+    /// Execute a page-local opcode whose prefix and opcode bytes have already
+    /// been consumed, and return its cycle count.
+    pub(crate) fn execute_on_page<M: Memory>(&mut self, mem: &mut M, page: Page, opcode: u8) -> u8 {
+        #[cfg(feature = "hooks")]
+        if self.opcode_stats.is_some() || self.profile.is_some() || self.interrupt_stats.is_some() {
+            return self.execute_instrumented(mem, page, opcode);
+        }
+        self.dispatch(mem, page, opcode)
+    }
+
+    /// [`Self::execute_on_page`] while any instrumentation is recording.
+    #[cfg(feature = "hooks")]
+    fn execute_instrumented<M: Memory>(&mut self, mem: &mut M, page: Page, opcode: u8) -> u8 {
+        if let Some(stats) = &mut self.opcode_stats {
+            stats.record(page.prefix() | opcode as u16);
+        }
+        let pc = self.reg.pc.wrapping_sub(page.opcode_len());
+        let s = self.reg.s;
+        let cycles = self.dispatch(mem, page, opcode);
+        if let Some(profile) = &mut self.profile {
+            let opcode = page.prefix() | opcode as u16;
+            profile.instruction(pc, opcode, s, self.reg.s, self.reg.pc, cycles as u64);
+        }
+        if let Some(stats) = &mut self.interrupt_stats
+            && page == Page::Page0
+            && opcode == 0x3B
+        {
+            stats.rti(self.cycles + cycles as u64);
+        }
+        cycles
+    }
+
+    /// Run a page-local opcode on the configured CPU's handlers, or on the
+    /// one installed in their place.
+    fn dispatch<M: Memory>(&mut self, mem: &mut M, page: Page, opcode: u8) -> u8 {
+        if !self.opcode_handlers.is_empty()
+            && let Some(cycles) = self.run_opcode_handler(mem, page, opcode)
+        {
+            return cycles;
+        }
+        if self.variant == CpuVariant::Hd6309 {
+            return hd6309::execute(self, mem, page, opcode);
+        }
+        match page {
+            Page::Page0 => page0::execute(self, mem, opcode),
+            Page::Page1 => page1::execute(self, mem, opcode),
+            Page::Page2 => page2::execute(self, mem, opcode),
         }
     }
 }
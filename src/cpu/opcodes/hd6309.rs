@@ -0,0 +1,2063 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! HD6309 opcode implementations, for
+//! [`CpuVariant::Hd6309`](crate::CpuVariant::Hd6309).
+//!
+//! The 6309 runs the documented 6809 instruction set unchanged and adds its
+//! own instructions in opcodes the 6809 leaves undefined. Its dispatch
+//! tables are the 6809 ones with the 6809's undocumented opcodes removed
+//! (they are illegal on the 6309) and the 6309 instructions added.
+//!
+//! Contains: SEXW, LDQ, the register-to-register operations (ADDR through
+//! CMPR), PSHSW/PULSW/PSHUW/PULUW, the inherent D, W, E and F operations,
+//! the D arithmetic and logic operations, SUB/CMP/ADD of W, E and F, loads
+//! and stores of W, Q, E and F, MULD/DIVD/DIVQ, LDMD/BITMD, OIM/AIM/EIM/TIM,
+//! the register-bit operations (BAND through STBT), and TFM.
+
+use super::page0::{read_reg, sbc8, sub8, write_reg};
+use super::{Dispatch, Handler, Page, UNDEFINED, page0, page1, page2};
+use crate::alu;
+use crate::cpu::Cpu;
+use crate::memory::Memory;
+use crate::registers::{ConditionCodes, MD_DZ, MD_FIRQ_IRQ, MD_IL, MD_NATIVE};
+
+/// Page 0 opcodes the 6809 runs as undocumented instructions, all illegal
+/// on the 6309 unless it defines them itself.
+const PAGE0_UNDOCUMENTED: [u8; 33] = [
+    0x01, 0x02, 0x05, 0x0B, 0x14, 0x15, 0x18, 0x1B, 0x38, 0x3E, 0x41, 0x42, 0x45, 0x4B, 0x4E, 0x51,
+    0x52, 0x55, 0x5B, 0x5E, 0x61, 0x62, 0x65, 0x6B, 0x71, 0x72, 0x75, 0x7B, 0x87, 0x8F, 0xC7, 0xCD,
+    0xCF,
+];
+
+/// Page 1 opcodes the 6809 runs as undocumented instructions.
+const PAGE1_UNDOCUMENTED: [u8; 8] = [0x20, 0x3E, 0x8F, 0xC3, 0xCF, 0xD3, 0xE3, 0xF3];
+
+/// Page 2 opcodes the 6809 runs as undocumented instructions.
+const PAGE2_UNDOCUMENTED: [u8; 5] = [0x3E, 0xC3, 0xD3, 0xE3, 0xF3];
+
+/// Base cycle counts for Page 0 opcodes on the 6309 in emulation mode.
+#[rustfmt::skip]
+const HD6309_PAGE0_CYCLES: [u8; 256] = {
+    let mut t = page0::PAGE0_CYCLES;
+    // Illegal opcodes take 1 cycle before the trap
+    let mut i = 0;
+    while i < PAGE0_UNDOCUMENTED.len() {
+        t[PAGE0_UNDOCUMENTED[i] as usize] = 1;
+        i += 1;
+    }
+    t[0x01] = 6; t[0x02] = 6; t[0x05] = 6; t[0x0B] = 6; // OIM AIM EIM TIM
+    t[0x61] = 7; t[0x62] = 7; t[0x65] = 7; t[0x6B] = 7;
+    t[0x71] = 7; t[0x72] = 7; t[0x75] = 7; t[0x7B] = 7;
+    t[0x14] = 4;  // SEXW
+    t[0xCD] = 5;  // LDQ imm
+    t
+};
+
+/// Base cycle counts for Page 1 opcodes on the 6309 in emulation mode.
+#[rustfmt::skip]
+const HD6309_PAGE1_CYCLES: [u8; 256] = {
+    let mut t = page1::PAGE1_CYCLES;
+    t[0x30] = 4; t[0x31] = 4; t[0x32] = 4; t[0x33] = 4; // ADDR ADCR SUBR SBCR
+    t[0x34] = 4; t[0x35] = 4; t[0x36] = 4; t[0x37] = 4; // ANDR ORR EORR CMPR
+    t[0x38] = 6; t[0x39] = 6; t[0x3A] = 6; t[0x3B] = 6; // PSHSW PULSW PSHUW PULUW
+    // Inherent D and W
+    t[0x40] = 3; t[0x43] = 3; t[0x44] = 3; t[0x46] = 3;
+    t[0x47] = 3; t[0x48] = 3; t[0x49] = 3; t[0x4A] = 3;
+    t[0x4C] = 3; t[0x4D] = 3; t[0x4F] = 3;
+    t[0x53] = 3; t[0x54] = 3; t[0x56] = 3; t[0x59] = 3;
+    t[0x5A] = 3; t[0x5C] = 3; t[0x5D] = 3; t[0x5F] = 3;
+    // SUBW CMPW ADDW
+    t[0x80] = 5; t[0x81] = 5; t[0x8B] = 5;
+    t[0x90] = 7; t[0x91] = 7; t[0x9B] = 7;
+    t[0xA0] = 7; t[0xA1] = 7; t[0xAB] = 7;
+    t[0xB0] = 8; t[0xB1] = 8; t[0xBB] = 8;
+    // SBCD ANDD BITD LDW EORD ADCD ORD, and STW
+    t[0x82] = 5; t[0x84] = 5; t[0x85] = 5; t[0x86] = 4;
+    t[0x88] = 5; t[0x89] = 5; t[0x8A] = 5;
+    t[0x92] = 7; t[0x94] = 7; t[0x95] = 7; t[0x96] = 6; t[0x97] = 6;
+    t[0x98] = 7; t[0x99] = 7; t[0x9A] = 7;
+    t[0xA2] = 7; t[0xA4] = 7; t[0xA5] = 7; t[0xA6] = 6; t[0xA7] = 6;
+    t[0xA8] = 7; t[0xA9] = 7; t[0xAA] = 7;
+    t[0xB2] = 8; t[0xB4] = 8; t[0xB5] = 8; t[0xB6] = 7; t[0xB7] = 7;
+    t[0xB8] = 8; t[0xB9] = 8; t[0xBA] = 8;
+    // LDQ STQ
+    t[0xDC] = 8; t[0xDD] = 8;
+    t[0xEC] = 8; t[0xED] = 8;
+    t[0xFC] = 9; t[0xFD] = 9;
+    t
+};
+
+/// Base cycle counts for Page 2 opcodes on the 6309 in emulation mode.
+#[rustfmt::skip]
+const HD6309_PAGE2_CYCLES: [u8; 256] = {
+    let mut t = page2::PAGE2_CYCLES;
+    t[0x30] = 7; t[0x31] = 7; t[0x32] = 7; t[0x33] = 7; // BAND BIAND BOR BIOR
+    t[0x34] = 7; t[0x35] = 7; t[0x36] = 7; t[0x37] = 8; // BEOR BIEOR LDBT STBT
+    t[0x38] = 3; t[0x39] = 3; t[0x3A] = 3; t[0x3B] = 3; // TFM, per byte
+    t[0x3C] = 4;  // BITMD
+    t[0x3D] = 5;  // LDMD
+    // Inherent E and F
+    t[0x43] = 3; t[0x4A] = 3; t[0x4C] = 3; t[0x4D] = 3; t[0x4F] = 3;
+    t[0x53] = 3; t[0x5A] = 3; t[0x5C] = 3; t[0x5D] = 3; t[0x5F] = 3;
+    // SUBE CMPE LDE ADDE, STE, and the same for F
+    t[0x80] = 3; t[0x81] = 3; t[0x86] = 3; t[0x8B] = 3;
+    t[0x90] = 5; t[0x91] = 5; t[0x96] = 5; t[0x97] = 5; t[0x9B] = 5;
+    t[0xA0] = 5; t[0xA1] = 5; t[0xA6] = 5; t[0xA7] = 5; t[0xAB] = 5;
+    t[0xB0] = 6; t[0xB1] = 6; t[0xB6] = 6; t[0xB7] = 6; t[0xBB] = 6;
+    t[0xC0] = 3; t[0xC1] = 3; t[0xC6] = 3; t[0xCB] = 3;
+    t[0xD0] = 5; t[0xD1] = 5; t[0xD6] = 5; t[0xD7] = 5; t[0xDB] = 5;
+    t[0xE0] = 5; t[0xE1] = 5; t[0xE6] = 5; t[0xE7] = 5; t[0xEB] = 5;
+    t[0xF0] = 6; t[0xF1] = 6; t[0xF6] = 6; t[0xF7] = 6; t[0xFB] = 6;
+    // DIVD DIVQ MULD
+    t[0x8D] = 25; t[0x8E] = 34; t[0x8F] = 28;
+    t[0x9D] = 27; t[0x9E] = 36; t[0x9F] = 30;
+    t[0xAD] = 27; t[0xAE] = 36; t[0xAF] = 30;
+    t[0xBD] = 28; t[0xBE] = 37; t[0xBF] = 31;
+    t
+};
+
+/// Base cycle counts for Page 0 opcodes on the 6309 in native mode.
+#[rustfmt::skip]
+const HD6309_NATIVE_PAGE0_CYCLES: [u8; 256] = {
+    let mut t = HD6309_PAGE0_CYCLES;
+    // Direct and extended read-modify-write, and inherent A and B
+    let mut op = 0;
+    while op < 0x10 {
+        t[op] -= 1;
+        t[0x40 + op] = 1;
+        t[0x50 + op] = 1;
+        t[0x70 + op] -= 1;
+        op += 1;
+    }
+    t[0x0D] = 4; t[0x6D] = 5; t[0x7D] = 5; // TST
+    t[0x01] = 6; t[0x02] = 6; t[0x05] = 6; t[0x0B] = 4; // OIM AIM EIM TIM
+    t[0x71] = 7; t[0x72] = 7; t[0x75] = 7; t[0x7B] = 5; t[0x6B] = 5;
+    // 8-bit direct and extended
+    let mut op = 0;
+    while op < 0x0C {
+        t[0x90 + op] = 3; t[0xD0 + op] = 3;
+        t[0xB0 + op] = 4; t[0xF0 + op] = 4;
+        op += 1;
+    }
+    t[0x12] = 1; t[0x13] = 1; t[0x16] = 4; t[0x17] = 7; // NOP SYNC LBRA LBSR
+    t[0x19] = 1; t[0x1A] = 2; t[0x1D] = 1;              // DAA ORCC SEX
+    t[0x1E] = 5; t[0x1F] = 4;                           // EXG TFR
+    t[0x34] = 4; t[0x35] = 4; t[0x36] = 4; t[0x37] = 4; // PSHS PULS PSHU PULU
+    t[0x39] = 4; t[0x3A] = 1; t[0x3C] = 23; t[0x3D] = 10; t[0x3F] = 21; // RTS ABX CWAI MUL SWI
+    // SUBD CMPX ADDD, BSR and JSR
+    t[0x83] = 3; t[0x8C] = 3; t[0xC3] = 3; t[0x8D] = 6;
+    t[0x93] = 4; t[0x9C] = 4; t[0xD3] = 4; t[0x9D] = 6;
+    t[0xA3] = 5; t[0xAC] = 5; t[0xE3] = 5; t[0xAD] = 6;
+    t[0xB3] = 5; t[0xBC] = 5; t[0xF3] = 5; t[0xBD] = 7;
+    // 16-bit loads and stores
+    t[0x9E] = 4; t[0x9F] = 4; t[0xDC] = 4; t[0xDD] = 4; t[0xDE] = 4; t[0xDF] = 4;
+    t[0xBE] = 5; t[0xBF] = 5; t[0xFC] = 5; t[0xFD] = 5; t[0xFE] = 5; t[0xFF] = 5;
+    t
+};
+
+/// Base cycle counts for Page 1 opcodes on the 6309 in native mode.
+#[rustfmt::skip]
+const HD6309_NATIVE_PAGE1_CYCLES: [u8; 256] = {
+    let mut t = HD6309_PAGE1_CYCLES;
+    t[0x3F] = 22; // SWI2
+    // Inherent D and W
+    let mut op = 0x40;
+    while op < 0x60 {
+        if t[op] == 3 {
+            t[op] = 2;
+        }
+        op += 1;
+    }
+    // 16-bit arithmetic and logic, by the 5 cycles of the immediate form
+    let mut op = 0x80;
+    while op < 0x8D {
+        if t[op] == 5 {
+            t[op] -= 1;
+            t[op + 0x10] = 5;
+            t[op + 0x20] = 6;
+            t[op + 0x30] = 6;
+        }
+        op += 1;
+    }
+    // LDW STW LDY STY LDS STS LDQ STQ
+    t[0x96] = 5; t[0x97] = 5; t[0x9E] = 5; t[0x9F] = 5; t[0xDE] = 5; t[0xDF] = 5;
+    t[0xB6] = 6; t[0xB7] = 6; t[0xBE] = 6; t[0xBF] = 6; t[0xFE] = 6; t[0xFF] = 6;
+    t[0xDC] = 7; t[0xDD] = 7; t[0xFC] = 8; t[0xFD] = 8;
+    t
+};
+
+/// Base cycle counts for Page 2 opcodes on the 6309 in native mode.
+#[rustfmt::skip]
+const HD6309_NATIVE_PAGE2_CYCLES: [u8; 256] = {
+    let mut t = HD6309_PAGE2_CYCLES;
+    t[0x30] = 6; t[0x31] = 6; t[0x32] = 6; t[0x33] = 6; // BAND BIAND BOR BIOR
+    t[0x34] = 6; t[0x35] = 6; t[0x36] = 6; t[0x37] = 7; // BEOR BIEOR LDBT STBT
+    t[0x3F] = 22; // SWI3
+    // Inherent E and F
+    let mut op = 0x40;
+    while op < 0x60 {
+        if t[op] == 3 {
+            t[op] = 2;
+        }
+        op += 1;
+    }
+    // E and F direct and extended
+    let mut op = 0x90;
+    while op < 0x100 {
+        if op & 0x30 == 0x10 && t[op] == 5 {
+            t[op] = 4;
+        } else if op & 0x30 == 0x30 && t[op] == 6 {
+            t[op] = 5;
+        }
+        op += 1;
+    }
+    // CMPU CMPS
+    t[0x83] = 4; t[0x8C] = 4; t[0x93] = 5; t[0x9C] = 5;
+    t[0xA3] = 6; t[0xAC] = 6; t[0xB3] = 6; t[0xBC] = 6;
+    // DIVD DIVQ MULD
+    t[0x9D] = 26; t[0x9E] = 35; t[0x9F] = 29;
+    t[0xBD] = 27; t[0xBE] = 36; t[0xBF] = 30;
+    t
+};
+
+/// Copy of `table` with every opcode in `ops` sent to `handler`.
+const fn strip<M: Memory, const N: usize>(
+    mut table: [Handler<M>; 256],
+    ops: [u8; N],
+    handler: Handler<M>,
+) -> [Handler<M>; 256] {
+    let mut i = 0;
+    while i < N {
+        table[ops[i] as usize] = handler;
+        i += 1;
+    }
+    table
+}
+
+impl<M: Memory> Dispatch<M> {
+    /// Handler table for Page 0 opcodes on the 6309.
+    const HD6309_PAGE0: [Handler<M>; 256] = {
+        let mut t = strip(Self::PAGE0, PAGE0_UNDOCUMENTED, illegal::<M>);
+        t[0x01] = oim_dir::<M>;
+        t[0x02] = aim_dir::<M>;
+        t[0x05] = eim_dir::<M>;
+        t[0x0B] = tim_dir::<M>;
+        t[0x14] = sexw::<M>;
+        t[0x61] = oim_idx::<M>;
+        t[0x62] = aim_idx::<M>;
+        t[0x65] = eim_idx::<M>;
+        t[0x6B] = tim_idx::<M>;
+        t[0x71] = oim_ext::<M>;
+        t[0x72] = aim_ext::<M>;
+        t[0x75] = eim_ext::<M>;
+        t[0x7B] = tim_ext::<M>;
+        t[0xCD] = ldq_imm::<M>;
+        t
+    };
+
+    /// Handler table for Page 1 opcodes on the 6309.
+    const HD6309_PAGE1: [Handler<M>; 256] = {
+        let mut t = strip(Self::PAGE1, PAGE1_UNDOCUMENTED, undefined::<M>);
+        t[0x30] = addr::<M>;
+        t[0x31] = adcr::<M>;
+        t[0x32] = subr::<M>;
+        t[0x33] = sbcr::<M>;
+        t[0x34] = andr::<M>;
+        t[0x35] = orr::<M>;
+        t[0x36] = eorr::<M>;
+        t[0x37] = cmpr::<M>;
+        t[0x38] = pshsw::<M>;
+        t[0x39] = pulsw::<M>;
+        t[0x3A] = pshuw::<M>;
+        t[0x3B] = puluw::<M>;
+        t[0x40] = negd::<M>;
+        t[0x43] = comd::<M>;
+        t[0x44] = lsrd::<M>;
+        t[0x46] = rord::<M>;
+        t[0x47] = asrd::<M>;
+        t[0x48] = asld::<M>;
+        t[0x49] = rold::<M>;
+        t[0x4A] = decd::<M>;
+        t[0x4C] = incd::<M>;
+        t[0x4D] = tstd::<M>;
+        t[0x4F] = clrd::<M>;
+        t[0x53] = comw::<M>;
+        t[0x54] = lsrw::<M>;
+        t[0x56] = rorw::<M>;
+        t[0x59] = rolw::<M>;
+        t[0x5A] = decw::<M>;
+        t[0x5C] = incw::<M>;
+        t[0x5D] = tstw::<M>;
+        t[0x5F] = clrw::<M>;
+        t[0x80] = subw_imm::<M>;
+        t[0x81] = cmpw_imm::<M>;
+        t[0x82] = sbcd_imm::<M>;
+        t[0x84] = andd_imm::<M>;
+        t[0x85] = bitd_imm::<M>;
+        t[0x86] = ldw_imm::<M>;
+        t[0x88] = eord_imm::<M>;
+        t[0x89] = adcd_imm::<M>;
+        t[0x8A] = ord_imm::<M>;
+        t[0x8B] = addw_imm::<M>;
+        t[0x90] = subw_dir::<M>;
+        t[0x91] = cmpw_dir::<M>;
+        t[0x92] = sbcd_dir::<M>;
+        t[0x94] = andd_dir::<M>;
+        t[0x95] = bitd_dir::<M>;
+        t[0x96] = ldw_dir::<M>;
+        t[0x97] = stw_dir::<M>;
+        t[0x98] = eord_dir::<M>;
+        t[0x99] = adcd_dir::<M>;
+        t[0x9A] = ord_dir::<M>;
+        t[0x9B] = addw_dir::<M>;
+        t[0xA0] = subw_idx::<M>;
+        t[0xA1] = cmpw_idx::<M>;
+        t[0xA2] = sbcd_idx::<M>;
+        t[0xA4] = andd_idx::<M>;
+        t[0xA5] = bitd_idx::<M>;
+        t[0xA6] = ldw_idx::<M>;
+        t[0xA7] = stw_idx::<M>;
+        t[0xA8] = eord_idx::<M>;
+        t[0xA9] = adcd_idx::<M>;
+        t[0xAA] = ord_idx::<M>;
+        t[0xAB] = addw_idx::<M>;
+        t[0xB0] = subw_ext::<M>;
+        t[0xB1] = cmpw_ext::<M>;
+        t[0xB2] = sbcd_ext::<M>;
+        t[0xB4] = andd_ext::<M>;
+        t[0xB5] = bitd_ext::<M>;
+        t[0xB6] = ldw_ext::<M>;
+        t[0xB7] = stw_ext::<M>;
+        t[0xB8] = eord_ext::<M>;
+        t[0xB9] = adcd_ext::<M>;
+        t[0xBA] = ord_ext::<M>;
+        t[0xBB] = addw_ext::<M>;
+        t[0xDC] = ldq_dir::<M>;
+        t[0xDD] = stq_dir::<M>;
+        t[0xEC] = ldq_idx::<M>;
+        t[0xED] = stq_idx::<M>;
+        t[0xFC] = ldq_ext::<M>;
+        t[0xFD] = stq_ext::<M>;
+        t
+    };
+
+    /// Handler table for Page 2 opcodes on the 6309.
+    const HD6309_PAGE2: [Handler<M>; 256] = {
+        let mut t = strip(Self::PAGE2, PAGE2_UNDOCUMENTED, undefined::<M>);
+        t[0x30] = band::<M>;
+        t[0x31] = biand::<M>;
+        t[0x32] = bor::<M>;
+        t[0x33] = bior::<M>;
+        t[0x34] = beor::<M>;
+        t[0x35] = bieor::<M>;
+        t[0x36] = ldbt::<M>;
+        t[0x37] = stbt::<M>;
+        t[0x38] = tfm_inc_inc::<M>;
+        t[0x39] = tfm_dec_dec::<M>;
+        t[0x3A] = tfm_inc_src::<M>;
+        t[0x3B] = tfm_inc_dst::<M>;
+        t[0x3C] = bitmd::<M>;
+        t[0x3D] = ldmd::<M>;
+        t[0x43] = come::<M>;
+        t[0x4A] = dece::<M>;
+        t[0x4C] = ince::<M>;
+        t[0x4D] = tste::<M>;
+        t[0x4F] = clre::<M>;
+        t[0x53] = comf::<M>;
+        t[0x5A] = decf::<M>;
+        t[0x5C] = incf::<M>;
+        t[0x5D] = tstf::<M>;
+        t[0x5F] = clrf::<M>;
+        t[0x80] = sube_imm::<M>;
+        t[0x81] = cmpe_imm::<M>;
+        t[0x86] = lde_imm::<M>;
+        t[0x8B] = adde_imm::<M>;
+        t[0x8D] = divd_imm::<M>;
+        t[0x8E] = divq_imm::<M>;
+        t[0x8F] = muld_imm::<M>;
+        t[0x90] = sube_dir::<M>;
+        t[0x91] = cmpe_dir::<M>;
+        t[0x96] = lde_dir::<M>;
+        t[0x97] = ste_dir::<M>;
+        t[0x9B] = adde_dir::<M>;
+        t[0x9D] = divd_dir::<M>;
+        t[0x9E] = divq_dir::<M>;
+        t[0x9F] = muld_dir::<M>;
+        t[0xA0] = sube_idx::<M>;
+        t[0xA1] = cmpe_idx::<M>;
+        t[0xA6] = lde_idx::<M>;
+        t[0xA7] = ste_idx::<M>;
+        t[0xAB] = adde_idx::<M>;
+        t[0xAD] = divd_idx::<M>;
+        t[0xAE] = divq_idx::<M>;
+        t[0xAF] = muld_idx::<M>;
+        t[0xB0] = sube_ext::<M>;
+        t[0xB1] = cmpe_ext::<M>;
+        t[0xB6] = lde_ext::<M>;
+        t[0xB7] = ste_ext::<M>;
+        t[0xBB] = adde_ext::<M>;
+        t[0xBD] = divd_ext::<M>;
+        t[0xBE] = divq_ext::<M>;
+        t[0xBF] = muld_ext::<M>;
+        t[0xC0] = subf_imm::<M>;
+        t[0xC1] = cmpf_imm::<M>;
+        t[0xC6] = ldf_imm::<M>;
+        t[0xCB] = addf_imm::<M>;
+        t[0xD0] = subf_dir::<M>;
+        t[0xD1] = cmpf_dir::<M>;
+        t[0xD6] = ldf_dir::<M>;
+        t[0xD7] = stf_dir::<M>;
+        t[0xDB] = addf_dir::<M>;
+        t[0xE0] = subf_idx::<M>;
+        t[0xE1] = cmpf_idx::<M>;
+        t[0xE6] = ldf_idx::<M>;
+        t[0xE7] = stf_idx::<M>;
+        t[0xEB] = addf_idx::<M>;
+        t[0xF0] = subf_ext::<M>;
+        t[0xF1] = cmpf_ext::<M>;
+        t[0xF6] = ldf_ext::<M>;
+        t[0xF7] = stf_ext::<M>;
+        t[0xFB] = addf_ext::<M>;
+        t
+    };
+}
+
+/// 6309 opcodes that can leave the straight-line instruction stream on top
+/// of those the 6809 tables report: the register-to-register operations,
+/// which may write PC, DIVD and DIVQ, which trap on division by zero, and
+/// TFM, which runs again until its transfer is done.
+pub(super) fn ends_block(page: Page, opcode: u8) -> bool {
+    match page {
+        Page::Page0 => false,
+        Page::Page1 => matches!(opcode, 0x30..=0x37),
+        Page::Page2 => matches!(opcode, 0x38..=0x3B) || matches!(opcode & 0xCF, 0x8D | 0x8E),
+    }
+}
+
+/// Execute a page-local opcode on the 6309 (prefix and opcode already
+/// fetched) and return its cycle count.
+///
+/// Opcodes the 6309 does not define are illegal, on every page.
+pub(super) fn execute<M: Memory>(cpu: &mut Cpu, mem: &mut M, page: Page, opcode: u8) -> u8 {
+    let op = opcode as usize;
+    // The cycle count follows the mode the instruction started in.
+    let native = cpu.native_mode();
+    match page {
+        Page::Page0 => {
+            let base = if native {
+                HD6309_NATIVE_PAGE0_CYCLES[op]
+            } else {
+                HD6309_PAGE0_CYCLES[op]
+            };
+            base + Dispatch::<M>::HD6309_PAGE0[op](cpu, mem)
+        }
+        Page::Page1 => match Dispatch::<M>::HD6309_PAGE1[op](cpu, mem) {
+            UNDEFINED => 2 + cpu.illegal_opcode(mem, 2),
+            extra if native => HD6309_NATIVE_PAGE1_CYCLES[op] + extra,
+            extra => HD6309_PAGE1_CYCLES[op] + extra,
+        },
+        Page::Page2 => match Dispatch::<M>::HD6309_PAGE2[op](cpu, mem) {
+            UNDEFINED => 2 + cpu.illegal_opcode(mem, 2),
+            extra if native => HD6309_NATIVE_PAGE2_CYCLES[op] + extra,
+            extra => HD6309_PAGE2_CYCLES[op] + extra,
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Q (D:W)
+// ---------------------------------------------------------------------------
+
+/// Read the 32-bit Q register (D:W).
+fn q(cpu: &Cpu) -> u32 {
+    ((cpu.reg.d as u32) << 16) | cpu.reg6309.w as u32
+}
+
+/// Write the 32-bit Q register (D:W).
+fn set_q(cpu: &mut Cpu, val: u32) {
+    cpu.reg.d = (val >> 16) as u16;
+    cpu.reg6309.w = val as u16;
+}
+
+// =================================================================
+// Page 0 — SEXW, LDQ immediate
+// =================================================================
+fn sexw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // SEXW: sign-extend W into D. Sets N and Z from Q.
+    let w = cpu.reg6309.w;
+    cpu.reg.d = if w & 0x8000 != 0 { 0xFFFF } else { 0x0000 };
+    cpu.reg.cc.set_negative(w & 0x8000 != 0);
+    cpu.reg.cc.set_zero(w == 0);
+    0
+}
+
+fn ldq_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let hi = cpu.fetch_word(mem);
+    let lo = cpu.fetch_word(mem);
+    let v = ((hi as u32) << 16) | lo as u32;
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    set_q(cpu, v);
+    0
+}
+
+// =================================================================
+// Register-to-register operations (ADDR through CMPR)
+// =================================================================
+// The post-byte names the source (high nibble) and destination (low
+// nibble) as for TFR. The operation has the destination's size: a 16-bit
+// source gives its low byte to an 8-bit destination, and an 8-bit source
+// is zero-extended for a 16-bit one. The zero register reads as 0 and
+// discards a result written to it.
+
+/// Destination code, destination size, destination value and source value
+/// (cut or extended to the destination's size) of a register operation.
+fn reg_operands(cpu: &Cpu, post: u8) -> (u8, bool, u16, u16) {
+    let dst_code = post & 0x0F;
+    let (src, _) = read_reg(cpu, post >> 4);
+    let (dst, wide) = read_reg(cpu, dst_code);
+    let src = if wide { src } else { src & 0xFF };
+    (dst_code, wide, dst, src)
+}
+
+fn addr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ADDR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::add16(d, s, &mut cpu.reg.cc)
+    } else {
+        alu::add8(d as u8, s as u8, &mut cpu.reg.cc) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn adcr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ADCR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::adc16(d, s, &mut cpu.reg.cc)
+    } else {
+        alu::adc8(d as u8, s as u8, &mut cpu.reg.cc) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn subr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // SUBR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::sub16(d, s, &mut cpu.reg.cc)
+    } else {
+        sub8(cpu, d as u8, s as u8) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn sbcr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // SBCR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::sbc16(d, s, &mut cpu.reg.cc)
+    } else {
+        sbc8(cpu, d as u8, s as u8) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn andr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ANDR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::and16(d, s, &mut cpu.reg.cc)
+    } else {
+        alu::and8(d as u8, s as u8, &mut cpu.reg.cc) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn orr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ORR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::or16(d, s, &mut cpu.reg.cc)
+    } else {
+        alu::or8(d as u8, s as u8, &mut cpu.reg.cc) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn eorr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // EORR
+    let post = cpu.fetch_byte(mem);
+    let (code, wide, d, s) = reg_operands(cpu, post);
+    let r = if wide {
+        alu::eor16(d, s, &mut cpu.reg.cc)
+    } else {
+        alu::eor8(d as u8, s as u8, &mut cpu.reg.cc) as u16
+    };
+    write_reg(cpu, code, r);
+    0
+}
+
+fn cmpr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CMPR
+    let post = cpu.fetch_byte(mem);
+    let (_, wide, d, s) = reg_operands(cpu, post);
+    if wide {
+        alu::sub16(d, s, &mut cpu.reg.cc);
+    } else {
+        sub8(cpu, d as u8, s as u8);
+    }
+    0
+}
+
+// =================================================================
+// PSHSW / PULSW / PSHUW / PULUW
+// =================================================================
+fn pshsw<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PSHSW
+    cpu.push_word_s(mem, cpu.reg6309.w);
+    0
+}
+
+fn pulsw<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PULSW
+    cpu.reg6309.w = cpu.pull_word_s(mem);
+    0
+}
+
+fn pshuw<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PSHUW
+    cpu.push_word_u(mem, cpu.reg6309.w);
+    0
+}
+
+fn puluw<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PULUW
+    cpu.reg6309.w = cpu.pull_word_u(mem);
+    0
+}
+
+// =================================================================
+// Inherent D
+// =================================================================
+fn negd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // NEGD
+    let d = cpu.reg.d;
+    let r = alu::neg16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn comd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // COMD
+    let d = cpu.reg.d;
+    let r = alu::com16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn lsrd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // LSRD
+    let d = cpu.reg.d;
+    let r = alu::lsr16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn rord<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // RORD
+    let d = cpu.reg.d;
+    let r = alu::ror16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn asrd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // ASRD
+    let d = cpu.reg.d;
+    let r = alu::asr16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn asld<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // ASLD
+    let d = cpu.reg.d;
+    let r = alu::asl16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn rold<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // ROLD
+    let d = cpu.reg.d;
+    let r = alu::rol16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn decd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // DECD
+    let d = cpu.reg.d;
+    let r = alu::dec16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn incd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // INCD
+    let d = cpu.reg.d;
+    let r = alu::inc16(d, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn tstd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // TSTD
+    let d = cpu.reg.d;
+    alu::tst16(d, &mut cpu.reg.cc);
+    0
+}
+
+fn clrd<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRD
+    let r = alu::clr16(&mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+// =================================================================
+// Inherent W
+// =================================================================
+fn comw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // COMW
+    let w = cpu.reg6309.w;
+    let r = alu::com16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn lsrw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // LSRW
+    let w = cpu.reg6309.w;
+    let r = alu::lsr16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn rorw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // RORW
+    let w = cpu.reg6309.w;
+    let r = alu::ror16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn rolw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // ROLW
+    let w = cpu.reg6309.w;
+    let r = alu::rol16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn decw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // DECW
+    let w = cpu.reg6309.w;
+    let r = alu::dec16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn incw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // INCW
+    let w = cpu.reg6309.w;
+    let r = alu::inc16(w, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn tstw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // TSTW
+    let w = cpu.reg6309.w;
+    alu::tst16(w, &mut cpu.reg.cc);
+    0
+}
+
+fn clrw<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRW
+    let r = alu::clr16(&mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+// =================================================================
+// SUBW, CMPW, ADDW
+// =================================================================
+fn subw_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let w = cpu.reg6309.w;
+    let r = alu::sub16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn subw_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::sub16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn subw_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::sub16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    ex
+}
+
+fn subw_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::sub16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn cmpw_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let w = cpu.reg6309.w;
+    alu::sub16(w, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpw_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    alu::sub16(w, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpw_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    alu::sub16(w, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn cmpw_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    alu::sub16(w, v, &mut cpu.reg.cc);
+    0
+}
+
+fn addw_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let w = cpu.reg6309.w;
+    let r = alu::add16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn addw_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::add16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+fn addw_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::add16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    ex
+}
+
+fn addw_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let w = cpu.reg6309.w;
+    let r = alu::add16(w, v, &mut cpu.reg.cc);
+    cpu.reg6309.w = r;
+    0
+}
+
+// =================================================================
+// SBCD, ANDD, BITD, EORD, ADCD, ORD
+// =================================================================
+fn sbcd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::sbc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn sbcd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sbc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn sbcd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sbc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn sbcd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sbc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn andd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::and16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn andd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::and16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn andd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::and16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn andd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::and16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn bitd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    alu::and16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn bitd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::and16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn bitd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::and16(d, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn bitd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::and16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn eord_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::eor16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn eord_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::eor16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn eord_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::eor16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn eord_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::eor16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn adcd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::adc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn adcd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::adc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn adcd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::adc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn adcd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::adc16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn ord_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::or16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn ord_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::or16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn ord_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::or16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn ord_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::or16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+// =================================================================
+// LDW / STW
+// =================================================================
+fn ldw_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.w = v;
+    0
+}
+
+fn ldw_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.w = v;
+    0
+}
+
+fn ldw_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.w = v;
+    ex
+}
+
+fn ldw_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.w = v;
+    0
+}
+
+fn stw_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg6309.w;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+fn stw_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg6309.w;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
+
+fn stw_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg6309.w;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// =================================================================
+// LDQ / STQ
+// =================================================================
+fn ldq_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let hi = mem.read_word(addr);
+    let lo = mem.read_word(addr.wrapping_add(2));
+    let v = ((hi as u32) << 16) | lo as u32;
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    set_q(cpu, v);
+    0
+}
+
+fn ldq_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let hi = mem.read_word(addr);
+    let lo = mem.read_word(addr.wrapping_add(2));
+    let v = ((hi as u32) << 16) | lo as u32;
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    set_q(cpu, v);
+    ex
+}
+
+fn ldq_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let hi = mem.read_word(addr);
+    let lo = mem.read_word(addr.wrapping_add(2));
+    let v = ((hi as u32) << 16) | lo as u32;
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    set_q(cpu, v);
+    0
+}
+
+fn stq_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = q(cpu);
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, (v >> 16) as u16);
+    mem.write_word(addr.wrapping_add(2), v as u16);
+    0
+}
+
+fn stq_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = q(cpu);
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, (v >> 16) as u16);
+    mem.write_word(addr.wrapping_add(2), v as u16);
+    ex
+}
+
+fn stq_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = q(cpu);
+    alu::ld32_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, (v >> 16) as u16);
+    mem.write_word(addr.wrapping_add(2), v as u16);
+    0
+}
+
+// =================================================================
+// MULD, DIVD, DIVQ
+// =================================================================
+
+/// MULD: Q = D × operand, signed.
+fn muld(cpu: &mut Cpu, v: u16) {
+    let r = alu::muld(cpu.reg.d, v, &mut cpu.reg.cc);
+    set_q(cpu, r);
+}
+
+/// DIVD: D ÷ operand, signed; quotient to B, remainder to A. Division by
+/// zero traps. Returns the cycles the trap adds.
+fn divd<M: Memory>(cpu: &mut Cpu, mem: &mut M, divisor: u8) -> u8 {
+    if divisor == 0 {
+        return division_by_zero(cpu, mem);
+    }
+    if let Some((quotient, remainder)) = alu::divd(cpu.reg.d, divisor, &mut cpu.reg.cc) {
+        cpu.reg.set_b(quotient);
+        cpu.reg.set_a(remainder);
+    }
+    0
+}
+
+/// DIVQ: Q ÷ operand, signed; quotient to W, remainder to D. Division by
+/// zero traps. Returns the cycles the trap adds.
+fn divq<M: Memory>(cpu: &mut Cpu, mem: &mut M, divisor: u16) -> u8 {
+    if divisor == 0 {
+        return division_by_zero(cpu, mem);
+    }
+    if let Some((quotient, remainder)) = alu::divq(q(cpu), divisor, &mut cpu.reg.cc) {
+        cpu.reg6309.w = quotient;
+        cpu.reg.d = remainder;
+    }
+    0
+}
+
+fn muld_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    muld(cpu, v);
+    0
+}
+
+fn muld_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    muld(cpu, v);
+    0
+}
+
+fn muld_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    muld(cpu, v);
+    ex
+}
+
+fn muld_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    muld(cpu, v);
+    0
+}
+
+fn divd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    divd(cpu, mem, v)
+}
+
+fn divd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    divd(cpu, mem, v)
+}
+
+fn divd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    ex + divd(cpu, mem, v)
+}
+
+fn divd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    divd(cpu, mem, v)
+}
+
+fn divq_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    divq(cpu, mem, v)
+}
+
+fn divq_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    divq(cpu, mem, v)
+}
+
+fn divq_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    ex + divq(cpu, mem, v)
+}
+
+fn divq_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    divq(cpu, mem, v)
+}
+
+/// Take the division-by-zero trap. Returns the cycles it adds.
+fn division_by_zero<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    cpu.hd6309_trap(mem, MD_DZ);
+    cpu.entire_state_cycles(19) as u8
+}
+
+// =================================================================
+// BITMD / LDMD
+// =================================================================
+fn bitmd<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BITMD: only the IL and DZ status bits can be tested; the tested
+    // bits are cleared.
+    let mask = cpu.fetch_byte(mem) & (MD_IL | MD_DZ);
+    cpu.reg.cc.set_zero(cpu.reg6309.md & mask == 0);
+    cpu.reg6309.md &= !mask;
+    0
+}
+
+fn ldmd<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDMD: only the native mode and FIRQ mode bits can be written.
+    let val = cpu.fetch_byte(mem) & (MD_NATIVE | MD_FIRQ_IRQ);
+    cpu.reg6309.md = (cpu.reg6309.md & !(MD_NATIVE | MD_FIRQ_IRQ)) | val;
+    0
+}
+
+// =================================================================
+// OIM, AIM, EIM, TIM
+// =================================================================
+// The immediate operand comes before the address.
+
+fn oim_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let r = alu::or8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn oim_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let r = alu::or8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn oim_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let r = alu::or8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn aim_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let r = alu::and8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn aim_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let r = alu::and8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn aim_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let r = alu::and8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn eim_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let r = alu::eor8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn eim_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let r = alu::eor8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn eim_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let r = alu::eor8(v, imm, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn tim_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    alu::and8(v, imm, &mut cpu.reg.cc);
+    0
+}
+
+fn tim_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    alu::and8(v, imm, &mut cpu.reg.cc);
+    ex
+}
+
+fn tim_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let imm = cpu.fetch_byte(mem);
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    alu::and8(v, imm, &mut cpu.reg.cc);
+    0
+}
+
+// =================================================================
+// BAND, BIAND, BOR, BIOR, BEOR, BIEOR, LDBT, STBT
+// =================================================================
+// The post-byte names the register (bits 7-6: CC, A or B), the memory bit
+// (bits 5-3) and the register bit (bits 2-0); the memory byte is direct.
+
+/// Read the register a bit-transfer post-byte names, or `None` for the
+/// undefined code 3.
+fn bit_reg(cpu: &Cpu, post: u8) -> Option<u8> {
+    match post >> 6 {
+        0 => Some(cpu.reg.cc.to_byte()),
+        1 => Some(cpu.reg.a()),
+        2 => Some(cpu.reg.b()),
+        _ => None,
+    }
+}
+
+/// Write the register a bit-transfer post-byte names.
+fn set_bit_reg(cpu: &mut Cpu, post: u8, val: u8) {
+    match post >> 6 {
+        0 => cpu.reg.cc = ConditionCodes::from_byte(val),
+        1 => cpu.reg.set_a(val),
+        _ => cpu.reg.set_b(val),
+    }
+}
+
+/// Combine the memory bit into the register bit with `op`. Returns the
+/// cycles beyond the base, or the illegal-opcode cycles for register code 3.
+fn bit_op<M: Memory>(cpu: &mut Cpu, mem: &mut M, op: fn(bool, bool) -> bool) -> u8 {
+    let post = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let Some(reg) = bit_reg(cpu, post) else {
+        return cpu.illegal_opcode(mem, 4);
+    };
+    let mem_bit = mem.read(addr) & (1 << ((post >> 3) & 7)) != 0;
+    let mask = 1 << (post & 7);
+    let reg_bit = op(reg & mask != 0, mem_bit);
+    set_bit_reg(cpu, post, if reg_bit { reg | mask } else { reg & !mask });
+    0
+}
+
+fn band<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BAND
+    bit_op(cpu, mem, |r, m| r & m)
+}
+
+fn biand<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BIAND
+    bit_op(cpu, mem, |r, m| r & !m)
+}
+
+fn bor<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BOR
+    bit_op(cpu, mem, |r, m| r | m)
+}
+
+fn bior<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BIOR
+    bit_op(cpu, mem, |r, m| r | !m)
+}
+
+fn beor<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BEOR
+    bit_op(cpu, mem, |r, m| r ^ m)
+}
+
+fn bieor<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BIEOR
+    bit_op(cpu, mem, |r, m| r ^ !m)
+}
+
+fn ldbt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDBT
+    bit_op(cpu, mem, |_, m| m)
+}
+
+fn stbt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // STBT: the register bit to the memory bit
+    let post = cpu.fetch_byte(mem);
+    let addr = cpu.addr_direct(mem);
+    let Some(reg) = bit_reg(cpu, post) else {
+        return cpu.illegal_opcode(mem, 4);
+    };
+    let reg_bit = reg & (1 << (post & 7)) != 0;
+    let mask = 1 << ((post >> 3) & 7);
+    let v = mem.read(addr);
+    mem.write(addr, if reg_bit { v | mask } else { v & !mask });
+    0
+}
+
+// =================================================================
+// TFM
+// =================================================================
+// A TFM moves one byte a step. While W has not reached zero it leaves PC
+// on itself, so that the host clocks its devices between bytes and an
+// interrupt can be taken: the TFM's own address is stacked, and after RTI
+// it carries on from W and the pointers as they were.
+
+/// The register a TFM post-byte nibble names: D, X, Y, U or S.
+fn tfm_reg(cpu: &mut Cpu, code: u8) -> Option<&mut u16> {
+    match code {
+        0x0 => Some(&mut cpu.reg.d),
+        0x1 => Some(&mut cpu.reg.x),
+        0x2 => Some(&mut cpu.reg.y),
+        0x3 => Some(&mut cpu.reg.u),
+        0x4 => Some(&mut cpu.reg.s),
+        _ => None,
+    }
+}
+
+/// Move one byte, stepping the source and destination pointers by
+/// `src_step` and `dst_step`. Returns the cycles beyond the base of 3 a byte.
+fn tfm<M: Memory>(cpu: &mut Cpu, mem: &mut M, src_step: i16, dst_step: i16) -> u8 {
+    let post = cpu.fetch_byte(mem);
+    let (src_code, dst_code) = (post >> 4, post & 0x0F);
+    let (Some(src), Some(dst)) = (
+        tfm_reg(cpu, src_code).map(|r| *r),
+        tfm_reg(cpu, dst_code).map(|r| *r),
+    ) else {
+        return cpu.illegal_opcode(mem, 3);
+    };
+    // 6 cycles of set-up, unless carrying on from the last step
+    let setup = if cpu.tfm { 0 } else { 6 };
+    if cpu.reg6309.w == 0 {
+        cpu.tfm = false;
+        return 3;
+    }
+    let v = mem.read(src);
+    mem.write(dst, v);
+    if let Some(r) = tfm_reg(cpu, src_code) {
+        *r = src.wrapping_add_signed(src_step);
+    }
+    if let Some(r) = tfm_reg(cpu, dst_code) {
+        *r = dst.wrapping_add_signed(dst_step);
+    }
+    cpu.reg6309.w -= 1;
+    cpu.tfm = cpu.reg6309.w != 0;
+    if cpu.tfm {
+        cpu.reg.pc = cpu.reg.pc.wrapping_sub(3);
+    }
+    setup
+}
+
+fn tfm_inc_inc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TFM r+,r+
+    tfm(cpu, mem, 1, 1)
+}
+
+fn tfm_dec_dec<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TFM r-,r-
+    tfm(cpu, mem, -1, -1)
+}
+
+fn tfm_inc_src<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TFM r+,r
+    tfm(cpu, mem, 1, 0)
+}
+
+fn tfm_inc_dst<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TFM r,r+
+    tfm(cpu, mem, 0, 1)
+}
+
+// =================================================================
+// Inherent E / F
+// =================================================================
+fn come<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // COME
+    let e = cpu.reg6309.e();
+    let r = alu::com8(e, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn dece<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // DECE
+    let e = cpu.reg6309.e();
+    let r = alu::dec8(e, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn ince<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // INCE
+    let e = cpu.reg6309.e();
+    let r = alu::inc8(e, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn tste<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // TSTE
+    let e = cpu.reg6309.e();
+    alu::tst8(e, &mut cpu.reg.cc);
+    0
+}
+
+fn clre<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRE
+    let r = alu::clr8(&mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn comf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // COMF
+    let f = cpu.reg6309.f();
+    let r = alu::com8(f, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn decf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // DECF
+    let f = cpu.reg6309.f();
+    let r = alu::dec8(f, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn incf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // INCF
+    let f = cpu.reg6309.f();
+    let r = alu::inc8(f, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn tstf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // TSTF
+    let f = cpu.reg6309.f();
+    alu::tst8(f, &mut cpu.reg.cc);
+    0
+}
+
+fn clrf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRF
+    let r = alu::clr8(&mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+// =================================================================
+// SUBE, CMPE, LDE, STE, ADDE
+// =================================================================
+fn sube_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let e = cpu.reg6309.e();
+    let r = sub8(cpu, e, v);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn sube_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = sub8(cpu, e, v);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn sube_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = sub8(cpu, e, v);
+    cpu.reg6309.set_e(r);
+    ex
+}
+
+fn sube_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = sub8(cpu, e, v);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn cmpe_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let e = cpu.reg6309.e();
+    sub8(cpu, e, v);
+    0
+}
+
+fn cmpe_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    sub8(cpu, e, v);
+    0
+}
+
+fn cmpe_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    sub8(cpu, e, v);
+    ex
+}
+
+fn cmpe_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    sub8(cpu, e, v);
+    0
+}
+
+fn lde_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(v);
+    0
+}
+
+fn lde_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(v);
+    0
+}
+
+fn lde_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(v);
+    ex
+}
+
+fn lde_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(v);
+    0
+}
+
+fn ste_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg6309.e();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn ste_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg6309.e();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    ex
+}
+
+fn ste_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg6309.e();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn adde_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let e = cpu.reg6309.e();
+    let r = alu::add8(e, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn adde_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = alu::add8(e, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+fn adde_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = alu::add8(e, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    ex
+}
+
+fn adde_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let e = cpu.reg6309.e();
+    let r = alu::add8(e, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_e(r);
+    0
+}
+
+// =================================================================
+// SUBF, CMPF, LDF, STF, ADDF
+// =================================================================
+fn subf_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let f = cpu.reg6309.f();
+    let r = sub8(cpu, f, v);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn subf_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = sub8(cpu, f, v);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn subf_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = sub8(cpu, f, v);
+    cpu.reg6309.set_f(r);
+    ex
+}
+
+fn subf_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = sub8(cpu, f, v);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn cmpf_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let f = cpu.reg6309.f();
+    sub8(cpu, f, v);
+    0
+}
+
+fn cmpf_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    sub8(cpu, f, v);
+    0
+}
+
+fn cmpf_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    sub8(cpu, f, v);
+    ex
+}
+
+fn cmpf_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    sub8(cpu, f, v);
+    0
+}
+
+fn ldf_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(v);
+    0
+}
+
+fn ldf_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(v);
+    0
+}
+
+fn ldf_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(v);
+    ex
+}
+
+fn ldf_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(v);
+    0
+}
+
+fn stf_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg6309.f();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn stf_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg6309.f();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    ex
+}
+
+fn stf_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg6309.f();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn addf_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let f = cpu.reg6309.f();
+    let r = alu::add8(f, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn addf_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = alu::add8(f, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+fn addf_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = alu::add8(f, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    ex
+}
+
+fn addf_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let f = cpu.reg6309.f();
+    let r = alu::add8(f, v, &mut cpu.reg.cc);
+    cpu.reg6309.set_f(r);
+    0
+}
+
+// ---------------------------------------------------------------------------
+// Undefined opcodes
+// ---------------------------------------------------------------------------
+
+fn illegal<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    cpu.illegal_opcode(mem, 1)
+}
+
+fn undefined<M: Memory>(_cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    UNDEFINED
+}
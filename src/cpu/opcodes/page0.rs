@@ -13,28 +13,30 @@
 //   limitations under the License.
 
 //! Page 0 opcode implementations (0x00..0xFF, excluding 0x10/0x11 page prefixes).
-//! Contains all undocumented page 0 opcodes except all store immediate,
+//! Contains all undocumented page 0 opcodes; the store immediates only run
+//! with [`Cpu::set_undocumented_opcodes`] enabled and are illegal otherwise,
 //! source: <https://github.com/hoglet67/6809Decoder/wiki/Undocumented-6809-Behaviours>
 
+use super::{Dispatch, Handler};
 use crate::alu;
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, CpuVariant, HalfCarryQuirks, Swi, TransferQuirks};
 use crate::memory::Memory;
-use crate::registers::{CC_C, CC_F, CC_H, CC_I, CC_N, CC_V, CC_Z};
+use crate::registers::{CC_C, CC_F, CC_H, CC_I, CC_N, CC_V, CC_Z, Condition, Reg};
 
 /// Base cycle counts for Page 0 opcodes (0x00..0xFF).
 /// Indexed-mode entries show the *base* cycles; extra cycles from the
 /// post-byte are added separately.
 #[rustfmt::skip]
-const PAGE0_CYCLES: [u8; 256] = [
+pub(super) const PAGE0_CYCLES: [u8; 256] = [
 //  0   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
     6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  3,  6, // 0x
     1,  1,  2,  2,  1,  1,  5,  9,  3,  2,  3,  2,  3,  2,  8,  7, // 1x (10,11 = page prefix)
     3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3,  3, // 2x
     4,  4,  4,  4,  5,  5,  5,  5,  4,  5,  3,  6, 21, 11, 19, 19, // 3x
-    2,  1,  1,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  1,  2, // 4x
-    2,  1,  1,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  1,  2, // 5x
-    6,  1,  1,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  3,  6, // 6x
-    7,  1,  1,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  4,  7, // 7x
+    2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2, // 4x
+    2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2,  2, // 5x
+    6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  6,  3,  6, // 6x
+    7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  7,  4,  7, // 7x
     2,  2,  2,  4,  2,  2,  2,  1,  2,  2,  2,  2,  4,  7,  3,  1, // 8x
     4,  4,  4,  6,  4,  4,  4,  4,  4,  4,  4,  4,  6,  7,  5,  5, // 9x
     4,  4,  4,  6,  4,  4,  4,  4,  4,  4,  4,  4,  6,  7,  5,  5, // Ax
@@ -50,1709 +52,2434 @@ pub(super) fn cycles(opcode: u8) -> u8 {
     PAGE0_CYCLES[opcode as usize]
 }
 
-pub fn execute(cpu: &mut Cpu, mem: &mut impl Memory, opcode: u8) {
-    cpu.cycles += PAGE0_CYCLES[opcode as usize] as u64;
+impl<M: Memory> Dispatch<M> {
+    /// Handler table for Page 0 opcodes. Unlisted opcodes are illegal.
+    pub(super) const PAGE0: [Handler<M>; 256] = dispatch_table!(M, illegal;
+        0x00 | 0x01 => neg_dir,
+        0x02 => xnc_dir,
+        0x03 => com_dir,
+        0x04 | 0x05 => lsr_dir,
+        0x06 => ror_dir,
+        0x07 => asr_dir,
+        0x08 => asl_dir,
+        0x09 => rol_dir,
+        0x0A => dec_dir,
+        0x0B => xdec_dir,
+        0x0C => inc_dir,
+        0x0D => tst_dir,
+        0x0E => jmp_dir,
+        0x0F => clr_dir,
+        0x12 | 0x1B => nop,
+        0x13 => sync,
+        0x14 | 0x15 | 0xCD => xhcf,
+        0x16 => lbra,
+        0x17 => lbsr,
+        0x18 => x18,
+        0x19 => daa,
+        0x1A => orcc,
+        0x1C => andcc,
+        0x1D => sex,
+        0x1E => exg_imm,
+        0x1F => tfr_imm,
+        0x20 => bra,
+        0x21 => brn,
+        0x22 => bhi,
+        0x23 => bls,
+        0x24 => bcc,
+        0x25 => bcs,
+        0x26 => bne,
+        0x27 => beq,
+        0x28 => bvc,
+        0x29 => bvs,
+        0x2A => bpl,
+        0x2B => bmi,
+        0x2C => bge,
+        0x2D => blt,
+        0x2E => bgt,
+        0x2F => ble,
+        0x30 => leax,
+        0x31 => leay,
+        0x32 => leas,
+        0x33 => leau,
+        0x34 => pshs_imm,
+        0x35 => puls_imm,
+        0x36 => pshu_imm,
+        0x37 => pulu_imm,
+        0x38 => xandcc,
+        0x39 => rts,
+        0x3A => abx,
+        0x3B => rti,
+        0x3C => cwai,
+        0x3D => mul,
+        0x3E => xres,
+        0x3F => swi,
+        0x40 | 0x41 => nega,
+        0x42 => xnca,
+        0x43 => coma,
+        0x44 | 0x45 => lsra,
+        0x46 => rora,
+        0x47 => asra,
+        0x48 => asla,
+        0x49 => rola,
+        0x4A => deca,
+        0x4B => xdeca,
+        0x4C => inca,
+        0x4D => tsta,
+        0x4E => xclra,
+        0x4F => clra,
+        0x50 | 0x51 => negb,
+        0x52 => xncb,
+        0x53 => comb,
+        0x54 | 0x55 => lsrb,
+        0x56 => rorb,
+        0x57 => asrb,
+        0x58 => aslb,
+        0x59 => rolb,
+        0x5A => decb,
+        0x5B => xdecb,
+        0x5C => incb,
+        0x5D => tstb,
+        0x5E => xclrb,
+        0x5F => clrb,
+        0x60 | 0x61 => neg_idx,
+        0x62 => xnc_idx,
+        0x63 => com_idx,
+        0x64 | 0x65 => lsr_idx,
+        0x66 => ror_idx,
+        0x67 => asr_idx,
+        0x68 => asl_idx,
+        0x69 => rol_idx,
+        0x6A => dec_idx,
+        0x6B => xdec_idx,
+        0x6C => inc_idx,
+        0x6D => tst_idx,
+        0x6E => jmp_idx,
+        0x6F => clr_idx,
+        0x70 | 0x71 => neg_ext,
+        0x72 => xnc_ext,
+        0x73 => com_ext,
+        0x74 | 0x75 => lsr_ext,
+        0x76 => ror_ext,
+        0x77 => asr_ext,
+        0x78 => asl_ext,
+        0x79 => rol_ext,
+        0x7A => dec_ext,
+        0x7B => xdec_ext,
+        0x7C => inc_ext,
+        0x7D => tst_ext,
+        0x7E => jmp_ext,
+        0x7F => clr_ext,
+        0x80 => suba_imm,
+        0x81 => cmpa_imm,
+        0x82 => sbca_imm,
+        0x83 => subd_imm,
+        0x84 => anda_imm,
+        0x85 => bita_imm,
+        0x86 => lda_imm,
+        0x87 => xsta_imm,
+        0x88 => eora_imm,
+        0x89 => adca_imm,
+        0x8A => ora_imm,
+        0x8B => adda_imm,
+        0x8C => cmpx_imm,
+        0x8D => bsr,
+        0x8E => ldx_imm,
+        0x8F => xstx_imm,
+        0x90 => suba_dir,
+        0x91 => cmpa_dir,
+        0x92 => sbca_dir,
+        0x93 => subd_dir,
+        0x94 => anda_dir,
+        0x95 => bita_dir,
+        0x96 => lda_dir,
+        0x97 => sta_dir,
+        0x98 => eora_dir,
+        0x99 => adca_dir,
+        0x9A => ora_dir,
+        0x9B => adda_dir,
+        0x9C => cmpx_dir,
+        0x9D => jsr_dir,
+        0x9E => ldx_dir,
+        0x9F => stx_dir,
+        0xA0 => suba_idx,
+        0xA1 => cmpa_idx,
+        0xA2 => sbca_idx,
+        0xA3 => subd_idx,
+        0xA4 => anda_idx,
+        0xA5 => bita_idx,
+        0xA6 => lda_idx,
+        0xA7 => sta_idx,
+        0xA8 => eora_idx,
+        0xA9 => adca_idx,
+        0xAA => ora_idx,
+        0xAB => adda_idx,
+        0xAC => cmpx_idx,
+        0xAD => jsr_idx,
+        0xAE => ldx_idx,
+        0xAF => stx_idx,
+        0xB0 => suba_ext,
+        0xB1 => cmpa_ext,
+        0xB2 => sbca_ext,
+        0xB3 => subd_ext,
+        0xB4 => anda_ext,
+        0xB5 => bita_ext,
+        0xB6 => lda_ext,
+        0xB7 => sta_ext,
+        0xB8 => eora_ext,
+        0xB9 => adca_ext,
+        0xBA => ora_ext,
+        0xBB => adda_ext,
+        0xBC => cmpx_ext,
+        0xBD => jsr_ext,
+        0xBE => ldx_ext,
+        0xBF => stx_ext,
+        0xC0 => subb_imm,
+        0xC1 => cmpb_imm,
+        0xC2 => sbcb_imm,
+        0xC3 => addd_imm,
+        0xC4 => andb_imm,
+        0xC5 => bitb_imm,
+        0xC6 => ldb_imm,
+        0xC7 => xstb_imm,
+        0xC8 => eorb_imm,
+        0xC9 => adcb_imm,
+        0xCA => orb_imm,
+        0xCB => addb_imm,
+        0xCC => ldd_imm,
+        0xCE => ldu_imm,
+        0xCF => xstu_imm,
+        0xD0 => subb_dir,
+        0xD1 => cmpb_dir,
+        0xD2 => sbcb_dir,
+        0xD3 => addd_dir,
+        0xD4 => andb_dir,
+        0xD5 => bitb_dir,
+        0xD6 => ldb_dir,
+        0xD7 => stb_dir,
+        0xD8 => eorb_dir,
+        0xD9 => adcb_dir,
+        0xDA => orb_dir,
+        0xDB => addb_dir,
+        0xDC => ldd_dir,
+        0xDD => std_dir,
+        0xDE => ldu_dir,
+        0xDF => stu_dir,
+        0xE0 => subb_idx,
+        0xE1 => cmpb_idx,
+        0xE2 => sbcb_idx,
+        0xE3 => addd_idx,
+        0xE4 => andb_idx,
+        0xE5 => bitb_idx,
+        0xE6 => ldb_idx,
+        0xE7 => stb_idx,
+        0xE8 => eorb_idx,
+        0xE9 => adcb_idx,
+        0xEA => orb_idx,
+        0xEB => addb_idx,
+        0xEC => ldd_idx,
+        0xED => std_idx,
+        0xEE => ldu_idx,
+        0xEF => stu_idx,
+        0xF0 => subb_ext,
+        0xF1 => cmpb_ext,
+        0xF2 => sbcb_ext,
+        0xF3 => addd_ext,
+        0xF4 => andb_ext,
+        0xF5 => bitb_ext,
+        0xF6 => ldb_ext,
+        0xF7 => stb_ext,
+        0xF8 => eorb_ext,
+        0xF9 => adcb_ext,
+        0xFA => orb_ext,
+        0xFB => addb_ext,
+        0xFC => ldd_ext,
+        0xFD => std_ext,
+        0xFE => ldu_ext,
+        0xFF => stu_ext,
+    );
+}
 
-    match opcode {
-        // =================================================================
-        // 0x00..0x0F — Direct-page read-modify-write + JMP/CLR
-        // =================================================================
-        0x00 | 0x01 => {
-            // NEG direct (0x00) and (0x01, undoc)
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::neg8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x02 => {
-            // XNC direct (undocumented)
-            // This instruction behaves like NEG if C=0 or COM if C=1
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = if cpu.reg.cc.carry() {
-                alu::com8(val, &mut cpu.reg.cc)
-            } else {
-                alu::neg8(val, &mut cpu.reg.cc)
-            };
-            mem.write(addr, r);
-        }
-        0x03 => {
-            // COM direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::com8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x04 | 0x05 => {
-            // LSR direct (0x04) and (0x05, undoc)
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::lsr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x06 => {
-            // ROR direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::ror8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x07 => {
-            // ASR direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::asr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x08 => {
-            // ASL/LSL direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::asl8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x09 => {
-            // ROL direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::rol8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x0A => {
-            // DEC direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x0B => {
-            // XDEC direct (undocumented)
-            // This instruction is similar to DEC instruction, except that
-            // the carry flag is modified.
-            // Flags:
-            // Z - set if the result is zero, otherwise cleared (the same as DEC)
-            // N - set if the result is negative, otherwise cleared (the same as DEC)
-            // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
-            // C - cleared if the operand is zero, otherwise set (different to DEC)
-            // all other flags unchanged
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            cpu.reg.cc.set_carry(val != 0);
-            mem.write(addr, r);
-        }
-        0x0C => {
-            // INC direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            let r = alu::inc8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x0D => {
-            // TST direct
-            let addr = cpu.addr_direct(mem);
-            let val = mem.read(addr);
-            alu::tst8(val, &mut cpu.reg.cc);
-        }
-        0x0E => {
-            // JMP direct
-            cpu.reg.pc = cpu.addr_direct(mem);
-        }
-        0x0F => {
-            // CLR direct
-            let addr = cpu.addr_direct(mem);
-            let r = alu::clr8(&mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
+/// Page 0 opcodes that can leave the straight-line instruction stream.
+///
+/// Includes EXG/TFR and PULS/PULU, which may load PC, and the page prefixes.
+pub(super) fn ends_block(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x0E | 0x10..=0x11 | 0x13..=0x17 | 0x1E..=0x2F | 0x35 | 0x37 | 0x39 | 0x3B | 0x3C | 0x3E | 0x3F
+            | 0x6E | 0x7E | 0x8D | 0x9D | 0xAD | 0xBD | 0xCD
+    )
+}
 
-        // =================================================================
-        // 0x12..0x1F — Inherent / misc
-        // =================================================================
-        0x12 => {} // NOP
-        0x13 => {
-            // SYNC
-            cpu.sync = true;
-        }
-        0x14 | 0x15 => {
-            // XHCF Halt and Catch Fire (undocumented)
-            cpu.halted = true;
-        }
-        0x16 => {
-            // LBRA
-            let addr = cpu.addr_relative16(mem);
-            cpu.reg.pc = addr;
-        }
-        0x17 => {
-            // LBSR
-            let addr = cpu.addr_relative16(mem);
-            cpu.push_word_s(mem, cpu.reg.pc);
-            cpu.reg.pc = addr;
-        }
-        0x18 => {
-            // X18 undocumented
-            // This instruction updates the flags where Bn is
-            // bit n of the byte at PC + 1 (the next opcode)
-            // Flags:
-            // E' = (F & B6)
-            // F' = (H & B5)
-            // H' = (I & B4)
-            // I' = (N & B3)
-            // N' = (Z & B2)
-            // Z' = (V & B1)
-            // V' = (C & B0) | (Z & B2)
-            // C' = 0
-            let post = mem.read(cpu.reg.pc);
-            let cc = cpu.reg.cc.to_byte();
-            let post_cc = cc & post;
-            cpu.reg.cc.set_entire(post_cc & CC_F != 0);
-            cpu.reg.cc.set_firq_inhibit(post_cc & CC_H != 0);
-            cpu.reg.cc.set_half_carry(post_cc & CC_I != 0);
-            cpu.reg.cc.set_irq_inhibit(post_cc & CC_N != 0);
-            cpu.reg.cc.set_negative(post_cc & CC_Z != 0);
-            cpu.reg.cc.set_zero(post_cc & CC_V != 0);
-            cpu.reg
-                .cc
-                .set_overflow((post_cc & CC_C) | (post_cc & CC_Z) != 0);
-            cpu.reg.cc.set_carry(false);
-        }
-        0x19 => {
-            // DAA
-            let a = cpu.reg.a();
-            let r = alu::daa(a, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x1A => {
-            // ORCC immediate
-            let val = cpu.fetch_byte(mem);
-            cpu.reg.cc.or_with(val);
-        }
-        0x1B => {} // NOP (undocumented)
-        0x1C => {
-            // ANDCC immediate
-            let val = cpu.fetch_byte(mem);
-            cpu.reg.cc.and_with(val);
-        }
-        0x1D => {
-            // SEX
-            let b = cpu.reg.b();
-            let d = alu::sex(b, &mut cpu.reg.cc);
-            cpu.reg.d = d;
-        }
-        0x1E => {
-            // EXG
-            let post = cpu.fetch_byte(mem);
-            exg(cpu, post);
-        }
-        0x1F => {
-            // TFR
-            let post = cpu.fetch_byte(mem);
-            tfr(cpu, post);
-        }
+/// Execute a Page 0 opcode (already fetched) through the dispatch table.
+///
+/// Returns the instruction's cycle count: the base cycles plus any extra
+/// cycles reported by the handler.
+pub(super) fn execute<M: Memory>(cpu: &mut Cpu, mem: &mut M, opcode: u8) -> u8 {
+    PAGE0_CYCLES[opcode as usize] + Dispatch::<M>::PAGE0[opcode as usize](cpu, mem)
+}
 
-        // =================================================================
-        // 0x20..0x2F — Short branches
-        // =================================================================
-        0x20 => {
-            // BRA
-            let addr = cpu.addr_relative8(mem);
-            cpu.reg.pc = addr;
-        }
-        0x21 => {
-            // BRN
-            let _addr = cpu.addr_relative8(mem);
-            // never branch
-        }
-        0x22 => {
-            // BHI: !(C|Z)
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.carry() && !cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x23 => {
-            // BLS: C|Z
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.carry() || cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x24 => {
-            // BHS/BCC: !C
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.carry() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x25 => {
-            // BLO/BCS: C
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.carry() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x26 => {
-            // BNE: !Z
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x27 => {
-            // BEQ: Z
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x28 => {
-            // BVC: !V
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x29 => {
-            // BVS: V
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2A => {
-            // BPL: !N
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.negative() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2B => {
-            // BMI: N
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.negative() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2C => {
-            // BGE: N==V  (N*V + !N*!V)
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.negative() == cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2D => {
-            // BLT: N!=V  (N*!V + !N*V)
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.negative() != cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2E => {
-            // BGT: !Z && N==V
-            let addr = cpu.addr_relative8(mem);
-            if !cpu.reg.cc.zero() && cpu.reg.cc.negative() == cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
-        }
-        0x2F => {
-            // BLE: Z || N!=V
-            let addr = cpu.addr_relative8(mem);
-            if cpu.reg.cc.zero() || cpu.reg.cc.negative() != cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-            }
+// =================================================================
+// 0x00..0x0F — Direct-page read-modify-write + JMP/CLR
+// =================================================================
+fn neg_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // NEG direct (0x00) and (0x01, undoc)
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = neg8(cpu, val);
+    mem.write(addr, r);
+    0
+}
+
+fn xnc_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XNC direct (undocumented)
+    // This instruction behaves like NEG if C=0 or COM if C=1
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = if cpu.reg.cc.carry() {
+        alu::com8(val, &mut cpu.reg.cc)
+    } else {
+        neg8(cpu, val)
+    };
+    mem.write(addr, r);
+    0
+}
+
+fn com_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // COM direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::com8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn lsr_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LSR direct (0x04) and (0x05, undoc)
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::lsr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn ror_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ROR direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::ror8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn asr_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ASR direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::asr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn asl_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ASL/LSL direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::asl8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn rol_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ROL direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::rol8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn dec_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // DEC direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn xdec_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XDEC direct (undocumented)
+    // This instruction is similar to DEC instruction, except that
+    // the carry flag is modified.
+    // Flags:
+    // Z - set if the result is zero, otherwise cleared (the same as DEC)
+    // N - set if the result is negative, otherwise cleared (the same as DEC)
+    // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
+    // C - cleared if the operand is zero, otherwise set (different to DEC)
+    // all other flags unchanged
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    cpu.reg.cc.set_carry(val != 0);
+    mem.write(addr, r);
+    0
+}
+
+fn inc_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // INC direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    let r = alu::inc8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn tst_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TST direct
+    let addr = cpu.addr_direct(mem);
+    let val = mem.read(addr);
+    alu::tst8(val, &mut cpu.reg.cc);
+    0
+}
+
+fn jmp_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JMP direct
+    cpu.reg.pc = cpu.addr_direct(mem);
+    0
+}
+
+fn clr_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CLR direct
+    let addr = cpu.addr_direct(mem);
+    let r = alu::clr8(&mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+// =================================================================
+// 0x12..0x1F — Inherent / misc
+// =================================================================
+fn nop<M: Memory>(_cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // NOP
+    0
+}
+
+fn sync<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // SYNC
+    cpu.sync = true;
+    0
+}
+
+fn xhcf<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XHCF Halt and Catch Fire (undocumented)
+    cpu.halted = true;
+    0
+}
+
+fn lbra<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBRA
+    let addr = cpu.addr_relative16(mem);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn lbsr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBSR
+    let addr = cpu.addr_relative16(mem);
+    cpu.push_word_s(mem, cpu.reg.pc);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn x18<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // X18 undocumented
+    // This instruction updates the flags where Bn is
+    // bit n of the byte at PC + 1 (the next opcode)
+    // Flags:
+    // E' = (F & B6)
+    // F' = (H & B5)
+    // H' = (I & B4)
+    // I' = (N & B3)
+    // N' = (Z & B2)
+    // Z' = (V & B1)
+    // V' = (C & B0) | (Z & B2)
+    // C' = 0
+    let post = mem.read(cpu.reg.pc);
+    let cc = cpu.reg.cc.to_byte();
+    let post_cc = cc & post;
+    cpu.reg.cc.set_entire(post_cc & CC_F != 0);
+    cpu.reg.cc.set_firq_inhibit(post_cc & CC_H != 0);
+    cpu.reg.cc.set_half_carry(post_cc & CC_I != 0);
+    cpu.reg.cc.set_irq_inhibit(post_cc & CC_N != 0);
+    cpu.reg.cc.set_negative(post_cc & CC_Z != 0);
+    cpu.reg.cc.set_zero(post_cc & CC_V != 0);
+    cpu.reg
+        .cc
+        .set_overflow((post_cc & CC_C) | (post_cc & CC_Z) != 0);
+    cpu.reg.cc.set_carry(false);
+    0
+}
+
+fn daa<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // DAA
+    let a = cpu.reg.a();
+    let r = alu::daa(a, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn orcc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ORCC immediate
+    let val = cpu.fetch_byte(mem);
+    cpu.reg.cc.or_with(val);
+    0
+}
+
+fn andcc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ANDCC immediate
+    let val = cpu.fetch_byte(mem);
+    cpu.reg.cc.and_with(val);
+    0
+}
+
+fn sex<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // SEX
+    let b = cpu.reg.b();
+    let d = alu::sex(b, &mut cpu.reg.cc);
+    cpu.reg.d = d;
+    0
+}
+
+fn exg_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // EXG
+    let post = cpu.fetch_byte(mem);
+    exg(cpu, post);
+    0
+}
+
+fn tfr_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // TFR
+    let post = cpu.fetch_byte(mem);
+    tfr(cpu, post);
+    0
+}
+
+// =================================================================
+// 0x20..0x2F — Short branches
+// =================================================================
+fn bra<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BRA
+    let addr = cpu.addr_relative8(mem);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn brn<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BRN
+    let _addr = cpu.addr_relative8(mem);
+    // never branch
+    0
+}
+
+fn bhi<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BHI: !(C|Z)
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Hi) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bls<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BLS: C|Z
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Ls) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bcc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BHS/BCC: !C
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Cc) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bcs<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BLO/BCS: C
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Cs) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bne<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BNE: !Z
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Ne) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn beq<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BEQ: Z
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Eq) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bvc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BVC: !V
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Vc) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bvs<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BVS: V
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Vs) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bpl<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BPL: !N
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Pl) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bmi<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BMI: N
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Mi) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bge<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BGE: N==V  (N*V + !N*!V)
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Ge) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn blt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BLT: N!=V  (N*!V + !N*V)
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Lt) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn bgt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BGT: !Z && N==V
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Gt) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+fn ble<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BLE: Z || N!=V
+    let addr = cpu.addr_relative8(mem);
+    if cpu.reg.cc.test(Condition::Le) {
+        cpu.reg.pc = addr;
+    }
+    0
+}
+
+// =================================================================
+// 0x30..0x3F — LEA, stack, misc inherent
+// =================================================================
+fn leax<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LEAX indexed
+    let (ea, extra) = cpu.addr_indexed(mem);
+    cpu.reg.x = ea;
+    cpu.reg.cc.set_zero(ea == 0);
+    extra
+}
+
+fn leay<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LEAY indexed
+    let (ea, extra) = cpu.addr_indexed(mem);
+    cpu.reg.y = ea;
+    cpu.reg.cc.set_zero(ea == 0);
+    extra
+}
+
+fn leas<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LEAS indexed
+    let (ea, extra) = cpu.addr_indexed(mem);
+    cpu.reg.s = ea;
+    cpu.arm_nmi();
+    extra
+}
+
+fn leau<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LEAU indexed
+    let (ea, extra) = cpu.addr_indexed(mem);
+    cpu.reg.u = ea;
+    extra
+}
+
+fn pshs_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PSHS
+    let post = cpu.fetch_byte(mem);
+    pshs(cpu, mem, post)
+}
+
+fn puls_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PULS
+    let post = cpu.fetch_byte(mem);
+    puls(cpu, mem, post)
+}
+
+fn pshu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PSHU
+    let post = cpu.fetch_byte(mem);
+    pshu(cpu, mem, post)
+}
+
+fn pulu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // PULU
+    let post = cpu.fetch_byte(mem);
+    pulu(cpu, mem, post)
+}
+
+fn xandcc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XANDCC immediate (undocumented)
+    let val = cpu.fetch_byte(mem);
+    cpu.reg.cc.and_with(val);
+    0
+}
+
+fn rts<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // RTS
+    cpu.reg.pc = cpu.pull_word_s(mem);
+    0
+}
+
+fn abx<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // ABX: X = X + B (unsigned)
+    cpu.reg.x = cpu.reg.x.wrapping_add(cpu.reg.b() as u16);
+    0
+}
+
+fn rti<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // RTI
+    let cc = cpu.pull_byte_s(mem);
+    cpu.reg.cc = crate::registers::ConditionCodes::from_byte(cc);
+    if cpu.reg.cc.entire() {
+        // Full restore: 15 cycles total (6 base + 9 extra), and 17 with
+        // E and F on a 6309 in native mode
+        let a = cpu.pull_byte_s(mem);
+        cpu.reg.set_a(a);
+        let b = cpu.pull_byte_s(mem);
+        cpu.reg.set_b(b);
+        let native = cpu.native_mode();
+        if native {
+            let e = cpu.pull_byte_s(mem);
+            cpu.reg6309.set_e(e);
+            let f = cpu.pull_byte_s(mem);
+            cpu.reg6309.set_f(f);
         }
+        cpu.reg.dp = cpu.pull_byte_s(mem);
+        cpu.reg.x = cpu.pull_word_s(mem);
+        cpu.reg.y = cpu.pull_word_s(mem);
+        cpu.reg.u = cpu.pull_word_s(mem);
+        cpu.reg.pc = cpu.pull_word_s(mem);
+        return if native { 11 } else { 9 };
+    }
+    cpu.reg.pc = cpu.pull_word_s(mem);
+    0
+}
+
+fn cwai<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CWAI
+    let post = cpu.fetch_byte(mem);
+    cpu.reg.cc.and_with(post);
+    cpu.reg.cc.set_entire(true);
+    cpu.push_entire_state(mem);
+    cpu.cwai = true;
+    0
+}
+
+fn mul<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // MUL
+    let a = cpu.reg.a();
+    let b = cpu.reg.b();
+    let d = alu::mul(a, b, &mut cpu.reg.cc);
+    cpu.reg.d = d;
+    0
+}
+
+fn xres<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // RESET (undocumented)
+    // This instruction is similar to SWI (0x3F), except the
+    // RESET vector (0xFFFE/F) is used to determine the next
+    // PC value, and it does not correctly set the E flag in
+    // the saved machine state.
+    // Flags: all flags are unchanged
+    // Note: unlike a hardware RESET, the F and I flags are not set.
+    cpu.push_entire_state(mem);
+    cpu.reg.pc = mem.read_word(cpu.vectors.reset);
+    0
+}
+
+fn swi<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // SWI
+    if cpu.swi_serviced(Swi::Swi, mem) {
+        return 0;
+    }
+    cpu.reg.cc.set_entire(true);
+    cpu.push_entire_state(mem);
+    cpu.reg.cc.set_irq_inhibit(true);
+    cpu.reg.cc.set_firq_inhibit(true);
+    cpu.reg.pc = mem.read_word(cpu.vectors.swi);
+    0
+}
+
+// =================================================================
+// 0x40..0x4F — Inherent A
+// =================================================================
+fn nega<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // NEGA (0x40) and (0x41, undoc)
+    let v = cpu.reg.a();
+    let r = neg8(cpu, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn xnca<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XNC A (undocumented)
+    // This instruction behaves like NEGA if C=0 or COMA if C=1
+    let v = cpu.reg.a();
+    let r = if cpu.reg.cc.carry() {
+        alu::com8(v, &mut cpu.reg.cc)
+    } else {
+        neg8(cpu, v)
+    };
+    cpu.reg.set_a(r);
+    0
+}
+
+fn coma<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::com8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn lsra<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // LSRA (0x44) and (0x45, undoc)
+    let v = cpu.reg.a();
+    let r = alu::lsr8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn rora<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::ror8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn asra<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::asr8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn asla<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::asl8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn rola<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::rol8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn deca<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::dec8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn xdeca<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XDEC A (undocumented)
+    // This instruction is similar to DEC instruction, except that
+    // the carry flag is modified.
+    // Flags:
+    // Z - set if the result is zero, otherwise cleared (the same as DEC)
+    // N - set if the result is negative, otherwise cleared (the same as DEC)
+    // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
+    // C - cleared if the operand is zero, otherwise set (different to DEC)
+    // all other flags unchanged
+    let v = cpu.reg.a();
+    let r = alu::dec8(v, &mut cpu.reg.cc);
+    cpu.reg.cc.set_carry(v != 0);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn inca<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    let r = alu::inc8(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn tsta<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.a();
+    alu::tst8(v, &mut cpu.reg.cc);
+    0
+}
+
+fn xclra<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XCLRA (undocumented)
+    // This instruction is similar to CLRA/B, except the C flag is unchanged.
+    cpu.reg.cc.set_negative(false);
+    cpu.reg.cc.set_zero(true);
+    cpu.reg.cc.set_overflow(false);
+    cpu.reg.set_a(0);
+    0
+}
+
+fn clra<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRA
+    let r = alu::clr8(&mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+// =================================================================
+// 0x50..0x5F — Inherent B
+// =================================================================
+fn negb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // NEGB (0x50) and (0x51, undoc)
+    let v = cpu.reg.b();
+    let r = neg8(cpu, v);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn xncb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XNC B (undocumented)
+    // This instruction behaves like NEGB if C=0 or COMB if C=1
+    let v = cpu.reg.b();
+    let r = if cpu.reg.cc.carry() {
+        alu::com8(v, &mut cpu.reg.cc)
+    } else {
+        neg8(cpu, v)
+    };
+    cpu.reg.set_b(r);
+    0
+}
+
+fn comb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::com8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn lsrb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // LSRB (0x54) and (0x55, undoc)
+    let v = cpu.reg.b();
+    let r = alu::lsr8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn rorb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::ror8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn asrb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::asr8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn aslb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::asl8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn rolb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::rol8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn decb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::dec8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn xdecb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XDEC B (undocumented)
+    // This instruction is similar to DEC instruction, except that
+    // the carry flag is modified.
+    // Flags:
+    // Z - set if the result is zero, otherwise cleared (the same as DEC)
+    // N - set if the result is negative, otherwise cleared (the same as DEC)
+    // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
+    // C - cleared if the operand is zero, otherwise set (different to DEC)
+    // all other flags unchanged
+    let v = cpu.reg.b();
+    let r = alu::dec8(v, &mut cpu.reg.cc);
+    cpu.reg.cc.set_carry(v != 0);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn incb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    let r = alu::inc8(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn tstb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    let v = cpu.reg.b();
+    alu::tst8(v, &mut cpu.reg.cc);
+    0
+}
+
+fn xclrb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // XCLRB (undocumented)
+    // This instruction is similar to CLRA/B, except the C flag is unchanged.
+    cpu.reg.cc.set_negative(false);
+    cpu.reg.cc.set_zero(true);
+    cpu.reg.cc.set_overflow(false);
+    cpu.reg.set_b(0);
+    0
+}
+
+fn clrb<M: Memory>(cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    // CLRB
+    let r = alu::clr8(&mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+// =================================================================
+// 0x60..0x6F — Indexed read-modify-write
+// =================================================================
+fn neg_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // NEG indexed (0x60) and (0x61, undoc)
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = neg8(cpu, val);
+    mem.write(addr, r);
+    ex
+}
+
+fn xnc_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XNC indexed (undocumented)
+    // This instruction behaves like NEG if C=0 or COM if C=1
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = if cpu.reg.cc.carry() {
+        alu::com8(val, &mut cpu.reg.cc)
+    } else {
+        neg8(cpu, val)
+    };
+    mem.write(addr, r);
+    ex
+}
+
+fn com_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::com8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn lsr_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LSR indexed (0x64) and (0x65, undoc)
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::lsr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn ror_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::ror8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn asr_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::asr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn asl_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::asl8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn rol_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::rol8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn dec_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn xdec_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XDEC indexed (undocumented)
+    // This instruction is similar to DEC instruction, except that
+    // the carry flag is modified.
+    // Flags:
+    // Z - set if the result is zero, otherwise cleared (the same as DEC)
+    // N - set if the result is negative, otherwise cleared (the same as DEC)
+    // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
+    // C - cleared if the operand is zero, otherwise set (different to DEC)
+    // all other flags unchanged
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    cpu.reg.cc.set_carry(val != 0);
+    mem.write(addr, r);
+    ex
+}
+
+fn inc_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    let r = alu::inc8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+fn tst_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let val = mem.read(addr);
+    alu::tst8(val, &mut cpu.reg.cc);
+    ex
+}
+
+fn jmp_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JMP indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    cpu.reg.pc = addr;
+    ex
+}
+
+fn clr_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CLR indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let r = alu::clr8(&mut cpu.reg.cc);
+    mem.write(addr, r);
+    ex
+}
+
+// =================================================================
+// 0x70..0x7F — Extended read-modify-write
+// =================================================================
+fn neg_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // NEG extended (0x70) and (0x71, undoc)
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = neg8(cpu, val);
+    mem.write(addr, r);
+    0
+}
+
+fn xnc_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XNC extended (undocumented)
+    // This instruction behaves like NEG if C=0 or COM if C=1
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = if cpu.reg.cc.carry() {
+        alu::com8(val, &mut cpu.reg.cc)
+    } else {
+        neg8(cpu, val)
+    };
+    mem.write(addr, r);
+    0
+}
+
+fn com_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::com8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn lsr_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LSR extended (0x74) and (0x75, undoc)
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::lsr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn ror_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::ror8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn asr_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::asr8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn asl_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::asl8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn rol_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::rol8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn dec_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn xdec_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XDEC extended (undocumented)
+    // This instruction is similar to DEC instruction, except that
+    // the carry flag is modified.
+    // Flags:
+    // Z - set if the result is zero, otherwise cleared (the same as DEC)
+    // N - set if the result is negative, otherwise cleared (the same as DEC)
+    // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
+    // C - cleared if the operand is zero, otherwise set (different to DEC)
+    // all other flags unchanged
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::dec8(val, &mut cpu.reg.cc);
+    cpu.reg.cc.set_carry(val != 0);
+    mem.write(addr, r);
+    0
+}
+
+fn inc_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    let r = alu::inc8(val, &mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+fn tst_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let val = mem.read(addr);
+    alu::tst8(val, &mut cpu.reg.cc);
+    0
+}
+
+fn jmp_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JMP extended
+    cpu.reg.pc = cpu.addr_extended(mem);
+    0
+}
+
+fn clr_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CLR
+    let addr = cpu.addr_extended(mem);
+    let r = alu::clr8(&mut cpu.reg.cc);
+    mem.write(addr, r);
+    0
+}
+
+// =================================================================
+// 0x80..0x8F — Immediate A / D / X
+// =================================================================
+fn suba_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = sub8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpa_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CMPA immediate
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    sub8(cpu, a, v);
+    0
+}
+
+fn sbca_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = sbc8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn subd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // SUBD immediate
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::sub16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn anda_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = alu::and8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn bita_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BITA immediate
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    alu::and8(a, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lda_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDA immediate
+    let v = cpu.fetch_byte(mem);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(v);
+    0
+}
+
+fn xsta_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTA immediate (undocumented): A is written over its own operand byte
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 1);
+    }
+    let v = cpu.reg.a();
+    store_imm8(cpu, mem, v)
+}
+
+fn eora_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = alu::eor8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adca_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = alu::adc8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn ora_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = alu::or8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adda_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let a = cpu.reg.a();
+    let r = alu::add8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpx_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // CMPX immediate
+    let v = cpu.fetch_word(mem);
+    let x = cpu.reg.x;
+    alu::sub16(x, v, &mut cpu.reg.cc);
+    0
+}
+
+fn bsr<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // BSR immediate
+    let addr = cpu.addr_relative8(mem);
+    cpu.push_word_s(mem, cpu.reg.pc);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn ldx_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDX immediate
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.x = v;
+    0
+}
+
+fn xstx_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTX immediate (undocumented): X is written over its own operand bytes
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 1);
+    }
+    let v = cpu.reg.x;
+    store_imm16(cpu, mem, v)
+}
+
+/// Store immediate: write `v` to the operand byte after the opcode, set the
+/// flags as ST does and step PC over it. Returns the cycles beyond the
+/// illegal-opcode base of 1.
+pub(super) fn store_imm8(cpu: &mut Cpu, mem: &mut impl Memory, v: u8) -> u8 {
+    let addr = cpu.reg.pc;
+    cpu.reg.pc = addr.wrapping_add(1);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    1
+}
+
+/// 16-bit [`store_imm8`]: `v` goes to the two operand bytes.
+pub(super) fn store_imm16(cpu: &mut Cpu, mem: &mut impl Memory, v: u16) -> u8 {
+    let addr = cpu.reg.pc;
+    cpu.reg.pc = addr.wrapping_add(2);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    2
+}
+
+// =================================================================
+// 0x90..0x9F — Direct A / D / X
+// =================================================================
+fn suba_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sub8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpa_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    sub8(cpu, a, v);
+    0
+}
+
+fn sbca_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sbc8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn subd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sub16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn anda_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::and8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn bita_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    alu::and8(a, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lda_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(v);
+    0
+}
+
+fn sta_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // STA direct
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.a();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn eora_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::eor8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adca_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::adc8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn ora_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::or8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adda_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::add8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpx_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let x = cpu.reg.x;
+    alu::sub16(x, v, &mut cpu.reg.cc);
+    0
+}
+
+fn jsr_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JSR direct
+    let addr = cpu.addr_direct(mem);
+    cpu.push_word_s(mem, cpu.reg.pc);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn ldx_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.x = v;
+    0
+}
+
+fn stx_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.x;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// =================================================================
+// 0xA0..0xAF — Indexed A / D / X
+// =================================================================
+fn suba_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sub8(cpu, a, v);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn cmpa_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    sub8(cpu, a, v);
+    ex
+}
+
+fn sbca_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sbc8(cpu, a, v);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn subd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sub16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn anda_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::and8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn bita_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    alu::and8(a, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn lda_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(v);
+    ex
+}
+
+fn sta_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.a();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    ex
+}
+
+fn eora_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // EORA indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::eor8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn adca_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::adc8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn ora_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::or8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn adda_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::add8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    ex
+}
+
+fn cmpx_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let x = cpu.reg.x;
+    alu::sub16(x, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn jsr_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JSR indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    cpu.push_word_s(mem, cpu.reg.pc);
+    cpu.reg.pc = addr;
+    ex
+}
+
+fn ldx_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.x = v;
+    ex
+}
+
+fn stx_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.x;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
+
+// =================================================================
+// 0xB0..0xBF — Extended A / D / X
+// =================================================================
+fn suba_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sub8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpa_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    sub8(cpu, a, v);
+    0
+}
+
+fn sbca_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = sbc8(cpu, a, v);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn subd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::sub16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn anda_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::and8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn bita_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    alu::and8(a, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lda_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_a(v);
+    0
+}
+
+fn sta_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.a();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn eora_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // EORA extended
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::eor8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adca_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::adc8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn ora_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::or8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn adda_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let a = cpu.reg.a();
+    let r = alu::add8(a, v, &mut cpu.reg.cc);
+    cpu.reg.set_a(r);
+    0
+}
+
+fn cmpx_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let x = cpu.reg.x;
+    alu::sub16(x, v, &mut cpu.reg.cc);
+    0
+}
+
+fn jsr_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // JSR extended
+    let addr = cpu.addr_extended(mem);
+    cpu.push_word_s(mem, cpu.reg.pc);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn ldx_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.x = v;
+    0
+}
+
+fn stx_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.x;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// =================================================================
+// 0xC0..0xCF — Immediate B / D / U
+// =================================================================
+fn subb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = sub8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn cmpb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    sub8(cpu, b, v);
+    0
+}
+
+fn sbcb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = sbc8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn addd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // ADDD immediate
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let r = alu::add16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn andb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = alu::and8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn bitb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    alu::and8(b, v, &mut cpu.reg.cc);
+    0
+}
+
+fn ldb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(v);
+    0
+}
+
+fn xstb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTB immediate (undocumented)
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 1);
+    }
+    let v = cpu.reg.b();
+    store_imm8(cpu, mem, v)
+}
+
+fn eorb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = alu::eor8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn adcb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = alu::adc8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn orb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = alu::or8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn addb_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_byte(mem);
+    let b = cpu.reg.b();
+    let r = alu::add8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn ldd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDD immediate
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.d = v;
+    0
+}
+
+fn ldu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDU
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.u = v;
+    0
+}
+
+fn xstu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTU immediate (undocumented)
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 1);
+    }
+    let v = cpu.reg.u;
+    store_imm16(cpu, mem, v)
+}
+
+// =================================================================
+// 0xD0..0xDF — Direct B / D / U
+// =================================================================
+fn subb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sub8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn cmpb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    sub8(cpu, b, v);
+    0
+}
+
+fn sbcb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sbc8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn addd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::add16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
+
+fn andb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::and8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn bitb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    alu::and8(b, v, &mut cpu.reg.cc);
+    0
+}
+
+fn ldb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(v);
+    0
+}
+
+fn stb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.b();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
+
+fn eorb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::eor8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn adcb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::adc8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn orb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::or8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn addb_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::add8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn ldd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDD direct
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.d = v;
+    0
+}
+
+fn std_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.d;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+fn ldu_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.u = v;
+    0
+}
+
+fn stu_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.u;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// =================================================================
+// 0xE0..0xEF — Indexed B / D / U
+// =================================================================
+fn subb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sub8(cpu, b, v);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn cmpb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    sub8(cpu, b, v);
+    ex
+}
+
+fn sbcb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sbc8(cpu, b, v);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn addd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::add16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    ex
+}
+
+fn andb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::and8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn bitb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    alu::and8(b, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn ldb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(v);
+    ex
+}
+
+fn stb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.b();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    ex
+}
+
+fn eorb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::eor8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn adcb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::adc8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn orb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::or8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn addb_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::add8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    ex
+}
+
+fn ldd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LDD indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.d = v;
+    ex
+}
+
+fn std_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.d;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
+
+fn ldu_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.u = v;
+    ex
+}
 
-        // =================================================================
-        // 0x30..0x3F — LEA, stack, misc inherent
-        // =================================================================
-        0x30 => {
-            // LEAX indexed
-            let (ea, extra) = cpu.addr_indexed(mem);
-            cpu.reg.x = ea;
-            cpu.reg.cc.set_zero(ea == 0);
-            cpu.cycles += extra as u64;
-        }
-        0x31 => {
-            // LEAY indexed
-            let (ea, extra) = cpu.addr_indexed(mem);
-            cpu.reg.y = ea;
-            cpu.reg.cc.set_zero(ea == 0);
-            cpu.cycles += extra as u64;
-        }
-        0x32 => {
-            // LEAS indexed
-            let (ea, extra) = cpu.addr_indexed(mem);
-            cpu.reg.s = ea;
-            cpu.arm_nmi();
-            cpu.cycles += extra as u64;
-        }
-        0x33 => {
-            // LEAU indexed
-            let (ea, extra) = cpu.addr_indexed(mem);
-            cpu.reg.u = ea;
-            cpu.cycles += extra as u64;
-        }
-        0x34 => {
-            // PSHS
-            let post = cpu.fetch_byte(mem);
-            pshs(cpu, mem, post);
-        }
-        0x35 => {
-            // PULS
-            let post = cpu.fetch_byte(mem);
-            puls(cpu, mem, post);
-        }
-        0x36 => {
-            // PSHU
-            let post = cpu.fetch_byte(mem);
-            pshu(cpu, mem, post);
-        }
-        0x37 => {
-            // PULU
-            let post = cpu.fetch_byte(mem);
-            pulu(cpu, mem, post);
-        }
-        0x38 => {
-            // XANDCC immediate (undocumented)
-            let val = cpu.fetch_byte(mem);
-            cpu.reg.cc.and_with(val);
-        }
-        0x39 => {
-            // RTS
-            cpu.reg.pc = cpu.pull_word_s(mem);
-        }
-        0x3A => {
-            // ABX: X = X + B (unsigned)
-            cpu.reg.x = cpu.reg.x.wrapping_add(cpu.reg.b() as u16);
-        }
-        0x3B => {
-            // RTI
-            let cc = cpu.pull_byte_s(mem);
-            cpu.reg.cc = crate::registers::ConditionCodes::from_byte(cc);
-            if cpu.reg.cc.entire() {
-                // Full restore: 15 cycles total (6 base + 9 extra)
-                let a = cpu.pull_byte_s(mem);
-                cpu.reg.set_a(a);
-                let b = cpu.pull_byte_s(mem);
-                cpu.reg.set_b(b);
-                cpu.reg.dp = cpu.pull_byte_s(mem);
-                cpu.reg.x = cpu.pull_word_s(mem);
-                cpu.reg.y = cpu.pull_word_s(mem);
-                cpu.reg.u = cpu.pull_word_s(mem);
-                cpu.cycles += 9;
-            }
-            cpu.reg.pc = cpu.pull_word_s(mem);
-        }
-        0x3C => {
-            // CWAI
-            let post = cpu.fetch_byte(mem);
-            cpu.reg.cc.and_with(post);
-            cpu.reg.cc.set_entire(true);
-            cpu.push_entire_state(mem);
-            cpu.cwai = true;
-        }
-        0x3D => {
-            // MUL
-            let a = cpu.reg.a();
-            let b = cpu.reg.b();
-            let d = alu::mul(a, b, &mut cpu.reg.cc);
-            cpu.reg.d = d;
-        }
-        0x3E => {
-            // RESET (undocumented)
-            // This instruction is similar to SWI (0x3F), except the
-            // RESET vector (0xFFFE/F) is used to determine the next
-            // PC value, and it does not correctly set the E flag in
-            // the saved machine state.
-            // Flags: all flags are unchanged
-            // Note: unlike a hardware RESET, the F and I flags are not set.
-            cpu.push_entire_state(mem);
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_RESET);
-        }
-        0x3F => {
-            // SWI
-            cpu.reg.cc.set_entire(true);
-            cpu.push_entire_state(mem);
-            cpu.reg.cc.set_irq_inhibit(true);
-            cpu.reg.cc.set_firq_inhibit(true);
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_SWI);
-        }
+fn stu_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.u;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
 
-        // =================================================================
-        // 0x40..0x4F — Inherent A
-        // =================================================================
-        0x40 | 0x41 => {
-            // NEGA (0x40) and (0x41, undoc)
-            let v = cpu.reg.a();
-            let r = alu::neg8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x42 => {
-            // XNC A (undocumented)
-            // This instruction behaves like NEGA if C=0 or COMA if C=1
-            let v = cpu.reg.a();
-            let r = if cpu.reg.cc.carry() {
-                alu::com8(v, &mut cpu.reg.cc)
-            } else {
-                alu::neg8(v, &mut cpu.reg.cc)
-            };
-            cpu.reg.set_a(r);
-        }
-        0x43 => {
-            let v = cpu.reg.a();
-            let r = alu::com8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x44 | 0x45 => {
-            // LSRA (0x44) and (0x45, undoc)
-            let v = cpu.reg.a();
-            let r = alu::lsr8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x46 => {
-            let v = cpu.reg.a();
-            let r = alu::ror8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x47 => {
-            let v = cpu.reg.a();
-            let r = alu::asr8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x48 => {
-            let v = cpu.reg.a();
-            let r = alu::asl8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x49 => {
-            let v = cpu.reg.a();
-            let r = alu::rol8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x4A => {
-            let v = cpu.reg.a();
-            let r = alu::dec8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x4B => {
-            // XDEC A (undocumented)
-            // This instruction is similar to DEC instruction, except that
-            // the carry flag is modified.
-            // Flags:
-            // Z - set if the result is zero, otherwise cleared (the same as DEC)
-            // N - set if the result is negative, otherwise cleared (the same as DEC)
-            // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
-            // C - cleared if the operand is zero, otherwise set (different to DEC)
-            // all other flags unchanged
-            let v = cpu.reg.a();
-            let r = alu::dec8(v, &mut cpu.reg.cc);
-            cpu.reg.cc.set_carry(v != 0);
-            cpu.reg.set_a(r);
-        }
-        0x4C => {
-            let v = cpu.reg.a();
-            let r = alu::inc8(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x4D => {
-            let v = cpu.reg.a();
-            alu::tst8(v, &mut cpu.reg.cc);
-        }
-        0x4E => {
-            // XCLRA (undocumented)
-            // This instruction is similar to CLRA/B, except the C flag is unchanged.
-            cpu.reg.cc.set_negative(false);
-            cpu.reg.cc.set_zero(true);
-            cpu.reg.cc.set_overflow(false);
-            cpu.reg.set_a(0);
-        }
-        0x4F => {
-            // CLRA
-            let r = alu::clr8(&mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
+// =================================================================
+// 0xF0..0xFF — Extended B / D / U
+// =================================================================
+fn subb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sub8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
 
-        // =================================================================
-        // 0x50..0x5F — Inherent B
-        // =================================================================
-        0x50 | 0x51 => {
-            // NEGB (0x50) and (0x51, undoc)
-            let v = cpu.reg.b();
-            let r = alu::neg8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x52 => {
-            // XNC B (undocumented)
-            // This instruction behaves like NEGB if C=0 or COMB if C=1
-            let v = cpu.reg.b();
-            let r = if cpu.reg.cc.carry() {
-                alu::com8(v, &mut cpu.reg.cc)
-            } else {
-                alu::neg8(v, &mut cpu.reg.cc)
-            };
-            cpu.reg.set_b(r);
-        }
-        0x53 => {
-            let v = cpu.reg.b();
-            let r = alu::com8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x54 | 0x55 => {
-            // LSRB (0x54) and (0x55, undoc)
-            let v = cpu.reg.b();
-            let r = alu::lsr8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x56 => {
-            let v = cpu.reg.b();
-            let r = alu::ror8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x57 => {
-            let v = cpu.reg.b();
-            let r = alu::asr8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x58 => {
-            let v = cpu.reg.b();
-            let r = alu::asl8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x59 => {
-            let v = cpu.reg.b();
-            let r = alu::rol8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x5A => {
-            let v = cpu.reg.b();
-            let r = alu::dec8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x5B => {
-            // XDEC B (undocumented)
-            // This instruction is similar to DEC instruction, except that
-            // the carry flag is modified.
-            // Flags:
-            // Z - set if the result is zero, otherwise cleared (the same as DEC)
-            // N - set if the result is negative, otherwise cleared (the same as DEC)
-            // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
-            // C - cleared if the operand is zero, otherwise set (different to DEC)
-            // all other flags unchanged
-            let v = cpu.reg.b();
-            let r = alu::dec8(v, &mut cpu.reg.cc);
-            cpu.reg.cc.set_carry(v != 0);
-            cpu.reg.set_b(r);
-        }
-        0x5C => {
-            let v = cpu.reg.b();
-            let r = alu::inc8(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0x5D => {
-            let v = cpu.reg.b();
-            alu::tst8(v, &mut cpu.reg.cc);
-        }
-        0x5E => {
-            // XCLRB (undocumented)
-            // This instruction is similar to CLRA/B, except the C flag is unchanged.
-            cpu.reg.cc.set_negative(false);
-            cpu.reg.cc.set_zero(true);
-            cpu.reg.cc.set_overflow(false);
-            cpu.reg.set_b(0);
-        }
-        0x5F => {
-            // CLRB
-            let r = alu::clr8(&mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
+fn cmpb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    sub8(cpu, b, v);
+    0
+}
 
-        // =================================================================
-        // 0x60..0x6F — Indexed read-modify-write
-        // =================================================================
-        0x60 | 0x61 => {
-            // NEG indexed (0x60) and (0x61, undoc)
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::neg8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x62 => {
-            // XNC indexed (undocumented)
-            // This instruction behaves like NEG if C=0 or COM if C=1
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = if cpu.reg.cc.carry() {
-                alu::com8(val, &mut cpu.reg.cc)
-            } else {
-                alu::neg8(val, &mut cpu.reg.cc)
-            };
-            mem.write(addr, r);
-        }
-        0x63 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::com8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x64 | 0x65 => {
-            // LSR indexed (0x64) and (0x65, undoc)
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::lsr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x66 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::ror8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x67 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::asr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x68 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::asl8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x69 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::rol8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x6A => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x6B => {
-            // XDEC indexed (undocumented)
-            // This instruction is similar to DEC instruction, except that
-            // the carry flag is modified.
-            // Flags:
-            // Z - set if the result is zero, otherwise cleared (the same as DEC)
-            // N - set if the result is negative, otherwise cleared (the same as DEC)
-            // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
-            // C - cleared if the operand is zero, otherwise set (different to DEC)
-            // all other flags unchanged
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            cpu.reg.cc.set_carry(val != 0);
-            mem.write(addr, r);
-        }
-        0x6C => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            let r = alu::inc8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x6D => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let val = mem.read(addr);
-            alu::tst8(val, &mut cpu.reg.cc);
-        }
-        0x6E => {
-            // JMP indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            cpu.reg.pc = addr;
-        }
-        0x6F => {
-            // CLR indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let r = alu::clr8(&mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
+fn sbcb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = sbc8(cpu, b, v);
+    cpu.reg.set_b(r);
+    0
+}
 
-        // =================================================================
-        // 0x70..0x7F — Extended read-modify-write
-        // =================================================================
-        0x70 | 0x71 => {
-            // NEG extended (0x70) and (0x71, undoc)
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::neg8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x72 => {
-            // XNC extended (undocumented)
-            // This instruction behaves like NEG if C=0 or COM if C=1
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = if cpu.reg.cc.carry() {
-                alu::com8(val, &mut cpu.reg.cc)
-            } else {
-                alu::neg8(val, &mut cpu.reg.cc)
-            };
-            mem.write(addr, r);
-        }
-        0x73 => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::com8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x74 | 0x75 => {
-            // LSR extended (0x74) and (0x75, undoc)
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::lsr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x76 => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::ror8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x77 => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::asr8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x78 => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::asl8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x79 => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::rol8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x7A => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x7B => {
-            // XDEC extended (undocumented)
-            // This instruction is similar to DEC instruction, except that
-            // the carry flag is modified.
-            // Flags:
-            // Z - set if the result is zero, otherwise cleared (the same as DEC)
-            // N - set if the result is negative, otherwise cleared (the same as DEC)
-            // V - set if the operand is 0x80, otherwise cleared (the same as DEC)
-            // C - cleared if the operand is zero, otherwise set (different to DEC)
-            // all other flags unchanged
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::dec8(val, &mut cpu.reg.cc);
-            cpu.reg.cc.set_carry(val != 0);
-            mem.write(addr, r);
-        }
-        0x7C => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            let r = alu::inc8(val, &mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
-        0x7D => {
-            let addr = cpu.addr_extended(mem);
-            let val = mem.read(addr);
-            alu::tst8(val, &mut cpu.reg.cc);
-        }
-        0x7E => {
-            // JMP extended
-            cpu.reg.pc = cpu.addr_extended(mem);
-        }
-        0x7F => {
-            // CLR
-            let addr = cpu.addr_extended(mem);
-            let r = alu::clr8(&mut cpu.reg.cc);
-            mem.write(addr, r);
-        }
+fn addd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let r = alu::add16(d, v, &mut cpu.reg.cc);
+    cpu.reg.d = r;
+    0
+}
 
-        // =================================================================
-        // 0x80..0x8F — Immediate A / D / X
-        // =================================================================
-        0x80 => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::sub8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x81 => {
-            // CMPA immediate
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            alu::sub8(a, v, &mut cpu.reg.cc);
-        }
-        0x82 => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::sbc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x83 => {
-            // SUBD immediate
-            let v = cpu.fetch_word(mem);
-            let d = cpu.reg.d;
-            let r = alu::sub16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0x84 => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::and8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x85 => {
-            // BITA immediate
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            alu::and8(a, v, &mut cpu.reg.cc);
-        }
-        0x86 => {
-            // LDA immediate
-            let v = cpu.fetch_byte(mem);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(v);
-        }
-        // 0x87 illegal
-        0x88 => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::eor8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x89 => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::adc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x8A => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::or8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x8B => {
-            let v = cpu.fetch_byte(mem);
-            let a = cpu.reg.a();
-            let r = alu::add8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x8C => {
-            // CMPX immediate
-            let v = cpu.fetch_word(mem);
-            let x = cpu.reg.x;
-            alu::sub16(x, v, &mut cpu.reg.cc);
-        }
-        0x8D => {
-            // BSR immediate
-            let addr = cpu.addr_relative8(mem);
-            cpu.push_word_s(mem, cpu.reg.pc);
-            cpu.reg.pc = addr;
-        }
-        0x8E => {
-            // LDX immediate
-            let v = cpu.fetch_word(mem);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.x = v;
-        }
-        // 0x8F illegal
-
-        // =================================================================
-        // 0x90..0x9F — Direct A / D / X
-        // =================================================================
-        0x90 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sub8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x91 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::sub8(a, v, &mut cpu.reg.cc);
-        }
-        0x92 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sbc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x93 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::sub16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0x94 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::and8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x95 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::and8(a, v, &mut cpu.reg.cc);
-        }
-        0x96 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(v);
-        }
-        0x97 => {
-            // STA direct
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.a();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0x98 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::eor8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x99 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::adc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x9A => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::or8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x9B => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::add8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0x9C => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let x = cpu.reg.x;
-            alu::sub16(x, v, &mut cpu.reg.cc);
-        }
-        0x9D => {
-            // JSR direct
-            let addr = cpu.addr_direct(mem);
-            cpu.push_word_s(mem, cpu.reg.pc);
-            cpu.reg.pc = addr;
-        }
-        0x9E => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.x = v;
-        }
-        0x9F => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.x;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn andb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::and8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
 
-        // =================================================================
-        // 0xA0..0xAF — Indexed A / D / X
-        // =================================================================
-        0xA0 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sub8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xA1 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::sub8(a, v, &mut cpu.reg.cc);
-        }
-        0xA2 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sbc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xA3 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::sub16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xA4 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::and8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xA5 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::and8(a, v, &mut cpu.reg.cc);
-        }
-        0xA6 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(v);
-        }
-        0xA7 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.a();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0xA8 => {
-            // EORA indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::eor8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xA9 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::adc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xAA => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::or8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xAB => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::add8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xAC => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let x = cpu.reg.x;
-            alu::sub16(x, v, &mut cpu.reg.cc);
-        }
-        0xAD => {
-            // JSR indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            cpu.push_word_s(mem, cpu.reg.pc);
-            cpu.reg.pc = addr;
-        }
-        0xAE => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.x = v;
-        }
-        0xAF => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.x;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn bitb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    alu::and8(b, v, &mut cpu.reg.cc);
+    0
+}
 
-        // =================================================================
-        // 0xB0..0xBF — Extended A / D / X
-        // =================================================================
-        0xB0 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sub8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xB1 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::sub8(a, v, &mut cpu.reg.cc);
-        }
-        0xB2 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::sbc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xB3 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::sub16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xB4 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::and8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xB5 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            alu::and8(a, v, &mut cpu.reg.cc);
-        }
-        0xB6 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_a(v);
-        }
-        0xB7 => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.a();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0xB8 => {
-            // EORA extended
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::eor8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xB9 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::adc8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xBA => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::or8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xBB => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let a = cpu.reg.a();
-            let r = alu::add8(a, v, &mut cpu.reg.cc);
-            cpu.reg.set_a(r);
-        }
-        0xBC => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let x = cpu.reg.x;
-            alu::sub16(x, v, &mut cpu.reg.cc);
-        }
-        0xBD => {
-            // JSR extended
-            let addr = cpu.addr_extended(mem);
-            cpu.push_word_s(mem, cpu.reg.pc);
-            cpu.reg.pc = addr;
-        }
-        0xBE => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.x = v;
-        }
-        0xBF => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.x;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn ldb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    cpu.reg.set_b(v);
+    0
+}
 
-        // =================================================================
-        // 0xC0..0xCF — Immediate B / D / U
-        // =================================================================
-        0xC0 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::sub8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xC1 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            alu::sub8(b, v, &mut cpu.reg.cc);
-        }
-        0xC2 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::sbc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xC3 => {
-            // ADDD immediate
-            let v = cpu.fetch_word(mem);
-            let d = cpu.reg.d;
-            let r = alu::add16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xC4 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::and8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xC5 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            alu::and8(b, v, &mut cpu.reg.cc);
-        }
-        0xC6 => {
-            let v = cpu.fetch_byte(mem);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(v);
-        }
-        // 0xC7 illegal
-        0xC8 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::eor8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xC9 => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::adc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xCA => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::or8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xCB => {
-            let v = cpu.fetch_byte(mem);
-            let b = cpu.reg.b();
-            let r = alu::add8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xCC => {
-            // LDD immediate
-            let v = cpu.fetch_word(mem);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.d = v;
-        }
-        0xCD => {
-            // XHCF Halt and Catch Fire (undocumented)
-            cpu.halted = true;
-        }
-        0xCE => {
-            let v = cpu.fetch_word(mem);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.u = v;
-        } // LDU
-        // 0xCF illegal
-
-        // =================================================================
-        // 0xD0..0xDF — Direct B / D / U
-        // =================================================================
-        0xD0 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sub8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xD1 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::sub8(b, v, &mut cpu.reg.cc);
-        }
-        0xD2 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sbc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xD3 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::add16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xD4 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::and8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xD5 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::and8(b, v, &mut cpu.reg.cc);
-        }
-        0xD6 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(v);
-        }
-        0xD7 => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.b();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0xD8 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::eor8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xD9 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::adc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xDA => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::or8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xDB => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::add8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xDC => {
-            // LDD direct
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.d = v;
-        }
-        0xDD => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.d;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xDE => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.u = v;
-        }
-        0xDF => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.u;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn stb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.b();
+    alu::ld8_flags(v, &mut cpu.reg.cc);
+    mem.write(addr, v);
+    0
+}
 
-        // =================================================================
-        // 0xE0..0xEF — Indexed B / D / U
-        // =================================================================
-        0xE0 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sub8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xE1 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::sub8(b, v, &mut cpu.reg.cc);
-        }
-        0xE2 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sbc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xE3 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::add16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xE4 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::and8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xE5 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::and8(b, v, &mut cpu.reg.cc);
-        }
-        0xE6 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(v);
-        }
-        0xE7 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.b();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0xE8 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::eor8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xE9 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::adc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xEA => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::or8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xEB => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::add8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xEC => {
-            // LDD indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.d = v;
-        }
-        0xED => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.d;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xEE => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.u = v;
-        }
-        0xEF => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.u;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn eorb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::eor8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
 
-        // =================================================================
-        // 0xF0..0xFF — Extended B / D / U
-        // =================================================================
-        0xF0 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sub8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xF1 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::sub8(b, v, &mut cpu.reg.cc);
-        }
-        0xF2 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::sbc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xF3 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let r = alu::add16(d, v, &mut cpu.reg.cc);
-            cpu.reg.d = r;
-        }
-        0xF4 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::and8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xF5 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            alu::and8(b, v, &mut cpu.reg.cc);
-        }
-        0xF6 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            cpu.reg.set_b(v);
-        }
-        0xF7 => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.b();
-            alu::ld8_flags(v, &mut cpu.reg.cc);
-            mem.write(addr, v);
-        }
-        0xF8 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::eor8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xF9 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::adc8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xFA => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::or8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xFB => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read(addr);
-            let b = cpu.reg.b();
-            let r = alu::add8(b, v, &mut cpu.reg.cc);
-            cpu.reg.set_b(r);
-        }
-        0xFC => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.d = v;
-        }
-        0xFD => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.d;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xFE => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.u = v;
-        }
-        0xFF => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.u;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
+fn adcb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::adc8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
 
-        // Illegal / undefined opcodes — treat as NOP (1 cycle already added)
-        _ => {
-            //debug!("Illegal opcode: {:02X}", opcode);
-            cpu.illegal = true;
-        }
-    }
+fn orb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::or8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn addb_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read(addr);
+    let b = cpu.reg.b();
+    let r = alu::add8(b, v, &mut cpu.reg.cc);
+    cpu.reg.set_b(r);
+    0
+}
+
+fn ldd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.d = v;
+    0
+}
+
+fn std_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.d;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+fn ldu_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.u = v;
+    0
+}
+
+fn stu_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.u;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// Illegal / undefined opcodes — handled by the CPU's illegal opcode policy
+fn illegal<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    cpu.illegal_opcode(mem, 1)
 }
 
 // ---------------------------------------------------------------------------
@@ -1760,155 +2487,196 @@ pub fn execute(cpu: &mut Cpu, mem: &mut impl Memory, opcode: u8) {
 // ---------------------------------------------------------------------------
 
 /// PSHS: push selected registers onto S. Each byte pushed adds 1 cycle.
-fn pshs(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) {
+fn pshs(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) -> u8 {
+    let mut cycles = 0;
     // Push order: PC, U, Y, X, DP, B, A, CC (highest bit first)
     if post & 0x80 != 0 {
         cpu.push_word_s(mem, cpu.reg.pc);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x40 != 0 {
         cpu.push_word_s(mem, cpu.reg.u);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x20 != 0 {
         cpu.push_word_s(mem, cpu.reg.y);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x10 != 0 {
         cpu.push_word_s(mem, cpu.reg.x);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x08 != 0 {
         cpu.push_byte_s(mem, cpu.reg.dp);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x04 != 0 {
         cpu.push_byte_s(mem, cpu.reg.b());
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x02 != 0 {
         cpu.push_byte_s(mem, cpu.reg.a());
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x01 != 0 {
         cpu.push_byte_s(mem, cpu.reg.cc.to_byte());
-        cpu.cycles += 1;
+        cycles += 1;
     }
+    cycles
 }
 
 /// PULS: pull selected registers from S. Each byte pulled adds 1 cycle.
-fn puls(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) {
+fn puls(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) -> u8 {
+    let mut cycles = 0;
     // Pull order: CC, A, B, DP, X, Y, U, PC (lowest bit first)
     if post & 0x01 != 0 {
         let v = cpu.pull_byte_s(mem);
         cpu.reg.cc = crate::registers::ConditionCodes::from_byte(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x02 != 0 {
         let v = cpu.pull_byte_s(mem);
         cpu.reg.set_a(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x04 != 0 {
         let v = cpu.pull_byte_s(mem);
         cpu.reg.set_b(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x08 != 0 {
         cpu.reg.dp = cpu.pull_byte_s(mem);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x10 != 0 {
         cpu.reg.x = cpu.pull_word_s(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x20 != 0 {
         cpu.reg.y = cpu.pull_word_s(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x40 != 0 {
         cpu.reg.u = cpu.pull_word_s(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x80 != 0 {
         cpu.reg.pc = cpu.pull_word_s(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
+    cycles
 }
 
 /// PSHU: push selected registers onto U.
-fn pshu(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) {
+fn pshu(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) -> u8 {
+    let mut cycles = 0;
     if post & 0x80 != 0 {
         cpu.push_word_u(mem, cpu.reg.pc);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x40 != 0 {
         cpu.push_word_u(mem, cpu.reg.s);
-        cpu.cycles += 2;
+        cycles += 2;
     } // S instead of U
     if post & 0x20 != 0 {
         cpu.push_word_u(mem, cpu.reg.y);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x10 != 0 {
         cpu.push_word_u(mem, cpu.reg.x);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x08 != 0 {
         cpu.push_byte_u(mem, cpu.reg.dp);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x04 != 0 {
         cpu.push_byte_u(mem, cpu.reg.b());
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x02 != 0 {
         cpu.push_byte_u(mem, cpu.reg.a());
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x01 != 0 {
         cpu.push_byte_u(mem, cpu.reg.cc.to_byte());
-        cpu.cycles += 1;
+        cycles += 1;
     }
+    cycles
 }
 
 /// PULU: pull selected registers from U.
-fn pulu(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) {
+fn pulu(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) -> u8 {
+    let mut cycles = 0;
     if post & 0x01 != 0 {
         let v = cpu.pull_byte_u(mem);
         cpu.reg.cc = crate::registers::ConditionCodes::from_byte(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x02 != 0 {
         let v = cpu.pull_byte_u(mem);
         cpu.reg.set_a(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x04 != 0 {
         let v = cpu.pull_byte_u(mem);
         cpu.reg.set_b(v);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x08 != 0 {
         cpu.reg.dp = cpu.pull_byte_u(mem);
-        cpu.cycles += 1;
+        cycles += 1;
     }
     if post & 0x10 != 0 {
         cpu.reg.x = cpu.pull_word_u(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x20 != 0 {
         cpu.reg.y = cpu.pull_word_u(mem);
-        cpu.cycles += 2;
+        cycles += 2;
     }
     if post & 0x40 != 0 {
         cpu.reg.s = cpu.pull_word_u(mem);
         cpu.arm_nmi();
-        cpu.cycles += 2;
+        cycles += 2;
     } // S instead of U
     if post & 0x80 != 0 {
         cpu.reg.pc = cpu.pull_word_u(mem);
-        cpu.cycles += 2;
+        cycles += 2;
+    }
+    cycles
+}
+
+// ---------------------------------------------------------------------------
+// Subtract-class ALU operations
+// ---------------------------------------------------------------------------
+
+/// SUB/CMP through the ALU, with H as the CPU's [`HalfCarryQuirks`] select.
+pub(super) fn sub8(cpu: &mut Cpu, a: u8, b: u8) -> u8 {
+    let r = alu::sub8(a, b, &mut cpu.reg.cc);
+    half_borrow(cpu, a, b, r);
+    r
+}
+
+/// SBC through the ALU, with H as the CPU's [`HalfCarryQuirks`] select.
+pub(super) fn sbc8(cpu: &mut Cpu, a: u8, b: u8) -> u8 {
+    let r = alu::sbc8(a, b, &mut cpu.reg.cc);
+    half_borrow(cpu, a, b, r);
+    r
+}
+
+/// NEG through the ALU, with H as the CPU's [`HalfCarryQuirks`] select.
+fn neg8(cpu: &mut Cpu, val: u8) -> u8 {
+    let r = alu::neg8(val, &mut cpu.reg.cc);
+    half_borrow(cpu, 0, val, r);
+    r
+}
+
+/// Set H to the borrow out of bit 3 of `a - b = r` under
+/// [`HalfCarryQuirks::Hardware`]; leave it alone otherwise.
+fn half_borrow(cpu: &mut Cpu, a: u8, b: u8, r: u8) {
+    if cpu.half_carry_quirks == HalfCarryQuirks::Hardware {
+        cpu.reg.cc.set_half_carry((a ^ b ^ r) & 0x10 != 0);
     }
 }
 
@@ -1918,39 +2686,70 @@ fn pulu(cpu: &mut Cpu, mem: &mut impl Memory, post: u8) {
 
 /// Read a register identified by a 4-bit code (from TFR/EXG post-byte).
 /// Returns (value, is_16bit).
-fn read_reg(cpu: &Cpu, code: u8) -> (u16, bool) {
+///
+/// On the 6309, codes 6 and 7 are W and V, C and D the zero register, and
+/// E and F the accumulators E and F.
+pub(super) fn read_reg(cpu: &Cpu, code: u8) -> (u16, bool) {
+    if let Some(reg) = Reg::from_code(code) {
+        return (cpu.reg.get(reg), reg.is_16bit());
+    }
+    let hd6309 = cpu.variant == CpuVariant::Hd6309;
     match code {
-        0x0 => (cpu.reg.d, true),
-        0x1 => (cpu.reg.x, true),
-        0x2 => (cpu.reg.y, true),
-        0x3 => (cpu.reg.u, true),
-        0x4 => (cpu.reg.s, true),
-        0x5 => (cpu.reg.pc, true),
-        0x8 => (cpu.reg.a() as u16, false),
-        0x9 => (cpu.reg.b() as u16, false),
-        0xA => (cpu.reg.cc.to_byte() as u16, false),
-        0xB => (cpu.reg.dp as u16, false),
-        _ => (0xFF, false), // undefined → 0xFF
+        0x6 if hd6309 => (cpu.reg6309.w, true),
+        0x7 if hd6309 => (cpu.reg6309.v, true),
+        0xC | 0xD if hd6309 => (0, false),
+        0xE if hd6309 => (cpu.reg6309.e() as u16, false),
+        0xF if hd6309 => (cpu.reg6309.f() as u16, false),
+        0x6 | 0x7 => (0xFFFF, true), // undefined → all ones
+        _ => (0xFF, false),
     }
 }
 
 /// Write a register identified by a 4-bit code.
-fn write_reg(cpu: &mut Cpu, code: u8, val: u16) {
-    match code {
-        0x0 => cpu.reg.d = val,
-        0x1 => cpu.reg.x = val,
-        0x2 => cpu.reg.y = val,
-        0x3 => cpu.reg.u = val,
-        0x4 => {
-            cpu.reg.s = val;
+pub(super) fn write_reg(cpu: &mut Cpu, code: u8, val: u16) {
+    if let Some(reg) = Reg::from_code(code) {
+        cpu.reg.set(reg, val);
+        if reg == Reg::S {
             cpu.arm_nmi();
         }
-        0x5 => cpu.reg.pc = val,
-        0x8 => cpu.reg.set_a(val as u8),
-        0x9 => cpu.reg.set_b(val as u8),
-        0xA => cpu.reg.cc = crate::registers::ConditionCodes::from_byte(val as u8),
-        0xB => cpu.reg.dp = val as u8,
-        _ => {} // undefined register — ignore
+        return;
+    }
+    let hd6309 = cpu.variant == CpuVariant::Hd6309;
+    match code {
+        0x6 if hd6309 => cpu.reg6309.w = val,
+        0x7 if hd6309 => cpu.reg6309.v = val,
+        0xE if hd6309 => cpu.reg6309.set_e(val as u8),
+        0xF if hd6309 => cpu.reg6309.set_f(val as u8),
+        _ => {} // undefined or zero register — ignore
+    }
+}
+
+/// Value a register of size `dst_16` receives from register `code` holding
+/// `val` of size `src_16`, under the CPU's [`TransferQuirks`].
+fn convert_reg(cpu: &Cpu, code: u8, val: u16, src_16: bool, dst_16: bool) -> u16 {
+    if src_16 == dst_16 {
+        return val;
+    }
+    // The 6309's zero register is zero at any size.
+    if cpu.variant == CpuVariant::Hd6309 && matches!(code, 0xC | 0xD) {
+        return 0;
+    }
+    match cpu.transfer_quirks {
+        // Mixed 8/16-bit transfer → 0xFF (undocumented)
+        TransferQuirks::Simple => {
+            if dst_16 {
+                0xFFFF
+            } else {
+                0xFF
+            }
+        }
+        // As measured: 16 → 8 keeps the low byte; 8 → 16 fills the high
+        // byte with 0xFF, except CC and DP, which fill both bytes.
+        TransferQuirks::Hardware => match (dst_16, code) {
+            (false, _) => val & 0xFF,
+            (true, 0xA | 0xB) => (val << 8) | val,
+            (true, _) => 0xFF00 | val,
+        },
     }
 }
 
@@ -1961,12 +2760,7 @@ fn tfr(cpu: &mut Cpu, post: u8) {
     let (src_val, src_16) = read_reg(cpu, src_code);
     let (_, dst_16) = read_reg(cpu, dst_code);
 
-    let val = if src_16 != dst_16 {
-        // Mixed 8/16-bit transfer → 0xFF (undocumented)
-        if dst_16 { 0xFFFF } else { 0xFF }
-    } else {
-        src_val
-    };
+    let val = convert_reg(cpu, src_code, src_val, src_16, dst_16);
     write_reg(cpu, dst_code, val);
 }
 
@@ -1977,14 +2771,8 @@ fn exg(cpu: &mut Cpu, post: u8) {
     let (src_val, src_16) = read_reg(cpu, src_code);
     let (dst_val, dst_16) = read_reg(cpu, dst_code);
 
-    if src_16 != dst_16 {
-        // Mixed 8/16-bit exchange → both get 0xFF (undocumented)
-        let sv = if src_16 { 0xFFFF } else { 0xFF };
-        let dv = if dst_16 { 0xFFFF } else { 0xFF };
-        write_reg(cpu, src_code, sv);
-        write_reg(cpu, dst_code, dv);
-    } else {
-        write_reg(cpu, src_code, dst_val);
-        write_reg(cpu, dst_code, src_val);
-    }
+    let sv = convert_reg(cpu, dst_code, dst_val, dst_16, src_16);
+    let dv = convert_reg(cpu, src_code, src_val, src_16, dst_16);
+    write_reg(cpu, src_code, sv);
+    write_reg(cpu, dst_code, dv);
 }
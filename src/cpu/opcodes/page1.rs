@@ -15,16 +15,20 @@
 //! Page 1 opcode implementations (prefix 0x10).
 //!
 //! Contains: long conditional branches, SWI2, CMPD, CMPY, LDY, STY, LDS, STS.
-//! Contains all undocumented page 1 opcodes except all store immediate,
+//! Contains all undocumented page 1 opcodes; the store immediates and the
+//! page 0 fallback for undefined opcodes only run with
+//! [`Cpu::set_undocumented_opcodes`] enabled,
 //! source: <https://github.com/hoglet67/6809Decoder/wiki/Undocumented-6809-Behaviours>
 
+use super::{Dispatch, Handler, UNDEFINED, page0, undefined_on_page};
 use crate::alu;
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, Swi};
 use crate::memory::Memory;
+use crate::registers::Condition;
 
 /// Base cycle counts for Page 1 opcodes. Invalid entries return a cycle count of 2.
 #[rustfmt::skip]
-const PAGE1_CYCLES: [u8; 256] = {
+pub(super) const PAGE1_CYCLES: [u8; 256] = {
     let mut t = [2u8; 256];
     // Long branches: 5 cycles (not taken), 6 cycles (taken).
     // We charge 5 base and add 1 if taken.
@@ -68,341 +72,500 @@ pub(super) fn cycles(sub: u8) -> u8 {
     PAGE1_CYCLES[sub as usize]
 }
 
-pub fn execute(cpu: &mut Cpu, mem: &mut impl Memory, opcode: u8) {
-    cpu.cycles += PAGE1_CYCLES[opcode as usize] as u64;
-
-    match opcode {
-        // =================================================================
-        // Long conditional branches (16-bit relative offset)
-        // =================================================================
-        0x20 => {
-            // XLBRA (same as LBRA with one extra cycle)
-            let addr = cpu.addr_relative16(mem);
-            cpu.reg.pc = addr;
-        }
-        0x21 => {
-            // LBRN
-            let _addr = cpu.addr_relative16(mem);
-        }
-        0x22 => {
-            // LBHI
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.carry() && !cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x23 => {
-            // LBLS
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.carry() || cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x24 => {
-            // LBHS/LBCC
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.carry() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x25 => {
-            // LBLO/LBCS
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.carry() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x26 => {
-            // LBNE
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x27 => {
-            // LBEQ
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.zero() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x28 => {
-            // LBVC
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x29 => {
-            // LBVS
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2A => {
-            // LBPL
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.negative() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2B => {
-            // LBMI
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.negative() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2C => {
-            // LBGE
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.negative() == cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2D => {
-            // LBLT
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.negative() != cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2E => {
-            // LBGT
-            let addr = cpu.addr_relative16(mem);
-            if !cpu.reg.cc.zero() && cpu.reg.cc.negative() == cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-        0x2F => {
-            // LBLE
-            let addr = cpu.addr_relative16(mem);
-            if cpu.reg.cc.zero() || cpu.reg.cc.negative() != cpu.reg.cc.overflow() {
-                cpu.reg.pc = addr;
-                cpu.cycles += 1;
-            }
-        }
-
-        // =================================================================
-        // SWI2
-        // =================================================================
-        0x3E => {
-            // SWi2 (undocumented)
-            // Does not set E, I or F flags
-            cpu.push_entire_state(mem);
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_SWI2);
-        }
-        0x3F => {
-            cpu.reg.cc.set_entire(true);
-            cpu.push_entire_state(mem);
-            // SWI2 does NOT set I or F flags
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_SWI2);
-        }
-
-        // =================================================================
-        // CMPD — compare D (16-bit subtract, discard result)
-        // =================================================================
-        0x83 => {
-            let v = cpu.fetch_word(mem);
-            let d = cpu.reg.d;
-            alu::sub16(d, v, &mut cpu.reg.cc);
-        }
-        0x93 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            alu::sub16(d, v, &mut cpu.reg.cc);
-        }
-        0xA3 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            alu::sub16(d, v, &mut cpu.reg.cc);
-        }
-        0xB3 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            alu::sub16(d, v, &mut cpu.reg.cc);
-        }
-
-        // =================================================================
-        // CMPY — compare Y
-        // =================================================================
-        0x8C => {
-            let v = cpu.fetch_word(mem);
-            let y = cpu.reg.y;
-            alu::sub16(y, v, &mut cpu.reg.cc);
-        }
-        0x9C => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let y = cpu.reg.y;
-            alu::sub16(y, v, &mut cpu.reg.cc);
-        }
-        0xAC => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let y = cpu.reg.y;
-            alu::sub16(y, v, &mut cpu.reg.cc);
-        }
-        0xBC => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let y = cpu.reg.y;
-            alu::sub16(y, v, &mut cpu.reg.cc);
-        }
-
-        // =================================================================
-        // LDY / STY
-        // =================================================================
-        0x8E => {
-            let v = cpu.fetch_word(mem);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.y = v;
-        }
-        0x9E => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.y = v;
-        }
-        0x9F => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.y;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xAE => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.y = v;
-        }
-        0xAF => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.y;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xBE => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.y = v;
-        }
-        0xBF => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.y;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-
-        // =================================================================
-        // LDS / STS
-        // =================================================================
-        0xC3 => {
-            // XADDD imm (undocumented)
-            // XADDD performs a 16-bit addition of the operand with D, and
-            // sets the Z,N,C,V flags in an identical manner to ADDD. The
-            // result is, however, not written back to D.
-            let v = cpu.fetch_word(mem);
-            let d = cpu.reg.d;
-            let _r = alu::add16(d, v, &mut cpu.reg.cc);
-        }
-        0xCE => {
-            let v = cpu.fetch_word(mem);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.s = v;
-            cpu.arm_nmi();
-        }
-        0xD3 => {
-            // XADDD direct (undocumented)
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let _r = alu::add16(d, v, &mut cpu.reg.cc);
-        }
-        0xDE => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.s = v;
-            cpu.arm_nmi();
-        }
-        0xDF => {
-            let addr = cpu.addr_direct(mem);
-            let v = cpu.reg.s;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xE3 => {
-            // XADDD indexed (undocumented)
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let _r = alu::add16(d, v, &mut cpu.reg.cc);
-        }
-        0xEE => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.s = v;
-            cpu.arm_nmi();
-        }
-        0xEF => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = cpu.reg.s;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-        0xF3 => {
-            // XADDD extended (undocumented)
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let d = cpu.reg.d;
-            let _r = alu::add16(d, v, &mut cpu.reg.cc);
-        }
-        0xFE => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            cpu.reg.s = v;
-            cpu.arm_nmi();
-        }
-        0xFF => {
-            let addr = cpu.addr_extended(mem);
-            let v = cpu.reg.s;
-            alu::ld16_flags(v, &mut cpu.reg.cc);
-            mem.write_word(addr, v);
-        }
-
-        // Illegal Page 1 opcodes
-        _ => {
-            // 1 cycle already consumed by the page prefix fetch
-            //debug!("Illegal Page 1 opcode: 0x10 {:02X}", opcode);
-            cpu.illegal = true;
-        }
+impl<M: Memory> Dispatch<M> {
+    /// Handler table for Page 1 opcodes. Unlisted opcodes are illegal.
+    pub(super) const PAGE1: [Handler<M>; 256] = dispatch_table!(M, illegal;
+        0x20 => xlbra,
+        0x21 => lbrn,
+        0x22 => lbhi,
+        0x23 => lbls,
+        0x24 => lbcc,
+        0x25 => lbcs,
+        0x26 => lbne,
+        0x27 => lbeq,
+        0x28 => lbvc,
+        0x29 => lbvs,
+        0x2A => lbpl,
+        0x2B => lbmi,
+        0x2C => lbge,
+        0x2D => lblt,
+        0x2E => lbgt,
+        0x2F => lble,
+        0x3E => xswi2,
+        0x3F => swi2,
+        0x83 => cmpd_imm,
+        0x8C => cmpy_imm,
+        0x8E => ldy_imm,
+        0x8F => xsty_imm,
+        0x93 => cmpd_dir,
+        0x9C => cmpy_dir,
+        0x9E => ldy_dir,
+        0x9F => sty_dir,
+        0xA3 => cmpd_idx,
+        0xAC => cmpy_idx,
+        0xAE => ldy_idx,
+        0xAF => sty_idx,
+        0xB3 => cmpd_ext,
+        0xBC => cmpy_ext,
+        0xBE => ldy_ext,
+        0xBF => sty_ext,
+        0xC3 => xaddd_imm,
+        0xCE => lds_imm,
+        0xCF => xsts_imm,
+        0xD3 => xaddd_dir,
+        0xDE => lds_dir,
+        0xDF => sts_dir,
+        0xE3 => xaddd_idx,
+        0xEE => lds_idx,
+        0xEF => sts_idx,
+        0xF3 => xaddd_ext,
+        0xFE => lds_ext,
+        0xFF => sts_ext,
+    );
+}
+
+/// Page 1 opcodes that can leave the straight-line instruction stream.
+pub(super) fn ends_block(opcode: u8) -> bool {
+    matches!(opcode, 0x20..=0x2F | 0x3E | 0x3F)
+}
+
+/// Execute a Page 1 opcode (already fetched) through the dispatch table.
+///
+/// Returns the instruction's cycle count: the base cycles plus any extra
+/// cycles reported by the handler.
+pub(super) fn execute<M: Memory>(cpu: &mut Cpu, mem: &mut M, opcode: u8) -> u8 {
+    match Dispatch::<M>::PAGE1[opcode as usize](cpu, mem) {
+        UNDEFINED => undefined_on_page(cpu, mem, opcode),
+        extra => PAGE1_CYCLES[opcode as usize] + extra,
+    }
+}
+
+// =================================================================
+// Long conditional branches (16-bit relative offset)
+// =================================================================
+fn xlbra<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XLBRA (same as LBRA with one extra cycle)
+    let addr = cpu.addr_relative16(mem);
+    cpu.reg.pc = addr;
+    0
+}
+
+fn lbrn<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBRN
+    let _addr = cpu.addr_relative16(mem);
+    0
+}
+
+fn lbhi<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBHI
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Hi) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbls<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBLS
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Ls) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbcc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBHS/LBCC
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Cc) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbcs<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBLO/LBCS
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Cs) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbne<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBNE
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Ne) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbeq<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBEQ
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Eq) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbvc<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBVC
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Vc) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbvs<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBVS
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Vs) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbpl<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBPL
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Pl) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbmi<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBMI
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Mi) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbge<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBGE
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Ge) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lblt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBLT
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Lt) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lbgt<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBGT
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Gt) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+fn lble<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // LBLE
+    let addr = cpu.addr_relative16(mem);
+    if cpu.reg.cc.test(Condition::Le) {
+        cpu.reg.pc = addr;
+        return 1;
+    }
+    0
+}
+
+// =================================================================
+// SWI2
+// =================================================================
+fn xswi2<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // SWi2 (undocumented)
+    // Does not set E, I or F flags
+    if cpu.swi_serviced(Swi::Swi2, mem) {
+        return 0;
+    }
+    cpu.push_entire_state(mem);
+    cpu.reg.pc = mem.read_word(cpu.vectors.swi2);
+    0
+}
+
+fn swi2<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    if cpu.swi_serviced(Swi::Swi2, mem) {
+        return 0;
+    }
+    cpu.reg.cc.set_entire(true);
+    cpu.push_entire_state(mem);
+    // SWI2 does NOT set I or F flags
+    cpu.reg.pc = mem.read_word(cpu.vectors.swi2);
+    0
+}
+
+// =================================================================
+// CMPD — compare D (16-bit subtract, discard result)
+// =================================================================
+fn cmpd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    alu::sub16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::sub16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::sub16(d, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn cmpd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    alu::sub16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+// =================================================================
+// CMPY — compare Y
+// =================================================================
+fn cmpy_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let y = cpu.reg.y;
+    alu::sub16(y, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpy_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let y = cpu.reg.y;
+    alu::sub16(y, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpy_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let y = cpu.reg.y;
+    alu::sub16(y, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn cmpy_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let y = cpu.reg.y;
+    alu::sub16(y, v, &mut cpu.reg.cc);
+    0
+}
+
+// =================================================================
+// LDY / STY
+// =================================================================
+fn ldy_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.y = v;
+    0
+}
+
+fn ldy_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.y = v;
+    0
+}
+
+fn xsty_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTY immediate (undocumented): Y is written over its own operand bytes
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 2);
+    }
+    let v = cpu.reg.y;
+    page0::store_imm16(cpu, mem, v)
+}
+
+fn sty_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.y;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+fn ldy_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.y = v;
+    ex
+}
+
+fn sty_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.y;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
+
+fn ldy_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.y = v;
+    0
+}
+
+fn sty_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.y;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// =================================================================
+// LDS / STS
+// =================================================================
+fn xaddd_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDD imm (undocumented)
+    // XADDD performs a 16-bit addition of the operand with D, and
+    // sets the Z,N,C,V flags in an identical manner to ADDD. The
+    // result is, however, not written back to D.
+    let v = cpu.fetch_word(mem);
+    let d = cpu.reg.d;
+    let _r = alu::add16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lds_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.s = v;
+    cpu.arm_nmi();
+    0
+}
+
+fn xsts_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XSTS immediate (undocumented)
+    if !cpu.undocumented_opcodes {
+        return cpu.illegal_opcode(mem, 2);
     }
+    let v = cpu.reg.s;
+    page0::store_imm16(cpu, mem, v)
+}
+
+fn xaddd_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDD direct (undocumented)
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let _r = alu::add16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lds_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.s = v;
+    cpu.arm_nmi();
+    0
+}
+
+fn sts_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = cpu.reg.s;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+fn xaddd_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDD indexed (undocumented)
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let _r = alu::add16(d, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn lds_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.s = v;
+    cpu.arm_nmi();
+    ex
+}
+
+fn sts_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = cpu.reg.s;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    ex
+}
+
+fn xaddd_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDD extended (undocumented)
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let d = cpu.reg.d;
+    let _r = alu::add16(d, v, &mut cpu.reg.cc);
+    0
+}
+
+fn lds_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    cpu.reg.s = v;
+    cpu.arm_nmi();
+    0
+}
+
+fn sts_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = cpu.reg.s;
+    alu::ld16_flags(v, &mut cpu.reg.cc);
+    mem.write_word(addr, v);
+    0
+}
+
+// Undefined Page 1 opcodes — resolved by `execute`, which knows the opcode
+fn illegal<M: Memory>(_cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    UNDEFINED
 }
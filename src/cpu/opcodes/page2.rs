@@ -15,16 +15,19 @@
 //! Page 2 opcode implementations (prefix 0x11).
 //!
 //! Contains: SWI3, CMPU, CMPS.
-//! Contains all undocumented page 2 opcodes except all store immediate,
+//! Contains all undocumented page 2 opcodes; the page 0 fallback for
+//! undefined opcodes only runs with [`Cpu::set_undocumented_opcodes`]
+//! enabled,
 //! source: <https://github.com/hoglet67/6809Decoder/wiki/Undocumented-6809-Behaviours>
 
+use super::{Dispatch, Handler, UNDEFINED, undefined_on_page};
 use crate::alu;
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, Swi};
 use crate::memory::Memory;
 
 /// Base cycle counts for Page 2 opcodes. Invalid entries return a cycle count of 2.
 #[rustfmt::skip]
-const PAGE2_CYCLES: [u8; 256] = {
+pub(super) const PAGE2_CYCLES: [u8; 256] = {
     let mut t = [2u8; 256];
     t[0x3E] = 20; // XFIRQ (undocumented)
     t[0x3F] = 20; // SWI3
@@ -48,129 +51,181 @@ pub(super) fn cycles(sub: u8) -> u8 {
     PAGE2_CYCLES[sub as usize]
 }
 
-pub fn execute(cpu: &mut Cpu, mem: &mut impl Memory, opcode: u8) {
-    cpu.cycles += PAGE2_CYCLES[opcode as usize] as u64;
-
-    match opcode {
-        // =================================================================
-        // XFIRQ (undocumented)
-        // =================================================================
-        // This instruction is similar to SWI (0x3F), except the
-        // FIRQ vector (0xFFF6/7) is used to determine the next
-        // PC value, and it does not correctly set the E flag in
-        // the saved machine state.
-        // Flags: all flags are unchanged
-        // Note: unlike a hardware FIRQ, the F and I flags are not set.
-        0x3E => {
-            cpu.push_entire_state(mem);
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_FIRQ);
-        }
-        // =================================================================
-        // SWI3
-        // =================================================================
-        0x3F => {
-            cpu.reg.cc.set_entire(true);
-            cpu.push_entire_state(mem);
-            // SWI3 does NOT set I or F flags
-            cpu.reg.pc = mem.read_word(crate::cpu::VEC_SWI3);
-        }
-
-        // =================================================================
-        // CMPU — compare U
-        // =================================================================
-        0x83 => {
-            let v = cpu.fetch_word(mem);
-            let u = cpu.reg.u;
-            alu::sub16(u, v, &mut cpu.reg.cc);
-        }
-        0x93 => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u;
-            alu::sub16(u, v, &mut cpu.reg.cc);
-        }
-        0xA3 => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u;
-            alu::sub16(u, v, &mut cpu.reg.cc);
-        }
-        0xB3 => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u;
-            alu::sub16(u, v, &mut cpu.reg.cc);
-        }
-
-        // =================================================================
-        // CMPS — compare S
-        // =================================================================
-        0x8C => {
-            let v = cpu.fetch_word(mem);
-            let s = cpu.reg.s;
-            alu::sub16(s, v, &mut cpu.reg.cc);
-        }
-        0x9C => {
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let s = cpu.reg.s;
-            alu::sub16(s, v, &mut cpu.reg.cc);
-        }
-        0xAC => {
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let s = cpu.reg.s;
-            alu::sub16(s, v, &mut cpu.reg.cc);
-        }
-        0xBC => {
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let s = cpu.reg.s;
-            alu::sub16(s, v, &mut cpu.reg.cc);
-        }
-
-        // =================================================================
-        // XADDU - add U (undocumented)
-        // =================================================================
-        // XADDU performs a 16-bit addition of the operand with
-        // (U | 0xFF00), and sets the Z,N,C,V flags in an identical manner
-        // to ADDD. The result is, however, not written back to U.
-        0xC3 => {
-            // XADDU imm
-            let v = cpu.fetch_word(mem);
-            let u = cpu.reg.u | 0xFF00;
-            let _r = alu::add16(u, v, &mut cpu.reg.cc);
-        }
-        0xD3 => {
-            // XADDU direct
-            let addr = cpu.addr_direct(mem);
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u | 0xFF00;
-            let _r = alu::add16(u, v, &mut cpu.reg.cc);
-        }
-        0xE3 => {
-            // XADDU indexed
-            let (addr, ex) = cpu.addr_indexed(mem);
-            cpu.cycles += ex as u64;
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u | 0xFF00;
-            let _r = alu::add16(u, v, &mut cpu.reg.cc);
-        }
-        0xF3 => {
-            // XADDU extended
-            let addr = cpu.addr_extended(mem);
-            let v = mem.read_word(addr);
-            let u = cpu.reg.u | 0xFF00;
-            let _r = alu::add16(u, v, &mut cpu.reg.cc);
-        }
-
-        // Illegal Page 2 opcodes
-        _ => {
-            // 1 cycle already consumed by the page prefix fetch
-            //debug!("Illegal Page 2 opcode: {:02X}", opcode);
-            cpu.illegal = true;
-        }
+impl<M: Memory> Dispatch<M> {
+    /// Handler table for Page 2 opcodes. Unlisted opcodes are illegal.
+    pub(super) const PAGE2: [Handler<M>; 256] = dispatch_table!(M, illegal;
+        0x3E => xfirq,
+        0x3F => swi3,
+        0x83 => cmpu_imm,
+        0x8C => cmps_imm,
+        0x93 => cmpu_dir,
+        0x9C => cmps_dir,
+        0xA3 => cmpu_idx,
+        0xAC => cmps_idx,
+        0xB3 => cmpu_ext,
+        0xBC => cmps_ext,
+        0xC3 => xaddu_imm,
+        0xD3 => xaddu_dir,
+        0xE3 => xaddu_idx,
+        0xF3 => xaddu_ext,
+    );
+}
+
+/// Page 2 opcodes that can leave the straight-line instruction stream.
+pub(super) fn ends_block(opcode: u8) -> bool {
+    matches!(opcode, 0x3E | 0x3F)
+}
+
+/// Execute a Page 2 opcode (already fetched) through the dispatch table.
+///
+/// Returns the instruction's cycle count: the base cycles plus any extra
+/// cycles reported by the handler.
+pub(super) fn execute<M: Memory>(cpu: &mut Cpu, mem: &mut M, opcode: u8) -> u8 {
+    match Dispatch::<M>::PAGE2[opcode as usize](cpu, mem) {
+        UNDEFINED => undefined_on_page(cpu, mem, opcode),
+        extra => PAGE2_CYCLES[opcode as usize] + extra,
+    }
+}
+
+// =================================================================
+// XFIRQ (undocumented)
+// =================================================================
+// This instruction is similar to SWI (0x3F), except the
+// FIRQ vector (0xFFF6/7) is used to determine the next
+// PC value, and it does not correctly set the E flag in
+// the saved machine state.
+// Flags: all flags are unchanged
+// Note: unlike a hardware FIRQ, the F and I flags are not set.
+fn xfirq<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    cpu.push_entire_state(mem);
+    cpu.reg.pc = mem.read_word(cpu.vectors.firq);
+    0
+}
+
+// =================================================================
+// SWI3
+// =================================================================
+fn swi3<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    if cpu.swi_serviced(Swi::Swi3, mem) {
+        return 0;
     }
+    cpu.reg.cc.set_entire(true);
+    cpu.push_entire_state(mem);
+    // SWI3 does NOT set I or F flags
+    cpu.reg.pc = mem.read_word(cpu.vectors.swi3);
+    0
+}
+
+// =================================================================
+// CMPU — compare U
+// =================================================================
+fn cmpu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let u = cpu.reg.u;
+    alu::sub16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpu_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u;
+    alu::sub16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmpu_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u;
+    alu::sub16(u, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn cmpu_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u;
+    alu::sub16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+// =================================================================
+// CMPS — compare S
+// =================================================================
+fn cmps_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let v = cpu.fetch_word(mem);
+    let s = cpu.reg.s;
+    alu::sub16(s, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmps_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let s = cpu.reg.s;
+    alu::sub16(s, v, &mut cpu.reg.cc);
+    0
+}
+
+fn cmps_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let s = cpu.reg.s;
+    alu::sub16(s, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn cmps_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let s = cpu.reg.s;
+    alu::sub16(s, v, &mut cpu.reg.cc);
+    0
+}
+
+// =================================================================
+// XADDU - add U (undocumented)
+// =================================================================
+// XADDU performs a 16-bit addition of the operand with
+// (U | 0xFF00), and sets the Z,N,C,V flags in an identical manner
+// to ADDD. The result is, however, not written back to U.
+fn xaddu_imm<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDU imm
+    let v = cpu.fetch_word(mem);
+    let u = cpu.reg.u | 0xFF00;
+    let _r = alu::add16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+fn xaddu_dir<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDU direct
+    let addr = cpu.addr_direct(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u | 0xFF00;
+    let _r = alu::add16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+fn xaddu_idx<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDU indexed
+    let (addr, ex) = cpu.addr_indexed(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u | 0xFF00;
+    let _r = alu::add16(u, v, &mut cpu.reg.cc);
+    ex
+}
+
+fn xaddu_ext<M: Memory>(cpu: &mut Cpu, mem: &mut M) -> u8 {
+    // XADDU extended
+    let addr = cpu.addr_extended(mem);
+    let v = mem.read_word(addr);
+    let u = cpu.reg.u | 0xFF00;
+    let _r = alu::add16(u, v, &mut cpu.reg.cc);
+    0
+}
+
+// Undefined Page 2 opcodes — resolved by `execute`, which knows the opcode
+fn illegal<M: Memory>(_cpu: &mut Cpu, _mem: &mut M) -> u8 {
+    UNDEFINED
 }
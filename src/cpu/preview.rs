@@ -0,0 +1,87 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Looking ahead at an instruction without executing it.
+
+use super::Cpu;
+use crate::disasm::{self, Mode};
+use crate::memory::Memory;
+use crate::stats::AddressingMode;
+
+impl Cpu {
+    /// Where the instruction at `addr` would address its operand if it ran
+    /// now, and how: the direct, extended or indexed address after any
+    /// indirection, or a branch's destination. `None` for inherent,
+    /// immediate and register operands, and for undefined opcodes.
+    ///
+    /// Nothing is changed: an auto-increment or decrement is worked out but
+    /// not applied. Only the instruction's bytes, and the pointer of an
+    /// indirect mode, are read from `mem`. Opcodes are decoded as the
+    /// MC6809's, untranslated (see [`Self::set_opcode_translator`]); on the
+    /// HD6309 the instructions it adds give `None`, though its indexed
+    /// modes are followed.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::stats::AddressingMode;
+    /// use mc6809_core::{Cpu, FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0xA7, 0x80], 0x0400); // STA ,X+
+    /// let mut cpu = Cpu::new();
+    /// cpu.registers_mut().x = 0xFF20;
+    ///
+    /// let preview = cpu.effective_address_at(&mut ram, 0x0400);
+    /// assert_eq!(preview, Some((0xFF20, AddressingMode::Indexed)));
+    /// assert_eq!(cpu.registers().x, 0xFF20);
+    /// ```
+    pub fn effective_address_at(
+        &self,
+        mem: &mut impl Memory,
+        addr: u16,
+    ) -> Option<(u16, AddressingMode)> {
+        let (opcode, operand) = match mem.read(addr) {
+            prefix @ (0x10 | 0x11) => (
+                (prefix as u16) << 8 | mem.read(addr.wrapping_add(1)) as u16,
+                addr.wrapping_add(2),
+            ),
+            op => (op as u16, addr.wrapping_add(1)),
+        };
+        let (_, mode) = disasm::opcode_info(opcode)?;
+        let ea = match mode {
+            Mode::Direct => (self.reg.dp as u16) << 8 | mem.read(operand) as u16,
+            Mode::Extended => mem.read_word(operand),
+            Mode::Indexed => {
+                // Decode on a copy of the registers, which the post-byte may
+                // step.
+                let mut scratch = Cpu::new();
+                scratch.variant = self.variant;
+                scratch.reg = self.reg;
+                scratch.reg6309 = self.reg6309;
+                scratch.reg.pc = operand;
+                crate::addressing::indexed(&mut scratch, mem).0
+            }
+            Mode::Rel8 => {
+                let offset = mem.read(operand) as i8 as u16;
+                operand.wrapping_add(1).wrapping_add(offset)
+            }
+            Mode::Rel16 => {
+                let offset = mem.read_word(operand);
+                operand.wrapping_add(2).wrapping_add(offset)
+            }
+            _ => return None,
+        };
+        Some((ea, AddressingMode::from_mode(mode)))
+    }
+}
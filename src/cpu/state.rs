@@ -0,0 +1,304 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A self-describing save state for the CPU alone, and [`CpuState`] to
+//! compare states as values.
+
+use super::{Cpu, CpuVariant};
+use crate::peripheral::BusSignals;
+use crate::registers::{Hd6309Registers, Registers};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Leading bytes of a state written by [`Cpu::save_state`]: a tag and the
+/// format version.
+const CPU_STATE_HEADER: &[u8] = b"M6809CP\x01";
+
+impl Cpu {
+    /// Save the CPU in a fixed, documented layout that tools in other
+    /// languages can read and write without this crate.
+    ///
+    /// Unlike [`Snapshot::snapshot`], the state names its format and the
+    /// variant it was saved from, so it stands alone.
+    ///
+    /// # Layout
+    /// All multi-byte values are big-endian and there is no padding.
+    /// Version 1:
+    ///
+    /// | Offset | Size | Field |
+    /// |-------:|-----:|-------|
+    /// | 0  | 8 | Header: the ASCII tag `M6809CP` and the version byte `0x01` |
+    /// | 8  | 1 | Variant: `0` MC6809, `1` HD6309 |
+    /// | 9  | 2 | D (A in the high byte) |
+    /// | 11 | 2 | X |
+    /// | 13 | 2 | Y |
+    /// | 15 | 2 | U |
+    /// | 17 | 2 | S |
+    /// | 19 | 2 | PC |
+    /// | 21 | 1 | DP |
+    /// | 22 | 1 | CC |
+    ///
+    /// An HD6309 state continues with the 6309 registers; an MC6809 state
+    /// leaves these six bytes out, so the fields after them move down:
+    ///
+    /// | Offset | Size | Field |
+    /// |-------:|-----:|-------|
+    /// | 23 | 2 | W (E in the high byte) |
+    /// | 25 | 2 | V |
+    /// | 27 | 1 | MD |
+    /// | 28 | 1 | `1` if a TFM is part way through, else `0` |
+    ///
+    /// Both then end with the execution state, at offset `n` = 23 for an
+    /// MC6809 and 29 for an HD6309:
+    ///
+    /// | Offset | Size | Field |
+    /// |-------:|-----:|-------|
+    /// | n      | 8 | Cycles executed |
+    /// | n + 8  | 1 | Halted |
+    /// | n + 9  | 1 | Last instruction was illegal |
+    /// | n + 10 | 1 | NMI armed (S has been loaded since reset) |
+    /// | n + 11 | 1 | Waiting in CWAI |
+    /// | n + 12 | 1 | Waiting in SYNC |
+    /// | n + 13 | 1 | DMA/BREQ asserted |
+    /// | n + 14 | 1 | Bus granted to DMA |
+    /// | n + 15 | 1 | Interrupt lines: NMI `0x01`, FIRQ `0x02`, IRQ `0x04` |
+    /// | n + 16 | 1 | Cycles stolen by the current DMA grant |
+    ///
+    /// Flags are `0` or `1`. A state is 40 bytes for an MC6809 and 46 for an
+    /// HD6309; in Python, `struct.unpack(">8sB6HBBQ7BBB", data)` reads the
+    /// former. Configuration, breakpoints and hooks are not part of the state.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::Cpu;
+    ///
+    /// let mut cpu = Cpu::new();
+    /// cpu.registers_mut().pc = 0x1234;
+    /// let state = cpu.save_state();
+    /// assert_eq!(state.len(), 40);
+    /// assert_eq!(&state[19..21], &[0x12, 0x34]);
+    ///
+    /// let mut copy = Cpu::new();
+    /// copy.load_state(&state).unwrap();
+    /// assert_eq!(copy.registers().pc, 0x1234);
+    /// ```
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut out = StateWriter::new();
+        out.bytes(CPU_STATE_HEADER);
+        out.u8(match self.variant {
+            CpuVariant::Mc6809 => 0,
+            CpuVariant::Hd6309 => 1,
+        });
+        self.snapshot(&mut out);
+        out.into_bytes()
+    }
+
+    /// Restore a state written by [`Self::save_state`], or by another tool
+    /// in the same layout. The variant switches to the one saved.
+    ///
+    /// Fails with [`StateError::BadHeader`] for anything but a version 1
+    /// CPU state. If loading fails the CPU may be left partly restored.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut state = StateReader::new(data);
+        if state.bytes(CPU_STATE_HEADER.len()) != Ok(CPU_STATE_HEADER) {
+            return Err(StateError::BadHeader);
+        }
+        let variant = match state.u8()? {
+            0 => CpuVariant::Mc6809,
+            1 => CpuVariant::Hd6309,
+            _ => return Err(StateError::Mismatch("cpu variant")),
+        };
+        if variant != self.variant {
+            self.set_variant(variant);
+        }
+        self.restore(&mut state)?;
+        if state.remaining() != 0 {
+            return Err(StateError::Mismatch("trailing data"));
+        }
+        Ok(())
+    }
+}
+
+/// Everything [`Cpu::save_state`] saves, as a value that can be compared,
+/// copied and inspected.
+///
+/// Table-driven tests can compare whole states in one assert, and property
+/// tests can check that two ways of running the same program, with and
+/// without a cache say, end in the same state.
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x86, 0x42, 0x12], 0x0400); // LDA #$42; NOP
+/// ram.set_reset_vector(0x0400);
+/// let mut plain = Cpu::new();
+/// let mut cached = Cpu::new();
+/// cached.set_decode_cache(true);
+/// for cpu in [&mut plain, &mut cached] {
+///     cpu.reset(&mut ram);
+///     cpu.run(&mut ram, 4);
+/// }
+/// assert_eq!(plain.state(), cached.state());
+///
+/// let mut expected = plain.state();
+/// expected.registers.x = 0x1234;
+/// assert_eq!(
+///     plain.state().differences(&expected),
+///     ["X: expected 1234, found 0000"]
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuState {
+    /// The processor emulated.
+    pub variant: CpuVariant,
+    /// The 6809 registers.
+    pub registers: Registers,
+    /// The HD6309 registers; compared on either variant.
+    pub hd6309: Hd6309Registers,
+    /// A TFM is part way through.
+    pub tfm: bool,
+    /// Cycles executed since reset.
+    pub cycles: u64,
+    /// The CPU is halted.
+    pub halted: bool,
+    /// The last instruction was illegal.
+    pub illegal: bool,
+    /// S has been loaded since reset, so NMI can be taken.
+    pub nmi_armed: bool,
+    /// Waiting in CWAI.
+    pub cwai: bool,
+    /// Waiting in SYNC.
+    pub sync: bool,
+    /// DMA/BREQ is asserted.
+    pub dma_breq: bool,
+    /// The bus is granted to a DMA master.
+    pub bus_granted: bool,
+    /// Interrupt lines pending: NMI latched, FIRQ and IRQ levels.
+    pub int_lines: BusSignals,
+    /// Cycles stolen by the current DMA grant.
+    pub dma_stolen: u8,
+}
+
+impl CpuState {
+    /// A line for each field that differs from `expected`, as
+    /// `"name: expected …, found …"`, registers one by one.
+    pub fn differences(&self, expected: &CpuState) -> Vec<String> {
+        let mut out = Vec::new();
+        if self.variant != expected.variant {
+            out.push(format!(
+                "variant: expected {:?}, found {:?}",
+                expected.variant, self.variant
+            ));
+        }
+        for (reg, expected, actual) in self.registers.differences(&expected.registers) {
+            let width = if reg.is_16bit() { 4 } else { 2 };
+            out.push(format!(
+                "{reg}: expected {expected:0width$X}, found {actual:0width$X}"
+            ));
+        }
+        for (name, expected, actual, width) in [
+            ("W", expected.hd6309.w, self.hd6309.w, 4),
+            ("V", expected.hd6309.v, self.hd6309.v, 4),
+            ("MD", expected.hd6309.md as u16, self.hd6309.md as u16, 2),
+        ] {
+            if expected != actual {
+                out.push(format!(
+                    "{name}: expected {expected:0width$X}, found {actual:0width$X}"
+                ));
+            }
+        }
+        if self.cycles != expected.cycles {
+            out.push(format!(
+                "cycles: expected {}, found {}",
+                expected.cycles, self.cycles
+            ));
+        }
+        for (name, expected, actual) in [
+            ("tfm", expected.tfm, self.tfm),
+            ("halted", expected.halted, self.halted),
+            ("illegal", expected.illegal, self.illegal),
+            ("nmi_armed", expected.nmi_armed, self.nmi_armed),
+            ("cwai", expected.cwai, self.cwai),
+            ("sync", expected.sync, self.sync),
+            ("dma_breq", expected.dma_breq, self.dma_breq),
+            ("bus_granted", expected.bus_granted, self.bus_granted),
+        ] {
+            if expected != actual {
+                out.push(format!("{name}: expected {expected}, found {actual}"));
+            }
+        }
+        if self.int_lines != expected.int_lines {
+            out.push(format!(
+                "int_lines: expected {:?}, found {:?}",
+                expected.int_lines, self.int_lines
+            ));
+        }
+        if self.dma_stolen != expected.dma_stolen {
+            out.push(format!(
+                "dma_stolen: expected {}, found {}",
+                expected.dma_stolen, self.dma_stolen
+            ));
+        }
+        out
+    }
+}
+
+impl Cpu {
+    /// The state [`Self::save_state`] would save, as a [`CpuState`].
+    pub fn state(&self) -> CpuState {
+        CpuState {
+            variant: self.variant,
+            registers: self.reg,
+            hd6309: self.reg6309,
+            tfm: self.tfm,
+            cycles: self.cycles,
+            halted: self.halted,
+            illegal: self.illegal,
+            nmi_armed: self.nmi_armed,
+            cwai: self.cwai,
+            sync: self.sync,
+            dma_breq: self.dma_breq,
+            bus_granted: self.bus_granted,
+            int_lines: self.int_lines,
+            dma_stolen: self.dma_stolen,
+        }
+    }
+
+    /// Put the CPU in `state`, switching variant if it differs. As with
+    /// [`Self::load_state`], configuration, breakpoints and hooks are left
+    /// alone.
+    pub fn set_state(&mut self, state: &CpuState) {
+        if state.variant != self.variant {
+            self.set_variant(state.variant);
+        }
+        self.reg = state.registers;
+        self.reg6309 = state.hd6309;
+        self.tfm = state.tfm;
+        self.cycles = state.cycles;
+        self.budget_end = None;
+        self.tick_left = 0;
+        self.halted = state.halted;
+        self.illegal = state.illegal;
+        self.nmi_armed = state.nmi_armed;
+        self.cwai = state.cwai;
+        self.sync = state.sync;
+        self.dma_breq = state.dma_breq;
+        self.bus_granted = state.bus_granted;
+        self.int_lines = state.int_lines;
+        self.dma_stolen = state.dma_stolen;
+        self.flush_decode_cache();
+        self.flush_block_cache();
+    }
+}
@@ -0,0 +1,75 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Write watchpoints for [`Cpu::run_until_stop`](super::Cpu::run_until_stop).
+//!
+//! While a run steps, [`WatchpointMemory`] sits between the CPU and the
+//! real memory and notes the first write to a watched address.
+
+use std::collections::BTreeSet;
+
+use crate::memory::{BusFault, Memory};
+
+/// Memory seen by the CPU while watchpoints are set.
+pub(super) struct WatchpointMemory<'a, M: Memory> {
+    mem: &'a mut M,
+    watchpoints: &'a BTreeSet<u16>,
+    /// First watched address written.
+    pub(super) hit: Option<u16>,
+}
+
+impl<'a, M: Memory> WatchpointMemory<'a, M> {
+    pub(super) fn new(mem: &'a mut M, watchpoints: &'a BTreeSet<u16>) -> Self {
+        Self {
+            mem,
+            watchpoints,
+            hit: None,
+        }
+    }
+
+    fn check(&mut self, addr: u16) {
+        if self.hit.is_none() && self.watchpoints.contains(&addr) {
+            self.hit = Some(addr);
+        }
+    }
+}
+
+impl<M: Memory> Memory for WatchpointMemory<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.check(addr);
+        self.mem.write(addr, val);
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        self.mem.read_word(addr)
+    }
+
+    fn write_word(&mut self, addr: u16, val: u16) {
+        self.check(addr);
+        self.check(addr.wrapping_add(1));
+        self.mem.write_word(addr, val);
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        self.mem.read_slice(addr, buf);
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.mem.take_fault()
+    }
+}
@@ -0,0 +1,48 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Peripheral devices for assembling machines from crate parts.
+//!
+//! Devices plug into a [`CompositeBus`](crate::bus::CompositeBus), either as
+//! its base memory or as [`Device`](crate::bus::Device)s mapped over it.
+
+mod block_storage;
+mod cartridge;
+mod jitter;
+mod mc6821;
+mod mc6844;
+mod mc6850;
+mod rtc;
+mod sam;
+mod timer;
+mod watchdog;
+
+pub use block_storage::{BlockStorage, DISK_DRQ, DISK_ERROR, DISK_READ, DISK_WRITE};
+pub use cartridge::Cartridge;
+pub use jitter::Jittered;
+pub use mc6821::{
+    Mc6821, PIA_C1_IRQ, PIA_C1_RISING, PIA_C2_IRQ, PIA_C2_OUTPUT, PIA_C2_RISING, PIA_DATA,
+    PIA_IRQ1, PIA_IRQ2,
+};
+pub use mc6844::{
+    DMA_BURST, DMA_BUSY, DMA_CHAIN, DMA_DECREMENT, DMA_END, DMA_HALT, DMA_IRQ, DMA_READ,
+    DMA_ROTATE, DmaPort, Mc6844,
+};
+pub use mc6850::{
+    ACIA_IRQ, ACIA_MASTER_RESET, ACIA_RDRF, ACIA_RX_IRQ, ACIA_TDRE, ACIA_TX_IRQ, Mc6850,
+};
+pub use rtc::{Mc146818, RTC_24H, RTC_BINARY, RTC_IRQF, RTC_PF, RTC_PIE, RTC_SET};
+pub use sam::{MapType, MemorySize, Sam};
+pub use timer::{IntervalTimer, TIMER_ENABLE, TIMER_PENDING, TIMER_PERIODIC, TimerMode};
+pub use watchdog::Watchdog;
@@ -0,0 +1,258 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Sector-oriented virtual disk backed by a host file.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::bus::Mmio;
+use crate::peripheral::Clocked;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Command: read the selected sector into the buffer.
+pub const DISK_READ: u8 = 0x01;
+/// Command: fill the buffer, then write it to the selected sector.
+pub const DISK_WRITE: u8 = 0x02;
+/// Status: the data register is waiting for the buffer to be read or
+/// filled.
+pub const DISK_DRQ: u8 = 0x01;
+/// Status: the last command failed.
+pub const DISK_ERROR: u8 = 0x80;
+
+/// Virtual disk with a PIO register interface.
+///
+/// Commands complete immediately, so the CPU can issue one and start
+/// transferring straight away. Registers, by offset:
+///
+/// | Offset | Register                                                       |
+/// |--------|----------------------------------------------------------------|
+/// | 0      | write: command ([`DISK_READ`], [`DISK_WRITE`]); read: status ([`DISK_DRQ`], [`DISK_ERROR`]) |
+/// | 1–3    | sector number, big-endian                                      |
+/// | 4      | data: the next buffer byte                                     |
+/// | 5–7    | disk size in sectors, big-endian (read-only)                   |
+///
+/// A read command fills the buffer with the sector and raises DRQ until the
+/// last byte has been read from the data register. A write command raises
+/// DRQ until the last byte has been written, then stores the buffer, growing
+/// the file if needed. Reading past the end of the disk sets
+/// [`DISK_ERROR`]; host I/O errors do too and can be retrieved with
+/// [`Self::take_error`].
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+///
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::{BlockStorage, DISK_DRQ, DISK_READ};
+///
+/// let mut image = vec![0u8; 512];
+/// image[256] = 0x42;
+/// let mut disk = BlockStorage::new(Cursor::new(image), 256);
+///
+/// disk.write(3, 1); // sector 1
+/// disk.write(0, DISK_READ);
+/// assert_eq!(disk.read(0), DISK_DRQ);
+/// assert_eq!(disk.read(4), 0x42);
+/// ```
+pub struct BlockStorage<F = File> {
+    file: F,
+    sector_size: usize,
+    sector: u32,
+    buffer: Box<[u8]>,
+    /// Next buffer byte for the data register.
+    pos: usize,
+    /// Command waiting on the data register, if any.
+    pending: Option<u8>,
+    status: u8,
+    error: Option<io::Error>,
+}
+
+impl BlockStorage<File> {
+    /// Open an existing disk image for reading and writing.
+    pub fn open(path: impl AsRef<Path>, sector_size: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Self::new(file, sector_size))
+    }
+}
+
+impl<F: Read + Write + Seek> BlockStorage<F> {
+    /// Create a disk backed by `file`, divided into `sector_size`-byte
+    /// sectors.
+    ///
+    /// # Panics
+    /// Panics if `sector_size` is zero.
+    pub fn new(file: F, sector_size: usize) -> Self {
+        assert!(sector_size > 0, "sector size must be non-zero");
+        Self {
+            file,
+            sector_size,
+            sector: 0,
+            buffer: vec![0; sector_size].into_boxed_slice(),
+            pos: 0,
+            pending: None,
+            status: 0,
+            error: None,
+        }
+    }
+
+    /// Bytes per sector.
+    pub fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    /// The host error behind the last [`DISK_ERROR`], if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// The backing file.
+    pub fn file(&self) -> &F {
+        &self.file
+    }
+
+    /// Consume the device and return the backing file.
+    pub fn into_file(self) -> F {
+        self.file
+    }
+
+    fn sectors(&mut self) -> io::Result<u32> {
+        let len = self.file.seek(SeekFrom::End(0))?;
+        Ok((len / self.sector_size as u64).min(0xFF_FFFF) as u32)
+    }
+
+    fn offset(&self) -> u64 {
+        self.sector as u64 * self.sector_size as u64
+    }
+
+    fn command(&mut self, command: u8) {
+        self.pos = 0;
+        self.pending = None;
+        self.status = 0;
+        let result = match command {
+            DISK_READ => self.read_sector(),
+            DISK_WRITE => {
+                self.pending = Some(DISK_WRITE);
+                Ok(())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown disk command {command:02X}"),
+            )),
+        };
+        match result {
+            Ok(()) => self.status = DISK_DRQ,
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn read_sector(&mut self) -> io::Result<()> {
+        let offset = self.offset();
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut self.buffer)?;
+        self.pending = Some(DISK_READ);
+        Ok(())
+    }
+
+    fn write_sector(&mut self) -> io::Result<()> {
+        let offset = self.offset();
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(&self.buffer)?;
+        self.file.flush()
+    }
+
+    fn fail(&mut self, err: io::Error) {
+        self.pending = None;
+        self.status = DISK_ERROR;
+        self.error = Some(err);
+    }
+
+    /// Advance the data register, finishing the pending command after the
+    /// last byte.
+    fn advance(&mut self) {
+        self.pos += 1;
+        if self.pos < self.sector_size {
+            return;
+        }
+        self.status = 0;
+        if self.pending.take() == Some(DISK_WRITE)
+            && let Err(err) = self.write_sector()
+        {
+            self.fail(err);
+        }
+    }
+}
+
+impl<F: Read + Write + Seek + Send> Mmio for BlockStorage<F> {
+    fn read(&mut self, offset: u16) -> u8 {
+        match offset & 0x07 {
+            0 => self.status,
+            offset @ 1..=3 => (self.sector >> (8 * (3 - offset))) as u8,
+            4 => {
+                if self.pending != Some(DISK_READ) {
+                    return 0xFF;
+                }
+                let val = self.buffer[self.pos];
+                self.advance();
+                val
+            }
+            offset => {
+                let sectors = self.sectors().unwrap_or(0);
+                (sectors >> (8 * (7 - offset))) as u8
+            }
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        match offset & 0x07 {
+            0 => self.command(val),
+            offset @ 1..=3 => {
+                let shift = 8 * (3 - offset);
+                self.sector = (self.sector & !(0xFF << shift)) | (val as u32) << shift;
+            }
+            4 if self.pending == Some(DISK_WRITE) => {
+                self.buffer[self.pos] = val;
+                self.advance();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<F> Clocked for BlockStorage<F> {}
+
+/// Saves the controller registers and sector buffer. The backing file is
+/// not part of the state, and neither is an untaken I/O error.
+impl<F> Snapshot for BlockStorage<F> {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u32(self.sector);
+        out.bytes(&self.buffer);
+        out.u32(self.pos as u32);
+        out.bool(self.pending.is_some());
+        out.u8(self.pending.unwrap_or(0));
+        out.u8(self.status);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.sector = state.u32()?;
+        state.bytes_into(&mut self.buffer)?;
+        self.pos = (state.u32()? as usize).min(self.buffer.len());
+        let pending = state.bool()?;
+        let command = state.u8()?;
+        self.pending = pending.then_some(command);
+        self.status = state.u8()?;
+        Ok(())
+    }
+}
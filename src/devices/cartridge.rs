@@ -0,0 +1,116 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Cartridge ROM pack.
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::Snapshot;
+
+/// ROM pack plugged into a cartridge port.
+///
+/// The ROM is read-only and repeats through whatever window it is mapped
+/// over, as an 8 KB pack does in the 16 KB cartridge space from
+/// [`Self::BASE`]; writes are ignored.
+///
+/// Autostart packs tie the port's CART line to the CPU's Q clock, so it
+/// never stops toggling while the pack is in. With
+/// [`autostart`](Self::autostart) the cartridge models that line as FIRQ
+/// held asserted by [`tick`](Clocked::tick) until
+/// [`Self::set_autostart`] releases it, for machines that wire CART
+/// straight to the CPU. On Dragon and Color Computer machines CART reaches
+/// FIRQ through PIA 1 CB1 instead, which the machine wires up itself (see
+/// `Dragon32::insert_cartridge` with the `dragon32` feature).
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::Cartridge;
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut cartridge = Cartridge::new(&[0x12, 0x34]).autostart(true);
+/// assert_eq!(cartridge.read(0x0003), 0x34); // the image repeats
+/// assert_eq!(cartridge.tick(1), BusSignals::FIRQ);
+/// ```
+pub struct Cartridge {
+    rom: Box<[u8]>,
+    autostart: bool,
+}
+
+impl Cartridge {
+    /// Usual start of cartridge space.
+    pub const BASE: u16 = 0xC000;
+
+    /// Largest image a cartridge holds.
+    pub const MAX_SIZE: usize = 0x4000;
+
+    /// Create a pack holding `rom`, with its CART line unconnected.
+    ///
+    /// # Panics
+    /// Panics if `rom` is empty or larger than [`Self::MAX_SIZE`].
+    pub fn new(rom: &[u8]) -> Self {
+        assert!(
+            !rom.is_empty() && rom.len() <= Self::MAX_SIZE,
+            "cartridge image of {} bytes is not 1-{} bytes",
+            rom.len(),
+            Self::MAX_SIZE
+        );
+        Self {
+            rom: rom.into(),
+            autostart: false,
+        }
+    }
+
+    /// Tie the CART line to the Q clock, or leave it unconnected.
+    pub fn autostart(mut self, autostart: bool) -> Self {
+        self.autostart = autostart;
+        self
+    }
+
+    /// Connect or disconnect the CART line while the pack is plugged in.
+    pub fn set_autostart(&mut self, autostart: bool) {
+        self.autostart = autostart;
+    }
+
+    /// Whether the CART line is driven.
+    pub fn is_autostart(&self) -> bool {
+        self.autostart
+    }
+
+    /// The ROM image.
+    pub fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+}
+
+impl Mmio for Cartridge {
+    fn read(&mut self, offset: u16) -> u8 {
+        self.rom[offset as usize % self.rom.len()]
+    }
+
+    fn write(&mut self, _offset: u16, _val: u8) {}
+}
+
+impl Clocked for Cartridge {
+    fn tick(&mut self, _cycles: u64) -> BusSignals {
+        if self.autostart {
+            BusSignals::FIRQ
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+/// Nothing to save: the ROM and the CART wiring are configuration.
+impl Snapshot for Cartridge {}
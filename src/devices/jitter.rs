@@ -0,0 +1,114 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Seeded timing jitter for devices.
+
+use crate::batch::SeedRng;
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Wrapper that makes a device's timing jitter reproducibly.
+///
+/// Each [`tick`](Clocked::tick) holds back a pseudo-random `0..=max_jitter`
+/// of the elapsed cycles and passes the rest, plus anything held back
+/// earlier, to the inner device. Events therefore land up to `max_jitter`
+/// cycles late but the device never loses time. The jitter derives only from
+/// the seed, so a run replays exactly. Register accesses pass straight
+/// through.
+///
+/// # Example
+/// ```
+/// use mc6809_core::devices::{IntervalTimer, Jittered, TimerMode};
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut timer = IntervalTimer::new(100, TimerMode::OneShot, BusSignals::IRQ);
+/// timer.start();
+/// let mut jittered = Jittered::new(timer, 8, 42);
+/// let mut cycles = 0;
+/// while jittered.tick(1).is_empty() {
+///     cycles += 1;
+/// }
+/// assert!((99..=99 + 8).contains(&cycles));
+/// ```
+pub struct Jittered<D> {
+    inner: D,
+    max_jitter: u64,
+    rng: SeedRng,
+    /// Cycles held back from the inner device so far.
+    owed: u64,
+}
+
+impl<D> Jittered<D> {
+    /// Wrap `inner`, delaying its ticks by up to `max_jitter` cycles drawn
+    /// from `seed`.
+    pub fn new(inner: D, max_jitter: u64, seed: u64) -> Self {
+        Self {
+            inner,
+            max_jitter,
+            rng: SeedRng::new(seed),
+            owed: 0,
+        }
+    }
+
+    /// The wrapped device.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Mutable access to the wrapped device.
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// Unwrap the device. Cycles still held back are dropped.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: Mmio> Mmio for Jittered<D> {
+    fn read(&mut self, offset: u16) -> u8 {
+        self.inner.read(offset)
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        self.inner.write(offset, val);
+    }
+}
+
+impl<D: Clocked> Clocked for Jittered<D> {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        let total = self.owed + cycles;
+        // Never hold back more than the jitter bound in total.
+        let hold = self.rng.up_to(self.max_jitter).min(total);
+        self.owed = hold;
+        self.inner.tick(total - hold)
+    }
+}
+
+impl<D: Snapshot> Snapshot for Jittered<D> {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.inner.snapshot(out);
+        self.rng.snapshot(out);
+        out.u64(self.owed);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.inner.restore(state)?;
+        self.rng.restore(state)?;
+        self.owed = state.u64()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC6821 Peripheral Interface Adapter.
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Control register: C1 interrupt enable.
+pub const PIA_C1_IRQ: u8 = 0x01;
+/// Control register: C1 interrupts on a rising edge instead of a falling one.
+pub const PIA_C1_RISING: u8 = 0x02;
+/// Control register: offset 0/2 addresses the data register rather than the
+/// data direction register.
+pub const PIA_DATA: u8 = 0x04;
+/// Control register: C2 interrupt enable while C2 is an input; while it is
+/// a manual output, the C2 level.
+pub const PIA_C2_IRQ: u8 = 0x08;
+/// Control register: C2 interrupts on a rising edge while it is an input;
+/// while it is an output, manual rather than handshake mode.
+pub const PIA_C2_RISING: u8 = 0x10;
+/// Control register: C2 is an output.
+pub const PIA_C2_OUTPUT: u8 = 0x20;
+
+/// Control register bits selecting the C2 mode.
+const C2_MODE: u8 = PIA_C2_IRQ | PIA_C2_RISING | PIA_C2_OUTPUT;
+/// Control register (read-only): C2 active transition seen.
+pub const PIA_IRQ2: u8 = 0x40;
+/// Control register (read-only): C1 active transition seen.
+pub const PIA_IRQ1: u8 = 0x80;
+
+/// One side (A or B) of the PIA.
+#[derive(Clone)]
+struct Side {
+    output: u8,
+    ddr: u8,
+    control: u8,
+    /// Levels driven onto the port pins from outside.
+    input: u8,
+    c1: bool,
+    /// C2 level while it is an input.
+    c2_in: bool,
+    /// C2 level in the handshake and pulse output modes.
+    c2_strobe: bool,
+}
+
+impl Side {
+    fn new() -> Self {
+        Self {
+            output: 0,
+            ddr: 0,
+            control: 0,
+            input: 0xFF,
+            c1: true,
+            c2_in: true,
+            c2_strobe: true,
+        }
+    }
+
+    /// Port pins: outputs where the DDR bit is set, inputs elsewhere.
+    fn pins(&self) -> u8 {
+        (self.output & self.ddr) | (self.input & !self.ddr)
+    }
+
+    fn read(&mut self, control: bool) -> u8 {
+        if control {
+            return self.control;
+        }
+        if self.control & PIA_DATA == 0 {
+            return self.ddr;
+        }
+        self.control &= !(PIA_IRQ1 | PIA_IRQ2);
+        self.pins()
+    }
+
+    fn write(&mut self, control: bool, val: u8) {
+        if control {
+            self.control = (self.control & (PIA_IRQ1 | PIA_IRQ2)) | (val & 0x3F);
+            if val & PIA_C2_OUTPUT != 0 {
+                self.control &= !PIA_IRQ2;
+            }
+        } else if self.control & PIA_DATA != 0 {
+            self.output = val;
+        } else {
+            self.ddr = val;
+        }
+    }
+
+    fn set_c1(&mut self, level: bool) {
+        let active = level == (self.control & PIA_C1_RISING != 0);
+        if level != self.c1 && active {
+            self.control |= PIA_IRQ1;
+            self.c2_strobe = true;
+        }
+        self.c1 = level;
+    }
+
+    fn set_c2(&mut self, level: bool) {
+        let rising = self.control & PIA_C2_RISING != 0;
+        let input = self.control & PIA_C2_OUTPUT == 0;
+        if input && level != self.c2_in && level == rising {
+            self.control |= PIA_IRQ2;
+        }
+        self.c2_in = level;
+    }
+
+    fn c2(&self) -> bool {
+        match self.control & C2_MODE {
+            C2_MODE => true,
+            mode if mode == PIA_C2_OUTPUT | PIA_C2_RISING => false,
+            mode if mode & PIA_C2_OUTPUT != 0 => self.c2_strobe,
+            _ => self.c2_in,
+        }
+    }
+
+    /// Start a handshake on a data register access.
+    fn strobe(&mut self) {
+        if self.control & C2_MODE == PIA_C2_OUTPUT {
+            self.c2_strobe = false;
+        }
+    }
+
+    fn irq(&self) -> bool {
+        (self.control & (PIA_IRQ1 | PIA_C1_IRQ) == PIA_IRQ1 | PIA_C1_IRQ)
+            || (self.control & (PIA_IRQ2 | PIA_C2_IRQ | PIA_C2_OUTPUT) == PIA_IRQ2 | PIA_C2_IRQ)
+    }
+}
+
+impl Snapshot for Side {
+    fn snapshot(&self, out: &mut StateWriter) {
+        for byte in [self.output, self.ddr, self.control, self.input] {
+            out.u8(byte);
+        }
+        for level in [self.c1, self.c2_in, self.c2_strobe] {
+            out.bool(level);
+        }
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for byte in [
+            &mut self.output,
+            &mut self.ddr,
+            &mut self.control,
+            &mut self.input,
+        ] {
+            *byte = state.u8()?;
+        }
+        for level in [&mut self.c1, &mut self.c2_in, &mut self.c2_strobe] {
+            *level = state.bool()?;
+        }
+        Ok(())
+    }
+}
+
+/// MC6821 PIA: two 8-bit I/O ports, each with two control lines.
+///
+/// Registers, by offset (modulo 4, so a larger mapping mirrors them):
+///
+/// | Offset | Register                                                    |
+/// |--------|-------------------------------------------------------------|
+/// | 0      | port A data, or data direction when [`PIA_DATA`] is clear   |
+/// | 1      | control A                                                   |
+/// | 2      | port B data, or data direction when [`PIA_DATA`] is clear   |
+/// | 3      | control B                                                   |
+///
+/// The host drives the port inputs and the CA1/CB1 and CA2/CB2 lines and
+/// reads back the outputs. A data direction bit of 1 makes the pin an
+/// output; input pins float high until driven. Reading a data register
+/// clears that side's [`PIA_IRQ1`] and [`PIA_IRQ2`] flags. C2 in the
+/// handshake output mode goes low on a data read (A) or write (B) and back
+/// high on the next active C1 transition; the pulse mode's one-cycle low is
+/// not modelled.
+///
+/// [`Clocked::tick`] asserts the line given to [`Self::new`] while either
+/// side's enabled interrupt flag is set.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::Mc6821;
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut pia = Mc6821::new(BusSignals::IRQ);
+/// pia.write(1, 0x05); // CRA: data register, CA1 falling-edge interrupt
+/// pia.set_input_a(0x42);
+/// assert_eq!(pia.read(0), 0x42);
+///
+/// pia.set_ca1(false);
+/// assert_eq!(pia.tick(1), BusSignals::IRQ);
+/// pia.read(0); // acknowledge
+/// assert!(pia.tick(1).is_empty());
+/// ```
+pub struct Mc6821 {
+    a: Side,
+    b: Side,
+    line: BusSignals,
+}
+
+impl Mc6821 {
+    /// Create a PIA in its reset state whose interrupt outputs drive `line`.
+    pub fn new(line: BusSignals) -> Self {
+        Self {
+            a: Side::new(),
+            b: Side::new(),
+            line,
+        }
+    }
+
+    /// Clear every register, as the PIA's reset input does. Input levels are
+    /// kept.
+    pub fn reset(&mut self) {
+        for side in [&mut self.a, &mut self.b] {
+            side.output = 0;
+            side.ddr = 0;
+            side.control = 0;
+            side.c2_strobe = true;
+        }
+    }
+
+    /// Drive the port A input pins.
+    pub fn set_input_a(&mut self, val: u8) {
+        self.a.input = val;
+    }
+
+    /// Drive the port B input pins.
+    pub fn set_input_b(&mut self, val: u8) {
+        self.b.input = val;
+    }
+
+    /// Levels on the port A pins: the output register where the pin is an
+    /// output and the input level elsewhere.
+    pub fn port_a(&self) -> u8 {
+        self.a.pins()
+    }
+
+    /// Levels on the port B pins.
+    pub fn port_b(&self) -> u8 {
+        self.b.pins()
+    }
+
+    /// Port A data direction register.
+    pub fn ddr_a(&self) -> u8 {
+        self.a.ddr
+    }
+
+    /// Port B data direction register.
+    pub fn ddr_b(&self) -> u8 {
+        self.b.ddr
+    }
+
+    /// Drive the CA1 input.
+    pub fn set_ca1(&mut self, level: bool) {
+        self.a.set_c1(level);
+    }
+
+    /// Drive the CB1 input.
+    pub fn set_cb1(&mut self, level: bool) {
+        self.b.set_c1(level);
+    }
+
+    /// Drive CA2 while it is an input.
+    pub fn set_ca2(&mut self, level: bool) {
+        self.a.set_c2(level);
+    }
+
+    /// Drive CB2 while it is an input.
+    pub fn set_cb2(&mut self, level: bool) {
+        self.b.set_c2(level);
+    }
+
+    /// Level on CA2: the programmed output, or the driven input level.
+    pub fn ca2(&self) -> bool {
+        self.a.c2()
+    }
+
+    /// Level on CB2.
+    pub fn cb2(&self) -> bool {
+        self.b.c2()
+    }
+
+    /// Whether the IRQA output is asserted.
+    pub fn irq_a(&self) -> bool {
+        self.a.irq()
+    }
+
+    /// Whether the IRQB output is asserted.
+    pub fn irq_b(&self) -> bool {
+        self.b.irq()
+    }
+}
+
+impl Mmio for Mc6821 {
+    fn read(&mut self, offset: u16) -> u8 {
+        match offset & 3 {
+            0 => {
+                if self.a.control & PIA_DATA != 0 {
+                    self.a.strobe();
+                }
+                self.a.read(false)
+            }
+            1 => self.a.read(true),
+            2 => self.b.read(false),
+            _ => self.b.read(true),
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        match offset & 3 {
+            0 => self.a.write(false, val),
+            1 => self.a.write(true, val),
+            2 => {
+                if self.b.control & PIA_DATA != 0 {
+                    self.b.strobe();
+                }
+                self.b.write(false, val)
+            }
+            _ => self.b.write(true, val),
+        }
+    }
+}
+
+impl Clocked for Mc6821 {
+    fn tick(&mut self, _cycles: u64) -> BusSignals {
+        if self.a.irq() || self.b.irq() {
+            self.line
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+impl Snapshot for Mc6821 {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.a.snapshot(out);
+        self.b.snapshot(out);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.a.restore(state)?;
+        self.b.restore(state)
+    }
+}
@@ -0,0 +1,362 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC6844 direct memory access controller.
+
+use crate::bus::Mmio;
+use crate::memory::Memory;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Channel control: transfer from memory to the peripheral (otherwise
+/// peripheral to memory).
+pub const DMA_READ: u8 = 0x01;
+/// Channel control: burst mode, one request moves the whole block.
+pub const DMA_BURST: u8 = 0x02;
+/// Channel control: halt mode (otherwise three-state control). The CPU
+/// gives up the bus the same way in both, so this bit is only stored.
+pub const DMA_HALT: u8 = 0x04;
+/// Channel control: decrement the address after each byte.
+pub const DMA_DECREMENT: u8 = 0x08;
+/// Channel control (read-only): the channel is enabled with bytes left.
+pub const DMA_BUSY: u8 = 0x40;
+/// Channel control (read-only): the byte count reached zero. Reading the
+/// register clears it.
+pub const DMA_END: u8 = 0x80;
+
+/// Priority control: rotate priority after each transfer (otherwise
+/// channel 0 is highest).
+pub const DMA_ROTATE: u8 = 0x80;
+/// Interrupt control (read-only): an enabled channel has ended.
+pub const DMA_IRQ: u8 = 0x80;
+/// Data chain: reload the chained channel from channel 3 each time it
+/// ends, instead of flagging the end.
+pub const DMA_CHAIN: u8 = 0x01;
+
+/// Number of DMA channels.
+const CHANNELS: usize = 4;
+
+/// Peripheral side of a DMA channel.
+///
+/// The controller asks the port whether it is requesting a transfer and then
+/// moves one byte between it and memory per cycle it is granted the bus.
+pub trait DmaPort: Send {
+    /// The port's transfer request line.
+    fn requesting(&mut self) -> bool;
+
+    /// Supply the next byte of a peripheral-to-memory transfer.
+    fn read(&mut self) -> u8;
+
+    /// Accept the next byte of a memory-to-peripheral transfer.
+    fn write(&mut self, val: u8);
+}
+
+#[derive(Default)]
+struct Channel {
+    addr: u16,
+    count: u16,
+    control: u8,
+    end: bool,
+    /// A burst transfer is in progress.
+    bursting: bool,
+    port: Option<Box<dyn DmaPort>>,
+}
+
+/// MC6844 DMAC: four DMA channels sharing the CPU's DMA/BREQ line.
+///
+/// Registers, by offset:
+///
+/// | Offset          | Register                                           |
+/// |-----------------|----------------------------------------------------|
+/// | `4n`–`4n+1`     | channel `n` address, big-endian                    |
+/// | `4n+2`–`4n+3`   | channel `n` byte count, big-endian                 |
+/// | `0x10+n`        | channel `n` control ([`DMA_READ`] and friends)     |
+/// | `0x14`          | priority: request enables in bits 0–3, [`DMA_ROTATE`] |
+/// | `0x15`          | interrupt: end enables in bits 0–3, [`DMA_IRQ`]    |
+/// | `0x16`          | data chain: [`DMA_CHAIN`], chained channel in bits 1–2 |
+///
+/// [`Clocked::tick`] asserts [`BusSignals::DMA_BREQ`] while a channel is
+/// ready to transfer and [`BusSignals::IRQ`] while an enabled channel has
+/// ended. Wire the controller into the host loop so that each cycle the CPU
+/// reports as [`bus_granted`](crate::Cpu::bus_granted) is spent in
+/// [`Self::service`]. Transfers happen in cycle-steal fashion in every mode:
+/// burst mode differs only in needing a single request per block.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::CompositeBus;
+/// use mc6809_core::devices::{DMA_READ, DmaPort, Mc6844};
+/// use mc6809_core::{BusSignals, Clocked, FlatRam, Memory};
+///
+/// /// Collects every byte it is sent.
+/// struct Sink(Vec<u8>);
+///
+/// impl DmaPort for Sink {
+///     fn requesting(&mut self) -> bool { true }
+///     fn read(&mut self) -> u8 { 0 }
+///     fn write(&mut self, val: u8) { self.0.push(val) }
+/// }
+///
+/// let mut dmac = Mc6844::new();
+/// dmac.attach(0, Sink(Vec::new()));
+/// let mut ram = FlatRam::new();
+/// ram.load(b"hi", 0x2000);
+///
+/// let mut bus = CompositeBus::new(ram);
+/// let id = bus.add_device(dmac);
+/// bus.map(id, 0xE000, 0x20, 0);
+/// for (offset, val) in [(0, 0x20), (1, 0x00), (2, 0), (3, 2), (0x10, DMA_READ), (0x14, 1)] {
+///     bus.write(0xE000 + offset, val);
+/// }
+///
+/// assert!(bus.tick(0).contains(BusSignals::DMA_BREQ));
+/// bus.master(id, |dmac: &mut Mc6844, bus| dmac.service(bus, 2));
+/// assert!(bus.tick(0).is_empty());
+/// ```
+pub struct Mc6844 {
+    channels: [Channel; CHANNELS],
+    priority: u8,
+    interrupt: u8,
+    chain: u8,
+    /// Channel checked first under rotating priority.
+    next: usize,
+}
+
+impl Mc6844 {
+    /// Create a controller with every register clear and no ports attached.
+    pub fn new() -> Self {
+        Self {
+            channels: Default::default(),
+            priority: 0,
+            interrupt: 0,
+            chain: 0,
+            next: 0,
+        }
+    }
+
+    /// Connect the peripheral side of `channel`.
+    ///
+    /// # Panics
+    /// Panics if `channel` is not 0–3.
+    pub fn attach(&mut self, channel: usize, port: impl DmaPort + 'static) {
+        self.channels[channel].port = Some(Box::new(port));
+    }
+
+    /// Disconnect and return the peripheral side of `channel`.
+    ///
+    /// # Panics
+    /// Panics if `channel` is not 0–3.
+    pub fn detach(&mut self, channel: usize) -> Option<Box<dyn DmaPort>> {
+        self.channels[channel].port.take()
+    }
+
+    /// Whether a channel is ready to transfer a byte.
+    pub fn requesting(&mut self) -> bool {
+        (0..CHANNELS).any(|n| self.ready(n))
+    }
+
+    /// Whether an enabled channel has ended and the IRQ output is asserted.
+    pub fn irq(&self) -> bool {
+        self.channels
+            .iter()
+            .enumerate()
+            .any(|(n, channel)| channel.end && self.interrupt & (1 << n) != 0)
+    }
+
+    /// Spend up to `cycles` bus cycles granted by the CPU, moving one byte per
+    /// cycle. Returns the number of cycles used, which is less than `cycles`
+    /// once no channel is ready.
+    pub fn service(&mut self, mem: &mut impl Memory, cycles: u64) -> u64 {
+        let mut used = 0;
+        while used < cycles {
+            let Some(n) = self.pick() else {
+                break;
+            };
+            self.transfer(mem, n);
+            used += 1;
+        }
+        used
+    }
+
+    fn ready(&mut self, n: usize) -> bool {
+        let enabled = self.priority & (1 << n) != 0;
+        let channel = &mut self.channels[n];
+        if !enabled || channel.count == 0 {
+            return false;
+        }
+        channel.bursting || channel.port.as_mut().is_some_and(|port| port.requesting())
+    }
+
+    /// The channel to serve next under the current priority mode.
+    fn pick(&mut self) -> Option<usize> {
+        let first = if self.priority & DMA_ROTATE != 0 {
+            self.next
+        } else {
+            0
+        };
+        (0..CHANNELS)
+            .map(|i| (first + i) % CHANNELS)
+            .find(|&n| self.ready(n))
+    }
+
+    fn transfer(&mut self, mem: &mut impl Memory, n: usize) {
+        let channel = &mut self.channels[n];
+        if let Some(port) = &mut channel.port {
+            if channel.control & DMA_READ != 0 {
+                port.write(mem.read(channel.addr));
+            } else {
+                mem.write(channel.addr, port.read());
+            }
+        }
+        channel.addr = if channel.control & DMA_DECREMENT != 0 {
+            channel.addr.wrapping_sub(1)
+        } else {
+            channel.addr.wrapping_add(1)
+        };
+        channel.count -= 1;
+        channel.bursting = channel.control & DMA_BURST != 0 && channel.count > 0;
+        if channel.count == 0 {
+            self.finish(n);
+        }
+        self.next = (n + 1) % CHANNELS;
+    }
+
+    /// Handle channel `n` reaching the end of its block.
+    fn finish(&mut self, n: usize) {
+        let chained = (self.chain >> 1 & 0x03) as usize;
+        if self.chain & DMA_CHAIN != 0 && n == chained && n != CHANNELS - 1 {
+            let (addr, count) = (self.channels[3].addr, self.channels[3].count);
+            self.channels[n].addr = addr;
+            self.channels[n].count = count;
+        } else {
+            self.channels[n].end = true;
+        }
+    }
+}
+
+impl Default for Mc6844 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mmio for Mc6844 {
+    fn read(&mut self, offset: u16) -> u8 {
+        match offset & 0x1F {
+            offset @ 0x00..=0x0F => {
+                let channel = &self.channels[offset as usize / 4];
+                let [hi, lo] = match offset & 0x03 {
+                    0 | 1 => channel.addr,
+                    _ => channel.count,
+                }
+                .to_be_bytes();
+                if offset & 0x01 == 0 { hi } else { lo }
+            }
+            offset @ 0x10..=0x13 => {
+                let n = offset as usize - 0x10;
+                let busy = self.priority & (1 << n) != 0 && self.channels[n].count > 0;
+                let channel = &mut self.channels[n];
+                let mut val = channel.control;
+                if busy {
+                    val |= DMA_BUSY;
+                }
+                if channel.end {
+                    val |= DMA_END;
+                    channel.end = false;
+                }
+                val
+            }
+            0x14 => self.priority,
+            0x15 => {
+                if self.irq() {
+                    self.interrupt | DMA_IRQ
+                } else {
+                    self.interrupt
+                }
+            }
+            0x16 => self.chain,
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        match offset & 0x1F {
+            offset @ 0x00..=0x0F => {
+                let channel = &mut self.channels[offset as usize / 4];
+                let reg = match offset & 0x03 {
+                    0 | 1 => &mut channel.addr,
+                    _ => &mut channel.count,
+                };
+                *reg = if offset & 0x01 == 0 {
+                    (*reg & 0x00FF) | (val as u16) << 8
+                } else {
+                    (*reg & 0xFF00) | val as u16
+                };
+            }
+            offset @ 0x10..=0x13 => {
+                self.channels[offset as usize - 0x10].control = val & !(DMA_BUSY | DMA_END);
+            }
+            0x14 => self.priority = val & (DMA_ROTATE | 0x0F),
+            0x15 => self.interrupt = val & 0x0F,
+            0x16 => self.chain = val & 0x07,
+            _ => {}
+        }
+    }
+}
+
+impl Clocked for Mc6844 {
+    fn tick(&mut self, _cycles: u64) -> BusSignals {
+        let mut signals = BusSignals::default();
+        if self.requesting() {
+            signals |= BusSignals::DMA_BREQ;
+        }
+        if self.irq() {
+            signals |= BusSignals::IRQ;
+        }
+        signals
+    }
+}
+
+/// Saves the registers and transfer progress of every channel. Attached
+/// ports are not saved and stay attached.
+impl Snapshot for Mc6844 {
+    fn snapshot(&self, out: &mut StateWriter) {
+        for channel in &self.channels {
+            out.u16(channel.addr);
+            out.u16(channel.count);
+            out.u8(channel.control);
+            out.bool(channel.end);
+            out.bool(channel.bursting);
+        }
+        out.u8(self.priority);
+        out.u8(self.interrupt);
+        out.u8(self.chain);
+        out.u8(self.next as u8);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for channel in &mut self.channels {
+            channel.addr = state.u16()?;
+            channel.count = state.u16()?;
+            channel.control = state.u8()?;
+            channel.end = state.bool()?;
+            channel.bursting = state.bool()?;
+        }
+        self.priority = state.u8()?;
+        self.interrupt = state.u8()?;
+        self.chain = state.u8()?;
+        self.next = state.u8()? as usize % CHANNELS;
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC6850 Asynchronous Communications Interface Adapter.
+
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Control register: both counter divide bits set, a master reset.
+pub const ACIA_MASTER_RESET: u8 = 0x03;
+/// Control register bits selecting the transmitter control mode.
+const TX_CONTROL: u8 = 0x60;
+/// Control register: transmitter interrupt enabled (`RTS` low).
+pub const ACIA_TX_IRQ: u8 = 0x20;
+/// Control register: receiver interrupt enable.
+pub const ACIA_RX_IRQ: u8 = 0x80;
+
+/// Status register: a received byte is waiting in the data register.
+pub const ACIA_RDRF: u8 = 0x01;
+/// Status register: the transmit data register can take a byte.
+pub const ACIA_TDRE: u8 = 0x02;
+/// Status register: the ACIA is requesting an interrupt.
+pub const ACIA_IRQ: u8 = 0x80;
+
+/// MC6850 ACIA: a serial port the host feeds and drains a byte at a time.
+///
+/// Registers, by offset (modulo 2, so a larger mapping mirrors them):
+///
+/// | Offset | Read                        | Write             |
+/// |--------|-----------------------------|-------------------|
+/// | 0      | status                      | control           |
+/// | 1      | receive data                | transmit data     |
+///
+/// Bytes the host passes to [`Self::receive`] are queued and presented one
+/// at a time: each is loaded into the receive data register on the
+/// [`Clocked::tick`] after the previous one was read, so nothing is lost
+/// to overrun. Transmitted bytes are sent at once and collected until
+/// [`Self::take_output`]. Baud rate, framing, parity and the modem lines
+/// are not modelled: word select and divide settings are stored but have
+/// no effect, and `DCD` and `CTS` always read as asserted.
+///
+/// A new ACIA is ready to use. Writing [`ACIA_MASTER_RESET`] to the control
+/// register holds it in reset, with the status register clear, until the
+/// next control write; the received byte is discarded but queued input is
+/// kept.
+///
+/// [`Clocked::tick`] asserts the line given to [`Self::new`] while a byte
+/// is waiting with [`ACIA_RX_IRQ`] set, or while the transmitter is ready
+/// with the [`ACIA_TX_IRQ`] mode selected.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::{ACIA_RDRF, ACIA_RX_IRQ, Mc6850};
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut acia = Mc6850::new(BusSignals::IRQ);
+/// acia.write(0, ACIA_RX_IRQ | 0x15); // 8N1, divide by 16
+/// acia.receive(b"OK");
+/// assert_eq!(acia.tick(1), BusSignals::IRQ);
+/// assert_eq!(acia.read(0) & ACIA_RDRF, ACIA_RDRF);
+/// assert_eq!(acia.read(1), b'O');
+///
+/// acia.write(1, b'?');
+/// assert_eq!(acia.take_output(), b"?");
+/// ```
+pub struct Mc6850 {
+    control: u8,
+    data: u8,
+    full: bool,
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+    line: BusSignals,
+}
+
+impl Mc6850 {
+    /// Create a ready ACIA whose interrupt output drives `line`.
+    pub fn new(line: BusSignals) -> Self {
+        Self {
+            control: 0,
+            data: 0,
+            full: false,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            line,
+        }
+    }
+
+    /// Queue bytes arriving on the serial input.
+    pub fn receive(&mut self, data: &[u8]) {
+        self.input.extend(data);
+    }
+
+    /// Bytes queued by [`Self::receive`] and not yet read by the CPU,
+    /// including one waiting in the receive data register.
+    pub fn pending_input(&self) -> usize {
+        self.input.len() + self.full as usize
+    }
+
+    /// Take the bytes transmitted since the last call.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        mem::take(&mut self.output)
+    }
+
+    /// Whether a master reset is holding the ACIA.
+    pub fn in_reset(&self) -> bool {
+        self.control & ACIA_MASTER_RESET == ACIA_MASTER_RESET
+    }
+
+    fn status(&self) -> u8 {
+        if self.in_reset() {
+            return 0;
+        }
+        let mut status = ACIA_TDRE;
+        if self.full {
+            status |= ACIA_RDRF;
+        }
+        if self.irq() {
+            status |= ACIA_IRQ;
+        }
+        status
+    }
+
+    fn irq(&self) -> bool {
+        !self.in_reset()
+            && ((self.control & ACIA_RX_IRQ != 0 && self.full)
+                || self.control & TX_CONTROL == ACIA_TX_IRQ)
+    }
+}
+
+impl Mmio for Mc6850 {
+    fn read(&mut self, offset: u16) -> u8 {
+        if offset & 1 == 0 {
+            self.status()
+        } else {
+            self.full = false;
+            self.data
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        if offset & 1 == 0 {
+            self.control = val;
+            if self.in_reset() {
+                self.full = false;
+            }
+        } else if !self.in_reset() {
+            self.output.push(val);
+        }
+    }
+}
+
+impl Clocked for Mc6850 {
+    fn tick(&mut self, _cycles: u64) -> BusSignals {
+        if !self.full
+            && !self.in_reset()
+            && let Some(byte) = self.input.pop_front()
+        {
+            self.data = byte;
+            self.full = true;
+        }
+        if self.irq() {
+            self.line
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+impl Snapshot for Mc6850 {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u8(self.control);
+        out.u8(self.data);
+        out.bool(self.full);
+        out.u32(self.input.len() as u32);
+        let (front, back) = self.input.as_slices();
+        out.bytes(front);
+        out.bytes(back);
+        out.u32(self.output.len() as u32);
+        out.bytes(&self.output);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.control = state.u8()?;
+        self.data = state.u8()?;
+        self.full = state.bool()?;
+        let len = state.u32()? as usize;
+        self.input = state.bytes(len)?.iter().copied().collect();
+        let len = state.u32()? as usize;
+        self.output = state.bytes(len)?.to_vec();
+        Ok(())
+    }
+}
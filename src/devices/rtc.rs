@@ -0,0 +1,336 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC146818-style real-time clock.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Register B: stop updates so the time can be set.
+pub const RTC_SET: u8 = 0x80;
+/// Register B: periodic interrupt enable.
+pub const RTC_PIE: u8 = 0x40;
+/// Register B: binary time registers (otherwise BCD).
+pub const RTC_BINARY: u8 = 0x04;
+/// Register B: 24-hour mode (otherwise 12-hour with bit 7 of the hours
+/// register marking PM).
+pub const RTC_24H: u8 = 0x02;
+/// Register C: an enabled interrupt is pending.
+pub const RTC_IRQF: u8 = 0x80;
+/// Register C: a periodic interval has elapsed.
+pub const RTC_PF: u8 = 0x40;
+
+const REG_SECONDS: u16 = 0x00;
+const REG_MINUTES: u16 = 0x02;
+const REG_HOURS: u16 = 0x04;
+const REG_WEEKDAY: u16 = 0x06;
+const REG_DAY: u16 = 0x07;
+const REG_MONTH: u16 = 0x08;
+const REG_YEAR: u16 = 0x09;
+const REG_A: u16 = 0x0A;
+const REG_B: u16 = 0x0B;
+const REG_C: u16 = 0x0C;
+const REG_D: u16 = 0x0D;
+
+/// Register D: the backup battery is good.
+const RTC_VRT: u8 = 0x80;
+
+/// Calendar time broken into the fields the clock registers show.
+#[derive(Clone, Copy)]
+struct DateTime {
+    year: i64,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DateTime {
+    fn from_unix(secs: i64) -> Self {
+        // Days to civil date, after Howard Hinnant's `civil_from_days`.
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = yoe + era * 400 + (month <= 2) as i64;
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+
+    fn to_unix(self) -> i64 {
+        let year = self.year - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = self.month as i64;
+        let mp = if month > 2 { month - 3 } else { month + 9 };
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        days * 86_400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    /// Day of the week, 1 for Sunday.
+    fn weekday(self) -> u8 {
+        ((self.to_unix().div_euclid(86_400) + 4).rem_euclid(7) + 1) as u8
+    }
+}
+
+/// Real-time clock modelled on the MC146818: host wall-clock time in the
+/// usual register layout, plus the periodic interrupt.
+///
+/// Registers are mapped directly by offset rather than through the chip's
+/// address/data port pair:
+///
+/// | Offset      | Register                                              |
+/// |-------------|-------------------------------------------------------|
+/// | `00`–`09`   | seconds, alarm, minutes, alarm, hours, alarm, day of week, day, month, year |
+/// | `0A`        | A: periodic rate select in bits 0–3                   |
+/// | `0B`        | B: [`RTC_SET`], [`RTC_PIE`], [`RTC_BINARY`], [`RTC_24H`] |
+/// | `0C`        | C: [`RTC_IRQF`], [`RTC_PF`]; cleared by reading       |
+/// | `0D`        | D: battery good (always set)                          |
+/// | `0E`–`3F`   | 50 bytes of user RAM                                  |
+///
+/// Writing the time stores the difference from the host clock, so the
+/// emulated clock keeps running from the new time. Alarms are stored but
+/// never fire and the update-ended interrupt is not modelled. The periodic
+/// interrupt counts CPU cycles, converted with the CPU clock rate given to
+/// [`Self::new`], and asserts IRQ until register C is read.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::{Mc146818, RTC_24H, RTC_BINARY};
+///
+/// // A clock fixed at 2024-02-29 13:45:30 UTC.
+/// let mut rtc = Mc146818::with_clock(1_000_000, || 1_709_214_330);
+/// rtc.write(0x0B, RTC_24H | RTC_BINARY);
+/// assert_eq!([rtc.read(0x04), rtc.read(0x02), rtc.read(0x00)], [13, 45, 30]);
+/// assert_eq!([rtc.read(0x09), rtc.read(0x08), rtc.read(0x07)], [24, 2, 29]);
+/// ```
+pub struct Mc146818 {
+    clock: Box<dyn FnMut() -> i64 + Send>,
+    /// Seconds added to the host clock.
+    offset: i64,
+    /// Time latched while [`RTC_SET`] is set.
+    frozen: Option<DateTime>,
+    regs: [u8; 64],
+    cpu_hz: u64,
+    elapsed: u64,
+}
+
+impl Mc146818 {
+    /// Create a clock showing host time, for a CPU running at `cpu_hz`.
+    pub fn new(cpu_hz: u64) -> Self {
+        Self::with_clock(cpu_hz, || {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs() as i64)
+        })
+    }
+
+    /// Create a clock reading seconds since the Unix epoch from `clock`
+    /// instead of the host, for tests and deterministic runs.
+    pub fn with_clock(cpu_hz: u64, clock: impl FnMut() -> i64 + Send + 'static) -> Self {
+        let mut regs = [0; 64];
+        regs[REG_B as usize] = RTC_24H;
+        regs[REG_D as usize] = RTC_VRT;
+        Self {
+            clock: Box::new(clock),
+            offset: 0,
+            frozen: None,
+            regs,
+            cpu_hz,
+            elapsed: 0,
+        }
+    }
+
+    /// Whether the IRQ output is asserted.
+    pub fn irq(&self) -> bool {
+        self.regs[REG_C as usize] & RTC_IRQF != 0
+    }
+
+    fn now(&mut self) -> DateTime {
+        match self.frozen {
+            Some(time) => time,
+            None => DateTime::from_unix((self.clock)() + self.offset),
+        }
+    }
+
+    fn set_time(&mut self, time: DateTime) {
+        match &mut self.frozen {
+            Some(frozen) => *frozen = time,
+            None => self.offset = time.to_unix() - (self.clock)(),
+        }
+    }
+
+    /// Cycles between periodic interrupts, or `None` if the rate select
+    /// bits are zero.
+    fn period(&self) -> Option<u64> {
+        let rate = (self.regs[REG_A as usize] & 0x0F) as u32;
+        // Rates 1 and 2 repeat rates 8 and 9 with a 32.768 kHz time base.
+        let rate = match rate {
+            0 => return None,
+            1 | 2 => rate + 7,
+            _ => rate,
+        };
+        Some(((self.cpu_hz << (rate - 1)) >> 15).max(1))
+    }
+
+    fn encode(&self, val: u8) -> u8 {
+        if self.regs[REG_B as usize] & RTC_BINARY != 0 {
+            val
+        } else {
+            ((val / 10) << 4) | (val % 10)
+        }
+    }
+
+    fn decode(&self, val: u8) -> u8 {
+        if self.regs[REG_B as usize] & RTC_BINARY != 0 {
+            val
+        } else {
+            (val >> 4) * 10 + (val & 0x0F)
+        }
+    }
+
+    fn encode_hour(&self, hour: u8) -> u8 {
+        if self.regs[REG_B as usize] & RTC_24H != 0 {
+            return self.encode(hour);
+        }
+        let pm = if hour >= 12 { 0x80 } else { 0 };
+        let hour = match hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        self.encode(hour) | pm
+    }
+
+    fn decode_hour(&self, val: u8) -> u8 {
+        if self.regs[REG_B as usize] & RTC_24H != 0 {
+            return self.decode(val);
+        }
+        let hour = self.decode(val & 0x7F) % 12;
+        if val & 0x80 != 0 { hour + 12 } else { hour }
+    }
+}
+
+impl Mmio for Mc146818 {
+    fn read(&mut self, offset: u16) -> u8 {
+        let offset = offset & 0x3F;
+        let time = self.now();
+        match offset {
+            REG_SECONDS => self.encode(time.second),
+            REG_MINUTES => self.encode(time.minute),
+            REG_HOURS => self.encode_hour(time.hour),
+            REG_WEEKDAY => self.encode(time.weekday()),
+            REG_DAY => self.encode(time.day),
+            REG_MONTH => self.encode(time.month),
+            REG_YEAR => self.encode(time.year.rem_euclid(100) as u8),
+            REG_C => std::mem::take(&mut self.regs[REG_C as usize]),
+            _ => self.regs[offset as usize],
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        let offset = offset & 0x3F;
+        let mut time = self.now();
+        match offset {
+            REG_SECONDS => time.second = self.decode(val),
+            REG_MINUTES => time.minute = self.decode(val),
+            REG_HOURS => time.hour = self.decode_hour(val),
+            REG_DAY => time.day = self.decode(val),
+            REG_MONTH => time.month = self.decode(val),
+            REG_YEAR => {
+                let year = self.decode(val) as i64;
+                time.year = if year < 70 { 2000 + year } else { 1900 + year };
+            }
+            REG_B => {
+                match (self.frozen, val & RTC_SET != 0) {
+                    (None, true) => self.frozen = Some(time),
+                    (Some(frozen), false) => {
+                        self.frozen = None;
+                        self.set_time(frozen);
+                    }
+                    _ => {}
+                }
+                self.regs[REG_B as usize] = val;
+                return;
+            }
+            REG_WEEKDAY | REG_C | REG_D => return,
+            _ => {
+                self.regs[offset as usize] = val;
+                return;
+            }
+        }
+        self.set_time(time);
+    }
+}
+
+impl Clocked for Mc146818 {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        if let Some(period) = self.period() {
+            self.elapsed += cycles;
+            if self.elapsed >= period {
+                self.elapsed %= period;
+                self.regs[REG_C as usize] |= RTC_PF;
+                if self.regs[REG_B as usize] & RTC_PIE != 0 {
+                    self.regs[REG_C as usize] |= RTC_IRQF;
+                }
+            }
+        }
+        if self.irq() {
+            BusSignals::IRQ
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+/// Saves the registers and the offset from the host clock, so a restored
+/// clock keeps following the host clock rather than resuming at the saved
+/// time.
+impl Snapshot for Mc146818 {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.i64(self.offset);
+        out.bool(self.frozen.is_some());
+        out.i64(self.frozen.map_or(0, DateTime::to_unix));
+        out.bytes(&self.regs);
+        out.u64(self.elapsed);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.offset = state.i64()?;
+        let frozen = state.bool()?;
+        let time = state.i64()?;
+        self.frozen = frozen.then(|| DateTime::from_unix(time));
+        state.bytes_into(&mut self.regs)?;
+        self.elapsed = state.u64()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC6883 / SN74LS783 Synchronous Address Multiplexer.
+
+use crate::memory::Memory;
+use crate::peripheral::Clocked;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// First SAM control address; each control bit has a clear address followed
+/// by a set address.
+const CONTROL_BASE: u16 = 0xFFC0;
+
+/// Last SAM control address.
+const CONTROL_END: u16 = 0xFFDF;
+
+/// Start of the I/O area, left for devices mapped over the SAM.
+const IO_BASE: u16 = 0xFF00;
+
+/// Start of the vector area, which the SAM redirects 16 KB down.
+const VECTOR_BASE: u16 = 0xFFE0;
+
+/// Distance between the vector area and the ROM holding the vectors.
+const VECTOR_REDIRECT: u16 = 0x4000;
+
+/// Start of ROM space in map type 0.
+const ROM_BASE: u16 = 0x8000;
+
+const BIT_P1: u16 = 10;
+const BIT_TY: u16 = 15;
+
+/// RAM size selected by the M1/M0 control bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemorySize {
+    /// 4 KB dynamic RAM (4K × 1 chips).
+    Size4K,
+    /// 16 KB dynamic RAM (16K × 1 chips).
+    Size16K,
+    /// 64 KB dynamic RAM, or static RAM (both M bits set).
+    Size64K,
+}
+
+/// Memory map selected by the TY control bit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapType {
+    /// 32 KB of RAM at 0x0000 and ROM from 0x8000.
+    RomRam,
+    /// RAM everywhere below the I/O area.
+    AllRam,
+}
+
+/// MC6883 SAM: RAM and ROM decoding for Color Computer and Dragon machines.
+///
+/// The SAM holds a 16-bit control register written one bit at a time: a
+/// write of any value to an even address in `0xFFC0..=0xFFDF` clears a bit
+/// and a write to the following odd address sets it. From the lowest bit:
+///
+/// | Bits    | Addresses       | Meaning                               |
+/// |---------|-----------------|---------------------------------------|
+/// | V0–V2   | `FFC0`–`FFC5`   | VDG addressing mode                   |
+/// | F0–F6   | `FFC6`–`FFD3`   | display offset in 512-byte steps      |
+/// | P1      | `FFD4`–`FFD5`   | RAM page for `0x0000..0x8000`         |
+/// | R0–R1   | `FFD6`–`FFD9`   | MPU rate                              |
+/// | M0–M1   | `FFDA`–`FFDD`   | RAM size ([`MemorySize`])             |
+/// | TY      | `FFDE`–`FFDF`   | map type ([`MapType`])                |
+///
+/// Reads of `0xFFE0..=0xFFFF` are redirected to `0xBFE0..=0xBFFF`, so the
+/// CPU vectors come from the top of the BASIC ROM. The I/O area
+/// `0xFF00..0xFFC0` reads as `0xFF` and ignores writes: map PIAs and other
+/// peripherals over it by using the SAM as the base of a
+/// [`CompositeBus`](crate::bus::CompositeBus).
+///
+/// The SAM's video address counter and DRAM refresh are not modelled; a
+/// video frontend reads [`Self::display_offset`] and [`Self::ram`] instead.
+///
+/// # Example
+/// ```
+/// use mc6809_core::Memory;
+/// use mc6809_core::devices::{MapType, Sam};
+///
+/// let mut rom = vec![0u8; 0x4000];
+/// rom[0x3FFE..].copy_from_slice(&[0xB3, 0xB4]); // reset vector
+/// let mut sam = Sam::new().rom(0x8000, &rom);
+///
+/// assert_eq!(sam.read_word(0xFFFE), 0xB3B4);
+/// sam.write(0xFFDF, 0); // set TY: all RAM
+/// assert_eq!(sam.map_type(), MapType::AllRam);
+/// ```
+pub struct Sam {
+    control: u16,
+    ram: Box<[u8]>,
+    /// ROM space `0x8000..0xFF00`; bytes without a loaded image read `0xFF`.
+    rom: Box<[u8]>,
+}
+
+impl Sam {
+    /// Create a SAM with 64 KB of zeroed RAM, empty ROM space and all control
+    /// bits clear.
+    pub fn new() -> Self {
+        Self {
+            control: 0,
+            ram: vec![0; 0x10000].into_boxed_slice(),
+            rom: vec![0xFF; (IO_BASE - ROM_BASE) as usize].into_boxed_slice(),
+        }
+    }
+
+    /// Load a ROM image at `start` in ROM space (`0x8000..0xFF00`).
+    ///
+    /// # Panics
+    /// Panics if the image does not fit in ROM space.
+    pub fn rom(mut self, start: u16, data: &[u8]) -> Self {
+        assert!(
+            start >= ROM_BASE && start as usize + data.len() <= IO_BASE as usize,
+            "ROM at {start:04X} does not fit in 8000-FEFF"
+        );
+        let offset = (start - ROM_BASE) as usize;
+        self.rom[offset..offset + data.len()].copy_from_slice(data);
+        self
+    }
+
+    /// Clear every control bit, as the SAM's reset input does.
+    pub fn reset(&mut self) {
+        self.control = 0;
+    }
+
+    /// The raw 16-bit control register, V0 in bit 0 through TY in bit 15.
+    pub fn control(&self) -> u16 {
+        self.control
+    }
+
+    /// Overwrite the whole control register.
+    pub fn set_control(&mut self, control: u16) {
+        self.control = control;
+    }
+
+    /// VDG addressing mode (V2–V0).
+    pub fn vdg_mode(&self) -> u8 {
+        (self.control & 0x07) as u8
+    }
+
+    /// Start of the display in RAM (F6–F0 × 512).
+    pub fn display_offset(&self) -> u16 {
+        ((self.control >> 3) & 0x7F) << 9
+    }
+
+    /// Whether page 1 (the upper 32 KB of RAM) is mapped at `0x0000` in
+    /// [`MapType::RomRam`].
+    pub fn page1(&self) -> bool {
+        self.bit(BIT_P1)
+    }
+
+    /// MPU rate (R1–R0): 0 is 0.89 MHz, 1 is 0.89 MHz with 1.78 MHz ROM
+    /// accesses, 2 and 3 are 1.78 MHz.
+    pub fn mpu_rate(&self) -> u8 {
+        ((self.control >> 11) & 0x03) as u8
+    }
+
+    /// RAM size (M1–M0).
+    pub fn memory_size(&self) -> MemorySize {
+        match (self.control >> 13) & 0x03 {
+            0 => MemorySize::Size4K,
+            1 => MemorySize::Size16K,
+            _ => MemorySize::Size64K,
+        }
+    }
+
+    /// Map type (TY).
+    pub fn map_type(&self) -> MapType {
+        if self.bit(BIT_TY) {
+            MapType::AllRam
+        } else {
+            MapType::RomRam
+        }
+    }
+
+    /// The full 64 KB of RAM, as the VDG sees it.
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Mutable access to the full 64 KB of RAM.
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn bit(&self, bit: u16) -> bool {
+        self.control & (1 << bit) != 0
+    }
+
+    /// RAM index for a CPU address below the I/O area, or `None` if it
+    /// decodes to ROM.
+    fn ram_index(&self, addr: u16) -> Option<usize> {
+        let addr = match self.map_type() {
+            MapType::AllRam => addr,
+            MapType::RomRam if addr >= ROM_BASE => return None,
+            MapType::RomRam if self.page1() => addr | 0x8000,
+            MapType::RomRam => addr,
+        };
+        let mask = match self.memory_size() {
+            MemorySize::Size4K => 0x0FFF,
+            MemorySize::Size16K => 0x3FFF,
+            MemorySize::Size64K => 0xFFFF,
+        };
+        Some((addr & mask) as usize)
+    }
+}
+
+impl Clocked for Sam {}
+
+impl Default for Sam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saves the control register and RAM; ROM images are configuration.
+impl Snapshot for Sam {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u16(self.control);
+        out.bytes(&self.ram);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.control = state.u16()?;
+        state.bytes_into(&mut self.ram)
+    }
+}
+
+impl Memory for Sam {
+    fn read(&mut self, addr: u16) -> u8 {
+        let addr = if addr >= VECTOR_BASE {
+            addr - VECTOR_REDIRECT
+        } else {
+            addr
+        };
+        if addr >= IO_BASE {
+            return 0xFF;
+        }
+        match self.ram_index(addr) {
+            Some(index) => self.ram[index],
+            None => self.rom[(addr - ROM_BASE) as usize],
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        if (CONTROL_BASE..=CONTROL_END).contains(&addr) {
+            let offset = addr - CONTROL_BASE;
+            let bit = 1 << (offset / 2);
+            if offset & 1 == 0 {
+                self.control &= !bit;
+            } else {
+                self.control |= bit;
+            }
+            return;
+        }
+        let addr = if addr >= VECTOR_BASE {
+            addr - VECTOR_REDIRECT
+        } else {
+            addr
+        };
+        if addr >= IO_BASE {
+            return;
+        }
+        if let Some(index) = self.ram_index(addr) {
+            self.ram[index] = val;
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Generic cycle-counting interval timer.
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Control register bit: the timer is counting.
+pub const TIMER_ENABLE: u8 = 0x01;
+/// Control register bit: reload and keep counting after each expiry.
+pub const TIMER_PERIODIC: u8 = 0x02;
+/// Status register bit: the timer has expired and is asserting its line.
+pub const TIMER_PENDING: u8 = 0x80;
+
+/// What an [`IntervalTimer`] does when it expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerMode {
+    /// Expire once, then stop.
+    OneShot,
+    /// Reload the period and keep counting.
+    Periodic,
+}
+
+/// Timer that counts CPU cycles and asserts an interrupt line on expiry.
+///
+/// The line stays asserted until acknowledged, either by the host with
+/// [`Self::acknowledge`] or by the CPU through the registers:
+///
+/// | Offset | Register                                                  |
+/// |--------|-----------------------------------------------------------|
+/// | 0      | control: [`TIMER_ENABLE`], [`TIMER_PERIODIC`]             |
+/// | 1      | status: [`TIMER_PENDING`]; reading or writing acknowledges |
+/// | 2–3    | period in cycles, big-endian; writing reloads the counter |
+///
+/// Periods set through the registers are limited to 16 bits; the host API
+/// takes any `u64`.
+///
+/// # Example
+/// ```
+/// use mc6809_core::devices::{IntervalTimer, TimerMode};
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut timer = IntervalTimer::new(1000, TimerMode::Periodic, BusSignals::FIRQ);
+/// timer.start();
+/// assert!(timer.tick(999).is_empty());
+/// assert_eq!(timer.tick(1), BusSignals::FIRQ);
+/// timer.acknowledge();
+/// assert!(timer.tick(500).is_empty());
+/// ```
+pub struct IntervalTimer {
+    period: u64,
+    remaining: u64,
+    mode: TimerMode,
+    line: BusSignals,
+    running: bool,
+    pending: bool,
+}
+
+impl IntervalTimer {
+    /// Create a stopped timer that asserts `line` (normally
+    /// [`BusSignals::IRQ`] or [`BusSignals::FIRQ`]) every `period` cycles.
+    pub fn new(period: u64, mode: TimerMode, line: BusSignals) -> Self {
+        Self {
+            period,
+            remaining: period,
+            mode,
+            line,
+            running: false,
+            pending: false,
+        }
+    }
+
+    /// Start counting a full period from now.
+    pub fn start(&mut self) {
+        self.remaining = self.period;
+        self.running = true;
+    }
+
+    /// Stop counting. A pending expiry stays pending.
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    /// Whether the timer is counting.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Whether the timer has expired and not been acknowledged.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Release the interrupt line.
+    pub fn acknowledge(&mut self) {
+        self.pending = false;
+    }
+
+    /// Cycles left until the next expiry.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// The reload period in cycles.
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// Change the period and restart the count from it.
+    pub fn set_period(&mut self, period: u64) {
+        self.period = period;
+        self.remaining = period;
+    }
+
+    /// The expiry mode.
+    pub fn mode(&self) -> TimerMode {
+        self.mode
+    }
+
+    /// Change the expiry mode.
+    pub fn set_mode(&mut self, mode: TimerMode) {
+        self.mode = mode;
+    }
+}
+
+impl Mmio for IntervalTimer {
+    fn read(&mut self, offset: u16) -> u8 {
+        match offset & 0x03 {
+            0 => {
+                let mut control = 0;
+                if self.running {
+                    control |= TIMER_ENABLE;
+                }
+                if self.mode == TimerMode::Periodic {
+                    control |= TIMER_PERIODIC;
+                }
+                control
+            }
+            1 => {
+                let status = if self.pending { TIMER_PENDING } else { 0 };
+                self.pending = false;
+                status
+            }
+            2 => (self.period >> 8) as u8,
+            _ => self.period as u8,
+        }
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        match offset & 0x03 {
+            0 => {
+                self.mode = if val & TIMER_PERIODIC != 0 {
+                    TimerMode::Periodic
+                } else {
+                    TimerMode::OneShot
+                };
+                match (self.running, val & TIMER_ENABLE != 0) {
+                    (false, true) => self.start(),
+                    (true, false) => self.stop(),
+                    _ => {}
+                }
+            }
+            1 => self.pending = false,
+            2 => self.set_period((self.period & 0x00FF) | (val as u64) << 8),
+            _ => self.set_period((self.period & 0xFF00) | val as u64),
+        }
+    }
+}
+
+impl Clocked for IntervalTimer {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        if self.running && self.period > 0 {
+            if cycles < self.remaining {
+                self.remaining -= cycles;
+            } else {
+                self.pending = true;
+                match self.mode {
+                    TimerMode::OneShot => {
+                        self.running = false;
+                        self.remaining = self.period;
+                    }
+                    TimerMode::Periodic => {
+                        let over = (cycles - self.remaining) % self.period;
+                        self.remaining = self.period - over;
+                    }
+                }
+            }
+        }
+        if self.pending {
+            self.line
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+impl Snapshot for IntervalTimer {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u64(self.period);
+        out.u64(self.remaining);
+        out.bool(self.mode == TimerMode::Periodic);
+        out.bool(self.running);
+        out.bool(self.pending);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.period = state.u64()?;
+        self.remaining = state.u64()?;
+        self.mode = if state.bool()? {
+            TimerMode::Periodic
+        } else {
+            TimerMode::OneShot
+        };
+        self.running = state.bool()?;
+        self.pending = state.bool()?;
+        Ok(())
+    }
+}
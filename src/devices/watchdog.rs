@@ -0,0 +1,127 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Watchdog timer.
+
+use crate::bus::Mmio;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Watchdog that pulses an interrupt or RESET line unless petted in time.
+///
+/// Any write to the device pets it and restarts the timeout. If `timeout`
+/// cycles pass without one, the watchdog bites: the next
+/// [`tick`](Clocked::tick) returns its line (normally [`BusSignals::NMI`] or
+/// [`BusSignals::RESET`]) and the following one releases it, so every bite is
+/// a fresh edge. The next timeout runs from the moment of the bite, counting
+/// the cycles of the tick that crossed it and of the release, so until the
+/// next pet the bites come every `timeout` cycles. Reads return the number
+/// of bites so far, saturated at 255.
+///
+/// # Example
+/// ```
+/// use mc6809_core::bus::Mmio;
+/// use mc6809_core::devices::Watchdog;
+/// use mc6809_core::{BusSignals, Clocked};
+///
+/// let mut watchdog = Watchdog::new(1000, BusSignals::NMI);
+/// assert!(watchdog.tick(900).is_empty());
+/// watchdog.write(0, 0); // pet
+/// assert!(watchdog.tick(900).is_empty());
+/// assert_eq!(watchdog.tick(100), BusSignals::NMI);
+/// assert!(watchdog.tick(1).is_empty());
+/// ```
+pub struct Watchdog {
+    timeout: u64,
+    /// Cycles since the last pet or bite.
+    elapsed: u64,
+    line: BusSignals,
+    bites: u64,
+    /// Bite still to be reported by the next tick.
+    biting: bool,
+}
+
+impl Watchdog {
+    /// Create a watchdog that pulses `line` after `timeout` cycles without a
+    /// pet.
+    ///
+    /// # Panics
+    /// Panics if `timeout` is zero.
+    pub fn new(timeout: u64, line: BusSignals) -> Self {
+        assert!(timeout > 0, "watchdog timeout must be non-zero");
+        Self {
+            timeout,
+            elapsed: 0,
+            line,
+            bites: 0,
+            biting: false,
+        }
+    }
+
+    /// Restart the timeout, as a write to the device does.
+    pub fn pet(&mut self) {
+        self.elapsed = 0;
+    }
+
+    /// Number of times the watchdog has bitten.
+    pub fn bites(&self) -> u64 {
+        self.bites
+    }
+
+    /// Cycles left before the next bite, or 0 if it is overdue (see
+    /// [`Clocked::tick`]).
+    pub fn remaining(&self) -> u64 {
+        self.timeout.saturating_sub(self.elapsed)
+    }
+}
+
+impl Mmio for Watchdog {
+    fn read(&mut self, _offset: u16) -> u8 {
+        self.bites.min(0xFF) as u8
+    }
+
+    fn write(&mut self, _offset: u16, _val: u8) {
+        self.pet();
+    }
+}
+
+impl Clocked for Watchdog {
+    /// A bite that falls due on the tick releasing the previous one is
+    /// returned by the tick after.
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        self.elapsed = self.elapsed.saturating_add(cycles);
+        if std::mem::take(&mut self.biting) || self.elapsed < self.timeout {
+            return BusSignals::default();
+        }
+        self.bites += 1;
+        self.biting = true;
+        self.elapsed -= self.timeout;
+        self.line
+    }
+}
+
+impl Snapshot for Watchdog {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u64(self.elapsed);
+        out.u64(self.bites);
+        out.bool(self.biting);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.elapsed = state.u64()?;
+        self.bites = state.u64()?;
+        self.biting = state.bool()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Diagnostic events: illegal opcodes, interrupts and halts.
+//!
+//! With the `defmt` feature each event is logged through [`defmt`], for
+//! targets where the host reads the log over a debug probe; the
+//! application supplies the `#[defmt::global_logger]`. Without it the
+//! events compile to nothing.
+
+/// Log a diagnostic event at trace level.
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "defmt")]
+        defmt::trace!($($arg)*);
+    };
+}
+
+/// Log a diagnostic event at info level.
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "defmt")]
+        defmt::info!($($arg)*);
+    };
+}
+
+/// Log a diagnostic event at warn level.
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "defmt")]
+        defmt::warn!($($arg)*);
+    };
+}
+
+pub(crate) use {log_info, log_trace, log_warn};
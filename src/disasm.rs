@@ -0,0 +1,631 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! MC6809 disassembler.
+//!
+//! [`disassemble`] decodes the instruction at an address into an
+//! [`Instruction`], which displays as a listing line:
+//!
+//! ```text
+//! 0400  8E 04 20       LDX   #$0420
+//! 0403  A6 80          LDA   ,X+
+//! 0405  26 FC          BNE   $0403
+//! ```
+//!
+//! Operands use Motorola syntax: `#` for immediate, `<` for direct page,
+//! `[...]` for indirect, and the destination address for branches and
+//! PC-relative indexing. Bytes that do not start a documented 6809
+//! instruction decode as a one-byte `FCB`.
+
+use std::fmt::{self, Write};
+
+use crate::memory::Memory;
+use crate::registers::Condition;
+use crate::symbols::SymbolTable;
+
+/// How an instruction's operand is encoded.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Inherent,
+    Imm8,
+    Imm16,
+    Direct,
+    Extended,
+    Indexed,
+    Rel8,
+    Rel16,
+    /// TFR and EXG register pair.
+    Pair,
+    /// PSHS and PULS register list.
+    ListS,
+    /// PSHU and PULU register list.
+    ListU,
+}
+
+/// Read-modify-write and accumulator instructions of rows 0x0, 0x4-0x7,
+/// by low nibble; empty names are undefined.
+const RMW: [&str; 16] = [
+    "NEG", "", "", "COM", "LSR", "", "ROR", "ASR", "ASL", "ROL", "DEC", "", "INC", "TST", "JMP",
+    "CLR",
+];
+
+/// Row 0x4, on accumulator A.
+const RMW_A: [&str; 16] = [
+    "NEGA", "", "", "COMA", "LSRA", "", "RORA", "ASRA", "ASLA", "ROLA", "DECA", "", "INCA", "TSTA",
+    "", "CLRA",
+];
+
+/// Row 0x5, on accumulator B.
+const RMW_B: [&str; 16] = [
+    "NEGB", "", "", "COMB", "LSRB", "", "RORB", "ASRB", "ASLB", "ROLB", "DECB", "", "INCB", "TSTB",
+    "", "CLRB",
+];
+
+/// Rows 0x8-0xB by low nibble.
+const ACC_A: [&str; 16] = [
+    "SUBA", "CMPA", "SBCA", "SUBD", "ANDA", "BITA", "LDA", "STA", "EORA", "ADCA", "ORA", "ADDA",
+    "CMPX", "JSR", "LDX", "STX",
+];
+
+/// Rows 0xC-0xF by low nibble.
+const ACC_B: [&str; 16] = [
+    "SUBB", "CMPB", "SBCB", "ADDD", "ANDB", "BITB", "LDB", "STB", "EORB", "ADCB", "ORB", "ADDB",
+    "LDD", "STD", "LDU", "STU",
+];
+
+/// Branch conditions, by low nibble of the opcode.
+const BRANCHES: [&str; 16] = [
+    "BRA", "BRN", "BHI", "BLS", "BCC", "BCS", "BNE", "BEQ", "BVC", "BVS", "BPL", "BMI", "BGE",
+    "BLT", "BGT", "BLE",
+];
+
+/// Long branches after the 0x10 prefix, by low nibble of the opcode.
+const LONG_BRANCHES: [&str; 16] = [
+    "LBRA", "LBRN", "LBHI", "LBLS", "LBCC", "LBCS", "LBNE", "LBEQ", "LBVC", "LBVS", "LBPL", "LBMI",
+    "LBGE", "LBLT", "LBGT", "LBLE",
+];
+
+/// Index registers, by bits 5-6 of an indexed post-byte.
+const INDEX_REGS: [&str; 4] = ["X", "Y", "U", "S"];
+
+/// TFR/EXG register codes.
+const PAIR_REGS: [&str; 16] = [
+    "D", "X", "Y", "U", "S", "PC", "?", "?", "A", "B", "CC", "DP", "?", "?", "?", "?",
+];
+
+/// The addressing mode of rows 0x8-0xF, by bits 4-5 of the opcode.
+const ACC_MODES: [Mode; 4] = [Mode::Imm8, Mode::Direct, Mode::Indexed, Mode::Extended];
+
+/// One decoded instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// Address of the first byte.
+    pub addr: u16,
+    /// The instruction's bytes, prefix included.
+    pub bytes: Vec<u8>,
+    /// Mnemonic, such as `LDA` or `FCB`.
+    pub mnemonic: String,
+    /// Operand text, empty for inherent instructions.
+    pub operand: String,
+    /// Address a branch, extended or PC-relative operand refers to.
+    pub target: Option<u16>,
+}
+
+impl Instruction {
+    /// Length in bytes.
+    pub fn len(&self) -> u16 {
+        self.bytes.len() as u16
+    }
+
+    /// Always `false`: every instruction is at least one byte.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Address of the byte after the instruction.
+    pub fn next(&self) -> u16 {
+        self.addr.wrapping_add(self.len())
+    }
+
+    /// The condition a branch tests, or `None` if this is not a branch;
+    /// [`ConditionCodes::test`](crate::ConditionCodes::test) tells whether
+    /// it would be taken.
+    pub fn condition(&self) -> Option<Condition> {
+        Condition::of_branch(self.opcode())
+    }
+
+    /// Where execution can go after this instruction.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::FlatRam;
+    /// use mc6809_core::disasm::{Successors, disassemble};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x26, 0xFE, 0x39], 0x0400); // BNE * / RTS
+    /// let bne = disassemble(&mut ram, 0x0400);
+    /// assert_eq!(bne.successors().iter().collect::<Vec<_>>(), [0x0402, 0x0400]);
+    ///
+    /// let rts = disassemble(&mut ram, 0x0402);
+    /// assert_eq!(rts.successors(), Successors::COMPUTED);
+    /// ```
+    pub fn successors(&self) -> Successors {
+        let next = Some(self.next());
+        let opcode = self.opcode();
+        let post = self.bytes.get(if opcode > 0xFF { 2 } else { 1 }).copied();
+        // The destination of a JMP or JSR, if the instruction gives it.
+        let jump = |fall_through| match (opcode, post) {
+            (0x7E | 0xBD, _) => Successors {
+                fall_through,
+                target: self.target,
+                computed: false,
+            },
+            (0x6E | 0xAD, Some(post)) if post & 0x90 != 0x90 && self.target.is_some() => {
+                Successors {
+                    fall_through,
+                    target: self.target,
+                    computed: false,
+                }
+            }
+            _ => Successors {
+                fall_through,
+                ..Successors::COMPUTED
+            },
+        };
+        if let Some(condition) = self.condition() {
+            return match condition {
+                // BSR and LBSR return to the next instruction.
+                Condition::Always if !matches!(opcode, 0x8D | 0x17) => Successors {
+                    fall_through: None,
+                    target: self.target,
+                    computed: false,
+                },
+                Condition::Never => Successors::next(self.next()),
+                _ => Successors {
+                    fall_through: next,
+                    target: self.target,
+                    computed: false,
+                },
+            };
+        }
+        match (opcode, post) {
+            // JSR
+            (0x9D | 0xAD | 0xBD, _) => jump(next),
+            // JMP
+            (0x0E | 0x6E | 0x7E, _) => jump(None),
+            // RTS, RTI
+            (0x39 | 0x3B, _) => Successors::COMPUTED,
+            // SWI, SWI2, SWI3 return to the next instruction.
+            (0x3F | 0x103F | 0x113F, _) => Successors {
+                fall_through: next,
+                ..Successors::COMPUTED
+            },
+            // PULS and PULU with PC; TFR and EXG to PC.
+            (0x35 | 0x37, Some(post)) if post & 0x80 != 0 => Successors::COMPUTED,
+            (0x1F, Some(post)) if post & 0x0F == 5 => Successors::COMPUTED,
+            (0x1E, Some(post)) if post & 0x0F == 5 || post >> 4 == 5 => Successors::COMPUTED,
+            _ => Successors::next(self.next()),
+        }
+    }
+
+    /// The opcode, with any page prefix in the high byte.
+    fn opcode(&self) -> u16 {
+        match self.bytes[..] {
+            [prefix @ (0x10 | 0x11), op, ..] => (prefix as u16) << 8 | op as u16,
+            [op, ..] => op as u16,
+            [] => 0,
+        }
+    }
+
+    /// The mnemonic and operand, as an assembler would take them.
+    pub fn text(&self) -> String {
+        if self.operand.is_empty() {
+            self.mnemonic.clone()
+        } else {
+            format!("{:<5} {}", self.mnemonic, self.operand)
+        }
+    }
+
+    /// [`Self::text`] with [`Self::target`] shown as a label from
+    /// `symbols`, if it has one there.
+    pub fn text_with(&self, symbols: &SymbolTable) -> String {
+        let label = self
+            .target
+            .and_then(|target| symbols.names_at(target).first());
+        match label {
+            Some(label) => {
+                let at = self.operand.find('$').expect("a target is printed in hex");
+                let end = self.operand[at + 1..]
+                    .find(|c: char| !c.is_ascii_hexdigit())
+                    .map_or(self.operand.len(), |len| at + 1 + len);
+                let operand = format!("{}{label}{}", &self.operand[..at], &self.operand[end..]);
+                format!("{:<5} {operand}", self.mnemonic)
+            }
+            None => self.text(),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = String::new();
+        for byte in &self.bytes {
+            write!(bytes, "{byte:02X} ")?;
+        }
+        write!(f, "{:04X}  {bytes:<15}{}", self.addr, self.text())
+    }
+}
+
+/// Decode the instruction at `addr`.
+///
+/// Reads go through `mem`, so disassembling I/O registers has whatever side
+/// effects reading them does. The 6809's undocumented opcodes and the
+/// HD6309 extensions decode as `FCB`.
+///
+/// # Example
+/// ```
+/// use mc6809_core::FlatRam;
+/// use mc6809_core::disasm::disassemble;
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x10, 0x8E, 0x12, 0x34, 0x26, 0xFA], 0x0400); // LDY #$1234 / BNE
+/// let insn = disassemble(&mut ram, 0x0400);
+/// assert_eq!(insn.text(), "LDY   #$1234");
+/// assert_eq!(disassemble(&mut ram, insn.next()).text(), "BNE   $0400");
+/// ```
+pub fn disassemble(mem: &mut impl Memory, addr: u16) -> Instruction {
+    let mut decoder = Decoder {
+        mem,
+        addr,
+        bytes: Vec::new(),
+    };
+    let opcode = decoder.byte();
+    let decoded = match opcode {
+        0x10 => {
+            let op = decoder.byte();
+            page1(op)
+        }
+        0x11 => {
+            let op = decoder.byte();
+            page2(op)
+        }
+        _ => page0(opcode),
+    };
+    let Some((mnemonic, mode)) = decoded else {
+        return Instruction {
+            addr,
+            bytes: vec![opcode],
+            mnemonic: "FCB".to_string(),
+            operand: format!("${opcode:02X}"),
+            target: None,
+        };
+    };
+    let (operand, target) = decoder.operand(mode);
+    Instruction {
+        addr,
+        bytes: decoder.bytes,
+        mnemonic: mnemonic.to_string(),
+        operand,
+        target,
+    }
+}
+
+/// Where execution can go after an instruction, from
+/// [`Instruction::successors`].
+///
+/// For calls (BSR, LBSR, JSR) and software interrupts the fall-through is
+/// where they return to, for stepping over them. Undefined opcodes fall
+/// through, as the one-byte `FCB` they disassemble to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Successors {
+    /// The next instruction, if execution can carry on into it (or return
+    /// to it).
+    pub fall_through: Option<u16>,
+    /// A branch, jump or call destination the instruction gives.
+    pub target: Option<u16>,
+    /// Execution can also go somewhere known only at run time: returns,
+    /// software interrupts, pulls and transfers into PC, and jumps through
+    /// a register, a pointer or the direct page.
+    pub computed: bool,
+}
+
+impl Successors {
+    /// Only to somewhere known at run time, as after RTS.
+    pub const COMPUTED: Successors = Successors {
+        fall_through: None,
+        target: None,
+        computed: true,
+    };
+
+    /// Only on to `next`.
+    pub const fn next(next: u16) -> Self {
+        Successors {
+            fall_through: Some(next),
+            target: None,
+            computed: false,
+        }
+    }
+
+    /// The known successor addresses: fall-through first, then target.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        self.fall_through.into_iter().chain(self.target)
+    }
+}
+
+/// Length in bytes of the instruction at `addr`, as [`disassemble`] would
+/// decode it, reading only the prefix, opcode and any indexed post-byte.
+///
+/// An undefined opcode counts as one byte, as the `FCB` it disassembles to.
+///
+/// # Example
+/// ```
+/// use mc6809_core::FlatRam;
+/// use mc6809_core::disasm::insn_length;
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x10, 0xAE, 0xA9, 0x01, 0x00], 0x0400); // LDY $100,Y
+/// assert_eq!(insn_length(&mut ram, 0x0400), 5);
+/// ```
+pub fn insn_length(mem: &mut impl Memory, addr: u16) -> u16 {
+    let (opcode, len) = match mem.read(addr) {
+        prefix @ (0x10 | 0x11) => (
+            (prefix as u16) << 8 | mem.read(addr.wrapping_add(1)) as u16,
+            2,
+        ),
+        op => (op as u16, 1),
+    };
+    let Some((_, mode)) = opcode_info(opcode) else {
+        return 1;
+    };
+    len + match mode {
+        Mode::Inherent => 0,
+        Mode::Imm8 | Mode::Direct | Mode::Rel8 | Mode::Pair | Mode::ListS | Mode::ListU => 1,
+        Mode::Imm16 | Mode::Extended | Mode::Rel16 => 2,
+        Mode::Indexed => {
+            let post = mem.read(addr.wrapping_add(len));
+            let offset = match post & 0x8F {
+                0x88 | 0x8C => 1,
+                0x89 | 0x8D | 0x8F => 2,
+                _ => 0,
+            };
+            1 + offset
+        }
+    }
+}
+
+/// The mnemonic and mode of `opcode`, written with its prefix for pages 1
+/// and 2 (`0x108E` is `LDY` immediate), or `None` if it is undefined.
+pub(crate) fn opcode_info(opcode: u16) -> Option<(&'static str, Mode)> {
+    match opcode >> 8 {
+        0x00 => page0(opcode as u8),
+        0x10 => page1(opcode as u8),
+        0x11 => page2(opcode as u8),
+        _ => None,
+    }
+}
+
+/// The mnemonic and mode of an unprefixed opcode.
+fn page0(op: u8) -> Option<(&'static str, Mode)> {
+    let low = (op & 0x0F) as usize;
+    let found = match op {
+        0x00..=0x0F => (RMW[low], Mode::Direct),
+        0x12 => ("NOP", Mode::Inherent),
+        0x13 => ("SYNC", Mode::Inherent),
+        0x16 => ("LBRA", Mode::Rel16),
+        0x17 => ("LBSR", Mode::Rel16),
+        0x19 => ("DAA", Mode::Inherent),
+        0x1A => ("ORCC", Mode::Imm8),
+        0x1C => ("ANDCC", Mode::Imm8),
+        0x1D => ("SEX", Mode::Inherent),
+        0x1E => ("EXG", Mode::Pair),
+        0x1F => ("TFR", Mode::Pair),
+        0x20..=0x2F => (BRANCHES[low], Mode::Rel8),
+        0x30 => ("LEAX", Mode::Indexed),
+        0x31 => ("LEAY", Mode::Indexed),
+        0x32 => ("LEAS", Mode::Indexed),
+        0x33 => ("LEAU", Mode::Indexed),
+        0x34 => ("PSHS", Mode::ListS),
+        0x35 => ("PULS", Mode::ListS),
+        0x36 => ("PSHU", Mode::ListU),
+        0x37 => ("PULU", Mode::ListU),
+        0x39 => ("RTS", Mode::Inherent),
+        0x3A => ("ABX", Mode::Inherent),
+        0x3B => ("RTI", Mode::Inherent),
+        0x3C => ("CWAI", Mode::Imm8),
+        0x3D => ("MUL", Mode::Inherent),
+        0x3F => ("SWI", Mode::Inherent),
+        0x40..=0x4F => (RMW_A[low], Mode::Inherent),
+        0x50..=0x5F => (RMW_B[low], Mode::Inherent),
+        0x60..=0x6F => (RMW[low], Mode::Indexed),
+        0x70..=0x7F => (RMW[low], Mode::Extended),
+        0x87 | 0x8F | 0xC7 | 0xCD | 0xCF => return None,
+        0x8D => ("BSR", Mode::Rel8),
+        0x80..=0xFF => {
+            let mode = ACC_MODES[((op >> 4) & 3) as usize];
+            let name = if op < 0xC0 { ACC_A[low] } else { ACC_B[low] };
+            // Word operations take a 16-bit immediate.
+            let mode = match (mode, low) {
+                (Mode::Imm8, 0x03 | 0x0C | 0x0E) => Mode::Imm16,
+                _ => mode,
+            };
+            (name, mode)
+        }
+        _ => return None,
+    };
+    (!found.0.is_empty()).then_some(found)
+}
+
+/// The mnemonic and mode of an opcode after the 0x10 prefix.
+fn page1(op: u8) -> Option<(&'static str, Mode)> {
+    let mode = ACC_MODES[((op >> 4) & 3) as usize];
+    let word = |mode: Mode| {
+        if mode == Mode::Imm8 {
+            Mode::Imm16
+        } else {
+            mode
+        }
+    };
+    match op {
+        0x21..=0x2F => Some((LONG_BRANCHES[(op & 0x0F) as usize], Mode::Rel16)),
+        0x3F => Some(("SWI2", Mode::Inherent)),
+        0x83 | 0x93 | 0xA3 | 0xB3 => Some(("CMPD", word(mode))),
+        0x8C | 0x9C | 0xAC | 0xBC => Some(("CMPY", word(mode))),
+        0x8E | 0x9E | 0xAE | 0xBE => Some(("LDY", word(mode))),
+        0x9F | 0xAF | 0xBF => Some(("STY", mode)),
+        0xCE | 0xDE | 0xEE | 0xFE => Some(("LDS", word(mode))),
+        0xDF | 0xEF | 0xFF => Some(("STS", mode)),
+        _ => None,
+    }
+}
+
+/// The mnemonic and mode of an opcode after the 0x11 prefix.
+fn page2(op: u8) -> Option<(&'static str, Mode)> {
+    let mode = ACC_MODES[((op >> 4) & 3) as usize];
+    let word = if mode == Mode::Imm8 {
+        Mode::Imm16
+    } else {
+        mode
+    };
+    match op {
+        0x3F => Some(("SWI3", Mode::Inherent)),
+        0x83 | 0x93 | 0xA3 | 0xB3 => Some(("CMPU", word)),
+        0x8C | 0x9C | 0xAC | 0xBC => Some(("CMPS", word)),
+        _ => None,
+    }
+}
+
+/// Reads an instruction's bytes in order.
+struct Decoder<'a, M> {
+    mem: &'a mut M,
+    addr: u16,
+    bytes: Vec<u8>,
+}
+
+impl<M: Memory> Decoder<'_, M> {
+    fn byte(&mut self) -> u8 {
+        let byte = self
+            .mem
+            .read(self.addr.wrapping_add(self.bytes.len() as u16));
+        self.bytes.push(byte);
+        byte
+    }
+
+    fn word(&mut self) -> u16 {
+        u16::from_be_bytes([self.byte(), self.byte()])
+    }
+
+    /// Address of the byte after what has been read.
+    fn end(&self) -> u16 {
+        self.addr.wrapping_add(self.bytes.len() as u16)
+    }
+
+    /// Read and format the operand, with the address it refers to.
+    fn operand(&mut self, mode: Mode) -> (String, Option<u16>) {
+        match mode {
+            Mode::Inherent => (String::new(), None),
+            Mode::Imm8 => (format!("#${:02X}", self.byte()), None),
+            Mode::Imm16 => (format!("#${:04X}", self.word()), None),
+            Mode::Direct => (format!("<${:02X}", self.byte()), None),
+            Mode::Extended => {
+                let addr = self.word();
+                (format!("${addr:04X}"), Some(addr))
+            }
+            Mode::Rel8 => {
+                let offset = self.byte() as i8;
+                let target = self.end().wrapping_add(offset as u16);
+                (format!("${target:04X}"), Some(target))
+            }
+            Mode::Rel16 => {
+                let offset = self.word();
+                let target = self.end().wrapping_add(offset);
+                (format!("${target:04X}"), Some(target))
+            }
+            Mode::Pair => {
+                let post = self.byte();
+                let operand = format!(
+                    "{},{}",
+                    PAIR_REGS[(post >> 4) as usize],
+                    PAIR_REGS[(post & 0x0F) as usize]
+                );
+                (operand, None)
+            }
+            Mode::ListS | Mode::ListU => {
+                let other = if mode == Mode::ListS { "U" } else { "S" };
+                let names = ["CC", "A", "B", "DP", "X", "Y", other, "PC"];
+                let post = self.byte();
+                let list: Vec<&str> = (0..8)
+                    .filter(|bit| post & (1 << bit) != 0)
+                    .map(|bit| names[bit])
+                    .collect();
+                (list.join(","), None)
+            }
+            Mode::Indexed => self.indexed(),
+        }
+    }
+
+    /// Read and format an indexed operand from its post-byte.
+    fn indexed(&mut self) -> (String, Option<u16>) {
+        let post = self.byte();
+        let reg = INDEX_REGS[((post >> 5) & 3) as usize];
+        if post & 0x80 == 0 {
+            // 5-bit signed offset
+            let offset = ((post << 3) as i8) >> 3;
+            return (format!("{offset},{reg}"), None);
+        }
+        let indirect = post & 0x10 != 0;
+        let mut target = None;
+        let text = match post & 0x0F {
+            // Single auto-increment and decrement cannot be indirect.
+            0x0 | 0x2 if indirect => "??".to_string(),
+            0x0 => format!(",{reg}+"),
+            0x1 => format!(",{reg}++"),
+            0x2 => format!(",-{reg}"),
+            0x3 => format!(",--{reg}"),
+            0x4 => format!(",{reg}"),
+            0x5 => format!("B,{reg}"),
+            0x6 => format!("A,{reg}"),
+            0x8 => format!("{},{reg}", self.byte() as i8),
+            0x9 => format!("{},{reg}", self.word() as i16),
+            0xB => format!("D,{reg}"),
+            0xC => {
+                let offset = self.byte() as i8;
+                let addr = self.end().wrapping_add(offset as u16);
+                target = Some(addr);
+                format!("${addr:04X},PCR")
+            }
+            0xD => {
+                let offset = self.word();
+                let addr = self.end().wrapping_add(offset);
+                target = Some(addr);
+                format!("${addr:04X},PCR")
+            }
+            // Without indirection (undocumented) the CPU still reads the
+            // address.
+            0xF => {
+                let addr = self.word();
+                if !indirect {
+                    return ("??".to_string(), None);
+                }
+                target = Some(addr);
+                format!("${addr:04X}")
+            }
+            _ => "??".to_string(),
+        };
+        if indirect {
+            (format!("[{text}]"), target)
+        } else {
+            (text, target)
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Program file formats.
+//!
+//! Each format module parses a file into an [`Image`]: the segments to
+//! place in memory and, if the file names one, the address to start at.
+//! [`Format::detect`] tells the formats apart by their contents.
+
+pub mod decb;
+pub mod flex;
+pub mod ihex;
+pub mod raw;
+pub mod srec;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::memory::Memory;
+
+/// Bytes to place at one address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// Address of the first byte.
+    pub addr: u16,
+    /// The bytes, which end at or before `0xFFFF`.
+    pub data: Vec<u8>,
+}
+
+/// A parsed program file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Image {
+    /// Segments in file order; later ones overwrite earlier ones where
+    /// they overlap.
+    pub segments: Vec<Segment>,
+    /// Start address, if the file gives one.
+    pub entry: Option<u16>,
+}
+
+impl Image {
+    /// Write every segment to `mem`, in order.
+    pub fn load_into(&self, mem: &mut impl Memory) {
+        for segment in &self.segments {
+            for (offset, &byte) in segment.data.iter().enumerate() {
+                mem.write(segment.addr.wrapping_add(offset as u16), byte);
+            }
+        }
+    }
+
+    /// Total bytes in all segments.
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(|segment| segment.data.len()).sum()
+    }
+
+    /// Whether the image has no bytes to load.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A program file format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Bytes with no addresses; see [`raw`].
+    Raw,
+    /// Motorola S-records; see [`srec`].
+    Srec,
+    /// Intel HEX; see [`ihex`].
+    IntelHex,
+    /// Disk Extended Color BASIC binary; see [`decb`].
+    Decb,
+    /// FLEX binary; see [`flex`].
+    Flex,
+}
+
+impl Format {
+    /// The format `data` is in: S-records, Intel HEX or a DECB binary if it
+    /// parses as one, otherwise raw bytes.
+    ///
+    /// FLEX binaries are never detected, since raw code can look like one;
+    /// parse them with [`Format::Flex`] explicitly.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::formats::Format;
+    ///
+    /// assert_eq!(Format::detect(b"S1050400862A46\nS9030400F8\n"), Format::Srec);
+    /// assert_eq!(Format::detect(&[0x86, 0x2A]), Format::Raw);
+    /// ```
+    pub fn detect(data: &[u8]) -> Format {
+        let candidate = match data.first() {
+            Some(b'S') => Format::Srec,
+            Some(b':') => Format::IntelHex,
+            Some(0x00) => Format::Decb,
+            _ => return Format::Raw,
+        };
+        match candidate.parse(data, 0) {
+            Ok(_) => candidate,
+            Err(_) => Format::Raw,
+        }
+    }
+
+    /// Parse `data` in this format. `addr` is where a raw image goes; the
+    /// other formats carry their own addresses and ignore it.
+    pub fn parse(self, data: &[u8], addr: u16) -> Result<Image, FormatError> {
+        match self {
+            Format::Raw => raw::parse(data, addr),
+            Format::Srec => srec::parse(data),
+            Format::IntelHex => ihex::parse(data),
+            Format::Decb => decb::parse(data),
+            Format::Flex => flex::parse(data),
+        }
+    }
+
+    /// `true` for formats that say where their bytes go.
+    pub fn has_addresses(self) -> bool {
+        self != Format::Raw
+    }
+}
+
+/// The non-blank lines of a text file, numbered from 1, with surrounding
+/// whitespace removed.
+fn text_lines(data: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    data.split(|&b| b == b'\n')
+        .map(<[u8]>::trim_ascii)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| (index + 1, line))
+}
+
+/// Decode pairs of hex digits.
+fn hex_bytes(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Why a file could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// The file ended inside the record starting at `offset`.
+    Truncated { offset: usize },
+    /// The byte at `offset` does not start a record the format knows.
+    BadRecord { offset: usize, byte: u8 },
+    /// `len` bytes at `addr` run past the end of the address space.
+    OutOfRange { addr: u16, len: usize },
+    /// Line `line` (counting from 1) of a text file could not be read.
+    BadLine { line: usize },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Truncated { offset } => {
+                write!(f, "file ends inside the record at offset {offset}")
+            }
+            FormatError::BadRecord { offset, byte } => {
+                write!(f, "unknown record type {byte:02X} at offset {offset}")
+            }
+            FormatError::OutOfRange { addr, len } => {
+                write!(f, "{len} bytes at {addr:04X} run past FFFF")
+            }
+            FormatError::BadLine { line } => write!(f, "cannot read line {line}"),
+        }
+    }
+}
+
+impl Error for FormatError {}
@@ -0,0 +1,85 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Disk Extended Color BASIC binaries (`.BIN`), as `LOADM` reads them on
+//! the Dragon and CoCo.
+//!
+//! | Record    | Bytes                                           |
+//! |-----------|-------------------------------------------------|
+//! | preamble  | `00`, length (2), address (2), length bytes     |
+//! | postamble | `FF`, `00 00`, exec address (2); ends the file  |
+//!
+//! Anything after the postamble is ignored.
+
+use super::{FormatError, Image, Segment};
+use crate::memory::Memory;
+
+/// Preamble (data block) marker.
+const PREAMBLE: u8 = 0x00;
+
+/// Postamble (exec address) marker.
+const POSTAMBLE: u8 = 0xFF;
+
+/// Parse a DECB binary.
+///
+/// # Example
+/// ```
+/// use mc6809_core::formats::decb;
+///
+/// let file = [0x00, 0x00, 0x02, 0x0E, 0x00, 0x86, 0x2A, 0xFF, 0x00, 0x00, 0x0E, 0x00];
+/// let image = decb::parse(&file).unwrap();
+/// assert_eq!(image.segments[0].addr, 0x0E00);
+/// assert_eq!(image.segments[0].data, [0x86, 0x2A]);
+/// assert_eq!(image.entry, Some(0x0E00));
+/// ```
+pub fn parse(data: &[u8]) -> Result<Image, FormatError> {
+    let mut image = Image::default();
+    let mut offset = 0;
+    loop {
+        let header = data
+            .get(offset..offset + 5)
+            .ok_or(FormatError::Truncated { offset })?;
+        let len = u16::from_be_bytes([header[1], header[2]]) as usize;
+        let addr = u16::from_be_bytes([header[3], header[4]]);
+        match header[0] {
+            PREAMBLE => {
+                let bytes = data
+                    .get(offset + 5..offset + 5 + len)
+                    .ok_or(FormatError::Truncated { offset })?;
+                if addr as usize + len > 0x10000 {
+                    return Err(FormatError::OutOfRange { addr, len });
+                }
+                image.segments.push(Segment {
+                    addr,
+                    data: bytes.to_vec(),
+                });
+                offset += 5 + len;
+            }
+            POSTAMBLE => {
+                image.entry = Some(addr);
+                return Ok(image);
+            }
+            byte => return Err(FormatError::BadRecord { offset, byte }),
+        }
+    }
+}
+
+/// Parse a DECB binary and write it to `mem`. Returns the exec address.
+///
+/// Nothing is written if the file does not parse.
+pub fn load(data: &[u8], mem: &mut impl Memory) -> Result<Option<u16>, FormatError> {
+    let image = parse(data)?;
+    image.load_into(mem);
+    Ok(image.entry)
+}
@@ -0,0 +1,95 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! FLEX binary (`.CMD`) files.
+//!
+//! A FLEX binary is a run of records, with zero bytes as padding between
+//! them where the file was blocked into disk sectors:
+//!
+//! | Record   | Bytes                                     |
+//! |----------|-------------------------------------------|
+//! | load     | `02`, address (2), count (1), count bytes |
+//! | transfer | `16`, address (2)                         |
+//!
+//! The transfer address is where FLEX starts the program; if a file holds
+//! several, the last one wins.
+
+use super::{FormatError, Image, Segment};
+use crate::memory::Memory;
+
+/// Load record marker.
+const LOAD: u8 = 0x02;
+
+/// Transfer address record marker.
+const TRANSFER: u8 = 0x16;
+
+/// Parse a FLEX binary.
+///
+/// # Example
+/// ```
+/// use mc6809_core::formats::flex;
+///
+/// // Two bytes at $0100 and a transfer address of $0100.
+/// let file = [0x02, 0x01, 0x00, 0x02, 0x86, 0x2A, 0x00, 0x00, 0x16, 0x01, 0x00];
+/// let image = flex::parse(&file).unwrap();
+/// assert_eq!(image.segments[0].addr, 0x0100);
+/// assert_eq!(image.segments[0].data, [0x86, 0x2A]);
+/// assert_eq!(image.entry, Some(0x0100));
+/// ```
+pub fn parse(data: &[u8]) -> Result<Image, FormatError> {
+    let mut image = Image::default();
+    let mut offset = 0;
+    while offset < data.len() {
+        let record = |len: usize| {
+            data.get(offset..offset + len)
+                .ok_or(FormatError::Truncated { offset })
+        };
+        match data[offset] {
+            0x00 => offset += 1,
+            LOAD => {
+                let header = record(4)?;
+                let addr = u16::from_be_bytes([header[1], header[2]]);
+                let len = header[3] as usize;
+                let bytes = data
+                    .get(offset + 4..offset + 4 + len)
+                    .ok_or(FormatError::Truncated { offset })?;
+                if addr as usize + len > 0x10000 {
+                    return Err(FormatError::OutOfRange { addr, len });
+                }
+                image.segments.push(Segment {
+                    addr,
+                    data: bytes.to_vec(),
+                });
+                offset += 4 + len;
+            }
+            TRANSFER => {
+                let header = record(3)?;
+                image.entry = Some(u16::from_be_bytes([header[1], header[2]]));
+                offset += 3;
+            }
+            byte => return Err(FormatError::BadRecord { offset, byte }),
+        }
+    }
+    Ok(image)
+}
+
+/// Parse a FLEX binary and write it to `mem`. Returns the transfer address,
+/// if the file has one.
+///
+/// Nothing is written if the file does not parse.
+pub fn load(data: &[u8], mem: &mut impl Memory) -> Result<Option<u16>, FormatError> {
+    let image = parse(data)?;
+    image.load_into(mem);
+    Ok(image.entry)
+}
@@ -0,0 +1,86 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Intel HEX files (`.hex`, `.ihx`).
+//!
+//! Each line is `:`, a byte count, an address, a record type and data, all
+//! in hex, ending in a checksum that makes the bytes sum to zero:
+//!
+//! | Type | Meaning                                           |
+//! |------|---------------------------------------------------|
+//! | `00` | Data                                              |
+//! | `01` | End of file; anything after it is ignored         |
+//! | `02`, `04` | Extended address; only zero fits a 6809     |
+//! | `03`, `05` | Start address; only its low 16 bits are kept |
+
+use super::{FormatError, Image, Segment, hex_bytes, text_lines};
+use crate::memory::Memory;
+
+/// Parse Intel HEX.
+///
+/// # Example
+/// ```
+/// use mc6809_core::formats::ihex;
+///
+/// let image = ihex::parse(b":02040000862A4A\n:00000001FF\n").unwrap();
+/// assert_eq!(image.segments[0].addr, 0x0400);
+/// assert_eq!(image.segments[0].data, [0x86, 0x2A]);
+/// ```
+pub fn parse(data: &[u8]) -> Result<Image, FormatError> {
+    let mut image = Image::default();
+    for (line, text) in text_lines(data) {
+        let bad = FormatError::BadLine { line };
+        let hex = text.strip_prefix(b":").ok_or(bad.clone())?;
+        let bytes = hex_bytes(hex).ok_or(bad.clone())?;
+        if bytes.len() < 5
+            || bytes[0] as usize != bytes.len() - 5
+            || bytes.iter().fold(0u8, |s, &b| s.wrapping_add(b)) != 0
+        {
+            return Err(bad);
+        }
+        let addr = u16::from_be_bytes([bytes[1], bytes[2]]);
+        let payload = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            0x00 => {
+                if addr as usize + payload.len() > 0x10000 {
+                    return Err(FormatError::OutOfRange {
+                        addr,
+                        len: payload.len(),
+                    });
+                }
+                image.segments.push(Segment {
+                    addr,
+                    data: payload.to_vec(),
+                });
+            }
+            0x01 => break,
+            0x02 | 0x04 if payload.iter().all(|&b| b == 0) => {}
+            0x03 | 0x05 if payload.len() == 4 => {
+                image.entry = Some(u16::from_be_bytes([payload[2], payload[3]]));
+            }
+            _ => return Err(bad),
+        }
+    }
+    Ok(image)
+}
+
+/// Parse Intel HEX and write it to `mem`. Returns the start address, if
+/// the file has one.
+///
+/// Nothing is written if the file does not parse.
+pub fn load(data: &[u8], mem: &mut impl Memory) -> Result<Option<u16>, FormatError> {
+    let image = parse(data)?;
+    image.load_into(mem);
+    Ok(image.entry)
+}
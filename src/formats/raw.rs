@@ -0,0 +1,146 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Raw binary images and memory dumps.
+//!
+//! A raw binary is just the bytes to place in memory, so the address to
+//! load it at comes from elsewhere. Dumps go the other way and read a range
+//! of memory back out, as raw bytes or as Motorola S-records.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{FormatError, Image, Segment};
+use crate::memory::Memory;
+
+/// Data bytes per S1 record written by [`dump_srec`].
+const SREC_BYTES: usize = 16;
+
+/// Check that `len` bytes from `addr` fit in the address space.
+fn check_range(addr: u16, len: usize) -> Result<(), FormatError> {
+    if addr as usize + len > 0x10000 {
+        return Err(FormatError::OutOfRange { addr, len });
+    }
+    Ok(())
+}
+
+/// Make an image of `data` to be loaded at `addr`.
+pub fn parse(data: &[u8], addr: u16) -> Result<Image, FormatError> {
+    check_range(addr, data.len())?;
+    Ok(Image {
+        segments: vec![Segment {
+            addr,
+            data: data.to_vec(),
+        }],
+        entry: None,
+    })
+}
+
+/// Write `data` to `mem` from `addr`.
+///
+/// Nothing is written if `data` runs past `0xFFFF`.
+///
+/// # Example
+/// ```
+/// use mc6809_core::formats::raw;
+/// use mc6809_core::{FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// raw::load(&[0x12, 0x3F], 0x0400, &mut ram).unwrap();
+/// assert_eq!(raw::dump(&mut ram, 0x0400, 2).unwrap(), [0x12, 0x3F]);
+/// ```
+pub fn load(data: &[u8], addr: u16, mem: &mut impl Memory) -> Result<(), FormatError> {
+    parse(data, addr)?.load_into(mem);
+    Ok(())
+}
+
+/// Read `len` bytes of `mem` from `addr`.
+pub fn dump(mem: &mut impl Memory, addr: u16, len: usize) -> Result<Vec<u8>, FormatError> {
+    check_range(addr, len)?;
+    let mut data = vec![0; len];
+    mem.read_slice(addr, &mut data);
+    Ok(data)
+}
+
+/// Read `len` bytes of `mem` from `addr` as S1 records of up to 16 bytes,
+/// ended by an S9 record holding `entry` (or 0 if there is none).
+///
+/// # Example
+/// ```
+/// use mc6809_core::FlatRam;
+/// use mc6809_core::formats::raw;
+///
+/// let mut ram = FlatRam::new();
+/// raw::load(&[0x86, 0x2A], 0x0400, &mut ram).unwrap();
+/// let srec = raw::dump_srec(&mut ram, 0x0400, 2, Some(0x0400)).unwrap();
+/// assert_eq!(srec, "S1050400862A46\nS9030400F8\n");
+/// ```
+pub fn dump_srec(
+    mem: &mut impl Memory,
+    addr: u16,
+    len: usize,
+    entry: Option<u16>,
+) -> Result<String, FormatError> {
+    let data = dump(mem, addr, len)?;
+    let mut out = String::new();
+    for (index, chunk) in data.chunks(SREC_BYTES).enumerate() {
+        let offset = (index * SREC_BYTES) as u16;
+        srec_record(&mut out, '1', addr.wrapping_add(offset), chunk);
+    }
+    srec_record(&mut out, '9', entry.unwrap_or(0), &[]);
+    Ok(out)
+}
+
+/// Append one S-record with a 16-bit address to `out`.
+fn srec_record(out: &mut String, kind: char, addr: u16, data: &[u8]) {
+    let count = (data.len() + 3) as u8;
+    let [hi, lo] = addr.to_be_bytes();
+    let sum = data
+        .iter()
+        .fold(count.wrapping_add(hi).wrapping_add(lo), |sum, &byte| {
+            sum.wrapping_add(byte)
+        });
+    let _ = write!(out, "S{kind}{count:02X}{addr:04X}");
+    for byte in data {
+        let _ = write!(out, "{byte:02X}");
+    }
+    let _ = writeln!(out, "{:02X}", !sum);
+}
+
+/// Write `len` bytes of `mem` from `addr` to the file at `path`.
+pub fn save(
+    path: impl AsRef<Path>,
+    mem: &mut impl Memory,
+    addr: u16,
+    len: usize,
+) -> io::Result<()> {
+    let data = dump(mem, addr, len).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    fs::write(path, data)
+}
+
+/// Write `len` bytes of `mem` from `addr` to the file at `path` as
+/// S-records, as [`dump_srec`] does.
+pub fn save_srec(
+    path: impl AsRef<Path>,
+    mem: &mut impl Memory,
+    addr: u16,
+    len: usize,
+    entry: Option<u16>,
+) -> io::Result<()> {
+    let srec = dump_srec(mem, addr, len, entry)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    fs::write(path, srec)
+}
@@ -0,0 +1,98 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Motorola S-record files (`.s19`, `.srec`).
+//!
+//! Each line is `S`, a record type, a byte count, an address and data, all
+//! in hex, ending in a checksum that makes the bytes sum to `FF`:
+//!
+//! | Record | Address | Meaning                              |
+//! |--------|---------|--------------------------------------|
+//! | `S0`   | 2 bytes | Header, ignored                      |
+//! | `S1`   | 2 bytes | Data                                 |
+//! | `S2`   | 3 bytes | Data, below `10000` here             |
+//! | `S3`   | 4 bytes | Data, below `10000` here             |
+//! | `S5`   | 2 bytes | Record count, ignored                |
+//! | `S9`   | 2 bytes | Start address                        |
+//! | `S8`   | 3 bytes | Start address, below `10000` here    |
+//! | `S7`   | 4 bytes | Start address, below `10000` here    |
+
+use super::{FormatError, Image, Segment, hex_bytes, text_lines};
+use crate::memory::Memory;
+
+/// Parse S-records.
+///
+/// # Example
+/// ```
+/// use mc6809_core::formats::srec;
+///
+/// let image = srec::parse(b"S1050400862A46\nS9030400F8\n").unwrap();
+/// assert_eq!(image.segments[0].addr, 0x0400);
+/// assert_eq!(image.segments[0].data, [0x86, 0x2A]);
+/// assert_eq!(image.entry, Some(0x0400));
+/// ```
+pub fn parse(data: &[u8]) -> Result<Image, FormatError> {
+    let mut image = Image::default();
+    for (line, text) in text_lines(data) {
+        let bad = FormatError::BadLine { line };
+        let (kind, hex) = match text {
+            [b'S', kind, hex @ ..] => (*kind, hex),
+            _ => return Err(bad),
+        };
+        let bytes = hex_bytes(hex).ok_or(bad.clone())?;
+        let (&count, rest) = bytes.split_first().ok_or(bad.clone())?;
+        if count as usize != rest.len() || bytes.iter().fold(0u8, |s, &b| s.wrapping_add(b)) != 0xFF
+        {
+            return Err(bad);
+        }
+        let addr_len = match kind {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(bad),
+        };
+        let body = &rest[..rest.len() - 1];
+        let (addr, payload) = body.split_at_checked(addr_len).ok_or(bad.clone())?;
+        let addr = addr.iter().fold(0usize, |a, &b| a << 8 | b as usize);
+        match kind {
+            b'1' | b'2' | b'3' => {
+                if addr + payload.len() > 0x10000 {
+                    return Err(FormatError::OutOfRange {
+                        addr: addr as u16,
+                        len: payload.len(),
+                    });
+                }
+                image.segments.push(Segment {
+                    addr: addr as u16,
+                    data: payload.to_vec(),
+                });
+            }
+            b'7' | b'8' | b'9' => {
+                image.entry = Some(u16::try_from(addr).map_err(|_| bad)?);
+            }
+            _ => {}
+        }
+    }
+    Ok(image)
+}
+
+/// Parse S-records and write them to `mem`. Returns the start address, if
+/// the file has one.
+///
+/// Nothing is written if the file does not parse.
+pub fn load(data: &[u8], mem: &mut impl Memory) -> Result<Option<u16>, FormatError> {
+    let image = parse(data)?;
+    image.load_into(mem);
+    Ok(image.entry)
+}
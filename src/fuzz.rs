@@ -0,0 +1,156 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! [`Arbitrary`] inputs for fuzzing the CPU.
+//!
+//! [`Registers`] and [`ConditionCodes`] implement [`Arbitrary`], and
+//! [`CpuState`] bundles them with a variant and a short instruction stream
+//! into one bounded fuzz input. Requires the `arbitrary` feature.
+//!
+//! # A cargo-fuzz target
+//!
+//! After `cargo fuzz init`, depend on this crate with the `arbitrary`
+//! feature in `fuzz/Cargo.toml` and add a target such as
+//! `fuzz/fuzz_targets/execute.rs`:
+//!
+//! ```ignore
+//! #![no_main]
+//!
+//! use libfuzzer_sys::fuzz_target;
+//! use mc6809_core::fuzz::CpuState;
+//!
+//! fuzz_target!(|state: CpuState| {
+//!     state.run();
+//! });
+//! ```
+//!
+//! `cargo fuzz run execute` then feeds the decoder arbitrary instruction
+//! streams from arbitrary register states on both variants. A panic, an
+//! arithmetic overflow in a debug build or a hang is a bug.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::registers::{ConditionCodes, Registers};
+use crate::{Cpu, CpuVariant, FlatRam, Memory};
+
+impl<'a> Arbitrary<'a> for ConditionCodes {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_byte(u.arbitrary()?))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(1))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Registers {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            d: u.arbitrary()?,
+            x: u.arbitrary()?,
+            y: u.arbitrary()?,
+            u: u.arbitrary()?,
+            s: u.arbitrary()?,
+            pc: u.arbitrary()?,
+            dp: u.arbitrary()?,
+            cc: u.arbitrary()?,
+        })
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (12, Some(12))
+    }
+}
+
+impl<'a> Arbitrary<'a> for CpuVariant {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[Self::Mc6809, Self::Hd6309])?)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(4))
+    }
+}
+
+/// A CPU state and a program to run from it, small enough to execute
+/// quickly however it was generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CpuState {
+    /// Processor to emulate.
+    pub variant: CpuVariant,
+    /// Registers to start from.
+    pub registers: Registers,
+    /// Bytes placed at PC, wrapping at the top of memory; at most
+    /// [`Self::MAX_PROGRAM`] of them.
+    pub program: Vec<u8>,
+    /// Instructions to execute; at most [`Self::MAX_STEPS`].
+    pub steps: u16,
+}
+
+impl CpuState {
+    /// Longest program an arbitrary state carries.
+    pub const MAX_PROGRAM: usize = 256;
+    /// Most instructions an arbitrary state executes.
+    pub const MAX_STEPS: u16 = 1024;
+
+    /// A CPU with this state and zeroed RAM holding the program.
+    pub fn build(&self) -> (Cpu, FlatRam) {
+        let mut ram = FlatRam::new();
+        let mut addr = self.registers.pc;
+        for &byte in &self.program {
+            ram.write(addr, byte);
+            addr = addr.wrapping_add(1);
+        }
+        let mut cpu = Cpu::new();
+        cpu.set_variant(self.variant);
+        *cpu.registers_mut() = self.registers;
+        (cpu, ram)
+    }
+
+    /// [`Self::build`] and execute up to [`Self::steps`] instructions,
+    /// stopping early if the CPU halts. Returns the CPU and RAM afterwards.
+    ///
+    /// [`Self::steps`]: CpuState::steps
+    pub fn run(&self) -> (Cpu, FlatRam) {
+        let (mut cpu, mut ram) = self.build();
+        for _ in 0..self.steps.min(Self::MAX_STEPS) {
+            if cpu.halted() {
+                break;
+            }
+            cpu.step(&mut ram);
+        }
+        (cpu, ram)
+    }
+}
+
+impl<'a> Arbitrary<'a> for CpuState {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let variant = u.arbitrary()?;
+        let registers = u.arbitrary()?;
+        let steps = u.int_in_range(0..=Self::MAX_STEPS)?;
+        let len = u.arbitrary_len::<u8>()?.min(Self::MAX_PROGRAM);
+        let program = u.bytes(len)?.to_vec();
+        Ok(Self {
+            variant,
+            registers,
+            program,
+            steps,
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        let (fixed, _) = Registers::size_hint(depth);
+        (fixed + 1 + 2, None)
+    }
+}
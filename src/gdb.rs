@@ -0,0 +1,320 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Remote debugging with GDB through the [`gdbstub`] crate.
+//!
+//! [`GdbTarget`] wraps a [`Machine`] and implements the `gdbstub` target
+//! traits over it: register and memory access, software breakpoints,
+//! single step and continue. [`GdbEventLoop`] runs the machine between
+//! stops for [`GdbStub::run_blocking`], polling the connection for a
+//! Ctrl-C while it does.
+//!
+//! The register block GDB sees is [`Mc6809`]'s: `cc`, `a`, `b` and `dp` as
+//! one byte each, then `x`, `y`, `u`, `s` and `pc` as big-endian words, 14
+//! bytes in all. A 6809-aware GDB (such as `m6809-gdb`) or a frontend
+//! speaking the remote protocol directly can attach to it.
+//!
+//! Requires the `gdbstub` feature.
+//!
+//! # Example
+//! ```no_run
+//! use std::net::TcpListener;
+//!
+//! use gdbstub::stub::GdbStub;
+//! use mc6809_core::FlatRam;
+//! use mc6809_core::gdb::{GdbEventLoop, GdbTarget};
+//! use mc6809_core::machine::Machine;
+//!
+//! let mut target = GdbTarget::new(Machine::new(FlatRam::new()));
+//! let (stream, _) = TcpListener::bind("127.0.0.1:9001")?.accept()?;
+//! GdbStub::new(stream)
+//!     .run_blocking::<GdbEventLoop<_, _>>(&mut target)
+//!     .expect("gdb session failed");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`GdbStub::run_blocking`]: gdbstub::stub::GdbStub::run_blocking
+
+use std::convert::Infallible;
+use std::marker::PhantomData;
+
+use gdbstub::arch::Arch;
+use gdbstub::common::Signal;
+use gdbstub::conn::{Connection, ConnectionExt};
+use gdbstub::stub::SingleThreadStopReason;
+use gdbstub::stub::run_blocking::{BlockingEventLoop, Event, WaitForStopReasonError};
+use gdbstub::target::ext::base::BaseOps;
+use gdbstub::target::ext::base::singlethread::{
+    SingleThreadBase, SingleThreadResume, SingleThreadResumeOps, SingleThreadSingleStep,
+    SingleThreadSingleStepOps,
+};
+use gdbstub::target::ext::breakpoints::{
+    Breakpoints, BreakpointsOps, SwBreakpoint, SwBreakpointOps,
+};
+use gdbstub::target::{Target, TargetResult};
+
+use crate::bus::Bus;
+use crate::machine::Machine;
+use crate::registers::{ConditionCodes, Registers};
+
+/// Instructions run between polls of the connection while continuing.
+const POLL_INTERVAL: u32 = 1024;
+
+/// Size of the register block GDB reads and writes.
+const REGISTER_BYTES: usize = 14;
+
+/// The 6809 as a `gdbstub` architecture.
+pub enum Mc6809 {}
+
+impl Arch for Mc6809 {
+    type Usize = u16;
+    type Registers = Registers;
+    type BreakpointKind = usize;
+    type RegId = ();
+}
+
+impl gdbstub::arch::Registers for Registers {
+    type ProgramCounter = u16;
+
+    fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for byte in [self.cc.to_byte(), self.a(), self.b(), self.dp] {
+            write_byte(Some(byte));
+        }
+        for word in [self.x, self.y, self.u, self.s, self.pc] {
+            word.to_be_bytes()
+                .into_iter()
+                .for_each(|b| write_byte(Some(b)));
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        let bytes: &[u8; REGISTER_BYTES] = bytes.try_into().map_err(|_| ())?;
+        let word = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        self.cc = ConditionCodes::from_byte(bytes[0]);
+        self.d = u16::from_be_bytes([bytes[1], bytes[2]]);
+        self.dp = bytes[3];
+        self.x = word(4);
+        self.y = word(6);
+        self.u = word(8);
+        self.s = word(10);
+        self.pc = word(12);
+        Ok(())
+    }
+}
+
+/// How the machine runs when GDB resumes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExecMode {
+    Step,
+    Continue,
+}
+
+/// What stopped [`GdbTarget::run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunEvent {
+    /// The connection has data waiting, most likely a Ctrl-C.
+    IncomingData,
+    /// The machine stopped, for the reason to report to GDB.
+    Stopped(SingleThreadStopReason<u16>),
+}
+
+/// A [`Machine`] debugged over the GDB remote protocol.
+///
+/// Breakpoints are the CPU's own (see [`Cpu::add_breakpoint`]), so any set
+/// before the session are honoured too. Memory access goes through the
+/// bus as the CPU's would, including any side effects on I/O registers,
+/// and writes drop whatever the CPU's caches hold for the bytes written.
+///
+/// [`Cpu::add_breakpoint`]: crate::Cpu::add_breakpoint
+pub struct GdbTarget<B: Bus> {
+    machine: Machine<B>,
+    mode: ExecMode,
+}
+
+impl<B: Bus> GdbTarget<B> {
+    /// Debug `machine`.
+    pub fn new(machine: Machine<B>) -> Self {
+        Self {
+            machine,
+            mode: ExecMode::Continue,
+        }
+    }
+
+    /// The machine being debugged.
+    pub fn machine(&self) -> &Machine<B> {
+        &self.machine
+    }
+
+    /// The machine being debugged.
+    pub fn machine_mut(&mut self) -> &mut Machine<B> {
+        &mut self.machine
+    }
+
+    /// Give the machine back.
+    pub fn into_inner(self) -> Machine<B> {
+        self.machine
+    }
+
+    /// Run as GDB last asked: one instruction after a step, otherwise
+    /// until a breakpoint, a halt or `incoming_data` returns `true`.
+    /// `incoming_data` is polled every 1024 instructions.
+    ///
+    /// A continue always executes the instruction at PC, so resuming from
+    /// a breakpoint runs past it.
+    pub fn run(&mut self, mut incoming_data: impl FnMut() -> bool) -> RunEvent {
+        if self.mode == ExecMode::Step {
+            self.machine.step();
+            return RunEvent::Stopped(SingleThreadStopReason::DoneStep);
+        }
+        let mut polled = 0;
+        loop {
+            self.machine.step();
+            let cpu = self.machine.cpu();
+            if cpu.halted() {
+                return RunEvent::Stopped(SingleThreadStopReason::Signal(Signal::SIGSTOP));
+            }
+            let pc = cpu.registers().pc;
+            if cpu.breakpoints().any(|addr| addr == pc) {
+                return RunEvent::Stopped(SingleThreadStopReason::SwBreak(()));
+            }
+            polled += 1;
+            if polled == POLL_INTERVAL {
+                polled = 0;
+                if incoming_data() {
+                    return RunEvent::IncomingData;
+                }
+            }
+        }
+    }
+}
+
+impl<B: Bus> Target for GdbTarget<B> {
+    type Arch = Mc6809;
+    type Error = Infallible;
+
+    fn base_ops(&mut self) -> BaseOps<'_, Mc6809, Infallible> {
+        BaseOps::SingleThread(self)
+    }
+
+    fn support_breakpoints(&mut self) -> Option<BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl<B: Bus> SingleThreadBase for GdbTarget<B> {
+    fn read_registers(&mut self, regs: &mut Registers) -> TargetResult<(), Self> {
+        *regs = *self.machine.cpu().registers();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &Registers) -> TargetResult<(), Self> {
+        *self.machine.cpu_mut().registers_mut() = *regs;
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u16, data: &mut [u8]) -> TargetResult<usize, Self> {
+        let bus = self.machine.bus_mut();
+        for (addr, byte) in (start_addr..=u16::MAX).zip(data.iter_mut()) {
+            *byte = bus.read(addr);
+        }
+        Ok(data.len().min(0x10000 - start_addr as usize))
+    }
+
+    fn write_addrs(&mut self, start_addr: u16, data: &[u8]) -> TargetResult<(), Self> {
+        for (addr, &byte) in (start_addr..=u16::MAX).zip(data) {
+            self.machine.bus_mut().write(addr, byte);
+            let cpu = self.machine.cpu_mut();
+            cpu.invalidate_decode_cache(addr);
+            cpu.invalidate_block_cache(addr);
+        }
+        Ok(())
+    }
+
+    fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl<B: Bus> SingleThreadResume for GdbTarget<B> {
+    fn resume(&mut self, signal: Option<Signal>) -> Result<(), Infallible> {
+        let _ = signal;
+        self.mode = ExecMode::Continue;
+        Ok(())
+    }
+
+    fn support_single_step(&mut self) -> Option<SingleThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl<B: Bus> SingleThreadSingleStep for GdbTarget<B> {
+    fn step(&mut self, signal: Option<Signal>) -> Result<(), Infallible> {
+        let _ = signal;
+        self.mode = ExecMode::Step;
+        Ok(())
+    }
+}
+
+impl<B: Bus> Breakpoints for GdbTarget<B> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl<B: Bus> SwBreakpoint for GdbTarget<B> {
+    fn add_sw_breakpoint(&mut self, addr: u16, _kind: usize) -> TargetResult<bool, Self> {
+        self.machine.cpu_mut().add_breakpoint(addr);
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u16, _kind: usize) -> TargetResult<bool, Self> {
+        Ok(self.machine.cpu_mut().remove_breakpoint(addr))
+    }
+}
+
+/// The [`BlockingEventLoop`] for a [`GdbTarget`] on connection `C`: runs
+/// the machine with [`GdbTarget::run`], peeking at the connection for
+/// incoming data, and answers a Ctrl-C with `SIGINT`.
+pub struct GdbEventLoop<B, C>(PhantomData<(B, C)>);
+
+impl<B: Bus, C: ConnectionExt> BlockingEventLoop for GdbEventLoop<B, C> {
+    type Target = GdbTarget<B>;
+    type Connection = C;
+    type StopReason = SingleThreadStopReason<u16>;
+
+    #[allow(clippy::type_complexity)]
+    fn wait_for_stop_reason(
+        target: &mut GdbTarget<B>,
+        conn: &mut C,
+    ) -> Result<Event<Self::StopReason>, WaitForStopReasonError<Infallible, <C as Connection>::Error>>
+    {
+        // A connection that cannot be peeked counts as having data, so the
+        // read below reports its error.
+        match target.run(|| conn.peek().map_or(true, |byte| byte.is_some())) {
+            RunEvent::IncomingData => conn
+                .read()
+                .map(Event::IncomingData)
+                .map_err(WaitForStopReasonError::Connection),
+            RunEvent::Stopped(reason) => Ok(Event::TargetStopped(reason)),
+        }
+    }
+
+    fn on_interrupt(_target: &mut GdbTarget<B>) -> Result<Option<Self::StopReason>, Infallible> {
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+    }
+}
@@ -49,15 +49,50 @@
 
 pub mod addressing;
 pub mod alu;
+pub mod backtrace;
+pub mod batch;
+pub mod bus;
+pub mod coverage;
 mod cpu;
+pub mod devices;
+mod diag;
+pub mod disasm;
+pub mod formats;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "gdbstub")]
+pub mod gdb;
+pub mod machine;
+#[cfg(feature = "dragon32")]
+pub mod machines;
+pub mod mc6801;
 pub mod memory;
 pub mod peripheral;
+#[cfg(feature = "hooks")]
+pub mod profile;
 pub mod registers;
+pub mod snapshot;
+pub mod stats;
+pub mod symbols;
+pub mod testing;
+pub mod vectors;
 
-pub use cpu::{Cpu, RegistersMut, instruction_cycles};
-pub use memory::Memory;
+pub use bus::FlatRam;
+#[cfg(feature = "hooks")]
+pub use cpu::InstructionHook;
+pub use cpu::{
+    CodeWrite, CodeWriteHook, ControlHandle, Cpu, CpuConfig, CpuError, CpuState, CpuVariant,
+    HalfCarryQuirks, IllegalOpcodeHook, IllegalOpcodeMode, IllegalOpcodePolicy, InterruptHandle,
+    LastInstruction, OpcodeFetch, OpcodeHandler, OpcodeTranslator, RegistersMut, ResetState, Stack,
+    StackAccess, StackAction, StackHook, StackMonitor, StackViolation, StepInfo, StepResult,
+    StopReason, Swi, SwiAction, SwiHook, TransferQuirks, VectorTable, instruction_cycles,
+};
+pub use memory::{BusFault, Memory};
 pub use peripheral::{BusSignals, Clocked};
-pub use registers::{ConditionCodes, Registers};
+pub use registers::{
+    Condition, ConditionCodes, FlagDelta, Hd6309Registers, Reg, Registers, RegistersBuilder,
+};
+pub use snapshot::Snapshot;
 
 #[cfg(test)]
 mod tests;
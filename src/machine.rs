@@ -0,0 +1,581 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A CPU and its bus, driven by one run loop.
+
+use std::time::Duration;
+
+use crate::Cpu;
+use crate::batch::SeedRng;
+use crate::bus::Bus;
+use crate::peripheral::BusSignals;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+#[cfg(feature = "hooks")]
+use crate::stats::RunStats;
+
+mod cluster;
+mod idle;
+mod realtime;
+#[cfg(feature = "tokio")]
+mod run_async;
+mod scheduler;
+
+pub use cluster::{Cluster, ClusterBus};
+pub use idle::{IdleDetector, IdleStep, LoopDetector};
+pub use scheduler::{EventFn, EventId, Scheduler};
+
+/// Callback run after each [`Machine::step`] with the CPU, the bus and the
+/// cycles the step took.
+pub type StepHook<B> = Box<dyn FnMut(&mut Cpu, &mut B, u64) + Send>;
+
+/// Callback run at the end of each [`Machine::run_frame`] with the CPU, the
+/// bus and the number of the frame just finished, counting from 0.
+pub type FrameHook<B> = Box<dyn FnMut(&mut Cpu, &mut B, u64) + Send>;
+
+/// Leading bytes of a state written by [`Machine::save_state`]: a tag and the
+/// format version.
+const STATE_HEADER: &[u8] = b"M6809ST\x01";
+
+/// Handle for removing a hook added with [`Machine::add_step_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HookId(usize);
+
+/// A [`Cpu`] and the [`Bus`] it runs against.
+///
+/// [`Self::step`] is the host loop described under [`Clocked`](crate::Clocked):
+/// execute one instruction, tick the bus with the cycles it took, reset the
+/// CPU if the bus asserts RESET and otherwise apply any change in the
+/// interrupt lines, then run the events that have fallen due on the
+/// [`Scheduler`] and finally the step hooks.
+///
+/// The machine keeps its own cycle count ([`Self::cycles`]) that, unlike
+/// [`Cpu::cycles`], is not cleared by a reset; scheduled events are timed
+/// against it.
+///
+/// # Example
+/// ```
+/// use mc6809_core::FlatRam;
+/// use mc6809_core::machine::Machine;
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x4C, 0x20, 0xFD], 0x0400); // loop: INCA / BRA loop
+/// ram.set_reset_vector(0x0400);
+///
+/// let mut machine = Machine::new(ram);
+/// machine.run_for(50);
+/// assert_eq!(machine.cpu().registers().a(), 10);
+/// ```
+pub struct Machine<B: Bus> {
+    cpu: Cpu,
+    bus: B,
+    /// Signals applied to the CPU after the last tick.
+    signals: BusSignals,
+    /// Signals raised by ticks since [`Self::run_until_signal`] last looked.
+    raised: BusSignals,
+    hooks: Vec<Option<StepHook<B>>>,
+    scheduler: Scheduler<B>,
+    /// Cycles run since the machine was built.
+    cycles: u64,
+    /// Pacing state for [`Self::run_realtime`].
+    anchor: Option<realtime::Anchor>,
+    fast_forward: bool,
+    /// Machine cycle count at which the last frame ended.
+    frame_end: Option<u64>,
+    frames: u64,
+    frame_hook: Option<FrameHook<B>>,
+    /// Source of undefined register values after reset, in seeded mode.
+    rng: Option<SeedRng>,
+    idle: Option<Box<dyn IdleDetector>>,
+    max_idle_skip: u64,
+    idle_skipped: u64,
+    /// Machine cycle count the current run stops at, bounding idle skips.
+    run_end: Option<u64>,
+}
+
+impl<B: Bus> Machine<B> {
+    /// Build a machine around `bus` and reset the CPU from it.
+    pub fn new(bus: B) -> Self {
+        Self::with_cpu(Cpu::new(), bus)
+    }
+
+    /// Build a machine from a configured CPU (caches, hooks) and reset it
+    /// from `bus`.
+    pub fn with_cpu(mut cpu: Cpu, mut bus: B) -> Self {
+        cpu.reset(&mut bus);
+        Self {
+            cpu,
+            bus,
+            signals: BusSignals::default(),
+            raised: BusSignals::default(),
+            hooks: Vec::new(),
+            scheduler: Scheduler::new(),
+            cycles: 0,
+            anchor: None,
+            fast_forward: false,
+            frame_end: None,
+            frames: 0,
+            frame_hook: None,
+            rng: None,
+            idle: None,
+            max_idle_skip: u64::MAX,
+            idle_skipped: 0,
+            run_end: None,
+        }
+    }
+
+    /// Build a machine whose nondeterminism all derives from `seed`, so a
+    /// run (a fuzzing crash, say) replays exactly from the seed.
+    ///
+    /// After every reset, including one the bus asserts, the registers the
+    /// MC6809 leaves undefined are filled from the seed with
+    /// [`Cpu::randomize_undefined`]. Seed the bus the same way: derive one
+    /// seed per source with [`instance_seed`](crate::batch::instance_seed)
+    /// and pass it to [`MemoryMap::open_bus_seed`](crate::bus::MemoryMap::open_bus_seed)
+    /// or wrap devices in [`Jittered`](crate::devices::Jittered).
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::FlatRam;
+    /// use mc6809_core::machine::Machine;
+    ///
+    /// let a = Machine::seeded(FlatRam::new(), 1234);
+    /// let b = Machine::seeded(FlatRam::new(), 1234);
+    /// assert_eq!(a.cpu().registers().x, b.cpu().registers().x);
+    /// ```
+    pub fn seeded(bus: B, seed: u64) -> Self {
+        let mut machine = Self::new(bus);
+        machine.rng = Some(SeedRng::new(seed));
+        machine.randomize_undefined();
+        machine
+    }
+
+    /// Reset the CPU from the bus and forget the last bus signals.
+    pub fn reset(&mut self) {
+        self.cpu.reset(&mut self.bus);
+        self.signals = BusSignals::default();
+        self.randomize_undefined();
+    }
+
+    /// In seeded mode, fill the registers reset leaves undefined.
+    fn randomize_undefined(&mut self) {
+        if let Some(rng) = &mut self.rng {
+            self.cpu.randomize_undefined(rng);
+        }
+    }
+
+    /// The CPU.
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    /// Mutable access to the CPU.
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    /// The bus.
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Mutable access to the bus.
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Take the machine apart.
+    pub fn into_parts(self) -> (Cpu, B) {
+        (self.cpu, self.bus)
+    }
+
+    /// Cycles run since the machine was built, across resets.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Emulated time since the machine was built, across resets, at the
+    /// CPU's clock rate ([`Cpu::set_clock_hz`]).
+    pub fn elapsed(&self) -> Duration {
+        self.cpu.cycles_to_duration(self.cycles)
+    }
+
+    /// The event scheduler.
+    pub fn scheduler(&self) -> &Scheduler<B> {
+        &self.scheduler
+    }
+
+    /// Mutable access to the event scheduler, to add or cancel events.
+    pub fn scheduler_mut(&mut self) -> &mut Scheduler<B> {
+        &mut self.scheduler
+    }
+
+    /// Signals returned by the last bus tick.
+    pub fn signals(&self) -> BusSignals {
+        self.signals
+    }
+
+    /// Run `hook` after every step, after signals have been applied.
+    pub fn add_step_hook(
+        &mut self,
+        hook: impl FnMut(&mut Cpu, &mut B, u64) + Send + 'static,
+    ) -> HookId {
+        self.hooks.push(Some(Box::new(hook)));
+        HookId(self.hooks.len() - 1)
+    }
+
+    /// Remove and return a hook added with [`Self::add_step_hook`].
+    pub fn remove_step_hook(&mut self, id: HookId) -> Option<StepHook<B>> {
+        self.hooks.get_mut(id.0)?.take()
+    }
+
+    /// Execute one instruction (or interrupt entry, or wait cycle), tick the
+    /// bus and apply its signals. Returns the cycles consumed, including any
+    /// skipped as idle (see [`Self::set_idle_detector`]).
+    pub fn step(&mut self) -> u64 {
+        let (cycles, period) = match &mut self.idle {
+            None => (self.cpu.step(&mut self.bus), None),
+            Some(detector) => {
+                let pc = self.cpu.registers().pc;
+                let mut probe = idle::WriteProbe {
+                    mem: &mut self.bus,
+                    wrote: false,
+                };
+                let cycles = self.cpu.step(&mut probe);
+                let step = IdleStep {
+                    pc,
+                    registers: *self.cpu.registers(),
+                    cycles,
+                    wrote: probe.wrote,
+                };
+                (cycles, detector.observe(&step))
+            }
+        };
+        self.advance(cycles);
+        self.finish_step(cycles, period)
+    }
+
+    /// Skip whole idle-loop periods if allowed, then run the step hooks.
+    fn finish_step(&mut self, mut cycles: u64, period: Option<u64>) -> u64 {
+        if let Some(skip) = period.and_then(|period| self.idle_skip(period)) {
+            self.cpu.add_idle_cycles(skip);
+            self.advance(skip);
+            self.idle_skipped += skip;
+            cycles += skip;
+        }
+        for hook in self.hooks.iter_mut().flatten() {
+            hook(&mut self.cpu, &mut self.bus, cycles);
+        }
+        cycles
+    }
+
+    /// Cycles to skip for an idle loop of `period` cycles: whole periods up
+    /// to the next scheduled event, the end of the current run and the
+    /// skip limit, whichever comes first.
+    fn idle_skip(&self, period: u64) -> Option<u64> {
+        if period == 0
+            || self.cpu.interrupt_pending()
+            || self.signals.contains(BusSignals::DMA_BREQ)
+        {
+            return None;
+        }
+        let limit = [
+            self.scheduler.next_due(),
+            self.run_end,
+            self.cycles.checked_add(self.max_idle_skip),
+        ]
+        .into_iter()
+        .flatten()
+        .min()?;
+        let skip = limit.saturating_sub(self.cycles) / period * period;
+        (skip > 0).then_some(skip)
+    }
+
+    /// Tick the bus for `cycles`, act on its signals and run due events.
+    fn advance(&mut self, cycles: u64) {
+        let signals = self.bus.tick(cycles);
+        self.raised |= signals & !self.signals;
+        let mut disturbed = signals != self.signals;
+        if signals.contains(BusSignals::RESET) {
+            self.reset();
+        } else if signals != self.signals {
+            self.cpu.apply_signals(signals, self.signals);
+            self.signals = signals;
+        }
+        self.cycles += cycles;
+        disturbed |= self
+            .scheduler
+            .next_due()
+            .is_some_and(|due| due <= self.cycles);
+        self.scheduler
+            .dispatch(self.cycles, &mut self.cpu, &mut self.bus);
+        if disturbed && let Some(detector) = &mut self.idle {
+            detector.reset();
+        }
+    }
+
+    /// Step until at least `cycles` cycles have been consumed, the CPU
+    /// halts or a [`ControlHandle`](crate::ControlHandle) pauses it.
+    /// Returns the cycles consumed.
+    pub fn run_for(&mut self, cycles: u64) -> u64 {
+        let end = self.cycles + cycles;
+        let outer = self.run_end.replace(end);
+        let mut elapsed = 0;
+        while elapsed < cycles && !self.cpu.halted() && !self.cpu.paused() {
+            elapsed += self.step();
+        }
+        self.run_end = outer;
+        elapsed
+    }
+
+    /// Step until the bus raises `signal`, or until at least `max_cycles`
+    /// cycles have been consumed or [`Self::run_for`] would stop. Returns
+    /// `true` if `signal` ended the run.
+    ///
+    /// The run ends after the step whose tick raised the signal: returned it
+    /// when the previous tick had not. Several signals ORed together stop
+    /// on the first of them. Any signal will do, though the user-defined
+    /// [`BusSignals::EVENT0`] to [`BusSignals::EVENT2`], which the CPU
+    /// ignores, are meant for this.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::machine::Machine;
+    /// use mc6809_core::{BusSignals, Clocked, FlatRam, Memory};
+    ///
+    /// /// RAM that raises EVENT0 every 100 cycles, like a frame counter.
+    /// struct Frames(FlatRam, u64);
+    ///
+    /// impl Memory for Frames {
+    ///     fn read(&mut self, addr: u16) -> u8 {
+    ///         self.0.read(addr)
+    ///     }
+    ///     fn write(&mut self, addr: u16, val: u8) {
+    ///         self.0.write(addr, val)
+    ///     }
+    /// }
+    ///
+    /// impl Clocked for Frames {
+    ///     fn tick(&mut self, cycles: u64) -> BusSignals {
+    ///         let before = self.1 / 100;
+    ///         self.1 += cycles;
+    ///         if self.1 / 100 != before {
+    ///             BusSignals::EVENT0
+    ///         } else {
+    ///             BusSignals::default()
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x20, 0xFE], 0x0400); // BRA *
+    /// ram.set_reset_vector(0x0400);
+    /// let mut machine = Machine::new(Frames(ram, 0));
+    /// assert!(machine.run_until_signal(BusSignals::EVENT0, 1000));
+    /// assert_eq!(machine.cycles(), 102);
+    /// assert!(machine.run_until_signal(BusSignals::EVENT0, 1000));
+    /// assert_eq!(machine.cycles(), 201);
+    /// ```
+    pub fn run_until_signal(&mut self, signal: BusSignals, max_cycles: u64) -> bool {
+        let end = self.cycles + max_cycles;
+        let outer = self.run_end.replace(end);
+        self.raised = BusSignals::default();
+        let mut fired = false;
+        while self.cycles < end && !self.cpu.halted() && !self.cpu.paused() {
+            self.step();
+            if !(self.raised & signal).is_empty() {
+                fired = true;
+                break;
+            }
+        }
+        self.run_end = outer;
+        fired
+    }
+
+    /// [`Self::run_for`], summing the run up in [`RunStats`]. Cycles
+    /// skipped as idle count towards [`RunStats::cycles`] but not
+    /// [`RunStats::instructions`]. Breakpoints are counted as for
+    /// [`Cpu::run_with_stats`].
+    ///
+    /// Only available with the `hooks` feature.
+    #[cfg(feature = "hooks")]
+    pub fn run_for_with_stats(&mut self, cycles: u64) -> RunStats {
+        self.cpu.begin_run_stats();
+        let end = self.cycles + cycles;
+        let outer = self.run_end.replace(end);
+        let mut elapsed = 0;
+        while elapsed < cycles && !self.cpu.halted() && !self.cpu.paused() {
+            let pc = self.cpu.registers().pc;
+            self.cpu.at_breakpoint();
+            elapsed += self.step();
+            self.cpu.left_breakpoint(pc);
+        }
+        self.run_end = outer;
+        self.cpu.end_run_stats(elapsed)
+    }
+
+    /// [`Self::run_for`] the cycles `dt` of emulated time takes at the
+    /// CPU's clock rate ([`Cpu::set_clock_hz`]). Unlike
+    /// [`Self::run_realtime`], this does not wait for the wall clock.
+    pub fn run_for_duration(&mut self, dt: Duration) -> u64 {
+        self.run_for(self.cpu.duration_to_cycles(dt))
+    }
+
+    /// Look for idle loops with `detector` and skip them, or stop with
+    /// `None`.
+    ///
+    /// After a step the detector reports as idle, the machine adds whole
+    /// loop periods to the CPU's cycle count instead of executing them, up
+    /// to the next scheduled event, the end of the current
+    /// [`Self::run_for`] or [`Self::run_frame`] and
+    /// [`Self::set_max_idle_skip`], and ticks the bus with them in one call.
+    /// Nothing is skipped while an interrupt is pending or DMA holds the
+    /// bus, nor from a bare [`Self::step`] with no event scheduled and no
+    /// skip limit.
+    ///
+    /// Firmware that busy-waits on a timer or a flag runs far faster, and
+    /// exactly as before as long as the loop can only end through a
+    /// scheduled event. A device that interrupts or changes a polled value
+    /// on its own is only seen at the end of a skip, so bound skips with
+    /// [`Self::set_max_idle_skip`] to keep such devices' timing close.
+    pub fn set_idle_detector(&mut self, detector: Option<Box<dyn IdleDetector>>) {
+        self.idle = detector;
+    }
+
+    /// Skip at most `cycles` cycles at a time (default: unbounded).
+    pub fn set_max_idle_skip(&mut self, cycles: u64) {
+        self.max_idle_skip = cycles;
+    }
+
+    /// Cycles skipped as idle since the machine was built.
+    pub fn idle_skipped(&self) -> u64 {
+        self.idle_skipped
+    }
+
+    /// Run one frame of `cycles_per_frame` cycles and call the frame hook.
+    /// Returns the cycles run.
+    ///
+    /// Frames are laid end to end on the machine's cycle count: each ends
+    /// `cycles_per_frame` after the previous one ended, not after the last
+    /// instruction of the previous frame, so the cycles an instruction runs
+    /// past a boundary are taken out of the next frame and the frame rate
+    /// does not drift. If the machine has run past the next boundary by
+    /// other means (such as [`Self::run_for`]), the frame starts from the
+    /// current cycle count instead. Returns early if the CPU halts or is
+    /// paused.
+    pub fn run_frame(&mut self, cycles_per_frame: u64) -> u64 {
+        let start_cycles = self.cycles;
+        let start = match self.frame_end {
+            Some(end) if end + cycles_per_frame > self.cycles => end,
+            _ => self.cycles,
+        };
+        let end = start + cycles_per_frame;
+        let outer = self.run_end.replace(end);
+        while self.cycles < end && !self.cpu.halted() && !self.cpu.paused() {
+            self.step();
+        }
+        self.run_end = outer;
+        self.frame_end = Some(end);
+        if let Some(hook) = &mut self.frame_hook {
+            hook(&mut self.cpu, &mut self.bus, self.frames);
+        }
+        self.frames += 1;
+        self.cycles - start_cycles
+    }
+
+    /// Install or remove the callback run at the end of each
+    /// [`Self::run_frame`].
+    pub fn set_frame_hook(&mut self, hook: Option<FrameHook<B>>) {
+        self.frame_hook = hook;
+    }
+
+    /// Number of frames run with [`Self::run_frame`].
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+impl<B: Bus + Snapshot> Machine<B> {
+    /// Save the whole machine: the CPU, the machine's cycle and frame
+    /// counts, the scheduler and the bus with every device on it.
+    ///
+    /// Hooks, scheduled callbacks and configuration such as ROM images and
+    /// address decoding are not saved; [`Self::load_state`] expects a
+    /// machine built the same way.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::FlatRam;
+    /// use mc6809_core::machine::Machine;
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x4C, 0x20, 0xFD], 0x0400); // loop: INCA / BRA loop
+    /// ram.set_reset_vector(0x0400);
+    ///
+    /// let mut machine = Machine::new(ram);
+    /// machine.run_for(50);
+    /// let state = machine.save_state();
+    /// machine.run_for(50);
+    /// machine.load_state(&state).unwrap();
+    /// assert_eq!(machine.cpu().registers().a(), 10);
+    /// ```
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut out = StateWriter::new();
+        out.bytes(STATE_HEADER);
+        out.section(&self.cpu);
+        out.u64(self.cycles);
+        self.signals.snapshot(&mut out);
+        out.bool(self.frame_end.is_some());
+        out.u64(self.frame_end.unwrap_or(0));
+        out.u64(self.frames);
+        out.bool(self.rng.is_some());
+        if let Some(rng) = &self.rng {
+            rng.snapshot(&mut out);
+        }
+        out.section(&self.scheduler);
+        out.section(&self.bus);
+        out.into_bytes()
+    }
+
+    /// Restore a state written by [`Self::save_state`].
+    ///
+    /// Real-time pacing restarts from the load. If loading fails the
+    /// machine may be left partly restored; load a good state or reset it
+    /// before running on.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut state = StateReader::new(data);
+        if state.bytes(STATE_HEADER.len()) != Ok(STATE_HEADER) {
+            return Err(StateError::BadHeader);
+        }
+        state.section(&mut self.cpu)?;
+        self.cycles = state.u64()?;
+        self.signals.restore(&mut state)?;
+        let frame_end = state.bool()?;
+        let end = state.u64()?;
+        self.frame_end = frame_end.then_some(end);
+        self.frames = state.u64()?;
+        if state.bool()? != self.rng.is_some() {
+            return Err(StateError::Mismatch("seeded mode"));
+        }
+        if let Some(rng) = &mut self.rng {
+            rng.restore(&mut state)?;
+        }
+        state.section(&mut self.scheduler)?;
+        state.section(&mut self.bus)?;
+        if state.remaining() != 0 {
+            return Err(StateError::Mismatch("trailing data"));
+        }
+        self.anchor = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Several CPUs interleaved on shared or separate buses.
+
+use crate::Cpu;
+use crate::memory::Memory;
+use crate::peripheral::BusSignals;
+
+/// Buses seen by the CPUs of a [`Cluster`].
+///
+/// CPUs are identified by the index [`Cluster::add_cpu`] returned. The
+/// implementation decides which memory each CPU sees (the same for a shared
+/// bus, different for separate ones) and which devices each CPU's cycles
+/// clock. Cross-CPU interrupt wiring lives here too: a device written by one
+/// CPU can assert lines in the signals returned for another.
+pub trait ClusterBus {
+    /// Memory as seen by `cpu`.
+    fn memory(&mut self, cpu: usize) -> &mut dyn Memory;
+
+    /// Advance whatever `cpu` clocks by `cycles` of its own clock and return
+    /// the signals on its pins.
+    fn tick(&mut self, cpu: usize, cycles: u64) -> BusSignals;
+}
+
+struct Node {
+    cpu: Cpu,
+    clock_hz: u64,
+    /// Cycles run, across resets.
+    cycles: u64,
+    signals: BusSignals,
+}
+
+/// CPUs run in lockstep slices of emulated time against a [`ClusterBus`].
+///
+/// Time is measured in cycles of CPU 0. [`Self::run_for`] advances it in
+/// quanta ([`Self::set_quantum`], 64 cycles by default); within each quantum
+/// every CPU in turn runs until it has caught up with the time elapsed at its
+/// own clock rate. Smaller quanta tighten cross-CPU timing at some cost in
+/// speed. After each instruction the CPU's signals are applied as in
+/// [`Machine::step`](super::Machine::step), including RESET.
+///
+/// # Example
+/// ```
+/// use mc6809_core::machine::{Cluster, ClusterBus};
+/// use mc6809_core::{BusSignals, FlatRam, Memory};
+///
+/// /// Two CPUs with their own RAM. Writing $FF00 on CPU 0 raises FIRQ on CPU 1.
+/// struct Board { ram: [FlatRam; 2], firq: bool }
+///
+/// impl ClusterBus for Board {
+///     fn memory(&mut self, cpu: usize) -> &mut dyn Memory {
+///         &mut self.ram[cpu]
+///     }
+///
+///     fn tick(&mut self, cpu: usize, _cycles: u64) -> BusSignals {
+///         if cpu == 0 && self.ram[0].as_slice()[0xFF00] != 0 {
+///             self.firq = true;
+///         }
+///         if cpu == 1 && self.firq { BusSignals::FIRQ } else { BusSignals::default() }
+///     }
+/// }
+///
+/// let mut ram = [FlatRam::new(), FlatRam::new()];
+/// ram[0].load(&[0x86, 0x01, 0xB7, 0xFF, 0x00, 0x20, 0xFE], 0x0400); // LDA #1 / STA $FF00 / BRA *
+/// ram[1].load(&[0x1C, 0xBF, 0x20, 0xFE], 0x0400); // ANDCC #$BF / BRA *
+/// ram[1].set_vector(0xFFF6, 0x0600);
+/// ram[1].load(&[0x20, 0xFE], 0x0600); // FIRQ handler: BRA *
+/// for ram in &mut ram {
+///     ram.set_reset_vector(0x0400);
+/// }
+///
+/// let mut cluster = Cluster::new(Board { ram, firq: false });
+/// cluster.add_cpu(1_000_000);
+/// cluster.add_cpu(894_886);
+/// cluster.run_for(100);
+/// assert_eq!(cluster.cpu(1).registers().pc, 0x0600);
+/// ```
+pub struct Cluster<B> {
+    bus: B,
+    nodes: Vec<Node>,
+    quantum: u64,
+    /// Elapsed time in CPU 0 cycles.
+    time: u64,
+}
+
+impl<B: ClusterBus> Cluster<B> {
+    /// Create a cluster with no CPUs.
+    pub fn new(bus: B) -> Self {
+        Self {
+            bus,
+            nodes: Vec::new(),
+            quantum: 64,
+            time: 0,
+        }
+    }
+
+    /// Add a CPU clocked at `clock_hz` and reset it from its memory. Returns
+    /// its index.
+    ///
+    /// # Panics
+    /// Panics if `clock_hz` is zero.
+    pub fn add_cpu(&mut self, clock_hz: u64) -> usize {
+        self.add_configured_cpu(Cpu::new(), clock_hz)
+    }
+
+    /// [`Self::add_cpu`] with a pre-configured CPU (caches, hooks).
+    ///
+    /// # Panics
+    /// Panics if `clock_hz` is zero.
+    pub fn add_configured_cpu(&mut self, mut cpu: Cpu, clock_hz: u64) -> usize {
+        assert!(clock_hz > 0, "clock rate must be non-zero");
+        let index = self.nodes.len();
+        cpu.reset(&mut self.bus.memory(index));
+        self.nodes.push(Node {
+            cpu,
+            clock_hz,
+            cycles: 0,
+            signals: BusSignals::default(),
+        });
+        index
+    }
+
+    /// Set the interleaving quantum in CPU 0 cycles.
+    ///
+    /// # Panics
+    /// Panics if `cycles` is zero.
+    pub fn set_quantum(&mut self, cycles: u64) {
+        assert!(cycles > 0, "quantum must be non-zero");
+        self.quantum = cycles;
+    }
+
+    /// CPU `index`.
+    ///
+    /// # Panics
+    /// Panics if there is no such CPU, as do the other per-CPU accessors.
+    pub fn cpu(&self, index: usize) -> &Cpu {
+        &self.nodes[index].cpu
+    }
+
+    /// Mutable access to CPU `index`.
+    pub fn cpu_mut(&mut self, index: usize) -> &mut Cpu {
+        &mut self.nodes[index].cpu
+    }
+
+    /// Cycles CPU `index` has run, across resets.
+    pub fn cycles(&self, index: usize) -> u64 {
+        self.nodes[index].cycles
+    }
+
+    /// Elapsed time in CPU 0 cycles.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// The buses.
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Mutable access to the buses.
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Reset every CPU from its memory.
+    pub fn reset(&mut self) {
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            node.cpu.reset(&mut self.bus.memory(index));
+            node.signals = BusSignals::default();
+        }
+    }
+
+    /// Advance emulated time by `cycles` cycles of CPU 0.
+    pub fn run_for(&mut self, cycles: u64) {
+        let end = self.time + cycles;
+        while self.time < end {
+            self.time = (self.time + self.quantum).min(end);
+            for index in 0..self.nodes.len() {
+                let due = self.due(index);
+                while self.nodes[index].cycles < due {
+                    self.step(index);
+                }
+            }
+        }
+    }
+
+    /// Cycles CPU `index` should have run by the current time.
+    fn due(&self, index: usize) -> u64 {
+        let Some(master) = self.nodes.first() else {
+            return 0;
+        };
+        let hz = self.nodes[index].clock_hz as u128;
+        (self.time as u128 * hz / master.clock_hz as u128) as u64
+    }
+
+    fn step(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+        let cycles = node.cpu.step_dyn(self.bus.memory(index));
+        node.cycles += cycles;
+        let signals = self.bus.tick(index, cycles);
+        if signals.contains(BusSignals::RESET) {
+            node.cpu.reset(&mut self.bus.memory(index));
+            node.signals = BusSignals::default();
+        } else if signals != node.signals {
+            node.cpu.apply_signals(signals, node.signals);
+            node.signals = signals;
+        }
+    }
+}
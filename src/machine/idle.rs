@@ -0,0 +1,177 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Idle-loop detection for skipping busy-wait loops.
+
+use crate::Memory;
+use crate::memory::BusFault;
+use crate::registers::Registers;
+
+/// One executed step, as seen by an [`IdleDetector`].
+#[derive(Clone, Copy, Debug)]
+pub struct IdleStep {
+    /// PC before the step.
+    pub pc: u16,
+    /// Registers after the step.
+    pub registers: Registers,
+    /// Cycles the step took.
+    pub cycles: u64,
+    /// Whether the step wrote to memory.
+    pub wrote: bool,
+}
+
+/// Recognises loops in which the CPU only waits, for
+/// [`Machine::set_idle_detector`](super::Machine::set_idle_detector).
+///
+/// The machine shows the detector every step. Returning `Some(period)`
+/// claims that the CPU is in a loop that repeats every `period` cycles and
+/// changes nothing, so that until an interrupt or a change in memory made
+/// from outside the CPU, running it on would only add cycles.
+pub trait IdleDetector: Send {
+    /// Observe one step; return the loop period in cycles if the CPU is idle.
+    fn observe(&mut self, step: &IdleStep) -> Option<u64>;
+
+    /// Forget what has been observed. The machine calls this when a
+    /// scheduled event has run or the bus signals have changed, since either
+    /// may have changed what the loop is waiting for.
+    fn reset(&mut self) {}
+}
+
+/// Where a candidate loop was last entered.
+struct LoopHead {
+    registers: Registers,
+    /// Cycles and writes since the head was recorded.
+    cycles: u64,
+    wrote: bool,
+}
+
+/// [`IdleDetector`] that watches backward branches and jumps.
+///
+/// Each time control goes back to an address at or before the instruction
+/// that took it there, the detector compares the registers with the last
+/// time it arrived at that address. If every register, PC and CC included,
+/// is the same and nothing was written in between, one iteration of the loop
+/// changed nothing and the CPU is idle. That covers `BRA *`, polling loops
+/// such as `loop: LDA $FF00 / BPL loop` while the polled value holds still,
+/// and SYNC or CWAI waits; loops that count, or store anything, never match.
+///
+/// Reads are assumed to have no side effects that matter when repeated, as
+/// for status registers that are polled.
+///
+/// # Example
+/// ```
+/// use mc6809_core::FlatRam;
+/// use mc6809_core::machine::{LoopDetector, Machine};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x20, 0xFE], 0x0400); // BRA *
+/// ram.set_reset_vector(0x0400);
+///
+/// let mut machine = Machine::new(ram);
+/// machine.set_idle_detector(Some(Box::new(LoopDetector::new())));
+/// machine.run_for(1_000_000);
+/// assert!(machine.idle_skipped() > 990_000);
+/// ```
+pub struct LoopDetector {
+    max_period: u64,
+    head: Option<LoopHead>,
+}
+
+impl LoopDetector {
+    /// Longest loop the detector looks for, in cycles, by default.
+    pub const DEFAULT_MAX_PERIOD: u64 = 256;
+
+    /// Create a detector for loops of up to [`Self::DEFAULT_MAX_PERIOD`]
+    /// cycles.
+    pub fn new() -> Self {
+        Self::with_max_period(Self::DEFAULT_MAX_PERIOD)
+    }
+
+    /// Create a detector for loops of up to `max_period` cycles.
+    pub fn with_max_period(max_period: u64) -> Self {
+        Self {
+            max_period,
+            head: None,
+        }
+    }
+}
+
+impl Default for LoopDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleDetector for LoopDetector {
+    fn observe(&mut self, step: &IdleStep) -> Option<u64> {
+        if let Some(head) = &mut self.head {
+            head.cycles += step.cycles;
+            head.wrote |= step.wrote;
+            if head.cycles > self.max_period {
+                self.head = None;
+            }
+        }
+        if step.registers.pc > step.pc {
+            return None;
+        }
+        let period = match &self.head {
+            Some(head) if head.registers == step.registers && !head.wrote => Some(head.cycles),
+            _ => None,
+        };
+        self.head = Some(LoopHead {
+            registers: step.registers,
+            cycles: 0,
+            wrote: false,
+        });
+        period
+    }
+
+    fn reset(&mut self) {
+        self.head = None;
+    }
+}
+
+/// Memory wrapper that notes whether anything was written.
+pub(super) struct WriteProbe<'a, M> {
+    pub(super) mem: &'a mut M,
+    pub(super) wrote: bool,
+}
+
+impl<M: Memory> Memory for WriteProbe<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.wrote = true;
+        self.mem.write(addr, val);
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        self.mem.read_word(addr)
+    }
+
+    fn write_word(&mut self, addr: u16, val: u16) {
+        self.wrote = true;
+        self.mem.write_word(addr, val);
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        self.mem.read_slice(addr, buf);
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.mem.take_fault()
+    }
+}
@@ -0,0 +1,112 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Wall-clock throttled execution.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::Machine;
+use crate::bus::Bus;
+
+/// Longest the emulation may fall behind wall-clock time before the lost
+/// time is dropped instead of being caught up at full speed.
+const MAX_LAG: Duration = Duration::from_millis(100);
+
+/// Wall-clock time covered by each batch of cycles between pacing checks.
+const SLICE: Duration = Duration::from_millis(1);
+
+/// Point at which emulated and wall-clock time were last lined up.
+#[derive(Clone, Copy)]
+pub(super) struct Anchor {
+    clock_hz: f64,
+    instant: Instant,
+    cycles: u64,
+}
+
+impl<B: Bus> Machine<B> {
+    /// Run for `wall` of wall-clock time, pacing execution so that emulated
+    /// time advances at `clock_hz` cycles per second (for example
+    /// `894_886.0` for a Color Computer). Returns the cycles run.
+    ///
+    /// Execution proceeds in slices of about a millisecond of emulated time,
+    /// sleeping whenever the machine is ahead of the wall clock. Pacing
+    /// carries over between calls, so a frontend calling this once per frame
+    /// gets no drift from per-call rounding. If the host cannot keep up and
+    /// falls more than 100 ms behind, the backlog is dropped rather than run
+    /// in a burst. With fast-forward on ([`Self::set_fast_forward`]) there is
+    /// no sleeping and execution runs as fast as the host allows.
+    ///
+    /// Stops early if the CPU halts or a
+    /// [`ControlHandle`](crate::ControlHandle) pauses it.
+    ///
+    /// # Panics
+    /// Panics if `clock_hz` is not positive.
+    pub fn run_realtime(&mut self, clock_hz: f64, wall: Duration) -> u64 {
+        assert!(clock_hz > 0.0, "clock rate must be positive");
+        let start = Instant::now();
+        let start_cycles = self.cycles;
+        let slice = ((clock_hz * SLICE.as_secs_f64()) as u64).max(1);
+
+        while !self.cpu.halted() && !self.cpu.paused() {
+            let now = Instant::now();
+            let left = wall.saturating_sub(now - start);
+            if left.is_zero() {
+                break;
+            }
+            if self.fast_forward {
+                self.anchor = None;
+                self.run_for(slice);
+                continue;
+            }
+
+            let anchor = match self.anchor {
+                Some(anchor) if anchor.clock_hz == clock_hz => anchor,
+                _ => *self.anchor.insert(Anchor {
+                    clock_hz,
+                    instant: now,
+                    cycles: self.cycles,
+                }),
+            };
+            let since = now - anchor.instant;
+            let due = anchor.cycles + (since.as_secs_f64() * clock_hz) as u64;
+            if self.cycles < due {
+                let behind = Duration::from_secs_f64((due - self.cycles) as f64 / clock_hz);
+                if behind > MAX_LAG {
+                    self.anchor = Some(Anchor {
+                        clock_hz,
+                        instant: now,
+                        cycles: self.cycles,
+                    });
+                }
+                self.run_for((due - self.cycles).min(slice));
+            } else {
+                let ahead = Duration::from_secs_f64((self.cycles - due) as f64 / clock_hz);
+                thread::sleep(ahead.max(Duration::from_micros(50)).min(left));
+            }
+        }
+        self.cycles - start_cycles
+    }
+
+    /// Turn fast-forward on or off for [`Self::run_realtime`]. Pacing picks
+    /// up from the current time when it is turned off again.
+    pub fn set_fast_forward(&mut self, enabled: bool) {
+        self.fast_forward = enabled;
+    }
+
+    /// `true` if fast-forward is on.
+    pub fn fast_forward(&self) -> bool {
+        self.fast_forward
+    }
+}
@@ -0,0 +1,66 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Async run loop for the tokio runtime.
+
+use tokio::task;
+
+use super::Machine;
+use crate::bus::Bus;
+
+impl<B: Bus> Machine<B> {
+    /// [`Self::run_for`] as a future that yields to the tokio executor
+    /// every `yield_every` cycles, so a machine can run inside an async
+    /// server without a thread of its own. Returns the cycles consumed.
+    ///
+    /// Emulation itself stays synchronous: devices backed by async I/O,
+    /// such as a serial port on a network socket, exchange data with the
+    /// machine between calls, through host-side buffers like
+    /// [`Mc6850::receive`](crate::devices::Mc6850::receive) and
+    /// [`Mc6850::take_output`](crate::devices::Mc6850::take_output).
+    /// Stops early if the CPU halts or is paused.
+    ///
+    /// Requires the `tokio` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::machine::Machine;
+    /// use mc6809_core::{FlatRam, Memory};
+    ///
+    /// let mut ram = FlatRam::new();
+    /// ram.load(&[0x4C, 0x20, 0xFD], 0x0400); // INCA / BRA
+    /// ram.set_reset_vector(0x0400);
+    /// let mut machine = Machine::new(ram);
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread()
+    ///     .build()
+    ///     .unwrap();
+    /// let cycles = runtime.block_on(machine.run_async(10_000, 1_000));
+    /// assert_eq!(cycles, 10_000);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `yield_every` is zero.
+    pub async fn run_async(&mut self, cycles: u64, yield_every: u64) -> u64 {
+        assert!(yield_every > 0, "yield interval must be positive");
+        let mut elapsed = 0;
+        while elapsed < cycles && !self.cpu.halted() && !self.cpu.paused() {
+            elapsed += self.run_for((cycles - elapsed).min(yield_every));
+            if elapsed < cycles {
+                task::yield_now().await;
+            }
+        }
+        elapsed
+    }
+}
@@ -0,0 +1,201 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Cycle-domain event scheduler.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::Cpu;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Callback run by the [`Scheduler`] with the CPU, the bus and the cycle the
+/// event was due at.
+pub type EventFn<B> = Box<dyn FnMut(&mut Cpu, &mut B, u64) + Send>;
+
+/// Handle for cancelling a scheduled event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventId(u64);
+
+struct Event<B> {
+    callback: EventFn<B>,
+    period: Option<u64>,
+}
+
+/// Callbacks keyed to points on the machine's cycle count.
+///
+/// Events run in order of their due cycle, and events due on the same cycle
+/// run in the order they were scheduled. A periodic event keeps that place
+/// among its peers each time it is rescheduled. The
+/// [`Machine`](super::Machine) dispatches events after each step, so an
+/// event runs at the end of the instruction during which it fell due.
+pub struct Scheduler<B> {
+    /// Pending events keyed by due cycle, then by id.
+    events: BTreeMap<(u64, EventId), Event<B>>,
+    /// Due cycle of each pending event.
+    due: HashMap<EventId, u64>,
+    next_id: u64,
+    now: u64,
+}
+
+impl<B> Scheduler<B> {
+    /// Create an empty scheduler at cycle 0.
+    pub fn new() -> Self {
+        Self {
+            events: BTreeMap::new(),
+            due: HashMap::new(),
+            next_id: 0,
+            now: 0,
+        }
+    }
+
+    /// The cycle count the scheduler was last advanced to.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Run `f` once when the cycle count reaches `cycle`. Events scheduled in
+    /// the past run on the next dispatch.
+    pub fn at_cycle(
+        &mut self,
+        cycle: u64,
+        f: impl FnMut(&mut Cpu, &mut B, u64) + Send + 'static,
+    ) -> EventId {
+        self.insert(cycle, Box::new(f), None)
+    }
+
+    /// Run `f` once, `delay` cycles from now.
+    pub fn after(
+        &mut self,
+        delay: u64,
+        f: impl FnMut(&mut Cpu, &mut B, u64) + Send + 'static,
+    ) -> EventId {
+        self.insert(self.now + delay, Box::new(f), None)
+    }
+
+    /// Run `f` every `period` cycles, starting `period` cycles from now.
+    ///
+    /// # Panics
+    /// Panics if `period` is zero.
+    pub fn every(
+        &mut self,
+        period: u64,
+        f: impl FnMut(&mut Cpu, &mut B, u64) + Send + 'static,
+    ) -> EventId {
+        assert!(period > 0, "event period must be non-zero");
+        self.insert(self.now + period, Box::new(f), Some(period))
+    }
+
+    /// Cancel a pending event. Returns `false` if it already ran (one-shot)
+    /// or was cancelled before.
+    pub fn cancel(&mut self, id: EventId) -> bool {
+        match self.due.remove(&id) {
+            Some(cycle) => self.events.remove(&(cycle, id)).is_some(),
+            None => false,
+        }
+    }
+
+    /// Due cycle of the earliest pending event.
+    pub fn next_due(&self) -> Option<u64> {
+        self.events.keys().next().map(|&(cycle, _)| cycle)
+    }
+
+    /// Number of pending events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// `true` if no events are pending.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Advance to `now` and run every event due by then, in order.
+    pub fn dispatch(&mut self, now: u64, cpu: &mut Cpu, bus: &mut B) {
+        self.now = now;
+        while let Some(entry) = self.events.first_entry() {
+            let (cycle, id) = *entry.key();
+            if cycle > now {
+                break;
+            }
+            let mut event = entry.remove();
+            (event.callback)(cpu, bus, cycle);
+            match event.period {
+                Some(period) => {
+                    self.due.insert(id, cycle + period);
+                    self.events.insert((cycle + period, id), event);
+                }
+                None => {
+                    self.due.remove(&id);
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, cycle: u64, callback: EventFn<B>, period: Option<u64>) -> EventId {
+        let id = EventId(self.next_id);
+        self.next_id += 1;
+        self.due.insert(id, cycle);
+        self.events.insert((cycle, id), Event { callback, period });
+        id
+    }
+}
+
+/// Saves the current cycle and when each pending event is next due. Callbacks
+/// cannot be saved: loading re-times the events of this scheduler that have
+/// the saved ids and drops those that were no longer pending when the state
+/// was saved, so the scheduler must have been set up the same way as the one
+/// that saved the state.
+impl<B> Snapshot for Scheduler<B> {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u64(self.now);
+        out.u64(self.next_id);
+        out.u32(self.events.len() as u32);
+        for &(cycle, id) in self.events.keys() {
+            out.u64(id.0);
+            out.u64(cycle);
+        }
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        let now = state.u64()?;
+        let next_id = state.u64()?;
+        let count = state.u32()?;
+        let mut due = HashMap::new();
+        for _ in 0..count {
+            let id = EventId(state.u64()?);
+            let cycle = state.u64()?;
+            if !self.due.contains_key(&id) || due.insert(id, cycle).is_some() {
+                return Err(StateError::Mismatch("scheduled event"));
+            }
+        }
+        let mut events: HashMap<EventId, Event<B>> = std::mem::take(&mut self.events)
+            .into_iter()
+            .map(|((_, id), event)| (id, event))
+            .collect();
+        for (&id, &cycle) in &due {
+            let event = events.remove(&id).expect("event checked above");
+            self.events.insert((cycle, id), event);
+        }
+        self.due = due;
+        self.now = now;
+        self.next_id = next_id;
+        Ok(())
+    }
+}
+
+impl<B> Default for Scheduler<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,384 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Dragon 32 reference machine.
+//!
+//! The Tandy Color Computer 2 is the same design apart from its keyboard
+//! matrix and NTSC video timing, so it needs only those two changes.
+//!
+//! The machine is an [`Sam`] holding RAM and the BASIC ROM, with two
+//! [`Mc6821`] PIAs mapped over its I/O area on a [`CompositeBus`]:
+//!
+//! | Range         | Device                                                   |
+//! |---------------|----------------------------------------------------------|
+//! | `C000`–`FEFF` | cartridge, if one is inserted                            |
+//! | `FF00`–`FF1F` | PIA 0: keyboard, HS on CA1, FS on CB1; IRQ               |
+//! | `FF20`–`FF3F` | PIA 1: sound, printer, VDG mode, CART on CB1; FIRQ       |
+//! | `FFC0`–`FFDF` | SAM control register                                     |
+//!
+//! The VDG is reduced to [`Dragon32::screen_text`], which reads the text
+//! screen out of RAM, and cassette, sound and joysticks are not connected.
+//! The aim is just enough hardware for BASIC to boot, print its banner and
+//! take typed input; the module doubles as a template for assembling other
+//! machines.
+
+use crate::Cpu;
+use crate::bus::{CompositeBus, DeviceId, Mmio};
+use crate::devices::{Cartridge, Mc6821, Sam};
+use crate::machine::Machine;
+use crate::peripheral::{BusSignals, Clocked};
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// CPU clock: the 14.218 MHz PAL crystal divided by 16.
+pub const CPU_HZ: u64 = 888_625;
+
+/// CPU cycles per video line.
+pub const CYCLES_PER_LINE: u64 = 57;
+
+/// Video lines per PAL frame.
+pub const LINES_PER_FRAME: u64 = 312;
+
+/// CPU cycles per video frame, about 50 per second.
+pub const CYCLES_PER_FRAME: u64 = CYCLES_PER_LINE * LINES_PER_FRAME;
+
+/// Text screen width in characters.
+pub const SCREEN_COLUMNS: usize = 32;
+
+/// Text screen height in characters.
+pub const SCREEN_ROWS: usize = 16;
+
+/// Keyboard matrix: `KEYS[row][column]`, rows on PIA 0 port A and columns
+/// strobed low on port B. `\r` is ENTER, `\x0C` CLEAR and `\x03` BREAK;
+/// `\0` marks SHIFT and unused positions.
+const KEYS: [[char; 8]; 7] = [
+    ['0', '1', '2', '3', '4', '5', '6', '7'],
+    ['8', '9', ':', ';', ',', '-', '.', '/'],
+    ['@', 'A', 'B', 'C', 'D', 'E', 'F', 'G'],
+    ['H', 'I', 'J', 'K', 'L', 'M', 'N', 'O'],
+    ['P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W'],
+    ['X', 'Y', 'Z', '↑', '↓', '←', '→', ' '],
+    ['\r', '\x0C', '\x03', '\0', '\0', '\0', '\0', '\0'],
+];
+
+/// Matrix position of `key` as (row, column).
+fn key_position(key: char) -> Option<(usize, usize)> {
+    let key = match key.to_ascii_uppercase() {
+        '\n' => '\r',
+        '\x08' => '←',
+        key => key,
+    };
+    if key == '\0' {
+        return None;
+    }
+    KEYS.iter().enumerate().find_map(|(row, keys)| {
+        let column = keys.iter().position(|&k| k == key)?;
+        Some((row, column))
+    })
+}
+
+/// Printable character for a VDG alphanumeric code. Semigraphics show as
+/// spaces; inverse video is ignored.
+fn vdg_char(code: u8) -> char {
+    if code & 0x80 != 0 {
+        return ' ';
+    }
+    match code & 0x3F {
+        c @ 0x00..=0x1F => (c + 0x40) as char,
+        c => c as char,
+    }
+}
+
+/// PIA 0 with the keyboard on its ports and video sync on its C1 lines.
+struct SystemPia {
+    pia: Mc6821,
+    /// Pressed keys: one bit per row for each column.
+    keys: [u8; 8],
+    /// Cycles into the current video line and lines into the frame.
+    line_cycles: u64,
+    line: u64,
+}
+
+impl SystemPia {
+    fn new() -> Self {
+        Self {
+            pia: Mc6821::new(BusSignals::IRQ),
+            keys: [0; 8],
+            line_cycles: 0,
+            line: 0,
+        }
+    }
+
+    /// Rows pulled low by pressed keys in the columns strobed on port B.
+    fn rows(&self) -> u8 {
+        let strobe = !self.pia.port_b();
+        let pressed = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|&(column, _)| strobe & (1 << column) != 0)
+            .fold(0, |rows, (_, &keys)| rows | keys);
+        !pressed
+    }
+}
+
+impl Mmio for SystemPia {
+    fn read(&mut self, offset: u16) -> u8 {
+        if offset & 3 == 0 {
+            let rows = self.rows();
+            self.pia.set_input_a(rows);
+        }
+        self.pia.read(offset)
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        self.pia.write(offset, val);
+    }
+}
+
+impl Clocked for SystemPia {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        self.line_cycles += cycles;
+        while self.line_cycles >= CYCLES_PER_LINE {
+            self.line_cycles -= CYCLES_PER_LINE;
+            // HS pulses low at the end of every line; FS once a frame.
+            self.pia.set_ca1(false);
+            self.pia.set_ca1(true);
+            self.line = (self.line + 1) % LINES_PER_FRAME;
+            if self.line == 0 {
+                self.pia.set_cb1(false);
+                self.pia.set_cb1(true);
+            }
+        }
+        self.pia.tick(cycles)
+    }
+}
+
+/// PIA 1 with the cartridge port's CART line on CB1.
+struct CartPia {
+    pia: Mc6821,
+    /// Whether an autostart cartridge is toggling CART.
+    cart: bool,
+}
+
+impl Mmio for CartPia {
+    fn read(&mut self, offset: u16) -> u8 {
+        self.pia.read(offset)
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        self.pia.write(offset, val);
+    }
+}
+
+impl Clocked for CartPia {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        if self.cart {
+            // CART follows the Q clock, so there is an edge every cycle.
+            self.pia.set_cb1(false);
+            self.pia.set_cb1(true);
+        }
+        self.pia.tick(cycles)
+    }
+}
+
+/// Saves the PIA; whether a cartridge is inserted is configuration.
+impl Snapshot for CartPia {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.pia.snapshot(out);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.pia.restore(state)
+    }
+}
+
+/// Saves the PIA and the video timing; held keys are host input and are
+/// not saved.
+impl Snapshot for SystemPia {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.pia.snapshot(out);
+        out.u64(self.line_cycles);
+        out.u64(self.line);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.pia.restore(state)?;
+        self.line_cycles = state.u64()?;
+        self.line = state.u64()? % LINES_PER_FRAME;
+        Ok(())
+    }
+}
+
+/// A Dragon 32 running a user-supplied BASIC ROM.
+///
+/// # Example
+/// ```
+/// use mc6809_core::machines::dragon32::Dragon32;
+///
+/// // A stand-in ROM that shows the screen at $0400, as BASIC does, and
+/// // writes "HI" to its top line:
+/// // STA $FFC9 (SAM F1) / LDD #$4849 / STD $0400 / BRA *
+/// let mut rom = vec![0x12; 0x4000];
+/// rom[..11].copy_from_slice(&[
+///     0xB7, 0xFF, 0xC9, 0xCC, 0x48, 0x49, 0xFD, 0x04, 0x00, 0x20, 0xFE,
+/// ]);
+/// rom[0x3FFE..].copy_from_slice(&[0x80, 0x00]); // reset vector
+///
+/// let mut dragon = Dragon32::new(&rom);
+/// dragon.run_frame();
+/// assert!(dragon.screen_text().starts_with("HI"));
+/// ```
+pub struct Dragon32 {
+    machine: Machine<CompositeBus<Sam>>,
+    pia0: DeviceId,
+    pia1: DeviceId,
+    cartridge: Option<DeviceId>,
+}
+
+impl Dragon32 {
+    /// Build the machine with `rom` at `0x8000` and reset it.
+    ///
+    /// # Panics
+    /// Panics if the ROM does not fit in `0x8000..0xFF00`.
+    pub fn new(rom: &[u8]) -> Self {
+        let mut bus = CompositeBus::new(Sam::new().rom(0x8000, rom));
+        let pia0 = bus.add_device(SystemPia::new());
+        bus.map(pia0, 0xFF00, 0x20, 0);
+        let pia1 = bus.add_device(CartPia {
+            pia: Mc6821::new(BusSignals::FIRQ),
+            cart: false,
+        });
+        bus.map(pia1, 0xFF20, 0x20, 0);
+        let mut cpu = Cpu::new();
+        cpu.set_clock_hz(CPU_HZ);
+        Self {
+            machine: Machine::with_cpu(cpu, bus),
+            pia0,
+            pia1,
+            cartridge: None,
+        }
+    }
+
+    /// Plug `cartridge` into the cartridge port at `0xC000`, replacing any
+    /// cartridge already there.
+    ///
+    /// An [`autostart`](Cartridge::autostart) cartridge drives CART, which
+    /// reaches the CPU through PIA 1 CB1 rather than as FIRQ straight from
+    /// the cartridge. Once BASIC has initialised and enabled the CB1
+    /// interrupt, the FIRQ it raises makes BASIC jump to `0xC000`; reset
+    /// the machine to autostart a cartridge plugged in afterwards.
+    pub fn insert_cartridge(&mut self, mut cartridge: Cartridge) {
+        let cart = cartridge.is_autostart();
+        cartridge.set_autostart(false);
+        let bus = self.machine.bus_mut();
+        match self.cartridge {
+            Some(id) => {
+                *bus.device_as::<Cartridge>(id)
+                    .expect("the cartridge slot holds a cartridge") = cartridge;
+            }
+            None => {
+                let id = bus.add_device(cartridge);
+                bus.map(id, Cartridge::BASE, 0xFF00 - Cartridge::BASE as usize, 0);
+                self.cartridge = Some(id);
+            }
+        }
+        self.cart_pia().cart = cart;
+    }
+
+    /// Press the reset button: reset the SAM, both PIAs and the CPU. RAM
+    /// survives, so BASIC warm-starts.
+    pub fn reset(&mut self) {
+        let bus = self.machine.bus_mut();
+        bus.base_mut().reset();
+        if let Some(system) = bus.device_as::<SystemPia>(self.pia0) {
+            system.pia.reset();
+        }
+        if let Some(cart_pia) = bus.device_as::<CartPia>(self.pia1) {
+            cart_pia.pia.reset();
+        }
+        self.machine.reset();
+    }
+
+    /// Run one video frame ([`CYCLES_PER_FRAME`] cycles). Returns the cycles
+    /// run.
+    pub fn run_frame(&mut self) -> u64 {
+        self.machine.run_frame(CYCLES_PER_FRAME)
+    }
+
+    /// The text screen as [`SCREEN_ROWS`] lines of [`SCREEN_COLUMNS`]
+    /// characters, each ending in `\n`, read from the RAM the SAM displays.
+    pub fn screen_text(&self) -> String {
+        let sam = self.machine.bus().base();
+        let start = sam.display_offset() as usize;
+        let screen = &sam.ram()[start..start + SCREEN_COLUMNS * SCREEN_ROWS];
+        let mut text = String::with_capacity((SCREEN_COLUMNS + 1) * SCREEN_ROWS);
+        for row in screen.chunks(SCREEN_COLUMNS) {
+            text.extend(row.iter().map(|&code| vdg_char(code)));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Hold down the key that types `key` unshifted: digits, letters,
+    /// `@:;,-./` and space, the arrows `↑↓←→` (`\x08` also means `←`), `\n`
+    /// or `\r` for ENTER, `\x0C` for CLEAR or `\x03` for BREAK. Returns
+    /// `false` if no key types it.
+    pub fn key_down(&mut self, key: char) -> bool {
+        self.set_key(key, true)
+    }
+
+    /// Release a key held with [`Self::key_down`].
+    pub fn key_up(&mut self, key: char) -> bool {
+        self.set_key(key, false)
+    }
+
+    /// The machine, for running it directly or saving its state.
+    pub fn machine(&self) -> &Machine<CompositeBus<Sam>> {
+        &self.machine
+    }
+
+    /// Mutable access to the machine.
+    pub fn machine_mut(&mut self) -> &mut Machine<CompositeBus<Sam>> {
+        &mut self.machine
+    }
+
+    /// PIA 1, whose ports drive sound, the printer and the VDG mode.
+    pub fn pia1(&mut self) -> &mut Mc6821 {
+        &mut self.cart_pia().pia
+    }
+
+    fn cart_pia(&mut self) -> &mut CartPia {
+        self.machine
+            .bus_mut()
+            .device_as(self.pia1)
+            .expect("PIA 1 is always present")
+    }
+
+    fn set_key(&mut self, key: char, down: bool) -> bool {
+        let Some((row, column)) = key_position(key) else {
+            return false;
+        };
+        let system = self
+            .machine
+            .bus_mut()
+            .device_as::<SystemPia>(self.pia0)
+            .expect("PIA 0 is always present");
+        if down {
+            system.keys[column] |= 1 << row;
+        } else {
+            system.keys[column] &= !(1 << row);
+        }
+        true
+    }
+}
@@ -0,0 +1,443 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A Motorola MC6801/6803 core.
+//!
+//! The 6801 is the 6800 with the 6809's D register arithmetic bolted on and
+//! a timer, four I/O ports and 128 bytes of RAM on the chip; the 6803 is the
+//! same part without the mask ROM. Many 6809 systems use one as an I/O
+//! processor, so [`Mc6801`] runs against the same [`Memory`] trait and shares
+//! the [`alu`](crate::alu) helpers with the 6809 [`Cpu`](crate::Cpu).
+//!
+//! The on-chip resources sit in front of `mem`: the registers at
+//! `0x0000-0x001F` and, while enabled in the RAM control register, the RAM
+//! at `0x0080-0x00FF`. Everything else, including the vectors, goes to
+//! `mem`, as in the 6803's expanded modes. The serial interface's
+//! registers hold what is written to them but do not transmit.
+//!
+//! # Example
+//! ```
+//! use mc6809_core::FlatRam;
+//! use mc6809_core::mc6801::Mc6801;
+//!
+//! let mut ram = FlatRam::new();
+//! ram.load(&[0xCC, 0x12, 0x34, 0xDD, 0x80], 0xC000); // LDD #$1234 / STD $80
+//! ram.set_reset_vector(0xC000);
+//!
+//! let mut cpu = Mc6801::new();
+//! cpu.reset(&mut ram);
+//! assert_eq!(cpu.step(&mut ram), 3);
+//! assert_eq!(cpu.step(&mut ram), 4);
+//! assert_eq!(cpu.internal_ram()[0..2], [0x12, 0x34]);
+//! ```
+
+use std::fmt;
+
+use crate::Memory;
+use crate::registers::ConditionCodes;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+mod execute;
+mod onchip;
+
+use execute::Effect;
+use onchip::{OnChip, OnChipBus};
+
+/// Reset vector.
+pub const VEC_RESET: u16 = 0xFFFE;
+/// Non-maskable interrupt vector.
+pub const VEC_NMI: u16 = 0xFFFC;
+/// Software interrupt vector.
+pub const VEC_SWI: u16 = 0xFFFA;
+/// External interrupt (IRQ1) vector.
+pub const VEC_IRQ: u16 = 0xFFF8;
+/// Timer input capture vector.
+pub const VEC_ICF: u16 = 0xFFF6;
+/// Timer output compare vector.
+pub const VEC_OCF: u16 = 0xFFF4;
+/// Timer overflow vector.
+pub const VEC_TOF: u16 = 0xFFF2;
+
+/// Bits 6 and 7 of the 6801 CC register, which always read as 1.
+const CC_UNUSED: u8 = 0xC0;
+
+/// Cycles to stack the registers and fetch a vector for an interrupt.
+const INTERRUPT_CYCLES: u64 = 12;
+
+/// Cycles to fetch a vector for an interrupt that ends a WAI, which has
+/// already stacked the registers.
+const WAKE_CYCLES: u64 = 3;
+
+/// The MC6801 register set.
+///
+/// D is stored as a `u16` with A in the high byte and B in the low byte, as
+/// in [`Registers`](crate::Registers).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Mc6801Registers {
+    /// Accumulator D (A:B). A = high byte, B = low byte.
+    pub d: u16,
+    /// Index register X
+    pub x: u16,
+    /// Stack pointer, pointing at the next free byte
+    pub sp: u16,
+    /// Program counter
+    pub pc: u16,
+    /// Condition codes; bits 6 and 7 always read as 1
+    pub cc: ConditionCodes,
+}
+
+impl Mc6801Registers {
+    pub const fn new() -> Self {
+        Self {
+            d: 0,
+            x: 0,
+            sp: 0,
+            pc: 0,
+            cc: ConditionCodes::from_byte(CC_UNUSED),
+        }
+    }
+
+    /// Read accumulator A (high byte of D).
+    pub const fn a(self) -> u8 {
+        (self.d >> 8) as u8
+    }
+
+    /// Read accumulator B (low byte of D).
+    pub const fn b(self) -> u8 {
+        self.d as u8
+    }
+
+    /// Write accumulator A (high byte of D), preserving B.
+    pub fn set_a(&mut self, val: u8) {
+        self.d = (self.d & 0x00FF) | ((val as u16) << 8);
+    }
+
+    /// Write accumulator B (low byte of D), preserving A.
+    pub fn set_b(&mut self, val: u8) {
+        self.d = (self.d & 0xFF00) | (val as u16);
+    }
+}
+
+impl fmt::Display for Mc6801Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PC={:04X} A={:02X} B={:02X} X={:04X} SP={:04X} CC={:02X}",
+            self.pc,
+            self.a(),
+            self.b(),
+            self.x,
+            self.sp,
+            self.cc.to_byte(),
+        )
+    }
+}
+
+impl Snapshot for Mc6801Registers {
+    fn snapshot(&self, out: &mut StateWriter) {
+        for word in [self.d, self.x, self.sp, self.pc] {
+            out.u16(word);
+        }
+        out.u8(self.cc.to_byte());
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for word in [&mut self.d, &mut self.x, &mut self.sp, &mut self.pc] {
+            *word = state.u16()?;
+        }
+        self.cc = ConditionCodes::from_byte(state.u8()? | CC_UNUSED);
+        Ok(())
+    }
+}
+
+/// An MC6801/6803 CPU with its on-chip RAM, ports and timer.
+///
+/// The interface follows [`Cpu`](crate::Cpu): [`Self::step`] executes one
+/// instruction or takes one interrupt against a [`Memory`] and returns the
+/// cycles it took, and the host drives the IRQ and NMI pins. The timer
+/// counts those cycles after each step, so a read of the counter sees it
+/// as it was when the instruction began.
+///
+/// Opcodes the 6801 does not define set [`Self::illegal`] and execute as a
+/// two-cycle NOP.
+pub struct Mc6801 {
+    reg: Mc6801Registers,
+    chip: OnChip,
+    cycles: u64,
+    irq: bool,
+    nmi: bool,
+    wai: bool,
+    illegal: bool,
+}
+
+impl Mc6801 {
+    /// Create a new CPU with all state zeroed.
+    pub fn new() -> Self {
+        Self {
+            reg: Mc6801Registers::new(),
+            chip: OnChip::new(),
+            cycles: 0,
+            irq: false,
+            nmi: false,
+            wai: false,
+            illegal: false,
+        }
+    }
+
+    /// Hardware reset: set I, reset the on-chip registers and read PC from
+    /// the reset vector. The internal RAM keeps its contents.
+    pub fn reset(&mut self, mem: &mut impl Memory) {
+        self.reg = Mc6801Registers::new();
+        self.reg.cc.set_irq_inhibit(true);
+        self.chip.reset();
+        self.reg.pc = mem.read_word(VEC_RESET);
+        self.cycles = 0;
+        self.irq = false;
+        self.nmi = false;
+        self.wai = false;
+        self.illegal = false;
+    }
+
+    /// Current register state.
+    pub fn registers(&self) -> &Mc6801Registers {
+        &self.reg
+    }
+
+    /// Mutable access to the registers.
+    pub fn registers_mut(&mut self) -> &mut Mc6801Registers {
+        &mut self.reg
+    }
+
+    /// Total cycles executed since reset.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// `true` if an undefined opcode has been executed since the flag was
+    /// last cleared.
+    pub fn illegal(&self) -> bool {
+        self.illegal
+    }
+
+    /// Clear the flag reported by [`Self::illegal`].
+    pub fn clear_illegal(&mut self) {
+        self.illegal = false;
+    }
+
+    /// `true` while a WAI is waiting for an interrupt.
+    pub fn waiting(&self) -> bool {
+        self.wai
+    }
+
+    /// Assert or de-assert the IRQ1 line (level-triggered).
+    pub fn set_irq(&mut self, active: bool) {
+        self.irq = active;
+    }
+
+    /// Trigger an NMI (edge-triggered).
+    pub fn trigger_nmi(&mut self) {
+        self.nmi = true;
+    }
+
+    /// Latch the timer's input capture on an edge of the input pin.
+    ///
+    /// Only an edge in the direction selected by the IEDG bit of the timer
+    /// control register captures the counter.
+    pub fn input_capture(&mut self, rising: bool) {
+        self.chip.input_capture(rising);
+    }
+
+    /// Current value of the timer's free-running counter.
+    pub fn counter(&self) -> u16 {
+        self.chip.counter()
+    }
+
+    /// The bits port `port` (1 to 4) drives: its data register masked by
+    /// its data direction register.
+    ///
+    /// # Panics
+    /// If `port` is not 1 to 4.
+    pub fn port_output(&self, port: usize) -> u8 {
+        self.chip.port_output(port)
+    }
+
+    /// Set the level of the pins of port `port` (1 to 4), as read back
+    /// through its data register by the bits programmed as inputs.
+    ///
+    /// # Panics
+    /// If `port` is not 1 to 4.
+    pub fn set_port_input(&mut self, port: usize, val: u8) {
+        self.chip.set_port_input(port, val);
+    }
+
+    /// The 128 bytes of on-chip RAM.
+    pub fn internal_ram(&self) -> &[u8; 128] {
+        self.chip.ram()
+    }
+
+    /// Execute a single instruction (or take a pending interrupt).
+    /// Returns the number of cycles consumed.
+    pub fn step<M: Memory>(&mut self, mem: &mut M) -> u64 {
+        let pending = self.pending();
+        let mut bus = OnChipBus {
+            chip: &mut self.chip,
+            mem,
+        };
+        let cycles = if let Some(vector) = pending {
+            self.nmi &= vector != VEC_NMI;
+            let cycles = if self.wai {
+                WAKE_CYCLES
+            } else {
+                push_state(&mut self.reg, &mut bus);
+                INTERRUPT_CYCLES
+            };
+            self.wai = false;
+            self.reg.cc.set_irq_inhibit(true);
+            self.reg.pc = bus.read_word(vector);
+            cycles
+        } else if self.wai {
+            1
+        } else {
+            let opcode = fetch8(&mut self.reg, &mut bus);
+            let (cycles, effect) = execute::execute(&mut self.reg, &mut bus, opcode);
+            match effect {
+                Effect::None => {}
+                Effect::Wait => self.wai = true,
+                Effect::Illegal => self.illegal = true,
+            }
+            cycles as u64
+        };
+        self.chip.tick(cycles);
+        self.cycles += cycles;
+        cycles
+    }
+
+    /// Run until at least `cycle_budget` cycles have elapsed.
+    /// Returns the actual number of cycles consumed.
+    pub fn run(&mut self, mem: &mut impl Memory, cycle_budget: u64) -> u64 {
+        let start = self.cycles;
+        while self.cycles - start < cycle_budget {
+            self.step(mem);
+        }
+        self.cycles - start
+    }
+
+    /// The vector of the interrupt to take before the next instruction, by
+    /// priority: NMI, then IRQ1, then the timer.
+    fn pending(&self) -> Option<u16> {
+        if self.nmi {
+            return Some(VEC_NMI);
+        }
+        if self.reg.cc.irq_inhibit() {
+            return None;
+        }
+        if self.irq {
+            return Some(VEC_IRQ);
+        }
+        self.chip.timer_vector()
+    }
+}
+
+impl Default for Mc6801 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Snapshot for Mc6801 {
+    fn snapshot(&self, out: &mut StateWriter) {
+        self.reg.snapshot(out);
+        self.chip.snapshot(out);
+        out.u64(self.cycles);
+        for flag in [self.irq, self.nmi, self.wai, self.illegal] {
+            out.bool(flag);
+        }
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.reg.restore(state)?;
+        self.chip.restore(state)?;
+        self.cycles = state.u64()?;
+        for flag in [
+            &mut self.irq,
+            &mut self.nmi,
+            &mut self.wai,
+            &mut self.illegal,
+        ] {
+            *flag = state.bool()?;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Fetch and stack helpers
+// ---------------------------------------------------------------------------
+
+fn fetch8(reg: &mut Mc6801Registers, mem: &mut impl Memory) -> u8 {
+    let val = mem.read(reg.pc);
+    reg.pc = reg.pc.wrapping_add(1);
+    val
+}
+
+fn fetch16(reg: &mut Mc6801Registers, mem: &mut impl Memory) -> u16 {
+    let mut bytes = [0; 2];
+    mem.read_slice(reg.pc, &mut bytes);
+    reg.pc = reg.pc.wrapping_add(2);
+    u16::from_be_bytes(bytes)
+}
+
+/// Push a byte: the 6801 stores at SP, then decrements it.
+fn push8(reg: &mut Mc6801Registers, mem: &mut impl Memory, val: u8) {
+    mem.write(reg.sp, val);
+    reg.sp = reg.sp.wrapping_sub(1);
+}
+
+/// Push a word, low byte first, so that it sits big-endian in memory.
+fn push16(reg: &mut Mc6801Registers, mem: &mut impl Memory, val: u16) {
+    push8(reg, mem, val as u8);
+    push8(reg, mem, (val >> 8) as u8);
+}
+
+fn pull8(reg: &mut Mc6801Registers, mem: &mut impl Memory) -> u8 {
+    reg.sp = reg.sp.wrapping_add(1);
+    mem.read(reg.sp)
+}
+
+fn pull16(reg: &mut Mc6801Registers, mem: &mut impl Memory) -> u16 {
+    let hi = pull8(reg, mem) as u16;
+    let lo = pull8(reg, mem) as u16;
+    (hi << 8) | lo
+}
+
+/// Stack PC, X, A, B and CC, as interrupts, SWI and WAI do.
+fn push_state(reg: &mut Mc6801Registers, mem: &mut impl Memory) {
+    let saved = *reg;
+    push16(reg, mem, saved.pc);
+    push16(reg, mem, saved.x);
+    push8(reg, mem, saved.a());
+    push8(reg, mem, saved.b());
+    push8(reg, mem, saved.cc.to_byte());
+}
+
+/// Unstack what [`push_state`] stacked, as RTI does.
+fn pull_state(reg: &mut Mc6801Registers, mem: &mut impl Memory) {
+    reg.cc = ConditionCodes::from_byte(pull8(reg, mem) | CC_UNUSED);
+    let b = pull8(reg, mem);
+    reg.set_b(b);
+    let a = pull8(reg, mem);
+    reg.set_a(a);
+    reg.x = pull16(reg, mem);
+    reg.pc = pull16(reg, mem);
+}
@@ -0,0 +1,372 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! The 6801 opcode map.
+//!
+//! The map is regular enough to decode by row and column: `0x40-0x7F` are
+//! the read-modify-write operations on A, B, indexed and extended operands,
+//! and `0x80-0xFF` the accumulator operations on immediate, direct, indexed
+//! and extended operands, A in the left half and B in the right. Where the
+//! 6800 and the 6809 disagree on a flag, the 6800 wins: shifts and rotates
+//! set V to N xor C, TST clears C, and MUL only sets C.
+
+use super::{
+    CC_UNUSED, Mc6801Registers, VEC_SWI, fetch8, fetch16, pull_state, pull8, pull16, push_state,
+    push8, push16,
+};
+use crate::Memory;
+use crate::alu;
+use crate::registers::{CC_C, CC_I, CC_V, Condition, ConditionCodes};
+
+/// What an instruction leaves for the core to do after it.
+pub(super) enum Effect {
+    None,
+    /// WAI: stop until an interrupt.
+    Wait,
+    /// The opcode is undefined.
+    Illegal,
+}
+
+/// Cycles per opcode; 0 marks an undefined opcode.
+#[rustfmt::skip]
+static CYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  A  B  C  D  E  F
+    0, 2, 0, 0, 3, 3, 2, 2, 3, 3, 2, 2, 2, 2, 2, 2, // 0x
+    2, 2, 0, 0, 0, 0, 2, 2, 0, 2, 0, 2, 0, 0, 0, 0, // 1x
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, // 2x
+    3, 3, 4, 4, 3, 3, 3, 3, 5, 5, 3,10, 4,10, 9,12, // 3x
+    2, 0, 0, 2, 2, 0, 2, 2, 2, 2, 2, 0, 2, 2, 0, 2, // 4x
+    2, 0, 0, 2, 2, 0, 2, 2, 2, 2, 2, 0, 2, 2, 0, 2, // 5x
+    6, 0, 0, 6, 6, 0, 6, 6, 6, 6, 6, 0, 6, 6, 3, 6, // 6x
+    6, 0, 0, 6, 6, 0, 6, 6, 6, 6, 6, 0, 6, 6, 3, 6, // 7x
+    2, 2, 2, 4, 2, 2, 2, 0, 2, 2, 2, 2, 4, 6, 3, 0, // 8x
+    3, 3, 3, 5, 3, 3, 3, 3, 3, 3, 3, 3, 5, 5, 4, 4, // 9x
+    4, 4, 4, 6, 4, 4, 4, 4, 4, 4, 4, 4, 6, 6, 5, 5, // Ax
+    4, 4, 4, 6, 4, 4, 4, 4, 4, 4, 4, 4, 6, 6, 5, 5, // Bx
+    2, 2, 2, 4, 2, 2, 2, 0, 2, 2, 2, 2, 3, 0, 3, 0, // Cx
+    3, 3, 3, 5, 3, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, // Dx
+    4, 4, 4, 6, 4, 4, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, // Ex
+    4, 4, 4, 6, 4, 4, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, // Fx
+];
+
+/// Cycles an undefined opcode takes.
+const ILLEGAL_CYCLES: u8 = 2;
+
+/// Execute `opcode`, whose byte has been fetched. Returns the cycles it
+/// took and what the core has to do next.
+pub(super) fn execute(
+    reg: &mut Mc6801Registers,
+    mem: &mut impl Memory,
+    opcode: u8,
+) -> (u8, Effect) {
+    let cycles = CYCLES[opcode as usize];
+    if cycles == 0 {
+        return (ILLEGAL_CYCLES, Effect::Illegal);
+    }
+    let effect = match opcode {
+        0x00..=0x3F => inherent(reg, mem, opcode),
+        0x40..=0x7F => {
+            read_modify_write(reg, mem, opcode);
+            Effect::None
+        }
+        _ => {
+            accumulator(reg, mem, opcode);
+            Effect::None
+        }
+    };
+    (cycles, effect)
+}
+
+/// Effective address of a direct, indexed or extended operand, selected
+/// by bits 4 and 5 of the opcode as in the accumulator rows.
+fn address(reg: &mut Mc6801Registers, mem: &mut impl Memory, mode: u8) -> u16 {
+    match mode {
+        1 => fetch8(reg, mem) as u16,
+        2 => reg.x.wrapping_add(fetch8(reg, mem) as u16),
+        _ => fetch16(reg, mem),
+    }
+}
+
+/// Whether the branch `opcode` (0x20-0x2F) is taken.
+fn condition(opcode: u8, cc: ConditionCodes) -> bool {
+    cc.test(Condition::from_code(opcode))
+}
+
+/// Set V to N xor C, as the 6800 does after every shift and rotate.
+fn shift_overflow(cc: &mut ConditionCodes) {
+    cc.set_overflow(cc.negative() != cc.carry());
+}
+
+fn inherent(reg: &mut Mc6801Registers, mem: &mut impl Memory, opcode: u8) -> Effect {
+    let (a, b, x) = (reg.a(), reg.b(), reg.x);
+    let cc = &mut reg.cc;
+    match opcode {
+        0x01 => {} // NOP
+        0x04 => {
+            // LSRD
+            reg.d = alu::lsr16(reg.d, cc);
+            shift_overflow(cc);
+        }
+        0x05 => {
+            // ASLD
+            reg.d = alu::asl16(reg.d, cc);
+            shift_overflow(cc);
+        }
+        0x06 => *cc = ConditionCodes::from_byte(a | CC_UNUSED), // TAP
+        0x07 => reg.set_a(reg.cc.to_byte()),                    // TPA
+        0x08 => {
+            // INX
+            reg.x = reg.x.wrapping_add(1);
+            cc.set_zero(reg.x == 0);
+        }
+        0x09 => {
+            // DEX
+            reg.x = reg.x.wrapping_sub(1);
+            cc.set_zero(reg.x == 0);
+        }
+        0x0A => cc.and_with(!CC_V), // CLV
+        0x0B => cc.or_with(CC_V),   // SEV
+        0x0C => cc.and_with(!CC_C), // CLC
+        0x0D => cc.or_with(CC_C),   // SEC
+        0x0E => cc.and_with(!CC_I), // CLI
+        0x0F => cc.or_with(CC_I),   // SEI
+        0x10 => {
+            // SBA
+            let a = alu::sub8(a, b, cc);
+            reg.set_a(a);
+        }
+        0x11 => {
+            // CBA
+            alu::sub8(a, b, cc);
+        }
+        0x16 => {
+            // TAB
+            alu::ld8_flags(a, cc);
+            reg.set_b(a);
+        }
+        0x17 => {
+            // TBA
+            alu::ld8_flags(b, cc);
+            reg.set_a(b);
+        }
+        0x19 => {
+            // DAA
+            let a = alu::daa(a, cc);
+            reg.set_a(a);
+        }
+        0x1B => {
+            // ABA
+            let a = alu::add8(a, b, cc);
+            reg.set_a(a);
+        }
+        0x20..=0x2F => {
+            let offset = fetch8(reg, mem) as i8;
+            if condition(opcode, reg.cc) {
+                reg.pc = reg.pc.wrapping_add(offset as u16);
+            }
+        }
+        0x30 => reg.x = reg.sp.wrapping_add(1),  // TSX
+        0x31 => reg.sp = reg.sp.wrapping_add(1), // INS
+        0x32 => {
+            // PULA
+            let a = pull8(reg, mem);
+            reg.set_a(a);
+        }
+        0x33 => {
+            // PULB
+            let b = pull8(reg, mem);
+            reg.set_b(b);
+        }
+        0x34 => reg.sp = reg.sp.wrapping_sub(1),      // DES
+        0x35 => reg.sp = reg.x.wrapping_sub(1),       // TXS
+        0x36 => push8(reg, mem, a),                   // PSHA
+        0x37 => push8(reg, mem, b),                   // PSHB
+        0x38 => reg.x = pull16(reg, mem),             // PULX
+        0x39 => reg.pc = pull16(reg, mem),            // RTS
+        0x3A => reg.x = reg.x.wrapping_add(b as u16), // ABX
+        0x3B => pull_state(reg, mem),                 // RTI
+        0x3C => push16(reg, mem, x),                  // PSHX
+        0x3D => {
+            // MUL
+            reg.d = a as u16 * b as u16;
+            cc.set_carry(reg.d & 0x0080 != 0);
+        }
+        0x3E => {
+            // WAI
+            push_state(reg, mem);
+            return Effect::Wait;
+        }
+        0x3F => {
+            // SWI
+            push_state(reg, mem);
+            reg.cc.set_irq_inhibit(true);
+            reg.pc = mem.read_word(VEC_SWI);
+        }
+        _ => unreachable!("undefined opcodes have no cycle count"),
+    }
+    Effect::None
+}
+
+/// NEG, COM, the shifts, DEC, INC, TST, JMP and CLR on A (0x4x), B (0x5x),
+/// an indexed operand (0x6x) or an extended one (0x7x).
+fn read_modify_write(reg: &mut Mc6801Registers, mem: &mut impl Memory, opcode: u8) {
+    let ea = match opcode >> 4 {
+        6 => Some(address(reg, mem, 2)),
+        7 => Some(address(reg, mem, 3)),
+        _ => None,
+    };
+    if opcode & 0x0F == 0x0E {
+        // JMP
+        reg.pc = ea.expect("JMP has a memory operand");
+        return;
+    }
+    let val = match (ea, opcode >> 4) {
+        (Some(ea), _) => mem.read(ea),
+        (None, 4) => reg.a(),
+        (None, _) => reg.b(),
+    };
+    let cc = &mut reg.cc;
+    let result = match opcode & 0x0F {
+        0x0 => alu::neg8(val, cc),
+        0x3 => alu::com8(val, cc),
+        0x4 => alu::lsr8(val, cc),
+        0x6 => alu::ror8(val, cc),
+        0x7 => alu::asr8(val, cc),
+        0x8 => alu::asl8(val, cc),
+        0x9 => alu::rol8(val, cc),
+        0xA => alu::dec8(val, cc),
+        0xC => alu::inc8(val, cc),
+        0xD => {
+            // TST
+            alu::tst8(val, cc);
+            cc.set_carry(false);
+            return;
+        }
+        _ => alu::clr8(cc),
+    };
+    if matches!(opcode & 0x0F, 0x4 | 0x6..=0x9) {
+        shift_overflow(cc);
+    }
+    match (ea, opcode >> 4) {
+        (Some(ea), _) => mem.write(ea, result),
+        (None, 4) => reg.set_a(result),
+        (None, _) => reg.set_b(result),
+    }
+}
+
+/// The accumulator rows, 0x80-0xFF.
+fn accumulator(reg: &mut Mc6801Registers, mem: &mut impl Memory, opcode: u8) {
+    let b_side = opcode & 0x40 != 0;
+    let mode = (opcode >> 4) & 3;
+    match (opcode & 0x0F, b_side) {
+        (0x3, _) | (0xC, false) => {
+            // SUBD, ADDD, CPX
+            let val = word_operand(reg, mem, mode);
+            let cc = &mut reg.cc;
+            match (opcode & 0x0F, b_side) {
+                (0x3, false) => reg.d = alu::sub16(reg.d, val, cc),
+                (0x3, true) => reg.d = alu::add16(reg.d, val, cc),
+                _ => {
+                    alu::sub16(reg.x, val, cc);
+                }
+            }
+        }
+        (0xC | 0xE, _) => {
+            // LDD, LDS, LDX
+            let val = word_operand(reg, mem, mode);
+            alu::ld16_flags(val, &mut reg.cc);
+            match (opcode & 0x0F, b_side) {
+                (0xC, _) => reg.d = val,
+                (_, false) => reg.sp = val,
+                _ => reg.x = val,
+            }
+        }
+        (0xD, false) => {
+            // BSR, JSR
+            let target = if mode == 0 {
+                let offset = fetch8(reg, mem) as i8;
+                reg.pc.wrapping_add(offset as u16)
+            } else {
+                address(reg, mem, mode)
+            };
+            let pc = reg.pc;
+            push16(reg, mem, pc);
+            reg.pc = target;
+        }
+        (0xD | 0xF, _) => {
+            // STD, STS, STX
+            let ea = address(reg, mem, mode);
+            let val = match (opcode & 0x0F, b_side) {
+                (0xD, _) => reg.d,
+                (_, false) => reg.sp,
+                _ => reg.x,
+            };
+            alu::ld16_flags(val, &mut reg.cc);
+            mem.write_word(ea, val);
+        }
+        (0x7, _) => {
+            // STA
+            let ea = address(reg, mem, mode);
+            let val = if b_side { reg.b() } else { reg.a() };
+            alu::ld8_flags(val, &mut reg.cc);
+            mem.write(ea, val);
+        }
+        (col, _) => {
+            let val = if mode == 0 {
+                fetch8(reg, mem)
+            } else {
+                let ea = address(reg, mem, mode);
+                mem.read(ea)
+            };
+            let acc = if b_side { reg.b() } else { reg.a() };
+            let cc = &mut reg.cc;
+            let result = match col {
+                0x0 => alu::sub8(acc, val, cc),
+                0x1 => {
+                    alu::sub8(acc, val, cc);
+                    return;
+                }
+                0x2 => alu::sbc8(acc, val, cc),
+                0x4 => alu::and8(acc, val, cc),
+                0x5 => {
+                    alu::and8(acc, val, cc);
+                    return;
+                }
+                0x6 => {
+                    alu::ld8_flags(val, cc);
+                    val
+                }
+                0x8 => alu::eor8(acc, val, cc),
+                0x9 => alu::adc8(acc, val, cc),
+                0xA => alu::or8(acc, val, cc),
+                _ => alu::add8(acc, val, cc),
+            };
+            if b_side {
+                reg.set_b(result);
+            } else {
+                reg.set_a(result);
+            }
+        }
+    }
+}
+
+/// A 16-bit operand: immediate in mode 0, otherwise read from memory.
+fn word_operand(reg: &mut Mc6801Registers, mem: &mut impl Memory, mode: u8) -> u16 {
+    if mode == 0 {
+        fetch16(reg, mem)
+    } else {
+        let ea = address(reg, mem, mode);
+        mem.read_word(ea)
+    }
+}
@@ -0,0 +1,283 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! The 6801's on-chip registers, RAM and timer.
+
+use super::{VEC_ICF, VEC_OCF, VEC_TOF};
+use crate::Memory;
+use crate::memory::BusFault;
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
+/// Last address of the internal register block.
+const REG_END: u16 = 0x001F;
+/// First address of the internal RAM.
+const RAM_BASE: u16 = 0x0080;
+/// Last address of the internal RAM.
+const RAM_END: u16 = 0x00FF;
+
+/// Data direction and data register addresses of ports 1 to 4.
+const PORT_DDR: [u16; 4] = [0x00, 0x01, 0x04, 0x05];
+const PORT_DATA: [u16; 4] = [0x02, 0x03, 0x06, 0x07];
+
+const TCSR: u16 = 0x08;
+const COUNTER_HI: u16 = 0x09;
+const COUNTER_LO: u16 = 0x0A;
+const COMPARE_HI: u16 = 0x0B;
+const COMPARE_LO: u16 = 0x0C;
+const CAPTURE_HI: u16 = 0x0D;
+const CAPTURE_LO: u16 = 0x0E;
+const TRCSR: u16 = 0x11;
+const RAM_CONTROL: u16 = 0x14;
+
+/// Timer control and status register bits.
+const TCSR_ICF: u8 = 0x80; // Input capture flag
+const TCSR_OCF: u8 = 0x40; // Output compare flag
+const TCSR_TOF: u8 = 0x20; // Timer overflow flag
+const TCSR_IEDG: u8 = 0x02; // Capture on a rising edge
+const TCSR_OLVL: u8 = 0x01; // Level driven onto P21 on a compare
+/// Writable bits of the timer control and status register.
+const TCSR_CONTROL: u8 = 0x1F;
+
+/// RAM enable bit of the RAM control register.
+const RAM_ENABLE: u8 = 0x40;
+
+/// Counter value a write to the counter presets.
+const COUNTER_PRESET: u16 = 0xFFF8;
+
+/// Everything on the chip apart from the CPU.
+pub(super) struct OnChip {
+    ram: [u8; 128],
+    /// The register block, for the registers without their own state.
+    regs: [u8; 0x20],
+    port_input: [u8; 4],
+    counter: u16,
+    compare: u16,
+    capture: u16,
+    /// Counter low byte, latched by a read of the high byte.
+    counter_latch: u8,
+    /// Flags that were set at the last read of the timer control and
+    /// status register; only those can be cleared by the access that
+    /// follows.
+    flags_seen: u8,
+}
+
+impl OnChip {
+    pub(super) fn new() -> Self {
+        let mut chip = Self {
+            ram: [0; 128],
+            regs: [0; 0x20],
+            port_input: [0; 4],
+            counter: 0,
+            compare: 0,
+            capture: 0,
+            counter_latch: 0,
+            flags_seen: 0,
+        };
+        chip.reset();
+        chip
+    }
+
+    /// Put the registers in their reset state; the RAM is left alone.
+    pub(super) fn reset(&mut self) {
+        self.regs = [0; 0x20];
+        self.regs[TRCSR as usize] = 0x20;
+        self.regs[RAM_CONTROL as usize] = 0x80 | RAM_ENABLE;
+        self.counter = 0;
+        self.compare = 0xFFFF;
+        self.capture = 0;
+        self.counter_latch = 0;
+        self.flags_seen = 0;
+    }
+
+    pub(super) fn ram(&self) -> &[u8; 128] {
+        &self.ram
+    }
+
+    pub(super) fn counter(&self) -> u16 {
+        self.counter
+    }
+
+    pub(super) fn port_output(&self, port: usize) -> u8 {
+        let i = port - 1;
+        self.regs[PORT_DATA[i] as usize] & self.regs[PORT_DDR[i] as usize]
+    }
+
+    pub(super) fn set_port_input(&mut self, port: usize, val: u8) {
+        self.port_input[port - 1] = val;
+    }
+
+    pub(super) fn input_capture(&mut self, rising: bool) {
+        if rising == (self.tcsr() & TCSR_IEDG != 0) {
+            self.capture = self.counter;
+            self.regs[TCSR as usize] |= TCSR_ICF;
+        }
+    }
+
+    /// Advance the counter by `cycles`, raising the compare and overflow
+    /// flags it passes.
+    pub(super) fn tick(&mut self, cycles: u64) {
+        let old = self.counter;
+        let to_compare = self.compare.wrapping_sub(old) as u64;
+        if to_compare != 0 && to_compare <= cycles || cycles > 0xFFFF {
+            self.regs[TCSR as usize] |= TCSR_OCF;
+            // OLVL goes out on P21.
+            let level = (self.tcsr() & TCSR_OLVL) << 1;
+            let data = &mut self.regs[PORT_DATA[1] as usize];
+            *data = (*data & !0x02) | level;
+        }
+        if old as u64 + cycles > 0xFFFF {
+            self.regs[TCSR as usize] |= TCSR_TOF;
+        }
+        self.counter = (old as u64).wrapping_add(cycles) as u16;
+    }
+
+    /// The vector of the highest-priority timer interrupt that is both
+    /// flagged and enabled.
+    pub(super) fn timer_vector(&self) -> Option<u16> {
+        let tcsr = self.tcsr();
+        // Each enable bit sits three places below its flag.
+        let pending = tcsr & (tcsr << 3);
+        if pending & TCSR_ICF != 0 {
+            Some(VEC_ICF)
+        } else if pending & TCSR_OCF != 0 {
+            Some(VEC_OCF)
+        } else if pending & TCSR_TOF != 0 {
+            Some(VEC_TOF)
+        } else {
+            None
+        }
+    }
+
+    fn tcsr(&self) -> u8 {
+        self.regs[TCSR as usize]
+    }
+
+    /// Clear `flag` if it was set when the status register was last read.
+    fn acknowledge(&mut self, flag: u8) {
+        if self.flags_seen & flag != 0 {
+            self.regs[TCSR as usize] &= !flag;
+            self.flags_seen &= !flag;
+        }
+    }
+
+    fn read_reg(&mut self, addr: u16) -> u8 {
+        match addr {
+            TCSR => {
+                self.flags_seen = self.tcsr() & (TCSR_ICF | TCSR_OCF | TCSR_TOF);
+                self.tcsr()
+            }
+            COUNTER_HI => {
+                self.acknowledge(TCSR_TOF);
+                self.counter_latch = self.counter as u8;
+                (self.counter >> 8) as u8
+            }
+            COUNTER_LO => self.counter_latch,
+            COMPARE_HI => (self.compare >> 8) as u8,
+            COMPARE_LO => self.compare as u8,
+            CAPTURE_HI => {
+                self.acknowledge(TCSR_ICF);
+                (self.capture >> 8) as u8
+            }
+            CAPTURE_LO => self.capture as u8,
+            _ => match PORT_DATA.iter().position(|&data| data == addr) {
+                Some(i) => {
+                    let ddr = self.regs[PORT_DDR[i] as usize];
+                    (self.regs[addr as usize] & ddr) | (self.port_input[i] & !ddr)
+                }
+                None => self.regs[addr as usize],
+            },
+        }
+    }
+
+    fn write_reg(&mut self, addr: u16, val: u8) {
+        match addr {
+            TCSR => {
+                let flags = self.tcsr() & !TCSR_CONTROL;
+                self.regs[TCSR as usize] = flags | (val & TCSR_CONTROL);
+            }
+            COUNTER_HI => self.counter = COUNTER_PRESET,
+            COUNTER_LO | CAPTURE_HI | CAPTURE_LO => {}
+            COMPARE_HI => {
+                self.compare = (self.compare & 0x00FF) | ((val as u16) << 8);
+                self.acknowledge(TCSR_OCF);
+            }
+            COMPARE_LO => {
+                self.compare = (self.compare & 0xFF00) | val as u16;
+                self.acknowledge(TCSR_OCF);
+            }
+            _ => self.regs[addr as usize] = val,
+        }
+    }
+
+    fn ram_enabled(&self) -> bool {
+        self.regs[RAM_CONTROL as usize] & RAM_ENABLE != 0
+    }
+}
+
+impl Snapshot for OnChip {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.bytes(&self.ram);
+        out.bytes(&self.regs);
+        out.bytes(&self.port_input);
+        for word in [self.counter, self.compare, self.capture] {
+            out.u16(word);
+        }
+        out.u8(self.counter_latch);
+        out.u8(self.flags_seen);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        state.bytes_into(&mut self.ram)?;
+        state.bytes_into(&mut self.regs)?;
+        state.bytes_into(&mut self.port_input)?;
+        for word in [&mut self.counter, &mut self.compare, &mut self.capture] {
+            *word = state.u16()?;
+        }
+        self.counter_latch = state.u8()?;
+        self.flags_seen = state.u8()?;
+        Ok(())
+    }
+}
+
+/// The 6801's view of the bus: the on-chip resources in front of `mem`.
+pub(super) struct OnChipBus<'a, M> {
+    pub(super) chip: &'a mut OnChip,
+    pub(super) mem: &'a mut M,
+}
+
+impl<M: Memory> Memory for OnChipBus<'_, M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        match addr {
+            0..=REG_END => self.chip.read_reg(addr),
+            RAM_BASE..=RAM_END if self.chip.ram_enabled() => {
+                self.chip.ram[(addr - RAM_BASE) as usize]
+            }
+            _ => self.mem.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0..=REG_END => self.chip.write_reg(addr, val),
+            RAM_BASE..=RAM_END if self.chip.ram_enabled() => {
+                self.chip.ram[(addr - RAM_BASE) as usize] = val;
+            }
+            _ => self.mem.write(addr, val),
+        }
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.mem.take_fault()
+    }
+}
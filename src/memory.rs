@@ -18,6 +18,11 @@
 /// The 6809 has a 16-bit address bus (64KB address space) and an 8-bit data bus.
 /// Re-implementations of word read/write methods must use big-endian byte order
 /// (high byte at `addr`, low byte at `addr + 1`).
+///
+/// The CPU fetches instruction operands through [`read_slice`](Self::read_slice),
+/// and the default [`read_word`](Self::read_word) goes through it too. Both fall
+/// back to one `read` per byte; implementations backed by a plain byte array
+/// should override `read_slice` to copy directly.
 pub trait Memory {
     /// Read a byte from the given address.
     fn read(&mut self, addr: u16) -> u8;
@@ -27,9 +32,9 @@ pub trait Memory {
 
     /// Read a big-endian 16-bit word (high byte at `addr`, low byte at `addr + 1`).
     fn read_word(&mut self, addr: u16) -> u16 {
-        let hi = self.read(addr) as u16;
-        let lo = self.read(addr.wrapping_add(1)) as u16;
-        (hi << 8) | lo
+        let mut buf = [0; 2];
+        self.read_slice(addr, &mut buf);
+        u16::from_be_bytes(buf)
     }
 
     /// Write a big-endian 16-bit word (high byte at `addr`, low byte at `addr + 1`).
@@ -37,4 +42,83 @@ pub trait Memory {
         self.write(addr, (val >> 8) as u8);
         self.write(addr.wrapping_add(1), val as u8);
     }
+
+    /// Fill `buf` with consecutive bytes starting at `addr`, wrapping at the
+    /// end of the address space. Must behave like a `read` per byte, in order.
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.read(addr.wrapping_add(i as u16));
+        }
+    }
+
+    /// Take an access the bus refused since the last call, if any, so
+    /// [`Cpu::try_step`](crate::Cpu::try_step) can report it. Memory that
+    /// never faults keeps the default, which returns `None`.
+    fn take_fault(&mut self) -> Option<BusFault> {
+        None
+    }
+}
+
+/// An access refused by the bus, reported through [`Memory::take_fault`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusFault {
+    /// Address accessed.
+    pub addr: u16,
+    /// Value of a refused write, or `None` for a refused read.
+    pub write: Option<u8>,
+}
+
+/// Forwarding impl so `&mut dyn Memory` (or any borrowed memory) can be passed
+/// where the CPU expects an owned `Memory` type.
+impl<M: Memory + ?Sized> Memory for &mut M {
+    fn read(&mut self, addr: u16) -> u8 {
+        (**self).read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        (**self).write(addr, val)
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        (**self).read_word(addr)
+    }
+
+    fn write_word(&mut self, addr: u16, val: u16) {
+        (**self).write_word(addr, val)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        (**self).read_slice(addr, buf)
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        (**self).take_fault()
+    }
+}
+
+/// Forwarding impl for boxed memory, including `Box<dyn Memory>`.
+impl<M: Memory + ?Sized> Memory for Box<M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        (**self).read(addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        (**self).write(addr, val)
+    }
+
+    fn read_word(&mut self, addr: u16) -> u16 {
+        (**self).read_word(addr)
+    }
+
+    fn write_word(&mut self, addr: u16, val: u16) {
+        (**self).write_word(addr, val)
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        (**self).read_slice(addr, buf)
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        (**self).take_fault()
+    }
 }
@@ -15,6 +15,8 @@
 use std::fmt;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
 
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
 /// Interrupt and control signals returned by [`Clocked::tick`].
 ///
 /// Each flag corresponds to a physical input pin on the 6809 CPU.
@@ -42,6 +44,17 @@ impl BusSignals {
     pub const IRQ: Self = Self(0x04);
     /// RESET pin asserted — the host loop should call [`Cpu::reset`](crate::Cpu::reset).
     pub const RESET: Self = Self(0x08);
+    /// DMA/BREQ pin asserted — a bus master is requesting the bus (see
+    /// [`Cpu::set_dma_breq`](crate::Cpu::set_dma_breq)).
+    pub const DMA_BREQ: Self = Self(0x10);
+    /// User-defined event, not wired to the CPU: a device can raise it to
+    /// mark something such as a vertical blank for
+    /// [`Machine::run_until_signal`](crate::machine::Machine::run_until_signal).
+    pub const EVENT0: Self = Self(0x20);
+    /// Second user-defined event (see [`Self::EVENT0`]).
+    pub const EVENT1: Self = Self(0x40);
+    /// Third user-defined event (see [`Self::EVENT0`]).
+    pub const EVENT2: Self = Self(0x80);
 
     /// Returns `true` if all bits in `other` are set in `self`.
     #[inline]
@@ -114,6 +127,17 @@ impl Not for BusSignals {
     }
 }
 
+impl Snapshot for BusSignals {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u8(self.0);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.0 = state.u8()?;
+        Ok(())
+    }
+}
+
 impl fmt::Debug for BusSignals {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         const FLAGS: &[(&str, BusSignals)] = &[
@@ -121,6 +145,10 @@ impl fmt::Debug for BusSignals {
             ("FIRQ", BusSignals::FIRQ),
             ("IRQ", BusSignals::IRQ),
             ("RESET", BusSignals::RESET),
+            ("DMA_BREQ", BusSignals::DMA_BREQ),
+            ("EVENT0", BusSignals::EVENT0),
+            ("EVENT1", BusSignals::EVENT1),
+            ("EVENT2", BusSignals::EVENT2),
         ];
         write!(f, "BusSignals(")?;
         let mut first = true;
@@ -0,0 +1,367 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Cycle profiling by subroutine.
+//!
+//! [`Cpu::set_profiler`](crate::Cpu::set_profiler) makes the CPU charge the
+//! cycles of every instruction, and of every interrupt entry, to the
+//! subroutine running it in a [`Profile`]. Calls are followed through
+//! `JSR`, `BSR`, `LBSR`, `SWI` and interrupts; a routine returns once the
+//! stack pointer rises above the address its return address was pushed to,
+//! whether by `RTS`, `RTI`, `PULS PC` or an adjustment of S.
+//!
+//! Routines are named by their entry address, or by symbol in the reports
+//! when given a [`SymbolTable`].
+//!
+//! Only available with the `hooks` feature.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::symbols::SymbolTable;
+
+/// Self and inclusive cycles of one routine, from [`Profile::routines`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoutineProfile {
+    /// Entry address.
+    pub addr: u16,
+    /// Times the routine was called.
+    pub calls: u64,
+    /// Cycles spent in the routine itself.
+    pub self_cycles: u64,
+    /// Cycles spent in the routine and everything it called, counting
+    /// recursive calls once.
+    pub total_cycles: u64,
+}
+
+/// Calls from one routine to another, from [`Profile::calls`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallProfile {
+    /// Entry address of the calling routine.
+    pub caller: u16,
+    /// Entry address of the called routine.
+    pub callee: u16,
+    /// Times `caller` called `callee`.
+    pub calls: u64,
+    /// Inclusive cycles of those calls.
+    pub cycles: u64,
+}
+
+/// A routine being run, above the one profiling started in.
+#[derive(Clone, Debug)]
+struct Frame {
+    routine: u16,
+    /// Where the return address, or interrupt state, was pushed.
+    s: u16,
+    /// [`Profile::cycles`] when the call was made.
+    entered: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Counts {
+    calls: u64,
+    self_cycles: u64,
+    total_cycles: u64,
+}
+
+/// Cycles charged to each routine, and to each caller and callee pair.
+///
+/// The routine profiling started in is the one at the first instruction
+/// run. Routines still running count with the cycles they have used so
+/// far. Cycles spent waiting in `SYNC` or `CWAI`, halted or lost to DMA are
+/// not charged to anything.
+///
+/// The call tracking trusts the stack: code that switches S to another
+/// stack, or returns by jumping, confuses it until the stack unwinds past
+/// the routines it lost track of.
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// // JSR $0410; NOP ... $0410: NOP; RTS
+/// ram.load(&[0xBD, 0x04, 0x10, 0x12], 0x0400);
+/// ram.load(&[0x12, 0x39], 0x0410);
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.registers_mut().s = 0x0800;
+/// cpu.set_profiler(true);
+/// for _ in 0..4 {
+///     cpu.step(&mut ram);
+/// }
+///
+/// let profile = cpu.profile().unwrap();
+/// let routines = profile.routines();
+/// // JSR (8) and NOP (2) in the caller; NOP (2) and RTS (5) in the callee.
+/// assert_eq!((routines[0].addr, routines[0].self_cycles), (0x0400, 10));
+/// assert_eq!((routines[1].addr, routines[1].total_cycles), (0x0410, 7));
+/// assert_eq!(profile.calls()[0].calls, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    root: Option<u16>,
+    stack: Vec<Frame>,
+    /// Cycles charged so far.
+    cycles: u64,
+    routines: BTreeMap<u16, Counts>,
+    /// Calls and inclusive cycles of finished calls, by caller and callee.
+    edges: BTreeMap<(u16, u16), (u64, u64)>,
+}
+
+/// Whether `opcode`, with its prefix, pushes a return address and jumps.
+fn is_call(opcode: u16) -> bool {
+    matches!(
+        opcode,
+        0x17 | 0x8D | 0x9D | 0xAD | 0xBD | 0x3F | 0x103F | 0x113F
+    )
+}
+
+impl Profile {
+    /// An empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge the instruction at `pc` and follow any call or return it
+    /// made. `s` and `new_pc` are the registers after it ran.
+    pub(crate) fn instruction(
+        &mut self,
+        pc: u16,
+        opcode: u16,
+        s_before: u16,
+        s: u16,
+        new_pc: u16,
+        cycles: u64,
+    ) {
+        if self.stack.is_empty() {
+            self.root.get_or_insert(pc);
+        }
+        self.charge(cycles);
+        if is_call(opcode) && s != s_before {
+            self.enter(new_pc, s);
+        } else {
+            self.unwind(s);
+        }
+    }
+
+    /// Follow an interrupt into `handler` with its state pushed below `s`,
+    /// and charge it the `cycles` taken.
+    pub(crate) fn interrupt(&mut self, handler: u16, s: u16, cycles: u64) {
+        self.enter(handler, s);
+        self.charge(cycles);
+    }
+
+    /// The counts so far, leaving this profile empty but still following
+    /// the routines that are running.
+    pub(crate) fn take(&mut self) -> Profile {
+        let cycles = std::mem::take(&mut self.cycles);
+        let taken = Profile {
+            root: self.root,
+            stack: self.stack.clone(),
+            cycles,
+            routines: std::mem::take(&mut self.routines),
+            edges: std::mem::take(&mut self.edges),
+        };
+        for frame in &mut self.stack {
+            frame.entered = 0;
+        }
+        taken
+    }
+
+    /// Cycles charged to all routines.
+    pub fn total_cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// The routine running, or `None` before the first instruction.
+    pub fn current(&self) -> Option<u16> {
+        self.stack.last().map(|frame| frame.routine).or(self.root)
+    }
+
+    /// Entry addresses of the routines running, outermost first.
+    pub fn call_stack(&self) -> Vec<u16> {
+        self.root
+            .into_iter()
+            .chain(self.stack.iter().map(|frame| frame.routine))
+            .collect()
+    }
+
+    /// Every routine charged or called, most self cycles first.
+    pub fn routines(&self) -> Vec<RoutineProfile> {
+        let mut routines = self.routines.clone();
+        for (i, frame) in self.stack.iter().enumerate() {
+            if !self.stack[..i].iter().any(|f| f.routine == frame.routine) {
+                routines.entry(frame.routine).or_default().total_cycles +=
+                    self.cycles - frame.entered;
+            }
+        }
+        if let Some(root) = self.root {
+            routines.entry(root).or_default().total_cycles = self.cycles;
+        }
+        let mut routines: Vec<_> = routines
+            .into_iter()
+            .map(|(addr, counts)| RoutineProfile {
+                addr,
+                calls: counts.calls,
+                self_cycles: counts.self_cycles,
+                total_cycles: counts.total_cycles,
+            })
+            .collect();
+        routines.sort_by_key(|routine| Reverse(routine.self_cycles));
+        routines
+    }
+
+    /// Every caller and callee pair, most cycles first.
+    pub fn calls(&self) -> Vec<CallProfile> {
+        let mut edges = self.edges.clone();
+        for i in 0..self.stack.len() {
+            let Some(edge) = self.edge(i) else {
+                continue;
+            };
+            if !(0..i).any(|j| self.edge(j) == Some(edge)) {
+                edges.entry(edge).or_default().1 += self.cycles - self.stack[i].entered;
+            }
+        }
+        let mut calls: Vec<_> = edges
+            .into_iter()
+            .map(|((caller, callee), (calls, cycles))| CallProfile {
+                caller,
+                callee,
+                calls,
+                cycles,
+            })
+            .collect();
+        calls.sort_by_key(|call| Reverse(call.cycles));
+        calls
+    }
+
+    /// A table of [`Self::routines`] with their share of the cycles.
+    pub fn flat_report(&self, symbols: Option<&SymbolTable>) -> String {
+        let share = |cycles: u64| 100.0 * cycles as f64 / self.cycles.max(1) as f64;
+        let mut out = format!("{} cycles\n\n", self.cycles);
+        let _ = writeln!(
+            out,
+            "{:>12}  {:>6}  {:>12}  {:>6}  {:>8}  Routine",
+            "Self", "%", "Total", "%", "Calls"
+        );
+        for routine in self.routines() {
+            let _ = writeln!(
+                out,
+                "{:>12}  {:>6.2}  {:>12}  {:>6.2}  {:>8}  {}",
+                routine.self_cycles,
+                share(routine.self_cycles),
+                routine.total_cycles,
+                share(routine.total_cycles),
+                routine.calls,
+                name(symbols, routine.addr)
+            );
+        }
+        out
+    }
+
+    /// Each routine, most total cycles first, with the routines that called
+    /// it and the routines it called.
+    pub fn call_graph_report(&self, symbols: Option<&SymbolTable>) -> String {
+        let mut routines = self.routines();
+        routines.sort_by_key(|routine| Reverse(routine.total_cycles));
+        let calls = self.calls();
+        let mut out = String::new();
+        for routine in routines {
+            let _ = writeln!(
+                out,
+                "{}: {} cycles, {} self, {} calls",
+                name(symbols, routine.addr),
+                routine.total_cycles,
+                routine.self_cycles,
+                routine.calls
+            );
+            for call in calls.iter().filter(|call| call.callee == routine.addr) {
+                let _ = writeln!(
+                    out,
+                    "    from {:<24}  {:>8} calls",
+                    name(symbols, call.caller),
+                    call.calls
+                );
+            }
+            for call in calls.iter().filter(|call| call.caller == routine.addr) {
+                let _ = writeln!(
+                    out,
+                    "    to   {:<24}  {:>8} calls  {:>12} cycles",
+                    name(symbols, call.callee),
+                    call.calls,
+                    call.cycles
+                );
+            }
+        }
+        out
+    }
+
+    fn charge(&mut self, cycles: u64) {
+        self.cycles += cycles;
+        if let Some(routine) = self.current() {
+            self.routines.entry(routine).or_default().self_cycles += cycles;
+        }
+    }
+
+    fn enter(&mut self, routine: u16, s: u16) {
+        self.routines.entry(routine).or_default().calls += 1;
+        if let Some(caller) = self.current() {
+            self.edges.entry((caller, routine)).or_default().0 += 1;
+        }
+        self.stack.push(Frame {
+            routine,
+            s,
+            entered: self.cycles,
+        });
+    }
+
+    /// Return from every routine whose return address is above `s`.
+    fn unwind(&mut self, s: u16) {
+        while let Some(top) = self.stack.last()
+            && s.wrapping_sub(top.s) as i16 > 0
+        {
+            let i = self.stack.len() - 1;
+            let edge = self.edge(i);
+            let frame = self.stack.pop().unwrap();
+            let elapsed = self.cycles - frame.entered;
+            if !self.stack.iter().any(|f| f.routine == frame.routine) {
+                self.routines.entry(frame.routine).or_default().total_cycles += elapsed;
+            }
+            if let Some(edge) = edge
+                && !(0..i).any(|j| self.edge(j) == Some(edge))
+            {
+                self.edges.entry(edge).or_default().1 += elapsed;
+            }
+        }
+    }
+
+    /// The caller and callee of `self.stack[i]`.
+    fn edge(&self, i: usize) -> Option<(u16, u16)> {
+        let caller = match i {
+            0 => self.root?,
+            _ => self.stack[i - 1].routine,
+        };
+        Some((caller, self.stack[i].routine))
+    }
+}
+
+/// `addr` by symbol, if there is one, else in hex.
+fn name(symbols: Option<&SymbolTable>, addr: u16) -> String {
+    symbols
+        .and_then(|symbols| symbols.describe(addr))
+        .unwrap_or_else(|| format!("${addr:04X}"))
+}
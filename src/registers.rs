@@ -14,6 +14,8 @@
 
 use std::fmt;
 
+use crate::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+
 // ---------------------------------------------------------------------------
 // Condition Code Register
 // ---------------------------------------------------------------------------
@@ -37,6 +39,23 @@ pub(crate) const CC_E: u8 = 0x80; // Entire state saved
 #[derive(Clone, Copy, Default, PartialEq, Eq)]
 pub struct ConditionCodes(pub(crate) u8);
 impl ConditionCodes {
+    /// Mask of the carry flag in the CC byte.
+    pub const C: u8 = CC_C;
+    /// Mask of the overflow flag.
+    pub const V: u8 = CC_V;
+    /// Mask of the zero flag.
+    pub const Z: u8 = CC_Z;
+    /// Mask of the negative flag.
+    pub const N: u8 = CC_N;
+    /// Mask of the IRQ mask.
+    pub const I: u8 = CC_I;
+    /// Mask of the half-carry flag.
+    pub const H: u8 = CC_H;
+    /// Mask of the FIRQ mask.
+    pub const F: u8 = CC_F;
+    /// Mask of the entire-state flag.
+    pub const E: u8 = CC_E;
+
     pub const fn new() -> Self {
         Self(0)
     }
@@ -125,6 +144,29 @@ impl ConditionCodes {
         self.0 &= mask;
     }
 
+    /// Whether a branch on `cond` is taken with these flags.
+    #[inline]
+    pub const fn test(self, cond: Condition) -> bool {
+        match cond {
+            Condition::Always => true,
+            Condition::Never => false,
+            Condition::Hi => !self.carry() && !self.zero(),
+            Condition::Ls => self.carry() || self.zero(),
+            Condition::Cc => !self.carry(),
+            Condition::Cs => self.carry(),
+            Condition::Ne => !self.zero(),
+            Condition::Eq => self.zero(),
+            Condition::Vc => !self.overflow(),
+            Condition::Vs => self.overflow(),
+            Condition::Pl => !self.negative(),
+            Condition::Mi => self.negative(),
+            Condition::Ge => self.negative() == self.overflow(),
+            Condition::Lt => self.negative() != self.overflow(),
+            Condition::Gt => !self.zero() && self.negative() == self.overflow(),
+            Condition::Le => self.zero() || self.negative() != self.overflow(),
+        }
+    }
+
     fn set_bit(&mut self, mask: u8, v: bool) {
         if v {
             self.0 |= mask;
@@ -158,6 +200,205 @@ impl fmt::Display for ConditionCodes {
     }
 }
 
+/// How an instruction changed the condition codes: the flags before and
+/// after, compared as masks of [`ConditionCodes::C`] and the like.
+///
+/// # Example
+/// ```
+/// use mc6809_core::registers::{ConditionCodes, FlagDelta};
+///
+/// // N set, Z cleared, I unchanged
+/// let delta = FlagDelta::new(
+///     ConditionCodes::from_byte(0x14),
+///     ConditionCodes::from_byte(0x18),
+/// );
+/// assert_eq!(delta.set(), ConditionCodes::N);
+/// assert_eq!(delta.cleared(), ConditionCodes::Z);
+/// assert_eq!(delta.changed(), ConditionCodes::N | ConditionCodes::Z);
+/// assert_eq!(delta.to_string(), "+N -Z");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlagDelta {
+    /// Flags before the instruction.
+    pub before: ConditionCodes,
+    /// Flags after it.
+    pub after: ConditionCodes,
+}
+
+impl FlagDelta {
+    /// The change from `before` to `after`.
+    pub const fn new(before: ConditionCodes, after: ConditionCodes) -> Self {
+        Self { before, after }
+    }
+
+    /// Flags that were clear and are now set.
+    pub const fn set(self) -> u8 {
+        !self.before.0 & self.after.0
+    }
+
+    /// Flags that were set and are now clear.
+    pub const fn cleared(self) -> u8 {
+        self.before.0 & !self.after.0
+    }
+
+    /// Flags that differ, set or cleared.
+    pub const fn changed(self) -> u8 {
+        self.before.0 ^ self.after.0
+    }
+
+    /// `true` if no flag changed.
+    pub const fn is_empty(self) -> bool {
+        self.changed() == 0
+    }
+}
+
+/// Each changed flag, `+` if set and `-` if cleared, from E down to C;
+/// empty if none changed.
+impl fmt::Display for FlagDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        for (mask, name) in [
+            (CC_E, 'E'),
+            (CC_F, 'F'),
+            (CC_H, 'H'),
+            (CC_I, 'I'),
+            (CC_N, 'N'),
+            (CC_Z, 'Z'),
+            (CC_V, 'V'),
+            (CC_C, 'C'),
+        ] {
+            if self.changed() & mask != 0 {
+                let sign = if self.set() & mask != 0 { '+' } else { '-' };
+                write!(f, "{sep}{sign}{name}")?;
+                sep = " ";
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A branch condition, numbered as in the low four bits of the branch
+/// opcodes: BRA is 0x20, BEQ 0x27 and LBEQ 0x1027.
+///
+/// # Example
+/// ```
+/// use mc6809_core::registers::{Condition, ConditionCodes};
+///
+/// let cc = ConditionCodes::from_byte(0x04); // Z
+/// assert!(cc.test(Condition::Eq));
+/// assert!(!cc.test(Condition::Gt));
+/// assert_eq!(Condition::of_branch(0x2E), Some(Condition::Gt));
+/// assert_eq!(Condition::of_branch(0x12), None); // NOP
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Condition {
+    /// Always (BRA).
+    Always,
+    /// Never (BRN).
+    Never,
+    /// Higher, unsigned: C and Z clear.
+    Hi,
+    /// Lower or same, unsigned: C or Z set.
+    Ls,
+    /// Carry clear, or higher or same unsigned (BHS).
+    Cc,
+    /// Carry set, or lower unsigned (BLO).
+    Cs,
+    /// Not equal: Z clear.
+    Ne,
+    /// Equal: Z set.
+    Eq,
+    /// Overflow clear.
+    Vc,
+    /// Overflow set.
+    Vs,
+    /// Plus: N clear.
+    Pl,
+    /// Minus: N set.
+    Mi,
+    /// Greater or equal, signed: N equals V.
+    Ge,
+    /// Less than, signed: N differs from V.
+    Lt,
+    /// Greater than, signed: Z clear and N equals V.
+    Gt,
+    /// Less or equal, signed: Z set or N differs from V.
+    Le,
+}
+
+impl Condition {
+    /// Every condition, in opcode order.
+    pub const ALL: [Condition; 16] = [
+        Condition::Always,
+        Condition::Never,
+        Condition::Hi,
+        Condition::Ls,
+        Condition::Cc,
+        Condition::Cs,
+        Condition::Ne,
+        Condition::Eq,
+        Condition::Vc,
+        Condition::Vs,
+        Condition::Pl,
+        Condition::Mi,
+        Condition::Ge,
+        Condition::Lt,
+        Condition::Gt,
+        Condition::Le,
+    ];
+
+    /// The condition numbered by the low four bits of `code`.
+    pub const fn from_code(code: u8) -> Condition {
+        Self::ALL[(code & 0x0F) as usize]
+    }
+
+    /// The condition the branch `opcode` tests, or `None` if it is not a
+    /// branch. A page prefix goes in the high byte, as in 0x1027 for LBEQ;
+    /// BSR, LBSR and LBRA branch [`Self::Always`].
+    pub const fn of_branch(opcode: u16) -> Option<Condition> {
+        match opcode {
+            0x20..=0x2F | 0x1020..=0x102F => Some(Self::from_code(opcode as u8)),
+            0x16 | 0x17 | 0x8D => Some(Condition::Always),
+            _ => None,
+        }
+    }
+
+    /// The condition's suffix in branch mnemonics: `"EQ"` for BEQ, `"RA"`
+    /// for BRA.
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Condition::Always => "RA",
+            Condition::Never => "RN",
+            Condition::Hi => "HI",
+            Condition::Ls => "LS",
+            Condition::Cc => "CC",
+            Condition::Cs => "CS",
+            Condition::Ne => "NE",
+            Condition::Eq => "EQ",
+            Condition::Vc => "VC",
+            Condition::Vs => "VS",
+            Condition::Pl => "PL",
+            Condition::Mi => "MI",
+            Condition::Ge => "GE",
+            Condition::Lt => "LT",
+            Condition::Gt => "GT",
+            Condition::Le => "LE",
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.suffix())
+    }
+}
+
+/// Bit positions in the HD6309 MD register.
+pub(crate) const MD_NATIVE: u8 = 0x01; // Native mode
+pub(crate) const MD_FIRQ_IRQ: u8 = 0x02; // FIRQ stacks the entire state, as IRQ
+pub(crate) const MD_IL: u8 = 0x40; // Illegal instruction trap taken
+pub(crate) const MD_DZ: u8 = 0x80; // Division by zero trap taken
+
 // ---------------------------------------------------------------------------
 // Register file
 // ---------------------------------------------------------------------------
@@ -167,11 +408,16 @@ impl fmt::Display for ConditionCodes {
 /// Register D is stored as a `u16` with A in the high byte and B in the low byte,
 /// matching the hardware layout.
 ///
+/// The fields are public and the accessors cover only the registers that
+/// share storage, A, B and D. Nothing here enforces CPU behaviour: writes
+/// that need it, such as a write to S arming NMI, are made through
+/// [`Cpu::registers_mut`](crate::Cpu::registers_mut).
+///
 /// `#[repr(C)]` guarantees a stable, predictable memory layout for use in
 /// JIT-compiled code and FFI contexts. Field offsets (bytes):
 /// `d`=0, `x`=2, `y`=4, `u`=6, `s`=8, `pc`=10, `dp`=12, `cc`=13.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Registers {
     /// Accumulator D (A:B). A = high byte, B = low byte.
     pub d: u16,
@@ -205,7 +451,7 @@ impl Registers {
         }
     }
 
-    // ---- A / B accessors (D = A:B, big-endian) ----
+    // ---- A / B / D accessors (D = A:B, big-endian) ----
 
     /// Read accumulator A (high byte of D).
     pub const fn a(self) -> u8 {
@@ -226,6 +472,340 @@ impl Registers {
     pub fn set_b(&mut self, val: u8) {
         self.d = (self.d & 0xFF00) | (val as u16);
     }
+
+    /// Read accumulator D (A:B).
+    pub const fn d(self) -> u16 {
+        self.d
+    }
+
+    /// Write accumulator D (A:B).
+    pub fn set_d(&mut self, val: u16) {
+        self.d = val;
+    }
+
+    // ---- access by name ----
+
+    /// Read `reg`, zero-extended if it is an 8-bit register.
+    pub fn get(&self, reg: Reg) -> u16 {
+        match reg {
+            Reg::A => self.a() as u16,
+            Reg::B => self.b() as u16,
+            Reg::D => self.d,
+            Reg::X => self.x,
+            Reg::Y => self.y,
+            Reg::U => self.u,
+            Reg::S => self.s,
+            Reg::PC => self.pc,
+            Reg::DP => self.dp as u16,
+            Reg::CC => self.cc.to_byte() as u16,
+        }
+    }
+
+    /// Write `reg`; an 8-bit register takes the low byte of `val`.
+    ///
+    /// Writing S here does not arm NMI as an instruction loading S does.
+    pub fn set(&mut self, reg: Reg, val: u16) {
+        match reg {
+            Reg::A => self.set_a(val as u8),
+            Reg::B => self.set_b(val as u8),
+            Reg::D => self.d = val,
+            Reg::X => self.x = val,
+            Reg::Y => self.y = val,
+            Reg::U => self.u = val,
+            Reg::S => self.s = val,
+            Reg::PC => self.pc = val,
+            Reg::DP => self.dp = val as u8,
+            Reg::CC => self.cc = ConditionCodes::from_byte(val as u8),
+        }
+    }
+
+    /// A builder starting from all-zero registers.
+    ///
+    /// # Example
+    /// ```
+    /// use mc6809_core::Registers;
+    ///
+    /// let regs = Registers::builder().a(0x42).x(0x1234).s(0x8000).build();
+    /// assert_eq!(regs.d, 0x4200);
+    /// assert_eq!(regs.s, 0x8000);
+    /// ```
+    pub const fn builder() -> RegistersBuilder {
+        RegistersBuilder(Self::new())
+    }
+
+    /// The registers that differ from `expected`, as (register, expected,
+    /// actual). A and B are compared in place of D.
+    ///
+    /// [`assert_regs!`](crate::assert_regs) reports these when it fails.
+    pub fn differences(&self, expected: &Registers) -> Vec<(Reg, u16, u16)> {
+        [
+            Reg::A,
+            Reg::B,
+            Reg::X,
+            Reg::Y,
+            Reg::U,
+            Reg::S,
+            Reg::PC,
+            Reg::DP,
+            Reg::CC,
+        ]
+        .into_iter()
+        .map(|reg| (reg, expected.get(reg), self.get(reg)))
+        .filter(|&(_, expected, actual)| expected != actual)
+        .collect()
+    }
+}
+
+/// Chainable construction of [`Registers`], from [`Registers::builder`].
+///
+/// Registers not given are zero. Setting A or B after D replaces that half.
+#[derive(Clone, Copy, Debug)]
+pub struct RegistersBuilder(Registers);
+
+impl RegistersBuilder {
+    /// Set accumulator A.
+    pub const fn a(mut self, val: u8) -> Self {
+        self.0.d = (self.0.d & 0x00FF) | ((val as u16) << 8);
+        self
+    }
+
+    /// Set accumulator B.
+    pub const fn b(mut self, val: u8) -> Self {
+        self.0.d = (self.0.d & 0xFF00) | (val as u16);
+        self
+    }
+
+    /// Set accumulator D.
+    pub const fn d(mut self, val: u16) -> Self {
+        self.0.d = val;
+        self
+    }
+
+    /// Set index register X.
+    pub const fn x(mut self, val: u16) -> Self {
+        self.0.x = val;
+        self
+    }
+
+    /// Set index register Y.
+    pub const fn y(mut self, val: u16) -> Self {
+        self.0.y = val;
+        self
+    }
+
+    /// Set the user stack pointer.
+    pub const fn u(mut self, val: u16) -> Self {
+        self.0.u = val;
+        self
+    }
+
+    /// Set the hardware stack pointer.
+    pub const fn s(mut self, val: u16) -> Self {
+        self.0.s = val;
+        self
+    }
+
+    /// Set the program counter.
+    pub const fn pc(mut self, val: u16) -> Self {
+        self.0.pc = val;
+        self
+    }
+
+    /// Set the direct page register.
+    pub const fn dp(mut self, val: u8) -> Self {
+        self.0.dp = val;
+        self
+    }
+
+    /// Set the condition codes from their byte.
+    pub const fn cc(mut self, val: u8) -> Self {
+        self.0.cc = ConditionCodes::from_byte(val);
+        self
+    }
+
+    /// The registers.
+    pub const fn build(self) -> Registers {
+        self.0
+    }
+}
+
+/// Assert that two [`Registers`] are equal, listing each register that
+/// differs when they are not.
+///
+/// Either side may be a `Registers` or a reference to one. An optional
+/// format string and arguments are added to the panic message, as with
+/// [`assert_eq!`].
+///
+/// # Example
+/// ```
+/// use mc6809_core::{Cpu, FlatRam, Registers, assert_regs};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x86, 0x42], 0x0400); // LDA #$42
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.step(&mut ram);
+///
+/// let expected = Registers::builder().a(0x42).pc(0x0402).cc(0x50).build();
+/// assert_regs!(cpu.registers(), expected);
+/// ```
+///
+/// A failure panics with a message such as
+///
+/// ```text
+/// registers differ:
+///     A: expected 43, found 42
+///    PC: expected 0403, found 0402
+/// ```
+#[macro_export]
+macro_rules! assert_regs {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_regs!($actual, $expected, "")
+    };
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {{
+        let actual: &$crate::Registers = &$actual;
+        let expected: &$crate::Registers = &$expected;
+        let differences = actual.differences(expected);
+        if !differences.is_empty() {
+            let mut message = ::std::format!($($arg)+);
+            if !message.is_empty() {
+                message.push_str(": ");
+            }
+            message.push_str("registers differ:");
+            for (reg, expected, actual) in differences {
+                let width = if reg.is_16bit() { 4 } else { 2 };
+                message.push_str(&::std::format!(
+                    "\n  {reg:>3}: expected {expected:0width$X}, found {actual:0width$X}"
+                ));
+            }
+            ::std::panic!("{}", message);
+        }
+    }};
+}
+
+/// A 6809 register, for reading and writing [`Registers`] by name.
+///
+/// # Example
+/// ```
+/// use mc6809_core::registers::{Reg, Registers};
+///
+/// let mut regs = Registers::new();
+/// regs.set(Reg::D, 0x1234);
+/// assert_eq!(regs.get(Reg::A), 0x12);
+///
+/// // The post-byte of TFR X,Y
+/// let post = Reg::X.code() << 4 | Reg::Y.code();
+/// assert_eq!(post, 0x12);
+/// assert_eq!(Reg::from_code(post & 0x0F), Some(Reg::Y));
+/// assert_eq!(Reg::from_name("dp"), Some(Reg::DP));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Reg {
+    /// Accumulator A.
+    A,
+    /// Accumulator B.
+    B,
+    /// Accumulator D (A:B).
+    D,
+    /// Index register X.
+    X,
+    /// Index register Y.
+    Y,
+    /// User stack pointer.
+    U,
+    /// Hardware stack pointer.
+    S,
+    /// Program counter.
+    PC,
+    /// Direct page register.
+    DP,
+    /// Condition codes.
+    CC,
+}
+
+impl Reg {
+    /// Every register, in the order of their TFR and EXG codes.
+    pub const ALL: [Reg; 10] = [
+        Reg::D,
+        Reg::X,
+        Reg::Y,
+        Reg::U,
+        Reg::S,
+        Reg::PC,
+        Reg::A,
+        Reg::B,
+        Reg::CC,
+        Reg::DP,
+    ];
+
+    /// The register's 4-bit code in a TFR or EXG post-byte.
+    pub const fn code(self) -> u8 {
+        match self {
+            Reg::D => 0x0,
+            Reg::X => 0x1,
+            Reg::Y => 0x2,
+            Reg::U => 0x3,
+            Reg::S => 0x4,
+            Reg::PC => 0x5,
+            Reg::A => 0x8,
+            Reg::B => 0x9,
+            Reg::CC => 0xA,
+            Reg::DP => 0xB,
+        }
+    }
+
+    /// The register with TFR and EXG code `code`, or `None` if the 6809
+    /// gives the code to no register.
+    pub const fn from_code(code: u8) -> Option<Reg> {
+        Some(match code {
+            0x0 => Reg::D,
+            0x1 => Reg::X,
+            0x2 => Reg::Y,
+            0x3 => Reg::U,
+            0x4 => Reg::S,
+            0x5 => Reg::PC,
+            0x8 => Reg::A,
+            0x9 => Reg::B,
+            0xA => Reg::CC,
+            0xB => Reg::DP,
+            _ => return None,
+        })
+    }
+
+    /// `true` for the 16-bit registers.
+    pub const fn is_16bit(self) -> bool {
+        matches!(self, Reg::D | Reg::X | Reg::Y | Reg::U | Reg::S | Reg::PC)
+    }
+
+    /// The register's name as an assembler writes it.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Reg::A => "A",
+            Reg::B => "B",
+            Reg::D => "D",
+            Reg::X => "X",
+            Reg::Y => "Y",
+            Reg::U => "U",
+            Reg::S => "S",
+            Reg::PC => "PC",
+            Reg::DP => "DP",
+            Reg::CC => "CC",
+        }
+    }
+
+    /// The register called `name`, in any case, or `None`.
+    pub fn from_name(name: &str) -> Option<Reg> {
+        Reg::ALL
+            .into_iter()
+            .find(|reg| reg.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
 }
 
 impl fmt::Display for Registers {
@@ -245,3 +825,118 @@ impl fmt::Display for Registers {
         )
     }
 }
+
+impl Snapshot for Registers {
+    fn snapshot(&self, out: &mut StateWriter) {
+        for word in [self.d, self.x, self.y, self.u, self.s, self.pc] {
+            out.u16(word);
+        }
+        out.u8(self.dp);
+        out.u8(self.cc.0);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for word in [
+            &mut self.d,
+            &mut self.x,
+            &mut self.y,
+            &mut self.u,
+            &mut self.s,
+            &mut self.pc,
+        ] {
+            *word = state.u16()?;
+        }
+        self.dp = state.u8()?;
+        self.cc = ConditionCodes(state.u8()?);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HD6309 registers
+// ---------------------------------------------------------------------------
+
+/// The registers the HD6309 adds to the 6809 set.
+///
+/// W is stored as a `u16` with E in the high byte and F in the low byte; D
+/// and W together form the 32-bit Q. The zero register has no storage: it
+/// reads as 0 and ignores writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Hd6309Registers {
+    /// Accumulator W (E:F). E = high byte, F = low byte.
+    pub w: u16,
+    /// Value register, kept across reset.
+    pub v: u16,
+    /// Mode and error register: native mode (bit 0), FIRQ mode (bit 1),
+    /// illegal instruction (bit 6) and division by zero (bit 7).
+    pub md: u8,
+}
+
+impl Hd6309Registers {
+    pub const fn new() -> Self {
+        Self { w: 0, v: 0, md: 0 }
+    }
+
+    /// Read accumulator E (high byte of W).
+    pub const fn e(self) -> u8 {
+        (self.w >> 8) as u8
+    }
+
+    /// Read accumulator F (low byte of W).
+    pub const fn f(self) -> u8 {
+        self.w as u8
+    }
+
+    /// Write accumulator E (high byte of W), preserving F.
+    pub fn set_e(&mut self, val: u8) {
+        self.w = (self.w & 0x00FF) | ((val as u16) << 8);
+    }
+
+    /// Write accumulator F (low byte of W), preserving E.
+    pub fn set_f(&mut self, val: u8) {
+        self.w = (self.w & 0xFF00) | (val as u16);
+    }
+
+    /// Read accumulator W (E:F).
+    pub const fn w(self) -> u16 {
+        self.w
+    }
+
+    /// Write accumulator W (E:F).
+    pub fn set_w(&mut self, val: u16) {
+        self.w = val;
+    }
+
+    /// Whether MD selects native mode.
+    pub const fn native(self) -> bool {
+        self.md & MD_NATIVE != 0
+    }
+}
+
+impl fmt::Display for Hd6309Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "E={:02X} F={:02X} V={:04X} MD={:02X}",
+            self.e(),
+            self.f(),
+            self.v,
+            self.md,
+        )
+    }
+}
+
+impl Snapshot for Hd6309Registers {
+    fn snapshot(&self, out: &mut StateWriter) {
+        out.u16(self.w);
+        out.u16(self.v);
+        out.u8(self.md);
+    }
+
+    fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.w = state.u16()?;
+        self.v = state.u16()?;
+        self.md = state.u8()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Save states.
+//!
+//! Components that hold emulation state implement [`Snapshot`], writing it
+//! with a [`StateWriter`] and reading it back with a [`StateReader`]. The
+//! format is a plain byte stream with no field names: a state can only be
+//! loaded into a component built the same way as the one that saved it.
+//! [`Machine::save_state`](crate::machine::Machine::save_state) collects the
+//! CPU, the scheduler and the whole bus into one buffer.
+//! [`Cpu::save_state`](crate::Cpu::save_state) saves the CPU alone in a
+//! documented layout for tools outside Rust.
+
+use std::error::Error;
+use std::fmt;
+
+/// State that can be saved and restored.
+///
+/// Both methods default to saving nothing, which suits components whose
+/// state is all configuration (ROM, address decoding) or that cannot be
+/// saved at all (closures). Stateful devices override both, and `restore` must
+/// read exactly what `snapshot` wrote.
+///
+/// # Example
+/// ```
+/// use mc6809_core::snapshot::{Snapshot, StateError, StateReader, StateWriter};
+///
+/// struct Counter(u16);
+///
+/// impl Snapshot for Counter {
+///     fn snapshot(&self, out: &mut StateWriter) {
+///         out.u16(self.0);
+///     }
+///     fn restore(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+///         self.0 = state.u16()?;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut out = StateWriter::new();
+/// Counter(1234).snapshot(&mut out);
+/// let bytes = out.into_bytes();
+///
+/// let mut counter = Counter(0);
+/// counter.restore(&mut StateReader::new(&bytes)).unwrap();
+/// assert_eq!(counter.0, 1234);
+/// ```
+pub trait Snapshot {
+    /// Append the state to `out`.
+    fn snapshot(&self, _out: &mut StateWriter) {}
+
+    /// Restore the state saved by [`Self::snapshot`] from `state`.
+    fn restore(&mut self, _state: &mut StateReader<'_>) -> Result<(), StateError> {
+        Ok(())
+    }
+}
+
+/// Why a state could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateError {
+    /// The state ended before everything was read.
+    Truncated,
+    /// The state does not start with a save-state header this version of the
+    /// crate understands.
+    BadHeader,
+    /// The state was saved from a differently built component; the message
+    /// names what did not match.
+    Mismatch(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Truncated => f.write_str("save state is truncated"),
+            StateError::BadHeader => {
+                f.write_str("not a save state, or from an unsupported version")
+            }
+            StateError::Mismatch(what) => {
+                write!(f, "save state does not match this machine: {what}")
+            }
+        }
+    }
+}
+
+impl Error for StateError {}
+
+/// Big-endian byte sink for [`Snapshot::snapshot`].
+#[derive(Default)]
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl StateWriter {
+    /// Create an empty writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a byte.
+    pub fn u8(&mut self, val: u8) {
+        self.buf.push(val);
+    }
+
+    /// Append a 16-bit word.
+    pub fn u16(&mut self, val: u16) {
+        self.buf.extend_from_slice(&val.to_be_bytes());
+    }
+
+    /// Append a 32-bit word.
+    pub fn u32(&mut self, val: u32) {
+        self.buf.extend_from_slice(&val.to_be_bytes());
+    }
+
+    /// Append a 64-bit word.
+    pub fn u64(&mut self, val: u64) {
+        self.buf.extend_from_slice(&val.to_be_bytes());
+    }
+
+    /// Append a signed 64-bit word.
+    pub fn i64(&mut self, val: i64) {
+        self.u64(val as u64);
+    }
+
+    /// Append a flag as one byte.
+    pub fn bool(&mut self, val: bool) {
+        self.u8(val as u8);
+    }
+
+    /// Append raw bytes. The reader must know their length.
+    pub fn bytes(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Append the state of `part` prefixed with its length, so that
+    /// [`StateReader::section`] can check it was read in full.
+    pub fn section(&mut self, part: &(impl Snapshot + ?Sized)) {
+        let mut inner = StateWriter::new();
+        part.snapshot(&mut inner);
+        self.u32(inner.buf.len() as u32);
+        self.bytes(&inner.buf);
+    }
+
+    /// The bytes written so far.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Big-endian byte source for [`Snapshot::restore`].
+///
+/// Every read fails with [`StateError::Truncated`] past the end of the data.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    /// Read from `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Bytes not read yet.
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Read a byte.
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read a 16-bit word.
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    /// Read a 32-bit word.
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    /// Read a 64-bit word.
+    pub fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    /// Read a signed 64-bit word.
+    pub fn i64(&mut self) -> Result<i64, StateError> {
+        Ok(self.u64()? as i64)
+    }
+
+    /// Read a flag written by [`StateWriter::bool`].
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        Ok(self.u8()? != 0)
+    }
+
+    /// Read `len` raw bytes.
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        self.take(len)
+    }
+
+    /// Fill `buf` with raw bytes.
+    pub fn bytes_into(&mut self, buf: &mut [u8]) -> Result<(), StateError> {
+        buf.copy_from_slice(self.take(buf.len())?);
+        Ok(())
+    }
+
+    /// Load `part` from a section written by [`StateWriter::section`].
+    ///
+    /// Fails with [`StateError::Mismatch`] if `part` does not read the
+    /// section exactly.
+    pub fn section(&mut self, part: &mut (impl Snapshot + ?Sized)) -> Result<(), StateError> {
+        let len = self.u32()? as usize;
+        let mut inner = StateReader::new(self.take(len)?);
+        part.restore(&mut inner)?;
+        if inner.remaining() != 0 {
+            return Err(StateError::Mismatch("section length"));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        if len > self.data.len() {
+            return Err(StateError::Truncated);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        Ok(self.take(N)?.try_into().expect("take returns N bytes"))
+    }
+}
@@ -0,0 +1,620 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Instruction and interrupt statistics.
+//!
+//! With the `hooks` feature,
+//! [`Cpu::set_opcode_stats`](crate::Cpu::set_opcode_stats) makes the CPU
+//! count every opcode it executes in an [`OpcodeStats`] table, which reports
+//! the counts per opcode and per addressing mode, most frequent first.
+//!
+//! [`Cpu::set_interrupt_stats`](crate::Cpu::set_interrupt_stats), also with
+//! `hooks`, makes it record every interrupt it takes in [`InterruptStats`]:
+//! how often each source fired, how long its handlers ran, how long it
+//! waited to be served and how deeply handlers nested.
+//!
+//! [`Cpu::run_with_stats`](crate::Cpu::run_with_stats) and
+//! [`Machine::run_for_with_stats`](crate::machine::Machine::run_for_with_stats),
+//! with `hooks` too, run like their plain versions and sum up the run in
+//! [`RunStats`].
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::disasm::{self, Mode};
+use crate::peripheral::BusSignals;
+
+/// Opcodes per page, times the unprefixed, 0x10 and 0x11 pages.
+const OPCODES: usize = 3 * 256;
+
+/// How an instruction's operand is addressed, as reported by
+/// [`OpcodeStats`] and [`Cpu::effective_address_at`](crate::Cpu::effective_address_at).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressingMode {
+    /// No operand, or an accumulator.
+    Inherent,
+    /// An 8 or 16-bit constant.
+    Immediate,
+    /// An address in the direct page.
+    Direct,
+    /// A 16-bit address.
+    Extended,
+    /// An indexed post-byte.
+    Indexed,
+    /// A short or long branch offset.
+    Relative,
+    /// A register post-byte: TFR, EXG, PSH and PUL.
+    Register,
+}
+
+impl AddressingMode {
+    /// Every mode, in the order [`OpcodeStats`] lists them.
+    pub const ALL: [AddressingMode; 7] = [
+        AddressingMode::Inherent,
+        AddressingMode::Immediate,
+        AddressingMode::Direct,
+        AddressingMode::Extended,
+        AddressingMode::Indexed,
+        AddressingMode::Relative,
+        AddressingMode::Register,
+    ];
+
+    pub(crate) fn from_mode(mode: Mode) -> Self {
+        match mode {
+            Mode::Inherent => AddressingMode::Inherent,
+            Mode::Imm8 | Mode::Imm16 => AddressingMode::Immediate,
+            Mode::Direct => AddressingMode::Direct,
+            Mode::Extended => AddressingMode::Extended,
+            Mode::Indexed => AddressingMode::Indexed,
+            Mode::Rel8 | Mode::Rel16 => AddressingMode::Relative,
+            Mode::Pair | Mode::ListS | Mode::ListU => AddressingMode::Register,
+        }
+    }
+}
+
+impl fmt::Display for AddressingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            AddressingMode::Inherent => "inherent",
+            AddressingMode::Immediate => "immediate",
+            AddressingMode::Direct => "direct",
+            AddressingMode::Extended => "extended",
+            AddressingMode::Indexed => "indexed",
+            AddressingMode::Relative => "relative",
+            AddressingMode::Register => "register",
+        })
+    }
+}
+
+/// One row of [`OpcodeStats::opcodes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpcodeCount {
+    /// The opcode, with its prefix for pages 1 and 2: `0x86` is `LDA`
+    /// immediate and `0x108E` is `LDY` immediate.
+    pub opcode: u16,
+    /// The 6809 mnemonic, or `None` for an undefined opcode.
+    pub mnemonic: Option<&'static str>,
+    /// The 6809 addressing mode, or `None` for an undefined opcode.
+    pub mode: Option<AddressingMode>,
+    /// Times executed.
+    pub count: u64,
+}
+
+/// Execution counts for every opcode.
+///
+/// Opcodes are written with their prefix for pages 1 and 2, as in
+/// [`OpcodeCount::opcode`]. Names and modes come from the 6809
+/// disassembler, so opcodes it does not define, including the HD6309's own,
+/// are counted but have neither and are left out of the per-mode totals.
+///
+/// `Display` prints the whole report: the opcodes executed, then the
+/// addressing modes, each most frequent first with its share of the total.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "hooks")] {
+/// use mc6809_core::stats::AddressingMode;
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// // LDA #1; LDY #2; LDA #3
+/// ram.load(&[0x86, 0x01, 0x10, 0x8E, 0x00, 0x02, 0x86, 0x03], 0x0400);
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.set_opcode_stats(true);
+/// for _ in 0..3 {
+///     cpu.step(&mut ram);
+/// }
+///
+/// let stats = cpu.opcode_stats().unwrap();
+/// assert_eq!(stats.count(0x86), 2);
+/// assert_eq!(stats.count(0x108E), 1);
+/// assert_eq!(stats.mode_count(AddressingMode::Immediate), 3);
+/// assert_eq!(stats.opcodes()[0].mnemonic, Some("LDA"));
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct OpcodeStats {
+    counts: Box<[u64; OPCODES]>,
+}
+
+/// Where `opcode` is counted, or `None` if it has an unknown prefix.
+fn index(opcode: u16) -> Option<usize> {
+    let page = match opcode >> 8 {
+        0x00 => 0,
+        0x10 => 1,
+        0x11 => 2,
+        _ => return None,
+    };
+    Some(page * 256 + (opcode & 0xFF) as usize)
+}
+
+/// The opcode counted at `index`.
+fn opcode_at(index: usize) -> u16 {
+    const PREFIXES: [u16; 3] = [0x0000, 0x1000, 0x1100];
+    PREFIXES[index / 256] | (index % 256) as u16
+}
+
+impl OpcodeStats {
+    /// An empty table.
+    pub fn new() -> Self {
+        Self {
+            counts: Box::new([0; OPCODES]),
+        }
+    }
+
+    /// Count one execution of `opcode`.
+    #[cfg(feature = "hooks")]
+    pub(crate) fn record(&mut self, opcode: u16) {
+        if let Some(index) = index(opcode) {
+            self.counts[index] += 1;
+        }
+    }
+
+    /// Times `opcode` was executed; 0 for one without a valid prefix.
+    pub fn count(&self, opcode: u16) -> u64 {
+        index(opcode).map_or(0, |index| self.counts[index])
+    }
+
+    /// Instructions counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Times an instruction using `mode` was executed.
+    pub fn mode_count(&self, mode: AddressingMode) -> u64 {
+        self.rows()
+            .filter(|row| row.mode == Some(mode))
+            .map(|row| row.count)
+            .sum()
+    }
+
+    /// Every opcode executed at least once, most frequent first.
+    pub fn opcodes(&self) -> Vec<OpcodeCount> {
+        let mut rows: Vec<_> = self.rows().collect();
+        rows.sort_by_key(|row| Reverse(row.count));
+        rows
+    }
+
+    /// Every addressing mode used at least once with its count, most
+    /// frequent first.
+    pub fn modes(&self) -> Vec<(AddressingMode, u64)> {
+        let mut modes: Vec<_> = AddressingMode::ALL
+            .iter()
+            .map(|&mode| (mode, self.mode_count(mode)))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        modes.sort_by_key(|&(_, count)| Reverse(count));
+        modes
+    }
+
+    /// Forget every count.
+    pub fn clear(&mut self) {
+        self.counts.fill(0);
+    }
+
+    /// Add the counts in `other`.
+    pub fn merge(&mut self, other: &OpcodeStats) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+    }
+
+    /// The opcodes executed, in opcode order.
+    fn rows(&self) -> impl Iterator<Item = OpcodeCount> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(index, &count)| {
+                let opcode = opcode_at(index);
+                let info = disasm::opcode_info(opcode);
+                OpcodeCount {
+                    opcode,
+                    mnemonic: info.map(|(name, _)| name),
+                    mode: info.map(|(_, mode)| AddressingMode::from_mode(mode)),
+                    count,
+                }
+            })
+    }
+}
+
+impl Default for OpcodeStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for OpcodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OpcodeStats({} instructions)", self.total())
+    }
+}
+
+impl fmt::Display for OpcodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        let share = |count: u64| 100.0 * count as f64 / total.max(1) as f64;
+        writeln!(f, "{total} instructions")?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>6}  {:<8}  {:<10}  {:>12}  {:>6}",
+            "Opcode", "Mnemonic", "Mode", "Count", "%"
+        )?;
+        for row in self.opcodes() {
+            let mode = row.mode.map_or("-".to_string(), |mode| mode.to_string());
+            writeln!(
+                f,
+                "{:>6}  {:<8}  {:<10}  {:>12}  {:>6.2}",
+                format!("{:02X}", row.opcode),
+                row.mnemonic.unwrap_or("???"),
+                mode,
+                row.count,
+                share(row.count)
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<10}  {:>12}  {:>6}", "Mode", "Count", "%")?;
+        for (mode, count) in self.modes() {
+            writeln!(f, "{mode:<10}  {count:>12}  {:>6.2}", share(count))?;
+        }
+        Ok(())
+    }
+}
+
+/// Where an interrupt came from, as counted by [`InterruptStats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InterruptSource {
+    /// The NMI line.
+    Nmi,
+    /// The FIRQ line.
+    Firq,
+    /// The IRQ line.
+    Irq,
+    /// An SWI instruction.
+    Swi,
+    /// An SWI2 instruction.
+    Swi2,
+    /// An SWI3 instruction.
+    Swi3,
+}
+
+impl InterruptSource {
+    /// Every source, in priority order, lines before instructions.
+    pub const ALL: [InterruptSource; 6] = [
+        InterruptSource::Nmi,
+        InterruptSource::Firq,
+        InterruptSource::Irq,
+        InterruptSource::Swi,
+        InterruptSource::Swi2,
+        InterruptSource::Swi3,
+    ];
+
+    /// The bus line that raises it, or `None` for an instruction.
+    pub fn line(self) -> Option<BusSignals> {
+        match self {
+            InterruptSource::Nmi => Some(BusSignals::NMI),
+            InterruptSource::Firq => Some(BusSignals::FIRQ),
+            InterruptSource::Irq => Some(BusSignals::IRQ),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for InterruptSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            InterruptSource::Nmi => "NMI",
+            InterruptSource::Firq => "FIRQ",
+            InterruptSource::Irq => "IRQ",
+            InterruptSource::Swi => "SWI",
+            InterruptSource::Swi2 => "SWI2",
+            InterruptSource::Swi3 => "SWI3",
+        })
+    }
+}
+
+/// What [`InterruptStats`] recorded for one [`InterruptSource`].
+#[cfg(feature = "hooks")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceStats {
+    /// Interrupts taken.
+    pub count: u64,
+    /// Handlers that have returned with `RTI`.
+    pub returned: u64,
+    /// Cycles of the returned handlers, from the start of the interrupt
+    /// sequence or SWI to the end of the `RTI`, nested handlers included.
+    pub handler_cycles: u64,
+    /// Cycles of the longest returned handler.
+    pub max_handler_cycles: u64,
+    /// Most cycles between the CPU first seeing the line asserted and the
+    /// handler's first instruction; always 0 for an SWI.
+    pub max_latency: u64,
+}
+
+/// A handler that has not yet returned.
+#[cfg(feature = "hooks")]
+#[derive(Clone, Copy, Debug)]
+struct ActiveHandler {
+    source: InterruptSource,
+    /// Cycle count when the interrupt started.
+    started: u64,
+}
+
+/// Interrupts taken, per source, with handler times and latencies.
+///
+/// A handler runs from its interrupt until the next `RTI`; handlers that
+/// leave some other way are counted, but stay open and make any handler
+/// below them end at the wrong `RTI`. A line's latency runs from the first
+/// step that finds it asserted, whether or not it is masked, to the
+/// handler's first instruction, so it includes the cycles of the
+/// instruction the interrupt waited for and of the interrupt sequence.
+///
+/// # Example
+/// ```
+/// use mc6809_core::stats::InterruptSource;
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// ram.load(&[0x12, 0x12], 0x0400); // NOP; NOP
+/// ram.load(&[0x3B], 0x0500); // RTI
+/// ram.load(&[0x05, 0x00], 0xFFF8); // IRQ vector
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.registers_mut().s = 0x0800;
+/// cpu.registers_mut().cc.set_irq_inhibit(false);
+/// cpu.set_interrupt_stats(true);
+///
+/// cpu.set_irq(true);
+/// cpu.step(&mut ram); // 19 cycles to stack and vector
+/// cpu.set_irq(false);
+/// cpu.step(&mut ram); // RTI, 15 cycles
+///
+/// let irq = cpu.interrupt_stats().unwrap().source(InterruptSource::Irq);
+/// assert_eq!((irq.count, irq.handler_cycles, irq.max_latency), (1, 34, 19));
+/// ```
+#[cfg(feature = "hooks")]
+#[derive(Clone, Debug, Default)]
+pub struct InterruptStats {
+    sources: [SourceStats; 6],
+    active: Vec<ActiveHandler>,
+    max_depth: usize,
+    /// When each line, in [`InterruptSource::ALL`] order, was first seen
+    /// asserted.
+    asserted: [Option<u64>; 3],
+}
+
+#[cfg(feature = "hooks")]
+impl InterruptStats {
+    /// Nothing recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note which lines are asserted at cycle `now`.
+    pub(crate) fn sample(&mut self, lines: BusSignals, now: u64) {
+        for (source, asserted) in InterruptSource::ALL.iter().zip(&mut self.asserted) {
+            match source.line() {
+                Some(line) if lines.contains(line) => {
+                    asserted.get_or_insert(now);
+                }
+                _ => *asserted = None,
+            }
+        }
+    }
+
+    /// Record an interrupt from `source` that started at cycle `started`
+    /// and reached its handler at `now`.
+    pub(crate) fn enter(&mut self, source: InterruptSource, started: u64, now: u64) {
+        let index = source as usize;
+        let stats = &mut self.sources[index];
+        stats.count += 1;
+        if let Some(asserted) = self.asserted.get_mut(index).and_then(Option::take) {
+            stats.max_latency = stats.max_latency.max(now - asserted);
+        }
+        self.active.push(ActiveHandler { source, started });
+        self.max_depth = self.max_depth.max(self.active.len());
+    }
+
+    /// Record an `RTI` that finished at cycle `now`.
+    pub(crate) fn rti(&mut self, now: u64) {
+        if let Some(handler) = self.active.pop() {
+            let cycles = now - handler.started;
+            let stats = &mut self.sources[handler.source as usize];
+            stats.returned += 1;
+            stats.handler_cycles += cycles;
+            stats.max_handler_cycles = stats.max_handler_cycles.max(cycles);
+        }
+    }
+
+    /// The figures so far, leaving these empty but still following the
+    /// handlers running and the lines asserted.
+    pub(crate) fn take(&mut self) -> InterruptStats {
+        let taken = self.clone();
+        self.sources = Default::default();
+        self.max_depth = self.active.len();
+        taken
+    }
+
+    /// What was recorded for `source`.
+    pub fn source(&self, source: InterruptSource) -> SourceStats {
+        self.sources[source as usize]
+    }
+
+    /// Interrupts taken from every source.
+    pub fn total(&self) -> u64 {
+        self.sources.iter().map(|stats| stats.count).sum()
+    }
+
+    /// Handlers running now, innermost last.
+    pub fn active(&self) -> Vec<InterruptSource> {
+        self.active.iter().map(|handler| handler.source).collect()
+    }
+
+    /// Most handlers running at once.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+#[cfg(feature = "hooks")]
+impl fmt::Display for InterruptStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<6}  {:>10}  {:>14}  {:>10}  {:>10}  {:>11}",
+            "Source", "Count", "Handler cycles", "Average", "Longest", "Max latency"
+        )?;
+        for source in InterruptSource::ALL {
+            let stats = self.source(source);
+            if stats.count == 0 {
+                continue;
+            }
+            let average = stats.handler_cycles / stats.returned.max(1);
+            writeln!(
+                f,
+                "{source:<6}  {:>10}  {:>14}  {average:>10}  {:>10}  {:>11}",
+                stats.count, stats.handler_cycles, stats.max_handler_cycles, stats.max_latency
+            )?;
+        }
+        writeln!(f, "Deepest nesting: {}", self.max_depth)
+    }
+}
+
+/// A summary of one run, from [`Cpu::run_with_stats`](crate::Cpu::run_with_stats)
+/// or [`Machine::run_for_with_stats`](crate::machine::Machine::run_for_with_stats).
+///
+/// # Example
+/// ```
+/// use mc6809_core::stats::InterruptSource;
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let mut ram = FlatRam::new();
+/// // PSHS A,B; NOP; PULS A,B; BRA *
+/// ram.load(&[0x34, 0x06, 0x12, 0x35, 0x06, 0x20, 0xFE], 0x0400);
+/// ram.set_reset_vector(0x0400);
+/// let mut cpu = Cpu::new();
+/// cpu.reset(&mut ram);
+/// cpu.registers_mut().s = 0x0800;
+/// cpu.add_breakpoint(0x0405);
+///
+/// let stats = cpu.run_with_stats(&mut ram, 19);
+/// assert_eq!((stats.instructions, stats.cycles), (4, 19));
+/// assert_eq!(stats.max_stack_depth, 2);
+/// assert_eq!(stats.breakpoints_hit, 1);
+/// assert_eq!(stats.interrupts(InterruptSource::Irq), 0);
+/// ```
+#[cfg(feature = "hooks")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// Instructions started.
+    pub instructions: u64,
+    /// Cycles consumed, as the plain run returns.
+    pub cycles: u64,
+    /// Most bytes S went below the highest it had been during the run,
+    /// checked before each instruction and after each interrupt entry, so
+    /// that loading S to set up a stack does not count.
+    pub max_stack_depth: u16,
+    /// Breakpoints (see [`Cpu::add_breakpoint`](crate::Cpu::add_breakpoint))
+    /// passed where [`Cpu::try_step`](crate::Cpu::try_step) would have
+    /// stopped. The run does not stop at them.
+    pub breakpoints_hit: u64,
+    interrupts: [u64; 6],
+}
+
+#[cfg(feature = "hooks")]
+impl RunStats {
+    /// Interrupts taken from `source`, counting only SWIs that vectored.
+    pub fn interrupts(&self, source: InterruptSource) -> u64 {
+        self.interrupts[source as usize]
+    }
+
+    /// Interrupts taken from every source.
+    pub fn total_interrupts(&self) -> u64 {
+        self.interrupts.iter().sum()
+    }
+}
+
+/// Builds the [`RunStats`] of a run in progress.
+#[cfg(feature = "hooks")]
+pub(crate) struct RunRecorder {
+    stats: RunStats,
+    /// Highest S seen so far.
+    top_s: u16,
+}
+
+#[cfg(feature = "hooks")]
+impl RunRecorder {
+    /// Start a run with S at `s`.
+    pub(crate) fn new(s: u16) -> Self {
+        Self {
+            stats: RunStats::default(),
+            top_s: s,
+        }
+    }
+
+    /// Note an instruction starting with S at `s`.
+    pub(crate) fn instruction(&mut self, s: u16) {
+        self.stats.instructions += 1;
+        self.stack(s);
+    }
+
+    /// Note a breakpoint passed.
+    pub(crate) fn breakpoint(&mut self) {
+        self.stats.breakpoints_hit += 1;
+    }
+
+    /// Note an interrupt from `source` that left S at `s`.
+    pub(crate) fn interrupt(&mut self, source: InterruptSource, s: u16) {
+        self.stats.interrupts[source as usize] += 1;
+        self.stack(s);
+    }
+
+    fn stack(&mut self, s: u16) {
+        self.top_s = self.top_s.max(s);
+        let depth = self.top_s - s;
+        self.stats.max_stack_depth = self.stats.max_stack_depth.max(depth);
+    }
+
+    /// The statistics of the run, which took `cycles`.
+    pub(crate) fn finish(self, cycles: u64) -> RunStats {
+        RunStats {
+            cycles,
+            ..self.stats
+        }
+    }
+}
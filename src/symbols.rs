@@ -0,0 +1,242 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Symbol tables from assembler output.
+//!
+//! A [`SymbolTable`] maps labels to addresses and back, so that tools can
+//! take a label where they would take an address and show one beside an
+//! address. Tables are read from the files 6809 assemblers write:
+//!
+//! | Format          | Lines                                  |
+//! |-----------------|----------------------------------------|
+//! | `.sym`          | `START EQU $0400` or `START = $0400`   |
+//! | NoICE `.noi`    | `DEF START 0400`                       |
+//! | lwasm/lwlink map| `Symbol: START (main.o) = 0400`        |
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::formats::FormatError;
+
+/// Labels and their addresses.
+///
+/// # Example
+/// ```
+/// use mc6809_core::symbols::SymbolTable;
+///
+/// let symbols = SymbolTable::parse("START EQU $0400\nLOOP EQU $0410\n").unwrap();
+/// assert_eq!(symbols.get("LOOP"), Some(0x0410));
+/// assert_eq!(symbols.resolve("LOOP+2"), Some(0x0412));
+/// assert_eq!(symbols.describe(0x0413), Some("LOOP+3".to_string()));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    by_name: HashMap<String, u16>,
+    by_addr: BTreeMap<u16, Vec<String>>,
+}
+
+impl SymbolTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse any of the supported formats, telling them apart by their
+    /// lines: `Symbol:` or `Section:` for a map, `DEF` for NoICE, and
+    /// `.sym` otherwise.
+    pub fn parse(text: &str) -> Result<Self, FormatError> {
+        let first_word = |prefix: &str| {
+            text.lines()
+                .any(|line| line.split_whitespace().next() == Some(prefix))
+        };
+        if first_word("Symbol:") || first_word("Section:") {
+            Self::parse_lwasm_map(text)
+        } else if first_word("DEF") {
+            Self::parse_noi(text)
+        } else {
+            Self::parse_sym(text)
+        }
+    }
+
+    /// Parse `.sym` lines of the form `LABEL EQU value`, `LABEL = value` or
+    /// `LABEL: EQU value`. Blank lines and `;` or `*` comments are skipped.
+    pub fn parse_sym(text: &str) -> Result<Self, FormatError> {
+        Self::parse_lines(text, |words| match words {
+            [name, op, value, ..] if op.eq_ignore_ascii_case("equ") || *op == "=" => {
+                Some(Some((name.trim_end_matches(':'), parse_value(value)?)))
+            }
+            _ => None,
+        })
+    }
+
+    /// Parse a NoICE file: `DEF LABEL value` lines, with every other
+    /// command ignored.
+    pub fn parse_noi(text: &str) -> Result<Self, FormatError> {
+        Self::parse_lines(text, |words| match words {
+            [def, name, value, ..] if def.eq_ignore_ascii_case("def") => {
+                Some(Some((*name, parse_value(value)?)))
+            }
+            [def, ..] if def.eq_ignore_ascii_case("def") => None,
+            _ => Some(None),
+        })
+    }
+
+    /// Parse an lwasm or lwlink map: `Symbol: LABEL (file) = value` lines,
+    /// with section lines ignored.
+    pub fn parse_lwasm_map(text: &str) -> Result<Self, FormatError> {
+        Self::parse_lines(text, |words| match words {
+            ["Symbol:", name, .., "=", value] => Some(Some((*name, parse_hex(value)?))),
+            ["Symbol:", ..] => None,
+            _ => Some(None),
+        })
+    }
+
+    /// Run `line` over the words of each line that is not blank or a
+    /// comment. It returns `None` for a malformed line, `Some(None)` to skip
+    /// one and `Some(Some(symbol))` to add a symbol.
+    fn parse_lines<'a>(
+        text: &'a str,
+        line: impl Fn(&[&'a str]) -> Option<Option<(&'a str, u16)>>,
+    ) -> Result<Self, FormatError> {
+        let mut table = Self::new();
+        for (index, text) in text.lines().enumerate() {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            if words
+                .first()
+                .is_none_or(|word| word.starts_with([';', '*', '#']))
+            {
+                continue;
+            }
+            match line(&words) {
+                Some(Some((name, addr))) => table.insert(name, addr),
+                Some(None) => {}
+                None => return Err(FormatError::BadLine { line: index + 1 }),
+            }
+        }
+        Ok(table)
+    }
+
+    /// Add `name` at `addr`, replacing any earlier address for `name`.
+    pub fn insert(&mut self, name: &str, addr: u16) {
+        if let Some(old) = self.by_name.insert(name.to_string(), addr)
+            && let Some(names) = self.by_addr.get_mut(&old)
+        {
+            names.retain(|n| n != name);
+            if names.is_empty() {
+                self.by_addr.remove(&old);
+            }
+        }
+        self.by_addr.entry(addr).or_default().push(name.to_string());
+    }
+
+    /// Add every symbol of `other`, replacing labels already present.
+    pub fn extend(&mut self, other: &SymbolTable) {
+        for (name, addr) in other.iter() {
+            self.insert(name, addr);
+        }
+    }
+
+    /// Address of `name`.
+    pub fn get(&self, name: &str) -> Option<u16> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Labels at exactly `addr`, in the order they were added.
+    pub fn names_at(&self, addr: u16) -> &[String] {
+        self.by_addr.get(&addr).map_or(&[], Vec::as_slice)
+    }
+
+    /// Turn a label, `label+offset`, `label-offset` or a hex address (`$0400`,
+    /// `0x0400` or `0400`) into an address. Offsets are decimal, or hex with
+    /// a `$` or `0x` prefix. Labels win over bare hex, so `ADD` is a label
+    /// if there is one.
+    pub fn resolve(&self, text: &str) -> Option<u16> {
+        let text = text.trim();
+        if let Some(addr) = self.get(text) {
+            return Some(addr);
+        }
+        let split = text
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '+' || c == '-');
+        if let Some((split, _)) = split {
+            let (base, offset) = text.split_at(split);
+            let base = self.resolve(base)?;
+            let amount = parse_offset(&offset[1..])?;
+            return Some(if offset.starts_with('+') {
+                base.wrapping_add(amount)
+            } else {
+                base.wrapping_sub(amount)
+            });
+        }
+        parse_value(text)
+    }
+
+    /// `addr` as the nearest label at or below it, plus an offset if it is
+    /// not exactly on the label: `LOOP` or `LOOP+3`. `None` if no label is
+    /// at or below `addr`.
+    pub fn describe(&self, addr: u16) -> Option<String> {
+        let (&base, names) = self.by_addr.range(..=addr).next_back()?;
+        let name = &names[0];
+        Some(match addr - base {
+            0 => name.clone(),
+            offset => format!("{name}+{offset}"),
+        })
+    }
+
+    /// Number of labels.
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    /// Whether the table has no labels.
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    /// Every label and its address, in address order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u16)> {
+        self.by_addr
+            .iter()
+            .flat_map(|(&addr, names)| names.iter().map(move |name| (name.as_str(), addr)))
+    }
+}
+
+/// Parse a hex value with an optional `$` or `0x` prefix or `h` suffix.
+fn parse_value(text: &str) -> Option<u16> {
+    let digits = text
+        .strip_prefix('$')
+        .or_else(|| text.strip_prefix("0x"))
+        .or_else(|| text.strip_prefix("0X"))
+        .or_else(|| text.strip_suffix(['h', 'H']))
+        .unwrap_or(text);
+    parse_hex(digits)
+}
+
+/// Parse bare hex digits, allowing values wider than 16 bits (as NoICE
+/// writes for banked addresses) by keeping the low 16 bits.
+fn parse_hex(text: &str) -> Option<u16> {
+    if text.is_empty() || text.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(text, 16).ok().map(|value| value as u16)
+}
+
+/// Parse an offset: decimal, or hex with a `$` or `0x` prefix.
+fn parse_offset(text: &str) -> Option<u16> {
+    if text.starts_with('$') || text.starts_with("0x") || text.starts_with("0X") {
+        parse_value(text)
+    } else {
+        text.parse().ok()
+    }
+}
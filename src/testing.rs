@@ -0,0 +1,284 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Golden-trace regression tests.
+//!
+//! [`record_golden`] steps a CPU and keeps its registers and cycle count
+//! after every step; [`GoldenTrace::save`] writes that out as text. Checked
+//! in beside a machine emulator's tests, the file pins how the core runs
+//! that machine's ROMs: [`compare_golden`] replays the same number of steps
+//! from the same start and reports the first step that comes out
+//! differently, for instance after upgrading this crate.
+//!
+//! The file has one line per step, with the registers and the total cycle
+//! count after it, after a header line:
+//!
+//! ```text
+//! # mc6809-core golden trace 1
+//! PC=0402 A=42 B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2
+//! ```
+//!
+//! Lines starting with `#` after the header are comments. Only the 6809
+//! registers are recorded; the HD6309 extensions are not compared.
+
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cpu::Cpu;
+use crate::memory::Memory;
+use crate::registers::{ConditionCodes, Registers};
+
+/// First line of a golden trace file.
+const HEADER: &str = "# mc6809-core golden trace 1";
+
+/// The state after one step of a [`GoldenTrace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoldenStep {
+    pub registers: Registers,
+    /// [`Cpu::cycles`] after the step.
+    pub cycles: u64,
+}
+
+/// The states a CPU went through, one per step, from [`record_golden`].
+///
+/// # Example
+/// ```
+/// use mc6809_core::testing::{GoldenTrace, record_golden};
+/// use mc6809_core::{Cpu, FlatRam, Memory};
+///
+/// let boot = || {
+///     let mut ram = FlatRam::new();
+///     ram.load(&[0x86, 0x42, 0x4C, 0x20, 0xFD], 0x0400); // LDA #$42 / INCA / BRA *-3
+///     ram.set_reset_vector(0x0400);
+///     let mut cpu = Cpu::new();
+///     cpu.reset(&mut ram);
+///     (cpu, ram)
+/// };
+/// let (mut cpu, mut ram) = boot();
+/// let golden = record_golden(&mut cpu, &mut ram, 10);
+///
+/// let text = golden.to_text();
+/// assert_eq!(GoldenTrace::parse(&text).unwrap(), golden);
+/// let (mut cpu, mut ram) = boot();
+/// assert_eq!(golden.compare(&mut cpu, &mut ram), Ok(()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldenTrace {
+    pub steps: Vec<GoldenStep>,
+}
+
+/// Why a golden trace could not be read, or where a replay left it.
+#[derive(Debug)]
+pub enum GoldenError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// Line `line` (counting from 1) is not a trace line, or the header
+    /// is missing.
+    Parse { line: usize },
+    /// Step `step` (counting from 0) came out differently, in the ways
+    /// listed, such as `"A: expected 42, found 43"`.
+    Mismatch {
+        step: usize,
+        differences: Vec<String>,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Io(e) => write!(f, "reading golden trace: {e}"),
+            GoldenError::Parse { line } => write!(f, "golden trace line {line}: not a trace line"),
+            GoldenError::Mismatch { step, differences } => {
+                write!(f, "step {step} differs: {}", differences.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for GoldenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GoldenError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for GoldenError {
+    /// I/O errors compare by kind.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (GoldenError::Io(a), GoldenError::Io(b)) => a.kind() == b.kind(),
+            (GoldenError::Parse { line: a }, GoldenError::Parse { line: b }) => a == b,
+            (
+                GoldenError::Mismatch {
+                    step: a,
+                    differences: da,
+                },
+                GoldenError::Mismatch {
+                    step: b,
+                    differences: db,
+                },
+            ) => a == b && da == db,
+            _ => false,
+        }
+    }
+}
+
+impl From<io::Error> for GoldenError {
+    fn from(e: io::Error) -> Self {
+        GoldenError::Io(e)
+    }
+}
+
+/// Take `steps` steps of `cpu` against `mem`, recording the state after
+/// each.
+pub fn record_golden<M: Memory>(cpu: &mut Cpu, mem: &mut M, steps: usize) -> GoldenTrace {
+    let steps = (0..steps)
+        .map(|_| {
+            cpu.step(mem);
+            GoldenStep {
+                registers: *cpu.registers(),
+                cycles: cpu.cycles(),
+            }
+        })
+        .collect();
+    GoldenTrace { steps }
+}
+
+/// Replay the trace saved at `path` on `cpu` and `mem`, which should be in
+/// the state the recording started from (see [`GoldenTrace::compare`]).
+pub fn compare_golden<M: Memory>(
+    path: impl AsRef<Path>,
+    cpu: &mut Cpu,
+    mem: &mut M,
+) -> Result<(), GoldenError> {
+    GoldenTrace::load(path)?.compare(cpu, mem)
+}
+
+impl GoldenTrace {
+    /// Step `cpu` once per recorded step and check each state, stopping at
+    /// the first that differs.
+    pub fn compare<M: Memory>(&self, cpu: &mut Cpu, mem: &mut M) -> Result<(), GoldenError> {
+        for (step, expected) in self.steps.iter().enumerate() {
+            cpu.step(mem);
+            let mut differences: Vec<String> = cpu
+                .registers()
+                .differences(&expected.registers)
+                .into_iter()
+                .map(|(reg, expected, actual)| {
+                    let width = if reg.is_16bit() { 4 } else { 2 };
+                    format!("{reg}: expected {expected:0width$X}, found {actual:0width$X}")
+                })
+                .collect();
+            if cpu.cycles() != expected.cycles {
+                differences.push(format!(
+                    "cycles: expected {}, found {}",
+                    expected.cycles,
+                    cpu.cycles()
+                ));
+            }
+            if !differences.is_empty() {
+                return Err(GoldenError::Mismatch { step, differences });
+            }
+        }
+        Ok(())
+    }
+
+    /// The trace in the file format.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        for step in &self.steps {
+            let r = &step.registers;
+            let _ = writeln!(
+                text,
+                "PC={:04X} A={:02X} B={:02X} X={:04X} Y={:04X} U={:04X} S={:04X} DP={:02X} CC={:02X} CYC={}",
+                r.pc,
+                r.a(),
+                r.b(),
+                r.x,
+                r.y,
+                r.u,
+                r.s,
+                r.dp,
+                r.cc.to_byte(),
+                step.cycles
+            );
+        }
+        text
+    }
+
+    /// Read a trace in the file format.
+    pub fn parse(text: &str) -> Result<Self, GoldenError> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err(GoldenError::Parse { line: 1 });
+        }
+        let steps = lines
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| parse_step(line).ok_or(GoldenError::Parse { line: index + 1 }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Write the trace to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Read a trace written by [`Self::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GoldenError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+/// One trace line, with every field present once and in order.
+fn parse_step(line: &str) -> Option<GoldenStep> {
+    let mut fields = line.split_whitespace();
+    let mut field = |name: &str, digits: usize| {
+        let value = fields.next()?.strip_prefix(name)?.strip_prefix('=')?;
+        (value.len() == digits)
+            .then(|| u16::from_str_radix(value, 16).ok())
+            .flatten()
+    };
+    let pc = field("PC", 4)?;
+    let a = field("A", 2)? as u8;
+    let b = field("B", 2)? as u8;
+    let x = field("X", 4)?;
+    let y = field("Y", 4)?;
+    let u = field("U", 4)?;
+    let s = field("S", 4)?;
+    let dp = field("DP", 2)? as u8;
+    let cc = field("CC", 2)? as u8;
+    let cycles = fields.next()?.strip_prefix("CYC=")?.parse().ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(GoldenStep {
+        registers: Registers {
+            d: u16::from_be_bytes([a, b]),
+            x,
+            y,
+            u,
+            s,
+            pc,
+            dp,
+            cc: ConditionCodes::from_byte(cc),
+        },
+        cycles,
+    })
+}
@@ -12,7 +12,34 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
+mod addressing_tests;
 mod alu_tests;
+mod backtrace_tests;
+mod batch_tests;
+mod block_cache_tests;
+mod bus_tests;
+mod coverage_tests;
 mod cpu_tests;
+mod decode_cache_tests;
+mod devices_tests;
+mod disasm_tests;
+#[cfg(feature = "dragon32")]
+mod dragon32_tests;
+mod formats_tests;
+#[cfg(feature = "arbitrary")]
+mod fuzz_tests;
+#[cfg(feature = "gdbstub")]
+mod gdb_tests;
+mod hd6309_tests;
 mod instruction_cycles_tests;
+mod machine_tests;
+mod mc6801_tests;
+mod memory_tests;
+#[cfg(feature = "hooks")]
+mod profile_tests;
 mod register_tests;
+#[cfg(feature = "hooks")]
+mod stats_tests;
+mod symbols_tests;
+mod testing_tests;
+mod vectors_tests;
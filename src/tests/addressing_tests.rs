@@ -0,0 +1,109 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tests for indexed addressing: every post-byte for every index register.
+
+use crate::addressing::indexed;
+use crate::{Cpu, Memory};
+
+/// Post-byte operand bytes, placed right after the post-byte.
+const OPERAND: [u8; 2] = [0x12, 0x34];
+
+/// Index register values, X, Y, U and S.
+const INDEX: [u16; 4] = [0x1000, 0x2000, 0x3000, 0x4000];
+
+/// Memory holding a post-byte and its operand at 0x8000, and an
+/// address-derived pattern everywhere else so indirection is visible.
+struct PatternMem {
+    post: u8,
+}
+
+impl Memory for PatternMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x8000 => self.post,
+            0x8001 => OPERAND[0],
+            0x8002 => OPERAND[1],
+            _ => (addr as u8) ^ (addr >> 8) as u8 ^ 0xA5,
+        }
+    }
+    fn write(&mut self, _addr: u16, _val: u8) {}
+}
+
+/// Effective address, extra cycles, index register afterwards and PC
+/// afterwards, following the 6809 indexed addressing table.
+fn expected(post: u8, mem: &mut PatternMem) -> (u16, u8, u16, u16) {
+    let r = INDEX[(post >> 5) as usize & 3];
+    let operand8 = OPERAND[0] as i8 as u16;
+    let operand16 = u16::from_be_bytes(OPERAND);
+    if post & 0x80 == 0 {
+        let offset = ((post << 3) as i8 >> 3) as u16;
+        return (r.wrapping_add(offset), 1, r, 0x8001);
+    }
+    let (ea, extra, reg, pc) = match post & 0x0F {
+        0x0 => (r, 2, r + 1, 0x8001),
+        0x1 => (r, 3, r + 2, 0x8001),
+        0x2 => (r - 1, 2, r - 1, 0x8001),
+        0x3 => (r - 2, 3, r - 2, 0x8001),
+        0x4 => (r, 0, r, 0x8001),
+        0x5 => (r.wrapping_add(0xFFFE), 1, r, 0x8001), // B = -2
+        0x6 | 0x7 => (r + 0x05, 1, r, 0x8001),         // A = 5
+        0x8 => (r.wrapping_add(operand8), 1, r, 0x8002),
+        0x9 => (r.wrapping_add(operand16), 4, r, 0x8003),
+        0xA => (0x80FF, 4, r, 0x8001),
+        0xB => (r + 0x05FE, 4, r, 0x8001),
+        0xC => (0x8002u16.wrapping_add(operand8), 1, r, 0x8002),
+        0xD => (0x8003u16.wrapping_add(operand16), 5, r, 0x8003),
+        0xE => (0xFFFF, 5, r, 0x8001),
+        _ => (operand16, 2, r, 0x8003),
+    };
+    if post & 0x10 != 0 {
+        (mem.read_word(ea), extra + 3, reg, pc)
+    } else {
+        (ea, extra, reg, pc)
+    }
+}
+
+#[test]
+fn indexed_matrix_covers_every_post_byte() {
+    for post in 0..=0xFF {
+        let mut mem = PatternMem { post };
+        let mut cpu = Cpu::new();
+        {
+            let mut reg = cpu.registers_mut();
+            reg.x = INDEX[0];
+            reg.y = INDEX[1];
+            reg.u = INDEX[2];
+            reg.s = INDEX[3];
+            reg.d = 0x05FE;
+            reg.pc = 0x8000;
+        }
+        let (ea, extra, reg_after, pc) = expected(post, &mut mem);
+
+        // indexed() fetches the post-byte itself.
+        assert_eq!(indexed(&mut cpu, &mut mem), (ea, extra), "post {post:02X}");
+
+        let regs = cpu.registers();
+        assert_eq!(regs.pc, pc, "post {post:02X} PC");
+        let index = [regs.x, regs.y, regs.u, regs.s];
+        for (i, value) in index.into_iter().enumerate() {
+            let want = if i == (post >> 5) as usize & 3 {
+                reg_after
+            } else {
+                INDEX[i]
+            };
+            assert_eq!(value, want, "post {post:02X} register {i}");
+        }
+    }
+}
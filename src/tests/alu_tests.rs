@@ -335,3 +335,109 @@ fn sbc8_with_carry() {
     assert_eq!(r, 0x0F); // 0x20 - 0x10 - 1
     assert!(!cc.carry());
 }
+
+#[test]
+fn adc16_carry_out() {
+    let mut cc = ConditionCodes::new();
+    cc.set_carry(true);
+    let r = alu::adc16(0xFFFF, 0x0000, &mut cc);
+    assert_eq!(r, 0x0000);
+    assert!(cc.carry());
+    assert!(cc.zero());
+}
+
+#[test]
+fn sbc16_borrow_in() {
+    let mut cc = ConditionCodes::new();
+    cc.set_carry(true);
+    let r = alu::sbc16(0x8000, 0x0000, &mut cc);
+    assert_eq!(r, 0x7FFF);
+    assert!(cc.overflow());
+    assert!(!cc.carry());
+}
+
+#[test]
+fn rol16_through_carry() {
+    let mut cc = ConditionCodes::new();
+    cc.set_carry(true);
+    let r = alu::rol16(0x8000, &mut cc);
+    assert_eq!(r, 0x0001);
+    assert!(cc.carry());
+    assert!(cc.overflow());
+}
+
+#[test]
+fn ld32_flags_negative() {
+    let mut cc = ConditionCodes::new();
+    alu::ld32_flags(0x8000_0000, &mut cc);
+    assert!(cc.negative());
+    assert!(!cc.zero());
+}
+
+#[test]
+fn muld_signed() {
+    let mut cc = ConditionCodes::new();
+    let r = alu::muld(0xFFFF, 0x0002, &mut cc);
+    assert_eq!(r, 0xFFFF_FFFE);
+    assert!(cc.negative());
+}
+
+#[test]
+fn divd_remainder_takes_dividend_sign() {
+    let mut cc = ConditionCodes::new();
+    let r = alu::divd((-7i16) as u16, 2, &mut cc);
+    assert_eq!(r, Some(((-3i8) as u8, (-1i8) as u8)));
+    assert!(cc.carry());
+    assert!(!cc.overflow());
+}
+
+#[test]
+fn divd_overflow_within_range_stores_result() {
+    let mut cc = ConditionCodes::new();
+    let r = alu::divd(200, 1, &mut cc);
+    assert_eq!(r, Some((200, 0)));
+    assert!(cc.overflow());
+}
+
+#[test]
+fn divq_range_overflow() {
+    let mut cc = ConditionCodes::new();
+    let r = alu::divq(0x0002_0000, 1, &mut cc);
+    assert_eq!(r, None);
+    assert!(cc.overflow());
+    assert!(!cc.zero());
+}
+
+#[cfg(feature = "alu-verify")]
+#[test]
+fn alu_matches_reference_tables() {
+    assert_eq!(alu::verify(), Ok(()));
+}
+
+#[cfg(feature = "alu-verify")]
+#[test]
+fn reference_check_reports_first_mismatch() {
+    use crate::alu::verify::{verify_binary, verify_unary};
+
+    // ADC checked against the ADD table: first wrong with carry in.
+    let add = include_bytes!("../alu/reference/add8.bin");
+    let err = verify_binary("add8", alu::adc8, add, 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "add8(00, 00) with CC 01: expected 00 CC 04, found 01 CC 00"
+    );
+
+    // DAA missing the MSN = 9, LSN > 9 case of the high-nibble correction.
+    fn daa_missing_case(a: u8, cc: &mut ConditionCodes) -> u8 {
+        let carry = cc.carry();
+        let result = alu::daa(a, cc);
+        if (0x9A..=0x9F).contains(&a) && !carry {
+            cc.set_carry(false);
+            return result.wrapping_sub(0x60);
+        }
+        result
+    }
+    let daa = include_bytes!("../alu/reference/daa.bin");
+    let err = verify_unary("daa", daa_missing_case, daa, 0x02).unwrap_err();
+    assert_eq!((err.a, err.b, err.cc), (0x9A, None, 0x00));
+}
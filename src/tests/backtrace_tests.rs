@@ -0,0 +1,135 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::backtrace::{FrameKind, StackWalker};
+use crate::symbols::SymbolTable;
+use crate::{Cpu, CpuVariant, FlatRam, Memory};
+
+/// MAIN calls SUB, which calls INNER, where an IRQ lands on `BRA *`; the
+/// handler then runs one NOP. Returns the CPU stopped in the handler.
+fn crashed(variant: CpuVariant) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    // MAIN: ANDCC #$EF; JSR SUB
+    ram.load(&[0x1C, 0xEF, 0xBD, 0xE1, 0x00], 0xE000);
+    // SUB: LBSR INNER
+    ram.load(&[0x17, 0x00, 0xFD], 0xE100);
+    // INNER: NOP; BRA *
+    ram.load(&[0x12, 0x20, 0xFE], 0xE200);
+    // HANDLER: NOP
+    ram.load(&[0x12], 0xE300);
+    ram.write_word(0xFFF8, 0xE300);
+    ram.set_reset_vector(0xE000);
+    let mut cpu = Cpu::new();
+    cpu.set_variant(variant);
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    cpu.registers_mut().x = 0x1234;
+    for _ in 0..5 {
+        cpu.step(&mut ram);
+    }
+    cpu.set_irq(true);
+    cpu.step(&mut ram);
+    cpu.set_irq(false);
+    cpu.step(&mut ram);
+    (cpu, ram)
+}
+
+#[test]
+fn walk_finds_calls_and_interrupt_frames() {
+    let (cpu, mut ram) = crashed(CpuVariant::Mc6809);
+    let trace = StackWalker::new()
+        .code(0xE000..=0xFFFF)
+        .walk(&cpu, &mut ram);
+    assert_eq!((trace.pc, trace.sp), (0xE301, 0x0800 - 16));
+
+    let found: Vec<_> = trace
+        .frames
+        .iter()
+        .map(|frame| (frame.sp, frame.pc))
+        .collect();
+    assert_eq!(
+        found,
+        [(0x07F0, 0xE201), (0x07FC, 0xE103), (0x07FE, 0xE005)]
+    );
+    let FrameKind::Interrupt(regs) = &trace.frames[0].kind else {
+        panic!("expected an interrupt frame, got {:?}", trace.frames[0]);
+    };
+    assert_eq!((regs.x, regs.s), (0x1234, 0x07FC));
+    assert!(regs.cc.entire());
+    let FrameKind::Call(call) = &trace.frames[1].kind else {
+        panic!("expected a call, got {:?}", trace.frames[1]);
+    };
+    assert_eq!((call.addr, call.mnemonic.as_str()), (0xE100, "LBSR"));
+}
+
+#[test]
+fn walk_reads_native_6309_interrupt_frames() {
+    let (mut cpu, mut ram) = crashed(CpuVariant::Hd6309);
+    // Switch to native mode after the fact: only the frame length matters.
+    ram.load(&[0x11, 0x3D, 0x01], 0xE301); // LDMD #$01
+    cpu.step(&mut ram);
+    let s = cpu.registers().s;
+    // Rebuild the interrupt frame with room for E and F.
+    let mut frame = [0; 14];
+    frame[0] = 0x80;
+    frame[12..].copy_from_slice(&0xE201u16.to_be_bytes());
+    ram.load(&frame, s - 2);
+    cpu.registers_mut().s = s - 2;
+
+    let trace = StackWalker::new()
+        .code(0xE000..=0xFFFF)
+        .walk(&cpu, &mut ram);
+    let found: Vec<_> = trace.frames.iter().map(|frame| frame.pc).collect();
+    assert_eq!(found, [0xE201, 0xE103, 0xE005]);
+}
+
+#[test]
+fn walk_stops_at_the_limit_and_ignores_code_outside_the_range() {
+    let (cpu, mut ram) = crashed(CpuVariant::Mc6809);
+    let trace = StackWalker::new()
+        .code(0xE000..=0xFFFF)
+        .limit(14)
+        .walk(&cpu, &mut ram);
+    assert_eq!(trace.frames.len(), 2);
+
+    // Only SUB's call to INNER is both made and returned to in SUB.
+    let trace = StackWalker::new()
+        .code(0xE100..=0xE1FF)
+        .walk(&cpu, &mut ram);
+    let found: Vec<_> = trace.frames.iter().map(|frame| frame.pc).collect();
+    assert_eq!(found, [0xE103]);
+}
+
+#[test]
+fn backtrace_text_uses_symbols() {
+    let (cpu, mut ram) = crashed(CpuVariant::Mc6809);
+    let trace = StackWalker::new()
+        .code(0xE000..=0xFFFF)
+        .walk(&cpu, &mut ram);
+    let mut symbols = SymbolTable::new();
+    symbols.insert("MAIN", 0xE000);
+    symbols.insert("SUB", 0xE100);
+    symbols.insert("INNER", 0xE200);
+    symbols.insert("HANDLER", 0xE300);
+
+    let text = trace.text_with(&symbols);
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("#0  E301 HANDLER+1"));
+    assert!(lines[1].starts_with("#1  E201 INNER+1"));
+    assert!(lines[1].contains("interrupted, PC=E201"));
+    assert!(lines[2].ends_with("called by LBSR  INNER at E100"));
+    assert!(lines[3].ends_with("called by JSR   SUB at E002"));
+    assert!(trace.to_string().contains("called by JSR   $E100 at E002"));
+}
@@ -0,0 +1,69 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::batch::{instance_seed, run_batch, run_batch_on};
+use crate::{Cpu, Memory};
+
+struct FlatMem(Box<[u8; 65536]>);
+
+impl Memory for FlatMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.0[addr as usize]
+    }
+    fn write(&mut self, addr: u16, val: u8) {
+        self.0[addr as usize] = val;
+    }
+}
+
+#[test]
+fn results_are_in_index_order() {
+    let indices = run_batch_on(3, 10, 0, |instance| instance.index);
+    assert_eq!(indices, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn seeds_are_deterministic_and_distinct() {
+    let one = run_batch_on(1, 8, 7, |instance| instance.seed);
+    let many = run_batch_on(4, 8, 7, |instance| instance.seed);
+    assert_eq!(one, many);
+    assert_eq!(one[3], instance_seed(7, 3));
+    let mut unique = one.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    assert_eq!(unique.len(), one.len());
+    assert_ne!(instance_seed(7, 0), instance_seed(8, 0));
+}
+
+#[test]
+fn runs_independent_cpus() {
+    // Each instance computes A = low seed byte + 1: LDA #n / INCA / XHCF
+    let results = run_batch(6, 1, |instance| {
+        let n = instance.seed as u8;
+        let mut mem = FlatMem(Box::new([0; 65536]));
+        mem.0[0x0400..0x0404].copy_from_slice(&[0x86, n, 0x4C, 0x14]);
+        mem.0[0xFFFE] = 0x04;
+        let mut cpu = Cpu::new();
+        cpu.reset(&mut mem);
+        cpu.run(&mut mem, 100);
+        (n, cpu.registers().a())
+    });
+    for (n, a) in results {
+        assert_eq!(a, n.wrapping_add(1));
+    }
+}
+
+#[test]
+fn empty_batch_returns_nothing() {
+    assert!(run_batch(0, 0, |instance| instance.index).is_empty());
+}
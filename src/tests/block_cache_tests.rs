@@ -0,0 +1,243 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tests for straight-line block execution.
+
+use crate::{ControlHandle, Cpu, IllegalOpcodePolicy, Memory, StackAction, StackMonitor};
+
+/// Flat RAM that counts reads, so tests can see which fetches were skipped.
+struct CountingMem {
+    mem: Box<[u8; 65536]>,
+    reads: usize,
+}
+
+impl CountingMem {
+    fn new(program: &[u8], start: u16) -> Self {
+        let mut mem = Box::new([0u8; 65536]);
+        mem[0xFFFE] = (start >> 8) as u8;
+        mem[0xFFFF] = start as u8;
+        mem[start as usize..start as usize + program.len()].copy_from_slice(program);
+        Self { mem, reads: 0 }
+    }
+}
+
+impl Memory for CountingMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.reads += 1;
+        self.mem[addr as usize]
+    }
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+}
+
+/// Run `program` for `budget` cycles with and without the block cache and
+/// check that both end in the same state. Returns both memories.
+fn run_both(program: &[u8], budget: u64) -> (CountingMem, CountingMem) {
+    let mut plain_mem = CountingMem::new(program, 0x0400);
+    let mut plain = Cpu::new();
+    plain.reset(&mut plain_mem);
+    plain.run_fast(&mut plain_mem, budget);
+
+    let mut block_mem = CountingMem::new(program, 0x0400);
+    let mut blocks = Cpu::new();
+    blocks.set_block_cache(true);
+    blocks.reset(&mut block_mem);
+    blocks.run_fast(&mut block_mem, budget);
+
+    // The Debug output covers every register and the cycle count.
+    assert_eq!(format!("{blocks:?}"), format!("{plain:?}"));
+    assert_eq!(blocks.halted(), plain.halted());
+    assert_eq!(block_mem.mem[..], plain_mem.mem[..]);
+    (plain_mem, block_mem)
+}
+
+#[test]
+fn block_run_matches_plain_run() {
+    //       LDX #$2000
+    //       LDB #$20
+    // loop: LDA ,X
+    //       INCA
+    //       STA ,X+
+    //       DECB
+    //       BNE loop
+    //       XHCF
+    let program = [
+        0x8E, 0x20, 0x00, 0xC6, 0x20, 0xA6, 0x84, 0x4C, 0xA7, 0x80, 0x5A, 0x26, 0xF8, 0x14,
+    ];
+    let (plain_mem, block_mem) = run_both(&program, 100_000);
+    assert_eq!(block_mem.mem[0x2000..0x2020], [1; 32]);
+    assert!(block_mem.reads < plain_mem.reads);
+}
+
+#[test]
+fn write_into_recorded_block_is_seen() {
+    // 0400: LDA #$01
+    // 0402: INCA        ; rewritten to DECA below
+    // 0403: BRA 0405
+    // 0405: LDB #$4A    ; $4A is DECA
+    // 0407: STB $0402
+    // 040A: BRA 0400
+    let program = [
+        0x86, 0x01, 0x4C, 0x20, 0x00, 0xC6, 0x4A, 0xF7, 0x04, 0x02, 0x20, 0xF4,
+    ];
+    for budget in [20, 40, 1_000] {
+        run_both(&program, budget);
+    }
+}
+
+#[test]
+fn block_overwriting_itself_is_seen() {
+    // 0400: LDB #$86    ; $86 is LDA immediate
+    // 0402: STB $0406   ; turn the LDB below into LDA
+    // 0405: NOP
+    // 0406: LDB #$01
+    // 0408: BRA 0400
+    let program = [0xC6, 0x86, 0xF7, 0x04, 0x06, 0x12, 0xC6, 0x01, 0x20, 0xF6];
+    run_both(&program, 1_000);
+}
+
+#[test]
+fn external_write_needs_explicit_flush() {
+    // LDA #$11 / NOP / BRA 0400
+    let mut mem = CountingMem::new(&[0x86, 0x11, 0x12, 0x20, 0xFB], 0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_block_cache(true);
+    cpu.reset(&mut mem);
+    cpu.run_fast(&mut mem, 10); // records the LDA / NOP block
+
+    mem.mem[0x0400] = 0xC6; // host-side patch to LDB #$11, not seen
+    cpu.registers_mut().set_b(0);
+    cpu.run_fast(&mut mem, 10);
+    assert_eq!(cpu.registers().b(), 0);
+
+    cpu.flush_block_cache();
+    cpu.run_fast(&mut mem, 10);
+    assert_eq!(cpu.registers().b(), 0x11);
+}
+
+#[test]
+fn block_and_decode_caches_are_exclusive() {
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    cpu.set_block_cache(true);
+    assert!(cpu.block_cache_enabled());
+    assert!(!cpu.decode_cache_enabled());
+    cpu.set_decode_cache(true);
+    assert!(!cpu.block_cache_enabled());
+}
+
+#[test]
+fn illegal_opcode_ends_block() {
+    // NOP / illegal 0x87 / LDA #$11 / BRA 0400
+    let mut mem = CountingMem::new(&[0x12, 0x87, 0x86, 0x11, 0x20, 0xFA], 0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_block_cache(true);
+    cpu.reset(&mut mem);
+    cpu.run_fast(&mut mem, 100);
+    assert!(cpu.illegal());
+
+    // A block recorded while illegal opcodes were NOPs must not carry one
+    // past a later change of policy.
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    cpu.run_fast(&mut mem, 100);
+    assert!(cpu.halted());
+    assert_eq!(cpu.registers().pc, 0x0402);
+}
+
+#[test]
+fn prefixed_jump_ends_block_with_undocumented_opcodes() {
+    // LDA #$01 / INCA / 0x10 JMP $0402 (page 0 JMP behind a page 1 prefix)
+    let program = [0x86, 0x01, 0x4C, 0x10, 0x7E, 0x04, 0x02];
+    let mut plain_mem = CountingMem::new(&program, 0x0400);
+    let mut plain = Cpu::new();
+    plain.set_undocumented_opcodes(true);
+    plain.reset(&mut plain_mem);
+    plain.run_fast(&mut plain_mem, 500);
+
+    let mut block_mem = CountingMem::new(&program, 0x0400);
+    let mut blocks = Cpu::new();
+    blocks.set_undocumented_opcodes(true);
+    blocks.set_block_cache(true);
+    blocks.reset(&mut block_mem);
+    blocks.run_fast(&mut block_mem, 500);
+
+    assert_eq!(format!("{blocks:?}"), format!("{plain:?}"));
+}
+
+#[test]
+fn halt_inside_block_stops_the_replay() {
+    // loop: PSHS A / INCA / BRA loop, with a stack monitor halting once S
+    // leaves $0800-$08FF. The halt comes while recording the block or while
+    // replaying it, depending on where S starts.
+    let program = [0x34, 0x02, 0x4C, 0x20, 0xFB];
+    for s in [0x0800, 0x0803] {
+        let run = |block_cache: bool| {
+            let mut mem = CountingMem::new(&program, 0x0400);
+            let mut cpu = Cpu::new();
+            cpu.set_block_cache(block_cache);
+            cpu.reset(&mut mem);
+            cpu.registers_mut().s = s;
+            cpu.set_stack_monitor(Some(StackMonitor {
+                s: Some(0x0800..=0x08FF),
+                u: None,
+                action: StackAction::Halt,
+            }));
+            cpu.run_fast(&mut mem, 1_000);
+            assert!(cpu.halted());
+            format!("{cpu:?}")
+        };
+        assert_eq!(run(true), run(false), "S = {s:04X}");
+    }
+}
+
+/// Memory that pauses the CPU on any write to $2000.
+struct PausingMem {
+    mem: CountingMem,
+    control: ControlHandle,
+}
+
+impl Memory for PausingMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem.read(addr)
+    }
+    fn write(&mut self, addr: u16, val: u8) {
+        if addr == 0x2000 {
+            self.control.pause();
+        }
+        self.mem.write(addr, val);
+    }
+}
+
+#[test]
+fn pause_inside_block_stops_the_replay() {
+    // loop: STA $2000 / INCA / BRA loop
+    let program = [0xB7, 0x20, 0x00, 0x4C, 0x20, 0xFA];
+    let mut cpu = Cpu::new();
+    cpu.set_block_cache(true);
+    let mut mem = PausingMem {
+        mem: CountingMem::new(&program, 0x0400),
+        control: cpu.control_handle(),
+    };
+    cpu.reset(&mut mem);
+    // Once to record the block, then to replay it.
+    for pass in 0..3 {
+        cpu.run_fast(&mut mem, 1_000);
+        assert_eq!(cpu.registers().pc, 0x0403, "pass {pass}");
+        assert_eq!(cpu.registers().a(), pass);
+        mem.control.resume();
+        cpu.step(&mut mem);
+        cpu.step(&mut mem);
+    }
+}
@@ -0,0 +1,511 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::bus::{Bank, CompositeBus, MemoryMap, Mmio, ShadowBus, WriteFault, WritePolicy};
+use crate::machine::Machine;
+use crate::snapshot::{StateReader, StateWriter};
+use crate::{BusFault, BusSignals, Clocked, Cpu, CpuError, FlatRam, Memory, Snapshot};
+
+#[test]
+fn flat_ram_load_and_vectors() {
+    let mut mem = FlatRam::new();
+    mem.load(&[0x12, 0x34], 0x0400);
+    mem.set_reset_vector(0x0400);
+    mem.set_vector(0xFFF8, 0x0600);
+    assert_eq!(mem.as_slice()[0x0400..0x0402], [0x12, 0x34]);
+    assert_eq!(mem.read_word(0xFFFE), 0x0400);
+    assert_eq!(mem.read_word(0xFFF8), 0x0600);
+}
+
+#[test]
+fn flat_ram_word_access_wraps() {
+    let mut mem = FlatRam::new();
+    mem.write_word(0xFFFF, 0xABCD);
+    assert_eq!(mem.as_slice()[0xFFFF], 0xAB);
+    assert_eq!(mem.as_slice()[0x0000], 0xCD);
+    assert_eq!(mem.read_word(0xFFFF), 0xABCD);
+
+    let mut buf = [0u8; 3];
+    mem.as_mut_slice()[0x0001] = 0xEF;
+    mem.read_slice(0xFFFF, &mut buf);
+    assert_eq!(buf, [0xAB, 0xCD, 0xEF]);
+}
+
+#[test]
+#[should_panic(expected = "exceeds 64 KB")]
+fn flat_ram_load_past_end_panics() {
+    FlatRam::new().load(&[0; 4], 0xFFFE);
+}
+
+// ---- MemoryMap ----
+
+/// MMIO device that remembers the last write and echoes it back.
+struct Latch(u8);
+
+impl Mmio for Latch {
+    fn read(&mut self, _offset: u16) -> u8 {
+        self.0
+    }
+    fn write(&mut self, offset: u16, val: u8) {
+        self.0 = val.wrapping_add(offset as u8);
+    }
+}
+
+#[test]
+fn memory_map_decodes_regions() {
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x8000)
+        .rom(0xC000, &[0xAA, 0xBB])
+        .mmio(0xFF00, 0x10, Latch(0));
+
+    map.write(0x7FFF, 0x11);
+    assert_eq!(map.read(0x7FFF), 0x11);
+    assert_eq!(map.read_word(0xC000), 0xAABB);
+
+    map.write(0xC000, 0x00); // ROM write is ignored
+    assert_eq!(map.read(0xC000), 0xAA);
+
+    map.write(0xFF02, 0x40);
+    assert_eq!(map.read(0xFF00), 0x42);
+
+    assert_eq!(map.region_at(0x0000), Some(0));
+    assert_eq!(map.region_at(0xC001), Some(1));
+    assert_eq!(map.region_at(0xC002), None);
+}
+
+#[test]
+fn memory_map_unmapped_access() {
+    let mut map = MemoryMap::new().unmapped_value(0x00);
+    map.write(0x1234, 0x56);
+    assert_eq!(map.read(0x1234), 0x00);
+}
+
+#[test]
+fn memory_map_later_region_wins() {
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x1000)
+        .rom(0x0800, &[0x99; 0x10]);
+    assert_eq!(map.read(0x0800), 0x99);
+    assert_eq!(map.region_at(0x0810), Some(0));
+}
+
+#[test]
+fn memory_map_load_bypasses_rom_protection() {
+    let mut map = MemoryMap::new().ram(0x0000, 0x100).rom(0x0100, &[0; 0x100]);
+    map.load(&[1, 2, 3, 4], 0x00FE);
+    assert_eq!(map.region_data(0).unwrap()[0xFE..], [1, 2]);
+    assert_eq!(map.region_data(1).unwrap()[..2], [3, 4]);
+}
+
+#[test]
+#[should_panic(expected = "not backed by RAM or ROM")]
+fn memory_map_load_into_mmio_panics() {
+    let mut map = MemoryMap::new().mmio_fn(0xFF00, 1, |_| 0, |_, _| {});
+    map.load(&[1], 0xFF00);
+}
+
+#[test]
+fn rom_write_trap_stops_host_loop() {
+    // 0400: LDA #$55 / STA $C000 / NOP
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x8000)
+        .rom(0xC000, &[0; 0x4000])
+        .on_write(WritePolicy::Trap);
+    map.load(&[0x86, 0x55, 0xB7, 0xC0, 0x00, 0x12], 0x0400);
+    map.load(&[0x04, 0x00], 0xFFFE);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut map);
+    let fault = loop {
+        cpu.step(&mut map);
+        if let Some(fault) = map.take_write_fault() {
+            break fault;
+        }
+    };
+    assert_eq!(
+        fault,
+        WriteFault {
+            region: 1,
+            addr: 0xC000,
+            val: 0x55
+        }
+    );
+    assert_eq!(cpu.registers().pc, 0x0405);
+    assert_eq!(map.read(0xC000), 0x00);
+    assert_eq!(map.take_write_fault(), None);
+}
+
+#[test]
+fn rom_write_trap_fails_try_step() {
+    // 0400: LDA #$55 / STA $C000
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x8000)
+        .rom(0xC000, &[0; 0x4000])
+        .on_write(WritePolicy::Trap);
+    map.load(&[0x86, 0x55, 0xB7, 0xC0, 0x00], 0x0400);
+    map.load(&[0x04, 0x00], 0xFFFE);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut map);
+    assert!(cpu.try_step(&mut map).is_ok());
+    let fault = BusFault {
+        addr: 0xC000,
+        write: Some(0x55),
+    };
+    assert_eq!(cpu.try_step(&mut map), Err(CpuError::BusFault(fault)));
+    assert!(map.take_write_fault().is_none());
+}
+
+#[test]
+fn read_only_ram_with_callback() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&hits);
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x100)
+        .on_write(WritePolicy::Callback(Box::new(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })));
+    map.write(0x0010, 0x01);
+    assert_eq!(map.read(0x0010), 0x00);
+    assert_eq!(hits.load(Ordering::Relaxed), 1);
+
+    map.set_write_policy(0, None);
+    map.write(0x0010, 0x01);
+    assert_eq!(map.read(0x0010), 0x01);
+    assert_eq!(hits.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn banked_region_selected_by_api() {
+    let mut map = MemoryMap::new().banked(0x8000, 0x100, [Bank::Rom(&[0xAA]), Bank::Ram]);
+    assert_eq!(map.read(0x8000), 0xAA);
+    assert_eq!(map.read(0x8001), 0xFF); // ROM padding
+    map.write(0x8000, 0x11); // dropped: ROM bank
+    assert_eq!(map.read(0x8000), 0xAA);
+
+    map.select_bank(0, 1);
+    assert_eq!(map.selected_bank(0), Some(1));
+    map.write(0x8000, 0x11);
+    assert_eq!(map.read(0x8000), 0x11);
+
+    map.select_bank(0, 2); // wraps to bank 0
+    assert_eq!(map.read(0x8000), 0xAA);
+}
+
+#[test]
+fn banked_region_selected_by_latch() {
+    let mut map = MemoryMap::new()
+        .ram(0x0000, 0x100)
+        .banked(0x4000, 0x10, [Bank::Ram, Bank::Ram, Bank::Ram])
+        .bank_latch(0x00FF);
+    map.write(0x4000, 0x10);
+    map.write(0x00FF, 2);
+    map.write(0x4000, 0x12);
+    assert_eq!(map.read(0x00FF), 0x00); // latch writes do not reach RAM
+    assert_eq!(map.bank_data_mut(1, 0).unwrap()[0], 0x10);
+    assert_eq!(map.region_data(1).unwrap()[0], 0x12);
+
+    map.write(0x00FF, 0);
+    assert_eq!(map.read(0x4000), 0x10);
+}
+
+#[test]
+#[should_panic(expected = "needs a banked region")]
+fn bank_latch_on_plain_region_panics() {
+    let _ = MemoryMap::new().ram(0x0000, 0x10).bank_latch(0x00FF);
+}
+
+#[test]
+fn mirrored_ram_repeats_through_window() {
+    let mut map = MemoryMap::new().ram(0x0000, 0x400).mirror(0x2000);
+    map.write(0x0010, 0x42);
+    assert_eq!(map.read(0x0410), 0x42);
+    assert_eq!(map.read(0x1C10), 0x42);
+    map.write(0x1FFF, 0x24);
+    assert_eq!(map.read(0x03FF), 0x24);
+    assert_eq!(map.region_at(0x2000), None);
+}
+
+#[test]
+fn mirrored_mmio_sees_region_offset() {
+    let mut map = MemoryMap::new()
+        .mmio_fn(0xFF00, 4, |offset| offset as u8, |_, _| {})
+        .mirror(0x20);
+    assert_eq!(map.read(0xFF01), 1);
+    assert_eq!(map.read(0xFF1E), 2);
+}
+
+#[test]
+#[should_panic(expected = "power of two")]
+fn mirror_needs_power_of_two_region() {
+    let _ = MemoryMap::new().ram(0x0000, 0x300).mirror(0x1000);
+}
+
+#[test]
+fn provided_buses_are_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<FlatRam>();
+    assert_send::<MemoryMap>();
+    assert_send::<CompositeBus<FlatRam>>();
+}
+
+/// Device returning a fixed tag and raising fixed signals once `period`
+/// cycles have been ticked.
+struct Tagged {
+    tag: u8,
+    written: Vec<(u16, u8)>,
+    elapsed: u64,
+    period: u64,
+    signals: BusSignals,
+}
+
+impl Tagged {
+    fn new(tag: u8, period: u64, signals: BusSignals) -> Self {
+        Self {
+            tag,
+            written: Vec::new(),
+            elapsed: 0,
+            period,
+            signals,
+        }
+    }
+}
+
+impl Mmio for Tagged {
+    fn read(&mut self, offset: u16) -> u8 {
+        self.tag.wrapping_add(offset as u8)
+    }
+
+    fn write(&mut self, offset: u16, val: u8) {
+        self.written.push((offset, val));
+    }
+}
+
+impl Clocked for Tagged {
+    fn tick(&mut self, cycles: u64) -> BusSignals {
+        self.elapsed += cycles;
+        if self.elapsed >= self.period {
+            self.signals
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+impl Snapshot for Tagged {}
+
+#[test]
+fn composite_bus_falls_through_to_base() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let dev = bus.add_device(Tagged::new(0x10, 1, BusSignals::default()));
+    bus.map(dev, 0xFF00, 4, 0);
+    bus.write(0x1000, 0x42);
+    assert_eq!(bus.read(0x1000), 0x42);
+    assert_eq!(bus.read(0xFF02), 0x12);
+    bus.write(0xFF03, 0x99);
+    assert_eq!(bus.base().as_slice()[0xFF03], 0);
+    assert_eq!(bus.device_at(0xFF03), Some(dev));
+    assert_eq!(bus.device_at(0xFF04), None);
+}
+
+#[test]
+fn composite_bus_overlap_priority() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let low = bus.add_device(Tagged::new(0x10, 1, BusSignals::default()));
+    let high = bus.add_device(Tagged::new(0x80, 1, BusSignals::default()));
+    bus.map(high, 0xFF04, 4, 1);
+    bus.map(low, 0xFF00, 16, 0);
+    assert_eq!(bus.device_at(0xFF04), Some(high));
+    assert_eq!(bus.read(0xFF05), 0x81);
+    assert_eq!(bus.read(0xFF08), 0x18);
+
+    // Equal priority: the later mapping wins.
+    let late = bus.add_device(Tagged::new(0x40, 1, BusSignals::default()));
+    bus.map(late, 0xFF0C, 4, 0);
+    assert_eq!(bus.read(0xFF0C), 0x40);
+}
+
+#[test]
+fn composite_bus_tick_combines_signals() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    bus.add_device(Tagged::new(0, 10, BusSignals::IRQ));
+    bus.add_device(Tagged::new(0, 20, BusSignals::FIRQ));
+    assert_eq!(bus.tick(5), BusSignals::default());
+    assert_eq!(bus.tick(5), BusSignals::IRQ);
+    assert_eq!(bus.tick(10), BusSignals::IRQ | BusSignals::FIRQ);
+}
+
+#[test]
+fn composite_bus_tick_includes_base() {
+    let mut inner = CompositeBus::new(FlatRam::new());
+    inner.add_device(Tagged::new(0, 10, BusSignals::NMI));
+    let mut bus = CompositeBus::new(inner);
+    bus.add_device(Tagged::new(0, 20, BusSignals::IRQ));
+    assert_eq!(bus.tick(5), BusSignals::default());
+    assert_eq!(bus.tick(5), BusSignals::NMI);
+    assert_eq!(bus.tick(10), BusSignals::NMI | BusSignals::IRQ);
+}
+
+#[test]
+#[should_panic(expected = "extends past 0xFFFF")]
+fn composite_bus_rejects_oversized_mapping() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let dev = bus.add_device(Tagged::new(0, 1, BusSignals::default()));
+    bus.map(dev, 0xFFF0, 0x20, 0);
+}
+
+#[test]
+fn composite_bus_master_sees_rest_of_bus() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let other = bus.add_device(Tagged::new(0x10, 1, BusSignals::default()));
+    let master = bus.add_device(Tagged::new(0x80, 1, BusSignals::default()));
+    bus.map(other, 0xFF00, 4, 0);
+    bus.map(master, 0xFF10, 4, 0);
+
+    let seen = bus.master(master, |dev: &mut Tagged, bus| {
+        dev.tag = 0x90;
+        bus.write(0x1000, 0x42);
+        [bus.read(0xFF01), bus.read(0xFF10)]
+    });
+    assert_eq!(seen, Some([0x11, 0xFF]));
+    assert_eq!(bus.read(0xFF10), 0x90);
+    assert_eq!(bus.read(0x1000), 0x42);
+    assert!(bus.master(master, |_: &mut FlatRamDevice, _| ()).is_none());
+    assert!(bus.device_as::<Tagged>(other).is_some());
+}
+
+/// A device type that is never added, for failed downcasts.
+struct FlatRamDevice;
+
+impl Mmio for FlatRamDevice {
+    fn read(&mut self, _offset: u16) -> u8 {
+        0
+    }
+
+    fn write(&mut self, _offset: u16, _val: u8) {}
+}
+
+impl Clocked for FlatRamDevice {}
+
+impl Snapshot for FlatRamDevice {}
+
+#[test]
+fn memory_map_seeded_open_bus_replays() {
+    let read_all = |seed| {
+        let mut map = MemoryMap::new().ram(0x0000, 0x100).open_bus_seed(seed);
+        (0..64).map(|i| map.read(0x8000 + i)).collect::<Vec<u8>>()
+    };
+    let a = read_all(5);
+    assert_eq!(a, read_all(5));
+    assert_ne!(a, read_all(6));
+    assert!(a.iter().any(|&b| b != 0xFF));
+}
+
+#[test]
+fn memory_map_snapshot_restores_ram_and_banks() {
+    let build = || {
+        MemoryMap::new()
+            .ram(0x0000, 0x100)
+            .rom(0xF000, &[0xAA; 0x10])
+            .banked(0x8000, 0x100, [Bank::Ram, Bank::Rom(&[0x55]), Bank::Ram])
+            .bank_latch(0xFF00)
+    };
+    let mut map = build();
+    map.write(0x0010, 0x12);
+    map.write(0x8000, 0x34);
+    map.write(0xFF00, 2);
+    map.write(0x8000, 0x56);
+    let mut out = StateWriter::new();
+    map.snapshot(&mut out);
+    let state = out.into_bytes();
+
+    let mut restored = build();
+    restored.restore(&mut StateReader::new(&state)).unwrap();
+    assert_eq!(restored.read(0x0010), 0x12);
+    assert_eq!(restored.selected_bank(2), Some(2));
+    assert_eq!(restored.read(0x8000), 0x56);
+    restored.select_bank(2, 0);
+    assert_eq!(restored.read(0x8000), 0x34);
+}
+
+/// Address and PC of each read a [`ShadowBus`] reported.
+type ReadLog = Arc<Mutex<Vec<(u16, u16)>>>;
+
+/// A shadow bus over flat RAM that logs what it reports.
+fn shadow_bus() -> (ShadowBus<FlatRam>, ReadLog) {
+    let reads = Arc::new(Mutex::new(Vec::new()));
+    let log = reads.clone();
+    let mut bus = ShadowBus::new(FlatRam::new()).rom(0xF000, 0x1000);
+    bus.on_uninitialized(Some(Box::new(move |addr, pc| {
+        log.lock().unwrap().push((addr, pc))
+    })));
+    (bus, reads)
+}
+
+#[test]
+fn shadow_bus_reports_first_read_of_unwritten_bytes() {
+    let (mut bus, reads) = shadow_bus();
+    bus.set_pc(0x1234);
+    bus.write(0x0100, 1);
+    bus.read(0x0100);
+    bus.read(0xF800);
+    bus.read(0x0200);
+    bus.read(0x0200);
+    bus.read_word(0x02FF);
+    assert_eq!(
+        *reads.lock().unwrap(),
+        [(0x0200, 0x1234), (0x02FF, 0x1234), (0x0300, 0x1234)]
+    );
+    assert!(bus.is_initialized(0xF000));
+    assert!(!bus.is_initialized(0x0200));
+
+    bus.mark_written(0x0400, 2);
+    bus.inner_mut().write(0x0500, 9);
+    reads.lock().unwrap().clear();
+    bus.read_word(0x0400);
+    assert!(reads.lock().unwrap().is_empty());
+
+    // Clearing forgets writes and reports alike.
+    bus.clear();
+    bus.read(0x0100);
+    bus.read(0x0200);
+    assert_eq!(*reads.lock().unwrap(), [(0x0100, 0x1234), (0x0200, 0x1234)]);
+}
+
+#[test]
+fn shadow_bus_tracks_cpu_pc_through_step_hook() {
+    let (mut bus, reads) = shadow_bus();
+    // LDS #$0200; PSHS A; PULS B; LDX $0300; LDA ,S
+    bus.inner_mut().load(
+        &[
+            0x10, 0xCE, 0x02, 0x00, 0x34, 0x02, 0x35, 0x04, 0xBE, 0x03, 0x00, 0xA6, 0xE4,
+        ],
+        0xF000,
+    );
+    bus.inner_mut().set_reset_vector(0xF000);
+    let mut machine = Machine::new(bus);
+    machine.add_step_hook(|cpu, bus: &mut ShadowBus<FlatRam>, _| bus.set_pc(cpu.registers().pc));
+    for _ in 0..5 {
+        machine.step();
+    }
+    // The pushed byte was written, so only the load and the read at the
+    // old top of the stack are reported.
+    assert_eq!(
+        *reads.lock().unwrap(),
+        [(0x0300, 0xF008), (0x0301, 0xF008), (0x0200, 0xF00B)]
+    );
+}
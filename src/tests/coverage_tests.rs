@@ -0,0 +1,103 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::coverage::{COVERAGE_BYTES, Coverage};
+#[cfg(feature = "hooks")]
+use crate::{Cpu, FlatRam};
+
+/// A CPU recording coverage, reset into `program` at 0x0400.
+#[cfg(feature = "hooks")]
+fn setup(program: &[u8]) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.set_coverage(true);
+    (cpu, ram)
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn coverage_is_off_by_default() {
+    let mut cpu = Cpu::new();
+    assert!(cpu.coverage().is_none());
+    assert!(cpu.take_coverage().is_none());
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn coverage_records_instruction_starts_on_every_path() {
+    // LDX #$0003; loop: LEAX -1,X; BNE loop; NOP
+    let program = [0x8E, 0x00, 0x03, 0x30, 0x1F, 0x26, 0xFC, 0x12];
+    let expected = [0x0400, 0x0403, 0x0405, 0x0407];
+    for mode in 0..3 {
+        let (mut cpu, mut ram) = setup(&program);
+        match mode {
+            0 => {
+                cpu.run(&mut ram, 30);
+            }
+            1 => {
+                cpu.set_decode_cache(true);
+                cpu.run(&mut ram, 30);
+            }
+            _ => {
+                cpu.set_block_cache(true);
+                cpu.run_fast(&mut ram, 30);
+            }
+        }
+        let coverage = cpu.coverage().unwrap();
+        assert_eq!(coverage.iter().take(4).collect::<Vec<_>>(), expected);
+        assert_eq!(coverage.count_in(0x0400..=0x0407), 4);
+    }
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn take_coverage_keeps_recording() {
+    let (mut cpu, mut ram) = setup(&[0x12, 0x12]);
+    cpu.step(&mut ram);
+    let first = cpu.take_coverage().unwrap();
+    cpu.step(&mut ram);
+    let second = cpu.take_coverage().unwrap();
+    assert_eq!(first.iter().collect::<Vec<_>>(), [0x0400]);
+    assert_eq!(second.iter().collect::<Vec<_>>(), [0x0401]);
+
+    cpu.set_coverage(false);
+    assert!(cpu.coverage().is_none());
+}
+
+#[test]
+fn coverage_merges_and_round_trips() {
+    let mut a = Coverage::new();
+    a.insert(0x0000);
+    a.insert(0xE000);
+    let mut b = Coverage::new();
+    b.insert(0xE000);
+    b.insert(0xFFFF);
+    a.merge(&b);
+    assert_eq!(a.len(), 3);
+    assert!(a.contains(0xFFFF));
+    assert!(!a.contains(0x0001));
+
+    let bytes = a.to_bytes();
+    assert_eq!(bytes.len(), COVERAGE_BYTES);
+    assert_eq!(bytes[0xE000 / 8], 0x01);
+    assert_eq!(bytes[COVERAGE_BYTES - 1], 0x80);
+    assert_eq!(Coverage::from_bytes(&bytes), Some(a.clone()));
+    assert_eq!(Coverage::from_bytes(&bytes[1..]), None);
+
+    a.clear();
+    assert!(a.is_empty());
+}
@@ -14,7 +14,18 @@
 
 //! Integration tests for the CPU — load short programs and verify behavior.
 
-use crate::{BusSignals, Cpu, Memory, registers::CC_E};
+use std::time::Duration;
+
+use crate::batch::SeedRng;
+use crate::registers::{CC_E, CC_F, CC_I};
+use crate::snapshot::StateError;
+use crate::stats::{AddressingMode, InterruptSource};
+use crate::{
+    BusSignals, CodeWrite, ConditionCodes, Cpu, CpuConfig, CpuError, HalfCarryQuirks,
+    IllegalOpcodeMode, IllegalOpcodePolicy, Memory, OpcodeFetch, Registers, ResetState, Stack,
+    StackAccess, StackAction, StackMonitor, StackViolation, StepInfo, StepResult, StopReason, Swi,
+    SwiAction, TransferQuirks, VectorTable, assert_regs,
+};
 
 /// Simple 64KB flat RAM mem for testing.
 struct TestMem {
@@ -391,6 +402,29 @@ fn pshs_puls_multiple() {
     assert_eq!(cpu.registers().s, 0x8000);
 }
 
+#[test]
+fn pshu_pulu_all_restores_every_register() {
+    // PSHU PC,S,Y,X,DP,B,A,CC then PULU the same, which returns to the
+    // pushed PC, just past the PSHU
+    let (mut cpu, mut mem) = setup(&[0x36, 0xFF, 0x37, 0xFF], 0x0400);
+    let pushed = Registers::builder()
+        .a(0xAA)
+        .b(0xBB)
+        .x(0x1234)
+        .y(0x5678)
+        .u(0x9000)
+        .s(0x8000)
+        .dp(0x20)
+        .cc(0x0F);
+    *cpu.registers_mut() = pushed.pc(0x0400).build();
+    cpu.step(&mut mem); // PSHU
+    assert_regs!(cpu.registers(), pushed.u(0x8FF4).pc(0x0402).build());
+    // Clobber everything but U and PC
+    *cpu.registers_mut() = Registers::builder().u(0x8FF4).pc(0x0402).build();
+    cpu.step(&mut mem); // PULU
+    assert_regs!(cpu.registers(), pushed.pc(0x0402).build(), "after PULU");
+}
+
 // ---- Transfer / Exchange ----
 
 #[test]
@@ -656,6 +690,77 @@ fn swi_instruction() {
     assert_eq!(cpu.registers().s, 0x8000 - 12);
 }
 
+#[test]
+fn swi_hook_services_call_without_vectoring() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x3F, // SWI
+            0x10, 0x3F, // SWI2
+            0x11, 0x3F, // SWI3
+        ],
+        0x0400,
+    );
+    cpu.registers_mut().s = 0x8000;
+    cpu.set_swi_hook(Some(Box::new(|swi, reg, mem| match swi {
+        Swi::Swi => {
+            mem.write(0x0200, reg.a());
+            reg.set_a(0x42);
+            SwiAction::Serviced
+        }
+        Swi::Swi2 => SwiAction::Serviced,
+        Swi::Swi3 => SwiAction::Vector,
+    })));
+    mem.mem[0xFFF2] = 0x30;
+    mem.mem[0xFFF3] = 0x00; // SWI3 vector → 0x3000
+    cpu.registers_mut().set_a(0x07);
+
+    assert_eq!(cpu.step(&mut mem), 19);
+    assert_eq!(cpu.registers().pc, 0x0401);
+    assert_eq!(cpu.registers().a(), 0x42);
+    assert_eq!(cpu.registers().s, 0x8000);
+    assert_eq!(mem.mem[0x0200], 0x07);
+
+    assert_eq!(cpu.step(&mut mem), 20);
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(cpu.registers().s, 0x8000);
+
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x3000);
+    assert_eq!(cpu.registers().s, 0x8000 - 12);
+}
+
+#[test]
+fn relocated_vectors_used_for_reset_interrupts_and_swi() {
+    let mut mem = TestMem::new();
+    mem.write_bytes(0x0400, &[0x3F, 0x10, 0x3F]); // SWI / SWI2
+    mem.mem[0x7FFE] = 0x04;
+    mem.mem[0x7FFF] = 0x00; // reset → 0x0400
+    mem.mem[0x7FFA] = 0x05;
+    mem.mem[0x7FFB] = 0x00; // SWI → 0x0500
+    mem.mem[0x0500] = 0x3B; // RTI
+    mem.mem[0x7FF4] = 0x06;
+    mem.mem[0x7FF5] = 0x00; // SWI2 → 0x0600
+    mem.mem[0x7FF8] = 0x07;
+    mem.mem[0x7FF9] = 0x00; // IRQ → 0x0700
+    let mut cpu = Cpu::new();
+    cpu.set_vectors(VectorTable::at(0x7FF0));
+    assert_eq!(cpu.vectors().irq, 0x7FF8);
+    cpu.reset(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0400);
+    cpu.registers_mut().s = 0x4000;
+
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    cpu.step(&mut mem); // RTI
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0600);
+
+    cpu.registers_mut().cc.set_irq_inhibit(false);
+    cpu.set_irq(true);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0700);
+}
+
 // ---- RTI (full from NMI) ----
 
 #[test]
@@ -957,6 +1062,108 @@ fn illegal_opcode_sets_flag_but_execution_continues() {
     assert!(!cpu.halted());
 }
 
+#[test]
+fn illegal_opcode_halt_policy_stops_run() {
+    let (mut cpu, mut mem) = setup(&[0x12, 0x10, 0x00, 0x12], 0x0400);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+
+    cpu.run(&mut mem, 100);
+    assert!(cpu.halted());
+    assert!(cpu.illegal());
+    assert_eq!(cpu.registers().pc, 0x0403);
+}
+
+#[test]
+fn illegal_opcode_trap_policy_enters_vector() {
+    // 0x11 0x00 is undefined on page 2.
+    let (mut cpu, mut mem) = setup(&[0x11, 0x00], 0x0400);
+    mem.write_bytes(0xFFF0, &[0x20, 0x00]);
+    cpu.registers_mut().s = 0x8000;
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::TrapToVector(0xFFF0));
+
+    let cycles = cpu.step(&mut mem);
+    assert_eq!(cycles, 20);
+    assert!(cpu.illegal());
+    let reg = cpu.registers();
+    assert_eq!(reg.pc, 0x2000);
+    assert_eq!(reg.s, 0x8000 - 12);
+    assert!(reg.cc.entire() && reg.cc.irq_inhibit() && reg.cc.firq_inhibit());
+    // The stacked PC is the instruction after the illegal one.
+    assert_eq!(mem.read_word(0x8000 - 2), 0x0402);
+}
+
+#[test]
+fn illegal_opcode_callback_policy_sees_instruction_address() {
+    let (mut cpu, mut mem) = setup(&[0x12, 0x87, 0x12, 0x12], 0x0400);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = seen.clone();
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Callback(Box::new(move |reg, addr| {
+        log.lock().unwrap().push(addr);
+        // Skip the following NOP.
+        reg.pc += 1;
+    })));
+
+    cpu.step(&mut mem);
+    let cycles = cpu.step(&mut mem);
+    assert_eq!(cycles, 1);
+    assert_eq!(*seen.lock().unwrap(), [0x0401]);
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert!(!cpu.halted());
+}
+
+#[test]
+fn store_immediate_writes_over_its_operand() {
+    let (mut cpu, mut mem) = setup(
+        &[0x87, 0x00, 0x8F, 0x00, 0x00, 0x10, 0xCF, 0x00, 0x00],
+        0x0400,
+    );
+    cpu.set_undocumented_opcodes(true);
+    cpu.registers_mut().set_a(0x80);
+    cpu.registers_mut().x = 0x1234;
+    cpu.registers_mut().s = 0x0000;
+
+    assert_eq!(cpu.step(&mut mem), 2);
+    assert_eq!(mem.read(0x0401), 0x80);
+    assert!(cpu.registers().cc.negative());
+    assert_eq!(cpu.step(&mut mem), 3);
+    assert_eq!(mem.read_word(0x0403), 0x1234);
+    assert_eq!(cpu.step(&mut mem), 4);
+    assert!(cpu.registers().cc.zero());
+    assert_eq!(cpu.registers().pc, 0x0409);
+    assert!(!cpu.illegal());
+}
+
+#[test]
+fn undefined_prefixed_opcode_runs_page0_instruction() {
+    // LDA #$42 and NOP behind page 1 and page 2 prefixes.
+    let (mut cpu, mut mem) = setup(&[0x10, 0x86, 0x42, 0x11, 0x12], 0x0400);
+    cpu.set_undocumented_opcodes(true);
+
+    assert_eq!(cpu.step(&mut mem), 3);
+    assert_eq!(cpu.registers().a(), 0x42);
+    assert_eq!(cpu.step(&mut mem), 3);
+    assert_eq!(cpu.registers().pc, 0x0405);
+    assert!(!cpu.illegal());
+}
+
+#[test]
+fn undocumented_opcodes_are_illegal_by_default() {
+    let (mut cpu, mut mem) = setup(&[0x10, 0x86, 0x42], 0x0400);
+    assert_eq!(cpu.step(&mut mem), 2);
+    assert!(cpu.illegal());
+    assert_eq!(cpu.registers().a(), 0);
+    assert_eq!(cpu.registers().pc, 0x0402);
+}
+
+#[test]
+fn repeated_prefix_stays_illegal_with_undocumented_opcodes() {
+    let (mut cpu, mut mem) = setup(&[0x10, 0x10, 0x12], 0x0400);
+    cpu.set_undocumented_opcodes(true);
+    assert_eq!(cpu.step(&mut mem), 2);
+    assert!(cpu.illegal());
+    assert_eq!(cpu.registers().pc, 0x0402);
+}
+
 // ---- X18: undocumented flag rotate (0x18) ----
 
 #[test]
@@ -1132,184 +1339,922 @@ fn exg_mixed_size_both_get_ff() {
     assert_eq!(cpu.registers().x, 0xFFFF);
 }
 
-// ---- Undocumented SWI2 (0x10 0x3E): does not set E before pushing ----
+/// Run `program` with hardware-measured TFR/EXG behaviour.
+fn run_hardware_transfer(program: &[u8], steps: usize) -> Cpu {
+    let (mut cpu, mut mem) = setup(program, 0x0400);
+    cpu.set_transfer_quirks(TransferQuirks::Hardware);
+    for _ in 0..steps {
+        cpu.step(&mut mem);
+    }
+    cpu
+}
 
 #[test]
-fn swi2_undoc_page1_no_e_flag_in_frame() {
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x1C, 0x7F, // ANDCC #$7F → clear E
-            0x10, 0x3E, // SWI2 undocumented
-        ],
-        0x0400,
-    );
-    cpu.registers_mut().s = 0x8000;
-    mem.mem[0xFFF4] = 0x20;
-    mem.mem[0xFFF5] = 0x00; // SWI2 vector → 0x2000
-    cpu.step(&mut mem); // ANDCC — E clear
-    cpu.step(&mut mem); // SWI2 undoc
-    assert_eq!(cpu.registers().pc, 0x2000);
-    assert_eq!(cpu.registers().s, 0x8000 - 12);
-    let saved_cc = mem.mem[cpu.registers().s as usize];
-    assert_eq!(saved_cc & 0x80, 0x00); // E was NOT set before push
+fn tfr_hardware_promotes_8_bit_source() {
+    // LDB #$42 / TFR B,X
+    let cpu = run_hardware_transfer(&[0xC6, 0x42, 0x1F, 0x91], 2);
+    assert_eq!(cpu.registers().x, 0xFF42);
 }
 
-// ---- XFIRQ (0x11 0x3E): push state, jump via FIRQ vector, no flag changes ----
-
 #[test]
-fn xfirq_undoc_jumps_via_firq_vector_no_flag_changes() {
-    let (mut cpu, mut mem) = setup(
+fn tfr_hardware_cc_and_dp_fill_both_bytes() {
+    // LDA #$37 / TFR A,DP / TFR DP,Y / ANDCC #$00 / ORCC #$05 / TFR CC,U
+    let cpu = run_hardware_transfer(
         &[
-            0x1C, 0xAF, // ANDCC #$AF → clear F and I
-            0x11, 0x3E, // XFIRQ
+            0x86, 0x37, 0x1F, 0x8B, 0x1F, 0xB2, 0x1C, 0x00, 0x1A, 0x05, 0x1F, 0xA3,
         ],
-        0x0400,
+        6,
     );
-    cpu.registers_mut().s = 0x8000;
-    mem.mem[0xFFF6] = 0x30;
-    mem.mem[0xFFF7] = 0x00; // FIRQ vector → 0x3000
-    cpu.step(&mut mem); // ANDCC
-    assert!(!cpu.registers().cc.firq_inhibit());
-    assert!(!cpu.registers().cc.irq_inhibit());
-    cpu.step(&mut mem); // XFIRQ
-    assert_eq!(cpu.registers().pc, 0x3000);
-    assert!(!cpu.registers().cc.firq_inhibit()); // F still clear
-    assert!(!cpu.registers().cc.irq_inhibit()); // I still clear
-    assert_eq!(cpu.registers().s, 0x8000 - 12); // full state pushed
+    assert_eq!(cpu.registers().y, 0x3737);
+    assert_eq!(cpu.registers().u, 0x0505);
 }
 
-// ---- XNC: NEG if C=0, COM if C=1 ----
-
 #[test]
-fn xnc_a_carry_clear_acts_as_nega() {
-    // 0x42: XNC A — C=0 → behaves like NEGA
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x86, 0x05, // LDA #$05
-            0x1C, 0xFE, // ANDCC #$FE → clear C
-            0x42, // XNC A
-        ],
-        0x0400,
-    );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().a(), 0xFB); // neg(0x05) = 0xFB (two's complement)
-    assert!(cpu.registers().cc.carry()); // neg of non-zero sets C
+fn tfr_hardware_16_bit_source_gives_low_byte() {
+    // LDX #$1234 / TFR X,A
+    let cpu = run_hardware_transfer(&[0x8E, 0x12, 0x34, 0x1F, 0x18], 2);
+    assert_eq!(cpu.registers().a(), 0x34);
 }
 
 #[test]
-fn xnc_a_carry_set_acts_as_coma() {
-    // 0x42: XNC A — C=1 → behaves like COMA
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x86, 0xAA, // LDA #$AA
-            0x1A, 0x01, // ORCC #$01 → set C
-            0x42, // XNC A
-        ],
-        0x0400,
-    );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().a(), 0x55); // ~$AA = $55
-    assert!(cpu.registers().cc.carry()); // COM always sets C
+fn exg_hardware_converts_both_ways() {
+    // LDA #$55 / LDX #$1234 / EXG A,X
+    let cpu = run_hardware_transfer(&[0x86, 0x55, 0x8E, 0x12, 0x34, 0x1E, 0x81], 3);
+    assert_eq!(cpu.registers().a(), 0x34);
+    assert_eq!(cpu.registers().x, 0xFF55);
 }
 
 #[test]
-fn xnc_b_carry_clear_acts_as_negb() {
-    // 0x52: XNC B — C=0 → behaves like NEGB
-    let (mut cpu, mut mem) = setup(
-        &[
-            0xC6, 0x01, // LDB #$01
-            0x1C, 0xFE, // ANDCC #$FE → clear C
-            0x52, // XNC B
-        ],
-        0x0400,
-    );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().b(), 0xFF);
+fn tfr_undefined_register_reads_all_ones_in_both_modes() {
+    // TFR 6,X / TFR C,A
+    for quirks in [TransferQuirks::Simple, TransferQuirks::Hardware] {
+        let (mut cpu, mut mem) = setup(&[0x1F, 0x61, 0x1F, 0xC8], 0x0400);
+        cpu.set_transfer_quirks(quirks);
+        cpu.step(&mut mem);
+        cpu.step(&mut mem);
+        assert_eq!(cpu.registers().x, 0xFFFF, "{quirks:?}");
+        assert_eq!(cpu.registers().a(), 0xFF, "{quirks:?}");
+    }
 }
 
+// ---- try_step ----
+
 #[test]
-fn xnc_direct_carry_set_acts_as_com() {
-    // 0x02: XNC direct — C=1 → behaves like COM
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x1A, 0x01, // ORCC #$01 → set C
-            0x02, 0x50, // XNC <$50
-        ],
-        0x0400,
+fn try_step_stops_at_breakpoint_once_per_visit() {
+    // 0400: NOP / BRA 0400
+    let (mut cpu, mut mem) = setup(&[0x12, 0x20, 0xFD], 0x0400);
+    cpu.add_breakpoint(0x0401);
+
+    assert_eq!(
+        cpu.try_step(&mut mem),
+        Ok(StepInfo {
+            pc: 0x0400,
+            cycles: 2
+        })
+    );
+    assert_eq!(
+        cpu.try_step(&mut mem),
+        Err(CpuError::Breakpoint { pc: 0x0401 })
+    );
+    assert_eq!(
+        cpu.try_step(&mut mem),
+        Ok(StepInfo {
+            pc: 0x0401,
+            cycles: 3
+        })
+    );
+    assert!(cpu.try_step(&mut mem).is_ok());
+    assert_eq!(
+        cpu.try_step(&mut mem),
+        Err(CpuError::Breakpoint { pc: 0x0401 })
     );
-    mem.mem[0x0050] = 0x0F;
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(mem.mem[0x0050], 0xF0); // ~0x0F = 0xF0
-}
 
-// ---- XDEC: DEC but C reflects (original != 0) ----
+    assert!(cpu.remove_breakpoint(0x0401));
+    assert_eq!(cpu.breakpoints().count(), 0);
+    assert!(cpu.try_step(&mut mem).is_ok());
+}
 
 #[test]
-fn xdec_a_nonzero_sets_carry() {
-    // 0x4B: XDEC A — operand=3 → result=2, C=1 (3 != 0)
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x86, 0x03, // LDA #$03
-            0x1C, 0xFE, // ANDCC #$FE → clear C
-            0x4B, // XDEC A
-        ],
-        0x0400,
-    );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().a(), 0x02);
-    assert!(cpu.registers().cc.carry()); // 3 != 0 → C set
+fn try_step_breakpoint_on_halted_cpu_reports_once() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400);
+    cpu.set_halted(true);
+    cpu.add_breakpoint(0x0400);
+    assert!(cpu.try_step(&mut mem).is_err());
+    assert!(cpu.try_step(&mut mem).is_ok());
+    assert!(cpu.try_step(&mut mem).is_ok());
 }
 
 #[test]
-fn xdec_a_zero_clears_carry() {
-    // 0x4B: XDEC A — operand=0 → result=0xFF, C=0 (0 == 0)
-    let (mut cpu, mut mem) = setup(
-        &[
-            0x86, 0x00, // LDA #$00
-            0x1A, 0x01, // ORCC #$01 → set C (to verify it gets cleared)
-            0x4B, // XDEC A
-        ],
-        0x0400,
+fn try_step_reports_trapped_illegal_opcode() {
+    // 0400: NOP / 0x10 0x00 (illegal) / NOP
+    let (mut cpu, mut mem) = setup(&[0x12, 0x10, 0x00, 0x12], 0x0400);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Trap);
+
+    assert!(cpu.try_step(&mut mem).is_ok());
+    let err = cpu.try_step(&mut mem).unwrap_err();
+    assert_eq!(err, CpuError::IllegalOpcode { pc: 0x0401 });
+    assert_eq!(err.to_string(), "illegal opcode at 0401");
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(
+        cpu.try_step(&mut mem),
+        Ok(StepInfo {
+            pc: 0x0403,
+            cycles: 2
+        })
     );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().a(), 0xFF);
-    assert!(!cpu.registers().cc.carry()); // 0 == 0 → C cleared
-    assert!(cpu.registers().cc.negative()); // 0xFF is negative
+    assert!(!cpu.halted());
 }
 
+// ---- step_info ----
+
 #[test]
-fn xdec_b_nonzero_sets_carry() {
-    // 0x5B: XDEC B — operand=0x80 → result=0x7F, V set, C set (0x80 != 0)
-    let (mut cpu, mut mem) = setup(
-        &[
-            0xC6, 0x80, // LDB #$80
-            0x1C, 0xFE, // ANDCC #$FE → clear C
-            0x5B, // XDEC B
-        ],
-        0x0400,
+fn step_info_reports_instruction() {
+    // 0400: LDA #$42 / SWI3
+    let (mut cpu, mut mem) = setup(&[0x86, 0x42, 0x11, 0x3F], 0x0400);
+    cpu.registers_mut().s = 0x0800;
+    mem.write_bytes(0xFFF2, &[0x06, 0x00]);
+    assert_eq!(
+        cpu.step_info(&mut mem),
+        StepResult {
+            cycles: 2,
+            pc_before: 0x0400,
+            pc_after: 0x0402,
+            opcode: Some(0x86),
+            interrupt_taken: None,
+        }
     );
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    cpu.step(&mut mem);
-    assert_eq!(cpu.registers().b(), 0x7F);
-    assert!(cpu.registers().cc.overflow()); // 0x80 → V set (same as DEC)
-    assert!(cpu.registers().cc.carry()); // 0x80 != 0 → C set
+    assert_eq!(
+        cpu.step_info(&mut mem),
+        StepResult {
+            cycles: 20,
+            pc_before: 0x0402,
+            pc_after: 0x0600,
+            opcode: Some(0x113F),
+            interrupt_taken: None,
+        }
+    );
+    assert!(cpu.last_instruction().is_none());
 }
 
 #[test]
-fn xdec_direct_zero_clears_carry() {
-    // 0x0B: XDEC direct — operand=0 → carry cleared
+fn step_info_reports_interrupt_and_wait() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    cpu.set_last_instruction(true);
+    cpu.set_firq(true);
+    let result = cpu.step_info(&mut mem);
+    assert_eq!(result.interrupt_taken, Some(InterruptSource::Firq));
+    assert_eq!(result.opcode, None);
+    assert_eq!((result.pc_before, result.pc_after), (0x0400, 0x0600));
+    assert_eq!(result.cycles, 10);
+    cpu.set_firq(false);
+    assert_eq!(cpu.step_info(&mut mem).opcode, Some(0x3B));
+    assert_eq!(cpu.last_instruction().unwrap().pc, 0x0600);
+
+    // 0400: SYNC
+    let (mut cpu, mut mem) = setup(&[0x13], 0x0400);
+    assert_eq!(cpu.step_info(&mut mem).opcode, Some(0x13));
+    let result = cpu.step_info(&mut mem);
+    assert_eq!(result.opcode, None);
+    assert_eq!(result.interrupt_taken, None);
+    assert_eq!(result.cycles, 1);
+}
+
+// ---- tick ----
+
+#[test]
+fn tick_clocks_each_step_through_its_cycles() {
+    // 0400: LDA $2000 (5 cycles) / NOP
+    let (mut cpu, mut mem) = setup(&[0xB6, 0x20, 0x00, 0x12], 0x0400);
+    mem.write_bytes(0x2000, &[0x99]);
+    assert!(!cpu.tick(&mut mem));
+    assert_eq!(cpu.registers().a(), 0x99);
+    assert_eq!(cpu.cycles(), 5);
+    assert_eq!(cpu.tick_cycles_left(), 4);
+    for _ in 0..3 {
+        assert!(!cpu.tick(&mut mem));
+    }
+    assert!(cpu.tick(&mut mem));
+    assert_eq!(cpu.tick_cycles_left(), 0);
+    assert!(!cpu.tick(&mut mem));
+    assert_eq!(cpu.registers().pc, 0x0404);
+
+    cpu.reset(&mut mem);
+    assert_eq!(cpu.tick_cycles_left(), 0);
+}
+
+#[test]
+fn load_state_starts_ticking_at_a_step_boundary() {
+    // 0400: LDA $2000 (5 cycles) / NOP
+    let (mut cpu, mut mem) = setup(&[0xB6, 0x20, 0x00, 0x12], 0x0400);
+    let state = cpu.save_state();
+    assert!(!cpu.tick(&mut mem));
+    assert_eq!(cpu.tick_cycles_left(), 4);
+
+    cpu.load_state(&state).unwrap();
+    assert_eq!(cpu.tick_cycles_left(), 0);
+    assert!(!cpu.tick(&mut mem));
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(cpu.cycles(), 5);
+}
+
+#[test]
+fn tick_samples_interrupts_between_steps() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    assert!(!cpu.tick(&mut mem));
+    cpu.set_irq(true);
+    assert!(cpu.tick(&mut mem));
+    assert_eq!(cpu.registers().pc, 0x0401);
+    assert!(!cpu.tick(&mut mem));
+    assert_eq!(cpu.registers().pc, 0x0500);
+    let mut ticks = 1;
+    while !cpu.tick(&mut mem) {
+        ticks += 1;
+    }
+    assert_eq!(ticks + 1, 19);
+}
+
+// ---- step_n ----
+
+#[test]
+fn step_n_counts_steps_and_instructions() {
+    // 0400: LDA #$01 / NOP / NOP
+    let (mut cpu, mut mem) = setup(&[0x86, 0x01, 0x12, 0x12], 0x0400);
+    assert_eq!(cpu.step_n(&mut mem, 2), 4);
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(cpu.instructions_retired(), 2);
+    assert_eq!(cpu.step_n(&mut mem, 0), 0);
+
+    cpu.reset(&mut mem);
+    assert_eq!(cpu.instructions_retired(), 0);
+    cpu.set_halted(true);
+    assert_eq!(cpu.step_n(&mut mem, 5), 0);
+}
+
+#[test]
+fn instructions_retired_skips_interrupt_entry_and_waiting() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    cpu.set_irq(true);
+    // IRQ entry, then the handler's RTI
+    cpu.step(&mut mem);
+    assert_eq!(cpu.instructions_retired(), 0);
+    cpu.set_irq(false);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.instructions_retired(), 1);
+
+    // 0400: SYNC
+    let (mut cpu, mut mem) = setup(&[0x13], 0x0400);
+    cpu.step_n(&mut mem, 4);
+    assert_eq!(cpu.instructions_retired(), 1);
+}
+
+#[test]
+fn instructions_retired_counts_in_every_run_loop() {
+    // 0400: LDB #$10 / DECB / BNE *-1 / BRA *
+    let program = [0xC6, 0x10, 0x5A, 0x26, 0xFD, 0x20, 0xFE];
+    let (mut cpu, mut mem) = setup(&program, 0x0400);
+    cpu.run(&mut mem, 200);
+    let expected = cpu.instructions_retired();
+    let cycles = cpu.cycles();
+
+    let (mut fast, mut mem) = setup(&program, 0x0400);
+    fast.set_block_cache(true);
+    fast.run_fast(&mut mem, 200);
+    assert_eq!(fast.cycles(), cycles);
+    assert_eq!(fast.instructions_retired(), expected);
+}
+
+// ---- run_budgeted ----
+
+#[test]
+fn run_budgeted_carries_overshoot_into_next_budget() {
+    // 0400: JSR $0400 pushes forever; 8 cycles each
+    let (mut cpu, mut mem) = setup(&[0xBD, 0x04, 0x00], 0x0400);
+    cpu.registers_mut().s = 0x8000;
+    let mut total = 0;
+    for frame in 1..=50 {
+        total += cpu.run_budgeted(&mut mem, 25);
+        assert_eq!(total, cpu.cycles());
+        assert_eq!(cpu.cycles() - cpu.cycle_debt(), frame * 25);
+        assert!(cpu.cycle_debt() < 8);
+    }
+}
+
+#[test]
+fn run_budgeted_restarts_after_other_runs() {
+    // 0400: BRA *
+    let (mut cpu, mut mem) = setup(&[0x20, 0xFE], 0x0400);
+    assert_eq!(cpu.run_budgeted(&mut mem, 10), 12);
+    cpu.run(&mut mem, 30);
+    assert_eq!(cpu.cycles(), 42);
+    assert_eq!(cpu.run_budgeted(&mut mem, 10), 12);
+    assert_eq!(cpu.cycle_debt(), 2);
+
+    cpu.reset(&mut mem);
+    assert_eq!(cpu.cycle_debt(), 0);
+    assert_eq!(cpu.run_budgeted(&mut mem, 0), 0);
+    cpu.set_halted(true);
+    assert_eq!(cpu.run_budgeted(&mut mem, 10), 0);
+    assert_eq!(cpu.cycle_debt(), 0);
+}
+
+// ---- run_until ----
+
+#[test]
+fn run_until_waits_for_memory_flag() {
+    // 0400: LDA #$FF / STA $2000 / BRA *
+    let (mut cpu, mut mem) = setup(&[0x86, 0xFF, 0xB7, 0x20, 0x00, 0x20, 0xFE], 0x0400);
+    assert!(cpu.run_until(&mut mem, 100, |_, mem| mem.read(0x2000) == 0xFF));
+    assert_eq!(cpu.registers().pc, 0x0405);
+    assert_eq!(cpu.cycles(), 7);
+}
+
+#[test]
+fn run_until_true_at_start_executes_nothing() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400);
+    assert!(cpu.run_until(&mut mem, 100, |cpu, _| cpu.registers().pc == 0x0400));
+    assert_eq!(cpu.cycles(), 0);
+}
+
+#[test]
+fn run_until_stops_when_halted_or_out_of_cycles() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400);
+    cpu.set_halted(true);
+    assert!(!cpu.run_until(&mut mem, 100, |_, _| false));
+    assert_eq!(cpu.cycles(), 0);
+
+    // 0400: BRA *
+    let (mut cpu, mut mem) = setup(&[0x20, 0xFE], 0x0400);
+    assert!(!cpu.run_until(&mut mem, 10, |_, _| false));
+    assert_eq!(cpu.cycles(), 12);
+}
+
+// ---- ControlHandle ----
+
+#[test]
+fn control_handle_pauses_every_run_loop() {
+    // 0400: BRA *
+    let (mut cpu, mut mem) = setup(&[0x20, 0xFE], 0x0400);
+    let handle = cpu.control_handle();
+    assert!(!cpu.paused());
+    handle.pause();
+    assert!(cpu.paused());
+    assert!(cpu.control_handle().is_paused());
+
+    assert_eq!(cpu.run(&mut mem, 100), 0);
+    assert_eq!(cpu.run_fast(&mut mem, 100), 0);
+    assert_eq!(cpu.run_budgeted(&mut mem, 100), 0);
+    assert_eq!(cpu.step_n(&mut mem, 10), 0);
+    assert!(!cpu.run_until(&mut mem, 100, |_, _| false));
+    assert_eq!(cpu.run_until_stop(&mut mem, 100), StopReason::Paused);
+    cpu.set_block_cache(true);
+    assert_eq!(cpu.run_fast(&mut mem, 100), 0);
+    assert_eq!(cpu.step(&mut mem), 3);
+
+    handle.resume();
+    assert_eq!(cpu.run(&mut mem, 9), 9);
+}
+
+#[test]
+fn control_handle_pauses_from_another_thread() {
+    // 0400: BRA *
+    let (mut cpu, mut mem) = setup(&[0x20, 0xFE], 0x0400);
+    let handle = cpu.control_handle();
+    let pauser = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        handle.pause();
+    });
+    let cycles = cpu.run(&mut mem, u64::MAX / 2);
+    pauser.join().unwrap();
+    assert_eq!(cycles % 3, 0);
+    assert!(cpu.paused());
+}
+
+// ---- InterruptHandle ----
+
+#[test]
+fn interrupt_handle_drives_irq_level() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    let handle = cpu.interrupt_handle();
+    handle.set_irq(true);
+    assert_eq!(handle.lines(), BusSignals::IRQ);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert!(cpu.registers().cc.irq_inhibit());
+
+    // Released through the handle, the line is released on the CPU.
+    handle.set_irq(false);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0400);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0401);
+}
+
+#[test]
+fn interrupt_handle_is_wired_or_with_set_irq() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    let handle = cpu.interrupt_handle();
+    cpu.set_irq(true);
+    handle.set_irq(true);
+    handle.set_irq(false);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0500);
+
+    let (mut cpu, mut mem) = setup_irq_test();
+    let handle = cpu.interrupt_handle();
+    handle.set_firq(true);
+    cpu.set_firq(false);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0600);
+}
+
+#[test]
+fn interrupt_handle_nmi_is_taken_once() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    mem.write_bytes(0xFFFC, &[0x07, 0x00]);
+    mem.write_bytes(0x0700, &[0x3B]); // RTI
+    let handle = cpu.interrupt_handle();
+    handle.nmi();
+    assert_eq!(handle.lines(), BusSignals::NMI);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0700);
+    assert!(handle.lines().is_empty());
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().pc, 0x0401);
+}
+
+#[test]
+fn interrupt_handle_is_sampled_by_run_fast() {
+    // 0400: ANDCC #$EF / BRA *
+    let (mut cpu, mut mem) = setup(&[0x1C, 0xEF, 0x20, 0xFE], 0x0400);
+    cpu.registers_mut().s = 0x0800;
+    mem.write_bytes(0xFFF8, &[0x05, 0x00]);
+    mem.write_bytes(0x0500, &[0x20, 0xFE]); // BRA *
+    cpu.run_fast(&mut mem, 20);
+    let handle = cpu.interrupt_handle();
+    std::thread::spawn(move || handle.set_irq(true))
+        .join()
+        .unwrap();
+    cpu.run_fast(&mut mem, 40);
+    assert_eq!(cpu.registers().pc, 0x0500);
+
+    let (mut cpu, mut mem) = setup(&[0x1C, 0xEF, 0x20, 0xFE], 0x0400);
+    cpu.registers_mut().s = 0x0800;
+    mem.write_bytes(0xFFF8, &[0x05, 0x00]);
+    mem.write_bytes(0x0500, &[0x20, 0xFE]);
+    cpu.set_block_cache(true);
+    cpu.run_fast(&mut mem, 20);
+    cpu.interrupt_handle().set_irq(true);
+    cpu.run_fast(&mut mem, 40);
+    assert_eq!(cpu.registers().pc, 0x0500);
+}
+
+// ---- run_until_stop ----
+
+#[test]
+fn run_until_stop_reports_breakpoint_then_runs_past() {
+    // 0400: NOP / BRA 0400
+    let (mut cpu, mut mem) = setup(&[0x12, 0x20, 0xFD], 0x0400);
+    cpu.add_breakpoint(0x0401);
+
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Breakpoint { pc: 0x0401 }
+    );
+    assert_eq!(cpu.cycles(), 2);
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Breakpoint { pc: 0x0401 }
+    );
+    assert_eq!(cpu.cycles(), 7);
+    cpu.clear_breakpoints();
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 10),
+        StopReason::CycleBudgetExhausted
+    );
+    assert!(cpu.cycles() >= 17);
+}
+
+#[test]
+fn run_until_stop_reports_watchpoint_write() {
+    // 0400: LDD #$1234 / STD $1FFF / STA $3000 / BRA *
+    let (mut cpu, mut mem) = setup(
+        &[
+            0xCC, 0x12, 0x34, 0xFD, 0x1F, 0xFF, 0xB7, 0x30, 0x00, 0x20, 0xFE,
+        ],
+        0x0400,
+    );
+    cpu.add_watchpoint(0x2000);
+    cpu.add_watchpoint(0x3000);
+    assert_eq!(cpu.watchpoints().collect::<Vec<_>>(), [0x2000, 0x3000]);
+
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Watchpoint {
+            pc: 0x0403,
+            addr: 0x2000
+        }
+    );
+    assert_eq!(mem.read(0x2000), 0x34);
+    assert!(cpu.remove_watchpoint(0x3000));
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::CycleBudgetExhausted
+    );
+    assert_eq!(cpu.watchpoints().collect::<Vec<_>>(), [0x2000]);
+}
+
+#[test]
+fn run_until_stop_reports_illegal_opcode_each_time() {
+    // 0400: 0x10 0x00 (illegal) / BRA 0400
+    let (mut cpu, mut mem) = setup(&[0x10, 0x00, 0x20, 0xFC], 0x0400);
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Illegal { pc: 0x0400 }
+    );
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Illegal { pc: 0x0400 }
+    );
+    assert!(cpu.illegal());
+
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 100),
+        StopReason::Illegal { pc: 0x0400 }
+    );
+    assert_eq!(cpu.run_until_stop(&mut mem, 100), StopReason::Halted);
+}
+
+#[test]
+fn run_until_stop_reports_waiting_states() {
+    // 0400: CWAI #$EF
+    let (mut cpu, mut mem) = setup(&[0x3C, 0xEF], 0x0400);
+    cpu.registers_mut().s = 0x0800;
+    assert_eq!(cpu.run_until_stop(&mut mem, 100), StopReason::CwaiWaiting);
+    let cycles = cpu.cycles();
+    assert_eq!(cpu.run_until_stop(&mut mem, 100), StopReason::CwaiWaiting);
+    assert_eq!(cpu.cycles(), cycles);
+
+    // 0400: SYNC
+    let (mut cpu, mut mem) = setup(&[0x13, 0x12], 0x0400);
+    assert_eq!(cpu.run_until_stop(&mut mem, 100), StopReason::SyncWaiting);
+    cpu.set_irq(true);
+    assert_eq!(
+        cpu.run_until_stop(&mut mem, 10),
+        StopReason::CycleBudgetExhausted
+    );
+}
+
+// ---- Stack monitor ----
+
+#[test]
+fn stack_monitor_traps_push_below_range() {
+    // PSHS D / PSHS D: the second push breaks the bounds at its first byte
+    let (mut cpu, mut mem) = setup(&[0x34, 0x06, 0x34, 0x06], 0x0400);
+    cpu.registers_mut().s = 0x7F02;
+    cpu.set_stack_monitor(Some(StackMonitor {
+        s: Some(0x7F00..=0x7FFF),
+        u: None,
+        action: StackAction::Trap,
+    }));
+
+    assert!(cpu.try_step(&mut mem).is_ok());
+    let err = cpu.try_step(&mut mem).unwrap_err();
+    assert_eq!(
+        err,
+        CpuError::StackViolation(StackViolation {
+            stack: Stack::S,
+            access: StackAccess::Push,
+            addr: 0x7EFF,
+            collision: false,
+        })
+    );
+    assert_eq!(err.to_string(), "S stack push to 7EFF out of bounds");
+    assert_eq!(cpu.registers().s, 0x7EFE);
+    assert!(!cpu.halted());
+}
+
+#[test]
+fn stack_monitor_reports_collision_and_pull_past_top() {
+    // PSHS D / PULU D / PULU D, reported byte by byte
+    let (mut cpu, mut mem) = setup(&[0x34, 0x06, 0x37, 0x06, 0x37, 0x06], 0x0400);
+    cpu.registers_mut().s = 0x7080;
+    cpu.registers_mut().u = 0x707E;
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = std::sync::Arc::clone(&seen);
+    cpu.set_stack_monitor(Some(StackMonitor {
+        s: Some(0x7000..=0x70FF),
+        u: Some(0x7000..=0x707F),
+        action: StackAction::Callback(Box::new(move |v, _| {
+            log.lock()
+                .unwrap()
+                .push((v.stack, v.access, v.addr, v.collision))
+        })),
+    }));
+
+    for _ in 0..3 {
+        cpu.step(&mut mem);
+    }
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        [
+            (Stack::S, StackAccess::Push, 0x707F, true),
+            (Stack::S, StackAccess::Push, 0x707E, true),
+            (Stack::U, StackAccess::Pull, 0x7080, false),
+            (Stack::U, StackAccess::Pull, 0x7081, false),
+        ]
+    );
+}
+
+#[test]
+fn stack_monitor_ignores_accesses_in_bounds() {
+    // BSR +0 / PSHS D / PULS D
+    let (mut cpu, mut mem) = setup(&[0x8D, 0x00, 0x34, 0x06, 0x35, 0x06], 0x0400);
+    cpu.registers_mut().s = 0x7F00;
+    cpu.registers_mut().u = 0x6F00;
+    cpu.set_stack_monitor(Some(StackMonitor {
+        s: Some(0x7E00..=0x7EFF),
+        u: Some(0x6E00..=0x6EFF),
+        action: StackAction::Halt,
+    }));
+    for _ in 0..3 {
+        cpu.step(&mut mem);
+    }
+    assert!(!cpu.halted());
+    assert_eq!(cpu.registers().s, 0x7EFE);
+}
+
+// ---- Reset register state ----
+
+#[test]
+fn reset_zeroes_undefined_registers_by_default() {
+    let (cpu, _) = setup(&[0x12], 0x0400);
+    let reg = cpu.registers();
+    assert_eq!((reg.d, reg.x, reg.y, reg.u, reg.s), (0, 0, 0, 0, 0));
+    assert_eq!(reg.cc.to_byte(), CC_I | CC_F);
+}
+
+#[test]
+fn reset_state_fixed_fills_undefined_registers() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400);
+    let mut fixed = Registers::new();
+    fixed.d = 0x1122;
+    fixed.x = 0x3344;
+    fixed.y = 0x5566;
+    fixed.u = 0x7788;
+    fixed.s = 0x99AA;
+    fixed.dp = 0x12;
+    fixed.pc = 0x1234;
+    fixed.cc = ConditionCodes::from_byte(!(CC_I | CC_F));
+    cpu.set_reset_state(ResetState::Fixed(fixed));
+    cpu.reset(&mut mem);
+
+    let reg = cpu.registers();
+    assert_eq!(
+        (reg.d, reg.x, reg.y, reg.u, reg.s),
+        (0x1122, 0x3344, 0x5566, 0x7788, 0x99AA)
+    );
+    assert_eq!(reg.dp, 0);
+    assert_eq!(reg.pc, 0x0400);
+    assert_eq!(reg.cc.to_byte(), 0xFF);
+}
+
+#[test]
+fn reset_state_random_is_reproducible_and_advances() {
+    let (mut a, mut mem) = setup(&[0x12], 0x0400);
+    let mut b = Cpu::new();
+    a.set_reset_state(ResetState::Random(SeedRng::new(42)));
+    b.set_reset_state(ResetState::Random(SeedRng::new(42)));
+    a.reset(&mut mem);
+    b.reset(&mut mem);
+    assert_eq!(a.registers(), b.registers());
+    assert!(a.registers().cc.irq_inhibit() && a.registers().cc.firq_inhibit());
+    assert_eq!(a.registers().dp, 0);
+
+    let first = *a.registers();
+    a.reset(&mut mem);
+    assert_ne!(*a.registers(), first);
+}
+
+// ---- Half-carry after subtract-class instructions ----
+
+#[test]
+fn subtract_leaves_half_carry_by_default() {
+    // ORCC #$20 / LDA #$10 / SUBA #$01 / ANDCC #$DF / SUBA #$00
+    let (mut cpu, mut mem) = setup(
+        &[0x1A, 0x20, 0x86, 0x10, 0x80, 0x01, 0x1C, 0xDF, 0x80, 0x00],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert!(cpu.registers().cc.half_carry());
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert!(!cpu.registers().cc.half_carry());
+}
+
+#[test]
+fn subtract_sets_half_borrow_on_hardware() {
+    // Each row: program, H expected after its last instruction.
+    let cases: [(&[u8], bool); 6] = [
+        (&[0x86, 0x10, 0x80, 0x01], true),  // LDA #$10 / SUBA #$01
+        (&[0x86, 0x1F, 0x80, 0x01], false), // LDA #$1F / SUBA #$01
+        (&[0x86, 0x20, 0x81, 0x01], true),  // LDA #$20 / CMPA #$01
+        (&[0x1A, 0x01, 0x86, 0x10, 0x82, 0x00], true), // ORCC #1 / LDA #$10 / SBCA #$00
+        (&[0x86, 0x01, 0x40, 0x12], true),  // LDA #$01 / NEGA / NOP
+        (&[0x86, 0x10, 0x40, 0x12], false), // LDA #$10 / NEGA / NOP
+    ];
+    for (program, expected) in cases {
+        let (mut cpu, mut mem) = setup(program, 0x0400);
+        cpu.set_half_carry_quirks(HalfCarryQuirks::Hardware);
+        while cpu.registers().pc < 0x0400 + program.len() as u16 {
+            cpu.step(&mut mem);
+        }
+        assert_eq!(cpu.registers().cc.half_carry(), expected, "{program:02X?}");
+    }
+}
+
+// ---- Undocumented SWI2 (0x10 0x3E): does not set E before pushing ----
+
+#[test]
+fn swi2_undoc_page1_no_e_flag_in_frame() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x1C, 0x7F, // ANDCC #$7F → clear E
+            0x10, 0x3E, // SWI2 undocumented
+        ],
+        0x0400,
+    );
+    cpu.registers_mut().s = 0x8000;
+    mem.mem[0xFFF4] = 0x20;
+    mem.mem[0xFFF5] = 0x00; // SWI2 vector → 0x2000
+    cpu.step(&mut mem); // ANDCC — E clear
+    cpu.step(&mut mem); // SWI2 undoc
+    assert_eq!(cpu.registers().pc, 0x2000);
+    assert_eq!(cpu.registers().s, 0x8000 - 12);
+    let saved_cc = mem.mem[cpu.registers().s as usize];
+    assert_eq!(saved_cc & 0x80, 0x00); // E was NOT set before push
+}
+
+// ---- XFIRQ (0x11 0x3E): push state, jump via FIRQ vector, no flag changes ----
+
+#[test]
+fn xfirq_undoc_jumps_via_firq_vector_no_flag_changes() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x1C, 0xAF, // ANDCC #$AF → clear F and I
+            0x11, 0x3E, // XFIRQ
+        ],
+        0x0400,
+    );
+    cpu.registers_mut().s = 0x8000;
+    mem.mem[0xFFF6] = 0x30;
+    mem.mem[0xFFF7] = 0x00; // FIRQ vector → 0x3000
+    cpu.step(&mut mem); // ANDCC
+    assert!(!cpu.registers().cc.firq_inhibit());
+    assert!(!cpu.registers().cc.irq_inhibit());
+    cpu.step(&mut mem); // XFIRQ
+    assert_eq!(cpu.registers().pc, 0x3000);
+    assert!(!cpu.registers().cc.firq_inhibit()); // F still clear
+    assert!(!cpu.registers().cc.irq_inhibit()); // I still clear
+    assert_eq!(cpu.registers().s, 0x8000 - 12); // full state pushed
+}
+
+// ---- XNC: NEG if C=0, COM if C=1 ----
+
+#[test]
+fn xnc_a_carry_clear_acts_as_nega() {
+    // 0x42: XNC A — C=0 → behaves like NEGA
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x86, 0x05, // LDA #$05
+            0x1C, 0xFE, // ANDCC #$FE → clear C
+            0x42, // XNC A
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0xFB); // neg(0x05) = 0xFB (two's complement)
+    assert!(cpu.registers().cc.carry()); // neg of non-zero sets C
+}
+
+#[test]
+fn xnc_a_carry_set_acts_as_coma() {
+    // 0x42: XNC A — C=1 → behaves like COMA
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x86, 0xAA, // LDA #$AA
+            0x1A, 0x01, // ORCC #$01 → set C
+            0x42, // XNC A
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0x55); // ~$AA = $55
+    assert!(cpu.registers().cc.carry()); // COM always sets C
+}
+
+#[test]
+fn xnc_b_carry_clear_acts_as_negb() {
+    // 0x52: XNC B — C=0 → behaves like NEGB
+    let (mut cpu, mut mem) = setup(
+        &[
+            0xC6, 0x01, // LDB #$01
+            0x1C, 0xFE, // ANDCC #$FE → clear C
+            0x52, // XNC B
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().b(), 0xFF);
+}
+
+#[test]
+fn xnc_direct_carry_set_acts_as_com() {
+    // 0x02: XNC direct — C=1 → behaves like COM
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x1A, 0x01, // ORCC #$01 → set C
+            0x02, 0x50, // XNC <$50
+        ],
+        0x0400,
+    );
+    mem.mem[0x0050] = 0x0F;
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(mem.mem[0x0050], 0xF0); // ~0x0F = 0xF0
+}
+
+// ---- XDEC: DEC but C reflects (original != 0) ----
+
+#[test]
+fn xdec_a_nonzero_sets_carry() {
+    // 0x4B: XDEC A — operand=3 → result=2, C=1 (3 != 0)
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x86, 0x03, // LDA #$03
+            0x1C, 0xFE, // ANDCC #$FE → clear C
+            0x4B, // XDEC A
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0x02);
+    assert!(cpu.registers().cc.carry()); // 3 != 0 → C set
+}
+
+#[test]
+fn xdec_a_zero_clears_carry() {
+    // 0x4B: XDEC A — operand=0 → result=0xFF, C=0 (0 == 0)
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x86, 0x00, // LDA #$00
+            0x1A, 0x01, // ORCC #$01 → set C (to verify it gets cleared)
+            0x4B, // XDEC A
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0xFF);
+    assert!(!cpu.registers().cc.carry()); // 0 == 0 → C cleared
+    assert!(cpu.registers().cc.negative()); // 0xFF is negative
+}
+
+#[test]
+fn xdec_b_nonzero_sets_carry() {
+    // 0x5B: XDEC B — operand=0x80 → result=0x7F, V set, C set (0x80 != 0)
+    let (mut cpu, mut mem) = setup(
+        &[
+            0xC6, 0x80, // LDB #$80
+            0x1C, 0xFE, // ANDCC #$FE → clear C
+            0x5B, // XDEC B
+        ],
+        0x0400,
+    );
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().b(), 0x7F);
+    assert!(cpu.registers().cc.overflow()); // 0x80 → V set (same as DEC)
+    assert!(cpu.registers().cc.carry()); // 0x80 != 0 → C set
+}
+
+#[test]
+fn xdec_direct_zero_clears_carry() {
+    // 0x0B: XDEC direct — operand=0 → carry cleared
     let (mut cpu, mut mem) = setup(
         &[
             0x1A, 0x01, // ORCC #$01 → set C
@@ -1565,3 +2510,578 @@ fn apply_signals_nmi_edge_detection() {
     cpu.step(&mut mem); // must NOT re-trigger NMI
     assert_eq!(cpu.registers().pc, 0x0401, "held NMI must not re-trigger");
 }
+
+// ---- Batch execution ----
+
+/// Countdown loop: LDB #$40 / loop: DECB / BNE loop / XHCF
+const COUNTDOWN: &[u8] = &[0xC6, 0x40, 0x5A, 0x26, 0xFD, 0x14];
+
+#[test]
+fn run_fast_matches_run() {
+    let (mut slow, mut slow_mem) = setup(COUNTDOWN, 0x0400);
+    let (mut fast, mut fast_mem) = setup(COUNTDOWN, 0x0400);
+
+    let slow_cycles = slow.run(&mut slow_mem, 10_000);
+    let fast_cycles = fast.run_fast(&mut fast_mem, 10_000);
+
+    assert!(fast.halted());
+    assert_eq!(fast_cycles, slow_cycles);
+    assert_eq!(fast.registers().pc, slow.registers().pc);
+    assert_eq!(fast.registers().b(), 0);
+}
+
+#[test]
+fn run_fast_services_pending_interrupts() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    mem.write_bytes(0x0400, &[0x20, 0xFE]); // BRA *
+    cpu.set_irq(true);
+    cpu.run_fast(&mut mem, 1);
+    assert_eq!(cpu.registers().pc, 0x0500);
+}
+
+#[test]
+fn run_dyn_matches_run() {
+    let (mut generic, mut generic_mem) = setup(COUNTDOWN, 0x0400);
+    let (mut dynamic, dyn_mem) = setup(COUNTDOWN, 0x0400);
+    let mut boxed: Box<dyn Memory> = Box::new(dyn_mem);
+
+    let generic_cycles = generic.run(&mut generic_mem, 10_000);
+    let dyn_cycles = dynamic.run_dyn(boxed.as_mut(), 10_000);
+
+    assert!(dynamic.halted());
+    assert_eq!(dyn_cycles, generic_cycles);
+    assert_eq!(dynamic.registers().pc, generic.registers().pc);
+}
+
+#[test]
+fn boxed_dyn_memory_is_memory() {
+    let (mut cpu, mem) = setup(&[0x86, 0x42, 0xB7, 0x20, 0x00], 0x0400); // LDA #$42 / STA $2000
+    let mut boxed: Box<dyn Memory> = Box::new(mem);
+    cpu.step(&mut boxed);
+    cpu.step(&mut boxed);
+    assert_eq!(boxed.read(0x2000), 0x42);
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn instruction_hook_sees_each_instruction() {
+    use std::sync::{Arc, Mutex};
+
+    let (mut cpu, mut mem) = setup(&[0x12, 0x12, 0x14], 0x0400); // NOP / NOP / XHCF
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    cpu.set_instruction_hook(Some(Box::new(move |reg, _| {
+        sink.lock().unwrap().push(reg.pc)
+    })));
+    cpu.run(&mut mem, 100);
+    assert_eq!(*seen.lock().unwrap(), [0x0400, 0x0401, 0x0402]);
+}
+
+#[test]
+fn dma_breq_steals_cycles_with_refresh() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400); // NOP
+    cpu.set_dma_breq(true);
+    let mut granted = 0;
+    for _ in 0..15 {
+        assert_eq!(cpu.step(&mut mem), 1);
+        granted += cpu.bus_granted() as u32;
+    }
+    assert_eq!(granted, 14);
+    assert!(!cpu.bus_granted());
+    assert_eq!(cpu.registers().pc, 0x0400);
+
+    cpu.apply_signals(BusSignals::default(), BusSignals::DMA_BREQ);
+    cpu.step(&mut mem);
+    assert!(!cpu.bus_granted());
+    assert_eq!(cpu.registers().pc, 0x0401);
+}
+
+#[test]
+fn clock_rate_converts_cycles_and_durations() {
+    let (mut cpu, mut mem) = setup(&[0x12], 0x0400); // NOP
+    assert_eq!(cpu.clock_hz(), Cpu::DEFAULT_CLOCK_HZ);
+    assert_eq!(cpu.cycles_to_duration(1), Duration::from_micros(1));
+
+    cpu.set_clock_hz(894_886);
+    cpu.reset(&mut mem);
+    assert_eq!(cpu.clock_hz(), 894_886);
+    assert_eq!(cpu.duration_to_cycles(Duration::from_secs(2)), 1_789_772);
+    assert_eq!(cpu.cycles_to_duration(894_886), Duration::from_secs(1));
+    // Rounds down in both directions.
+    assert_eq!(cpu.cycles_to_duration(1), Duration::from_nanos(1_117));
+    assert_eq!(cpu.duration_to_cycles(Duration::from_nanos(1_117)), 0);
+    assert_eq!(cpu.duration_to_cycles(Duration::MAX), u64::MAX);
+}
+
+/// KONAMI-1 opcode scrambling: the XOR mask depends on address bits 1 and 3.
+fn konami1_mask(addr: u16) -> u8 {
+    let high = if addr & 0x02 != 0 { 0x80 } else { 0x20 };
+    let low = if addr & 0x08 != 0 { 0x08 } else { 0x02 };
+    high | low
+}
+
+/// LDX #$1234 / LDY #$5678 / BRA * at 0x0400 with scrambled opcodes.
+fn konami1_program() -> [u8; 9] {
+    let mut program = [0x8E, 0x12, 0x34, 0x10, 0x8E, 0x56, 0x78, 0x20, 0xFE];
+    for offset in [0, 3, 4, 7] {
+        program[offset] ^= konami1_mask(0x0400 + offset as u16);
+    }
+    program
+}
+
+#[test]
+fn opcode_translator_decodes_opcode_fetches_only() {
+    let (mut cpu, mut mem) = setup(&konami1_program(), 0x0400);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = std::sync::Arc::clone(&seen);
+    cpu.set_opcode_translator(Some(Box::new(move |pc, byte, fetch| {
+        log.lock().unwrap().push((pc, fetch));
+        byte ^ konami1_mask(pc)
+    })));
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().x, 0x1234);
+    assert_eq!(cpu.registers().y, 0x5678);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (0x0400, OpcodeFetch::First),
+            (0x0403, OpcodeFetch::First),
+            (0x0404, OpcodeFetch::Page1),
+        ]
+    );
+}
+
+#[test]
+fn opcode_translator_applies_to_recorded_blocks() {
+    let (mut cpu, mut mem) = setup(&konami1_program(), 0x0400);
+    cpu.set_block_cache(true);
+    cpu.set_opcode_translator(Some(Box::new(|pc, byte, _| byte ^ konami1_mask(pc))));
+    cpu.run_fast(&mut mem, 100);
+    assert_eq!(cpu.registers().x, 0x1234);
+    assert_eq!(cpu.registers().y, 0x5678);
+    assert_eq!(cpu.registers().pc, 0x0407);
+}
+
+#[test]
+fn opcode_handler_replaces_one_opcode_on_one_cpu() {
+    // LDY #$1234 / LDX #$5678 / NOP
+    let program = [0x10, 0x8E, 0x12, 0x34, 0x8E, 0x56, 0x78, 0x12];
+    let (mut cpu, mut mem) = setup(&program, 0x0400);
+    // LDY immediate loads the operand byte-swapped and takes 3 cycles.
+    cpu.set_opcode_handler(
+        0x108E,
+        Some(Box::new(|reg, mem| {
+            reg.y = u16::from_le_bytes([mem.read(reg.pc), mem.read(reg.pc.wrapping_add(1))]);
+            reg.pc = reg.pc.wrapping_add(2);
+            3
+        })),
+    );
+    assert_eq!(cpu.step(&mut mem), 3);
+    assert_eq!(cpu.registers().y, 0x3412);
+    assert_eq!(cpu.registers().pc, 0x0404);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().x, 0x5678);
+
+    let (mut other, mut mem) = setup(&program, 0x0400);
+    other.step(&mut mem);
+    assert_eq!(other.registers().y, 0x1234);
+
+    cpu.set_opcode_handler(0x108E, None);
+    cpu.registers_mut().pc = 0x0400;
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().y, 0x1234);
+}
+
+#[test]
+fn opcode_handler_also_runs_for_prefixed_alias() {
+    // LDA #$42 after a page 1 prefix, which the 6809 ignores
+    let (mut cpu, mut mem) = setup(&[0x10, 0x86, 0x42], 0x0400);
+    cpu.set_undocumented_opcodes(true);
+    cpu.set_opcode_handler(
+        0x86,
+        Some(Box::new(|reg, mem| {
+            reg.set_a(!mem.read(reg.pc));
+            reg.pc = reg.pc.wrapping_add(1);
+            2
+        })),
+    );
+    assert_eq!(cpu.step(&mut mem), 3);
+    assert_eq!(cpu.registers().a(), 0xBD);
+    assert_eq!(cpu.registers().pc, 0x0403);
+}
+
+#[test]
+fn opcode_handler_can_branch_inside_recorded_blocks() {
+    // loop: NOP / INCA / NOP / BRA loop, with INCA patched to fall through
+    // once, then jump to XHCF at $0500
+    let (mut cpu, mut mem) = setup(&[0x12, 0x4C, 0x12, 0x20, 0xFB], 0x0400);
+    mem.mem[0x0500] = 0x14;
+    cpu.set_block_cache(true);
+    let mut calls = 0;
+    cpu.set_opcode_handler(
+        0x4C,
+        Some(Box::new(move |reg, _| {
+            calls += 1;
+            if calls > 1 {
+                reg.pc = 0x0500;
+            }
+            2
+        })),
+    );
+    cpu.run_fast(&mut mem, 100);
+    assert!(cpu.halted());
+    assert_eq!(cpu.registers().pc, 0x0501);
+}
+
+#[test]
+#[should_panic(expected = "is not an opcode on page 0, 1 or 2")]
+fn opcode_handler_rejects_page_prefix() {
+    Cpu::new().set_opcode_handler(0x10, None);
+}
+
+#[test]
+fn config_gathers_the_accuracy_options() {
+    let mut cpu = Cpu::new();
+    assert_eq!(cpu.config(), CpuConfig::default());
+
+    cpu.set_undocumented_opcodes(true);
+    cpu.set_transfer_quirks(TransferQuirks::Hardware);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::TrapToVector(0xFFF0));
+    cpu.set_clock_hz(1_789_772);
+    let config = cpu.config();
+    assert!(config.undocumented_opcodes);
+    assert_eq!(config.transfer_quirks, TransferQuirks::Hardware);
+    assert_eq!(
+        config.illegal_opcodes,
+        IllegalOpcodeMode::TrapToVector(0xFFF0)
+    );
+    assert_eq!(config.clock_hz, 1_789_772);
+
+    let copy = Cpu::with_config(config);
+    assert_eq!(copy.config(), config);
+}
+
+#[test]
+fn config_keeps_an_installed_illegal_opcode_callback() {
+    let (mut cpu, mut mem) = setup(&[0x87], 0x0400); // illegal STA immediate
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Callback(Box::new(|reg, _| {
+        reg.x = 0xBEEF;
+    })));
+    let config = cpu.config();
+    assert_eq!(config.illegal_opcodes, IllegalOpcodeMode::Callback);
+    cpu.set_config(config);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().x, 0xBEEF);
+
+    // Without a callback to keep, the policy falls back to the default.
+    let fresh = Cpu::with_config(config);
+    assert_eq!(fresh.config().illegal_opcodes, IllegalOpcodeMode::Hardware);
+}
+
+#[test]
+fn save_state_follows_the_documented_layout() {
+    let mut cpu = Cpu::new();
+    {
+        let mut regs = cpu.registers_mut();
+        regs.d = 0x1234;
+        regs.x = 0x5678;
+        regs.y = 0x9ABC;
+        regs.u = 0xDEF0;
+        regs.s = 0x1357;
+        regs.pc = 0x2468;
+        regs.dp = 0xAA;
+        regs.cc = ConditionCodes::from_byte(0x55);
+    }
+    cpu.set_irq(true);
+    let state = cpu.save_state();
+
+    let mut expected = b"M6809CP\x01".to_vec();
+    expected.push(0);
+    expected.extend_from_slice(&[
+        0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x13, 0x57, 0x24, 0x68, 0xAA, 0x55,
+    ]);
+    expected.extend_from_slice(&[0; 8]); // cycles
+    expected.extend_from_slice(&[0, 0, 1, 0, 0, 0, 0]); // NMI armed by the S write
+    expected.extend_from_slice(&[0x04, 0]); // IRQ line, no DMA
+    assert_eq!(state, expected);
+
+    let mut copy = Cpu::new();
+    copy.load_state(&state).unwrap();
+    assert_eq!(copy.save_state(), state);
+}
+
+#[test]
+fn load_state_rejects_foreign_data() {
+    let mut cpu = Cpu::new();
+    let mut state = cpu.save_state();
+    assert_eq!(cpu.load_state(b"M6809ST\x01"), Err(StateError::BadHeader));
+    assert_eq!(cpu.load_state(&state[..30]), Err(StateError::Truncated));
+    state[8] = 2;
+    assert_eq!(
+        cpu.load_state(&state),
+        Err(StateError::Mismatch("cpu variant"))
+    );
+    state[8] = 0;
+    state.push(0);
+    assert_eq!(
+        cpu.load_state(&state),
+        Err(StateError::Mismatch("trailing data"))
+    );
+}
+
+#[test]
+fn state_round_trips_and_matches_save_state() {
+    let (mut cpu, mut mem) = setup(&[0x86, 0x42, 0x1A, 0x01], 0x0400); // LDA #$42; ORCC #$01
+    cpu.registers_mut().s = 0x0800;
+    cpu.set_firq(true);
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    let state = cpu.state();
+    assert_eq!(state.registers.a(), 0x42);
+    assert_eq!(state.cycles, 5);
+    assert!(state.nmi_armed);
+    assert_eq!(state.int_lines, BusSignals::FIRQ);
+
+    let mut copy = Cpu::new();
+    copy.set_state(&state);
+    assert_eq!(copy.state(), state);
+    assert_eq!(copy.save_state(), cpu.save_state());
+    assert!(state.differences(&copy.state()).is_empty());
+}
+
+#[test]
+fn state_differences_name_each_field() {
+    let cpu = Cpu::new();
+    let mut expected = cpu.state();
+    expected.registers.set_b(0x12);
+    expected.hd6309.w = 0x3456;
+    expected.cycles = 7;
+    expected.halted = true;
+    expected.int_lines = BusSignals::NMI;
+    assert_eq!(
+        cpu.state().differences(&expected),
+        [
+            "B: expected 12, found 00",
+            "W: expected 3456, found 0000",
+            "cycles: expected 7, found 0",
+            "halted: expected true, found false",
+            "int_lines: expected BusSignals(NMI), found BusSignals(empty)",
+        ]
+    );
+}
+
+/// Run `program` at 0x0400 for `steps` steps with a code write hook, with
+/// or without the decode cache, and return what the hook saw.
+fn code_writes(program: &[u8], steps: usize, decode_cache: bool) -> (Cpu, Vec<CodeWrite>) {
+    let (mut cpu, mut mem) = setup(program, 0x0400);
+    cpu.set_decode_cache(decode_cache);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = std::sync::Arc::clone(&seen);
+    cpu.set_code_write_hook(Some(Box::new(move |write| log.lock().unwrap().push(write))));
+    for _ in 0..steps {
+        cpu.step(&mut mem);
+    }
+    let seen = seen.lock().unwrap().clone();
+    (cpu, seen)
+}
+
+#[test]
+fn code_write_hook_reports_patched_instructions() {
+    // loop: LDA #$01; STA $0500; INC $0401; BRA loop
+    let program = [0x86, 0x01, 0xB7, 0x05, 0x00, 0x7C, 0x04, 0x01, 0x20, 0xF6];
+    for decode_cache in [false, true] {
+        let (cpu, seen) = code_writes(&program, 8, decode_cache);
+        // The data store is not reported; each INC of the operand is, and
+        // the patched operand is what the next LDA loads.
+        assert_eq!(
+            seen,
+            [
+                CodeWrite {
+                    pc: 0x0405,
+                    addr: 0x0401,
+                    val: 0x02
+                },
+                CodeWrite {
+                    pc: 0x0405,
+                    addr: 0x0401,
+                    val: 0x03
+                },
+            ]
+        );
+        assert_eq!(cpu.registers().a(), 0x02);
+        assert!(cpu.executed(0x0409));
+        assert!(!cpu.executed(0x040A));
+        assert!(!cpu.executed(0x0500));
+    }
+}
+
+#[test]
+fn code_write_hook_ignores_writes_to_unexecuted_bytes() {
+    // STA $0403 (the next instruction, not yet run); NOP; NOP
+    let (mut cpu, seen) = code_writes(&[0xB7, 0x04, 0x03, 0x12, 0x12], 2, false);
+    assert!(seen.is_empty());
+    assert!(cpu.executed(0x0403));
+
+    cpu.set_code_write_hook(None);
+    assert!(!cpu.executed(0x0400));
+}
+
+// ---- Last instruction ----
+
+#[test]
+fn last_instruction_records_bytes_cycles_and_address() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x10, 0x8E, 0x12, 0x34, // LDY #$1234
+            0x96, 0x20, // LDA <$20
+            0x10, 0x27, 0x00, 0x10, // LBEQ +$10 (not taken)
+            0xAD, 0x9F, 0x10, 0x00, // JSR [$1000]
+        ],
+        0x0400,
+    );
+    mem.write_bytes(0x1000, &[0x05, 0x00]);
+    mem.write_bytes(0x3020, &[0x80]);
+    cpu.registers_mut().s = 0x0800;
+    cpu.registers_mut().dp = 0x30;
+    assert_eq!(cpu.last_instruction(), None);
+    cpu.set_last_instruction(true);
+    assert_eq!(cpu.last_instruction(), None);
+
+    let mut steps = Vec::new();
+    for _ in 0..4 {
+        cpu.step(&mut mem);
+        let last = cpu.last_instruction().unwrap();
+        steps.push((
+            last.pc,
+            last.bytes().to_vec(),
+            last.cycles,
+            last.effective_address,
+        ));
+    }
+    assert_eq!(
+        steps,
+        [
+            (0x0400, vec![0x10, 0x8E, 0x12, 0x34], 4, None),
+            (0x0404, vec![0x96, 0x20], 4, Some(0x3020)),
+            (0x0406, vec![0x10, 0x27, 0x00, 0x10], 5, Some(0x041A)),
+            (0x040A, vec![0xAD, 0x9F, 0x10, 0x00], 12, Some(0x0500)),
+        ]
+    );
+    assert_eq!(cpu.last_instruction().unwrap().length, 4);
+
+    cpu.set_last_instruction(false);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.last_instruction(), None);
+}
+
+#[test]
+fn last_instruction_is_none_after_interrupt_entry() {
+    let (mut cpu, mut mem) = setup_irq_test();
+    cpu.set_last_instruction(true);
+    cpu.step(&mut mem); // NOP
+    assert_eq!(cpu.last_instruction().unwrap().bytes(), [0x12]);
+
+    cpu.set_irq(true);
+    cpu.step(&mut mem); // IRQ entry
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.last_instruction(), None);
+
+    cpu.set_irq(false);
+    cpu.step(&mut mem); // RTI
+    let last = cpu.last_instruction().unwrap();
+    assert_eq!((last.pc, last.length, last.cycles), (0x0500, 1, 15));
+}
+
+#[test]
+fn last_instruction_reports_flag_changes() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x86, 0x80, // LDA #$80
+            0x8B, 0x80, // ADDA #$80
+            0x12, // NOP
+        ],
+        0x0400,
+    );
+    cpu.set_last_instruction(true);
+    cpu.registers_mut().cc = ConditionCodes::from_byte(ConditionCodes::Z);
+
+    cpu.step(&mut mem);
+    let flags = cpu.last_instruction().unwrap().flags;
+    assert_eq!(flags.set(), ConditionCodes::N);
+    assert_eq!(flags.cleared(), ConditionCodes::Z);
+
+    cpu.step(&mut mem);
+    let flags = cpu.last_instruction().unwrap().flags;
+    assert_eq!(flags.to_string(), "-N +Z +V +C");
+    assert_eq!(flags.after.to_byte(), 0x07);
+
+    cpu.step(&mut mem);
+    assert!(cpu.last_instruction().unwrap().flags.is_empty());
+}
+
+// ---- Effective address preview ----
+
+#[test]
+fn effective_address_at_each_mode() {
+    let (mut cpu, mut mem) = setup(
+        &[
+            0x97, 0x20, // STA <$20
+            0xB7, 0xFF, 0x20, // STA $FF20
+            0xA7, 0xC3, // STA ,--U
+            0xA6, 0x9F, 0x20, 0x00, // LDA [$2000]
+            0xA6, 0x8C, 0x10, // LDA $10,PCR
+            0x10, 0x26, 0xFF, 0xF0, // LBNE -$10
+            0x86, 0x01, // LDA #$01
+            0x12, // NOP
+            0x1F, 0x89, // TFR A,B
+        ],
+        0x0400,
+    );
+    mem.write_bytes(0x2000, &[0xC0, 0x00]);
+    cpu.registers_mut().dp = 0x12;
+    cpu.registers_mut().u = 0x0800;
+    let before = cpu.state();
+
+    let previews: Vec<_> = [
+        0x0400, 0x0402, 0x0405, 0x0407, 0x040B, 0x040E, 0x0412, 0x0414, 0x0415,
+    ]
+    .into_iter()
+    .map(|addr| cpu.effective_address_at(&mut mem, addr))
+    .collect();
+    assert_eq!(
+        previews,
+        [
+            Some((0x1220, AddressingMode::Direct)),
+            Some((0xFF20, AddressingMode::Extended)),
+            Some((0x07FE, AddressingMode::Indexed)),
+            Some((0xC000, AddressingMode::Indexed)),
+            Some((0x041E, AddressingMode::Indexed)),
+            Some((0x0402, AddressingMode::Relative)),
+            None,
+            None,
+            None,
+        ]
+    );
+    assert_eq!(cpu.state(), before);
+}
+
+#[test]
+fn effective_address_at_matches_execution() {
+    // LDD ,Y++ / STB $80,X / BRA to the start
+    let (mut cpu, mut mem) = setup(&[0xEC, 0xA1, 0xE7, 0x88, 0x80, 0x20, 0xF9], 0x0400);
+    cpu.registers_mut().x = 0x3000;
+    cpu.registers_mut().y = 0x2000;
+    cpu.set_last_instruction(true);
+    for _ in 0..9 {
+        let pc = cpu.registers().pc;
+        let preview = cpu.effective_address_at(&mut mem, pc).map(|(ea, _)| ea);
+        cpu.step(&mut mem);
+        assert_eq!(
+            preview,
+            cpu.last_instruction().unwrap().effective_address,
+            "{pc:04X}"
+        );
+    }
+    assert_eq!(cpu.registers().y, 0x2006);
+}
@@ -0,0 +1,222 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tests for the pre-decoded instruction cache.
+
+use crate::{Cpu, Memory};
+
+/// Flat RAM that counts reads, so tests can see which fetches were served
+/// from the cache.
+struct CountingMem {
+    mem: Box<[u8; 65536]>,
+    reads: usize,
+}
+
+impl CountingMem {
+    fn new(program: &[u8], start: u16) -> Self {
+        let mut mem = Box::new([0u8; 65536]);
+        mem[0xFFFE] = (start >> 8) as u8;
+        mem[0xFFFF] = start as u8;
+        mem[start as usize..start as usize + program.len()].copy_from_slice(program);
+        Self { mem, reads: 0 }
+    }
+}
+
+impl Memory for CountingMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.reads += 1;
+        self.mem[addr as usize]
+    }
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+}
+
+/// Countdown loop: LDB #$10 / loop: DECB / BNE loop / XHCF
+const LOOP: &[u8] = &[0xC6, 0x10, 0x5A, 0x26, 0xFD, 0x14];
+
+fn run_to_halt(cpu: &mut Cpu, mem: &mut CountingMem) {
+    while !cpu.halted() {
+        cpu.step(mem);
+    }
+}
+
+#[test]
+fn cached_run_matches_uncached_run() {
+    let mut mem_a = CountingMem::new(LOOP, 0x0400);
+    let mut plain = Cpu::new();
+    plain.reset(&mut mem_a);
+    run_to_halt(&mut plain, &mut mem_a);
+
+    let mut mem_b = CountingMem::new(LOOP, 0x0400);
+    let mut cached = Cpu::new();
+    cached.set_decode_cache(true);
+    cached.reset(&mut mem_b);
+    run_to_halt(&mut cached, &mut mem_b);
+
+    assert_eq!(cached.registers().pc, plain.registers().pc);
+    assert_eq!(cached.registers().b(), 0);
+    assert_eq!(cached.cycles(), plain.cycles());
+    assert!(mem_b.reads < mem_a.reads);
+}
+
+#[test]
+fn hit_serves_instruction_without_reading_memory() {
+    // BRA * — a one-instruction loop
+    let mut mem = CountingMem::new(&[0x20, 0xFE], 0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    cpu.reset(&mut mem);
+
+    cpu.step(&mut mem); // miss: opcode and offset are read and recorded
+    let reads = mem.reads;
+    cpu.step(&mut mem); // hit
+    assert_eq!(mem.reads, reads);
+    assert_eq!(cpu.registers().pc, 0x0400);
+}
+
+#[test]
+fn cpu_write_invalidates_cached_instruction() {
+    // 0400: LDA #$01
+    // 0402: LDB #$86    ; $86 is the LDA immediate opcode
+    // 0404: STB $0400   ; rewrite opcode byte at 0400 (still LDA)
+    // 0407: LDB #$C6    ; $C6 is LDB immediate
+    // 0409: STB $0400   ; 0400 now reads LDB #$01
+    // 040C: BRA $0400
+    let program = [
+        0x86, 0x01, 0xC6, 0x86, 0xF7, 0x04, 0x00, 0xC6, 0xC6, 0xF7, 0x04, 0x00, 0x20, 0xF2,
+    ];
+    let mut mem = CountingMem::new(&program, 0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    cpu.reset(&mut mem);
+
+    for _ in 0..6 {
+        cpu.step(&mut mem);
+    }
+    assert_eq!(cpu.registers().pc, 0x0400);
+    cpu.registers_mut().set_b(0);
+    cpu.step(&mut mem); // must execute the rewritten LDB #$01
+    assert_eq!(cpu.registers().b(), 0x01);
+}
+
+#[test]
+fn external_write_needs_explicit_invalidation() {
+    let mut mem = CountingMem::new(&[0x86, 0x11, 0x20, 0xFC], 0x0400); // LDA #$11 / BRA 0400
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    cpu.reset(&mut mem);
+
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    mem.mem[0x0401] = 0x22; // host-side patch, not seen by the cache
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0x11);
+
+    cpu.step(&mut mem);
+    cpu.invalidate_decode_cache(0x0401);
+    cpu.step(&mut mem);
+    assert_eq!(cpu.registers().a(), 0x22);
+}
+
+#[test]
+fn disabling_cache_discards_it() {
+    let mut cpu = Cpu::new();
+    assert!(!cpu.decode_cache_enabled());
+    cpu.set_decode_cache(true);
+    assert!(cpu.decode_cache_enabled());
+    cpu.set_decode_cache(false);
+    assert!(!cpu.decode_cache_enabled());
+}
+
+#[test]
+fn run_fast_dispatches_from_cache() {
+    // LDX #$2000 / LDD #$A55A / loop: STD ,X++ / CMPX #$2010 / BNE loop / XHCF
+    let program = [
+        0x8E, 0x20, 0x00, 0xCC, 0xA5, 0x5A, 0xED, 0x81, 0x8C, 0x20, 0x10, 0x26, 0xF9, 0x14,
+    ];
+    let mut mem_a = CountingMem::new(&program, 0x0400);
+    let mut plain = Cpu::new();
+    plain.reset(&mut mem_a);
+    plain.run_fast(&mut mem_a, 10_000);
+
+    let mut mem_b = CountingMem::new(&program, 0x0400);
+    let mut cached = Cpu::new();
+    cached.set_decode_cache(true);
+    cached.reset(&mut mem_b);
+    cached.run_fast(&mut mem_b, 10_000);
+
+    assert!(cached.halted());
+    assert_eq!(cached.registers(), plain.registers());
+    assert_eq!(cached.cycles(), plain.cycles());
+    assert_eq!(mem_b.mem[0x2000..0x2010], mem_a.mem[0x2000..0x2010]);
+    // Only the first pass through the loop fetches its instructions.
+    assert!(mem_b.reads < mem_a.reads / 2);
+}
+
+#[test]
+fn hit_reports_its_opcode_bytes() {
+    // LDY #$1234 / BRA *-4
+    let mut mem = CountingMem::new(&[0x10, 0x8E, 0x12, 0x34, 0x20, 0xFA], 0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    cpu.set_last_instruction(true);
+    cpu.reset(&mut mem);
+
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    let reads = mem.reads;
+    cpu.step(&mut mem); // hit
+    assert_eq!(mem.reads, reads);
+    assert_eq!(cpu.registers().y, 0x1234);
+    let last = cpu.last_instruction().unwrap();
+    assert_eq!(last.pc, 0x0400);
+    assert_eq!(last.bytes(), [0x10, 0x8E, 0x12, 0x34]);
+}
+
+/// Flat RAM with a counter at $FF00 that advances on every read.
+struct CounterMem {
+    mem: Box<[u8; 65536]>,
+    counter: u8,
+}
+
+impl Memory for CounterMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        if addr == 0xFF00 {
+            self.counter = self.counter.wrapping_add(1);
+            return self.counter;
+        }
+        self.mem[addr as usize]
+    }
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+}
+
+#[test]
+fn data_read_just_past_instruction_is_not_cached() {
+    // LDA $FF00 at $FEFD: the operand addresses the byte after the instruction
+    let mut mem = CounterMem {
+        mem: Box::new([0u8; 65536]),
+        counter: 0,
+    };
+    mem.mem[0xFEFD..0xFF00].copy_from_slice(&[0xB6, 0xFF, 0x00]);
+    let mut cpu = Cpu::new();
+    cpu.set_decode_cache(true);
+    for expected in 1..=3 {
+        cpu.registers_mut().pc = 0xFEFD;
+        cpu.step(&mut mem);
+        assert_eq!(cpu.registers().a(), expected);
+    }
+}
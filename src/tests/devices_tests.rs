@@ -0,0 +1,740 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use crate::bus::{CompositeBus, Mmio};
+use crate::devices::{
+    ACIA_IRQ, ACIA_MASTER_RESET, ACIA_RDRF, ACIA_RX_IRQ, ACIA_TDRE, ACIA_TX_IRQ, BlockStorage,
+    Cartridge, DISK_DRQ, DISK_ERROR, DISK_READ, DISK_WRITE, DMA_BUSY, DMA_CHAIN, DMA_DECREMENT,
+    DMA_END, DMA_IRQ, DMA_READ, DMA_ROTATE, DmaPort, IntervalTimer, Jittered, MapType, Mc6821,
+    Mc6844, Mc6850, Mc146818, MemorySize, PIA_C1_IRQ, PIA_C1_RISING, PIA_C2_IRQ, PIA_C2_OUTPUT,
+    PIA_C2_RISING, PIA_DATA, PIA_IRQ1, PIA_IRQ2, RTC_24H, RTC_BINARY, RTC_IRQF, RTC_PF, RTC_PIE,
+    RTC_SET, Sam, TIMER_ENABLE, TIMER_PENDING, TIMER_PERIODIC, TimerMode, Watchdog,
+};
+use crate::snapshot::{StateReader, StateWriter};
+use crate::{BusSignals, Clocked, Cpu, FlatRam, Memory, Snapshot};
+
+#[test]
+fn sam_control_bits_set_and_clear() {
+    let mut sam = Sam::new();
+    sam.write(0xFFC1, 0); // V0
+    sam.write(0xFFC5, 0); // V2
+    assert_eq!(sam.vdg_mode(), 0b101);
+    sam.write(0xFFC1, 0xFF); // value is ignored; the address selects the bit
+    sam.write(0xFFC0, 0xFF);
+    assert_eq!(sam.vdg_mode(), 0b100);
+
+    sam.write(0xFFC9, 0); // F1
+    sam.write(0xFFD3, 0); // F6
+    assert_eq!(sam.display_offset(), 0x8400);
+
+    sam.write(0xFFDB, 0); // M0
+    assert_eq!(sam.memory_size(), MemorySize::Size16K);
+    sam.write(0xFFDD, 0); // M1
+    assert_eq!(sam.memory_size(), MemorySize::Size64K);
+    sam.write(0xFFD9, 0); // R1
+    assert_eq!(sam.mpu_rate(), 2);
+
+    sam.reset();
+    assert_eq!(sam.control(), 0);
+}
+
+#[test]
+fn sam_small_ram_aliases() {
+    let mut sam = Sam::new();
+    sam.write(0x0010, 0x42);
+    assert_eq!(sam.read(0x1010), 0x42);
+    sam.write(0xFFDB, 0); // 16K
+    assert_eq!(sam.read(0x1010), 0x00);
+    assert_eq!(sam.read(0x4010), 0x42);
+}
+
+#[test]
+fn sam_rom_and_page_select() {
+    let mut sam = Sam::new().rom(0xA000, &[0x39]);
+    sam.set_control(0x6000); // 64K
+    sam.write(0xA000, 0x12);
+    assert_eq!(sam.read(0xA000), 0x39);
+    assert_eq!(sam.read(0x8000), 0xFF);
+
+    sam.write(0x0000, 0x11);
+    sam.write(0xFFD5, 0); // P1
+    assert!(sam.page1());
+    sam.write(0x0000, 0x22);
+    assert_eq!(sam.ram()[0x8000], 0x22);
+    sam.write(0xFFD4, 0);
+    assert_eq!(sam.read(0x0000), 0x11);
+
+    sam.write(0xFFDF, 0); // TY
+    assert_eq!(sam.map_type(), MapType::AllRam);
+    assert_eq!(sam.read(0x8000), 0x22);
+    sam.write(0xA000, 0x12);
+    assert_eq!(sam.read(0xA000), 0x12);
+}
+
+#[test]
+fn sam_redirects_vectors_and_leaves_io_open() {
+    let mut rom = vec![0u8; 0x2000];
+    rom[0x1FFE..].copy_from_slice(&[0xA0, 0x27]);
+    let mut sam = Sam::new().rom(0xA000, &rom);
+    assert_eq!(sam.read_word(0xFFFE), 0xA027);
+    assert_eq!(sam.read(0xFF00), 0xFF);
+    sam.write(0xFF00, 0x00);
+    assert_eq!(sam.read(0xFF00), 0xFF);
+}
+
+#[test]
+#[should_panic(expected = "does not fit")]
+fn sam_rejects_rom_in_io_area() {
+    let _ = Sam::new().rom(0xFE00, &[0; 0x200]);
+}
+
+/// Latch standing in for a PIA in the I/O area.
+struct Latch(u8);
+
+impl Mmio for Latch {
+    fn read(&mut self, _offset: u16) -> u8 {
+        self.0
+    }
+
+    fn write(&mut self, _offset: u16, val: u8) {
+        self.0 = val;
+    }
+}
+
+impl Clocked for Latch {}
+
+impl Snapshot for Latch {}
+
+#[test]
+fn sam_boots_under_composite_bus() {
+    // LDA #$5A / STA $FF00 / STA $FFDF / BRA *
+    let program = [0x86, 0x5A, 0xB7, 0xFF, 0x00, 0xB7, 0xFF, 0xDF, 0x20, 0xFE];
+    let mut rom = vec![0u8; 0x4000];
+    rom[..program.len()].copy_from_slice(&program);
+    rom[0x3FFE..].copy_from_slice(&[0x80, 0x00]);
+
+    let mut bus = CompositeBus::new(Sam::new().rom(0x8000, &rom));
+    let pia = bus.add_device(Latch(0));
+    bus.map(pia, 0xFF00, 0x20, 0);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    assert_eq!(cpu.registers().pc, 0x8000);
+    for _ in 0..3 {
+        cpu.step(&mut bus);
+    }
+    assert_eq!(bus.read(0xFF00), 0x5A);
+    assert_eq!(bus.base().map_type(), MapType::AllRam);
+}
+
+#[test]
+fn interval_timer_one_shot_stops() {
+    let mut timer = IntervalTimer::new(100, TimerMode::OneShot, BusSignals::IRQ);
+    assert!(timer.tick(1000).is_empty());
+    timer.start();
+    assert!(timer.tick(60).is_empty());
+    assert_eq!(timer.tick(60), BusSignals::IRQ);
+    assert!(!timer.is_running());
+    timer.acknowledge();
+    assert!(timer.tick(1000).is_empty());
+}
+
+#[test]
+fn interval_timer_periodic_keeps_phase() {
+    let mut timer = IntervalTimer::new(100, TimerMode::Periodic, BusSignals::FIRQ);
+    timer.start();
+    assert_eq!(timer.tick(250), BusSignals::FIRQ);
+    assert_eq!(timer.remaining(), 50);
+    timer.acknowledge();
+    assert!(timer.tick(49).is_empty());
+    assert_eq!(timer.tick(1), BusSignals::FIRQ);
+}
+
+#[test]
+fn interval_timer_registers() {
+    let mut timer = IntervalTimer::new(0, TimerMode::OneShot, BusSignals::IRQ);
+    timer.write(2, 0x01);
+    timer.write(3, 0x00);
+    assert_eq!(timer.period(), 0x100);
+    timer.write(0, TIMER_ENABLE | TIMER_PERIODIC);
+    assert_eq!(timer.read(0), TIMER_ENABLE | TIMER_PERIODIC);
+    assert_eq!(timer.tick(0x100), BusSignals::IRQ);
+    assert_eq!(timer.read(1), TIMER_PENDING);
+    assert_eq!(timer.read(1), 0);
+    assert!(timer.tick(1).is_empty());
+}
+
+#[test]
+fn interval_timer_drives_cpu_irq() {
+    // LDS #$0C00 / ANDCC #$EF / loop: BRA loop
+    // IRQ handler: INC $2000 / LDA $FF01 (acknowledge) / RTI
+    let mut ram = FlatRam::new();
+    ram.load(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x20, 0xFE], 0x0400);
+    ram.load(&[0x7C, 0x20, 0x00, 0xB6, 0xFF, 0x01, 0x3B], 0x0600);
+    ram.set_reset_vector(0x0400);
+    ram.set_vector(0xFFF8, 0x0600);
+
+    let mut bus = CompositeBus::new(ram);
+    let mut timer = IntervalTimer::new(500, TimerMode::Periodic, BusSignals::IRQ);
+    timer.start();
+    let id = bus.add_device(timer);
+    bus.map(id, 0xFF00, 4, 0);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    while cpu.cycles() < 5_250 {
+        let cycles = cpu.step(&mut bus);
+        let signals = bus.tick(cycles);
+        cpu.set_irq(signals.contains(BusSignals::IRQ));
+    }
+    assert_eq!(bus.read(0x2000), 10);
+}
+
+/// DMA port that supplies a fixed sequence and records what it is sent.
+struct Port {
+    supply: Vec<u8>,
+    received: Arc<Mutex<Vec<u8>>>,
+}
+
+impl DmaPort for Port {
+    fn requesting(&mut self) -> bool {
+        true
+    }
+
+    fn read(&mut self) -> u8 {
+        self.supply.remove(0)
+    }
+
+    fn write(&mut self, val: u8) {
+        self.received.lock().unwrap().push(val);
+    }
+}
+
+fn program_channel(dmac: &mut Mc6844, n: u16, addr: u16, count: u16, control: u8) {
+    let base = n * 4;
+    let [ah, al] = addr.to_be_bytes();
+    let [ch, cl] = count.to_be_bytes();
+    for (offset, val) in [(base, ah), (base + 1, al), (base + 2, ch), (base + 3, cl)] {
+        Mmio::write(dmac, offset, val);
+    }
+    Mmio::write(dmac, 0x10 + n, control);
+}
+
+#[test]
+fn mc6844_moves_block_to_memory_and_flags_end() {
+    let mut dmac = Mc6844::new();
+    dmac.attach(
+        1,
+        Port {
+            supply: vec![1, 2, 3],
+            received: Default::default(),
+        },
+    );
+    program_channel(&mut dmac, 1, 0x3002, 3, DMA_DECREMENT);
+    Mmio::write(&mut dmac, 0x15, 0x02);
+    assert!(dmac.tick(0).is_empty());
+    Mmio::write(&mut dmac, 0x14, 0x02);
+    assert_eq!(dmac.tick(0), BusSignals::DMA_BREQ);
+    assert_eq!(Mmio::read(&mut dmac, 0x11) & DMA_BUSY, DMA_BUSY);
+
+    let mut ram = FlatRam::new();
+    assert_eq!(dmac.service(&mut ram, 10), 3);
+    assert_eq!(ram.as_slice()[0x3000..0x3003], [3, 2, 1]);
+    assert_eq!(dmac.tick(0), BusSignals::IRQ);
+    assert_eq!(Mmio::read(&mut dmac, 0x15), DMA_IRQ | 0x02);
+    assert_eq!(Mmio::read(&mut dmac, 0x11), DMA_DECREMENT | DMA_END);
+    assert!(dmac.tick(0).is_empty());
+}
+
+#[test]
+fn mc6844_fixed_and_rotating_priority() {
+    let received = [(); 2].map(|_| Arc::new(Mutex::new(Vec::new())));
+    let mut dmac = Mc6844::new();
+    for (n, received) in received.iter().enumerate() {
+        dmac.attach(
+            n,
+            Port {
+                supply: Vec::new(),
+                received: received.clone(),
+            },
+        );
+        program_channel(&mut dmac, n as u16, 0x1000, 4, DMA_READ);
+    }
+    let mut ram = FlatRam::new();
+
+    Mmio::write(&mut dmac, 0x14, 0x03);
+    dmac.service(&mut ram, 2);
+    assert_eq!(received[0].lock().unwrap().len(), 2);
+    assert!(received[1].lock().unwrap().is_empty());
+
+    Mmio::write(&mut dmac, 0x14, DMA_ROTATE | 0x03);
+    dmac.service(&mut ram, 2);
+    assert_eq!(received[0].lock().unwrap().len(), 3);
+    assert_eq!(received[1].lock().unwrap().len(), 1);
+}
+
+#[test]
+fn mc6844_chains_from_channel_3() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut dmac = Mc6844::new();
+    dmac.attach(
+        0,
+        Port {
+            supply: Vec::new(),
+            received: received.clone(),
+        },
+    );
+    program_channel(&mut dmac, 0, 0x1000, 1, DMA_READ);
+    program_channel(&mut dmac, 3, 0x2000, 2, 0);
+    Mmio::write(&mut dmac, 0x16, DMA_CHAIN);
+    Mmio::write(&mut dmac, 0x14, 0x01);
+
+    let mut ram = FlatRam::new();
+    ram.load(&[0x11], 0x1000);
+    ram.load(&[0x22, 0x33], 0x2000);
+    // Channel 0 is reloaded each time it ends until chaining is turned off.
+    assert_eq!(dmac.service(&mut ram, 5), 5);
+    assert_eq!(*received.lock().unwrap(), [0x11, 0x22, 0x33, 0x22, 0x33]);
+    Mmio::write(&mut dmac, 0x16, 0);
+    assert_eq!(dmac.service(&mut ram, 5), 2);
+    assert_eq!(Mmio::read(&mut dmac, 0x10) & DMA_END, DMA_END);
+}
+
+#[test]
+fn mc6844_steals_cycles_from_cpu() {
+    // loop: INC $2000 / BRA loop
+    let mut ram = FlatRam::new();
+    ram.load(&[0x7C, 0x20, 0x00, 0x20, 0xFB], 0x0400);
+    ram.set_reset_vector(0x0400);
+
+    let mut dmac = Mc6844::new();
+    dmac.attach(
+        0,
+        Port {
+            supply: (0..32).collect(),
+            received: Default::default(),
+        },
+    );
+    program_channel(&mut dmac, 0, 0x3000, 32, 0);
+    Mmio::write(&mut dmac, 0x14, 0x01);
+
+    let mut bus = CompositeBus::new(ram);
+    let id = bus.add_device(dmac);
+    bus.map(id, 0xE000, 0x20, 0);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    let mut prev = BusSignals::default();
+    let mut refresh = 0;
+    for _ in 0..200 {
+        let cycles = cpu.step(&mut bus);
+        if cpu.bus_granted() {
+            bus.master(id, |dmac: &mut Mc6844, bus| dmac.service(bus, cycles));
+        } else if prev.contains(BusSignals::DMA_BREQ) {
+            refresh += 1;
+        }
+        let signals = bus.tick(cycles);
+        cpu.apply_signals(signals, prev);
+        prev = signals;
+    }
+    assert_eq!(
+        bus.base().as_slice()[0x3000..0x3020],
+        *(0..32).collect::<Vec<u8>>()
+    );
+    // One refresh cycle after every 14 stolen cycles.
+    assert_eq!(refresh, 2);
+    assert!(bus.read(0x2000) > 0);
+}
+
+#[test]
+fn block_storage_reads_and_writes_sectors() {
+    let mut image = vec![0u8; 3 * 128];
+    image[128..256].fill(0xAA);
+    let mut disk = BlockStorage::new(Cursor::new(image), 128);
+    assert_eq!([disk.read(5), disk.read(6), disk.read(7)], [0, 0, 3]);
+
+    disk.write(3, 1);
+    disk.write(0, DISK_READ);
+    assert_eq!(disk.read(0), DISK_DRQ);
+    assert!((0..128).all(|_| disk.read(4) == 0xAA));
+    assert_eq!(disk.read(0), 0);
+    assert_eq!(disk.read(4), 0xFF);
+
+    disk.write(3, 3); // one past the end: grows the image
+    disk.write(0, DISK_WRITE);
+    for i in 0..128 {
+        assert_eq!(disk.read(0), DISK_DRQ);
+        disk.write(4, i);
+    }
+    assert_eq!(disk.read(0), 0);
+    assert_eq!(disk.read(7), 4);
+    let image = disk.into_file().into_inner();
+    assert_eq!(image[3 * 128 + 5], 5);
+}
+
+#[test]
+fn block_storage_flags_errors() {
+    let mut disk = BlockStorage::new(Cursor::new(vec![0u8; 256]), 256);
+    disk.write(3, 1);
+    disk.write(0, DISK_READ);
+    assert_eq!(disk.read(0), DISK_ERROR);
+    assert!(disk.take_error().is_some());
+    disk.write(0, 0x7F);
+    assert_eq!(disk.read(0), DISK_ERROR);
+    disk.write(3, 0);
+    disk.write(0, DISK_READ);
+    assert_eq!(disk.read(0), DISK_DRQ);
+}
+
+#[test]
+fn block_storage_boots_sector_into_memory() {
+    // Boot loader: read sector 0 to $2000 and jump to it.
+    //        CLR $E001 / CLR $E002 / CLR $E003 / LDA #1 / STA $E000
+    //        LDX #$2000 / LDB #0
+    // loop:  LDA $E004 / STA ,X+ / DECB / BNE loop / JMP $2000
+    let loader = [
+        0x7F, 0xE0, 0x01, 0x7F, 0xE0, 0x02, 0x7F, 0xE0, 0x03, 0x86, 0x01, 0xB7, 0xE0, 0x00, 0x8E,
+        0x20, 0x00, 0xC6, 0x00, 0xB6, 0xE0, 0x04, 0xA7, 0x80, 0x5A, 0x26, 0xF8, 0x7E, 0x20, 0x00,
+    ];
+    let mut image = vec![0u8; 256];
+    image[..4].copy_from_slice(&[0x86, 0x99, 0x20, 0xFE]); // LDA #$99 / BRA *
+
+    let mut ram = FlatRam::new();
+    ram.load(&loader, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut bus = CompositeBus::new(ram);
+    let disk = bus.add_device(BlockStorage::new(Cursor::new(image), 256));
+    bus.map(disk, 0xE000, 8, 0);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    cpu.run(&mut bus, 10_000);
+    assert_eq!(cpu.registers().a(), 0x99);
+    assert_eq!(cpu.registers().pc, 0x2002);
+}
+
+/// 2024-02-29 13:45:30 UTC, a Thursday.
+const RTC_EPOCH: i64 = 1_709_214_330;
+
+#[test]
+fn rtc_reads_bcd_and_12_hour_time() {
+    let mut rtc = Mc146818::with_clock(1_000_000, || RTC_EPOCH);
+    rtc.write(0x0B, 0);
+    assert_eq!(rtc.read(0x04), 0x81); // 1 PM
+    assert_eq!(rtc.read(0x02), 0x45);
+    assert_eq!(rtc.read(0x06), 5);
+    assert_eq!(rtc.read(0x09), 0x24);
+    assert_eq!(rtc.read(0x0D), 0x80);
+}
+
+#[test]
+fn rtc_set_time_keeps_running() {
+    let now = Arc::new(Mutex::new(RTC_EPOCH));
+    let clock = now.clone();
+    let mut rtc = Mc146818::with_clock(1_000_000, move || *clock.lock().unwrap());
+    rtc.write(0x0B, RTC_SET | RTC_24H | RTC_BINARY);
+    *now.lock().unwrap() += 5;
+    assert_eq!(rtc.read(0x00), 30); // frozen while SET
+    rtc.write(0x04, 23);
+    rtc.write(0x02, 59);
+    rtc.write(0x00, 58);
+    rtc.write(0x07, 31);
+    rtc.write(0x08, 12);
+    rtc.write(0x09, 99);
+    rtc.write(0x0B, RTC_24H | RTC_BINARY);
+
+    *now.lock().unwrap() += 3;
+    let date = [0x09, 0x08, 0x07, 0x04, 0x02, 0x00].map(|reg| rtc.read(reg));
+    assert_eq!(date, [0, 1, 1, 0, 0, 1]);
+}
+
+#[test]
+fn rtc_periodic_interrupt() {
+    let mut rtc = Mc146818::with_clock(32_768, || 0);
+    rtc.write(0x0A, 0x06); // 1024 Hz: every 32 cycles
+    assert!(rtc.tick(40).is_empty());
+    assert_eq!(rtc.read(0x0C), RTC_PF);
+    assert_eq!(rtc.read(0x0C), 0);
+
+    rtc.write(0x0B, RTC_PIE | RTC_24H);
+    assert!(rtc.tick(20).is_empty());
+    assert_eq!(rtc.tick(4), BusSignals::IRQ);
+    assert_eq!(rtc.read(0x0C), RTC_IRQF | RTC_PF);
+    assert!(rtc.tick(1).is_empty());
+}
+
+#[test]
+fn rtc_user_ram() {
+    let mut rtc = Mc146818::with_clock(1_000_000, || 0);
+    rtc.write(0x0E, 0x5A);
+    rtc.write(0x3F, 0xA5);
+    assert_eq!([rtc.read(0x0E), rtc.read(0x3F)], [0x5A, 0xA5]);
+}
+
+#[test]
+fn watchdog_bites_once_per_timeout() {
+    let mut watchdog = Watchdog::new(100, BusSignals::RESET);
+    assert_eq!(watchdog.tick(250), BusSignals::RESET);
+    assert!(watchdog.tick(50).is_empty());
+    assert_eq!(watchdog.remaining(), 0);
+    assert_eq!(watchdog.tick(100), BusSignals::RESET);
+    assert_eq!(watchdog.read(0), 2);
+}
+
+#[test]
+fn watchdog_bites_every_timeout_cycles() {
+    // Ticks of 7 cycles: each bite lands on the first tick to reach the
+    // next multiple of 100, however far the last one overshot.
+    let mut watchdog = Watchdog::new(100, BusSignals::NMI);
+    let mut bites = Vec::new();
+    let mut now = 0;
+    while bites.len() < 3 {
+        now += 7;
+        if !watchdog.tick(7).is_empty() {
+            bites.push(now);
+        }
+    }
+    assert_eq!(bites, [105, 203, 301]);
+    assert_eq!(watchdog.remaining(), 100 - 1);
+
+    // A pet starts the count afresh.
+    assert!(watchdog.tick(7).is_empty());
+    watchdog.pet();
+    assert_eq!(watchdog.remaining(), 100);
+}
+
+#[test]
+fn watchdog_drives_cpu_nmi() {
+    // LDS #$0C00 / loop: BRA loop
+    // NMI handler: INC $2000 / STA $FF00 (pet) / RTI
+    let mut ram = FlatRam::new();
+    ram.load(&[0x10, 0xCE, 0x0C, 0x00, 0x20, 0xFE], 0x0400);
+    ram.load(&[0x7C, 0x20, 0x00, 0xB7, 0xFF, 0x00, 0x3B], 0x0600);
+    ram.set_reset_vector(0x0400);
+    ram.set_vector(0xFFFC, 0x0600);
+
+    let mut bus = CompositeBus::new(ram);
+    let id = bus.add_device(Watchdog::new(1000, BusSignals::NMI));
+    bus.map(id, 0xFF00, 1, 0);
+
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    let mut prev = BusSignals::default();
+    while cpu.cycles() < 10_500 {
+        let cycles = cpu.step(&mut bus);
+        let signals = bus.tick(cycles);
+        cpu.apply_signals(signals, prev);
+        prev = signals;
+    }
+    assert_eq!(bus.read(0x2000), 10);
+}
+
+/// Cycles until a jittered 100-cycle one-shot timer fires.
+fn jittered_fire_time(seed: u64) -> u64 {
+    let mut timer = IntervalTimer::new(100, TimerMode::OneShot, BusSignals::IRQ);
+    timer.start();
+    let mut jittered = Jittered::new(timer, 16, seed);
+    let mut cycles = 0;
+    while jittered.tick(1).is_empty() {
+        cycles += 1;
+    }
+    cycles
+}
+
+#[test]
+fn jittered_delays_reproducibly() {
+    let times: Vec<u64> = (0..32).map(jittered_fire_time).collect();
+    assert!(times.iter().all(|t| (99..=99 + 16).contains(t)));
+    assert_eq!(times, (0..32).map(jittered_fire_time).collect::<Vec<_>>());
+    assert!(times.iter().any(|&t| t != times[0]));
+}
+
+#[test]
+fn pia_ports_mix_outputs_and_inputs() {
+    let mut pia = Mc6821::new(BusSignals::IRQ);
+    pia.write(0, 0x0F); // DDRA: low nibble out
+    assert_eq!(pia.read(0), 0x0F);
+    pia.write(1, PIA_DATA);
+    pia.write(0, 0xA5);
+    pia.set_input_a(0x3C);
+    assert_eq!(pia.read(0), 0x35);
+    assert_eq!(pia.port_a(), 0x35);
+    assert_eq!(pia.ddr_a(), 0x0F);
+
+    // Offsets repeat every four bytes.
+    pia.write(6, 0xFF);
+    pia.write(7, PIA_DATA);
+    pia.write(6, 0x81);
+    assert_eq!(pia.port_b(), 0x81);
+    pia.reset();
+    assert_eq!(pia.read(1), 0);
+}
+
+#[test]
+fn pia_control_line_interrupts() {
+    let mut pia = Mc6821::new(BusSignals::FIRQ);
+    pia.write(1, PIA_DATA); // CA1 falling edge, interrupt disabled
+    pia.set_ca1(false);
+    assert_eq!(pia.read(1) & PIA_IRQ1, PIA_IRQ1);
+    assert!(pia.tick(1).is_empty());
+    pia.write(1, PIA_DATA | PIA_C1_IRQ);
+    assert_eq!(pia.tick(1), BusSignals::FIRQ);
+    pia.read(0);
+    assert!(!pia.irq_a());
+
+    // Rising edges only when asked for.
+    pia.write(1, PIA_DATA | PIA_C1_IRQ | PIA_C1_RISING);
+    pia.set_ca1(false);
+    assert!(!pia.irq_a());
+    pia.set_ca1(true);
+    assert!(pia.irq_a());
+
+    // CB2 as an input interrupts on its selected edge.
+    pia.write(3, PIA_DATA | PIA_C2_IRQ | PIA_C2_RISING);
+    pia.set_cb2(false);
+    pia.set_cb2(true);
+    assert_eq!(pia.read(3) & PIA_IRQ2, PIA_IRQ2);
+    assert!(pia.irq_b());
+}
+
+#[test]
+fn pia_c2_outputs() {
+    let mut pia = Mc6821::new(BusSignals::IRQ);
+    pia.write(3, PIA_DATA | PIA_C2_OUTPUT | PIA_C2_RISING);
+    assert!(!pia.cb2());
+    pia.write(3, PIA_DATA | PIA_C2_OUTPUT | PIA_C2_RISING | PIA_C2_IRQ);
+    assert!(pia.cb2());
+
+    // Handshake: low after a port A read until the next active CA1 edge.
+    pia.write(1, PIA_DATA | PIA_C2_OUTPUT);
+    assert!(pia.ca2());
+    pia.read(0);
+    assert!(!pia.ca2());
+    pia.set_ca1(false);
+    assert!(pia.ca2());
+}
+
+#[test]
+fn acia_receives_in_order_and_transmits() {
+    let mut acia = Mc6850::new(BusSignals::IRQ);
+    assert_eq!(acia.read(0), ACIA_TDRE);
+    acia.receive(b"HI");
+    assert_eq!(acia.pending_input(), 2);
+
+    // One byte is presented per tick, and only once the last was read.
+    let _ = acia.tick(1);
+    let _ = acia.tick(1);
+    assert_eq!(acia.read(0), ACIA_TDRE | ACIA_RDRF);
+    assert_eq!(acia.read(1), b'H');
+    assert_eq!(acia.read(0), ACIA_TDRE);
+    let _ = acia.tick(1);
+    assert_eq!(acia.read(3), b'I');
+    assert_eq!(acia.pending_input(), 0);
+
+    acia.write(1, b'O');
+    acia.write(3, b'K');
+    assert_eq!(acia.take_output(), b"OK");
+    assert!(acia.take_output().is_empty());
+}
+
+#[test]
+fn acia_master_reset_holds_until_next_control_write() {
+    let mut acia = Mc6850::new(BusSignals::IRQ);
+    acia.receive(b"AB");
+    let _ = acia.tick(1);
+    acia.write(0, ACIA_MASTER_RESET);
+    assert!(acia.in_reset());
+    assert_eq!(acia.read(0), 0);
+    acia.write(1, b'X');
+    let _ = acia.tick(1);
+    assert!(acia.take_output().is_empty());
+    assert_eq!(acia.pending_input(), 1);
+
+    acia.write(0, 0x15);
+    let _ = acia.tick(1);
+    assert_eq!(acia.read(1), b'B');
+}
+
+#[test]
+fn acia_interrupts() {
+    let mut acia = Mc6850::new(BusSignals::FIRQ);
+    acia.receive(b"A");
+    let _ = acia.tick(1);
+    assert!(acia.tick(1).is_empty());
+    acia.write(0, ACIA_RX_IRQ | 0x15);
+    assert_eq!(acia.tick(1), BusSignals::FIRQ);
+    assert_eq!(acia.read(0) & ACIA_IRQ, ACIA_IRQ);
+    acia.read(1);
+    assert!(acia.tick(1).is_empty());
+
+    // The transmitter is always ready, so its interrupt stays asserted.
+    acia.write(0, ACIA_TX_IRQ | 0x15);
+    assert_eq!(acia.tick(1), BusSignals::FIRQ);
+}
+
+#[test]
+fn acia_snapshot_keeps_queued_bytes() {
+    let mut acia = Mc6850::new(BusSignals::IRQ);
+    acia.write(0, ACIA_RX_IRQ | 0x15);
+    acia.receive(b"xyz");
+    let _ = acia.tick(1);
+    acia.write(1, b'!');
+    let mut out = StateWriter::new();
+    acia.snapshot(&mut out);
+    let state = out.into_bytes();
+
+    let mut back = Mc6850::new(BusSignals::IRQ);
+    back.restore(&mut StateReader::new(&state)).unwrap();
+    assert_eq!(back.read(1), b'x');
+    let _ = back.tick(1);
+    assert_eq!(back.read(1), b'y');
+    assert_eq!(back.pending_input(), 1);
+    assert_eq!(back.take_output(), b"!");
+}
+
+#[test]
+fn cartridge_rom_repeats_and_ignores_writes() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let id = bus.add_device(Cartridge::new(&[0x11, 0x22, 0x33, 0x44]));
+    bus.map(id, Cartridge::BASE, 0x4000, 0);
+
+    assert_eq!(bus.read(0xC001), 0x22);
+    assert_eq!(bus.read(0xFFFF), 0x44);
+    bus.write(0xC000, 0x99);
+    assert_eq!(bus.read(0xC000), 0x11);
+    assert!(bus.tick(10).is_empty());
+}
+
+#[test]
+fn cartridge_autostart_holds_firq_until_released() {
+    let mut bus = CompositeBus::new(FlatRam::new());
+    let id = bus.add_device(Cartridge::new(&[0x12]).autostart(true));
+    bus.map(id, Cartridge::BASE, 0x4000, 0);
+    assert_eq!(bus.tick(1), BusSignals::FIRQ);
+    assert_eq!(bus.tick(1000), BusSignals::FIRQ);
+
+    let cartridge = bus.device_as::<Cartridge>(id).unwrap();
+    assert!(cartridge.is_autostart());
+    cartridge.set_autostart(false);
+    assert!(bus.tick(1).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "cartridge image")]
+fn cartridge_rejects_oversized_image() {
+    Cartridge::new(&[0; Cartridge::MAX_SIZE + 1]);
+}
@@ -0,0 +1,200 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::FlatRam;
+use crate::disasm::{Successors, disassemble, insn_length};
+use crate::registers::Condition;
+use crate::symbols::SymbolTable;
+
+/// The text of the instruction `bytes` make at 0x0400.
+fn text(bytes: &[u8]) -> String {
+    let mut ram = FlatRam::new();
+    ram.load(bytes, 0x0400);
+    let insn = disassemble(&mut ram, 0x0400);
+    assert_eq!(insn.bytes, bytes, "length of {}", insn.text());
+    insn.text()
+}
+
+#[test]
+fn decodes_each_addressing_mode() {
+    assert_eq!(text(&[0x12]), "NOP");
+    assert_eq!(text(&[0x4F]), "CLRA");
+    assert_eq!(text(&[0x5A]), "DECB");
+    assert_eq!(text(&[0x86, 0x42]), "LDA   #$42");
+    assert_eq!(text(&[0xCC, 0x12, 0x34]), "LDD   #$1234");
+    assert_eq!(text(&[0x97, 0x10]), "STA   <$10");
+    assert_eq!(text(&[0x7E, 0xC0, 0x00]), "JMP   $C000");
+    assert_eq!(text(&[0x1F, 0x8B]), "TFR   A,DP");
+    assert_eq!(text(&[0x1E, 0x12]), "EXG   X,Y");
+    assert_eq!(text(&[0x34, 0x76]), "PSHS  A,B,X,Y,U");
+    assert_eq!(text(&[0x37, 0xC1]), "PULU  CC,S,PC");
+    assert_eq!(text(&[0x1A, 0x50]), "ORCC  #$50");
+}
+
+#[test]
+fn decodes_prefixed_opcodes() {
+    assert_eq!(text(&[0x10, 0x8E, 0x12, 0x34]), "LDY   #$1234");
+    assert_eq!(text(&[0x10, 0xDF, 0x20]), "STS   <$20");
+    assert_eq!(text(&[0x10, 0x3F]), "SWI2");
+    assert_eq!(text(&[0x11, 0x83, 0x00, 0x01]), "CMPU  #$0001");
+    assert_eq!(text(&[0x11, 0xBC, 0x80, 0x00]), "CMPS  $8000");
+}
+
+#[test]
+fn decodes_indexed_post_bytes() {
+    assert_eq!(text(&[0xA6, 0x1F]), "LDA   -1,X");
+    assert_eq!(text(&[0xA6, 0x25]), "LDA   5,Y");
+    assert_eq!(text(&[0xA6, 0x80]), "LDA   ,X+");
+    assert_eq!(text(&[0xA6, 0xC3]), "LDA   ,--U");
+    assert_eq!(text(&[0xA6, 0xE5]), "LDA   B,S");
+    assert_eq!(text(&[0xA6, 0x88, 0x80]), "LDA   -128,X");
+    assert_eq!(text(&[0xA6, 0xA9, 0x01, 0x00]), "LDA   256,Y");
+    assert_eq!(text(&[0xA6, 0x8B]), "LDA   D,X");
+    assert_eq!(text(&[0xA6, 0x94]), "LDA   [,X]");
+    assert_eq!(text(&[0xA6, 0x9F, 0x12, 0x34]), "LDA   [$1234]");
+    // PC-relative offsets count from the end of the instruction.
+    assert_eq!(text(&[0x30, 0x8C, 0x10]), "LEAX  $0413,PCR");
+    assert_eq!(text(&[0x30, 0x8D, 0xFF, 0xFC]), "LEAX  $0400,PCR");
+    assert_eq!(text(&[0x10, 0xAE, 0x99, 0x12, 0x34]), "LDY   [4660,X]");
+}
+
+#[test]
+fn branches_show_their_destination() {
+    assert_eq!(text(&[0x20, 0xFE]), "BRA   $0400");
+    assert_eq!(text(&[0x8D, 0x10]), "BSR   $0412");
+    assert_eq!(text(&[0x16, 0x01, 0x00]), "LBRA  $0503");
+    assert_eq!(text(&[0x10, 0x26, 0xFF, 0xFC]), "LBNE  $0400");
+}
+
+#[test]
+fn undefined_opcodes_are_single_bytes() {
+    for bytes in [[0x01, 0x00], [0x87, 0x00], [0x10, 0x86], [0x11, 0x10]] {
+        let mut ram = FlatRam::new();
+        ram.load(&bytes, 0x0400);
+        let insn = disassemble(&mut ram, 0x0400);
+        assert_eq!(insn.len(), 1);
+        assert_eq!(insn.text(), format!("FCB   ${:02X}", bytes[0]));
+    }
+}
+
+#[test]
+fn listing_line_and_labels() {
+    let mut ram = FlatRam::new();
+    ram.load(&[0x8E, 0x04, 0x20, 0x26, 0xFB], 0x0400);
+    let insn = disassemble(&mut ram, 0x0400);
+    assert_eq!(insn.to_string(), "0400  8E 04 20       LDX   #$0420");
+    assert_eq!(insn.next(), 0x0403);
+
+    let branch = disassemble(&mut ram, insn.next());
+    assert_eq!(branch.target, Some(0x0400));
+    let symbols = SymbolTable::parse("START EQU $0400\n").unwrap();
+    assert_eq!(branch.text_with(&symbols), "BNE   START");
+    assert_eq!(insn.text_with(&symbols), "LDX   #$0420");
+}
+
+/// Branches report the condition they test; other instructions none.
+#[test]
+fn branch_conditions() {
+    let condition = |bytes: &[u8]| {
+        let mut ram = FlatRam::new();
+        ram.load(bytes, 0x0400);
+        disassemble(&mut ram, 0x0400).condition()
+    };
+    assert_eq!(condition(&[0x27, 0xFE]), Some(Condition::Eq));
+    assert_eq!(condition(&[0x10, 0x2D, 0x00, 0x10]), Some(Condition::Lt));
+    assert_eq!(condition(&[0x16, 0x00, 0x10]), Some(Condition::Always));
+    assert_eq!(condition(&[0x10, 0x8E, 0x12, 0x34]), None);
+    assert_eq!(condition(&[0x12]), None);
+}
+
+/// `insn_length` agrees with the disassembly for every opcode and indexed
+/// post-byte.
+#[test]
+fn insn_length_matches_disassembly() {
+    let mut ram = FlatRam::new();
+    for prefix in [None, Some(0x10), Some(0x11)] {
+        for opcode in 0..=0xFF_u8 {
+            for post in [0x00, 0x1F, 0x84, 0x88, 0x89, 0x8C, 0x8D, 0x8F, 0x9F, 0xB9] {
+                let bytes: Vec<u8> = prefix
+                    .into_iter()
+                    .chain([opcode, post, 0x12, 0x34])
+                    .collect();
+                ram.load(&bytes, 0x0400);
+                let insn = disassemble(&mut ram, 0x0400);
+                assert_eq!(
+                    insn_length(&mut ram, 0x0400),
+                    insn.len(),
+                    "{bytes:02X?} {}",
+                    insn.text()
+                );
+            }
+        }
+    }
+    ram.load(&[0xA6, 0x8F, 0x12, 0x34], 0x0400);
+    assert_eq!(insn_length(&mut ram, 0x0400), 4); // LDA with post-byte $8F
+}
+
+/// Successors of each kind of control flow.
+#[test]
+fn successors_of_each_kind() {
+    let successors = |bytes: &[u8]| {
+        let mut ram = FlatRam::new();
+        ram.load(bytes, 0x0400);
+        disassemble(&mut ram, 0x0400).successors()
+    };
+    let both = |next, target| Successors {
+        fall_through: Some(next),
+        target: Some(target),
+        computed: false,
+    };
+    let jump = |target| Successors {
+        fall_through: None,
+        target: Some(target),
+        computed: false,
+    };
+    let computed_after = |next| Successors {
+        fall_through: Some(next),
+        ..Successors::COMPUTED
+    };
+
+    assert_eq!(successors(&[0x12]), Successors::next(0x0401)); // NOP
+    assert_eq!(successors(&[0x87]), Successors::next(0x0401)); // FCB
+    assert_eq!(successors(&[0x27, 0x10]), both(0x0402, 0x0412)); // BEQ
+    assert_eq!(successors(&[0x10, 0x2C, 0x01, 0x00]), both(0x0404, 0x0504)); // LBGE
+    assert_eq!(successors(&[0x20, 0xFE]), jump(0x0400)); // BRA *
+    assert_eq!(successors(&[0x21, 0x10]), Successors::next(0x0402)); // BRN
+    assert_eq!(successors(&[0x8D, 0x10]), both(0x0402, 0x0412)); // BSR
+    assert_eq!(successors(&[0x17, 0x00, 0x10]), both(0x0403, 0x0413)); // LBSR
+    assert_eq!(successors(&[0xBD, 0xC0, 0x00]), both(0x0403, 0xC000)); // JSR $C000
+    assert_eq!(successors(&[0x7E, 0xC0, 0x00]), jump(0xC000)); // JMP $C000
+    assert_eq!(successors(&[0x6E, 0x8C, 0x10]), jump(0x0413)); // JMP $10,PCR
+    assert_eq!(successors(&[0x6E, 0x84]), Successors::COMPUTED); // JMP ,X
+    assert_eq!(successors(&[0x6E, 0x9F, 0xFF, 0xFE]), Successors::COMPUTED); // JMP [$FFFE]
+    assert_eq!(successors(&[0x0E, 0x10]), Successors::COMPUTED); // JMP <$10
+    assert_eq!(successors(&[0xAD, 0x94]), computed_after(0x0402)); // JSR [,X]
+    assert_eq!(successors(&[0x39]), Successors::COMPUTED); // RTS
+    assert_eq!(successors(&[0x3B]), Successors::COMPUTED); // RTI
+    assert_eq!(successors(&[0x11, 0x3F]), computed_after(0x0402)); // SWI3
+    assert_eq!(successors(&[0x35, 0x86]), Successors::COMPUTED); // PULS A,B,PC
+    assert_eq!(successors(&[0x35, 0x06]), Successors::next(0x0402)); // PULS A,B
+    assert_eq!(successors(&[0x1F, 0x15]), Successors::COMPUTED); // TFR X,PC
+    assert_eq!(successors(&[0x1E, 0x51]), Successors::COMPUTED); // EXG PC,X
+    assert_eq!(successors(&[0x1F, 0x51]), Successors::next(0x0402)); // TFR PC,X
+
+    assert_eq!(
+        both(0x0402, 0x0412).iter().collect::<Vec<_>>(),
+        [0x0402, 0x0412]
+    );
+    assert_eq!(Successors::COMPUTED.iter().count(), 0);
+}
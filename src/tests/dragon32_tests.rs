@@ -0,0 +1,126 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::Memory;
+use crate::devices::Cartridge;
+use crate::machines::dragon32::{CPU_HZ, CYCLES_PER_FRAME, Dragon32};
+
+/// A 16KB ROM image with `program` at 0x8000, also the reset address.
+fn rom_with(program: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0x12; 0x4000];
+    rom[..program.len()].copy_from_slice(program);
+    rom[0x3FFE..].copy_from_slice(&[0x80, 0x00]);
+    rom
+}
+
+#[test]
+fn dragon32_shows_text_screen() {
+    // STA $FFC9 (SAM F1: display at $0400) / LDX #$0400 / LDY #msg
+    // loop: LDA ,Y+ / BEQ done / STA ,X+ / BRA loop / done: BRA done
+    // msg: "OK 1.0", 0
+    let mut rom = rom_with(&[
+        0xB7, 0xFF, 0xC9, 0x8E, 0x04, 0x00, 0x10, 0x8E, 0x80, 0x14, 0xA6, 0xA0, 0x27, 0x04, 0xA7,
+        0x80, 0x20, 0xF8, 0x20, 0xFE,
+    ]);
+    rom[0x14..0x1B].copy_from_slice(b"OK 1.0\0");
+    let mut dragon = Dragon32::new(&rom);
+    assert!(dragon.run_frame() >= CYCLES_PER_FRAME);
+
+    let screen = dragon.screen_text();
+    assert_eq!(screen.lines().count(), 16);
+    assert!(screen.starts_with("OK 1.0@@"), "{screen}");
+    assert!(screen.lines().all(|line| line.chars().count() == 32));
+}
+
+#[test]
+fn dragon32_clocks_the_cpu_at_its_crystal_rate() {
+    let dragon = Dragon32::new(&rom_with(&[0x20, 0xFE]));
+    assert_eq!(dragon.machine().cpu().clock_hz(), CPU_HZ);
+}
+
+#[test]
+fn dragon32_scans_keyboard_matrix() {
+    let mut dragon = Dragon32::new(&rom_with(&[0x20, 0xFE]));
+    let bus = dragon.machine_mut().bus_mut();
+    bus.write(0xFF03, 0x00);
+    bus.write(0xFF02, 0xFF); // DDRB: columns out
+    bus.write(0xFF03, 0x04);
+    bus.write(0xFF01, 0x04);
+
+    assert!(dragon.key_down('a'));
+    assert!(!dragon.key_down('!'));
+    let bus = dragon.machine_mut().bus_mut();
+    bus.write(0xFF02, 0xFE); // column 0
+    assert_eq!(bus.read(0xFF00), 0xFF);
+    bus.write(0xFF02, 0xFD); // column 1: A is on row 2
+    assert_eq!(bus.read(0xFF00), 0xFB);
+    bus.write(0xFF02, 0x00);
+    assert_eq!(bus.read(0xFF00), 0xFB);
+
+    dragon.key_up('A');
+    assert_eq!(dragon.machine_mut().bus_mut().read(0xFF00), 0xFF);
+}
+
+#[test]
+fn dragon32_field_sync_interrupts() {
+    // LDS #$0300 / LDA #$05 / STA $FF03 (FS on CB1, falling edge, enabled)
+    // ANDCC #$EF / loop: BRA loop
+    // IRQ: LDA $FF02 (acknowledge) / INC $0200 / RTI
+    let mut rom = rom_with(&[
+        0x10, 0xCE, 0x03, 0x00, 0x86, 0x05, 0xB7, 0xFF, 0x03, 0x1C, 0xEF, 0x20, 0xFE,
+    ]);
+    rom[0x20..0x27].copy_from_slice(&[0xB6, 0xFF, 0x02, 0x7C, 0x02, 0x00, 0x3B]);
+    rom[0x3FF8..0x3FFA].copy_from_slice(&[0x80, 0x20]);
+    let mut dragon = Dragon32::new(&rom);
+    for _ in 0..10 {
+        dragon.run_frame();
+    }
+    let ticks = dragon.machine_mut().bus_mut().read(0x0200);
+    assert!((9..=10).contains(&ticks), "{ticks}");
+
+    dragon.reset();
+    assert_eq!(dragon.machine().cpu().registers().pc, 0x8000);
+}
+
+/// BASIC stand-in that enables the CB1 (CART) interrupt on PIA 1 and waits
+/// with FIRQ unmasked, jumping to the cartridge on FIRQ:
+/// LDA #$07 / STA $FF23 / ANDCC #$BF / BRA *
+fn cartridge_host_rom() -> Vec<u8> {
+    let mut rom = rom_with(&[0x86, 0x07, 0xB7, 0xFF, 0x23, 0x1C, 0xBF, 0x20, 0xFE]);
+    rom[0x3FF6..0x3FF8].copy_from_slice(&[0xC0, 0x00]); // FIRQ vector
+    rom
+}
+
+/// LDA #$42 / STA $0500 / BRA *
+const CARTRIDGE: [u8; 7] = [0x86, 0x42, 0xB7, 0x05, 0x00, 0x20, 0xFE];
+
+#[test]
+fn dragon32_autostarts_cartridge() {
+    let mut dragon = Dragon32::new(&cartridge_host_rom());
+    dragon.insert_cartridge(Cartridge::new(&CARTRIDGE).autostart(true));
+    dragon.run_frame();
+    assert_eq!(dragon.machine_mut().bus_mut().read(0x0500), 0x42);
+    assert!(dragon.machine().cpu().registers().pc >= 0xC000);
+}
+
+#[test]
+fn dragon32_maps_cartridge_without_autostart() {
+    let mut dragon = Dragon32::new(&cartridge_host_rom());
+    dragon.insert_cartridge(Cartridge::new(&CARTRIDGE));
+    dragon.run_frame();
+    let bus = dragon.machine_mut().bus_mut();
+    assert_eq!(bus.read(0x0500), 0x00);
+    assert_eq!(bus.read(0xC000), 0x86);
+    assert_eq!(bus.read(0xFEFF), CARTRIDGE[0x3EFF % CARTRIDGE.len()]);
+}
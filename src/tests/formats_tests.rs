@@ -0,0 +1,293 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::formats::{Format, FormatError, Segment, decb, flex, ihex, raw, srec};
+use crate::{FlatRam, Memory};
+
+#[test]
+fn flex_parses_records_between_padding() {
+    let file = [
+        0x02, 0x10, 0x00, 0x03, 0xAA, 0xBB, 0xCC, // load 3 bytes at $1000
+        0x00, 0x00, 0x00, // sector padding
+        0x02, 0x20, 0x00, 0x01, 0xDD, // load 1 byte at $2000
+        0x16, 0x10, 0x00, // transfer to $1000
+        0x00, 0x00,
+    ];
+    let image = flex::parse(&file).unwrap();
+    assert_eq!(
+        image.segments,
+        [
+            Segment {
+                addr: 0x1000,
+                data: vec![0xAA, 0xBB, 0xCC],
+            },
+            Segment {
+                addr: 0x2000,
+                data: vec![0xDD],
+            },
+        ]
+    );
+    assert_eq!(image.entry, Some(0x1000));
+    assert_eq!(image.len(), 4);
+
+    let mut ram = FlatRam::new();
+    assert_eq!(flex::load(&file, &mut ram), Ok(Some(0x1000)));
+    assert_eq!(ram.read(0x1002), 0xCC);
+    assert_eq!(ram.read(0x2000), 0xDD);
+}
+
+#[test]
+fn flex_without_transfer_record() {
+    let image = flex::parse(&[0x02, 0x00, 0x10, 0x00]).unwrap();
+    assert_eq!(image.entry, None);
+    assert!(image.is_empty());
+}
+
+#[test]
+fn flex_rejects_bad_files() {
+    assert_eq!(
+        flex::parse(&[0x02, 0x10, 0x00, 0x04, 0xAA]),
+        Err(FormatError::Truncated { offset: 0 })
+    );
+    assert_eq!(
+        flex::parse(&[0x00, 0x16, 0x10]),
+        Err(FormatError::Truncated { offset: 1 })
+    );
+    assert_eq!(
+        flex::parse(&[0x00, 0x00, 0x53]),
+        Err(FormatError::BadRecord {
+            offset: 2,
+            byte: 0x53
+        })
+    );
+    assert_eq!(
+        flex::parse(&[0x02, 0xFF, 0xFF, 0x02, 0x00, 0x00]),
+        Err(FormatError::OutOfRange {
+            addr: 0xFFFF,
+            len: 2
+        })
+    );
+
+    let mut ram = FlatRam::new();
+    assert!(flex::load(&[0x02, 0x10, 0x00, 0x01, 0xAA, 0x53], &mut ram).is_err());
+    assert_eq!(ram.read(0x1000), 0x00);
+}
+
+#[test]
+fn raw_load_and_dump_round_trip() {
+    let mut ram = FlatRam::new();
+    raw::load(&[1, 2, 3], 0xFFFD, &mut ram).unwrap();
+    assert_eq!(raw::dump(&mut ram, 0xFFFC, 4).unwrap(), [0, 1, 2, 3]);
+
+    let image = raw::parse(&[9; 4], 0x1000).unwrap();
+    assert_eq!(image.segments[0].addr, 0x1000);
+    assert_eq!(image.entry, None);
+}
+
+#[test]
+fn raw_parse_makes_one_segment_without_entry() {
+    let image = raw::parse(&[0x12, 0x3F], 0xFFFE).unwrap();
+    assert_eq!(
+        image.segments,
+        [Segment {
+            addr: 0xFFFE,
+            data: vec![0x12, 0x3F],
+        }]
+    );
+    assert_eq!(image.entry, None);
+    assert_eq!(
+        raw::parse(&[0; 3], 0xFFFE).unwrap_err(),
+        FormatError::OutOfRange {
+            addr: 0xFFFE,
+            len: 3
+        }
+    );
+}
+
+#[test]
+fn raw_rejects_ranges_past_ffff() {
+    let mut ram = FlatRam::new();
+    let out_of_range = FormatError::OutOfRange {
+        addr: 0xFFFE,
+        len: 3,
+    };
+    assert_eq!(
+        raw::load(&[1, 2, 3], 0xFFFE, &mut ram),
+        Err(out_of_range.clone())
+    );
+    assert_eq!(ram.read(0xFFFE), 0);
+    assert_eq!(raw::dump(&mut ram, 0xFFFE, 3), Err(out_of_range));
+}
+
+#[test]
+fn raw_dump_srec_splits_records() {
+    let mut ram = FlatRam::new();
+    let data: Vec<u8> = (0..20).collect();
+    raw::load(&data, 0x2000, &mut ram).unwrap();
+    let srec = raw::dump_srec(&mut ram, 0x2000, 20, None).unwrap();
+    assert!(raw::dump_srec(&mut ram, 0xFFF0, 16, None).is_ok());
+    let lines: Vec<&str> = srec.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "S1132000000102030405060708090A0B0C0D0E0F54",
+            "S10720101011121382",
+            "S9030000FC",
+        ]
+    );
+}
+
+#[test]
+fn raw_save_writes_files() {
+    let mut ram = FlatRam::new();
+    raw::load(&[0xDE, 0xAD], 0x0100, &mut ram).unwrap();
+    let dir = std::env::temp_dir();
+    let bin = dir.join(format!("mc6809-raw-{}.bin", std::process::id()));
+    let srec = dir.join(format!("mc6809-raw-{}.s19", std::process::id()));
+
+    raw::save(&bin, &mut ram, 0x0100, 2).unwrap();
+    raw::save_srec(&srec, &mut ram, 0x0100, 2, Some(0x0100)).unwrap();
+    assert_eq!(std::fs::read(&bin).unwrap(), [0xDE, 0xAD]);
+    assert!(
+        std::fs::read_to_string(&srec)
+            .unwrap()
+            .starts_with("S1050100DEAD")
+    );
+    assert!(raw::save(&bin, &mut ram, 0xFFFF, 2).is_err());
+
+    std::fs::remove_file(bin).unwrap();
+    std::fs::remove_file(srec).unwrap();
+}
+
+#[test]
+fn srec_parses_data_and_start_records() {
+    let file = b"S00600004844521B\r\nS1050400862A46\nS2050004103FA7\n\nS5030002FA\nS804000400F7\n";
+    let image = srec::parse(file).unwrap();
+    assert_eq!(
+        image.segments,
+        [
+            Segment {
+                addr: 0x0400,
+                data: vec![0x86, 0x2A],
+            },
+            Segment {
+                addr: 0x0410,
+                data: vec![0x3F],
+            },
+        ]
+    );
+    assert_eq!(image.entry, Some(0x0400));
+
+    let mut ram = FlatRam::new();
+    assert_eq!(srec::load(file, &mut ram), Ok(Some(0x0400)));
+    assert_eq!(ram.read(0x0410), 0x3F);
+}
+
+#[test]
+fn srec_rejects_bad_lines() {
+    let bad = |line| Err(FormatError::BadLine { line });
+    assert_eq!(srec::parse(b"S1050400862A47\n"), bad(1)); // checksum
+    assert_eq!(srec::parse(b"S9030400F8\nS1060400862A46\n"), bad(2)); // count
+    assert_eq!(srec::parse(b"S4030400F8\n"), bad(1));
+    assert_eq!(srec::parse(b"X1050400862A46\n"), bad(1));
+    assert_eq!(srec::parse(b"S1050400862AG6\n"), bad(1));
+    assert_eq!(
+        srec::parse(b"S2050100003FBA\n"),
+        Err(FormatError::OutOfRange {
+            addr: 0x0000,
+            len: 1
+        })
+    );
+}
+
+#[test]
+fn ihex_parses_until_end_of_file() {
+    let file = b":02040000862A4A\n:020000040000FA\n:0400000500000400F3\n:00000001FF\ngarbage\n";
+    let image = ihex::parse(file).unwrap();
+    assert_eq!(
+        image.segments,
+        [Segment {
+            addr: 0x0400,
+            data: vec![0x86, 0x2A],
+        }]
+    );
+    assert_eq!(image.entry, Some(0x0400));
+}
+
+#[test]
+fn ihex_rejects_bad_lines() {
+    let bad = |line| Err(FormatError::BadLine { line });
+    assert_eq!(ihex::parse(b":02040000862A4B\n"), bad(1)); // checksum
+    assert_eq!(ihex::parse(b":00000001FF"[1..].as_ref()), bad(1));
+    assert_eq!(ihex::parse(b":020000040001F9\n"), bad(1)); // beyond 64 KB
+    assert_eq!(ihex::parse(b":0100000600F9\n"), bad(1)); // unknown type
+}
+
+#[test]
+fn decb_parses_blocks_up_to_the_postamble() {
+    let file = [
+        0x00, 0x00, 0x02, 0x0E, 0x00, 0x86, 0x2A, // 2 bytes at $0E00
+        0x00, 0x00, 0x01, 0x20, 0x00, 0x39, // 1 byte at $2000
+        0xFF, 0x00, 0x00, 0x0E, 0x00, // exec $0E00
+        0x55, // ignored
+    ];
+    let image = decb::parse(&file).unwrap();
+    assert_eq!(image.segments.len(), 2);
+    assert_eq!(image.segments[1].addr, 0x2000);
+    assert_eq!(image.entry, Some(0x0E00));
+
+    let mut ram = FlatRam::new();
+    assert_eq!(decb::load(&file, &mut ram), Ok(Some(0x0E00)));
+    assert_eq!(ram.read(0x2000), 0x39);
+}
+
+#[test]
+fn decb_rejects_bad_files() {
+    assert_eq!(
+        decb::parse(&[0x00, 0x00, 0x02, 0x0E, 0x00, 0x86]),
+        Err(FormatError::Truncated { offset: 0 })
+    );
+    assert_eq!(
+        decb::parse(&[0x00, 0x00, 0x00, 0x0E, 0x00]),
+        Err(FormatError::Truncated { offset: 5 })
+    );
+    assert_eq!(
+        decb::parse(&[0x12, 0x00, 0x00, 0x0E, 0x00]),
+        Err(FormatError::BadRecord {
+            offset: 0,
+            byte: 0x12
+        })
+    );
+}
+
+#[test]
+fn detect_tells_formats_apart() {
+    assert_eq!(Format::detect(b"S1050400862A46\n"), Format::Srec);
+    assert_eq!(Format::detect(b":00000001FF\n"), Format::IntelHex);
+    assert_eq!(
+        Format::detect(&[0xFF, 0x00, 0x00, 0x0E, 0x00][..]),
+        Format::Raw
+    );
+    assert_eq!(
+        Format::detect(&[0x00, 0x00, 0x00, 0x0E, 0x00, 0xFF, 0x00, 0x00, 0x0E, 0x00]),
+        Format::Decb
+    );
+    // Code that merely starts like a format stays raw.
+    assert_eq!(Format::detect(&[0x00, 0x10, 0x12]), Format::Raw);
+    assert_eq!(Format::detect(b"SWAP"), Format::Raw);
+    assert!(!Format::Raw.has_addresses());
+
+    let image = Format::Raw.parse(&[0x12], 0x0400).unwrap();
+    assert_eq!(image.segments[0].addr, 0x0400);
+}
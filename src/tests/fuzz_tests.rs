@@ -0,0 +1,79 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::fuzz::CpuState;
+use crate::registers::{ConditionCodes, Registers};
+use crate::{CpuVariant, Memory};
+
+#[test]
+fn registers_are_taken_field_by_field() {
+    let bytes = [0x12, 0x34, 0, 1, 0, 2, 0, 3, 0, 4, 0x04, 0x00, 0x56, 0xD0];
+    let mut u = Unstructured::new(&bytes);
+    let regs = Registers::arbitrary(&mut u).unwrap();
+    assert_eq!(regs.d, u16::from_le_bytes([0x12, 0x34]));
+    assert_eq!(regs.pc, 0x0004);
+    assert_eq!(regs.dp, 0x56);
+    assert_eq!(regs.cc, ConditionCodes::from_byte(0xD0));
+    assert!(u.is_empty());
+}
+
+#[test]
+fn cpu_state_is_bounded_and_builds() {
+    let bytes = [0xA5; 4096];
+    let state = CpuState::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+    assert!(state.program.len() <= CpuState::MAX_PROGRAM);
+    assert!(state.steps <= CpuState::MAX_STEPS);
+
+    let (cpu, mut ram) = state.build();
+    assert_eq!(*cpu.registers(), state.registers);
+    assert_eq!(cpu.variant(), state.variant);
+    let pc = state.registers.pc;
+    for (i, &byte) in state.program.iter().enumerate() {
+        assert_eq!(ram.read(pc.wrapping_add(i as u16)), byte);
+    }
+}
+
+#[test]
+fn cpu_state_runs_its_steps() {
+    let state = CpuState {
+        variant: CpuVariant::Mc6809,
+        registers: Registers {
+            pc: 0xFFFF,
+            ..Registers::new()
+        },
+        program: vec![0x4C, 0x4C, 0x4C], // INCA x3, wrapping to 0x0000
+        steps: 2,
+    };
+    let (cpu, _) = state.run();
+    assert_eq!(cpu.registers().a(), 2);
+    assert_eq!(cpu.registers().pc, 0x0001);
+}
+
+#[test]
+fn arbitrary_streams_execute_without_panicking() {
+    let mut seed = 0x2545_F491_4F6C_DD1Du64;
+    let mut bytes = [0; 512];
+    for _ in 0..200 {
+        for byte in &mut bytes {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            *byte = seed as u8;
+        }
+        let state = CpuState::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        state.run();
+    }
+}
@@ -0,0 +1,127 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use gdbstub::arch::Registers as _;
+use gdbstub::common::Signal;
+use gdbstub::stub::SingleThreadStopReason;
+use gdbstub::target::ext::base::singlethread::{
+    SingleThreadBase, SingleThreadResume, SingleThreadSingleStep,
+};
+use gdbstub::target::ext::breakpoints::SwBreakpoint;
+
+use crate::gdb::{GdbTarget, RunEvent};
+use crate::machine::Machine;
+use crate::registers::Registers;
+use crate::{ConditionCodes, FlatRam};
+
+/// A target whose machine has `program` at 0x0400 and has been reset.
+fn target_with(program: &[u8]) -> GdbTarget<FlatRam> {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    GdbTarget::new(Machine::new(ram))
+}
+
+#[test]
+fn registers_round_trip_through_the_gdb_layout() {
+    let mut regs = Registers::new();
+    regs.cc = ConditionCodes::from_byte(0x85);
+    regs.d = 0x1234;
+    regs.dp = 0x56;
+    regs.x = 0x789A;
+    regs.y = 0xBCDE;
+    regs.u = 0x0102;
+    regs.s = 0x0304;
+    regs.pc = 0x0506;
+
+    let mut bytes = Vec::new();
+    regs.gdb_serialize(|byte| bytes.push(byte.unwrap()));
+    assert_eq!(
+        bytes,
+        [
+            0x85, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06
+        ]
+    );
+
+    let mut back = Registers::new();
+    back.gdb_deserialize(&bytes).unwrap();
+    assert_eq!(back, regs);
+    assert!(back.gdb_deserialize(&bytes[..13]).is_err());
+}
+
+#[test]
+fn target_reads_and_writes_registers_and_memory() {
+    let mut target = target_with(&[0x86, 0x42]); // LDA #$42
+    let mut regs = Registers::new();
+    assert!(target.read_registers(&mut regs).is_ok());
+    assert_eq!(regs.pc, 0x0400);
+
+    regs.x = 0xCAFE;
+    assert!(target.write_registers(&regs).is_ok());
+    assert_eq!(target.machine().cpu().registers().x, 0xCAFE);
+
+    let mut data = [0; 2];
+    assert_eq!(target.read_addrs(0x0400, &mut data).ok(), Some(2));
+    assert_eq!(data, [0x86, 0x42]);
+    assert!(target.write_addrs(0x0401, &[0x17]).is_ok());
+    target.step(None).unwrap();
+    target.run(|| false);
+    assert_eq!(target.machine().cpu().registers().a(), 0x17);
+
+    // Reads stop at the top of the address space.
+    let mut data = [0; 4];
+    assert_eq!(target.read_addrs(0xFFFE, &mut data).ok(), Some(2));
+}
+
+#[test]
+fn step_runs_one_instruction() {
+    let mut target = target_with(&[0x12, 0x12]); // NOP / NOP
+    target.step(None).unwrap();
+    assert_eq!(
+        target.run(|| false),
+        RunEvent::Stopped(SingleThreadStopReason::DoneStep)
+    );
+    assert_eq!(target.machine().cpu().registers().pc, 0x0401);
+}
+
+#[test]
+fn continue_stops_at_breakpoints_and_runs_past_them() {
+    let mut target = target_with(&[0x4C, 0x20, 0xFD]); // INCA / BRA
+    assert_eq!(target.add_sw_breakpoint(0x0401, 1).ok(), Some(true));
+    target.resume(None).unwrap();
+    assert_eq!(
+        target.run(|| false),
+        RunEvent::Stopped(SingleThreadStopReason::SwBreak(()))
+    );
+    assert_eq!(target.machine().cpu().registers().pc, 0x0401);
+    assert_eq!(target.machine().cpu().registers().a(), 1);
+
+    target.run(|| false);
+    assert_eq!(target.machine().cpu().registers().a(), 2);
+
+    assert_eq!(target.remove_sw_breakpoint(0x0401, 1).ok(), Some(true));
+    assert_eq!(target.remove_sw_breakpoint(0x0401, 1).ok(), Some(false));
+    assert_eq!(target.run(|| true), RunEvent::IncomingData);
+}
+
+#[test]
+fn continue_reports_a_halted_cpu() {
+    let mut target = target_with(&[0x12]);
+    target.machine_mut().cpu_mut().set_halted(true);
+    target.resume(None).unwrap();
+    assert_eq!(
+        target.run(|| false),
+        RunEvent::Stopped(SingleThreadStopReason::Signal(Signal::SIGSTOP))
+    );
+}
@@ -0,0 +1,498 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tests for the HD6309 variant.
+
+use crate::snapshot::{StateReader, StateWriter};
+use crate::{Cpu, CpuError, CpuVariant, FlatRam, IllegalOpcodePolicy, Memory, Snapshot};
+
+/// A 6309 reset into `program` at 0x0400, with S at 0x4000.
+fn hd6309(program: &[u8]) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.set_variant(CpuVariant::Hd6309);
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x4000;
+    (cpu, ram)
+}
+
+#[test]
+fn ldw_and_ldq_load_w_and_q() {
+    // LDW #$1234 ; LDQ #$11223344
+    let (mut cpu, mut ram) = hd6309(&[0x10, 0x86, 0x12, 0x34, 0xCD, 0x11, 0x22, 0x33, 0x44]);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().w, 0x1234);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().d, 0x1122);
+    assert_eq!(cpu.hd6309_registers().w, 0x3344);
+    assert_eq!(cpu.hd6309_registers().e(), 0x33);
+    assert_eq!(cpu.hd6309_registers().f(), 0x44);
+    assert!(!cpu.registers().cc.negative());
+    assert!(!cpu.registers().cc.zero());
+}
+
+#[test]
+fn stq_stores_d_then_w() {
+    // LDQ #$89ABCDEF ; STQ <$20
+    let (mut cpu, mut ram) = hd6309(&[0xCD, 0x89, 0xAB, 0xCD, 0xEF, 0x10, 0xDD, 0x20]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(ram.read_word(0x0020), 0x89AB);
+    assert_eq!(ram.read_word(0x0022), 0xCDEF);
+    assert!(cpu.registers().cc.negative());
+}
+
+#[test]
+fn register_ops_use_w_and_the_zero_register() {
+    // LDW #$0100 ; ADDR W,X ; TFR 0,Y ; CMPR 0,D
+    let (mut cpu, mut ram) = hd6309(&[
+        0x10, 0x86, 0x01, 0x00, 0x10, 0x30, 0x61, 0x1F, 0xC2, 0x10, 0x37, 0xC0,
+    ]);
+    cpu.registers_mut().x = 0x0234;
+    cpu.registers_mut().y = 0xFFFF;
+    cpu.registers_mut().d = 0;
+    for _ in 0..4 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.registers().x, 0x0334);
+    assert_eq!(cpu.registers().y, 0x0000);
+    assert!(cpu.registers().cc.zero());
+}
+
+#[test]
+fn tfr_moves_to_and_from_e_f_and_w() {
+    // LDA #$5A ; TFR A,E ; TFR E,F ; TFR W,X
+    let (mut cpu, mut ram) = hd6309(&[0x86, 0x5A, 0x1F, 0x8E, 0x1F, 0xEF, 0x1F, 0x61]);
+    for _ in 0..4 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.hd6309_registers().e(), 0x5A);
+    assert_eq!(cpu.hd6309_registers().f(), 0x5A);
+    assert_eq!(cpu.registers().x, 0x5A5A);
+}
+
+#[test]
+fn undocumented_6809_opcode_is_illegal_on_6309() {
+    // XRES, which the 6809 runs as RESET
+    let (mut cpu, mut ram) = hd6309(&[0x3E]);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Trap);
+    assert_eq!(
+        cpu.try_step(&mut ram),
+        Err(CpuError::IllegalOpcode { pc: 0x0400 })
+    );
+    assert_eq!(cpu.registers().pc, 0x0401);
+}
+
+#[test]
+fn ldmd_writes_mode_bits_and_bitmd_tests_status_bits() {
+    // LDMD #$FF ; BITMD #$80 ; BITMD #$80
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x3D, 0xFF, 0x11, 0x3C, 0x80, 0x11, 0x3C, 0x80]);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().md, 0x03);
+    cpu.hd6309_registers_mut().md |= 0x80;
+    cpu.step(&mut ram);
+    assert!(!cpu.registers().cc.zero());
+    assert_eq!(cpu.hd6309_registers().md, 0x03);
+    cpu.step(&mut ram);
+    assert!(cpu.registers().cc.zero());
+}
+
+#[test]
+fn indexed_w_post_increment_and_e_offset() {
+    // LDW #$2000 ; LDA ,W++ ; LDB E,X
+    let (mut cpu, mut ram) = hd6309(&[0x10, 0x86, 0x20, 0x00, 0xA6, 0xCF, 0xE6, 0x87]);
+    ram.write(0x2000, 0x42);
+    ram.write(0x1FFF, 0x24);
+    cpu.registers_mut().x = 0x2000;
+    cpu.hd6309_registers_mut().set_e(0xFF);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a(), 0x42);
+    assert_eq!(cpu.hd6309_registers().w, 0x2002);
+    cpu.hd6309_registers_mut().set_e(0xFF);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().b(), 0x24);
+}
+
+#[test]
+fn pshsw_and_pulsw_round_trip_w() {
+    // LDW #$BEEF ; PSHSW ; CLRW ; PULSW
+    let (mut cpu, mut ram) = hd6309(&[0x10, 0x86, 0xBE, 0xEF, 0x10, 0x38, 0x10, 0x5F, 0x10, 0x39]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().s, 0x3FFE);
+    assert_eq!(ram.read_word(0x3FFE), 0xBEEF);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().w, 0);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().w, 0xBEEF);
+    assert_eq!(cpu.registers().s, 0x4000);
+}
+
+#[test]
+fn snapshot_round_trips_6309_registers() {
+    let (mut cpu, _) = hd6309(&[]);
+    cpu.hd6309_registers_mut().w = 0x1234;
+    cpu.hd6309_registers_mut().v = 0x5678;
+    cpu.hd6309_registers_mut().md = 0x01;
+    let mut out = StateWriter::new();
+    cpu.snapshot(&mut out);
+    let state = out.into_bytes();
+
+    let mut restored = Cpu::new();
+    restored.set_variant(CpuVariant::Hd6309);
+    restored.restore(&mut StateReader::new(&state)).unwrap();
+    assert_eq!(restored.hd6309_registers(), cpu.hd6309_registers());
+}
+
+#[test]
+fn mc6809_ignores_6309_opcodes() {
+    // LDW #$1234 is an undefined page 1 opcode on the 6809
+    let mut ram = FlatRam::new();
+    ram.load(&[0x10, 0x86, 0x12, 0x34], 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().w, 0);
+}
+
+#[test]
+fn native_mode_uses_native_cycle_counts() {
+    // NOP ; LDMD #$01 ; NOP ; LDA ,X++ (post-byte 0x81)
+    let (mut cpu, mut ram) = hd6309(&[0x12, 0x11, 0x3D, 0x01, 0x12, 0xA6, 0x81]);
+    assert_eq!(cpu.step(&mut ram), 2);
+    cpu.step(&mut ram);
+    assert!(cpu.hd6309_registers().native());
+    assert_eq!(cpu.step(&mut ram), 1);
+    assert_eq!(cpu.step(&mut ram), 6);
+}
+
+#[test]
+fn native_mode_interrupt_frame_includes_e_and_f() {
+    // LDMD #$01 ; NOP, with the IRQ handler at 0x0500: RTI
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x3D, 0x01, 0x12]);
+    ram.load(&[0x3B], 0x0500);
+    ram.write_word(0xFFF8, 0x0500);
+    cpu.step(&mut ram);
+    cpu.registers_mut().cc.set_irq_inhibit(false);
+    cpu.hd6309_registers_mut().w = 0xE0F0;
+    cpu.set_irq(true);
+    assert_eq!(cpu.step(&mut ram), 21);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.registers().s, 0x4000 - 14);
+    // CC, A, B, E, F, DP from the bottom of the frame
+    assert_eq!(ram.read(0x4000 - 11), 0xE0);
+    assert_eq!(ram.read(0x4000 - 10), 0xF0);
+    cpu.set_irq(false);
+    cpu.hd6309_registers_mut().w = 0;
+    assert_eq!(cpu.step(&mut ram), 17);
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(cpu.registers().s, 0x4000);
+    assert_eq!(cpu.hd6309_registers().w, 0xE0F0);
+}
+
+#[test]
+fn firq_saves_entire_state_when_md_selects_it() {
+    // LDMD #$02, with the FIRQ handler at 0x0500
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x3D, 0x02]);
+    ram.write_word(0xFFF6, 0x0500);
+    cpu.step(&mut ram);
+    cpu.registers_mut().cc.set_firq_inhibit(false);
+    cpu.set_firq(true);
+    assert_eq!(cpu.step(&mut ram), 19);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.registers().s, 0x4000 - 12);
+    assert!(cpu.registers().cc.entire());
+}
+
+#[test]
+fn addw_subw_and_cmpw() {
+    // LDW #$1000 ; ADDW #$0234 ; SUBW #$0034 ; CMPW #$1200
+    let (mut cpu, mut ram) = hd6309(&[
+        0x10, 0x86, 0x10, 0x00, 0x10, 0x8B, 0x02, 0x34, 0x10, 0x80, 0x00, 0x34, 0x10, 0x81, 0x12,
+        0x00,
+    ]);
+    for _ in 0..3 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.hd6309_registers().w, 0x1200);
+    cpu.step(&mut ram);
+    assert!(cpu.registers().cc.zero());
+    assert_eq!(cpu.hd6309_registers().w, 0x1200);
+}
+
+#[test]
+fn muld_multiplies_signed_into_q() {
+    // LDD #-3 ; MULD #1000
+    let (mut cpu, mut ram) = hd6309(&[0xCC, 0xFF, 0xFD, 0x11, 0x8F, 0x03, 0xE8]);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.step(&mut ram), 28);
+    assert_eq!(cpu.registers().d, 0xFFFF);
+    assert_eq!(cpu.hd6309_registers().w, (-3000i16) as u16);
+    assert!(cpu.registers().cc.negative());
+}
+
+#[test]
+fn divd_puts_quotient_in_b_and_remainder_in_a() {
+    // LDD #-100 ; DIVD #7
+    let (mut cpu, mut ram) = hd6309(&[0xCC, 0xFF, 0x9C, 0x11, 0x8D, 0x07]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().b() as i8, -14);
+    assert_eq!(cpu.registers().a() as i8, -2);
+    assert!(cpu.registers().cc.negative());
+    assert!(!cpu.registers().cc.overflow());
+}
+
+#[test]
+fn divq_puts_quotient_in_w_and_remainder_in_d() {
+    // LDQ #100000 ; DIVQ #7
+    let (mut cpu, mut ram) = hd6309(&[0xCD, 0x00, 0x01, 0x86, 0xA0, 0x11, 0x8E, 0x00, 0x07]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.hd6309_registers().w, 14285);
+    assert_eq!(cpu.registers().d, 5);
+    assert!(cpu.registers().cc.carry());
+}
+
+#[test]
+fn division_overflow_leaves_registers_alone() {
+    // LDD #$7FFF ; DIVD #1
+    let (mut cpu, mut ram) = hd6309(&[0xCC, 0x7F, 0xFF, 0x11, 0x8D, 0x01]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().d, 0x7FFF);
+    assert!(cpu.registers().cc.overflow());
+}
+
+#[test]
+fn division_by_zero_traps_and_sets_dz() {
+    // LDD #$1234 ; DIVD #0, with the trap handler at 0x0500
+    let (mut cpu, mut ram) = hd6309(&[0xCC, 0x12, 0x34, 0x11, 0x8D, 0x00]);
+    ram.write_word(0xFFF0, 0x0500);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.registers().d, 0x1234);
+    assert_eq!(cpu.hd6309_registers().md & 0x80, 0x80);
+    assert_eq!(cpu.registers().s, 0x4000 - 12);
+    assert_eq!(ram.read_word(0x4000 - 2), 0x0406);
+}
+
+#[test]
+fn division_by_zero_traps_from_a_recorded_block() {
+    // loop: DIVQ ,X ; LDA #$55 ; BRA loop, with the trap handler at
+    // 0x0500: BRA *
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0xAE, 0x84, 0x86, 0x55, 0x20, 0xF9]);
+    ram.load(&[0x20, 0xFE], 0x0500);
+    ram.write_word(0xFFF0, 0x0500);
+    ram.write_word(0x2000, 0x0001);
+    cpu.registers_mut().x = 0x2000;
+    cpu.set_block_cache(true);
+    cpu.run_fast(&mut ram, 500);
+    ram.write_word(0x2000, 0x0000);
+    cpu.run_fast(&mut ram, 500);
+    assert_eq!(cpu.registers().pc, 0x0500);
+}
+
+#[test]
+fn tfm_copies_one_byte_a_step() {
+    // TFM X+,Y+
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x38, 0x12]);
+    ram.load(b"abc", 0x2000);
+    cpu.registers_mut().x = 0x2000;
+    cpu.registers_mut().y = 0x3000;
+    cpu.hd6309_registers_mut().w = 3;
+    assert_eq!(cpu.step(&mut ram), 9);
+    assert_eq!(cpu.registers().pc, 0x0400);
+    assert_eq!(cpu.step(&mut ram), 3);
+    assert_eq!(cpu.step(&mut ram), 3);
+    assert_eq!(cpu.registers().pc, 0x0403);
+    assert_eq!(cpu.instructions_retired(), 1);
+    assert_eq!(cpu.hd6309_registers().w, 0);
+    assert_eq!(cpu.registers().x, 0x2003);
+    assert_eq!(cpu.registers().y, 0x3003);
+    let mut copy = [0; 3];
+    ram.read_slice(0x3000, &mut copy);
+    assert_eq!(&copy, b"abc");
+    // With W zero the TFM moves nothing
+    cpu.registers_mut().pc = 0x0400;
+    assert_eq!(cpu.step(&mut ram), 6);
+    assert_eq!(cpu.registers().pc, 0x0403);
+}
+
+#[test]
+fn tfm_fills_and_copies_downwards() {
+    // TFM X+,Y ; TFM U-,D-
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x3A, 0x12, 0x11, 0x39, 0x30]);
+    ram.load(b"xyz", 0x2000);
+    cpu.registers_mut().x = 0x2000;
+    cpu.registers_mut().y = 0x3000;
+    cpu.hd6309_registers_mut().w = 3;
+    while cpu.registers().pc == 0x0400 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(ram.read(0x3000), b'z');
+    assert_eq!(cpu.registers().y, 0x3000);
+    cpu.registers_mut().u = 0x2002;
+    cpu.registers_mut().d = 0x3102;
+    cpu.hd6309_registers_mut().w = 3;
+    while cpu.registers().pc == 0x0403 {
+        cpu.step(&mut ram);
+    }
+    let mut copy = [0; 3];
+    ram.read_slice(0x3100, &mut copy);
+    assert_eq!(&copy, b"xyz");
+    assert_eq!(cpu.registers().u, 0x1FFF);
+}
+
+#[test]
+fn tfm_is_interruptible_between_bytes() {
+    // TFM X+,Y+, with the IRQ handler at 0x0500: RTI
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x38, 0x12]);
+    ram.load(&[0x3B], 0x0500);
+    ram.write_word(0xFFF8, 0x0500);
+    ram.load(b"abcd", 0x2000);
+    cpu.registers_mut().cc.set_irq_inhibit(false);
+    cpu.registers_mut().x = 0x2000;
+    cpu.registers_mut().y = 0x3000;
+    cpu.hd6309_registers_mut().w = 4;
+    cpu.step(&mut ram);
+    cpu.set_irq(true);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(ram.read_word(0x4000 - 2), 0x0400);
+    cpu.set_irq(false);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0x0400);
+    assert_eq!(cpu.hd6309_registers().w, 3);
+    // Resuming pays the set-up cycles again
+    assert_eq!(cpu.step(&mut ram), 9);
+    while cpu.registers().pc == 0x0400 {
+        cpu.step(&mut ram);
+    }
+    let mut copy = [0; 4];
+    ram.read_slice(0x3000, &mut copy);
+    assert_eq!(&copy, b"abcd");
+}
+
+#[test]
+fn tfm_with_other_registers_is_illegal() {
+    // TFM W+,X+
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x38, 0x61]);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::Trap);
+    assert_eq!(
+        cpu.try_step(&mut ram),
+        Err(CpuError::IllegalOpcode { pc: 0x0400 })
+    );
+}
+
+#[test]
+fn memory_immediate_logic() {
+    // AIM #$0F,<$20 ; OIM #$80,,X ; EIM #$FF,$2000 ; TIM #$00,<$20
+    let (mut cpu, mut ram) = hd6309(&[
+        0x02, 0x0F, 0x20, 0x61, 0x80, 0x84, 0x75, 0xFF, 0x20, 0x00, 0x0B, 0x00, 0x20,
+    ]);
+    ram.write(0x0020, 0x5A);
+    ram.write(0x1000, 0x01);
+    ram.write(0x2000, 0x0F);
+    cpu.registers_mut().x = 0x1000;
+    assert_eq!(cpu.step(&mut ram), 6);
+    assert_eq!(ram.read(0x0020), 0x0A);
+    cpu.step(&mut ram);
+    assert_eq!(ram.read(0x1000), 0x81);
+    assert!(cpu.registers().cc.negative());
+    cpu.step(&mut ram);
+    assert_eq!(ram.read(0x2000), 0xF0);
+    cpu.step(&mut ram);
+    assert!(cpu.registers().cc.zero());
+    assert_eq!(ram.read(0x0020), 0x0A);
+}
+
+#[test]
+fn register_bit_operations() {
+    // LDBT A,7,3,<$20 ; BAND CC,0,0,<$20 ; BIEOR B,1,2,<$20 ; STBT A,7,5,<$21
+    let (mut cpu, mut ram) = hd6309(&[
+        0x11, 0x36, 0x5F, 0x20, 0x11, 0x30, 0x00, 0x20, 0x11, 0x35, 0x8A, 0x20, 0x11, 0x37, 0x6F,
+        0x21,
+    ]);
+    ram.write(0x0020, 0x08);
+    cpu.registers_mut().cc.set_carry(true);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a(), 0x80);
+    cpu.step(&mut ram);
+    assert!(!cpu.registers().cc.carry());
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().b(), 0x04);
+    assert_eq!(cpu.step(&mut ram), 8);
+    assert_eq!(ram.read(0x0021), 0x20);
+}
+
+#[test]
+fn illegal_opcode_traps_and_sets_il_by_default() {
+    // XRES, then the trap handler at 0x0500: BITMD #$40 ; BITMD #$40
+    let (mut cpu, mut ram) = hd6309(&[0x3E]);
+    ram.load(&[0x11, 0x3C, 0x40, 0x11, 0x3C, 0x40], 0x0500);
+    ram.write_word(0xFFF0, 0x0500);
+    assert_eq!(cpu.step(&mut ram), 20);
+    assert!(cpu.illegal());
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.registers().s, 0x4000 - 12);
+    assert_eq!(ram.read_word(0x4000 - 2), 0x0401);
+    cpu.step(&mut ram);
+    assert!(!cpu.registers().cc.zero());
+    cpu.step(&mut ram);
+    assert!(cpu.registers().cc.zero());
+}
+
+#[test]
+fn undefined_page_opcode_traps_in_native_mode() {
+    // LDMD #$01 ; 0x10 0x00 (undefined)
+    let (mut cpu, mut ram) = hd6309(&[0x11, 0x3D, 0x01, 0x10, 0x00]);
+    ram.write_word(0xFFF0, 0x0500);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.step(&mut ram), 22);
+    assert_eq!(cpu.registers().pc, 0x0500);
+    assert_eq!(cpu.registers().s, 0x4000 - 14);
+    assert_eq!(cpu.hd6309_registers().md & 0x40, 0x40);
+}
+
+#[test]
+fn treat_as_nop_carries_on_past_illegal_opcode() {
+    let (mut cpu, mut ram) = hd6309(&[0x3E, 0x12]);
+    cpu.set_illegal_opcode_policy(IllegalOpcodePolicy::TreatAsNop);
+    assert_eq!(cpu.step(&mut ram), 1);
+    assert_eq!(cpu.registers().pc, 0x0401);
+    assert_eq!(cpu.hd6309_registers().md, 0);
+}
+
+#[test]
+fn save_state_carries_the_variant_and_6309_registers() {
+    let (mut cpu, _) = hd6309(&[]);
+    cpu.hd6309_registers_mut().w = 0x1234;
+    cpu.hd6309_registers_mut().v = 0x5678;
+    cpu.hd6309_registers_mut().md = 0x01;
+    let state = cpu.save_state();
+    assert_eq!(state.len(), 46);
+    assert_eq!(state[8], 1);
+    assert_eq!(&state[23..28], &[0x12, 0x34, 0x56, 0x78, 0x01]);
+
+    let mut restored = Cpu::new();
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.variant(), CpuVariant::Hd6309);
+    assert_eq!(restored.hd6309_registers(), cpu.hd6309_registers());
+}
@@ -41,6 +41,29 @@ fn page0_nega_is_2_cycles() {
     assert_eq!(instruction_cycles(&[0x40]), 2);
 }
 
+/// Undocumented aliases cost the same as the instructions they alias.
+#[test]
+fn page0_undocumented_aliases_match_their_instructions() {
+    for (alias, documented) in [
+        (0x41, 0x40),
+        (0x42, 0x43),
+        (0x4E, 0x4F),
+        (0x51, 0x50),
+        (0x52, 0x53),
+        (0x5E, 0x5F),
+        (0x61, 0x60),
+        (0x62, 0x63),
+        (0x71, 0x70),
+        (0x72, 0x73),
+    ] {
+        assert_eq!(
+            instruction_cycles(&[alias]),
+            instruction_cycles(&[documented]),
+            "{alias:02X}"
+        );
+    }
+}
+
 /// An unrecognised page 0 opcode (0x87) returns 1.
 #[test]
 fn page0_illegal_opcode_returns_one() {
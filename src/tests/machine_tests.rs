@@ -0,0 +1,501 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::bus::CompositeBus;
+use crate::devices::{IntervalTimer, TimerMode, Watchdog};
+use crate::machine::{Cluster, ClusterBus, LoopDetector, Machine};
+use crate::registers::Registers;
+use crate::snapshot::StateError;
+#[cfg(feature = "hooks")]
+use crate::stats::InterruptSource;
+use crate::{BusSignals, Cpu, FlatRam, Memory};
+
+/// RAM with `program` at 0x0400 and the reset vector pointing at it.
+fn ram_with(program: &[u8]) -> FlatRam {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    ram
+}
+
+#[test]
+fn machine_resets_on_construction_and_runs() {
+    let mut machine = Machine::new(ram_with(&[0x4C, 0x20, 0xFD])); // INCA / BRA
+    assert_eq!(machine.cpu().registers().pc, 0x0400);
+    assert_eq!(machine.run_for(100), 100);
+    assert_eq!(machine.cpu().registers().a(), 20);
+    machine.reset();
+    assert_eq!(machine.cpu().cycles(), 0);
+}
+
+#[test]
+fn machine_run_loops_stop_while_paused() {
+    let mut machine = Machine::new(ram_with(&[0x20, 0xFE])); // BRA *
+    let handle = machine.cpu_mut().control_handle();
+    handle.pause();
+    assert_eq!(machine.run_for(100), 0);
+    assert_eq!(machine.run_frame(100), 0);
+    assert_eq!(
+        machine.run_realtime(1_000_000.0, Duration::from_secs(10)),
+        0
+    );
+    handle.resume();
+    assert_eq!(machine.run_for(9), 9);
+}
+
+#[test]
+fn machine_run_until_signal_stops_on_raised_event() {
+    // BRA *, with a timer raising EVENT1 every 50 cycles until acknowledged
+    let mut bus = CompositeBus::new(ram_with(&[0x20, 0xFE]));
+    let timer = bus.add_device(IntervalTimer::new(
+        50,
+        TimerMode::Periodic,
+        BusSignals::EVENT1,
+    ));
+    bus.map(timer, 0xFF00, 4, 0);
+    let mut machine = Machine::new(bus);
+    assert!(!machine.run_until_signal(BusSignals::EVENT1, 60));
+    assert_eq!(machine.cycles(), 60);
+
+    machine
+        .bus_mut()
+        .device_as::<IntervalTimer>(timer)
+        .unwrap()
+        .start();
+    assert!(machine.run_until_signal(BusSignals::EVENT0 | BusSignals::EVENT1, 1000));
+    assert_eq!(machine.cycles(), 111);
+    assert_eq!(machine.signals(), BusSignals::EVENT1);
+    assert_eq!(format!("{:?}", machine.signals()), "BusSignals(EVENT1)");
+    // Still held, so not raised again until acknowledged
+    assert!(!machine.run_until_signal(BusSignals::EVENT1, 100));
+    machine
+        .bus_mut()
+        .device_as::<IntervalTimer>(timer)
+        .unwrap()
+        .acknowledge();
+    assert!(machine.run_until_signal(BusSignals::EVENT1, 100));
+    // The CPU ignores event signals
+    assert_eq!(machine.cpu().registers().pc, 0x0400);
+}
+
+#[test]
+fn machine_applies_bus_interrupts() {
+    // LDS #$0C00 / ANDCC #$EF / loop: BRA loop
+    // IRQ handler: INC $2000 / LDA $FF01 (acknowledge) / RTI
+    let mut ram = ram_with(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x20, 0xFE]);
+    ram.load(&[0x7C, 0x20, 0x00, 0xB6, 0xFF, 0x01, 0x3B], 0x0600);
+    ram.set_vector(0xFFF8, 0x0600);
+    let mut bus = CompositeBus::new(ram);
+    let mut timer = IntervalTimer::new(1000, TimerMode::Periodic, BusSignals::IRQ);
+    timer.start();
+    let id = bus.add_device(timer);
+    bus.map(id, 0xFF00, 4, 0);
+
+    let mut machine = Machine::new(bus);
+    machine.run_for(10_500);
+    assert_eq!(machine.bus_mut().read(0x2000), 10);
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn machine_run_for_with_stats_counts_bus_interrupts() {
+    // LDS #$0C00 / ANDCC #$EF / loop: BRA loop
+    // IRQ handler: LDA $FF01 (acknowledge) / RTI
+    let mut ram = ram_with(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x20, 0xFE]);
+    ram.load(&[0xB6, 0xFF, 0x01, 0x3B], 0x0600);
+    ram.set_vector(0xFFF8, 0x0600);
+    let mut bus = CompositeBus::new(ram);
+    let mut timer = IntervalTimer::new(1000, TimerMode::Periodic, BusSignals::IRQ);
+    timer.start();
+    let id = bus.add_device(timer);
+    bus.map(id, 0xFF00, 4, 0);
+
+    let mut machine = Machine::new(bus);
+    machine.cpu_mut().add_breakpoint(0x0600);
+    let stats = machine.run_for_with_stats(10_500);
+    assert!(stats.cycles >= 10_500);
+    assert_eq!(stats.interrupts(InterruptSource::Irq), 10);
+    assert_eq!(stats.total_interrupts(), 10);
+    assert_eq!(stats.breakpoints_hit, 10);
+    assert_eq!(stats.max_stack_depth, 12);
+    assert!(stats.instructions > 3000);
+}
+
+#[test]
+fn machine_resets_cpu_on_reset_signal() {
+    let mut bus = CompositeBus::new(ram_with(&[0x4C, 0x20, 0xFD]));
+    bus.add_device(Watchdog::new(1000, BusSignals::RESET));
+    let mut machine = Machine::new(bus);
+    machine.run_for(900);
+    assert!(machine.cpu().registers().a() > 100);
+    machine.run_for(110);
+    assert!(machine.cpu().registers().a() < 5);
+    assert!(machine.cpu().cycles() < 20);
+}
+
+#[test]
+fn machine_step_hooks() {
+    let seen = Arc::new(AtomicU64::new(0));
+    let counter = seen.clone();
+    let mut machine = Machine::new(ram_with(&[0x12, 0x20, 0xFD])); // NOP / BRA
+    let id = machine.add_step_hook(move |cpu, _, cycles| {
+        assert!(cpu.cycles() > 0);
+        counter.fetch_add(cycles, Ordering::Relaxed);
+    });
+    machine.run_for(50);
+    assert_eq!(seen.load(Ordering::Relaxed), machine.cpu().cycles());
+    assert!(machine.remove_step_hook(id).is_some());
+    assert!(machine.remove_step_hook(id).is_none());
+    machine.run_for(50);
+    assert_ne!(seen.load(Ordering::Relaxed), machine.cpu().cycles());
+}
+
+#[test]
+fn scheduler_runs_events_in_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut machine = Machine::new(ram_with(&[0x12, 0x20, 0xFD])); // NOP / BRA
+    let scheduler = machine.scheduler_mut();
+    for (cycle, tag) in [(30, "c"), (10, "a"), (30, "d"), (20, "b")] {
+        let log = log.clone();
+        scheduler.at_cycle(cycle, move |_, _, due| log.lock().unwrap().push((due, tag)));
+    }
+    let cancelled = {
+        let log = log.clone();
+        scheduler.at_cycle(25, move |_, _, _| log.lock().unwrap().push((25, "x")))
+    };
+    assert!(scheduler.cancel(cancelled));
+    assert!(!scheduler.cancel(cancelled));
+    assert_eq!(scheduler.next_due(), Some(10));
+
+    machine.run_for(100);
+    assert_eq!(
+        *log.lock().unwrap(),
+        [(10, "a"), (20, "b"), (30, "c"), (30, "d")]
+    );
+    assert!(machine.scheduler().is_empty());
+}
+
+#[test]
+fn scheduler_periodic_events_fire_after_their_cycle() {
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut machine = Machine::new(ram_with(&[0x12, 0x20, 0xFD])); // NOP / BRA
+    let log = fired.clone();
+    let id = machine
+        .scheduler_mut()
+        .every(100, move |_, bus: &mut FlatRam, due| {
+            let count = bus.read(0x2000);
+            bus.write(0x2000, count + 1);
+            log.lock().unwrap().push(due);
+        });
+    machine.run_for(1000);
+    assert_eq!(machine.bus_mut().read(0x2000), 10);
+    assert_eq!(fired.lock().unwrap()[..3], [100, 200, 300]);
+
+    // Machine time keeps counting across a CPU reset.
+    machine.reset();
+    machine.run_for(100);
+    assert_eq!(machine.bus_mut().read(0x2000), 11);
+    assert!(machine.scheduler_mut().cancel(id));
+}
+
+#[test]
+fn run_realtime_paces_execution() {
+    let mut machine = Machine::new(ram_with(&[0x12, 0x20, 0xFD])); // NOP / BRA
+    let start = Instant::now();
+    let cycles = machine.run_realtime(100_000.0, Duration::from_millis(30));
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    // 3000 cycles are due; allow a slice plus one instruction of overshoot.
+    assert!(cycles > 0 && cycles <= 3_000 + 100 + 3, "{cycles}");
+}
+
+#[test]
+fn run_realtime_fast_forward_skips_sleeping() {
+    let mut machine = Machine::new(ram_with(&[0x12, 0x20, 0xFD])); // NOP / BRA
+    machine.set_fast_forward(true);
+    assert!(machine.fast_forward());
+    let cycles = machine.run_realtime(1_000.0, Duration::from_millis(10));
+    assert!(cycles > 1_000, "{cycles}");
+
+    machine.set_fast_forward(false);
+    let cycles = machine.run_realtime(1_000.0, Duration::from_millis(10));
+    assert!(cycles <= 10 + 1 + 3, "{cycles}");
+}
+
+/// Two CPUs sharing one RAM. CPU 1's IRQ follows the byte at $FF00.
+struct SharedRam(FlatRam);
+
+impl ClusterBus for SharedRam {
+    fn memory(&mut self, _cpu: usize) -> &mut dyn Memory {
+        &mut self.0
+    }
+
+    fn tick(&mut self, cpu: usize, _cycles: u64) -> BusSignals {
+        if cpu == 1 && self.0.as_slice()[0xFF00] != 0 {
+            BusSignals::IRQ
+        } else {
+            BusSignals::default()
+        }
+    }
+}
+
+#[test]
+fn cluster_interleaves_by_clock_rate() {
+    // NOP / BRA
+    let mut cluster = Cluster::new(SharedRam(ram_with(&[0x12, 0x20, 0xFD])));
+    cluster.add_cpu(2_000_000);
+    cluster.add_cpu(1_000_000);
+    cluster.set_quantum(10);
+    cluster.run_for(10_000);
+    assert_eq!(cluster.time(), 10_000);
+    assert!(cluster.cycles(0).abs_diff(10_000) < 5);
+    assert!(cluster.cycles(1).abs_diff(5_000) < 5);
+}
+
+#[test]
+fn cluster_wires_interrupts_across_cpus() {
+    // CPU 0 at $0400: LDA #1 / loop: DECB / BNE loop / STA $FF00 / BRA *
+    // CPU 1 at $0500: LDS #$0C00 / ANDCC #$EF / BRA *
+    // CPU 1 IRQ handler: CLR $FF00 / INC $2000 / RTI
+    let mut ram = ram_with(&[0x86, 0x01, 0x5A, 0x26, 0xFD, 0xB7, 0xFF, 0x00, 0x20, 0xFE]);
+    ram.load(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x20, 0xFE], 0x0500);
+    ram.load(&[0x7F, 0xFF, 0x00, 0x7C, 0x20, 0x00, 0x3B], 0x0600);
+    ram.set_vector(0xFFF8, 0x0600);
+
+    let mut cluster = Cluster::new(SharedRam(ram));
+    cluster.add_cpu(1_000_000);
+    cluster.add_cpu(1_000_000);
+    cluster.cpu_mut(1).registers_mut().pc = 0x0500;
+    cluster.run_for(2_000);
+    let ram = cluster.bus().0.as_slice();
+    assert_eq!(ram[0x2000], 1);
+    assert_eq!(ram[0xFF00], 0);
+    assert_eq!(cluster.cpu(0).registers().pc, 0x0408);
+}
+
+#[test]
+fn run_frame_carries_overshoot() {
+    // loop: LBRA loop (5 cycles)
+    let mut machine = Machine::new(ram_with(&[0x16, 0xFF, 0xFD]));
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let log = frames.clone();
+    machine.set_frame_hook(Some(Box::new(move |_, _, frame| {
+        log.lock().unwrap().push(frame);
+    })));
+
+    // Boundaries fall at 12, 24, 36 and 48; frames end at 15, 25, 40 and 50.
+    let runs: Vec<u64> = (0..4).map(|_| machine.run_frame(12)).collect();
+    assert_eq!(runs, [15, 10, 15, 10]);
+    assert_eq!(machine.cycles(), 50);
+    assert_eq!(machine.frames(), 4);
+    assert_eq!(*frames.lock().unwrap(), [0, 1, 2, 3]);
+
+    // Running past the next boundary restarts frames from the current cycle.
+    machine.run_for(100);
+    assert_eq!(machine.run_frame(12), 15);
+}
+
+#[test]
+fn seeded_machine_replays_undefined_state() {
+    let regs = |seed| {
+        let machine = Machine::seeded(ram_with(&[0x12, 0x20, 0xFD]), seed);
+        *machine.cpu().registers()
+    };
+    let undefined = |r: Registers| (r.d, r.x, r.y, r.u, r.s, r.cc);
+    let a = regs(77);
+    assert_eq!(undefined(a), undefined(regs(77)));
+    assert_ne!(undefined(a), undefined(regs(78)));
+    // Reset still defines PC, DP and the interrupt masks.
+    assert_eq!((a.pc, a.dp), (0x0400, 0));
+    assert!(a.cc.irq_inhibit() && a.cc.firq_inhibit());
+
+    // A reset draws fresh values from the same stream.
+    let mut machine = Machine::seeded(ram_with(&[0x12, 0x20, 0xFD]), 77);
+    machine.reset();
+    assert_ne!(machine.cpu().registers().x, a.x);
+}
+
+/// Timer-driven IRQ counter at $2000 plus a scheduled counter at $2001.
+fn interrupt_machine() -> Machine<CompositeBus<FlatRam>> {
+    // LDS #$0C00 / ANDCC #$EF / loop: INCB / BRA loop
+    // IRQ handler: INC $2000 / LDA $FF01 (acknowledge) / RTI
+    let mut ram = ram_with(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x5C, 0x20, 0xFD]);
+    ram.load(&[0x7C, 0x20, 0x00, 0xB6, 0xFF, 0x01, 0x3B], 0x0600);
+    ram.set_vector(0xFFF8, 0x0600);
+    let mut bus = CompositeBus::new(ram);
+    let mut timer = IntervalTimer::new(333, TimerMode::Periodic, BusSignals::IRQ);
+    timer.start();
+    let id = bus.add_device(timer);
+    bus.map(id, 0xFF00, 4, 0);
+
+    let mut machine = Machine::new(bus);
+    machine
+        .scheduler_mut()
+        .every(250, |_, bus: &mut CompositeBus<FlatRam>, _| {
+            let count = bus.read(0x2001);
+            bus.write(0x2001, count.wrapping_add(1));
+        });
+    machine
+}
+
+#[test]
+fn save_state_replays_exactly() {
+    let mut machine = interrupt_machine();
+    machine.run_for(5_000);
+    let state = machine.save_state();
+
+    let observe = |machine: &mut Machine<CompositeBus<FlatRam>>| {
+        machine.run_for(5_000);
+        let regs = *machine.cpu().registers();
+        (
+            regs.d,
+            regs.pc,
+            machine.cycles(),
+            machine.bus_mut().read(0x2000),
+            machine.bus_mut().read(0x2001),
+        )
+    };
+    let expected = observe(&mut machine);
+    machine.load_state(&state).unwrap();
+    assert_eq!(observe(&mut machine), expected);
+
+    // A freshly built machine picks up where the saved one was.
+    let mut fresh = interrupt_machine();
+    fresh.load_state(&state).unwrap();
+    assert_eq!(observe(&mut fresh), expected);
+}
+
+#[test]
+fn load_state_rejects_foreign_states() {
+    let mut machine = interrupt_machine();
+    let state = machine.save_state();
+    assert_eq!(machine.load_state(b"junk"), Err(StateError::BadHeader));
+    assert_eq!(
+        machine.load_state(&state[..state.len() - 1]),
+        Err(StateError::Truncated)
+    );
+
+    // A bus with a different set of devices does not match.
+    let mut plain = Machine::new(CompositeBus::new(FlatRam::new()));
+    assert!(matches!(
+        plain.load_state(&state),
+        Err(StateError::Mismatch(_))
+    ));
+}
+
+/// Polls $2000 until an event sets it at cycle 5000, then copies it to
+/// $2001 and spins: LDS #$0C00 / loop: LDA $2000 / BEQ loop / STA $2001 / BRA *
+fn polling_machine(detector: bool) -> Machine<FlatRam> {
+    let program = [
+        0x10, 0xCE, 0x0C, 0x00, 0xB6, 0x20, 0x00, 0x27, 0xFB, 0xB7, 0x20, 0x01, 0x20, 0xFE,
+    ];
+    let mut machine = Machine::new(ram_with(&program));
+    machine
+        .scheduler_mut()
+        .at_cycle(5_000, |_, bus: &mut FlatRam, _| bus.write(0x2000, 0x42));
+    if detector {
+        machine.set_idle_detector(Some(Box::new(LoopDetector::new())));
+    }
+    machine
+}
+
+#[test]
+fn idle_skip_matches_full_emulation() {
+    let mut full = polling_machine(false);
+    let mut skipping = polling_machine(true);
+    for machine in [&mut full, &mut skipping] {
+        machine.run_for(4_000);
+        machine.run_for(6_000);
+    }
+    assert_eq!(skipping.cycles(), full.cycles());
+    assert_eq!(skipping.cpu().cycles(), full.cpu().cycles());
+    assert_eq!(skipping.cpu().registers(), full.cpu().registers());
+    assert_eq!(skipping.bus_mut().read(0x2001), 0x42);
+    assert!(
+        skipping.idle_skipped() > 9_000,
+        "{}",
+        skipping.idle_skipped()
+    );
+    assert_eq!(full.idle_skipped(), 0);
+}
+
+#[test]
+fn idle_skip_ignores_busy_loops() {
+    // loop: INCB / BRA loop, and loop: INC $2000 / BRA loop
+    for program in [&[0x5C, 0x20, 0xFD][..], &[0x7C, 0x20, 0x00, 0x20, 0xFB]] {
+        let mut machine = Machine::new(ram_with(program));
+        machine.set_idle_detector(Some(Box::new(LoopDetector::new())));
+        machine.run_for(10_000);
+        assert_eq!(machine.idle_skipped(), 0);
+    }
+}
+
+#[test]
+fn idle_skip_bounded_for_device_interrupts() {
+    // LDS #$0C00 / ANDCC #$EF / loop: BRA loop
+    // IRQ handler: INC $2000 / LDA $FF01 (acknowledge) / RTI
+    let mut ram = ram_with(&[0x10, 0xCE, 0x0C, 0x00, 0x1C, 0xEF, 0x20, 0xFE]);
+    ram.load(&[0x7C, 0x20, 0x00, 0xB6, 0xFF, 0x01, 0x3B], 0x0600);
+    ram.set_vector(0xFFF8, 0x0600);
+    let mut bus = CompositeBus::new(ram);
+    let mut timer = IntervalTimer::new(1000, TimerMode::Periodic, BusSignals::IRQ);
+    timer.start();
+    let id = bus.add_device(timer);
+    bus.map(id, 0xFF00, 4, 0);
+
+    let mut machine = Machine::new(bus);
+    machine.set_idle_detector(Some(Box::new(LoopDetector::new())));
+    machine.set_max_idle_skip(30);
+    machine.run_for(10_500);
+    assert_eq!(machine.bus_mut().read(0x2000), 10);
+    assert!(machine.idle_skipped() > 5_000);
+}
+
+#[test]
+fn run_for_duration_uses_the_cpu_clock() {
+    let mut cpu = Cpu::new();
+    cpu.set_clock_hz(100_000);
+    let mut machine = Machine::with_cpu(cpu, ram_with(&[0x20, 0xFE])); // BRA *
+    let cycles = machine.run_for_duration(Duration::from_millis(10));
+    assert_eq!(cycles, 1_002);
+    assert_eq!(machine.elapsed(), Duration::from_micros(10_020));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn machine_run_async_yields_to_other_tasks() {
+    let mut machine = Machine::new(ram_with(&[0x4C, 0x20, 0xFD])); // INCA / BRA
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let polls = Arc::new(AtomicU64::new(0));
+    let cycles = runtime.block_on(async {
+        let counter = polls.clone();
+        tokio::spawn(async move {
+            loop {
+                counter.fetch_add(1, Ordering::Relaxed);
+                tokio::task::yield_now().await;
+            }
+        });
+        machine.run_async(10_000, 500).await
+    });
+    assert_eq!(cycles, 10_000);
+    assert!(polls.load(Ordering::Relaxed) >= 10);
+
+    machine.cpu_mut().set_halted(true);
+    assert_eq!(runtime.block_on(machine.run_async(1_000, 100)), 0);
+}
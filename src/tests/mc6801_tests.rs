@@ -0,0 +1,300 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tests for the MC6801/6803 core.
+
+use crate::mc6801::{Mc6801, VEC_IRQ, VEC_OCF, VEC_TOF};
+use crate::snapshot::{StateReader, StateWriter};
+use crate::{FlatRam, Memory, Snapshot};
+
+/// A 6801 reset into `program` at 0xC000, with SP at 0x01FF.
+fn mc6801(program: &[u8]) -> (Mc6801, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0xC000);
+    ram.set_reset_vector(0xC000);
+    let mut cpu = Mc6801::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().sp = 0x01FF;
+    (cpu, ram)
+}
+
+#[test]
+fn reset_loads_pc_and_masks_interrupts() {
+    let (cpu, _) = mc6801(&[]);
+    assert_eq!(cpu.registers().pc, 0xC000);
+    assert!(cpu.registers().cc.irq_inhibit());
+    assert_eq!(cpu.registers().cc.to_byte() & 0xC0, 0xC0);
+}
+
+#[test]
+fn d_register_arithmetic() {
+    // LDD #$1234 ; ADDD #$0F00 ; SUBD #$0034 ; LSRD ; ASLD
+    let (mut cpu, mut ram) = mc6801(&[
+        0xCC, 0x12, 0x34, 0xC3, 0x0F, 0x00, 0x83, 0x00, 0x34, 0x04, 0x05,
+    ]);
+    let cycles: Vec<u64> = (0..5).map(|_| cpu.step(&mut ram)).collect();
+    assert_eq!(cycles, [3, 4, 4, 3, 3]);
+    assert_eq!(cpu.registers().d, 0x2100);
+    assert_eq!(cpu.registers().a(), 0x21);
+}
+
+#[test]
+fn accumulator_ops_on_each_operand_mode() {
+    // LDAA #$10 ; ADDA $40 ; ADDA 1,X ; ADDA $E000 ; STAA $0200
+    let (mut cpu, mut ram) = mc6801(&[
+        0x86, 0x10, 0x9B, 0x40, 0xAB, 0x01, 0xBB, 0xE0, 0x00, 0xB7, 0x02, 0x00,
+    ]);
+    ram.write(0x0040, 0x01);
+    ram.write(0x3001, 0x02);
+    ram.write(0xE000, 0x03);
+    cpu.registers_mut().x = 0x3000;
+    let cycles: Vec<u64> = (0..5).map(|_| cpu.step(&mut ram)).collect();
+    assert_eq!(cycles, [2, 3, 4, 4, 4]);
+    assert_eq!(ram.read(0x0200), 0x16);
+}
+
+#[test]
+fn stack_grows_down_from_the_next_free_byte() {
+    // LDX #$1234 ; PSHX ; PSHA ; PULB ; PULX
+    let (mut cpu, mut ram) = mc6801(&[0xCE, 0x12, 0x34, 0x3C, 0x36, 0x33, 0x38]);
+    cpu.registers_mut().set_a(0xAA);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().sp, 0x01FD);
+    assert_eq!(ram.read_word(0x01FE), 0x1234);
+    cpu.step(&mut ram);
+    assert_eq!(ram.read(0x01FD), 0xAA);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().b(), 0xAA);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().x, 0x1234);
+    assert_eq!(cpu.registers().sp, 0x01FF);
+}
+
+#[test]
+fn jsr_and_rts() {
+    // JSR $C010 ; ... $C010: RTS
+    let mut program = vec![0xBD, 0xC0, 0x10];
+    program.resize(0x10, 0x01);
+    program.push(0x39);
+    let (mut cpu, mut ram) = mc6801(&program);
+    assert_eq!(cpu.step(&mut ram), 6);
+    assert_eq!(cpu.registers().pc, 0xC010);
+    assert_eq!(ram.read_word(0x01FE), 0xC003);
+    assert_eq!(cpu.step(&mut ram), 5);
+    assert_eq!(cpu.registers().pc, 0xC003);
+}
+
+#[test]
+fn branches_follow_the_condition_codes() {
+    // CLRA ; BEQ +2 ; NOP ; NOP ; BNE +2 ; BRN +2
+    let (mut cpu, mut ram) = mc6801(&[0x4F, 0x27, 0x02, 0x01, 0x01, 0x26, 0x02, 0x21, 0x02]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0xC005);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0xC007);
+    assert_eq!(cpu.step(&mut ram), 3);
+    assert_eq!(cpu.registers().pc, 0xC009);
+}
+
+#[test]
+fn flags_follow_the_6800_where_it_differs_from_the_6809() {
+    // SEC ; TSTA ; LDAA #$01 ; LSRA ; LDAA #$80 ; LDAB #$02 ; MUL
+    let (mut cpu, mut ram) = mc6801(&[0x0D, 0x4D, 0x86, 0x01, 0x44, 0x86, 0x80, 0xC6, 0x02, 0x3D]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert!(!cpu.registers().cc.carry());
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    // C set and N clear, so V = N ^ C is set.
+    assert!(cpu.registers().cc.carry());
+    assert!(cpu.registers().cc.overflow());
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    cpu.registers_mut().cc.set_zero(false);
+    assert_eq!(cpu.step(&mut ram), 10);
+    assert_eq!(cpu.registers().d, 0x0100);
+    assert!(!cpu.registers().cc.carry());
+    assert!(!cpu.registers().cc.zero());
+}
+
+#[test]
+fn abx_tap_and_tpa() {
+    // LDAB #$FF ; ABX ; LDAA #$01 ; TAP ; TPA
+    let (mut cpu, mut ram) = mc6801(&[0xC6, 0xFF, 0x3A, 0x86, 0x01, 0x06, 0x07]);
+    cpu.registers_mut().x = 0x1001;
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().x, 0x1100);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert!(cpu.registers().cc.carry());
+    assert!(!cpu.registers().cc.irq_inhibit());
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a(), 0xC1);
+}
+
+#[test]
+fn irq_stacks_the_registers_and_rti_restores_them() {
+    // CLI ; NOP ... handler at $C100: RTI
+    let mut program = vec![0x0E, 0x01];
+    program.resize(0x100, 0x01);
+    program.push(0x3B);
+    let (mut cpu, mut ram) = mc6801(&program);
+    ram.write_word(VEC_IRQ, 0xC100);
+    cpu.step(&mut ram);
+    cpu.registers_mut().d = 0x1122;
+    cpu.registers_mut().x = 0x3344;
+    cpu.set_irq(true);
+    assert_eq!(cpu.step(&mut ram), 12);
+    assert_eq!(cpu.registers().pc, 0xC100);
+    assert_eq!(cpu.registers().sp, 0x01F8);
+    // CC, B, A, X, PC from the lowest address up.
+    assert_eq!(ram.read(0x01FA), 0x22);
+    assert_eq!(ram.read(0x01FB), 0x11);
+    assert_eq!(ram.read_word(0x01FC), 0x3344);
+    assert_eq!(ram.read_word(0x01FE), 0xC001);
+    cpu.set_irq(false);
+    assert_eq!(cpu.step(&mut ram), 10);
+    assert_eq!(cpu.registers().pc, 0xC001);
+    assert_eq!(cpu.registers().sp, 0x01FF);
+    assert!(!cpu.registers().cc.irq_inhibit());
+}
+
+#[test]
+fn wai_waits_with_the_registers_already_stacked() {
+    // CLI ; WAI
+    let (mut cpu, mut ram) = mc6801(&[0x0E, 0x3E]);
+    ram.write_word(VEC_IRQ, 0xD000);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.step(&mut ram), 9);
+    assert!(cpu.waiting());
+    assert_eq!(cpu.step(&mut ram), 1);
+    cpu.set_irq(true);
+    assert_eq!(cpu.step(&mut ram), 3);
+    assert!(!cpu.waiting());
+    assert_eq!(cpu.registers().pc, 0xD000);
+    assert_eq!(cpu.registers().sp, 0x01F8);
+}
+
+#[test]
+fn internal_ram_shadows_external_memory() {
+    // LDAA #$5A ; STAA $90 ; CLR $14 ; STAA $90
+    let (mut cpu, mut ram) = mc6801(&[0x86, 0x5A, 0x97, 0x90, 0x7F, 0x00, 0x14, 0x97, 0x90]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.internal_ram()[0x10], 0x5A);
+    assert_eq!(ram.read(0x0090), 0x00);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(ram.read(0x0090), 0x5A);
+}
+
+#[test]
+fn ports_mix_outputs_with_input_pins() {
+    // LDAA #$0F ; STAA $00 ; LDAA #$A5 ; STAA $02 ; LDAA $02
+    let (mut cpu, mut ram) = mc6801(&[0x86, 0x0F, 0x97, 0x00, 0x86, 0xA5, 0x97, 0x02, 0x96, 0x02]);
+    cpu.set_port_input(1, 0x3C);
+    for _ in 0..5 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.port_output(1), 0x05);
+    assert_eq!(cpu.registers().a(), 0x35);
+}
+
+#[test]
+fn timer_overflow_interrupts_and_is_cleared_by_reading_the_counter() {
+    // LDAA #$04 ; STAA $08 ; CLI ; BRA *
+    // handler: LDAA $08 ; LDAA $09 ; RTI
+    let mut program = vec![0x86, 0x04, 0x97, 0x08, 0x0E, 0x20, 0xFE];
+    program.resize(0x20, 0x01);
+    program.extend([0x96, 0x08, 0x96, 0x09, 0x3B]);
+    let (mut cpu, mut ram) = mc6801(&program);
+    ram.write_word(VEC_TOF, 0xC020);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    while cpu.registers().pc != 0xC020 {
+        assert!(cpu.cycles() < 0x11000, "no overflow interrupt");
+        cpu.step(&mut ram);
+    }
+    assert!(cpu.cycles() > 0xFFFF);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a() & 0x20, 0x20);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0xC005);
+    // The flag is gone, so the loop runs on.
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().pc, 0xC005);
+}
+
+#[test]
+fn output_compare_flags_and_drives_p21() {
+    // LDD #$0040 ; STD $0B ; LDAA #$02 ; STAA $01 ; LDAA #$09 ; STAA $08
+    // CLI ; BRA *
+    let (mut cpu, mut ram) = mc6801(&[
+        0xCC, 0x00, 0x40, 0xDD, 0x0B, 0x86, 0x02, 0x97, 0x01, 0x86, 0x09, 0x97, 0x08, 0x0E, 0x20,
+        0xFE,
+    ]);
+    ram.write_word(VEC_OCF, 0xD000);
+    while cpu.registers().pc < 0xD000 {
+        assert!(cpu.cycles() < 0x80, "no compare interrupt");
+        cpu.step(&mut ram);
+    }
+    assert!(cpu.counter() >= 0x40);
+    assert_eq!(cpu.port_output(2), 0x02);
+}
+
+#[test]
+fn input_capture_flags_only_the_selected_edge() {
+    // LDAA $08 ; LDAA $08
+    let (mut cpu, mut ram) = mc6801(&[0x96, 0x08, 0x96, 0x08]);
+    // IEDG is clear after reset, so only a falling edge captures.
+    cpu.input_capture(true);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a() & 0x80, 0);
+    cpu.input_capture(false);
+    cpu.step(&mut ram);
+    assert_eq!(cpu.registers().a() & 0x80, 0x80);
+}
+
+#[test]
+fn undefined_opcodes_are_flagged() {
+    let (mut cpu, mut ram) = mc6801(&[0x02, 0x01]);
+    assert_eq!(cpu.step(&mut ram), 2);
+    assert!(cpu.illegal());
+    cpu.clear_illegal();
+    cpu.step(&mut ram);
+    assert!(!cpu.illegal());
+}
+
+#[test]
+fn snapshot_round_trip() {
+    // LDD #$BEEF ; STD $80
+    let (mut cpu, mut ram) = mc6801(&[0xCC, 0xBE, 0xEF, 0xDD, 0x80]);
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    let mut out = StateWriter::new();
+    cpu.snapshot(&mut out);
+    let bytes = out.into_bytes();
+
+    let mut restored = Mc6801::new();
+    restored.restore(&mut StateReader::new(&bytes)).unwrap();
+    assert_eq!(restored.registers(), cpu.registers());
+    assert_eq!(restored.cycles(), cpu.cycles());
+    assert_eq!(restored.internal_ram()[0..2], [0xBE, 0xEF]);
+    assert_eq!(restored.counter(), cpu.counter());
+}
@@ -0,0 +1,121 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::{Cpu, Memory};
+
+/// Memory that records every byte access.
+struct LoggingMem {
+    mem: [u8; 65536],
+    reads: Vec<u16>,
+}
+
+impl LoggingMem {
+    fn new() -> Self {
+        Self {
+            mem: [0u8; 65536],
+            reads: Vec::new(),
+        }
+    }
+}
+
+impl Memory for LoggingMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.reads.push(addr);
+        self.mem[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+}
+
+#[test]
+fn read_slice_reads_bytes_in_order_and_wraps() {
+    let mut mem = LoggingMem::new();
+    mem.mem[0xFFFE] = 0x11;
+    mem.mem[0xFFFF] = 0x22;
+    mem.mem[0x0000] = 0x33;
+    let mut buf = [0u8; 3];
+    mem.read_slice(0xFFFE, &mut buf);
+    assert_eq!(buf, [0x11, 0x22, 0x33]);
+    assert_eq!(mem.reads, [0xFFFE, 0xFFFF, 0x0000]);
+}
+
+/// Write and read back through a `Memory` taken by value.
+fn word_roundtrip(mut mem: impl Memory) -> u16 {
+    mem.write_word(0x1000, 0xBEEF);
+    mem.read_word(0x1000)
+}
+
+#[test]
+fn forwarding_impls_reach_the_backing_memory() {
+    let mut mem = LoggingMem::new();
+    let dyn_mem: &mut dyn Memory = &mut mem;
+    assert_eq!(word_roundtrip(dyn_mem), 0xBEEF);
+    assert_eq!(mem.mem[0x1000..0x1002], [0xBE, 0xEF]);
+
+    let boxed: Box<dyn Memory> = Box::new(LoggingMem::new());
+    assert_eq!(word_roundtrip(boxed), 0xBEEF);
+}
+
+/// Memory that serves everything through `read_slice` and records each call.
+struct SliceMem {
+    mem: [u8; 65536],
+    slices: Vec<(u16, usize)>,
+}
+
+impl Memory for SliceMem {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.mem[addr as usize] = val;
+    }
+
+    fn read_slice(&mut self, addr: u16, buf: &mut [u8]) {
+        self.slices.push((addr, buf.len()));
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.mem[addr.wrapping_add(i as u16) as usize];
+        }
+    }
+}
+
+#[test]
+fn read_word_defaults_to_read_slice() {
+    let mut mem = SliceMem {
+        mem: [0u8; 65536],
+        slices: Vec::new(),
+    };
+    mem.mem[0xFFFF] = 0x12;
+    mem.mem[0x0000] = 0x34;
+    assert_eq!(mem.read_word(0xFFFF), 0x1234);
+    assert_eq!(mem.slices, [(0xFFFF, 2)]);
+}
+
+#[test]
+fn cpu_fetches_word_operands_through_read_slice() {
+    let mut mem = SliceMem {
+        mem: [0u8; 65536],
+        slices: Vec::new(),
+    };
+    // LDX #$1234 ; LDY #$5678
+    mem.mem[0x0400..0x0407].copy_from_slice(&[0x8E, 0x12, 0x34, 0x10, 0x8E, 0x56, 0x78]);
+    let mut cpu = Cpu::new();
+    cpu.registers_mut().pc = 0x0400;
+    cpu.step(&mut mem);
+    cpu.step(&mut mem);
+    assert_eq!((cpu.registers().x, cpu.registers().y), (0x1234, 0x5678));
+    assert_eq!(mem.slices, [(0x0401, 2), (0x0405, 2)]);
+}
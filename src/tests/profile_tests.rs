@@ -0,0 +1,196 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::profile::{CallProfile, RoutineProfile};
+use crate::symbols::SymbolTable;
+use crate::{Cpu, FlatRam};
+
+/// MAIN calls SUB_A twice, which calls SUB_B, then loops forever.
+const MAIN: [u8; 8] = [
+    0xBD, 0x04, 0x20, // JSR SUB_A
+    0xBD, 0x04, 0x20, // JSR SUB_A
+    0x20, 0xFE, // BRA *
+];
+const SUB_A: [u8; 4] = [0xBD, 0x04, 0x30, 0x39]; // JSR SUB_B; RTS
+const SUB_B: [u8; 2] = [0x12, 0x39]; // NOP; RTS
+
+/// A profiling CPU reset into MAIN at 0x0400.
+fn setup() -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(&MAIN, 0x0400);
+    ram.load(&SUB_A, 0x0420);
+    ram.load(&SUB_B, 0x0430);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    cpu.set_profiler(true);
+    (cpu, ram)
+}
+
+fn routine(addr: u16, calls: u64, self_cycles: u64, total_cycles: u64) -> RoutineProfile {
+    RoutineProfile {
+        addr,
+        calls,
+        self_cycles,
+        total_cycles,
+    }
+}
+
+#[test]
+fn profiler_is_off_by_default() {
+    let mut cpu = Cpu::new();
+    assert!(cpu.profile().is_none());
+    assert!(cpu.take_profile().is_none());
+}
+
+#[test]
+fn profiler_attributes_cycles_to_nested_calls_on_every_path() {
+    for mode in 0..3 {
+        let (mut cpu, mut ram) = setup();
+        match mode {
+            0 => {
+                cpu.run(&mut ram, 59);
+            }
+            1 => {
+                cpu.set_decode_cache(true);
+                cpu.run(&mut ram, 59);
+            }
+            _ => {
+                cpu.set_block_cache(true);
+                cpu.run_fast(&mut ram, 59);
+            }
+        }
+        let profile = cpu.profile().unwrap();
+        assert_eq!(profile.total_cycles(), 59);
+        assert_eq!(profile.call_stack(), [0x0400]);
+        assert_eq!(
+            profile.routines(),
+            [
+                routine(0x0420, 2, 26, 40),
+                routine(0x0400, 0, 19, 59),
+                routine(0x0430, 2, 14, 14),
+            ]
+        );
+        assert_eq!(
+            profile.calls(),
+            [
+                CallProfile {
+                    caller: 0x0400,
+                    callee: 0x0420,
+                    calls: 2,
+                    cycles: 40,
+                },
+                CallProfile {
+                    caller: 0x0420,
+                    callee: 0x0430,
+                    calls: 2,
+                    cycles: 14,
+                },
+            ]
+        );
+    }
+}
+
+#[test]
+fn profiler_counts_running_routines_and_keeps_them_across_take() {
+    let (mut cpu, mut ram) = setup();
+    // JSR SUB_A; JSR SUB_B; NOP
+    for _ in 0..3 {
+        cpu.step(&mut ram);
+    }
+    let first = cpu.take_profile().unwrap();
+    assert_eq!(first.call_stack(), [0x0400, 0x0420, 0x0430]);
+    assert_eq!(first.routines()[0], routine(0x0400, 0, 8, 18));
+    assert_eq!(first.routines()[1], routine(0x0420, 1, 8, 10));
+
+    // RTS; RTS
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    let second = cpu.profile().unwrap();
+    assert_eq!(second.total_cycles(), 10);
+    assert_eq!(second.current(), Some(0x0400));
+    assert_eq!(
+        second.routines(),
+        [
+            routine(0x0420, 0, 5, 10),
+            routine(0x0430, 0, 5, 5),
+            routine(0x0400, 0, 0, 10)
+        ]
+    );
+}
+
+#[test]
+fn profiler_follows_interrupts_and_puls_pc() {
+    let mut ram = FlatRam::new();
+    // NOP; NOP; NOP
+    ram.load(&[0x12, 0x12, 0x12], 0x0400);
+    // IRQ handler: BSR SUB; RTI
+    ram.load(&[0x8D, 0x01, 0x3B], 0x0500);
+    // SUB: PULS PC
+    ram.load(&[0x35, 0x80], 0x0503);
+    ram.load(&[0x05, 0x00], 0xFFF8);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    cpu.registers_mut().cc.set_irq_inhibit(false);
+    cpu.set_profiler(true);
+
+    cpu.step(&mut ram);
+    cpu.set_irq(true);
+    let entry = cpu.step(&mut ram);
+    cpu.set_irq(false);
+    assert_eq!(cpu.profile().unwrap().current(), Some(0x0500));
+    // BSR; PULS PC; RTI; NOP
+    for _ in 0..4 {
+        cpu.step(&mut ram);
+    }
+
+    let profile = cpu.profile().unwrap();
+    assert_eq!(profile.call_stack(), [0x0400]);
+    let handler = profile
+        .routines()
+        .into_iter()
+        .find(|routine| routine.addr == 0x0500)
+        .unwrap();
+    assert_eq!(handler.calls, 1);
+    assert_eq!(handler.self_cycles, entry + 7 + 15);
+    assert_eq!(handler.total_cycles, entry + 7 + 15 + 7);
+    assert_eq!(profile.calls().len(), 2);
+}
+
+#[test]
+fn profile_reports_use_symbols() {
+    let (mut cpu, mut ram) = setup();
+    cpu.run(&mut ram, 59);
+    let mut symbols = SymbolTable::new();
+    symbols.insert("MAIN", 0x0400);
+    symbols.insert("SUB_A", 0x0420);
+    let profile = cpu.profile().unwrap();
+
+    let flat = profile.flat_report(Some(&symbols));
+    assert!(flat.starts_with("59 cycles\n"));
+    let lines: Vec<_> = flat.lines().skip(3).collect();
+    assert!(lines[0].ends_with("SUB_A"));
+    assert!(lines[1].ends_with("MAIN"));
+    // No label at SUB_B: the nearest one below it.
+    assert!(lines[2].ends_with("SUB_A+16"));
+    assert!(profile.flat_report(None).contains("$0430"));
+
+    let graph = profile.call_graph_report(Some(&symbols));
+    assert!(graph.starts_with("MAIN: 59 cycles, 19 self, 0 calls\n"));
+    assert!(graph.contains("from MAIN"));
+    assert!(graph.contains("to   SUB_A+16"));
+}
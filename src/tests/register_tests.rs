@@ -14,7 +14,7 @@
 
 //! Unit tests for the CPU registers.
 
-use crate::registers::{ConditionCodes, Registers};
+use crate::registers::{Condition, ConditionCodes, FlagDelta, Hd6309Registers, Reg, Registers};
 use std::mem;
 
 // These are offsets into `reg` (which starts at offset 0), matching the
@@ -63,3 +63,222 @@ fn registers_field_offsets() {
     assert_eq!(mem::offset_of!(Registers, dp), OFF_DP);
     assert_eq!(mem::offset_of!(Registers, cc), OFF_CC);
 }
+
+// ---------------------------------------------------------------------------
+// Access by name
+// ---------------------------------------------------------------------------
+
+/// `get` reads each register, zero-extending the 8-bit ones.
+#[test]
+fn get_reads_each_register() {
+    let regs = Registers {
+        d: 0x1234,
+        x: 0x2345,
+        y: 0x3456,
+        u: 0x4567,
+        s: 0x5678,
+        pc: 0x6789,
+        dp: 0x9A,
+        cc: ConditionCodes::from_byte(0xBC),
+    };
+    let values: Vec<u16> = Reg::ALL.iter().map(|&reg| regs.get(reg)).collect();
+    assert_eq!(
+        values,
+        [
+            0x1234, 0x2345, 0x3456, 0x4567, 0x5678, 0x6789, 0x12, 0x34, 0xBC, 0x9A
+        ]
+    );
+}
+
+/// `set` writes only the named register; 8-bit registers take the low byte.
+#[test]
+fn set_writes_one_register() {
+    let mut regs = Registers::new();
+    regs.set(Reg::D, 0x1234);
+    regs.set(Reg::A, 0xFF56);
+    assert_eq!(regs.d, 0x5634);
+    regs.set(Reg::B, 0x78);
+    assert_eq!(regs.d, 0x5678);
+    regs.set(Reg::DP, 0x1FE);
+    regs.set(Reg::CC, 0x85);
+    assert_eq!(regs.dp, 0xFE);
+    assert_eq!(regs.cc.to_byte(), 0x85);
+    for reg in [Reg::X, Reg::Y, Reg::U, Reg::S, Reg::PC] {
+        regs.set(reg, 0xBEEF);
+        assert_eq!(regs.get(reg), 0xBEEF, "{reg}");
+    }
+}
+
+/// Codes and names round-trip; unused codes and names give `None`.
+#[test]
+fn reg_codes_and_names() {
+    for reg in Reg::ALL {
+        assert_eq!(Reg::from_code(reg.code()), Some(reg));
+        assert_eq!(Reg::from_name(reg.name()), Some(reg));
+        assert_eq!(reg.is_16bit(), reg.code() < 8);
+    }
+    for code in [0x6, 0x7, 0xC, 0xD, 0xE, 0xF, 0x10] {
+        assert_eq!(Reg::from_code(code), None);
+    }
+    assert_eq!(Reg::from_name("pc"), Some(Reg::PC));
+    assert_eq!(Reg::from_name("W"), None);
+    assert_eq!(format!("{:<3}|", Reg::S), "S  |");
+}
+
+/// D and W are the pairs of their 8-bit halves.
+#[test]
+fn paired_accumulators() {
+    let mut regs = Registers::new();
+    regs.set_d(0x1234);
+    assert_eq!((regs.d(), regs.a(), regs.b()), (0x1234, 0x12, 0x34));
+    regs.set_a(0xAB);
+    assert_eq!(regs.d(), 0xAB34);
+
+    let mut regs = Hd6309Registers::new();
+    regs.set_w(0x5678);
+    assert_eq!((regs.w(), regs.e(), regs.f()), (0x5678, 0x56, 0x78));
+    regs.set_f(0xCD);
+    assert_eq!(regs.w(), 0x56CD);
+}
+
+// ---------------------------------------------------------------------------
+// Branch conditions
+// ---------------------------------------------------------------------------
+
+/// Each condition against the N, Z, V and C combinations it depends on.
+#[test]
+fn condition_test_truth_table() {
+    // Flags as NZVC.
+    let taken = |cond, flags: u8| ConditionCodes::from_byte(flags).test(cond);
+    for flags in 0..16 {
+        let (n, z, v, c) = (
+            flags & 8 != 0,
+            flags & 4 != 0,
+            flags & 2 != 0,
+            flags & 1 != 0,
+        );
+        assert!(taken(Condition::Always, flags));
+        assert!(!taken(Condition::Never, flags));
+        assert_eq!(taken(Condition::Hi, flags), !c && !z, "{flags:04b}");
+        assert_eq!(taken(Condition::Ls, flags), c || z, "{flags:04b}");
+        assert_eq!(taken(Condition::Eq, flags), z, "{flags:04b}");
+        assert_eq!(taken(Condition::Mi, flags), n, "{flags:04b}");
+        assert_eq!(taken(Condition::Vs, flags), v, "{flags:04b}");
+        assert_eq!(taken(Condition::Cs, flags), c, "{flags:04b}");
+        assert_eq!(taken(Condition::Ge, flags), n == v, "{flags:04b}");
+        assert_eq!(taken(Condition::Gt, flags), !z && n == v, "{flags:04b}");
+        // Odd codes take the opposite of the even code before them.
+        for code in (0..16).step_by(2) {
+            assert_ne!(
+                taken(Condition::from_code(code), flags),
+                taken(Condition::from_code(code + 1), flags)
+            );
+        }
+    }
+}
+
+/// Codes, opcodes and suffixes follow the branch opcode table.
+#[test]
+fn condition_codes_and_suffixes() {
+    for (code, cond) in Condition::ALL.into_iter().enumerate() {
+        assert_eq!(Condition::from_code(code as u8), cond);
+        assert_eq!(Condition::of_branch(0x20 + code as u16), Some(cond));
+        assert_eq!(Condition::of_branch(0x1020 + code as u16), Some(cond));
+    }
+    assert_eq!(Condition::of_branch(0x8D), Some(Condition::Always)); // BSR
+    assert_eq!(Condition::of_branch(0x17), Some(Condition::Always)); // LBSR
+    assert_eq!(Condition::of_branch(0x1127), None);
+    assert_eq!(Condition::of_branch(0x1030), None);
+    assert_eq!(Condition::Le.to_string(), "LE");
+    assert_eq!(Condition::Always.to_string(), "RA");
+}
+
+// ---------------------------------------------------------------------------
+// Builder and assertions
+// ---------------------------------------------------------------------------
+
+/// The builder sets each register it is given and leaves the rest zero.
+#[test]
+fn builder_sets_registers() {
+    let regs = Registers::builder()
+        .d(0x1234)
+        .b(0x56)
+        .x(0x2345)
+        .y(0x3456)
+        .u(0x4567)
+        .s(0x5678)
+        .pc(0x6789)
+        .dp(0x9A)
+        .cc(0xBC)
+        .build();
+    assert_eq!(
+        regs,
+        Registers {
+            d: 0x1256,
+            x: 0x2345,
+            y: 0x3456,
+            u: 0x4567,
+            s: 0x5678,
+            pc: 0x6789,
+            dp: 0x9A,
+            cc: ConditionCodes::from_byte(0xBC),
+        }
+    );
+    assert_eq!(Registers::builder().a(0x42).build().d, 0x4200);
+    assert_eq!(Registers::builder().build(), Registers::new());
+}
+
+/// `differences` lists A and B rather than D, in a fixed order.
+#[test]
+fn differences_lists_changed_registers() {
+    let expected = Registers::builder().a(0x12).x(0x1000).build();
+    let actual = Registers::builder().a(0x13).x(0x1000).cc(0x04).build();
+    assert_eq!(
+        actual.differences(&expected),
+        [(Reg::A, 0x12, 0x13), (Reg::CC, 0x00, 0x04)]
+    );
+    assert!(expected.differences(&expected).is_empty());
+}
+
+#[test]
+fn assert_regs_passes_on_equal_registers() {
+    let regs = Registers::builder().x(0x1234).build();
+    crate::assert_regs!(regs, Registers::builder().x(0x1234).build());
+    crate::assert_regs!(&regs, regs, "with a message {}", 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "after LDX: registers differ:\n    X: expected 1234, found 1235\n   PC: expected 0403, found 0400"
+)]
+fn assert_regs_lists_each_difference() {
+    let regs = Registers::builder().x(0x1235).pc(0x0400).build();
+    crate::assert_regs!(
+        regs,
+        Registers::builder().x(0x1234).pc(0x0403).build(),
+        "after {}",
+        "LDX"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Flag deltas
+// ---------------------------------------------------------------------------
+
+/// Set, cleared and changed masks, and the text listing them.
+#[test]
+fn flag_delta_masks_and_text() {
+    let delta = FlagDelta::new(
+        ConditionCodes::from_byte(ConditionCodes::E | ConditionCodes::C),
+        ConditionCodes::from_byte(ConditionCodes::E | ConditionCodes::H | ConditionCodes::V),
+    );
+    assert_eq!(delta.set(), ConditionCodes::H | ConditionCodes::V);
+    assert_eq!(delta.cleared(), ConditionCodes::C);
+    assert_eq!(delta.changed(), 0x23);
+    assert!(!delta.is_empty());
+    assert_eq!(delta.to_string(), "+H +V -C");
+
+    let same = ConditionCodes::from_byte(0xFF);
+    assert!(FlagDelta::new(same, same).is_empty());
+    assert_eq!(FlagDelta::new(same, same).to_string(), "");
+}
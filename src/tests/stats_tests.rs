@@ -0,0 +1,264 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::cpu::{Swi, SwiAction};
+use crate::stats::{AddressingMode, InterruptSource, OpcodeStats, RunStats, SourceStats};
+use crate::{Cpu, FlatRam, Memory};
+
+/// A CPU counting opcodes, reset into `program` at 0x0400.
+fn setup(program: &[u8]) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.set_opcode_stats(true);
+    (cpu, ram)
+}
+
+#[test]
+fn opcode_stats_are_off_by_default() {
+    let mut cpu = Cpu::new();
+    assert!(cpu.opcode_stats().is_none());
+    assert!(cpu.take_opcode_stats().is_none());
+}
+
+#[test]
+fn opcode_stats_count_on_every_path() {
+    // LDX #$0003; loop: LEAX -1,X; BNE loop; CMPU #0; NOP
+    let program = [
+        0x8E, 0x00, 0x03, 0x30, 0x1F, 0x26, 0xFC, 0x11, 0x83, 0x00, 0x00, 0x12,
+    ];
+    for mode in 0..3 {
+        let (mut cpu, mut ram) = setup(&program);
+        match mode {
+            0 => {
+                cpu.run(&mut ram, 30);
+            }
+            1 => {
+                cpu.set_decode_cache(true);
+                cpu.run(&mut ram, 30);
+            }
+            _ => {
+                cpu.set_block_cache(true);
+                cpu.run_fast(&mut ram, 30);
+            }
+        }
+        let stats = cpu.opcode_stats().unwrap();
+        assert_eq!(stats.count(0x8E), 1);
+        assert_eq!(stats.count(0x30), 3);
+        assert_eq!(stats.count(0x26), 3);
+        assert_eq!(stats.count(0x1183), 1);
+        assert_eq!(stats.count(0x83), 0);
+        assert_eq!(stats.mode_count(AddressingMode::Indexed), 3);
+        assert_eq!(stats.mode_count(AddressingMode::Relative), 3);
+        assert_eq!(stats.mode_count(AddressingMode::Immediate), 2);
+    }
+}
+
+#[test]
+fn opcode_report_is_sorted_and_named() {
+    // NOP; NOP; LDA #1; TFR A,B; FCB $01
+    let (mut cpu, mut ram) = setup(&[0x12, 0x12, 0x86, 0x01, 0x1F, 0x89, 0x01]);
+    for _ in 0..4 {
+        cpu.step(&mut ram);
+    }
+    let stats = cpu.take_opcode_stats().unwrap();
+    assert_eq!(stats.total(), 4);
+    let rows = stats.opcodes();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        (rows[0].opcode, rows[0].mnemonic, rows[0].count),
+        (0x12, Some("NOP"), 2)
+    );
+    assert_eq!(rows[1].mode, Some(AddressingMode::Register));
+    assert_eq!(rows[2].mnemonic, Some("LDA"));
+    assert_eq!(
+        stats.modes(),
+        [
+            (AddressingMode::Inherent, 2),
+            (AddressingMode::Immediate, 1),
+            (AddressingMode::Register, 1),
+        ]
+    );
+
+    let report = stats.to_string();
+    assert!(report.starts_with("4 instructions\n"));
+    assert!(report.contains("NOP"));
+    assert!(report.contains("register"));
+    assert_eq!(cpu.opcode_stats().unwrap().total(), 0);
+}
+
+#[test]
+fn opcode_stats_count_undefined_opcodes_without_a_mode() {
+    // FCB $01,$00 (undefined); NOP
+    let (mut cpu, mut ram) = setup(&[0x01, 0x00, 0x12]);
+    cpu.step(&mut ram);
+    let stats = cpu.opcode_stats().unwrap();
+    assert_eq!(stats.count(0x01), 1);
+    let row = stats.opcodes()[0];
+    assert_eq!((row.mnemonic, row.mode), (None, None));
+    assert!(stats.modes().is_empty());
+    assert!(stats.to_string().contains("???"));
+}
+
+#[test]
+fn opcode_stats_merge_and_clear() {
+    let (mut cpu, mut ram) = setup(&[0x12]);
+    cpu.step(&mut ram);
+    let mut stats = OpcodeStats::new();
+    stats.merge(cpu.opcode_stats().unwrap());
+    stats.merge(cpu.opcode_stats().unwrap());
+    assert_eq!(stats.count(0x12), 2);
+    assert_eq!(stats.count(0x2012), 0);
+
+    stats.clear();
+    assert_eq!(stats.total(), 0);
+    assert!(stats.opcodes().is_empty());
+}
+
+/// A CPU recording interrupt statistics, reset into `program` at 0x0400
+/// with the stack at 0x0800.
+fn setup_interrupts(program: &[u8]) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    cpu.set_interrupt_stats(true);
+    (cpu, ram)
+}
+
+#[test]
+fn interrupt_stats_are_off_by_default() {
+    let mut cpu = Cpu::new();
+    assert!(cpu.interrupt_stats().is_none());
+    assert!(cpu.take_interrupt_stats().is_none());
+}
+
+#[test]
+fn interrupt_stats_time_nested_handlers_and_masked_latency() {
+    // NOP; NOP; NOP; ANDCC #$AF
+    let (mut cpu, mut ram) = setup_interrupts(&[0x12, 0x12, 0x12, 0x1C, 0xAF]);
+    ram.load(&[0x12, 0x3B], 0x0500); // IRQ: NOP; RTI
+    ram.load(&[0x3B], 0x0600); // FIRQ: RTI
+    ram.load(&[0x06, 0x00, 0x05, 0x00], 0xFFF6);
+
+    // Held off by the I flag for three NOPs and the ANDCC.
+    cpu.set_irq(true);
+    for _ in 0..5 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.registers().pc, 0x0500);
+    cpu.set_irq(false);
+    cpu.set_firq(true);
+    cpu.step(&mut ram);
+    cpu.set_firq(false);
+    assert_eq!(
+        cpu.interrupt_stats().unwrap().active(),
+        [InterruptSource::Irq, InterruptSource::Firq]
+    );
+    // FIRQ's RTI, then the IRQ handler's NOP and RTI.
+    for _ in 0..3 {
+        cpu.step(&mut ram);
+    }
+    assert_eq!(cpu.cycles(), 61);
+
+    let stats = cpu.interrupt_stats().unwrap();
+    assert_eq!(
+        stats.source(InterruptSource::Irq),
+        SourceStats {
+            count: 1,
+            returned: 1,
+            handler_cycles: 52,
+            max_handler_cycles: 52,
+            max_latency: 28,
+        }
+    );
+    assert_eq!(
+        stats.source(InterruptSource::Firq),
+        SourceStats {
+            count: 1,
+            returned: 1,
+            handler_cycles: 16,
+            max_handler_cycles: 16,
+            max_latency: 10,
+        }
+    );
+    assert_eq!(stats.total(), 2);
+    assert_eq!(stats.max_depth(), 2);
+    assert!(stats.active().is_empty());
+    let report = stats.to_string();
+    assert!(report.contains("IRQ"));
+    assert!(!report.contains("NMI"));
+    assert!(report.ends_with("Deepest nesting: 2\n"));
+}
+
+#[test]
+fn interrupt_stats_count_vectored_swis_across_take() {
+    // SWI; SWI2
+    let (mut cpu, mut ram) = setup_interrupts(&[0x3F, 0x10, 0x3F]);
+    ram.load(&[0x3B], 0x0500); // RTI
+    ram.load(&[0x05, 0x00], 0xFFFA);
+    cpu.set_swi_hook(Some(Box::new(|swi, _, _: &mut dyn Memory| match swi {
+        Swi::Swi2 => SwiAction::Serviced,
+        _ => SwiAction::Vector,
+    })));
+
+    cpu.step(&mut ram);
+    let taken = cpu.take_interrupt_stats().unwrap();
+    assert_eq!(taken.source(InterruptSource::Swi).count, 1);
+    assert_eq!(taken.active(), [InterruptSource::Swi]);
+
+    // RTI; SWI2, handled by the hook.
+    cpu.step(&mut ram);
+    cpu.step(&mut ram);
+    let stats = cpu.interrupt_stats().unwrap();
+    let swi = stats.source(InterruptSource::Swi);
+    assert_eq!((swi.count, swi.returned, swi.handler_cycles), (0, 1, 34));
+    assert_eq!(swi.max_latency, 0);
+    assert_eq!(stats.source(InterruptSource::Swi2).count, 0);
+    assert_eq!(stats.max_depth(), 1);
+}
+
+#[test]
+fn run_with_stats_sums_up_the_run() {
+    // SWI; BRA *, with the SWI handler at 0x0500: RTI
+    let mut ram = FlatRam::new();
+    ram.load(&[0x3F, 0x20, 0xFE], 0x0400);
+    ram.load(&[0x3B], 0x0500);
+    ram.load(&[0x05, 0x00], 0xFFFA);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    cpu.add_breakpoint(0x0401);
+
+    let stats = cpu.run_with_stats(&mut ram, 19 + 15 + 3 + 3);
+    assert_eq!(stats.instructions, 4);
+    assert_eq!(stats.cycles, 40);
+    assert_eq!(stats.interrupts(InterruptSource::Swi), 1);
+    assert_eq!(stats.total_interrupts(), 1);
+    assert_eq!(stats.max_stack_depth, 12);
+    assert_eq!(stats.breakpoints_hit, 2);
+
+    // Each run starts afresh, and plain runs collect nothing.
+    let stats = cpu.run_with_stats(&mut ram, 3);
+    assert_eq!((stats.instructions, stats.breakpoints_hit), (1, 1));
+    assert_eq!(stats.max_stack_depth, 0);
+    cpu.run(&mut ram, 3);
+    let stats = cpu.run_with_stats(&mut ram, 0);
+    assert_eq!(stats, RunStats::default());
+}
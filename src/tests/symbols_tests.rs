@@ -0,0 +1,109 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::formats::FormatError;
+use crate::symbols::SymbolTable;
+
+#[test]
+fn symbols_parse_sym_files() {
+    let text = "\
+; symbols for demo
+START   EQU $0400
+LOOP:   equ $0410
+COUNT   =   0x0020
+* more
+DONE    EQU 1234h
+";
+    let symbols = SymbolTable::parse(text).unwrap();
+    assert_eq!(symbols.len(), 4);
+    assert_eq!(symbols.get("START"), Some(0x0400));
+    assert_eq!(symbols.get("LOOP"), Some(0x0410));
+    assert_eq!(symbols.get("COUNT"), Some(0x0020));
+    assert_eq!(symbols.get("DONE"), Some(0x1234));
+    assert_eq!(
+        SymbolTable::parse_sym("START EQU $0400\nnonsense\n").unwrap_err(),
+        FormatError::BadLine { line: 2 }
+    );
+}
+
+#[test]
+fn symbols_parse_noice_files() {
+    let text = "\
+FILE demo.asm
+DEF START 0400
+DEF IRQ 0x0480
+LINE 12 0400
+DEF BANKED 10800
+";
+    let symbols = SymbolTable::parse(text).unwrap();
+    assert_eq!(symbols.get("START"), Some(0x0400));
+    assert_eq!(symbols.get("IRQ"), Some(0x0480));
+    assert_eq!(symbols.get("BANKED"), Some(0x0800));
+    assert!(SymbolTable::parse_noi("DEF START\n").is_err());
+}
+
+#[test]
+fn symbols_parse_lwasm_maps() {
+    let text = "\
+Section: code (main.o) load at 0400, length 0020
+Symbol: START (main.o) = 0400
+Symbol: LOOP (main.o) = 0408
+";
+    let symbols = SymbolTable::parse(text).unwrap();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols.get("LOOP"), Some(0x0408));
+    assert_eq!(
+        SymbolTable::parse_lwasm_map("Symbol: START (main.o)\n").unwrap_err(),
+        FormatError::BadLine { line: 1 }
+    );
+}
+
+#[test]
+fn symbols_resolve_labels_offsets_and_addresses() {
+    let mut symbols = SymbolTable::new();
+    symbols.insert("BUF", 0x2000);
+    symbols.insert("ADD", 0x3000);
+    assert_eq!(symbols.resolve("BUF"), Some(0x2000));
+    assert_eq!(symbols.resolve("BUF+16"), Some(0x2010));
+    assert_eq!(symbols.resolve("BUF+$10"), Some(0x2010));
+    assert_eq!(symbols.resolve("BUF-1"), Some(0x1FFF));
+    assert_eq!(symbols.resolve("ADD"), Some(0x3000));
+    assert_eq!(symbols.resolve("$ADD"), Some(0x0ADD));
+    assert_eq!(symbols.resolve("0x8000"), Some(0x8000));
+    assert_eq!(symbols.resolve("NOPE"), None);
+    assert_eq!(symbols.resolve(""), None);
+}
+
+#[test]
+fn symbols_describe_and_replace() {
+    let mut symbols = SymbolTable::new();
+    assert_eq!(symbols.describe(0x1000), None);
+    symbols.insert("MAIN", 0x1000);
+    symbols.insert("ENTRY", 0x1000);
+    symbols.insert("TEMP", 0x1100);
+    assert_eq!(symbols.describe(0x1000), Some("MAIN".to_string()));
+    assert_eq!(symbols.describe(0x10FF), Some("MAIN+255".to_string()));
+    assert_eq!(symbols.describe(0x0FFF), None);
+    assert_eq!(symbols.names_at(0x1000), ["MAIN", "ENTRY"]);
+
+    symbols.insert("TEMP", 0x1200);
+    assert!(symbols.names_at(0x1100).is_empty());
+    assert_eq!(symbols.len(), 3);
+
+    let mut merged = SymbolTable::new();
+    merged.insert("TEMP", 0x0001);
+    merged.extend(&symbols);
+    let all: Vec<(&str, u16)> = merged.iter().collect();
+    assert_eq!(all, [("MAIN", 0x1000), ("ENTRY", 0x1000), ("TEMP", 0x1200)]);
+}
@@ -0,0 +1,132 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::testing::{GoldenError, GoldenTrace, compare_golden, record_golden};
+use crate::{Cpu, FlatRam, Memory};
+
+/// A CPU reset into `program` at 0x0400, with S set.
+fn setup(program: &[u8]) -> (Cpu, FlatRam) {
+    let mut ram = FlatRam::new();
+    ram.load(program, 0x0400);
+    ram.set_reset_vector(0x0400);
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut ram);
+    cpu.registers_mut().s = 0x0800;
+    (cpu, ram)
+}
+
+// LDA #$42 / INCA / LDX #$1234 / BRA $0400
+const PROGRAM: &[u8] = &[0x86, 0x42, 0x4C, 0x8E, 0x12, 0x34, 0x20, 0xF8];
+
+#[test]
+fn record_keeps_state_after_each_step() {
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    let golden = record_golden(&mut cpu, &mut ram, 4);
+    assert_eq!(golden.steps.len(), 4);
+    assert_eq!(golden.steps[0].registers.pc, 0x0402);
+    assert_eq!(golden.steps[0].registers.a(), 0x42);
+    assert_eq!(golden.steps[1].registers.a(), 0x43);
+    assert_eq!(golden.steps[2].registers.x, 0x1234);
+    assert_eq!(golden.steps[3].registers.pc, 0x0400);
+    assert_eq!(golden.steps[3].cycles, cpu.cycles());
+}
+
+#[test]
+fn text_round_trips() {
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    let golden = record_golden(&mut cpu, &mut ram, 6);
+    let text = golden.to_text();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("# mc6809-core golden trace 1"));
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("PC=0402 A=42 B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=")
+    );
+    assert_eq!(GoldenTrace::parse(&text).unwrap(), golden);
+
+    let commented = text.replacen('\n', "\n# comment\n\n", 2);
+    assert_eq!(GoldenTrace::parse(&commented).unwrap(), golden);
+}
+
+#[test]
+fn parse_rejects_malformed_lines() {
+    assert_eq!(
+        GoldenTrace::parse("PC=0400"),
+        Err(GoldenError::Parse { line: 1 })
+    );
+    let line = "PC=0402 A=42 B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2";
+    for bad in [
+        "PC=0402 A=42",
+        "PC=402 A=42 B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2",
+        "PC=0402 B=00 A=42 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2",
+        "PC=0402 A=4G B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2",
+        "PC=0402 A=42 B=00 X=0000 Y=0000 U=0000 S=0800 DP=00 CC=50 CYC=2 E=1",
+    ] {
+        let text = format!("# mc6809-core golden trace 1\n{line}\n{bad}\n");
+        assert_eq!(
+            GoldenTrace::parse(&text),
+            Err(GoldenError::Parse { line: 3 }),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn compare_reports_first_differing_step() {
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    let golden = record_golden(&mut cpu, &mut ram, 6);
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    assert_eq!(golden.compare(&mut cpu, &mut ram), Ok(()));
+
+    let (mut cpu, mut ram) = setup(PROGRAM);
+
+    // Load a different immediate: A is off from the first step on.
+    ram.write(0x0401, 0x10);
+    assert_eq!(
+        golden.compare(&mut cpu, &mut ram),
+        Err(GoldenError::Mismatch {
+            step: 0,
+            differences: vec!["A: expected 42, found 10".to_string()],
+        })
+    );
+
+    // Wrong cycle totals are reported too.
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    let mut golden = golden;
+    golden.steps[2].cycles += 1;
+    let err = golden.compare(&mut cpu, &mut ram).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "step 2 differs: cycles: expected 8, found 7"
+    );
+}
+
+#[test]
+fn compare_golden_reads_saved_file() {
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    let golden = record_golden(&mut cpu, &mut ram, 8);
+    let path = std::env::temp_dir().join(format!("mc6809-golden-{}.txt", std::process::id()));
+    golden.save(&path).unwrap();
+
+    let (mut cpu, mut ram) = setup(PROGRAM);
+    assert_eq!(compare_golden(&path, &mut cpu, &mut ram), Ok(()));
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        compare_golden(&path, &mut cpu, &mut ram),
+        Err(GoldenError::Io(_))
+    ));
+}
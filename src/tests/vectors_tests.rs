@@ -0,0 +1,216 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use crate::batch::SeedRng;
+use crate::vectors::{self, CycleKind, Mismatch, Report, VectorError};
+
+/// A one-case file with `regs` filled in around the given PC, RAM and cycles.
+fn case(name: &str, initial: &str, expected: &str, cycles: &str) -> String {
+    format!(
+        r#"[{{"name": "{name}", "initial": {{{initial}}}, "final": {{{expected}}}, "cycles": [{cycles}]}}]"#
+    )
+}
+
+const ZERO: &str = r#""s": 0, "u": 0, "x": 0, "y": 0, "dp": 0, "cc": 0"#;
+
+#[test]
+fn vectors_parse_cases() {
+    let file = case(
+        "86 42 \\\"imm\\\" é \\u00e9 \\ud83d\\ude00",
+        &format!(r#""pc": 1024, {ZERO}, "d": 4660, "ram": [[1024, 134], [1025, 66]]"#),
+        &format!(r#""pc": 1026, {ZERO}, "a": 66, "b": 52, "ram": []"#),
+        r#"[1024, 134, "read"], [1025, 66, "read"], [null, null, "idle"]"#,
+    );
+    let cases = vectors::parse(&file).unwrap();
+    assert_eq!(cases.len(), 1);
+    let case = &cases[0];
+    assert_eq!(case.name, "86 42 \"imm\" é é 😀");
+    assert_eq!(case.initial.registers.d, 0x1234);
+    assert_eq!(case.expected.registers.d, 0x4234);
+    assert_eq!(case.initial.ram, [(1024, 0x86), (1025, 0x42)]);
+    assert_eq!(case.cycles[2].kind, CycleKind::Other);
+    assert_eq!(case.cycles[2].addr, None);
+    assert_eq!(case.opcode(), 0x0086);
+}
+
+#[test]
+fn vectors_reject_bad_files() {
+    assert_eq!(
+        vectors::parse("[{\"name\": 1,}]"),
+        Err(VectorError::Json { offset: 12 })
+    );
+    assert_eq!(
+        vectors::parse("[{\"name\": \"x\"}]"),
+        Err(VectorError::Field {
+            case: 0,
+            field: "initial"
+        })
+    );
+    let file = case(
+        "x",
+        &format!(r#""pc": 70000, {ZERO}, "a": 0, "b": 0, "ram": []"#),
+        &format!(r#""pc": 0, {ZERO}, "a": 0, "b": 0, "ram": []"#),
+        "",
+    );
+    assert_eq!(
+        vectors::parse(&file),
+        Err(VectorError::Field {
+            case: 0,
+            field: "pc"
+        })
+    );
+}
+
+#[test]
+fn vectors_run_and_report_by_opcode() {
+    let lda = case(
+        "86 42",
+        &format!(r#""pc": 1024, {ZERO}, "a": 0, "b": 0, "ram": [[1024, 134], [1025, 66]]"#),
+        &format!(r#""pc": 1026, {ZERO}, "a": 66, "b": 0, "ram": [[1024, 134]]"#),
+        r#"[1024, 134, "read"], [1025, 66, "read"]"#,
+    );
+    // LDA #$80 sets N, which this case wrongly leaves out, and claims a
+    // cycle too many.
+    let bad = case(
+        "86 80",
+        &format!(r#""pc": 1024, {ZERO}, "a": 0, "b": 0, "ram": [[1024, 134], [1025, 128]]"#),
+        &format!(r#""pc": 1026, {ZERO}, "a": 128, "b": 0, "ram": []"#),
+        r#"[1024, 134, "read"], [1025, 128, "read"], [65535, null, "read"]"#,
+    );
+    // LDS #$0200 / page 2: LDY #$1234 on the 0x10 page.
+    let ldy = case(
+        "10 8e",
+        &format!(
+            r#""pc": 0, {ZERO}, "a": 0, "b": 0, "ram": [[0, 16], [1, 142], [2, 18], [3, 52]]"#
+        ),
+        r#""pc": 4, "s": 0, "u": 0, "x": 0, "y": 4660, "dp": 0, "cc": 0, "a": 0, "b": 0, "ram": []"#,
+        r#"[0, 16, "read"], [1, 142, "read"], [2, 18, "read"], [3, 52, "read"]"#,
+    );
+
+    let mut report = Report::new();
+    for file in [&lda, &bad, &ldy] {
+        report.run_all(&vectors::parse(file).unwrap());
+    }
+    assert_eq!((report.passed(), report.failed()), (2, 1));
+
+    let results: Vec<_> = report
+        .opcodes()
+        .map(|(op, r)| (op, r.passed, r.failed))
+        .collect();
+    assert_eq!(results, [(0x0086, 1, 1), (0x108E, 1, 0)]);
+    let (_, lda_result) = report.opcodes().next().unwrap();
+    let (name, mismatches) = lda_result.first_failure.as_ref().unwrap();
+    assert_eq!(name, "86 80");
+    assert_eq!(
+        mismatches,
+        &[
+            Mismatch::Register {
+                name: "CC",
+                expected: 0x00,
+                actual: 0x08,
+            },
+            Mismatch::Cycles {
+                expected: 3,
+                actual: 2,
+            },
+        ]
+    );
+    let text = report.to_string();
+    assert!(text.contains("0086 FAIL 1/2"), "{text}");
+    assert!(text.contains("108E pass 1/1"), "{text}");
+    assert!(text.ends_with("2 passed, 1 failed\n"), "{text}");
+}
+
+#[test]
+fn vectors_check_write_order() {
+    // PSHS A,B pushes B first, then A.
+    let initial = r#""pc": 0, "s": 256, "u": 0, "x": 0, "y": 0, "dp": 0, "cc": 0, "a": 1, "b": 2, "ram": [[0, 52], [1, 6]]"#;
+    let expected = r#""pc": 2, "s": 254, "u": 0, "x": 0, "y": 0, "dp": 0, "cc": 0, "a": 1, "b": 2, "ram": [[254, 1], [255, 2]]"#;
+    let cycles = |first: &str, second: &str| {
+        format!(
+            r#"[0, 52, "read"], [1, 6, "read"], [2, null, "read"], [65535, null, "read"], [256, null, "read"], {first}, {second}"#
+        )
+    };
+    let right = case(
+        "34 06",
+        initial,
+        expected,
+        &cycles(r#"[255, 2, "write"]"#, r#"[254, 1, "write"]"#),
+    );
+    let wrong = case(
+        "34 06",
+        initial,
+        expected,
+        &cycles(r#"[254, 1, "write"]"#, r#"[255, 2, "write"]"#),
+    );
+
+    let right = vectors::run_case(&vectors::parse(&right).unwrap()[0]);
+    assert_eq!(right, []);
+    let wrong = vectors::run_case(&vectors::parse(&wrong).unwrap()[0]);
+    assert!(
+        matches!(wrong.as_slice(), [Mismatch::Writes { .. }]),
+        "{wrong:?}"
+    );
+}
+
+#[test]
+fn vectors_generated_from_the_core_pass_against_it() {
+    let mut rng = SeedRng::new(0x6809);
+    let mut report = Report::new();
+    for page in [0x0000, 0x1000, 0x1100] {
+        for op in 0..=0xFF {
+            let opcode = page | op;
+            if matches!(op, 0x10 | 0x11) {
+                continue;
+            }
+            let cases = vectors::generate(opcode, 4, &mut rng);
+            let parsed = vectors::parse(&vectors::to_json(&cases)).unwrap();
+            assert_eq!(parsed, cases, "{opcode:04X}");
+            assert!(parsed.iter().all(|case| case.opcode() == opcode));
+            report.run_all(&parsed);
+        }
+    }
+    assert_eq!(report.failed(), 0, "{report}");
+}
+
+#[test]
+fn vectors_generate_records_bus_activity() {
+    // STA extended: three fetches, a dummy cycle and the write.
+    let cases = vectors::generate(0xB7, 1, &mut SeedRng::new(7));
+    let case = &cases[0];
+    let pc = case.initial.registers.pc;
+    let target = u16::from_be_bytes([case.cycles[1].value.unwrap(), case.cycles[2].value.unwrap()]);
+    assert_eq!(case.cycles.len(), 5);
+    assert_eq!(case.cycles[0].addr, Some(pc));
+    assert_eq!(case.cycles[0].value, Some(0xB7));
+    let write = case
+        .cycles
+        .iter()
+        .find(|cycle| cycle.kind == CycleKind::Write)
+        .unwrap();
+    assert_eq!(write.addr, Some(target));
+    assert_eq!(write.value, Some(case.initial.registers.a()));
+    assert!(
+        case.expected
+            .ram
+            .contains(&(target, case.initial.registers.a()))
+    );
+    assert_eq!(case.name, "b7 0");
+}
+
+#[test]
+#[should_panic(expected = "page prefix")]
+fn vectors_generate_rejects_prefixes() {
+    vectors::generate(0x0010, 1, &mut SeedRng::new(1));
+}
@@ -0,0 +1,450 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Single-step test vectors.
+//!
+//! Runs JSON test files in the style of Tom Harte's single-step tests
+//! against the core. Each file holds an array of cases, and each case gives
+//! the CPU state and the RAM bytes before and after one instruction,
+//! together with the bus cycles it takes:
+//!
+//! ```json
+//! [{
+//!   "name": "86 42",
+//!   "initial": {"pc": 1024, "s": 0, "u": 0, "x": 0, "y": 0, "dp": 0,
+//!               "cc": 0, "a": 0, "b": 0, "ram": [[1024, 134], [1025, 66]]},
+//!   "final":   {"pc": 1026, "s": 0, "u": 0, "x": 0, "y": 0, "dp": 0,
+//!               "cc": 0, "a": 66, "b": 0, "ram": [[1024, 134], [1025, 66]]},
+//!   "cycles":  [[1024, 134, "read"], [1025, 66, "read"]]
+//! }]
+//! ```
+//!
+//! Registers may give `d` instead of `a` and `b`. A cycle's address or
+//! value may be `null` where the bus holds nothing meaningful.
+//!
+//! [`generate`] and [`to_json`] go the other way and write this core's
+//! behaviour out as test files for other emulators.
+//!
+//! The core is not cycle-stepped, so a case is checked on its final
+//! registers and RAM, on its cycle count, and on the order of the bytes it
+//! writes; the order and addresses of reads, including the dummy reads the
+//! real part makes on internal cycles, are not compared.
+
+mod generate;
+mod json;
+
+pub use generate::{generate, to_json};
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use self::json::Value;
+use crate::cpu::Cpu;
+use crate::memory::Memory;
+use crate::registers::{ConditionCodes, Registers};
+
+/// CPU and RAM state on one side of a test case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorState {
+    pub registers: Registers,
+    /// RAM bytes as (address, value) pairs; all other RAM is zero.
+    pub ram: Vec<(u16, u8)>,
+}
+
+/// Direction of a bus cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleKind {
+    Read,
+    Write,
+    /// Any other cycle type the file names, such as an idle cycle.
+    Other,
+}
+
+/// One bus cycle of a test case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusCycle {
+    pub addr: Option<u16>,
+    pub value: Option<u8>,
+    pub kind: CycleKind,
+}
+
+/// One test case: a single instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub initial: VectorState,
+    pub expected: VectorState,
+    pub cycles: Vec<BusCycle>,
+}
+
+impl TestCase {
+    /// The opcode under test, read from the initial RAM at PC: `0x00nn`,
+    /// or `0x10nn`/`0x11nn` for the prefixed pages.
+    pub fn opcode(&self) -> u16 {
+        let byte = |addr: u16| {
+            self.initial
+                .ram
+                .iter()
+                .rev()
+                .find(|&&(a, _)| a == addr)
+                .map_or(0, |&(_, value)| value)
+        };
+        let pc = self.initial.registers.pc;
+        match byte(pc) {
+            page @ (0x10 | 0x11) => u16::from_be_bytes([page, byte(pc.wrapping_add(1))]),
+            opcode => opcode as u16,
+        }
+    }
+}
+
+/// Why a test file could not be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VectorError {
+    /// The file is not valid JSON; the error is at byte `offset`.
+    Json { offset: usize },
+    /// Case `case` (counting from 0) is missing `field` or has it in the
+    /// wrong shape.
+    Field { case: usize, field: &'static str },
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorError::Json { offset } => write!(f, "invalid JSON at byte {offset}"),
+            VectorError::Field { case, field } => {
+                write!(f, "test case {case}: missing or malformed `{field}`")
+            }
+        }
+    }
+}
+
+impl Error for VectorError {}
+
+/// Parse a test file: a JSON array of cases.
+pub fn parse(text: &str) -> Result<Vec<TestCase>, VectorError> {
+    let root = json::parse(text).map_err(|offset| VectorError::Json { offset })?;
+    let cases = root.as_array().ok_or(VectorError::Field {
+        case: 0,
+        field: "[",
+    })?;
+    cases
+        .iter()
+        .enumerate()
+        .map(|(index, case)| {
+            parse_case(case).map_err(|field| VectorError::Field { case: index, field })
+        })
+        .collect()
+}
+
+fn parse_case(case: &Value) -> Result<TestCase, &'static str> {
+    let name = case
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("name")?
+        .to_string();
+    let initial = parse_state(case.get("initial").ok_or("initial")?)?;
+    let expected = parse_state(case.get("final").ok_or("final")?)?;
+    let cycles = case
+        .get("cycles")
+        .and_then(Value::as_array)
+        .ok_or("cycles")?
+        .iter()
+        .map(parse_cycle)
+        .collect::<Option<_>>()
+        .ok_or("cycles")?;
+    Ok(TestCase {
+        name,
+        initial,
+        expected,
+        cycles,
+    })
+}
+
+fn parse_state(state: &Value) -> Result<VectorState, &'static str> {
+    let word = |key: &'static str| {
+        state
+            .get(key)
+            .and_then(|v| v.as_uint(0xFFFF))
+            .map(|v| v as u16)
+            .ok_or(key)
+    };
+    let byte = |key: &'static str| word(key).and_then(|v| u8::try_from(v).map_err(|_| key));
+    let d = match state.get("d") {
+        Some(_) => word("d")?,
+        None => u16::from_be_bytes([byte("a")?, byte("b")?]),
+    };
+    let registers = Registers {
+        d,
+        x: word("x")?,
+        y: word("y")?,
+        u: word("u")?,
+        s: word("s")?,
+        pc: word("pc")?,
+        dp: byte("dp")?,
+        cc: ConditionCodes::from_byte(byte("cc")?),
+    };
+    let ram = state
+        .get("ram")
+        .and_then(Value::as_array)
+        .ok_or("ram")?
+        .iter()
+        .map(|pair| match pair.as_array()? {
+            [addr, value] => Some((addr.as_uint(0xFFFF)? as u16, value.as_uint(0xFF)? as u8)),
+            _ => None,
+        })
+        .collect::<Option<_>>()
+        .ok_or("ram")?;
+    Ok(VectorState { registers, ram })
+}
+
+fn parse_cycle(cycle: &Value) -> Option<BusCycle> {
+    let [addr, value, kind] = cycle.as_array()? else {
+        return None;
+    };
+    let optional = |v: &Value, max: u64| match v {
+        Value::Null => Some(None),
+        v => v.as_uint(max).map(Some),
+    };
+    let kind = match kind.as_str()? {
+        "read" => CycleKind::Read,
+        "write" => CycleKind::Write,
+        _ => CycleKind::Other,
+    };
+    Some(BusCycle {
+        addr: optional(addr, 0xFFFF)?.map(|a| a as u16),
+        value: optional(value, 0xFF)?.map(|v| v as u8),
+        kind,
+    })
+}
+
+/// A difference between the expected and the actual outcome of a case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// Register `name` ended as `actual` instead of `expected`.
+    Register {
+        name: &'static str,
+        expected: u16,
+        actual: u16,
+    },
+    /// RAM at `addr` ended as `actual` instead of `expected`.
+    Ram { addr: u16, expected: u8, actual: u8 },
+    /// The instruction took `actual` cycles instead of `expected`.
+    Cycles { expected: u64, actual: u64 },
+    /// The writes, as (address, value), differ from the expected ones.
+    Writes {
+        expected: Vec<(u16, u8)>,
+        actual: Vec<(u16, u8)>,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Register {
+                name,
+                expected,
+                actual,
+            } => write!(f, "{name}: expected {expected:04X}, got {actual:04X}"),
+            Mismatch::Ram {
+                addr,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "RAM {addr:04X}: expected {expected:02X}, got {actual:02X}"
+            ),
+            Mismatch::Cycles { expected, actual } => {
+                write!(f, "cycles: expected {expected}, got {actual}")
+            }
+            Mismatch::Writes { expected, actual } => {
+                write!(f, "writes: expected {expected:02X?}, got {actual:02X?}")
+            }
+        }
+    }
+}
+
+/// 64 KB of RAM that records the writes made to it.
+struct VectorRam {
+    mem: Box<[u8]>,
+    writes: Vec<(u16, u8)>,
+}
+
+impl Memory for VectorRam {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.mem[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.writes.push((addr, val));
+        self.mem[addr as usize] = val;
+    }
+}
+
+/// Run one case on a fresh CPU and return everything that differs from
+/// the expected outcome; an empty list means the case passed.
+pub fn run_case(case: &TestCase) -> Vec<Mismatch> {
+    let mut ram = VectorRam {
+        mem: vec![0; 0x10000].into_boxed_slice(),
+        writes: Vec::new(),
+    };
+    for &(addr, value) in &case.initial.ram {
+        ram.mem[addr as usize] = value;
+    }
+    let mut cpu = Cpu::new();
+    *cpu.registers_mut() = case.initial.registers;
+    let cycles = cpu.step(&mut ram);
+
+    let mut mismatches = Vec::new();
+    let expected = &case.expected.registers;
+    for (reg, expected, actual) in cpu.registers().differences(expected) {
+        mismatches.push(Mismatch::Register {
+            name: reg.name(),
+            expected,
+            actual,
+        });
+    }
+    for &(addr, expected) in &case.expected.ram {
+        let actual = ram.mem[addr as usize];
+        if expected != actual {
+            mismatches.push(Mismatch::Ram {
+                addr,
+                expected,
+                actual,
+            });
+        }
+    }
+    let expected_cycles = case.cycles.len() as u64;
+    if cycles != expected_cycles {
+        mismatches.push(Mismatch::Cycles {
+            expected: expected_cycles,
+            actual: cycles,
+        });
+    }
+    let expected_writes: Vec<(u16, u8)> = case
+        .cycles
+        .iter()
+        .filter(|cycle| cycle.kind == CycleKind::Write)
+        .filter_map(|cycle| Some((cycle.addr?, cycle.value?)))
+        .collect();
+    if expected_writes != ram.writes {
+        mismatches.push(Mismatch::Writes {
+            expected: expected_writes,
+            actual: ram.writes,
+        });
+    }
+    mismatches
+}
+
+/// Results for one opcode.
+#[derive(Clone, Debug, Default)]
+pub struct OpcodeResult {
+    pub passed: usize,
+    pub failed: usize,
+    /// Name and mismatches of the first case that failed.
+    pub first_failure: Option<(String, Vec<Mismatch>)>,
+}
+
+/// Pass and fail counts by opcode, as [`TestCase::opcode`] gives it.
+///
+/// # Example
+/// ```
+/// use mc6809_core::vectors::{self, Report};
+///
+/// let file = r#"[{
+///   "name": "12",
+///   "initial": {"pc": 256, "s": 0, "u": 0, "x": 0, "y": 0, "dp": 0, "cc": 0,
+///               "a": 0, "b": 0, "ram": [[256, 18]]},
+///   "final":   {"pc": 257, "s": 0, "u": 0, "x": 0, "y": 0, "dp": 0, "cc": 0,
+///               "a": 0, "b": 0, "ram": [[256, 18]]},
+///   "cycles":  [[256, 18, "read"], [257, null, "read"]]
+/// }]"#;
+/// let mut report = Report::new();
+/// report.run_all(&vectors::parse(file).unwrap());
+/// assert_eq!((report.passed(), report.failed()), (1, 0));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    opcodes: BTreeMap<u16, OpcodeResult>,
+}
+
+impl Report {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `case` and record the result. Returns `true` if it passed.
+    pub fn run(&mut self, case: &TestCase) -> bool {
+        let mismatches = run_case(case);
+        let result = self.opcodes.entry(case.opcode()).or_default();
+        if mismatches.is_empty() {
+            result.passed += 1;
+            return true;
+        }
+        result.failed += 1;
+        result
+            .first_failure
+            .get_or_insert_with(|| (case.name.clone(), mismatches));
+        false
+    }
+
+    /// Run every case in `cases`.
+    pub fn run_all(&mut self, cases: &[TestCase]) {
+        for case in cases {
+            self.run(case);
+        }
+    }
+
+    /// Results by opcode, in opcode order.
+    pub fn opcodes(&self) -> impl Iterator<Item = (u16, &OpcodeResult)> {
+        self.opcodes
+            .iter()
+            .map(|(&opcode, result)| (opcode, result))
+    }
+
+    /// Cases passed in total.
+    pub fn passed(&self) -> usize {
+        self.opcodes.values().map(|r| r.passed).sum()
+    }
+
+    /// Cases failed in total.
+    pub fn failed(&self) -> usize {
+        self.opcodes.values().map(|r| r.failed).sum()
+    }
+}
+
+/// One line per opcode with its counts and, if any case failed, the first
+/// failure, then a total.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (opcode, result) in self.opcodes() {
+            let status = if result.failed == 0 { "pass" } else { "FAIL" };
+            write!(
+                f,
+                "{opcode:04X} {status} {}/{}",
+                result.passed,
+                result.passed + result.failed
+            )?;
+            if let Some((name, mismatches)) = &result.first_failure {
+                write!(f, "  first failure \"{name}\":")?;
+                for mismatch in mismatches {
+                    write!(f, " {mismatch};")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "{} passed, {} failed", self.passed(), self.failed())
+    }
+}
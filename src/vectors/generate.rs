@@ -0,0 +1,231 @@
+//   Copyright 2026 Martin Åkesson
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Test vectors generated from this core.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use super::json;
+use super::{BusCycle, CycleKind, TestCase, VectorState};
+use crate::batch::SeedRng;
+use crate::cpu::Cpu;
+use crate::memory::Memory;
+use crate::registers::{ConditionCodes, Registers};
+
+/// Memory filled with random bytes as the instruction first touches them,
+/// recording the bus accesses.
+struct RandomRam<'a> {
+    rng: &'a mut SeedRng,
+    /// The value each touched address held before the instruction.
+    initial: BTreeMap<u16, u8>,
+    current: BTreeMap<u16, u8>,
+    accesses: Vec<BusCycle>,
+}
+
+impl RandomRam<'_> {
+    fn touch(&mut self, addr: u16) -> u8 {
+        if let Some(&value) = self.current.get(&addr) {
+            return value;
+        }
+        let value = self.rng.next_u8();
+        self.initial.insert(addr, value);
+        self.current.insert(addr, value);
+        value
+    }
+
+    /// Fix the byte at `addr` before the instruction runs.
+    fn preset(&mut self, addr: u16, value: u8) {
+        self.initial.insert(addr, value);
+        self.current.insert(addr, value);
+    }
+}
+
+impl Memory for RandomRam<'_> {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = self.touch(addr);
+        self.accesses.push(BusCycle {
+            addr: Some(addr),
+            value: Some(value),
+            kind: CycleKind::Read,
+        });
+        value
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.touch(addr);
+        self.current.insert(addr, val);
+        self.accesses.push(BusCycle {
+            addr: Some(addr),
+            value: Some(val),
+            kind: CycleKind::Write,
+        });
+    }
+}
+
+/// Run `opcode` from `count` random states and record the results as test
+/// cases, named `"<opcode> <index>"`.
+///
+/// `opcode` is given as [`TestCase::opcode`] returns it: `0x00nn`, or
+/// `0x10nn`/`0x11nn` for the prefixed pages. Registers and every byte the
+/// instruction reads start out random, except the opcode itself; the
+/// initial and final RAM list exactly the addresses the instruction
+/// touched. The cycles are this core's reads and writes in the order it
+/// makes them, padded with `"internal"` cycles, with no address or value,
+/// up to its cycle count. They are not the real part's cycle-by-cycle bus
+/// activity, which the core does not model.
+///
+/// # Panics
+/// Panics if the high byte of `opcode` is not 0x00, 0x10 or 0x11, or if
+/// `opcode` is one of the prefixes 0x10 and 0x11 itself.
+///
+/// # Example
+/// ```
+/// use mc6809_core::batch::SeedRng;
+/// use mc6809_core::vectors::{self, Report};
+///
+/// let cases = vectors::generate(0x86, 10, &mut SeedRng::new(1)); // LDA #
+/// let file = vectors::to_json(&cases);
+///
+/// let mut report = Report::new();
+/// report.run_all(&vectors::parse(&file).unwrap());
+/// assert_eq!(report.passed(), 10);
+/// ```
+pub fn generate(opcode: u16, count: usize, rng: &mut SeedRng) -> Vec<TestCase> {
+    let [page, op] = opcode.to_be_bytes();
+    let prefix: &[u8] = match page {
+        0x00 if matches!(op, 0x10 | 0x11) => panic!("{op:02X} is a page prefix, not an opcode"),
+        0x00 => &[],
+        0x10 => &[0x10],
+        0x11 => &[0x11],
+        _ => panic!("{opcode:04X} is not an opcode on page 0, 1 or 2"),
+    };
+    (0..count)
+        .map(|index| {
+            let registers = Registers {
+                d: rng.next_u16(),
+                x: rng.next_u16(),
+                y: rng.next_u16(),
+                u: rng.next_u16(),
+                s: rng.next_u16(),
+                pc: rng.next_u16(),
+                dp: rng.next_u8(),
+                cc: ConditionCodes::from_byte(rng.next_u8()),
+            };
+            let mut ram = RandomRam {
+                rng,
+                initial: BTreeMap::new(),
+                current: BTreeMap::new(),
+                accesses: Vec::new(),
+            };
+            for (addr, &byte) in (0..)
+                .map(|i| registers.pc.wrapping_add(i))
+                .zip(prefix.iter().chain([&op]))
+            {
+                ram.preset(addr, byte);
+            }
+
+            let mut cpu = Cpu::new();
+            *cpu.registers_mut() = registers;
+            let cycles = cpu.step(&mut ram) as usize;
+
+            let mut bus = ram.accesses;
+            if bus.len() < cycles {
+                bus.resize(
+                    cycles,
+                    BusCycle {
+                        addr: None,
+                        value: None,
+                        kind: CycleKind::Other,
+                    },
+                );
+            }
+            TestCase {
+                name: format!("{opcode:02x} {index}"),
+                initial: VectorState {
+                    registers,
+                    ram: ram.initial.into_iter().collect(),
+                },
+                expected: VectorState {
+                    registers: *cpu.registers(),
+                    ram: ram.current.into_iter().collect(),
+                },
+                cycles: bus,
+            }
+        })
+        .collect()
+}
+
+/// Write `cases` as a JSON test file that [`parse`](super::parse) reads,
+/// one case per line.
+pub fn to_json(cases: &[TestCase]) -> String {
+    let mut out = String::from("[\n");
+    for (index, case) in cases.iter().enumerate() {
+        out.push_str("{\"name\": ");
+        json::write_string(&mut out, &case.name);
+        out.push_str(", \"initial\": ");
+        write_state(&mut out, &case.initial);
+        out.push_str(", \"final\": ");
+        write_state(&mut out, &case.expected);
+        out.push_str(", \"cycles\": [");
+        for (i, cycle) in case.cycles.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            let kind = match cycle.kind {
+                CycleKind::Read => "read",
+                CycleKind::Write => "write",
+                CycleKind::Other => "internal",
+            };
+            let _ = write!(
+                out,
+                "[{}, {}, \"{kind}\"]",
+                optional(cycle.addr.map(u32::from)),
+                optional(cycle.value.map(u32::from))
+            );
+        }
+        out.push_str("]}");
+        out.push_str(if index + 1 < cases.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("]\n");
+    out
+}
+
+fn optional(value: Option<u32>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
+
+fn write_state(out: &mut String, state: &VectorState) {
+    let r = &state.registers;
+    let _ = write!(
+        out,
+        "{{\"pc\": {}, \"s\": {}, \"u\": {}, \"x\": {}, \"y\": {}, \"dp\": {}, \"cc\": {}, \"a\": {}, \"b\": {}, \"ram\": [",
+        r.pc,
+        r.s,
+        r.u,
+        r.x,
+        r.y,
+        r.dp,
+        r.cc.to_byte(),
+        r.a(),
+        r.b()
+    );
+    for (i, (addr, value)) in state.ram.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "[{addr}, {value}]");
+    }
+    out.push_str("]}");
+}